@@ -0,0 +1,1166 @@
+//! Command-line utility initializing and operating deltafi configs and pools
+
+use std::{convert::TryFrom, process::exit, str::FromStr};
+
+use clap::{
+    crate_description, crate_name, crate_version, value_t, value_t_or_exit, App, AppSettings, Arg,
+    ArgMatches, SubCommand,
+};
+use deltafi_swap::{
+    client::{find_market_authority, find_swap_authority, SwapClient},
+    curve::{PoolState, SwapQuote},
+    instruction::{
+        commit_new_admin, init_observation_buffer, init_registry_page, initialize,
+        initialize_config, InitializeData, SwapData, SwapDirection,
+    },
+    math::{Decimal, BPS_DENOMINATOR},
+    processor::quote_market_swap,
+    state::{
+        find_observation_buffer_address, find_registry_page_address, ConfigInfo, Fees,
+        RegistryPage, Rewards, SwapInfo, SwapInfoData,
+    },
+};
+use solana_clap_utils::{
+    input_parsers::{pubkey_of, value_of},
+    input_validators::{is_parsable, is_pubkey, is_url, is_valid_pubkey, is_valid_signer},
+    keypair::signer_from_path,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_program::{
+    account_info::{AccountInfo, IntoAccountInfo},
+    clock::Clock,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_sdk::{
+    account::{from_account, Account},
+    commitment_config::CommitmentConfig,
+    signature::{Keypair, Signature, Signer},
+    system_instruction::create_account,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
+use spl_token::{
+    instruction::{initialize_account, initialize_mint, set_authority, transfer, AuthorityType},
+    state::{Account as Token, Mint},
+};
+
+type Error = Box<dyn std::error::Error>;
+type CommandResult = Result<(), Error>;
+
+struct Config {
+    rpc_client: RpcClient,
+    fee_payer: Box<dyn Signer>,
+    admin: Option<Box<dyn Signer>>,
+    program_id: Pubkey,
+}
+
+impl Config {
+    /// Admin of the config, the fee payer unless set
+    fn admin(&self) -> &dyn Signer {
+        self.admin.as_deref().unwrap_or(self.fee_payer.as_ref())
+    }
+}
+
+/// Send the instructions in one transaction, signed by the fee payer and the
+/// signers
+fn send_transaction(
+    config: &Config,
+    instructions: &[Instruction],
+    signers: &[&dyn Signer],
+) -> Result<Signature, Error> {
+    let mut transaction =
+        Transaction::new_with_payer(instructions, Some(&config.fee_payer.pubkey()));
+    let mut all_signers = vec![config.fee_payer.as_ref()];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+    transaction.try_sign(&all_signers, recent_blockhash)?;
+    let signature = config
+        .rpc_client
+        .send_and_confirm_transaction_with_spinner(&transaction)?;
+    println!("Signature: {}", signature);
+    Ok(signature)
+}
+
+/// Instructions creating a token account of the mint owned by the owner
+fn create_token_account(
+    config: &Config,
+    account: &Keypair,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Vec<Instruction>, Error> {
+    let lamports = config
+        .rpc_client
+        .get_minimum_balance_for_rent_exemption(Token::LEN)?;
+    Ok(vec![
+        create_account(
+            &config.fee_payer.pubkey(),
+            &account.pubkey(),
+            lamports,
+            Token::LEN as u64,
+            &spl_token::id(),
+        ),
+        initialize_account(&spl_token::id(), &account.pubkey(), mint, owner)?,
+    ])
+}
+
+/// Parse a fraction written like `5/1000`
+fn parse_fraction(value: &str) -> Result<(u64, u64), String> {
+    let (numerator, denominator) = value
+        .split_once('/')
+        .ok_or_else(|| format!("{} is not a fraction like 5/1000", value))?;
+    let numerator = numerator.parse::<u64>().map_err(|err| err.to_string())?;
+    let denominator = denominator.parse::<u64>().map_err(|err| err.to_string())?;
+    if denominator == 0 {
+        return Err("denominator is zero".to_string());
+    }
+    Ok((numerator, denominator))
+}
+
+fn is_fraction(value: String) -> Result<(), String> {
+    parse_fraction(&value).map(|_| ())
+}
+
+fn is_decimal(value: String) -> Result<(), String> {
+    Decimal::from_str(&value)
+        .map(|_| ())
+        .map_err(|_| format!("{} is not a decimal", value))
+}
+
+fn fraction_of(matches: &ArgMatches<'_>, name: &str) -> Option<(u64, u64)> {
+    matches
+        .value_of(name)
+        .map(|value| parse_fraction(value).unwrap())
+}
+
+fn decimal_of(matches: &ArgMatches<'_>, name: &str) -> Decimal {
+    Decimal::from_str(matches.value_of(name).unwrap()).unwrap()
+}
+
+/// Fees with the ones set by the arguments replaced
+fn fees_of(matches: &ArgMatches<'_>, fees: &Fees) -> Fees {
+    let mut fees = fees.clone();
+    if let Some((numerator, denominator)) = fraction_of(matches, "trade_fee") {
+        fees.trade_fee_numerator = numerator;
+        fees.trade_fee_denominator = denominator;
+    }
+    if let Some((numerator, denominator)) = fraction_of(matches, "withdraw_fee") {
+        fees.withdraw_fee_numerator = numerator;
+        fees.withdraw_fee_denominator = denominator;
+    }
+    if let Some((numerator, denominator)) = fraction_of(matches, "admin_trade_fee") {
+        fees.admin_trade_fee_numerator = numerator;
+        fees.admin_trade_fee_denominator = denominator;
+    }
+    if let Some((numerator, denominator)) = fraction_of(matches, "admin_withdraw_fee") {
+        fees.admin_withdraw_fee_numerator = numerator;
+        fees.admin_withdraw_fee_denominator = denominator;
+    }
+    if let Some(min_trade_fee) = value_of(matches, "min_trade_fee") {
+        fees.min_trade_fee = min_trade_fee;
+    }
+    fees
+}
+
+/// Rewards with the ones set by the arguments replaced
+fn rewards_of(matches: &ArgMatches<'_>, rewards: &Rewards) -> Rewards {
+    let mut rewards = rewards.clone();
+    if let Some((numerator, denominator)) = fraction_of(matches, "trade_reward") {
+        rewards.trade_reward_numerator = numerator;
+        rewards.trade_reward_denominator = denominator;
+    }
+    if let Some(trade_reward_cap) = value_of(matches, "trade_reward_cap") {
+        rewards.trade_reward_cap = trade_reward_cap;
+    }
+    if let Some((numerator, denominator)) = fraction_of(matches, "liquidity_reward") {
+        rewards.liquidity_reward_numerator = numerator;
+        rewards.liquidity_reward_denominator = denominator;
+    }
+    rewards
+}
+
+fn command_init_config(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let fees = fees_of(matches, &Fees::default());
+    let rewards = rewards_of(matches, &Rewards::default());
+    let admin = config.admin().pubkey();
+
+    let config_keypair = Keypair::new();
+    let deltafi_mint = Keypair::new();
+    let (market_authority, _) = find_market_authority(&config.program_id, &config_keypair.pubkey());
+
+    let mint_lamports = config
+        .rpc_client
+        .get_minimum_balance_for_rent_exemption(Mint::LEN)?;
+    let config_lamports = config
+        .rpc_client
+        .get_minimum_balance_for_rent_exemption(ConfigInfo::LEN)?;
+    send_transaction(
+        config,
+        &[
+            create_account(
+                &config.fee_payer.pubkey(),
+                &deltafi_mint.pubkey(),
+                mint_lamports,
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            initialize_mint(
+                &spl_token::id(),
+                &deltafi_mint.pubkey(),
+                &market_authority,
+                Some(&admin),
+                value_t_or_exit!(matches, "deltafi_decimals", u8),
+            )?,
+            create_account(
+                &config.fee_payer.pubkey(),
+                &config_keypair.pubkey(),
+                config_lamports,
+                ConfigInfo::LEN as u64,
+                &config.program_id,
+            ),
+            initialize_config(
+                config.program_id,
+                config_keypair.pubkey(),
+                market_authority,
+                deltafi_mint.pubkey(),
+                admin,
+                fees,
+                rewards,
+                value_t_or_exit!(matches, "max_mid_price_deviation_bps", u64),
+                value_t_or_exit!(matches, "min_mid_price_update_interval", i64),
+            )?,
+        ],
+        &[&config_keypair, &deltafi_mint, config.admin()],
+    )?;
+
+    println!("Config: {}", config_keypair.pubkey());
+    println!("Market authority: {}", market_authority);
+    println!("DELFI mint: {}", deltafi_mint.pubkey());
+    println!("Admin: {}", admin);
+    Ok(())
+}
+
+/// First registry page of the config with room for a pool, along with the
+/// instruction creating it when the page does not exist yet
+fn find_open_registry_page(
+    config: &Config,
+    config_pubkey: &Pubkey,
+) -> Result<(u8, Option<Instruction>), Error> {
+    for page_index in 0..=u8::MAX {
+        let (page_pubkey, _) =
+            find_registry_page_address(&config.program_id, config_pubkey, page_index);
+        let account = config
+            .rpc_client
+            .get_account_with_commitment(&page_pubkey, config.rpc_client.commitment())?
+            .value;
+        match account {
+            None => {
+                let instruction = init_registry_page(
+                    config.program_id,
+                    *config_pubkey,
+                    config.fee_payer.pubkey(),
+                    page_index,
+                )?;
+                return Ok((page_index, Some(instruction)));
+            }
+            Some(account) if !RegistryPage::load(&account.data)?.is_full() => {
+                return Ok((page_index, None))
+            }
+            Some(_) => {}
+        }
+    }
+    Err("every registry page of the config is full".into())
+}
+
+fn command_init_pool(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let config_pubkey = pubkey_of(matches, "swap_config").unwrap();
+    let token_a_mint = pubkey_of(matches, "token_a_mint").unwrap();
+    let token_b_mint = pubkey_of(matches, "token_b_mint").unwrap();
+    let oracle_a = pubkey_of(matches, "oracle_a").unwrap();
+    let oracle_b = pubkey_of(matches, "oracle_b").unwrap();
+    let amount_a = value_t_or_exit!(matches, "amount_a", u64);
+    let amount_b = value_t_or_exit!(matches, "amount_b", u64);
+    let owner = config.fee_payer.pubkey();
+    let source_a = pubkey_of(matches, "source_a")
+        .unwrap_or_else(|| get_associated_token_address(&owner, &token_a_mint));
+    let source_b = pubkey_of(matches, "source_b")
+        .unwrap_or_else(|| get_associated_token_address(&owner, &token_b_mint));
+    let admin_fee_owner = pubkey_of(matches, "admin_fee_owner").unwrap_or(owner);
+
+    let (registry_page_index, init_page) = find_open_registry_page(config, &config_pubkey)?;
+    if let Some(init_page) = init_page {
+        println!("Creating registry page {}", registry_page_index);
+        send_transaction(config, &[init_page], &[])?;
+    }
+
+    let swap = Keypair::new();
+    let (swap_authority, nonce) = find_swap_authority(&config.program_id, &swap.pubkey());
+
+    println!("Creating token accounts");
+    let token_a = Keypair::new();
+    let token_b = Keypair::new();
+    let admin_fee_a = Keypair::new();
+    let admin_fee_b = Keypair::new();
+    let mut instructions = create_token_account(config, &token_a, &token_a_mint, &owner)?;
+    instructions.extend(create_token_account(
+        config,
+        &token_b,
+        &token_b_mint,
+        &owner,
+    )?);
+    instructions.extend(create_token_account(
+        config,
+        &admin_fee_a,
+        &token_a_mint,
+        &admin_fee_owner,
+    )?);
+    instructions.extend(create_token_account(
+        config,
+        &admin_fee_b,
+        &token_b_mint,
+        &admin_fee_owner,
+    )?);
+    send_transaction(
+        config,
+        &instructions,
+        &[&token_a, &token_b, &admin_fee_a, &admin_fee_b],
+    )?;
+
+    println!("Creating pool mint");
+    let pool_mint = Keypair::new();
+    let pool_token = Keypair::new();
+    let mint_lamports = config
+        .rpc_client
+        .get_minimum_balance_for_rent_exemption(Mint::LEN)?;
+    let mut instructions = vec![
+        create_account(
+            &owner,
+            &pool_mint.pubkey(),
+            mint_lamports,
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        initialize_mint(
+            &spl_token::id(),
+            &pool_mint.pubkey(),
+            &swap_authority,
+            None,
+            spl_token::native_mint::DECIMALS,
+        )?,
+    ];
+    instructions.extend(create_token_account(
+        config,
+        &pool_token,
+        &pool_mint.pubkey(),
+        &owner,
+    )?);
+    send_transaction(config, &instructions, &[&pool_mint, &pool_token])?;
+
+    println!("Initializing pool");
+    let swap_lamports = config
+        .rpc_client
+        .get_minimum_balance_for_rent_exemption(SwapInfo::LEN)?;
+    send_transaction(
+        config,
+        &[
+            transfer(
+                &spl_token::id(),
+                &source_a,
+                &token_a.pubkey(),
+                &owner,
+                &[],
+                amount_a,
+            )?,
+            transfer(
+                &spl_token::id(),
+                &source_b,
+                &token_b.pubkey(),
+                &owner,
+                &[],
+                amount_b,
+            )?,
+            set_authority(
+                &spl_token::id(),
+                &token_a.pubkey(),
+                Some(&swap_authority),
+                AuthorityType::AccountOwner,
+                &owner,
+                &[],
+            )?,
+            set_authority(
+                &spl_token::id(),
+                &token_b.pubkey(),
+                Some(&swap_authority),
+                AuthorityType::AccountOwner,
+                &owner,
+                &[],
+            )?,
+            create_account(
+                &owner,
+                &swap.pubkey(),
+                swap_lamports,
+                SwapInfo::LEN as u64,
+                &config.program_id,
+            ),
+            initialize(
+                config.program_id,
+                config_pubkey,
+                swap.pubkey(),
+                swap_authority,
+                admin_fee_a.pubkey(),
+                admin_fee_b.pubkey(),
+                token_a.pubkey(),
+                token_b.pubkey(),
+                pool_mint.pubkey(),
+                pool_token.pubkey(),
+                oracle_a,
+                oracle_b,
+                registry_page_index,
+                token_a_mint,
+                token_b_mint,
+                InitializeData {
+                    nonce,
+                    slope: u64::try_from(decimal_of(matches, "slope").to_scaled_val()?)?,
+                    mid_price: decimal_of(matches, "mid_price").to_scaled_val()?,
+                    is_open_twap: matches.is_present("open_twap"),
+                    twap_window_secs: value_t_or_exit!(matches, "twap_window_secs", u64),
+                    pair_symbol: matches.value_of("pair_symbol").map(String::from),
+                },
+            )?,
+        ],
+        &[&swap],
+    )?;
+
+    println!("Creating observation buffer");
+    send_transaction(
+        config,
+        &[init_observation_buffer(
+            config.program_id,
+            swap.pubkey(),
+            owner,
+        )?],
+        &[],
+    )?;
+
+    println!("Pool: {}", swap.pubkey());
+    println!("Swap authority: {}", swap_authority);
+    println!("Token A: {}", token_a.pubkey());
+    println!("Token B: {}", token_b.pubkey());
+    println!("Admin fee A: {}", admin_fee_a.pubkey());
+    println!("Admin fee B: {}", admin_fee_b.pubkey());
+    println!("Pool mint: {}", pool_mint.pubkey());
+    println!("Pool token: {}", pool_token.pubkey());
+    Ok(())
+}
+
+fn get_config_info(config: &Config, config_pubkey: &Pubkey) -> Result<ConfigInfo, Error> {
+    let data = config.rpc_client.get_account_data(config_pubkey)?;
+    Ok(ConfigInfo::unpack(&data)?)
+}
+
+fn check_admin(config: &Config, config_info: &ConfigInfo) -> CommandResult {
+    if config.admin().pubkey() != config_info.admin_key {
+        return Err(format!(
+            "{} is not the admin of the config, {} is",
+            config.admin().pubkey(),
+            config_info.admin_key
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Load the pool given by the arguments along with its config
+fn get_swap_client(config: &Config, matches: &ArgMatches<'_>) -> Result<SwapClient, Error> {
+    let config_pubkey = pubkey_of(matches, "swap_config").unwrap();
+    let swap_pubkey = pubkey_of(matches, "pool").unwrap();
+    let swap_data = config.rpc_client.get_account_data(&swap_pubkey)?;
+    Ok(SwapClient::new(
+        config.program_id,
+        config_pubkey,
+        get_config_info(config, &config_pubkey)?,
+        swap_pubkey,
+        SwapInfo::unpack(&swap_data)?,
+    ))
+}
+
+/// Send an admin instruction of the pool, printing the pool before and after
+fn process_swap_admin(
+    config: &Config,
+    swap_client: &SwapClient,
+    instruction: Instruction,
+) -> CommandResult {
+    check_admin(config, &swap_client.config)?;
+    println!("Before: {:#?}", swap_client.swap);
+    send_transaction(config, &[instruction], &[config.admin()])?;
+    let data = config
+        .rpc_client
+        .get_account_data(&swap_client.swap_pubkey)?;
+    println!("After: {:#?}", SwapInfo::unpack(&data)?);
+    Ok(())
+}
+
+fn command_pause(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let swap_client = get_swap_client(config, matches)?;
+    process_swap_admin(config, &swap_client, swap_client.pause()?)
+}
+
+fn command_unpause(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let swap_client = get_swap_client(config, matches)?;
+    process_swap_admin(config, &swap_client, swap_client.unpause()?)
+}
+
+fn command_set_fees(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let swap_client = get_swap_client(config, matches)?;
+    let fees = fees_of(matches, &swap_client.swap.fees);
+    process_swap_admin(config, &swap_client, swap_client.set_new_fees(fees)?)
+}
+
+fn command_set_rewards(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let swap_client = get_swap_client(config, matches)?;
+    let rewards = rewards_of(matches, &swap_client.swap.rewards);
+    process_swap_admin(config, &swap_client, swap_client.set_new_rewards(rewards)?)
+}
+
+fn command_set_fee_account(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let swap_client = get_swap_client(config, matches)?;
+    let fee_account = pubkey_of(matches, "fee_account").unwrap();
+    process_swap_admin(
+        config,
+        &swap_client,
+        swap_client.set_fee_account(fee_account)?,
+    )
+}
+
+fn command_set_admin(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let config_pubkey = pubkey_of(matches, "swap_config").unwrap();
+    let new_admin = pubkey_of(matches, "new_admin").unwrap();
+    let config_info = get_config_info(config, &config_pubkey)?;
+    check_admin(config, &config_info)?;
+
+    println!("Before: {:#?}", config_info);
+    send_transaction(
+        config,
+        &[commit_new_admin(
+            config.program_id,
+            config_pubkey,
+            config_info.admin_key,
+            config_info.deltafi_mint,
+            new_admin,
+        )?],
+        &[config.admin()],
+    )?;
+    println!("After: {:#?}", get_config_info(config, &config_pubkey)?);
+    Ok(())
+}
+
+/// Pool state repriced to the market and quote of a swap at the current
+/// slot, priced like the program does before any fee discount
+fn quote_market(
+    config: &Config,
+    swap_client: &SwapClient,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+) -> Result<(PoolState, SwapQuote), Error> {
+    let swap_data = config
+        .rpc_client
+        .get_account_data(&swap_client.swap_pubkey)?;
+    let reserve_in = config
+        .rpc_client
+        .get_token_account_balance(match swap_direction {
+            SwapDirection::SellBase => &swap_client.swap.token_a,
+            SwapDirection::SellQuote => &swap_client.swap.token_b,
+        })?
+        .amount
+        .parse::<u64>()?;
+    let clock_account = config.rpc_client.get_account(&sysvar::clock::id())?;
+    let clock: Clock = from_account(&clock_account).ok_or("cannot read the clock")?;
+
+    let (observation_buffer, _) =
+        find_observation_buffer_address(&config.program_id, &swap_client.swap_pubkey);
+    let keys = [
+        observation_buffer,
+        swap_client.swap.pyth_a,
+        swap_client.swap.pyth_b,
+    ];
+    let mut accounts: Vec<Account> = config
+        .rpc_client
+        .get_multiple_accounts(&keys)?
+        .into_iter()
+        .map(Option::unwrap_or_default)
+        .collect();
+    let account_infos: Vec<AccountInfo> = keys
+        .iter()
+        .zip(accounts.iter_mut())
+        .map(|account| account.into_account_info())
+        .collect();
+
+    let (state, _, quote) = quote_market_swap(
+        SwapInfoData::load(&swap_data)?,
+        &account_infos[0],
+        &account_infos[1],
+        &account_infos[2],
+        &clock,
+        reserve_in,
+        amount_in,
+        swap_direction,
+    )?;
+    Ok((state, quote))
+}
+
+fn print_quote(
+    state: &PoolState,
+    quote: &SwapQuote,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+) -> CommandResult {
+    let price_impact_bps = state.price_impact_bps(
+        amount_in,
+        quote
+            .amount_out
+            .checked_add(quote.trade_fee)
+            .ok_or("amount out overflow")?,
+        swap_direction,
+    )?;
+    println!("Market price: {}", state.market_price);
+    println!("Amount in: {}", amount_in);
+    println!("Amount out: {}", quote.amount_out);
+    println!("Trade fee: {}", quote.trade_fee);
+    println!("Admin fee: {}", quote.admin_fee);
+    println!("DELFI reward: {}", quote.reward_amount);
+    println!("Price impact: {} bps", price_impact_bps);
+    Ok(())
+}
+
+fn swap_direction_of(matches: &ArgMatches<'_>) -> SwapDirection {
+    match matches.value_of("direction").unwrap() {
+        "sell-base" => SwapDirection::SellBase,
+        _ => SwapDirection::SellQuote,
+    }
+}
+
+fn command_quote(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let swap_client = get_swap_client(config, matches)?;
+    let amount_in = value_t_or_exit!(matches, "amount_in", u64);
+    let swap_direction = swap_direction_of(matches);
+
+    let (state, quote) = quote_market(config, &swap_client, amount_in, swap_direction)?;
+    print_quote(&state, &quote, amount_in, swap_direction)
+}
+
+/// Token account given by the argument, or the associated token account of
+/// the fee payer, created along the swap when missing
+fn token_account_of(
+    config: &Config,
+    matches: &ArgMatches<'_>,
+    name: &str,
+    mint: &Pubkey,
+    instructions: &mut Vec<Instruction>,
+) -> Result<Pubkey, Error> {
+    if let Some(pubkey) = pubkey_of(matches, name) {
+        return Ok(pubkey);
+    }
+    let owner = config.fee_payer.pubkey();
+    let pubkey = get_associated_token_address(&owner, mint);
+    if config
+        .rpc_client
+        .get_account_with_commitment(&pubkey, config.rpc_client.commitment())?
+        .value
+        .is_none()
+    {
+        instructions.push(create_associated_token_account(&owner, &owner, mint));
+    }
+    Ok(pubkey)
+}
+
+fn command_swap(config: &Config, matches: &ArgMatches<'_>) -> CommandResult {
+    let swap_client = get_swap_client(config, matches)?;
+    let amount_in = value_t_or_exit!(matches, "amount_in", u64);
+    let swap_direction = swap_direction_of(matches);
+    let slippage_bps = value_t_or_exit!(matches, "slippage_bps", u64);
+
+    let (state, quote) = quote_market(config, &swap_client, amount_in, swap_direction)?;
+    print_quote(&state, &quote, amount_in, swap_direction)?;
+    let minimum_amount_out = match value_of(matches, "minimum_amount_out") {
+        Some(minimum_amount_out) => minimum_amount_out,
+        None => u64::try_from(
+            u128::from(quote.amount_out) * u128::from(BPS_DENOMINATOR.saturating_sub(slippage_bps))
+                / u128::from(BPS_DENOMINATOR),
+        )?,
+    };
+    println!("Minimum amount out: {}", minimum_amount_out);
+
+    let mut instructions = vec![];
+    let base_account = token_account_of(
+        config,
+        matches,
+        "base_account",
+        &swap_client.swap.token_a_mint,
+        &mut instructions,
+    )?;
+    let quote_account = token_account_of(
+        config,
+        matches,
+        "quote_account",
+        &swap_client.swap.token_b_mint,
+        &mut instructions,
+    )?;
+    let reward_account = token_account_of(
+        config,
+        matches,
+        "reward_account",
+        &swap_client.config.deltafi_mint,
+        &mut instructions,
+    )?;
+    instructions.push(swap_client.swap(
+        config.fee_payer.pubkey(),
+        base_account,
+        quote_account,
+        reward_account,
+        SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps: value_t_or_exit!(matches, "max_price_impact_bps", u64),
+        },
+    )?);
+    send_transaction(config, &instructions, &[])?;
+    Ok(())
+}
+
+fn main() {
+    let app_matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg({
+            let arg = Arg::with_name("config_file")
+                .short("C")
+                .long("config")
+                .value_name("PATH")
+                .takes_value(true)
+                .global(true)
+                .help("Configuration file to use");
+            if let Some(ref config_file) = *solana_cli_config::CONFIG_FILE {
+                arg.default_value(config_file)
+            } else {
+                arg
+            }
+        })
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .short("u")
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .global(true)
+                .validator(is_url)
+                .help("JSON RPC URL for the cluster [default: value from configuration file]"),
+        )
+        .arg(
+            Arg::with_name("keypair")
+                .short("k")
+                .long("keypair")
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .global(true)
+                .validator(is_valid_signer)
+                .help("Fee payer, signing as the owner of the accounts it creates [default: client keypair]"),
+        )
+        .arg(
+            Arg::with_name("admin")
+                .long("admin")
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .global(true)
+                .validator(is_valid_signer)
+                .help("Admin of the config, signing its admin instructions [default: fee payer]"),
+        )
+        .arg(
+            Arg::with_name("program_id")
+                .long("program-id")
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .global(true)
+                .validator(is_pubkey)
+                .help("Deltafi swap program id [default: deployed program id]"),
+        )
+        .subcommand(
+            SubCommand::with_name("init-config")
+                .about("Create a config along with its DELFI mint")
+                .args(&fee_args(true))
+                .arg(
+                    Arg::with_name("min_trade_fee")
+                        .long("min-trade-fee")
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Min trade fee of a swap, in the token received"),
+                )
+                .args(&reward_args(true))
+                .arg(
+                    Arg::with_name("max_mid_price_deviation_bps")
+                        .long("max-mid-price-deviation-bps")
+                        .value_name("BPS")
+                        .takes_value(true)
+                        .default_value("500")
+                        .help("Max deviation of a mid price update"),
+                )
+                .arg(
+                    Arg::with_name("min_mid_price_update_interval")
+                        .long("min-mid-price-update-interval")
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .default_value("3600")
+                        .help("Min seconds between mid price updates"),
+                )
+                .arg(
+                    Arg::with_name("deltafi_decimals")
+                        .long("deltafi-decimals")
+                        .value_name("DECIMALS")
+                        .takes_value(true)
+                        .default_value("9")
+                        .help("Decimals of the DELFI mint"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("init-pool")
+                .about("Create a pool of the config, funded from the fee payer token accounts")
+                .arg(pubkey_arg("swap_config", "swap-config", "Config of the pool").required(true))
+                .arg(pubkey_arg("token_a_mint", "token-a-mint", "Mint of token A").required(true))
+                .arg(pubkey_arg("token_b_mint", "token-b-mint", "Mint of token B").required(true))
+                .arg(pubkey_arg("oracle_a", "oracle-a", "Pyth price account of token A").required(true))
+                .arg(pubkey_arg("oracle_b", "oracle-b", "Pyth price account of token B").required(true))
+                .arg(amount_arg("amount_a", "amount-a", "Initial token A liquidity").required(true))
+                .arg(amount_arg("amount_b", "amount-b", "Initial token B liquidity").required(true))
+                .arg(pubkey_arg(
+                    "source_a",
+                    "source-a",
+                    "Token A account funding the pool [default: associated token account of the fee payer]",
+                ))
+                .arg(pubkey_arg(
+                    "source_b",
+                    "source-b",
+                    "Token B account funding the pool [default: associated token account of the fee payer]",
+                ))
+                .arg(pubkey_arg(
+                    "admin_fee_owner",
+                    "admin-fee-owner",
+                    "Owner of the admin fee accounts [default: fee payer]",
+                ))
+                .arg(
+                    Arg::with_name("mid_price")
+                        .long("mid-price")
+                        .value_name("PRICE")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_decimal)
+                        .help("Price of token A in token B used without oracle price"),
+                )
+                .arg(
+                    Arg::with_name("slope")
+                        .long("slope")
+                        .value_name("SLOPE")
+                        .takes_value(true)
+                        .default_value("0.5")
+                        .validator(is_decimal)
+                        .help("Slope of the curve, between 0 and 1"),
+                )
+                .arg(
+                    Arg::with_name("open_twap")
+                        .long("open-twap")
+                        .takes_value(false)
+                        .help("Price the pool with its twap when the oracle price is unavailable"),
+                )
+                .arg(
+                    Arg::with_name("twap_window_secs")
+                        .long("twap-window-secs")
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .default_value("3600")
+                        .help("Seconds averaged by the twap, zero averages all of it"),
+                )
+                .arg(
+                    Arg::with_name("pair_symbol")
+                        .long("pair-symbol")
+                        .value_name("SYMBOL")
+                        .takes_value(true)
+                        .help("Symbol of the pair, like SOL-USDC, naming the pool mint"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("pause")
+                .about("Pause the swaps and deposits of a pool")
+                .args(&pool_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("unpause")
+                .about("Resume a paused pool")
+                .args(&pool_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("set-fees")
+                .about("Set the fees of a pool, the fees left out being kept")
+                .args(&pool_args())
+                .args(&fee_args(false)),
+        )
+        .subcommand(
+            SubCommand::with_name("set-rewards")
+                .about("Set the rewards of a pool, the rewards left out being kept")
+                .args(&pool_args())
+                .args(&reward_args(false)),
+        )
+        .subcommand(
+            SubCommand::with_name("set-fee-account")
+                .about("Collect the admin fees of a pool token in another account")
+                .args(&pool_args())
+                .arg(
+                    pubkey_arg(
+                        "fee_account",
+                        "fee-account",
+                        "Token account collecting the admin fees of its mint",
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("set-admin")
+                .about("Transfer the admin of a config")
+                .arg(pubkey_arg("swap_config", "swap-config", "Config").required(true))
+                .arg(pubkey_arg("new_admin", "new-admin", "New admin").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("quote")
+                .about("Quote a swap of a pool at the current slot")
+                .args(&pool_args())
+                .args(&swap_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("swap")
+                .about("Quote then swap the tokens of the fee payer")
+                .args(&pool_args())
+                .args(&swap_args())
+                .arg(
+                    Arg::with_name("slippage_bps")
+                        .long("slippage-bps")
+                        .value_name("BPS")
+                        .takes_value(true)
+                        .default_value("50")
+                        .validator(is_parsable::<u64>)
+                        .help("Slippage below the quoted amount out accepted by the swap"),
+                )
+                .arg(
+                    amount_arg(
+                        "minimum_amount_out",
+                        "minimum-amount-out",
+                        "Minimum amount out, overriding the slippage",
+                    ),
+                )
+                .arg(
+                    Arg::with_name("max_price_impact_bps")
+                        .long("max-price-impact-bps")
+                        .value_name("BPS")
+                        .takes_value(true)
+                        .default_value("0")
+                        .validator(is_parsable::<u64>)
+                        .help("Max price impact of the swap, 0 for no limit"),
+                )
+                .arg(pubkey_arg(
+                    "base_account",
+                    "base-account",
+                    "Token A account [default: associated token account of the fee payer]",
+                ))
+                .arg(pubkey_arg(
+                    "quote_account",
+                    "quote-account",
+                    "Token B account [default: associated token account of the fee payer]",
+                ))
+                .arg(pubkey_arg(
+                    "reward_account",
+                    "reward-account",
+                    "DELFI account receiving the reward [default: associated token account of the fee payer]",
+                )),
+        )
+        .get_matches();
+
+    let (sub_command, sub_matches) = app_matches.subcommand();
+    let matches = sub_matches.unwrap();
+    let mut wallet_manager = None;
+
+    let config = {
+        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+            solana_cli_config::Config::load(config_file).unwrap_or_default()
+        } else {
+            solana_cli_config::Config::default()
+        };
+        let json_rpc_url = value_t!(matches, "json_rpc_url", String)
+            .unwrap_or_else(|_| cli_config.json_rpc_url.clone());
+        let fee_payer = signer_from_path(
+            matches,
+            matches
+                .value_of("keypair")
+                .unwrap_or(&cli_config.keypair_path),
+            "keypair",
+            &mut wallet_manager,
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            exit(1);
+        });
+        let admin = matches.value_of("admin").map(|path| {
+            signer_from_path(matches, path, "admin", &mut wallet_manager).unwrap_or_else(|err| {
+                eprintln!("error: {}", err);
+                exit(1);
+            })
+        });
+        Config {
+            rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
+            fee_payer,
+            admin,
+            program_id: pubkey_of(matches, "program_id").unwrap_or_else(deltafi_swap::id),
+        }
+    };
+
+    let result = match sub_command {
+        "init-config" => command_init_config(&config, matches),
+        "init-pool" => command_init_pool(&config, matches),
+        "pause" => command_pause(&config, matches),
+        "unpause" => command_unpause(&config, matches),
+        "set-fees" => command_set_fees(&config, matches),
+        "set-rewards" => command_set_rewards(&config, matches),
+        "set-fee-account" => command_set_fee_account(&config, matches),
+        "set-admin" => command_set_admin(&config, matches),
+        "quote" => command_quote(&config, matches),
+        "swap" => command_swap(&config, matches),
+        _ => unreachable!(),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        exit(1);
+    }
+}
+
+fn pubkey_arg<'a>(name: &'a str, long: &'a str, help: &'a str) -> Arg<'a, 'a> {
+    Arg::with_name(name)
+        .long(long)
+        .value_name("PUBKEY")
+        .takes_value(true)
+        .validator(is_valid_pubkey)
+        .help(help)
+}
+
+fn pool_args<'a>() -> Vec<Arg<'a, 'a>> {
+    vec![
+        pubkey_arg("swap_config", "swap-config", "Config of the pool").required(true),
+        pubkey_arg("pool", "pool", "Token-swap account of the pool").required(true),
+    ]
+}
+
+fn swap_args<'a>() -> Vec<Arg<'a, 'a>> {
+    vec![
+        amount_arg("amount_in", "amount-in", "Amount sold").required(true),
+        Arg::with_name("direction")
+            .long("direction")
+            .value_name("DIRECTION")
+            .takes_value(true)
+            .possible_values(&["sell-base", "sell-quote"])
+            .required(true)
+            .help("Sell token A for token B, or token B for token A"),
+    ]
+}
+
+fn amount_arg<'a>(name: &'a str, long: &'a str, help: &'a str) -> Arg<'a, 'a> {
+    Arg::with_name(name)
+        .long(long)
+        .value_name("AMOUNT")
+        .takes_value(true)
+        .validator(is_parsable::<u64>)
+        .help(help)
+}
+
+fn fraction_arg<'a>(name: &'a str, long: &'a str, help: &'a str) -> Arg<'a, 'a> {
+    Arg::with_name(name)
+        .long(long)
+        .value_name("FRACTION")
+        .takes_value(true)
+        .validator(is_fraction)
+        .help(help)
+}
+
+/// Fee arguments, defaulting to the fees of a new config when creating one
+fn fee_args<'a>(is_new_config: bool) -> Vec<Arg<'a, 'a>> {
+    vec![
+        (
+            fraction_arg("trade_fee", "trade-fee", "Trade fee"),
+            "5/1000",
+        ),
+        (
+            fraction_arg("withdraw_fee", "withdraw-fee", "Withdraw fee"),
+            "2/100",
+        ),
+        (
+            fraction_arg(
+                "admin_trade_fee",
+                "admin-trade-fee",
+                "Share of the trade fee going to the admin",
+            ),
+            "2/5",
+        ),
+        (
+            fraction_arg(
+                "admin_withdraw_fee",
+                "admin-withdraw-fee",
+                "Share of the withdraw fee going to the admin",
+            ),
+            "2/5",
+        ),
+    ]
+    .into_iter()
+    .map(|(arg, default_value)| {
+        if is_new_config {
+            arg.default_value(default_value)
+        } else {
+            arg
+        }
+    })
+    .collect()
+}
+
+/// Reward arguments, defaulting to the rewards of a new config when creating
+/// one
+fn reward_args<'a>(is_new_config: bool) -> Vec<Arg<'a, 'a>> {
+    vec![
+        (
+            fraction_arg("trade_reward", "trade-reward", "DELFI reward of a trade"),
+            "1/1000",
+        ),
+        (
+            Arg::with_name("trade_reward_cap")
+                .long("trade-reward-cap")
+                .value_name("AMOUNT")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .help("Max DELFI reward of a trade"),
+            "10000000000",
+        ),
+        (
+            fraction_arg(
+                "liquidity_reward",
+                "liquidity-reward",
+                "DELFI reward of the liquidity",
+            ),
+            "1/1000",
+        ),
+    ]
+    .into_iter()
+    .map(|(arg, default_value)| {
+        if is_new_config {
+            arg.default_value(default_value)
+        } else {
+            arg
+        }
+    })
+    .collect()
+}
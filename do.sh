@@ -3,7 +3,7 @@
 set -ex
 cd "$(dirname "$0")"
 
-solana_version="1.7.11"
+solana_version="1.10.29"
 export PATH="$HOME"/.local/share/solana/install/active_release/bin:"$PATH"
 
 usage() {
@@ -20,6 +20,7 @@ Supported actions:
     new-swap
     fmt
     test
+    bench-cu
     e2e-test
     update
 EOF
@@ -90,6 +91,9 @@ perform_action() {
     test-bpf)
             cargo test-bpf ${@:2}
         ;;
+    bench-cu)
+            cargo test-bpf --test compute_units ${@:2} -- --nocapture
+        ;;
     exact-test)
             cargo test ${@:2} -- --exact
         ;;
@@ -31,6 +31,8 @@ export const DEFAULT_REWARDS: Rewards = {
   tradeRewardNumerator: new BN(DEFAULT_REWARD_NUMERATOR),
   tradeRewardDenominator: new BN(DEFAULT_REWARD_DENOMINATOR),
   tradeRewardCap: new BN(DEFAULT_REWARD_CAP),
+  liquidityRewardNumerator: new BN(0),
+  liquidityRewardDenominator: new BN(DEFAULT_REWARD_DENOMINATOR),
 };
 
 export const CLUSTER_URL = "http://localhost:8899";
@@ -1,3 +1,4 @@
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import {
   PublicKey,
   TransactionInstruction,
@@ -8,7 +9,12 @@ import BN from "bn.js";
 
 import { Fees, Rewards } from "../state";
 import { FeesLayout, RewardsLayout } from "../state";
-import { u64 } from "../util";
+import {
+  u64,
+  u128,
+  findObservationBufferAddress,
+  findPriceFeedAddress,
+} from "../util";
 
 export enum AdminInstruction {
   Initialize = 100,
@@ -21,6 +27,9 @@ export enum AdminInstruction {
   CommitNewAdmin,
   SetNewFees,
   SetNewRewards,
+  SetMidPrice = 107,
+  BuybackAndBurn = 120,
+  Recalibrate = 134,
 }
 
 export interface AdminInitializeData {
@@ -46,6 +55,27 @@ export const RampADataLayout = struct<RampAData>(
   "ramp"
 );
 
+export interface SetMidPriceData {
+  midPrice: BN;
+}
+
+/** @internal */
+export const SetMidPriceDataLayout = struct<SetMidPriceData>(
+  [u128("midPrice")],
+  "midPriceData"
+);
+
+export interface BuybackAndBurnData {
+  amountIn: BN;
+  minimumAmountOut: BN;
+}
+
+/** @internal */
+export const BuybackAndBurnDataLayout = struct<BuybackAndBurnData>(
+  [u64("amountIn"), u64("minimumAmountOut")],
+  "buybackData"
+);
+
 export const createAdminInitializeInstruction = (
   config: PublicKey,
   adminKey: PublicKey,
@@ -325,3 +355,158 @@ export const createSetNewRewardsInstruction = (
     programId,
   });
 };
+
+export const createSetMidPriceInstruction = async (
+  config: PublicKey,
+  tokenSwap: PublicKey,
+  adminKey: PublicKey,
+  pythA: PublicKey,
+  pythB: PublicKey,
+  poolMint: PublicKey,
+  midPriceData: SetMidPriceData,
+  programId: PublicKey
+) => {
+  const keys = [
+    { pubkey: config, isSigner: false, isWritable: false },
+    { pubkey: tokenSwap, isSigner: false, isWritable: true },
+    { pubkey: adminKey, isSigner: true, isWritable: false },
+    { pubkey: pythA, isSigner: false, isWritable: false },
+    { pubkey: pythB, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    {
+      pubkey: await findObservationBufferAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: await findPriceFeedAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    { pubkey: poolMint, isSigner: false, isWritable: false },
+  ];
+  const dataLayout = struct([u8("instruction"), SetMidPriceDataLayout]);
+  let data = Buffer.alloc(dataLayout.span);
+  dataLayout.encode(
+    {
+      instruction: AdminInstruction.SetMidPrice,
+      midPriceData,
+    },
+    data
+  );
+
+  return new TransactionInstruction({
+    keys,
+    data,
+    programId,
+  });
+};
+
+export const createRecalibrateInstruction = async (
+  config: PublicKey,
+  tokenSwap: PublicKey,
+  adminKey: PublicKey,
+  tokenA: PublicKey,
+  tokenB: PublicKey,
+  pythA: PublicKey,
+  pythB: PublicKey,
+  poolMint: PublicKey,
+  programId: PublicKey
+) => {
+  const keys = [
+    { pubkey: config, isSigner: false, isWritable: false },
+    { pubkey: tokenSwap, isSigner: false, isWritable: true },
+    { pubkey: adminKey, isSigner: true, isWritable: false },
+    { pubkey: tokenA, isSigner: false, isWritable: false },
+    { pubkey: tokenB, isSigner: false, isWritable: false },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: pythA, isSigner: false, isWritable: false },
+    { pubkey: pythB, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    {
+      pubkey: await findPriceFeedAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    { pubkey: poolMint, isSigner: false, isWritable: false },
+  ];
+  const dataLayout = struct([u8("instruction")]);
+  let data = Buffer.alloc(dataLayout.span);
+  dataLayout.encode(
+    {
+      instruction: AdminInstruction.Recalibrate,
+    },
+    data
+  );
+
+  return new TransactionInstruction({
+    keys,
+    data,
+    programId,
+  });
+};
+
+/// Buys DELFI back with the admin fees of a token-swap in the DELFI pool, the
+/// pyth accounts and pool mint being the ones of the DELFI pool
+export const createBuybackAndBurnInstruction = async (
+  config: PublicKey,
+  tokenSwap: PublicKey,
+  swapAuthority: PublicKey,
+  adminKey: PublicKey,
+  adminFeeSource: PublicKey,
+  deltafiSwap: PublicKey,
+  deltafiSwapAuthority: PublicKey,
+  tokenA: PublicKey,
+  tokenB: PublicKey,
+  adminFeeDestination: PublicKey,
+  deltafiMint: PublicKey,
+  pythA: PublicKey,
+  pythB: PublicKey,
+  deltafiPoolMint: PublicKey,
+  buybackData: BuybackAndBurnData,
+  programId: PublicKey
+) => {
+  const keys = [
+    { pubkey: config, isSigner: false, isWritable: false },
+    { pubkey: tokenSwap, isSigner: false, isWritable: false },
+    { pubkey: swapAuthority, isSigner: false, isWritable: false },
+    { pubkey: adminKey, isSigner: true, isWritable: false },
+    { pubkey: adminFeeSource, isSigner: false, isWritable: true },
+    { pubkey: deltafiSwap, isSigner: false, isWritable: true },
+    { pubkey: deltafiSwapAuthority, isSigner: false, isWritable: false },
+    { pubkey: tokenA, isSigner: false, isWritable: true },
+    { pubkey: tokenB, isSigner: false, isWritable: true },
+    { pubkey: adminFeeDestination, isSigner: false, isWritable: true },
+    { pubkey: deltafiMint, isSigner: false, isWritable: true },
+    { pubkey: pythA, isSigner: false, isWritable: false },
+    { pubkey: pythB, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    {
+      pubkey: await findObservationBufferAddress(deltafiSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: await findPriceFeedAddress(deltafiSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    { pubkey: deltafiPoolMint, isSigner: false, isWritable: false },
+  ];
+  const dataLayout = struct([u8("instruction"), BuybackAndBurnDataLayout]);
+  let data = Buffer.alloc(dataLayout.span);
+  dataLayout.encode(
+    {
+      instruction: AdminInstruction.BuybackAndBurn,
+      buybackData,
+    },
+    data
+  );
+
+  return new TransactionInstruction({
+    keys,
+    data,
+    programId,
+  });
+};
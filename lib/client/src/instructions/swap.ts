@@ -1,9 +1,18 @@
 import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
-import { PublicKey, TransactionInstruction } from "@solana/web3.js";
+import {
+  PublicKey,
+  SYSVAR_CLOCK_PUBKEY,
+  TransactionInstruction,
+} from "@solana/web3.js";
 import { struct, u8 } from "buffer-layout";
 import BN from "bn.js";
 
-import { u64 } from "../util";
+import {
+  u64,
+  findObservationBufferAddress,
+  findPriceFeedAddress,
+  findWhitelistAddress,
+} from "../util";
 
 export enum SwapInstruction {
   Initialize = 0,
@@ -11,6 +20,8 @@ export enum SwapInstruction {
   Deposit,
   Withdraw,
   WithdrawOne,
+  EmergencyWithdraw = 0x21,
+  Sync,
 }
 
 export interface InitializeData {
@@ -29,15 +40,60 @@ export const InitializeDataLayout = struct<InitializeData>(
 export interface SwapData {
   amountIn: BN;
   minimumAmountOut: BN;
-  swapDirection: BN;
+  swapDirection: number;
+  maxPriceImpactBps: BN;
 }
 
 /** @internal */
 export const SwapDataLayout = struct<SwapData>(
-  [u64("amountIn"), u64("minimumAmountOut"), u64("swapDirection")],
+  [
+    u64("amountIn"),
+    u64("minimumAmountOut"),
+    u8("swapDirection"),
+    u64("maxPriceImpactBps"),
+  ],
   "swapData"
 );
 
+export interface DepositData {
+  tokenAAmount: BN;
+  tokenBAmount: BN;
+  minMintAmount: BN;
+}
+
+/** @internal */
+export const DepositDataLayout = struct<DepositData>(
+  [u64("tokenAAmount"), u64("tokenBAmount"), u64("minMintAmount")],
+  "depositData"
+);
+
+export interface WithdrawData {
+  poolTokenAmount: BN;
+  minimumTokenAAmount: BN;
+  minimumTokenBAmount: BN;
+}
+
+/** @internal */
+export const WithdrawDataLayout = struct<WithdrawData>(
+  [
+    u64("poolTokenAmount"),
+    u64("minimumTokenAAmount"),
+    u64("minimumTokenBAmount"),
+  ],
+  "withdrawData"
+);
+
+export interface EmergencyWithdrawData {
+  minimumTokenAAmount: BN;
+  minimumTokenBAmount: BN;
+}
+
+/** @internal */
+export const EmergencyWithdrawDataLayout = struct<EmergencyWithdrawData>(
+  [u64("minimumTokenAAmount"), u64("minimumTokenBAmount")],
+  "withdrawData"
+);
+
 export const createInitSwapInstruction = (
   config: PublicKey,
   tokenSwap: PublicKey,
@@ -81,3 +137,324 @@ export const createInitSwapInstruction = (
     data,
   });
 };
+
+/// Appends the OpenBook market of the token-swap along with its bids and asks
+/// to an instruction pricing the pool, right after its fixed accounts. Only
+/// token-swaps with a market take it.
+export const appendOpenbookMarket = (
+  instruction: TransactionInstruction,
+  market: PublicKey,
+  bids: PublicKey,
+  asks: PublicKey
+): TransactionInstruction => {
+  instruction.keys.push(
+    { pubkey: market, isSigner: false, isWritable: false },
+    { pubkey: bids, isSigner: false, isWritable: false },
+    { pubkey: asks, isSigner: false, isWritable: false }
+  );
+  return instruction;
+};
+
+/// Appends the transfer hook program of the token-swap to a swap or deposit,
+/// after any OpenBook market. Nothing is appended without a hook.
+export const appendTransferHook = (
+  instruction: TransactionInstruction,
+  transferHookProgram: PublicKey
+): TransactionInstruction => {
+  if (!transferHookProgram.equals(new PublicKey(0))) {
+    instruction.keys.push({
+      pubkey: transferHookProgram,
+      isSigner: false,
+      isWritable: false,
+    });
+  }
+  return instruction;
+};
+
+export const createSwapInstruction = async (
+  config: PublicKey,
+  tokenSwap: PublicKey,
+  marketAuthority: PublicKey,
+  swapAuthority: PublicKey,
+  userTransferAuthority: PublicKey,
+  source: PublicKey,
+  swapSource: PublicKey,
+  swapDestination: PublicKey,
+  destination: PublicKey,
+  rewardToken: PublicKey,
+  rewardMint: PublicKey,
+  adminFeeDestination: PublicKey,
+  pythA: PublicKey,
+  pythB: PublicKey,
+  poolMint: PublicKey,
+  swapData: SwapData,
+  programId: PublicKey
+): Promise<TransactionInstruction> => {
+  const keys = [
+    { pubkey: config, isSigner: false, isWritable: true },
+    { pubkey: tokenSwap, isSigner: false, isWritable: true },
+    { pubkey: marketAuthority, isSigner: false, isWritable: false },
+    { pubkey: swapAuthority, isSigner: false, isWritable: false },
+    { pubkey: userTransferAuthority, isSigner: true, isWritable: false },
+    { pubkey: source, isSigner: false, isWritable: true },
+    { pubkey: swapSource, isSigner: false, isWritable: true },
+    { pubkey: swapDestination, isSigner: false, isWritable: true },
+    { pubkey: destination, isSigner: false, isWritable: true },
+    { pubkey: rewardToken, isSigner: false, isWritable: true },
+    { pubkey: rewardMint, isSigner: false, isWritable: true },
+    { pubkey: adminFeeDestination, isSigner: false, isWritable: true },
+    { pubkey: pythA, isSigner: false, isWritable: false },
+    { pubkey: pythB, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    {
+      pubkey: await findObservationBufferAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: await findWhitelistAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: await findPriceFeedAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    { pubkey: poolMint, isSigner: false, isWritable: false },
+  ];
+  const dataLayout = struct([u8("instruction"), SwapDataLayout]);
+  let data = Buffer.alloc(dataLayout.span);
+  dataLayout.encode(
+    {
+      instruction: SwapInstruction.Swap,
+      swapData,
+    },
+    data
+  );
+
+  return new TransactionInstruction({
+    keys,
+    programId,
+    data,
+  });
+};
+
+export const createDepositInstruction = async (
+  tokenSwap: PublicKey,
+  authority: PublicKey,
+  userTransferAuthority: PublicKey,
+  depositTokenA: PublicKey,
+  depositTokenB: PublicKey,
+  swapTokenA: PublicKey,
+  swapTokenB: PublicKey,
+  poolMint: PublicKey,
+  destination: PublicKey,
+  liquidityProvider: PublicKey,
+  liquidityOwner: PublicKey,
+  pythA: PublicKey,
+  pythB: PublicKey,
+  depositData: DepositData,
+  programId: PublicKey
+): Promise<TransactionInstruction> => {
+  const keys = [
+    { pubkey: tokenSwap, isSigner: false, isWritable: true },
+    { pubkey: authority, isSigner: false, isWritable: false },
+    { pubkey: userTransferAuthority, isSigner: true, isWritable: false },
+    { pubkey: depositTokenA, isSigner: false, isWritable: true },
+    { pubkey: depositTokenB, isSigner: false, isWritable: true },
+    { pubkey: swapTokenA, isSigner: false, isWritable: true },
+    { pubkey: swapTokenB, isSigner: false, isWritable: true },
+    { pubkey: poolMint, isSigner: false, isWritable: true },
+    { pubkey: destination, isSigner: false, isWritable: true },
+    { pubkey: liquidityProvider, isSigner: false, isWritable: true },
+    { pubkey: liquidityOwner, isSigner: true, isWritable: false },
+    { pubkey: pythA, isSigner: false, isWritable: false },
+    { pubkey: pythB, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    {
+      pubkey: await findObservationBufferAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: await findPriceFeedAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  const dataLayout = struct([u8("instruction"), DepositDataLayout]);
+  let data = Buffer.alloc(dataLayout.span);
+  dataLayout.encode(
+    {
+      instruction: SwapInstruction.Deposit,
+      depositData,
+    },
+    data
+  );
+
+  return new TransactionInstruction({
+    keys,
+    programId,
+    data,
+  });
+};
+
+export const createWithdrawInstruction = async (
+  tokenSwap: PublicKey,
+  authority: PublicKey,
+  userTransferAuthority: PublicKey,
+  poolMint: PublicKey,
+  source: PublicKey,
+  swapTokenA: PublicKey,
+  swapTokenB: PublicKey,
+  destinationTokenA: PublicKey,
+  destinationTokenB: PublicKey,
+  adminFeeA: PublicKey,
+  adminFeeB: PublicKey,
+  liquidityProvider: PublicKey,
+  liquidityOwner: PublicKey,
+  pythA: PublicKey,
+  pythB: PublicKey,
+  withdrawData: WithdrawData,
+  programId: PublicKey
+): Promise<TransactionInstruction> => {
+  const keys = [
+    { pubkey: tokenSwap, isSigner: false, isWritable: true },
+    { pubkey: authority, isSigner: false, isWritable: false },
+    { pubkey: userTransferAuthority, isSigner: true, isWritable: false },
+    { pubkey: poolMint, isSigner: false, isWritable: true },
+    { pubkey: source, isSigner: false, isWritable: true },
+    { pubkey: swapTokenA, isSigner: false, isWritable: true },
+    { pubkey: swapTokenB, isSigner: false, isWritable: true },
+    { pubkey: destinationTokenA, isSigner: false, isWritable: true },
+    { pubkey: destinationTokenB, isSigner: false, isWritable: true },
+    { pubkey: adminFeeA, isSigner: false, isWritable: true },
+    { pubkey: adminFeeB, isSigner: false, isWritable: true },
+    { pubkey: liquidityProvider, isSigner: false, isWritable: true },
+    { pubkey: liquidityOwner, isSigner: true, isWritable: false },
+    { pubkey: pythA, isSigner: false, isWritable: false },
+    { pubkey: pythB, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    {
+      pubkey: await findObservationBufferAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: await findPriceFeedAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  const dataLayout = struct([u8("instruction"), WithdrawDataLayout]);
+  let data = Buffer.alloc(dataLayout.span);
+  dataLayout.encode(
+    {
+      instruction: SwapInstruction.Withdraw,
+      withdrawData,
+    },
+    data
+  );
+
+  return new TransactionInstruction({
+    keys,
+    programId,
+    data,
+  });
+};
+
+export const createEmergencyWithdrawInstruction = async (
+  tokenSwap: PublicKey,
+  authority: PublicKey,
+  userTransferAuthority: PublicKey,
+  poolMint: PublicKey,
+  source: PublicKey,
+  swapTokenA: PublicKey,
+  swapTokenB: PublicKey,
+  destinationTokenA: PublicKey,
+  destinationTokenB: PublicKey,
+  adminFeeA: PublicKey,
+  adminFeeB: PublicKey,
+  liquidityProvider: PublicKey,
+  liquidityOwner: PublicKey,
+  withdrawData: EmergencyWithdrawData,
+  programId: PublicKey
+): Promise<TransactionInstruction> => {
+  const keys = [
+    { pubkey: tokenSwap, isSigner: false, isWritable: true },
+    { pubkey: authority, isSigner: false, isWritable: false },
+    { pubkey: userTransferAuthority, isSigner: true, isWritable: false },
+    { pubkey: poolMint, isSigner: false, isWritable: true },
+    { pubkey: source, isSigner: false, isWritable: true },
+    { pubkey: swapTokenA, isSigner: false, isWritable: true },
+    { pubkey: swapTokenB, isSigner: false, isWritable: true },
+    { pubkey: destinationTokenA, isSigner: false, isWritable: true },
+    { pubkey: destinationTokenB, isSigner: false, isWritable: true },
+    { pubkey: adminFeeA, isSigner: false, isWritable: true },
+    { pubkey: adminFeeB, isSigner: false, isWritable: true },
+    { pubkey: liquidityProvider, isSigner: false, isWritable: true },
+    { pubkey: liquidityOwner, isSigner: true, isWritable: false },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    {
+      pubkey: await findPriceFeedAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+  ];
+  const dataLayout = struct([u8("instruction"), EmergencyWithdrawDataLayout]);
+  let data = Buffer.alloc(dataLayout.span);
+  dataLayout.encode(
+    {
+      instruction: SwapInstruction.EmergencyWithdraw,
+      withdrawData,
+    },
+    data
+  );
+
+  return new TransactionInstruction({
+    keys,
+    programId,
+    data,
+  });
+};
+
+export const createSyncInstruction = async (
+  tokenSwap: PublicKey,
+  swapTokenA: PublicKey,
+  swapTokenB: PublicKey,
+  poolMint: PublicKey,
+  programId: PublicKey
+): Promise<TransactionInstruction> => {
+  const keys = [
+    { pubkey: tokenSwap, isSigner: false, isWritable: true },
+    { pubkey: swapTokenA, isSigner: false, isWritable: false },
+    { pubkey: swapTokenB, isSigner: false, isWritable: false },
+    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    {
+      pubkey: await findPriceFeedAddress(tokenSwap, programId),
+      isSigner: false,
+      isWritable: true,
+    },
+    { pubkey: poolMint, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+  ];
+  const dataLayout = struct([u8("instruction")]);
+  let data = Buffer.alloc(dataLayout.span);
+  dataLayout.encode(
+    {
+      instruction: SwapInstruction.Sync,
+    },
+    data
+  );
+
+  return new TransactionInstruction({
+    keys,
+    programId,
+    data,
+  });
+};
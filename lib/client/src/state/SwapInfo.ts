@@ -2,59 +2,81 @@ import { AccountInfo, PublicKey } from "@solana/web3.js";
 import { struct, u8 } from "buffer-layout";
 import BN from "bn.js";
 
-import { publicKey, u64, bool, AccountParser } from "../util";
+import { publicKey, u64, u128, bool, AccountParser } from "../util";
 import {
   Fees,
   Rewards,
   FeesLayout,
   RewardsLayout,
-  FixedU64,
-  FixedU64Layout,
+  PoolState,
+  PoolStateLayout,
+  HalvingSchedule,
+  HalvingScheduleLayout,
+  GaugeVotes,
+  GaugeVotesLayout,
+  GaugeWeight,
+  GaugeWeightLayout,
+  DirectionalFees,
+  DirectionalFeesLayout,
+  CurveType,
+  CurveTypeLayout,
 } from ".";
 
-export enum RState {
-  /// r = 1
-  One = 110,
-
-  /// r > 1
-  AboveOne,
-
-  /// r < 1
-  BelowOne,
-}
-
+/// Decimal values are scaled by 10^18
 export interface SwapInfo {
   isInitialized: boolean;
   isPaused: boolean;
   nonce: number;
-  initialAmpFactor: BN;
-  targetAmpFactor: BN;
-  startRampTo: BN;
-  stopRampTo: BN;
   tokenA: PublicKey;
   tokenB: PublicKey;
-  deltafiToken: PublicKey;
   poolMint: PublicKey;
   tokenMintA: PublicKey;
   tokenMintB: PublicKey;
-  deltafiMint: PublicKey;
   adminFeeKeyA: PublicKey;
   adminFeeKeyB: PublicKey;
   fees: Fees;
   rewards: Rewards;
-  k: FixedU64;
-  i: FixedU64;
-  r: number;
-  baseTarget: FixedU64;
-  quoteTarget: FixedU64;
-  baseReserve: FixedU64;
-  quoteReserve: FixedU64;
-  isOpenTwap: BN;
+  poolState: PoolState;
+  isOpenTwap: boolean;
   blockTimestampLast: BN;
-  basePriceCumulativeLast: FixedU64;
-  receiveAmount: FixedU64;
-  baseBalance: FixedU64;
-  quoteBalance: FixedU64;
+  cumulativeTicks: BN;
+  basePriceCumulativeLast: BN;
+  version: number;
+  lastMidPriceUpdateTs: BN;
+  pythA: PublicKey;
+  pythB: PublicKey;
+  twapWindowSecs: BN;
+  maxPriceImpactBps: BN;
+  maxAmountIn: BN;
+  maxAmountInReserveBps: BN;
+  maxSwapsPerSlot: BN;
+  maxVolumePerSlot: BN;
+  lastSwapSlot: BN;
+  slotSwapCount: BN;
+  slotSwapVolume: BN;
+  isFeeCompounding: boolean;
+  isWhitelistOnly: boolean;
+  transferHookProgram: PublicKey;
+  rewardPerShare: BN;
+  rewardLastUpdateTs: BN;
+  deltafiPrice: BN;
+  halvingSchedule: HalvingSchedule;
+  gaugeVotes: GaugeVotes;
+  gaugeWeight: GaugeWeight;
+  tokenADecimals: number;
+  tokenBDecimals: number;
+  maxBaseReserve: BN;
+  maxQuoteReserve: BN;
+  maxUserLiquidity: BN;
+  isImmutable: boolean;
+  directionalFees: DirectionalFees;
+  minTradeFee: BN;
+  isFeeInQuote: boolean;
+  hasFreezableMint: boolean;
+  cumulativeFeePerLp: BN;
+  openbookMarket: PublicKey;
+  configKey: PublicKey;
+  curveType: CurveType;
 }
 
 /** @internal */
@@ -63,42 +85,72 @@ export const SwapInfoLayout = struct<SwapInfo>(
     bool("isInitialized"),
     bool("isPaused"),
     u8("nonce"),
-    u64("initialAmpFactor"),
-    u64("targetAmpFactor"),
-    u64("startRampTo"),
-    u64("stopRampTo"),
     publicKey("tokenA"),
     publicKey("tokenB"),
-    publicKey("deltafiToken"),
     publicKey("poolMint"),
     publicKey("tokenMintA"),
     publicKey("tokenMintB"),
-    publicKey("deltafiMint"),
     publicKey("adminFeeKeyA"),
     publicKey("adminFeeKeyB"),
     FeesLayout("fees"),
     RewardsLayout("rewards"),
-    FixedU64Layout("k"),
-    FixedU64Layout("i"),
-    u8("r"),
-    FixedU64Layout("baseTarget"),
-    FixedU64Layout("quoteTarget"),
-    FixedU64Layout("baseReserve"),
-    FixedU64Layout("quoteReserve"),
-    u64("isOpenTwap"),
+    PoolStateLayout("poolState"),
+    bool("isOpenTwap"),
     u64("blockTimestampLast"),
-    FixedU64Layout("basePriceCumulativeLast"),
-    FixedU64Layout("receiveAmount"),
-    FixedU64Layout("baseBalance"),
-    FixedU64Layout("quoteBalance"),
+    u64("cumulativeTicks"),
+    u128("basePriceCumulativeLast"),
+    u8("version"),
+    u64("lastMidPriceUpdateTs"),
+    publicKey("pythA"),
+    publicKey("pythB"),
+    u64("twapWindowSecs"),
+    u64("maxPriceImpactBps"),
+    u64("maxAmountIn"),
+    u64("maxAmountInReserveBps"),
+    u64("maxSwapsPerSlot"),
+    u64("maxVolumePerSlot"),
+    u64("lastSwapSlot"),
+    u64("slotSwapCount"),
+    u64("slotSwapVolume"),
+    bool("isFeeCompounding"),
+    bool("isWhitelistOnly"),
+    publicKey("transferHookProgram"),
+    u128("rewardPerShare"),
+    u64("rewardLastUpdateTs"),
+    u128("deltafiPrice"),
+    HalvingScheduleLayout("halvingSchedule"),
+    GaugeVotesLayout("gaugeVotes"),
+    GaugeWeightLayout("gaugeWeight"),
+    u8("tokenADecimals"),
+    u8("tokenBDecimals"),
+    u64("maxBaseReserve"),
+    u64("maxQuoteReserve"),
+    u64("maxUserLiquidity"),
+    bool("isImmutable"),
+    DirectionalFeesLayout("directionalFees"),
+    u64("minTradeFee"),
+    bool("isFeeInQuote"),
+    bool("hasFreezableMint"),
+    u128("cumulativeFeePerLp"),
+    publicKey("openbookMarket"),
+    publicKey("configKey"),
+    CurveTypeLayout("curveType"),
   ],
-  "configInfo"
+  "swapInfo"
 );
 
 export const SWAP_INFO_SIZE = SwapInfoLayout.span;
 
+/// Version 1 accounts end before the version byte, the fields following it
+/// being zero
+export const SWAP_INFO_SIZE_V1 = 461;
+export const SWAP_INFO_VERSION_1 = 1;
+
 export const isSwapInfo = (info: AccountInfo<Buffer>): boolean => {
-  return info.data.length === SWAP_INFO_SIZE;
+  return (
+    info.data.length === SWAP_INFO_SIZE ||
+    info.data.length === SWAP_INFO_SIZE_V1
+  );
 };
 
 export const parseSwapInfo: AccountParser<SwapInfo> = (
@@ -107,8 +159,12 @@ export const parseSwapInfo: AccountParser<SwapInfo> = (
 ) => {
   if (!isSwapInfo(info)) return;
 
-  const buffer = Buffer.from(info.data);
+  const buffer = Buffer.alloc(SWAP_INFO_SIZE);
+  info.data.copy(buffer);
   const swapInfo = SwapInfoLayout.decode(buffer);
+  if (info.data.length === SWAP_INFO_SIZE_V1) {
+    swapInfo.version = SWAP_INFO_VERSION_1;
+  }
 
   if (!swapInfo.isInitialized) return;
 
@@ -0,0 +1,36 @@
+import { PublicKey } from "@solana/web3.js";
+
+import { SWAP_INFO_SIZE, SWAP_INFO_SIZE_V1, parseSwapInfo } from "./SwapInfo";
+import { CurveKind } from "./curve";
+
+const accountInfo = (data: Buffer) => ({
+  data,
+  executable: false,
+  lamports: 0,
+  owner: new PublicKey(0),
+});
+
+describe("SwapInfo tests", () => {
+  it("layout", () => {
+    expect(SWAP_INFO_SIZE).toEqual(919);
+
+    const data = Buffer.alloc(SWAP_INFO_SIZE);
+    data[0] = 1;
+    // version byte, then the curve type in the last 25 bytes
+    data[SWAP_INFO_SIZE_V1] = 3;
+    data[SWAP_INFO_SIZE - 25] = CurveKind.DynamicSlope;
+    data.writeUInt32LE(2, SWAP_INFO_SIZE - 8);
+    const swapInfo = parseSwapInfo(new PublicKey(0), accountInfo(data))!.data;
+    expect(swapInfo.version).toEqual(3);
+    expect(swapInfo.curveType.kind).toEqual(CurveKind.DynamicSlope);
+    expect(swapInfo.curveType.exponent.toNumber()).toEqual(2);
+  });
+
+  it("version 1 accounts", () => {
+    const data = Buffer.alloc(SWAP_INFO_SIZE_V1);
+    data[0] = 1;
+    const swapInfo = parseSwapInfo(new PublicKey(0), accountInfo(data))!.data;
+    expect(swapInfo.version).toEqual(1);
+    expect(swapInfo.curveType.kind).toEqual(CurveKind.Standard);
+  });
+});
@@ -0,0 +1,24 @@
+import BN from "bn.js";
+
+import { struct, u8 } from "buffer-layout";
+import { u64, u128 } from "../util";
+
+export enum CurveKind {
+  /// Slope of the pool at any inventory
+  Standard,
+
+  /// Slope widened as the inventory deviates from its target
+  DynamicSlope,
+}
+
+/// Curve a token-swap quotes its swaps on, the max slope and exponent being
+/// zero for the standard curve. The max slope is scaled by 10^18
+export interface CurveType {
+  kind: CurveKind;
+  maxSlope: BN;
+  exponent: BN;
+}
+
+/** @internal */
+export const CurveTypeLayout = (property: string = "curveType") =>
+  struct<CurveType>([u8("kind"), u128("maxSlope"), u64("exponent")], property);
@@ -29,3 +29,22 @@ export const FeesLayout = (property: string = "fees") =>
     ],
     property
   );
+
+export interface DirectionalFees {
+  sellBaseFeeNumerator: BN;
+  sellBaseFeeDenominator: BN;
+  sellQuoteFeeNumerator: BN;
+  sellQuoteFeeDenominator: BN;
+}
+
+/** @internal */
+export const DirectionalFeesLayout = (property: string = "directionalFees") =>
+  struct<DirectionalFees>(
+    [
+      u64("sellBaseFeeNumerator"),
+      u64("sellBaseFeeDenominator"),
+      u64("sellQuoteFeeNumerator"),
+      u64("sellQuoteFeeDenominator"),
+    ],
+    property
+  );
@@ -1,5 +1,7 @@
 export * from "./fees";
 export * from "./rewards";
 export * from "./fixed64";
+export * from "./pool";
+export * from "./curve";
 export * from "./ConfigInfo";
 export * from "./SwapInfo";
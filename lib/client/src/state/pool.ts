@@ -0,0 +1,41 @@
+import BN from "bn.js";
+
+import { struct, u8 } from "buffer-layout";
+import { u128 } from "../util";
+
+export enum Multiplier {
+  /// multiplier = 1
+  One,
+
+  /// multiplier > 1
+  AboveOne,
+
+  /// multiplier < 1
+  BelowOne,
+}
+
+/// Decimal values are scaled by 10^18
+export interface PoolState {
+  marketPrice: BN;
+  slope: BN;
+  baseReserve: BN;
+  quoteReserve: BN;
+  baseTarget: BN;
+  quoteTarget: BN;
+  multiplier: Multiplier;
+}
+
+/** @internal */
+export const PoolStateLayout = (property: string = "poolState") =>
+  struct<PoolState>(
+    [
+      u128("marketPrice"),
+      u128("slope"),
+      u128("baseReserve"),
+      u128("quoteReserve"),
+      u128("baseTarget"),
+      u128("quoteTarget"),
+      u8("multiplier"),
+    ],
+    property
+  );
@@ -1,12 +1,14 @@
 import BN from "bn.js";
 
-import { struct } from "buffer-layout";
+import { struct, u8 } from "buffer-layout";
 import { u64 } from "../util";
 
 export interface Rewards {
   tradeRewardNumerator: BN;
   tradeRewardDenominator: BN;
   tradeRewardCap: BN;
+  liquidityRewardNumerator: BN;
+  liquidityRewardDenominator: BN;
 }
 
 /** @internal */
@@ -16,6 +18,47 @@ export const RewardsLayout = (property: string = "rewards") =>
       u64("tradeRewardNumerator"),
       u64("tradeRewardDenominator"),
       u64("tradeRewardCap"),
+      u64("liquidityRewardNumerator"),
+      u64("liquidityRewardDenominator"),
     ],
     property
   );
+
+export interface HalvingSchedule {
+  startTs: BN;
+  periodSecs: BN;
+  halvingCount: number;
+}
+
+/** @internal */
+export const HalvingScheduleLayout = (property: string = "halvingSchedule") =>
+  struct<HalvingSchedule>(
+    [u64("startTs"), u64("periodSecs"), u8("halvingCount")],
+    property
+  );
+
+export interface GaugeVotes {
+  epoch: BN;
+  votes: BN;
+  previousVotes: BN;
+}
+
+/** @internal */
+export const GaugeVotesLayout = (property: string = "gaugeVotes") =>
+  struct<GaugeVotes>(
+    [u64("epoch"), u64("votes"), u64("previousVotes")],
+    property
+  );
+
+export interface GaugeWeight {
+  epoch: BN;
+  votes: BN;
+  totalVotes: BN;
+}
+
+/** @internal */
+export const GaugeWeightLayout = (property: string = "gaugeWeight") =>
+  struct<GaugeWeight>(
+    [u64("epoch"), u64("votes"), u64("totalVotes")],
+    property
+  );
@@ -0,0 +1,35 @@
+import { PublicKey } from "@solana/web3.js";
+
+export const OBSERVATION_BUFFER_SEED = "observations";
+export const WHITELIST_SEED = "whitelist";
+export const PRICE_FEED_SEED = "price_feed";
+
+const findSwapAddress = async (
+  seed: string,
+  tokenSwap: PublicKey,
+  programId: PublicKey
+): Promise<PublicKey> => {
+  const [address] = await PublicKey.findProgramAddress(
+    [tokenSwap.toBuffer(), Buffer.from(seed)],
+    programId
+  );
+  return address;
+};
+
+/// Observation buffer of the TWAP of a token-swap
+export const findObservationBufferAddress = (
+  tokenSwap: PublicKey,
+  programId: PublicKey
+) => findSwapAddress(OBSERVATION_BUFFER_SEED, tokenSwap, programId);
+
+/// Whitelist of the traders of a token-swap, passed whether or not it exists
+export const findWhitelistAddress = (
+  tokenSwap: PublicKey,
+  programId: PublicKey
+) => findSwapAddress(WHITELIST_SEED, tokenSwap, programId);
+
+/// Price feed of a token-swap, passed whether or not it exists
+export const findPriceFeedAddress = (
+  tokenSwap: PublicKey,
+  programId: PublicKey
+) => findSwapAddress(PRICE_FEED_SEED, tokenSwap, programId);
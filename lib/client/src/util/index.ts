@@ -1,4 +1,5 @@
 export * from "./layout";
 export * from "./account";
+export * from "./address";
 export * from "./send-and-confirm-transaction";
 export * from "./calculator";
@@ -1,5 +1,7 @@
 //! Module for processing admin-only instructions.
 
+use std::convert::TryInto;
+
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
@@ -8,19 +10,39 @@ use solana_program::{
     program_option::COption,
     program_pack::Pack,
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
 use spl_token::instruction::AuthorityType;
 
 use crate::{
+    curve::{CurveType, PoolState},
     error::SwapError,
-    instruction::{AdminInitializeData, AdminInstruction, CommitNewAdmin},
+    event::{Event, SwapEvent},
+    instruction::{
+        AdminInitializeData, AdminInstruction, BuybackAndBurn, CommitNewAdmin,
+        SetDeltafiPriceOracle, SetDeniedMint, SetEpochRewardBudget, SetFeeCompounding,
+        SetFeeInQuote, SetMaxPriceImpact, SetMaxTradeSize, SetMidPrice, SetMidPriceBounds,
+        SetMinTradeFee, SetOpenbookMarket, SetPokeReward, SetProposalQuorum, SetReferralReward,
+        SetReserveCaps, SetSlotThrottle, SetTransferHook, SetUserLiquidityCap, SetWhitelistOnly,
+        SetWhitelistTrader, Snapshot, SwapDirection,
+    },
+    math::{Decimal, TryMul, TrySub, BPS_DENOMINATOR},
+    openbook,
     processor::{
-        assert_rent_exempt, assert_uninitialized, authority_id, set_authority, unpack_mint,
-        unpack_token_account,
+        accumulate_rewards, accumulate_trade_fee, assert_rent_exempt, assert_uninitialized,
+        authority_id, check_deny_list, check_observation_buffer, check_pyth_price_account,
+        check_swap_config, check_whitelist, collected_trade_fee, create_program_account,
+        get_market_price_from_pyth, grow_account, quote_market_swap, set_authority,
+        split_openbook_market, swapped_balances, throttle_swap, token_burn, token_transfer,
+        unpack_mint, unpack_token_account, update_price_feed, update_twap,
     },
-    state::{ConfigInfo, SwapInfo, PROGRAM_VERSION},
-    state::{Fees, Rewards},
+    state::{
+        find_deny_list_address, find_snapshot_address, find_whitelist_address, ConfigInfo,
+        DenyList, DirectionalFees, FeeDiscount, LiquiditySnapshot, Observation, ObservationBuffer,
+        SwapInfo, SwapInfoData, Whitelist, DENY_LIST_SEED, DENY_LIST_SIZE, PROGRAM_VERSION,
+        SNAPSHOT_SEED, WHITELIST_SEED, WHITELIST_SIZE,
+    },
+    state::{Fees, HalvingSchedule, Rewards},
 };
 
 /// Process admin instruction
@@ -31,9 +53,21 @@ pub fn process_admin_instruction(
 ) -> ProgramResult {
     let instruction = AdminInstruction::unpack(input)?;
     match instruction {
-        AdminInstruction::Initialize(AdminInitializeData { fees, rewards }) => {
+        AdminInstruction::Initialize(AdminInitializeData {
+            fees,
+            rewards,
+            max_mid_price_deviation_bps,
+            min_mid_price_update_interval,
+        }) => {
             msg!("AdminInstruction : Initialization");
-            initialize(program_id, &fees, &rewards, accounts)
+            initialize(
+                program_id,
+                &fees,
+                &rewards,
+                max_mid_price_deviation_bps,
+                min_mid_price_update_interval,
+                accounts,
+            )
         }
         AdminInstruction::Pause => {
             msg!("Instruction: Pause");
@@ -59,6 +93,157 @@ pub fn process_admin_instruction(
             msg!("Instruction: SetRewardsInfo");
             set_new_rewards(program_id, &new_rewards, accounts)
         }
+        AdminInstruction::SetHalvingSchedule(halving_schedule) => {
+            msg!("Instruction: SetHalvingSchedule");
+            set_halving_schedule(program_id, halving_schedule, accounts)
+        }
+        AdminInstruction::BuybackAndBurn(BuybackAndBurn {
+            amount_in,
+            minimum_amount_out,
+        }) => {
+            msg!("Instruction: BuybackAndBurn");
+            buyback_and_burn(program_id, amount_in, minimum_amount_out, accounts)
+        }
+        AdminInstruction::SetMidPrice(SetMidPrice { mid_price }) => {
+            msg!("Instruction: SetMidPrice");
+            set_mid_price(program_id, mid_price, accounts)
+        }
+        AdminInstruction::MigrateState => {
+            msg!("Instruction: MigrateState");
+            migrate_state(program_id, accounts)
+        }
+        AdminInstruction::SetMaxPriceImpact(SetMaxPriceImpact {
+            max_price_impact_bps,
+        }) => {
+            msg!("Instruction: SetMaxPriceImpact");
+            set_max_price_impact(program_id, max_price_impact_bps, accounts)
+        }
+        AdminInstruction::SetMaxTradeSize(trade_size) => {
+            msg!("Instruction: SetMaxTradeSize");
+            set_max_trade_size(program_id, &trade_size, accounts)
+        }
+        AdminInstruction::SetSlotThrottle(throttle) => {
+            msg!("Instruction: SetSlotThrottle");
+            set_slot_throttle(program_id, &throttle, accounts)
+        }
+        AdminInstruction::SetFeeCompounding(SetFeeCompounding { is_fee_compounding }) => {
+            msg!("Instruction: SetFeeCompounding");
+            set_fee_compounding(program_id, is_fee_compounding, accounts)
+        }
+        AdminInstruction::InitializeWhitelist => {
+            msg!("Instruction: InitializeWhitelist");
+            initialize_whitelist(program_id, accounts)
+        }
+        AdminInstruction::SetWhitelistTrader(SetWhitelistTrader { trader, is_allowed }) => {
+            msg!("Instruction: SetWhitelistTrader");
+            set_whitelist_trader(program_id, trader, is_allowed, accounts)
+        }
+        AdminInstruction::SetWhitelistOnly(SetWhitelistOnly { is_whitelist_only }) => {
+            msg!("Instruction: SetWhitelistOnly");
+            set_whitelist_only(program_id, is_whitelist_only, accounts)
+        }
+        AdminInstruction::SetTransferHook(SetTransferHook {
+            transfer_hook_program,
+        }) => {
+            msg!("Instruction: SetTransferHook");
+            set_transfer_hook(program_id, transfer_hook_program, accounts)
+        }
+        AdminInstruction::SetDeltafiPriceOracle(SetDeltafiPriceOracle {
+            deltafi_price_oracle,
+        }) => {
+            msg!("Instruction: SetDeltafiPriceOracle");
+            set_deltafi_price_oracle(program_id, deltafi_price_oracle, accounts)
+        }
+        AdminInstruction::SetEpochRewardBudget(SetEpochRewardBudget {
+            epoch_reward_budget,
+        }) => {
+            msg!("Instruction: SetEpochRewardBudget");
+            set_epoch_reward_budget(program_id, epoch_reward_budget, accounts)
+        }
+        AdminInstruction::SetPokeReward(SetPokeReward {
+            poke_reward,
+            min_poke_interval,
+        }) => {
+            msg!("Instruction: SetPokeReward");
+            set_poke_reward(program_id, poke_reward, min_poke_interval, accounts)
+        }
+        AdminInstruction::SetProposalQuorum(SetProposalQuorum { proposal_quorum }) => {
+            msg!("Instruction: SetProposalQuorum");
+            set_proposal_quorum(program_id, proposal_quorum, accounts)
+        }
+        AdminInstruction::Snapshot(Snapshot {
+            snapshot_id,
+            merkle_root,
+        }) => {
+            msg!("Instruction: Snapshot");
+            snapshot(program_id, snapshot_id, merkle_root, accounts)
+        }
+        AdminInstruction::SetReserveCaps(caps) => {
+            msg!("Instruction: SetReserveCaps");
+            set_reserve_caps(program_id, &caps, accounts)
+        }
+        AdminInstruction::SetUserLiquidityCap(SetUserLiquidityCap { max_user_liquidity }) => {
+            msg!("Instruction: SetUserLiquidityCap");
+            set_user_liquidity_cap(program_id, max_user_liquidity, accounts)
+        }
+        AdminInstruction::MakeImmutable => {
+            msg!("Instruction: MakeImmutable");
+            make_immutable(program_id, accounts)
+        }
+        AdminInstruction::SetFeeDiscount(fee_discount) => {
+            msg!("Instruction: SetFeeDiscount");
+            set_fee_discount(program_id, fee_discount, accounts)
+        }
+        AdminInstruction::SetDirectionalFees(directional_fees) => {
+            msg!("Instruction: SetDirectionalFees");
+            set_directional_fees(program_id, directional_fees, accounts)
+        }
+        AdminInstruction::SetMinTradeFee(SetMinTradeFee { min_trade_fee }) => {
+            msg!("Instruction: SetMinTradeFee");
+            set_min_trade_fee(program_id, min_trade_fee, accounts)
+        }
+        AdminInstruction::SetFeeInQuote(SetFeeInQuote { is_fee_in_quote }) => {
+            msg!("Instruction: SetFeeInQuote");
+            set_fee_in_quote(program_id, is_fee_in_quote, accounts)
+        }
+        AdminInstruction::SetReferralReward(SetReferralReward {
+            referral_reward_bps,
+        }) => {
+            msg!("Instruction: SetReferralReward");
+            set_referral_reward(program_id, referral_reward_bps, accounts)
+        }
+        AdminInstruction::InitializeDenyList => {
+            msg!("Instruction: InitializeDenyList");
+            initialize_deny_list(program_id, accounts)
+        }
+        AdminInstruction::SetDeniedMint(SetDeniedMint { mint, is_denied }) => {
+            msg!("Instruction: SetDeniedMint");
+            set_denied_mint(program_id, mint, is_denied, accounts)
+        }
+        AdminInstruction::Recalibrate => {
+            msg!("Instruction: Recalibrate");
+            recalibrate(program_id, accounts)
+        }
+        AdminInstruction::SetOpenbookMarket(SetOpenbookMarket { openbook_market }) => {
+            msg!("Instruction: SetOpenbookMarket");
+            set_openbook_market(program_id, openbook_market, accounts)
+        }
+        AdminInstruction::SetCurveType(curve_type) => {
+            msg!("Instruction: SetCurveType");
+            set_curve_type(program_id, curve_type, accounts)
+        }
+        AdminInstruction::SetMidPriceBounds(SetMidPriceBounds {
+            max_mid_price_deviation_bps,
+            min_mid_price_update_interval,
+        }) => {
+            msg!("Instruction: SetMidPriceBounds");
+            set_mid_price_bounds(
+                program_id,
+                max_mid_price_deviation_bps,
+                min_mid_price_update_interval,
+                accounts,
+            )
+        }
     }
 }
 
@@ -74,12 +259,28 @@ fn is_admin(expected_admin_key: &Pubkey, admin_account_info: &AccountInfo) -> Pr
     Ok(())
 }
 
+/// Unpack a token-swap of the config whose parameters the admin can still
+/// change
+fn unpack_mutable_swap(
+    config_info: &AccountInfo,
+    swap_info: &AccountInfo,
+) -> Result<SwapInfo, ProgramError> {
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    check_swap_config(&token_swap.config_key, config_info)?;
+    if token_swap.is_immutable {
+        return Err(SwapError::ImmutablePool.into());
+    }
+    Ok(token_swap)
+}
+
 /// Initialize configuration
 #[inline(never)]
 fn initialize(
     program_id: &Pubkey,
     fees: &Fees,
     rewards: &Rewards,
+    max_mid_price_deviation_bps: u64,
+    min_mid_price_update_interval: i64,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -122,6 +323,8 @@ fn initialize(
     config.deltafi_mint = *deltafi_mint_info.key;
     config.fees = Fees::new(fees);
     config.rewards = Rewards::new(rewards);
+    config.max_mid_price_deviation_bps = max_mid_price_deviation_bps;
+    config.min_mid_price_update_interval = min_mid_price_update_interval;
     ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
     Ok(())
 }
@@ -141,7 +344,7 @@ fn pause(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let config = ConfigInfo::unpack(&config_info.data.borrow())?;
     is_admin(&config.admin_key, admin_info)?;
 
-    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
     token_swap.is_paused = true;
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
     Ok(())
@@ -162,7 +365,7 @@ fn unpause(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let config = ConfigInfo::unpack(&config_info.data.borrow())?;
     is_admin(&config.admin_key, admin_info)?;
 
-    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
     token_swap.is_paused = false;
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
     Ok(())
@@ -185,7 +388,7 @@ fn set_fee_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
 
     let config = ConfigInfo::unpack(&config_info.data.borrow())?;
     is_admin(&config.admin_key, admin_info)?;
-    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
     if *authority_info.key != authority_id(program_id, swap_info.key, token_swap.nonce)? {
         return Err(SwapError::InvalidProgramAddress.into());
     }
@@ -239,7 +442,7 @@ fn commit_new_admin(
     Ok(())
 }
 
-/// Set new fees
+/// Set new fees of the pool
 #[inline(never)]
 fn set_new_fees(program_id: &Pubkey, new_fees: &Fees, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -247,6 +450,66 @@ fn set_new_fees(program_id: &Pubkey, new_fees: &Fees, accounts: &[AccountInfo])
     let swap_info = next_account_info(account_info_iter)?;
     let admin_info = next_account_info(account_info_iter)?;
 
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if !new_fees.is_valid() {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    // the min trade fee is only changed by `SetMinTradeFee`
+    token_swap.fees = Fees {
+        min_trade_fee: token_swap.fees.min_trade_fee,
+        ..Fees::new(new_fees)
+    };
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the trade fee rates of the pool per swap direction
+#[inline(never)]
+fn set_directional_fees(
+    program_id: &Pubkey,
+    directional_fees: DirectionalFees,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if !directional_fees.is_valid() {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.directional_fees = directional_fees;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set new min trade fee of the pool
+#[inline(never)]
+fn set_min_trade_fee(
+    program_id: &Pubkey,
+    min_trade_fee: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
     if config_info.owner != program_id || swap_info.owner != program_id {
         return Err(SwapError::InvalidAccountOwner.into());
     }
@@ -254,13 +517,13 @@ fn set_new_fees(program_id: &Pubkey, new_fees: &Fees, accounts: &[AccountInfo])
     let config = ConfigInfo::unpack(&config_info.data.borrow())?;
     is_admin(&config.admin_key, admin_info)?;
 
-    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
-    token_swap.fees = Fees::new(new_fees);
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.fees.min_trade_fee = min_trade_fee;
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
     Ok(())
 }
 
-/// Set new rewards
+/// Set new rewards of the pool
 #[inline(never)]
 fn set_new_rewards(
     program_id: &Pubkey,
@@ -275,12 +538,1238 @@ fn set_new_rewards(
     if config_info.owner != program_id || swap_info.owner != program_id {
         return Err(SwapError::InvalidAccountOwner.into());
     }
+    if !new_rewards.is_valid() {
+        return Err(SwapError::InvalidInput.into());
+    }
 
     let config = ConfigInfo::unpack(&config_info.data.borrow())?;
     is_admin(&config.admin_key, admin_info)?;
 
-    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
     token_swap.rewards = Rewards::new(new_rewards);
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
     Ok(())
 }
+
+/// Set new halving schedule of the rewards
+#[inline(never)]
+fn set_halving_schedule(
+    program_id: &Pubkey,
+    halving_schedule: HalvingSchedule,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.halving_schedule = halving_schedule;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Sell admin fees for DELFI through a DELFI pool and burn the DELFI bought
+#[inline(never)]
+fn buyback_and_burn(
+    program_id: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let swap_authority_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let admin_fee_source_info = next_account_info(account_info_iter)?;
+    let deltafi_swap_info = next_account_info(account_info_iter)?;
+    let deltafi_swap_authority_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let admin_fee_destination_info = next_account_info(account_info_iter)?;
+    let deltafi_mint_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id
+        || swap_info.owner != program_id
+        || deltafi_swap_info.owner != program_id
+    {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    if config.deltafi_mint != *deltafi_mint_info.key {
+        return Err(SwapError::IncorrectMint.into());
+    }
+
+    // the admin fees sold belong to the token-swap, owned by the admin or its authority
+    let swap_nonce = {
+        let swap_data = swap_info.data.borrow();
+        let token_swap = SwapInfoData::load(&swap_data)?;
+        check_swap_config(&token_swap.config_key, config_info)?;
+        if *admin_fee_source_info.key != token_swap.admin_fee_key_a
+            && *admin_fee_source_info.key != token_swap.admin_fee_key_b
+        {
+            return Err(SwapError::InvalidAdmin.into());
+        }
+        token_swap.nonce
+    };
+    if *swap_authority_info.key != authority_id(program_id, swap_info.key, swap_nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    let token_program_id = *token_program_info.key;
+    let admin_fee_source = unpack_token_account(admin_fee_source_info, &token_program_id)?;
+    let admin_fee_authority_info = if admin_fee_source.owner == *swap_authority_info.key {
+        swap_authority_info
+    } else if admin_fee_source.owner == *admin_info.key {
+        admin_info
+    } else {
+        return Err(SwapError::InvalidOwner.into());
+    };
+
+    check_observation_buffer(program_id, deltafi_swap_info, observation_buffer_info)?;
+    let mut deltafi_swap_data = deltafi_swap_info.data.borrow_mut();
+    let deltafi_swap = SwapInfoData::load_mut(&mut deltafi_swap_data)?;
+    check_swap_config(&deltafi_swap.config_key, config_info)?;
+    if deltafi_swap.is_paused()? {
+        return Err(SwapError::IsPaused.into());
+    }
+    let (openbook_infos, _) =
+        split_openbook_market(&deltafi_swap.openbook_market, account_info_iter.as_slice())?;
+    // the admin fee token is sold for the DELFI side of the pool
+    let swap_direction = if deltafi_swap.token_b_mint == config.deltafi_mint
+        && deltafi_swap.token_a_mint == admin_fee_source.mint
+    {
+        SwapDirection::SellBase
+    } else if deltafi_swap.token_a_mint == config.deltafi_mint
+        && deltafi_swap.token_b_mint == admin_fee_source.mint
+    {
+        SwapDirection::SellQuote
+    } else {
+        return Err(SwapError::IncorrectMint.into());
+    };
+    if *token_a_info.key != deltafi_swap.token_a || *token_b_info.key != deltafi_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    let admin_fee_key = match swap_direction {
+        _ if deltafi_swap.is_fee_in_quote()? => deltafi_swap.admin_fee_key_b,
+        SwapDirection::SellBase => deltafi_swap.admin_fee_key_b,
+        SwapDirection::SellQuote => deltafi_swap.admin_fee_key_a,
+    };
+    if *admin_fee_destination_info.key != admin_fee_key {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+    if *pyth_a_price_info.key != deltafi_swap.pyth_a
+        || *pyth_b_price_info.key != deltafi_swap.pyth_b
+    {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+    let deltafi_swap_nonce = deltafi_swap.nonce;
+    if *deltafi_swap_authority_info.key
+        != authority_id(program_id, deltafi_swap_info.key, deltafi_swap_nonce)?
+    {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+
+    let token_a = unpack_token_account(token_a_info, &token_program_id)?;
+    let token_b = unpack_token_account(token_b_info, &token_program_id)?;
+    let reserve_in = match swap_direction {
+        SwapDirection::SellBase => token_a.amount,
+        SwapDirection::SellQuote => token_b.amount,
+    };
+    // priced with the same PMM quote as a swap, under the same caps
+    let (state, twap, quote) = quote_market_swap(
+        deltafi_swap,
+        observation_buffer_info,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        openbook_infos,
+        clock,
+        reserve_in,
+        amount_in,
+        swap_direction,
+    )?;
+    if quote.amount_out < minimum_amount_out {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    let slot_volume = throttle_swap(deltafi_swap, clock, &quote, amount_in, swap_direction)?;
+    let (base_balance, quote_balance) = swapped_balances(
+        token_a.amount,
+        token_b.amount,
+        amount_in,
+        quote.amount_out,
+        swap_direction,
+    )?;
+
+    let (fee_direction, trade_fee, admin_fee) =
+        collected_trade_fee(deltafi_swap, &state, swap_direction, &quote)?;
+
+    accumulate_rewards(deltafi_swap, clock)?;
+    let mut new_pool_state = PoolState::new(PoolState {
+        base_reserve: Decimal::from(base_balance),
+        quote_reserve: Decimal::from(quote_balance),
+        multiplier: quote.multiplier,
+        ..state
+    })?;
+    if deltafi_swap.is_fee_compounding()? {
+        new_pool_state = new_pool_state.compound_trade_fee(fee_direction, trade_fee, admin_fee)?;
+    }
+    accumulate_trade_fee(deltafi_swap, &state, fee_direction, trade_fee, admin_fee)?;
+    deltafi_swap.set_pool_state(&new_pool_state);
+    deltafi_swap.set_slot_volume(&slot_volume);
+    update_twap(deltafi_swap, observation_buffer_info, &twap, clock)?;
+    drop(deltafi_swap_data);
+
+    let (swap_in_info, swap_out_info) = match swap_direction {
+        SwapDirection::SellBase => (token_a_info, token_b_info),
+        SwapDirection::SellQuote => (token_b_info, token_a_info),
+    };
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        admin_fee_source_info.clone(),
+        swap_in_info.clone(),
+        admin_fee_authority_info.clone(),
+        swap_nonce,
+        amount_in,
+    )?;
+    // the DELFI bought never leaves the pool, it is burned from the reserve
+    token_burn(
+        deltafi_swap_info.key,
+        token_program_info.clone(),
+        swap_out_info.clone(),
+        deltafi_mint_info.clone(),
+        deltafi_swap_authority_info.clone(),
+        deltafi_swap_nonce,
+        quote.amount_out,
+    )?;
+    let admin_fee_source_info = if fee_direction == swap_direction {
+        swap_out_info
+    } else {
+        swap_in_info
+    };
+    token_transfer(
+        deltafi_swap_info.key,
+        token_program_info.clone(),
+        admin_fee_source_info.clone(),
+        admin_fee_destination_info.clone(),
+        deltafi_swap_authority_info.clone(),
+        deltafi_swap_nonce,
+        admin_fee,
+    )?;
+
+    Event::Swap(SwapEvent {
+        swap: *deltafi_swap_info.key,
+        swap_direction,
+        amount_in,
+        amount_out: quote.amount_out,
+        trade_fee,
+        admin_fee,
+        base_reserve: base_balance,
+        quote_reserve: quote_balance,
+        market_price: state.market_price.to_scaled_val()?,
+    })
+    .emit();
+
+    update_price_feed(
+        program_id,
+        deltafi_swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
+    Ok(())
+}
+
+/// Set new max price impact of the swaps
+#[inline(never)]
+fn set_max_price_impact(
+    program_id: &Pubkey,
+    max_price_impact_bps: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    if max_price_impact_bps > BPS_DENOMINATOR {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.max_price_impact_bps = max_price_impact_bps;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set new max trade size of the swaps
+#[inline(never)]
+fn set_max_trade_size(
+    program_id: &Pubkey,
+    trade_size: &SetMaxTradeSize,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    if trade_size.max_amount_in_reserve_bps > BPS_DENOMINATOR {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.max_amount_in = trade_size.max_amount_in;
+    token_swap.max_amount_in_reserve_bps = trade_size.max_amount_in_reserve_bps;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set new per slot throttle of the swaps
+#[inline(never)]
+fn set_slot_throttle(
+    program_id: &Pubkey,
+    throttle: &SetSlotThrottle,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.max_swaps_per_slot = throttle.max_swaps_per_slot;
+    token_swap.max_volume_per_slot = throttle.max_volume_per_slot;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Turn the fee compounding of the pool on or off
+#[inline(never)]
+fn set_fee_compounding(
+    program_id: &Pubkey,
+    is_fee_compounding: bool,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.is_fee_compounding = is_fee_compounding;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Turn the collection of the trade fees in the quote token on or off
+#[inline(never)]
+fn set_fee_in_quote(
+    program_id: &Pubkey,
+    is_fee_in_quote: bool,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.is_fee_in_quote = is_fee_in_quote;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Create the empty trader whitelist of the pool
+#[inline(never)]
+fn initialize_whitelist(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let whitelist_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    if !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    check_swap_config(
+        &SwapInfoData::load(&swap_info.data.borrow())?.config_key,
+        config_info,
+    )?;
+
+    let (whitelist_key, bump_seed) = find_whitelist_address(program_id, swap_info.key);
+    if *whitelist_info.key != whitelist_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if whitelist_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    create_program_account(
+        program_id,
+        whitelist_info,
+        payer_info,
+        system_program_info,
+        rent,
+        WHITELIST_SIZE,
+        &[swap_info.key.as_ref(), WHITELIST_SEED, &[bump_seed]],
+    )?;
+    Whitelist::init(
+        &mut whitelist_info.data.borrow_mut(),
+        *swap_info.key,
+        bump_seed,
+    )?;
+    Ok(())
+}
+
+/// Add or remove a trader of the whitelist of the pool
+#[inline(never)]
+fn set_whitelist_trader(
+    program_id: &Pubkey,
+    trader: Pubkey,
+    is_allowed: bool,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let whitelist_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    check_whitelist(program_id, swap_info, whitelist_info)?;
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    unpack_mutable_swap(config_info, swap_info)?;
+
+    let mut whitelist_data = whitelist_info.data.borrow_mut();
+    let whitelist = Whitelist::load_mut(&mut whitelist_data)?;
+    if is_allowed {
+        whitelist.add(trader)?;
+    } else {
+        whitelist.remove(&trader);
+    }
+    Ok(())
+}
+
+/// Restrict the swaps of the pool to the traders on its whitelist
+#[inline(never)]
+fn set_whitelist_only(
+    program_id: &Pubkey,
+    is_whitelist_only: bool,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let whitelist_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    // swaps of a whitelist only pool read the whitelist, so it must exist
+    if is_whitelist_only {
+        check_whitelist(program_id, swap_info, whitelist_info)?;
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.is_whitelist_only = is_whitelist_only;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the program approving the swaps and deposits of the pool
+#[inline(never)]
+fn set_transfer_hook(
+    program_id: &Pubkey,
+    transfer_hook_program: Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    // the hook is called with the swap tags, which would re-enter this program
+    if transfer_hook_program == *program_id {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.transfer_hook_program = transfer_hook_program;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the OpenBook market pricing a pool when its oracles are stale and its
+/// twap is unavailable
+#[inline(never)]
+fn set_openbook_market(
+    program_id: &Pubkey,
+    openbook_market: Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    // the market must trade the tokens of the pool, base for quote
+    if openbook_market != Pubkey::default() {
+        let market_info = next_account_info(account_info_iter)?;
+        if *market_info.key != openbook_market {
+            return Err(SwapError::IncorrectOracleAccount.into());
+        }
+        if *market_info.owner != openbook::id() {
+            return Err(SwapError::InvalidAccountOwner.into());
+        }
+        let market = openbook::load_market(&market_info.data.borrow())?;
+        if market.own_address != openbook_market {
+            return Err(SwapError::IncorrectOracleAccount.into());
+        }
+        if market.coin_mint != token_swap.token_a_mint || market.pc_mint != token_swap.token_b_mint
+        {
+            return Err(SwapError::IncorrectMint.into());
+        }
+    }
+    token_swap.openbook_market = openbook_market;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the curve the swaps of the pool are quoted on
+#[inline(never)]
+fn set_curve_type(
+    program_id: &Pubkey,
+    curve_type: CurveType,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.curve_type = curve_type;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the price oracle the liquidity rewards are valued at
+#[inline(never)]
+fn set_deltafi_price_oracle(
+    program_id: &Pubkey,
+    deltafi_price_oracle: Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.deltafi_price_oracle = deltafi_price_oracle;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the rewards mintable per epoch, the rewards already minted in the
+/// current epoch still count against the new budget
+#[inline(never)]
+fn set_epoch_reward_budget(
+    program_id: &Pubkey,
+    epoch_reward_budget: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.epoch_reward_budget = epoch_reward_budget;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the DELFI minted to the keeper of a `Poke` and the min seconds since
+/// the last update of a pool for it to be rewarded
+#[inline(never)]
+fn set_poke_reward(
+    program_id: &Pubkey,
+    poke_reward: u64,
+    min_poke_interval: i64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if min_poke_interval < 0 {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.poke_reward = poke_reward;
+    config.min_poke_interval = min_poke_interval;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the min votes for a proposal to pass, zero disabling the governance.
+/// Proposals are executed against the quorum at the time of their execution
+#[inline(never)]
+fn set_proposal_quorum(
+    program_id: &Pubkey,
+    proposal_quorum: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.proposal_quorum = proposal_quorum;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Record the liquidity of a token-swap at the current block in a new
+/// snapshot, with the merkle root of the provider balances computed off-chain
+#[inline(never)]
+fn snapshot(
+    program_id: &Pubkey,
+    snapshot_id: u64,
+    merkle_root: [u8; 32],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let snapshot_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    if !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    check_swap_config(&token_swap.config_key, config_info)?;
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    let pool_mint = unpack_mint(pool_mint_info, &spl_token::id())?;
+
+    let (snapshot_key, bump_seed) = find_snapshot_address(program_id, swap_info.key, snapshot_id);
+    if *snapshot_info.key != snapshot_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if snapshot_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    create_program_account(
+        program_id,
+        snapshot_info,
+        payer_info,
+        system_program_info,
+        rent,
+        LiquiditySnapshot::LEN,
+        &[
+            swap_info.key.as_ref(),
+            &snapshot_id.to_le_bytes(),
+            SNAPSHOT_SEED,
+            &[bump_seed],
+        ],
+    )?;
+    assert_uninitialized::<LiquiditySnapshot>(snapshot_info)?;
+
+    let clock = Clock::get()?;
+    LiquiditySnapshot::pack(
+        LiquiditySnapshot {
+            is_initialized: true,
+            swap: *swap_info.key,
+            snapshot_id,
+            slot: clock.slot,
+            snapshot_ts: clock.unix_timestamp,
+            pool_mint_supply: pool_mint.supply,
+            base_reserve: token_swap.pool_state.base_reserve,
+            quote_reserve: token_swap.pool_state.quote_reserve,
+            merkle_root,
+        },
+        &mut snapshot_info.data.borrow_mut(),
+    )?;
+    Ok(())
+}
+
+/// Set new reserve caps of the deposits
+#[inline(never)]
+fn set_reserve_caps(
+    program_id: &Pubkey,
+    caps: &SetReserveCaps,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.max_base_reserve = caps.max_base_reserve;
+    token_swap.max_quote_reserve = caps.max_quote_reserve;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set new liquidity cap of the user positions
+#[inline(never)]
+fn set_user_liquidity_cap(
+    program_id: &Pubkey,
+    max_user_liquidity: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.max_user_liquidity = max_user_liquidity;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Make the parameters of the pool final
+#[inline(never)]
+fn make_immutable(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    token_swap.is_immutable = true;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the slice of the liquidity rewards claimed minted to the referrers,
+/// shared by every pool
+#[inline(never)]
+fn set_referral_reward(
+    program_id: &Pubkey,
+    referral_reward_bps: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if referral_reward_bps > BPS_DENOMINATOR {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.referral_reward_bps = referral_reward_bps;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Create the empty deny-list of the mints of the config
+#[inline(never)]
+fn initialize_deny_list(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let deny_list_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    if !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+
+    let (deny_list_key, bump_seed) = find_deny_list_address(program_id, config_info.key);
+    if *deny_list_info.key != deny_list_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if deny_list_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    create_program_account(
+        program_id,
+        deny_list_info,
+        payer_info,
+        system_program_info,
+        rent,
+        DENY_LIST_SIZE,
+        &[config_info.key.as_ref(), DENY_LIST_SEED, &[bump_seed]],
+    )?;
+    DenyList::init(
+        &mut deny_list_info.data.borrow_mut(),
+        *config_info.key,
+        bump_seed,
+    )?;
+    Ok(())
+}
+
+/// Add or remove a mint of the deny-list of the config
+#[inline(never)]
+fn set_denied_mint(
+    program_id: &Pubkey,
+    mint: Pubkey,
+    is_denied: bool,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let deny_list_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || deny_list_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    check_deny_list(program_id, config_info, deny_list_info)?;
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut deny_list_data = deny_list_info.data.borrow_mut();
+    let deny_list = DenyList::load_mut(&mut deny_list_data)?;
+    if is_denied {
+        deny_list.add(mint)?;
+    } else {
+        deny_list.remove(&mint);
+    }
+    Ok(())
+}
+
+/// Reset the pool state to the balances of the swap accounts at the market price
+#[inline(never)]
+fn recalibrate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    if *token_a_info.key != token_swap.token_a || *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *pyth_a_price_info.key != token_swap.pyth_a || *pyth_b_price_info.key != token_swap.pyth_b {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+
+    let token_a = unpack_token_account(token_a_info, token_program_info.key)?;
+    let token_b = unpack_token_account(token_b_info, token_program_info.key)?;
+    // the stored market price stands in for stale oracles
+    let market_price = get_market_price_from_pyth(pyth_a_price_info, pyth_b_price_info, clock)
+        .unwrap_or(token_swap.pool_state.market_price);
+    token_swap.pool_state =
+        token_swap
+            .pool_state
+            .recalibrate(token_a.amount, token_b.amount, market_price)?;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    update_price_feed(
+        program_id,
+        swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
+    Ok(())
+}
+
+/// Set the trade fee discount tiers of the DELFI holders, shared by every pool
+#[inline(never)]
+fn set_fee_discount(
+    program_id: &Pubkey,
+    fee_discount: FeeDiscount,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if fee_discount
+        .tiers
+        .iter()
+        .any(|tier| tier.discount_bps > BPS_DENOMINATOR)
+    {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.fee_discount = fee_discount;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set new mid price for the pool without live oracle
+#[inline(never)]
+fn set_mid_price(program_id: &Pubkey, mid_price: u128, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    check_observation_buffer(program_id, swap_info, observation_buffer_info)?;
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = unpack_mutable_swap(config_info, swap_info)?;
+    if *pyth_a_price_info.key != token_swap.pyth_a || *pyth_b_price_info.key != token_swap.pyth_b {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+    if get_market_price_from_pyth(pyth_a_price_info, pyth_b_price_info, clock).is_ok() {
+        return Err(SwapError::OracleActive.into());
+    }
+
+    let next_update_ts = token_swap
+        .last_mid_price_update_ts
+        .checked_add(config.min_mid_price_update_interval)
+        .ok_or(SwapError::CalculationFailure)?;
+    if clock.unix_timestamp < next_update_ts {
+        return Err(SwapError::MidPriceUpdateTooFrequent.into());
+    }
+
+    let market_price = token_swap.pool_state.market_price;
+    let new_market_price = Decimal::from_scaled_val(mid_price);
+    if new_market_price.is_zero() {
+        return Err(SwapError::InvalidInput.into());
+    }
+    let deviation = if new_market_price > market_price {
+        new_market_price.try_sub(market_price)?
+    } else {
+        market_price.try_sub(new_market_price)?
+    };
+    if deviation.try_mul(BPS_DENOMINATOR)?
+        > market_price.try_mul(config.max_mid_price_deviation_bps)?
+    {
+        return Err(SwapError::MidPriceDeviationExceeded.into());
+    }
+
+    // close the twap period at the old mid price before switching to the new one
+    let pool_mid_price = token_swap.pool_state.get_mid_price()?;
+    let block_timestamp_last: u64 = clock
+        .unix_timestamp
+        .try_into()
+        .map_err(|_| SwapError::CalculationFailure)?;
+    let time_elapsed = block_timestamp_last
+        .checked_sub(token_swap.block_timestamp_last)
+        .ok_or(SwapError::CalculationFailure)?;
+    if token_swap.is_open_twap
+        && time_elapsed > 0
+        && !token_swap.pool_state.base_reserve.is_zero()
+        && !token_swap.pool_state.quote_reserve.is_zero()
+    {
+        let twap = token_swap.twap().accumulate(pool_mid_price, time_elapsed)?;
+        token_swap.base_price_cumulative_last = twap.base_price_cumulative_last;
+        token_swap.cumulative_ticks = twap.cumulative_ticks;
+    }
+    token_swap.block_timestamp_last = block_timestamp_last;
+    if token_swap.is_open_twap {
+        ObservationBuffer::load_mut(&mut observation_buffer_info.data.borrow_mut())?.record(
+            Observation::new(block_timestamp_last, token_swap.base_price_cumulative_last),
+        );
+    }
+
+    token_swap.pool_state = PoolState::new(PoolState {
+        market_price: new_market_price,
+        ..token_swap.pool_state
+    })?;
+    token_swap.last_mid_price_update_ts = clock.unix_timestamp;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    update_price_feed(
+        program_id,
+        swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
+    Ok(())
+}
+
+/// Set the max change and the min interval of the admin mid price updates.
+/// The configs migrated from version 1 have zero bounds, freezing the mid
+/// price until they are set
+#[inline(never)]
+fn set_mid_price_bounds(
+    program_id: &Pubkey,
+    max_mid_price_deviation_bps: u64,
+    min_mid_price_update_interval: i64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if max_mid_price_deviation_bps > BPS_DENOMINATOR || min_mid_price_update_interval < 0 {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.max_mid_price_deviation_bps = max_mid_price_deviation_bps;
+    config.min_mid_price_update_interval = min_mid_price_update_interval;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Rewrite outdated config and token-swap accounts into the current layout
+#[inline(never)]
+fn migrate_state(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    // admin key keeps its offset in every config layout
+    let config = ConfigInfo::unpack_versioned(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    if config.version < PROGRAM_VERSION {
+        grow_account(
+            config_info,
+            payer_info,
+            system_program_info,
+            rent,
+            ConfigInfo::LEN,
+        )?;
+        ConfigInfo::pack(
+            ConfigInfo {
+                version: PROGRAM_VERSION,
+                ..config
+            },
+            &mut config_info.data.borrow_mut(),
+        )?;
+    }
+
+    if let Ok(swap_info) = next_account_info(account_info_iter) {
+        let pyth_a_price_info = next_account_info(account_info_iter)?;
+        let pyth_b_price_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(SwapError::InvalidAccountOwner.into());
+        }
+
+        let token_swap = SwapInfo::unpack_versioned(&swap_info.data.borrow())?;
+        if token_swap.version < PROGRAM_VERSION {
+            check_pyth_price_account(pyth_a_price_info)?;
+            check_pyth_price_account(pyth_b_price_info)?;
+            grow_account(
+                swap_info,
+                payer_info,
+                system_program_info,
+                rent,
+                SwapInfo::LEN,
+            )?;
+            // version 1 pools did not store their oracles nor their config
+            SwapInfo::pack(
+                SwapInfo {
+                    pyth_a: *pyth_a_price_info.key,
+                    pyth_b: *pyth_b_price_info.key,
+                    config_key: *config_info.key,
+                    version: PROGRAM_VERSION,
+                    ..token_swap
+                },
+                &mut swap_info.data.borrow_mut(),
+            )?;
+        }
+    }
+
+    Ok(())
+}
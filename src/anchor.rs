@@ -0,0 +1,353 @@
+//! Anchor cross-program invocations of the swap program
+//!
+//! Anchor programs enable the `anchor-cpi` feature, take
+//! `Program<'info, DeltafiSwap>` in their accounts, and call the cpi functions
+//! with a `CpiContext` of the account structs below. Remaining accounts of the
+//! context are the optional trailing accounts of the instruction, see
+//! [interface](../interface/index.html).
+
+// the client modules generated by `#[derive(Accounts)]` are undocumented
+#![allow(missing_docs)]
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    instruction::{DepositData, SwapData, WithdrawData, WithdrawExactData},
+    interface,
+};
+
+/// Swap program, for `Program<'info, DeltafiSwap>` accounts
+#[derive(Clone)]
+pub struct DeltafiSwap;
+
+impl anchor_lang::Id for DeltafiSwap {
+    fn id() -> Pubkey {
+        crate::id()
+    }
+}
+
+/// Accounts of a `Swap` instruction
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    /// CHECK: config info, checked by the swap program
+    #[account(mut)]
+    pub config: AccountInfo<'info>,
+    /// CHECK: token-swap, checked by the swap program
+    #[account(mut)]
+    pub swap: AccountInfo<'info>,
+    /// CHECK: market authority, checked by the swap program
+    pub market_authority: AccountInfo<'info>,
+    /// CHECK: swap authority, checked by the swap program
+    pub swap_authority: AccountInfo<'info>,
+    /// CHECK: user transfer authority, signer
+    pub user_transfer_authority: AccountInfo<'info>,
+    /// CHECK: user token account of token a
+    #[account(mut)]
+    pub source: AccountInfo<'info>,
+    /// CHECK: pool token account of token a, checked by the swap program
+    #[account(mut)]
+    pub swap_source: AccountInfo<'info>,
+    /// CHECK: pool token account of token b, checked by the swap program
+    #[account(mut)]
+    pub swap_destination: AccountInfo<'info>,
+    /// CHECK: user token account of token b
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+    /// CHECK: user deltafi token account receiving the reward
+    #[account(mut)]
+    pub reward_token: AccountInfo<'info>,
+    /// CHECK: deltafi mint, checked by the swap program
+    #[account(mut)]
+    pub reward_mint: AccountInfo<'info>,
+    /// CHECK: admin fee account of the token bought, checked by the swap program
+    #[account(mut)]
+    pub admin_fee_destination: AccountInfo<'info>,
+    /// CHECK: pyth price account of token a, checked by the swap program
+    pub pyth_a: AccountInfo<'info>,
+    /// CHECK: pyth price account of token b, checked by the swap program
+    pub pyth_b: AccountInfo<'info>,
+    /// CHECK: clock sysvar
+    pub clock: AccountInfo<'info>,
+    /// CHECK: token program
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: observation buffer of the token-swap, checked by the swap program
+    #[account(mut)]
+    pub observation_buffer: AccountInfo<'info>,
+    /// CHECK: whitelist of the token-swap, checked by the swap program
+    pub whitelist: AccountInfo<'info>,
+    /// CHECK: price feed of the token-swap, checked by the swap program
+    #[account(mut)]
+    pub price_feed: AccountInfo<'info>,
+    /// CHECK: pool mint, checked by the swap program
+    pub pool_mint: AccountInfo<'info>,
+}
+
+/// Accounts of a `Deposit` or `DepositImbalanced` instruction, the admin fee
+/// accounts are only used by the latter
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    /// CHECK: token-swap, checked by the swap program
+    #[account(mut)]
+    pub swap: AccountInfo<'info>,
+    /// CHECK: swap authority, checked by the swap program
+    pub swap_authority: AccountInfo<'info>,
+    /// CHECK: user transfer authority, signer
+    pub user_transfer_authority: AccountInfo<'info>,
+    /// CHECK: user token account of token a
+    #[account(mut)]
+    pub source_a: AccountInfo<'info>,
+    /// CHECK: user token account of token b
+    #[account(mut)]
+    pub source_b: AccountInfo<'info>,
+    /// CHECK: pool token account of token a, checked by the swap program
+    #[account(mut)]
+    pub swap_token_a: AccountInfo<'info>,
+    /// CHECK: pool token account of token b, checked by the swap program
+    #[account(mut)]
+    pub swap_token_b: AccountInfo<'info>,
+    /// CHECK: pool mint, checked by the swap program
+    #[account(mut)]
+    pub pool_mint: AccountInfo<'info>,
+    /// CHECK: user pool token account
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+    /// CHECK: admin fee account of token a, checked by the swap program
+    #[account(mut)]
+    pub admin_fee_a: AccountInfo<'info>,
+    /// CHECK: admin fee account of token b, checked by the swap program
+    #[account(mut)]
+    pub admin_fee_b: AccountInfo<'info>,
+    /// CHECK: liquidity provider, checked by the swap program
+    #[account(mut)]
+    pub liquidity_provider: AccountInfo<'info>,
+    /// CHECK: liquidity provider owner or delegate, signer
+    pub liquidity_owner: AccountInfo<'info>,
+    /// CHECK: pyth price account of token a, checked by the swap program
+    pub pyth_a: AccountInfo<'info>,
+    /// CHECK: pyth price account of token b, checked by the swap program
+    pub pyth_b: AccountInfo<'info>,
+    /// CHECK: clock sysvar
+    pub clock: AccountInfo<'info>,
+    /// CHECK: token program
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: observation buffer of the token-swap, checked by the swap program
+    #[account(mut)]
+    pub observation_buffer: AccountInfo<'info>,
+    /// CHECK: price feed of the token-swap, checked by the swap program
+    #[account(mut)]
+    pub price_feed: AccountInfo<'info>,
+}
+
+/// Accounts of a `Withdraw` or `WithdrawExact` instruction
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: token-swap, checked by the swap program
+    #[account(mut)]
+    pub swap: AccountInfo<'info>,
+    /// CHECK: swap authority, checked by the swap program
+    pub swap_authority: AccountInfo<'info>,
+    /// CHECK: user transfer authority, signer
+    pub user_transfer_authority: AccountInfo<'info>,
+    /// CHECK: pool mint, checked by the swap program
+    #[account(mut)]
+    pub pool_mint: AccountInfo<'info>,
+    /// CHECK: user pool token account
+    #[account(mut)]
+    pub source: AccountInfo<'info>,
+    /// CHECK: pool token account of token a, checked by the swap program
+    #[account(mut)]
+    pub swap_token_a: AccountInfo<'info>,
+    /// CHECK: pool token account of token b, checked by the swap program
+    #[account(mut)]
+    pub swap_token_b: AccountInfo<'info>,
+    /// CHECK: user token account of token a
+    #[account(mut)]
+    pub destination_a: AccountInfo<'info>,
+    /// CHECK: user token account of token b
+    #[account(mut)]
+    pub destination_b: AccountInfo<'info>,
+    /// CHECK: admin fee account of token a, checked by the swap program
+    #[account(mut)]
+    pub admin_fee_a: AccountInfo<'info>,
+    /// CHECK: admin fee account of token b, checked by the swap program
+    #[account(mut)]
+    pub admin_fee_b: AccountInfo<'info>,
+    /// CHECK: liquidity provider, checked by the swap program
+    #[account(mut)]
+    pub liquidity_provider: AccountInfo<'info>,
+    /// CHECK: liquidity provider owner or delegate, signer
+    pub liquidity_owner: AccountInfo<'info>,
+    /// CHECK: pyth price account of token a, checked by the swap program
+    pub pyth_a: AccountInfo<'info>,
+    /// CHECK: pyth price account of token b, checked by the swap program
+    pub pyth_b: AccountInfo<'info>,
+    /// CHECK: clock sysvar
+    pub clock: AccountInfo<'info>,
+    /// CHECK: token program
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: observation buffer of the token-swap, checked by the swap program
+    #[account(mut)]
+    pub observation_buffer: AccountInfo<'info>,
+    /// CHECK: price feed of the token-swap, checked by the swap program
+    #[account(mut)]
+    pub price_feed: AccountInfo<'info>,
+}
+
+impl<'info> From<Swap<'info>> for interface::Swap<'info> {
+    fn from(accounts: Swap<'info>) -> Self {
+        Self {
+            config: accounts.config,
+            swap: accounts.swap,
+            market_authority: accounts.market_authority,
+            swap_authority: accounts.swap_authority,
+            user_transfer_authority: accounts.user_transfer_authority,
+            source: accounts.source,
+            swap_source: accounts.swap_source,
+            swap_destination: accounts.swap_destination,
+            destination: accounts.destination,
+            reward_token: accounts.reward_token,
+            reward_mint: accounts.reward_mint,
+            admin_fee_destination: accounts.admin_fee_destination,
+            pyth_a: accounts.pyth_a,
+            pyth_b: accounts.pyth_b,
+            clock: accounts.clock,
+            token_program: accounts.token_program,
+            observation_buffer: accounts.observation_buffer,
+            whitelist: accounts.whitelist,
+            price_feed: accounts.price_feed,
+            pool_mint: accounts.pool_mint,
+        }
+    }
+}
+
+impl<'info> From<Deposit<'info>> for interface::Deposit<'info> {
+    fn from(accounts: Deposit<'info>) -> Self {
+        Self {
+            swap: accounts.swap,
+            swap_authority: accounts.swap_authority,
+            user_transfer_authority: accounts.user_transfer_authority,
+            source_a: accounts.source_a,
+            source_b: accounts.source_b,
+            swap_token_a: accounts.swap_token_a,
+            swap_token_b: accounts.swap_token_b,
+            pool_mint: accounts.pool_mint,
+            destination: accounts.destination,
+            admin_fee_a: accounts.admin_fee_a,
+            admin_fee_b: accounts.admin_fee_b,
+            liquidity_provider: accounts.liquidity_provider,
+            liquidity_owner: accounts.liquidity_owner,
+            pyth_a: accounts.pyth_a,
+            pyth_b: accounts.pyth_b,
+            clock: accounts.clock,
+            token_program: accounts.token_program,
+            observation_buffer: accounts.observation_buffer,
+            price_feed: accounts.price_feed,
+        }
+    }
+}
+
+impl<'info> From<Withdraw<'info>> for interface::Withdraw<'info> {
+    fn from(accounts: Withdraw<'info>) -> Self {
+        Self {
+            swap: accounts.swap,
+            swap_authority: accounts.swap_authority,
+            user_transfer_authority: accounts.user_transfer_authority,
+            pool_mint: accounts.pool_mint,
+            source: accounts.source,
+            swap_token_a: accounts.swap_token_a,
+            swap_token_b: accounts.swap_token_b,
+            destination_a: accounts.destination_a,
+            destination_b: accounts.destination_b,
+            admin_fee_a: accounts.admin_fee_a,
+            admin_fee_b: accounts.admin_fee_b,
+            liquidity_provider: accounts.liquidity_provider,
+            liquidity_owner: accounts.liquidity_owner,
+            pyth_a: accounts.pyth_a,
+            pyth_b: accounts.pyth_b,
+            clock: accounts.clock,
+            token_program: accounts.token_program,
+            observation_buffer: accounts.observation_buffer,
+            price_feed: accounts.price_feed,
+        }
+    }
+}
+
+/// Cpi functions of the swap program
+pub mod cpi {
+    use super::*;
+
+    /// Swap tokens
+    pub fn swap<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, Swap<'info>>,
+        swap_data: SwapData,
+    ) -> Result<()> {
+        interface::swap(
+            &ctx.program,
+            ctx.accounts.into(),
+            &ctx.remaining_accounts,
+            swap_data,
+            ctx.signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+
+    /// Deposit tokens at the current pool ratio
+    pub fn deposit<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, Deposit<'info>>,
+        deposit_data: DepositData,
+    ) -> Result<()> {
+        interface::deposit(
+            &ctx.program,
+            ctx.accounts.into(),
+            &ctx.remaining_accounts,
+            deposit_data,
+            ctx.signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+
+    /// Deposit tokens in any ratio
+    pub fn deposit_imbalanced<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, Deposit<'info>>,
+        deposit_data: DepositData,
+    ) -> Result<()> {
+        interface::deposit_imbalanced(
+            &ctx.program,
+            ctx.accounts.into(),
+            &ctx.remaining_accounts,
+            deposit_data,
+            ctx.signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+
+    /// Withdraw tokens at the current pool ratio
+    pub fn withdraw<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, Withdraw<'info>>,
+        withdraw_data: WithdrawData,
+    ) -> Result<()> {
+        interface::withdraw(
+            &ctx.program,
+            ctx.accounts.into(),
+            &ctx.remaining_accounts,
+            withdraw_data,
+            ctx.signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+
+    /// Withdraw exact token amounts
+    pub fn withdraw_exact<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, Withdraw<'info>>,
+        withdraw_data: WithdrawExactData,
+    ) -> Result<()> {
+        interface::withdraw_exact(
+            &ctx.program,
+            ctx.accounts.into(),
+            &ctx.remaining_accounts,
+            withdraw_data,
+            ctx.signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+}
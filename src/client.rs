@@ -0,0 +1,1690 @@
+//! Client helpers to build program instructions off-chain
+//!
+//! Wraps the raw builders in [instruction](../instruction/index.html) and fills
+//! the account lists from the unpacked on-chain state.
+
+#![allow(clippy::too_many_arguments)]
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::{
+    curve::CurveType,
+    error::SwapError,
+    instruction::{
+        self, BuybackAndBurn, CreateDcaPlanData, DepositData, EmergencyWithdrawData, QuoteSwapData,
+        SwapData, SwapDirection, WithdrawData, WithdrawExactData,
+    },
+    state::{
+        find_dca_plan_address, find_order_authority_address, ConfigInfo, DcaPlan, DirectionalFees,
+        FeeDiscount, Fees, HalvingSchedule, Rewards, SignedOrder, SwapInfo,
+    },
+};
+
+/// Find the market authority derived from the config account
+pub fn find_market_authority(program_id: &Pubkey, config_pubkey: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[config_pubkey.as_ref()], program_id)
+}
+
+/// Find the swap authority derived from the swap account
+pub fn find_swap_authority(program_id: &Pubkey, swap_pubkey: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[swap_pubkey.as_ref()], program_id)
+}
+
+/// Swap pool loaded from chain, used to build its instructions
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapClient {
+    /// Program id
+    pub program_id: Pubkey,
+    /// Config account
+    pub config_pubkey: Pubkey,
+    /// Unpacked config
+    pub config: ConfigInfo,
+    /// Token-swap account
+    pub swap_pubkey: Pubkey,
+    /// Unpacked token-swap
+    pub swap: SwapInfo,
+    /// Bids and asks of the OpenBook market of the token-swap, passed along
+    /// with the market to the instructions pricing the pool
+    pub openbook_book: Option<(Pubkey, Pubkey)>,
+}
+
+impl SwapClient {
+    /// Create new client from unpacked accounts
+    pub fn new(
+        program_id: Pubkey,
+        config_pubkey: Pubkey,
+        config: ConfigInfo,
+        swap_pubkey: Pubkey,
+        swap: SwapInfo,
+    ) -> Self {
+        Self {
+            program_id,
+            config_pubkey,
+            config,
+            swap_pubkey,
+            swap,
+            openbook_book: None,
+        }
+    }
+
+    /// Market authority, mint authority of the deltafi token
+    pub fn market_authority(&self) -> Result<Pubkey, ProgramError> {
+        Pubkey::create_program_address(
+            &[self.config_pubkey.as_ref(), &[self.config.bump_seed]],
+            &self.program_id,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)
+    }
+
+    /// Swap authority, owner of the pool token accounts
+    pub fn swap_authority(&self) -> Result<Pubkey, ProgramError> {
+        Pubkey::create_program_address(
+            &[self.swap_pubkey.as_ref(), &[self.swap.nonce]],
+            &self.program_id,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)
+    }
+
+    /// Appends the OpenBook market of the token-swap to an instruction pricing
+    /// the pool, once its bids and asks are known. Nothing is appended for
+    /// token-swaps without a market
+    fn append_openbook_market(
+        &self,
+        instruction: Instruction,
+    ) -> Result<Instruction, ProgramError> {
+        if self.swap.openbook_market == Pubkey::default() {
+            return Ok(instruction);
+        }
+        let (bids_pubkey, asks_pubkey) = self
+            .openbook_book
+            .ok_or(SwapError::IncorrectOracleAccount)?;
+        Ok(instruction::append_openbook_market(
+            instruction,
+            self.swap.openbook_market,
+            bids_pubkey,
+            asks_pubkey,
+        ))
+    }
+
+    /// Admin fee account receiving the trade fee of a swap
+    pub fn admin_fee_destination(&self, swap_direction: SwapDirection) -> Pubkey {
+        match swap_direction {
+            _ if self.swap.is_fee_in_quote => self.swap.admin_fee_key_b,
+            SwapDirection::SellBase => self.swap.admin_fee_key_b,
+            SwapDirection::SellQuote => self.swap.admin_fee_key_a,
+        }
+    }
+
+    /// Creates a 'swap' instruction.
+    ///
+    /// Base and quote accounts are the user token accounts of token a and token b,
+    /// the one being sold is debited by the user transfer authority.
+    pub fn swap(
+        &self,
+        user_transfer_authority_pubkey: Pubkey,
+        base_pubkey: Pubkey,
+        quote_pubkey: Pubkey,
+        reward_token_pubkey: Pubkey,
+        swap_data: SwapData,
+    ) -> Result<Instruction, ProgramError> {
+        let instruction = instruction::swap(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.market_authority()?,
+            self.swap_authority()?,
+            user_transfer_authority_pubkey,
+            base_pubkey,
+            self.swap.token_a,
+            self.swap.token_b,
+            quote_pubkey,
+            reward_token_pubkey,
+            self.config.deltafi_mint,
+            self.admin_fee_destination(swap_data.swap_direction),
+            self.swap.pyth_a,
+            self.swap.pyth_b,
+            self.swap.pool_mint,
+            swap_data,
+        )?;
+        Ok(instruction::append_transfer_hook(
+            self.append_openbook_market(instruction)?,
+            self.swap.transfer_hook_program,
+        ))
+    }
+
+    /// Creates a 'flash_swap' instruction, the output is transferred before the
+    /// callback program pays the input back.
+    ///
+    /// The callback accounts are passed as is to the callback program.
+    pub fn flash_swap(
+        &self,
+        user_transfer_authority_pubkey: Pubkey,
+        base_pubkey: Pubkey,
+        quote_pubkey: Pubkey,
+        reward_token_pubkey: Pubkey,
+        swap_data: SwapData,
+        callback_program: Pubkey,
+        callback_accounts: Vec<AccountMeta>,
+        callback_data: Vec<u8>,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::flash_swap(
+            self.swap(
+                user_transfer_authority_pubkey,
+                base_pubkey,
+                quote_pubkey,
+                reward_token_pubkey,
+                swap_data,
+            )?,
+            callback_program,
+            callback_accounts,
+            callback_data,
+        )
+    }
+
+    /// Creates a 'relayed_swap' instruction, signed by the owner of the user
+    /// token account sold from and submitted by the fee payer, paid the
+    /// relayer fee in the token sold.
+    pub fn relayed_swap(
+        &self,
+        owner_pubkey: Pubkey,
+        base_pubkey: Pubkey,
+        quote_pubkey: Pubkey,
+        reward_token_pubkey: Pubkey,
+        swap_data: SwapData,
+        fee_payer_pubkey: Pubkey,
+        relayer_fee_token_pubkey: Pubkey,
+        relayer_fee: u64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::relay_swap(
+            self.swap(
+                owner_pubkey,
+                base_pubkey,
+                quote_pubkey,
+                reward_token_pubkey,
+                swap_data,
+            )?,
+            fee_payer_pubkey,
+            relayer_fee_token_pubkey,
+            relayer_fee,
+        )
+    }
+
+    /// Creates the instructions settling the signed order, the ed25519
+    /// instruction verifying the signature of the maker followed by the
+    /// 'settle_signed_order' instruction paid by the payer.
+    ///
+    /// Base and quote accounts are the token accounts of the maker, the one
+    /// being sold delegating the order amount to the order authority.
+    pub fn settle_signed_order(
+        &self,
+        order: SignedOrder,
+        signature: &[u8; 64],
+        base_pubkey: Pubkey,
+        quote_pubkey: Pubkey,
+        reward_token_pubkey: Pubkey,
+        payer_pubkey: Pubkey,
+    ) -> Result<Vec<Instruction>, ProgramError> {
+        let (order_authority, _) = find_order_authority_address(&self.program_id);
+        let swap = self.swap(
+            order_authority,
+            base_pubkey,
+            quote_pubkey,
+            reward_token_pubkey,
+            SwapData {
+                amount_in: order.amount_in,
+                minimum_amount_out: order.minimum_amount_out,
+                swap_direction: order.swap_direction,
+                max_price_impact_bps: 0,
+            },
+        )?;
+        Ok(vec![
+            instruction::verify_signed_order(&order, signature),
+            instruction::settle_signed_order(swap, order, payer_pubkey)?,
+        ])
+    }
+
+    /// Creates a 'cancel_signed_orders' instruction, voiding the orders of the
+    /// maker with a nonce below the next nonce.
+    pub fn cancel_signed_orders(
+        &self,
+        maker_pubkey: Pubkey,
+        payer_pubkey: Pubkey,
+        next_nonce: u64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::cancel_signed_orders(self.program_id, maker_pubkey, payer_pubkey, next_nonce)
+    }
+
+    /// Mint of the token sold in the swap direction
+    fn mint_sold(&self, swap_direction: SwapDirection) -> Pubkey {
+        match swap_direction {
+            SwapDirection::SellBase => self.swap.token_a_mint,
+            SwapDirection::SellQuote => self.swap.token_b_mint,
+        }
+    }
+
+    /// Creates a 'create_dca_plan' instruction, escrowing the plan amount
+    /// from the source account of the owner, of the mint sold.
+    pub fn create_dca_plan(
+        &self,
+        owner_pubkey: Pubkey,
+        source_pubkey: Pubkey,
+        payer_pubkey: Pubkey,
+        plan_data: CreateDcaPlanData,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::create_dca_plan(
+            self.program_id,
+            self.swap_pubkey,
+            owner_pubkey,
+            source_pubkey,
+            self.mint_sold(plan_data.swap_direction),
+            payer_pubkey,
+            plan_data,
+        )
+    }
+
+    /// Creates an 'execute_dca' instruction for the period due of the plan,
+    /// rewarding the keeper token account of the mint sold.
+    ///
+    /// The owner token account is the one of the mint bought, the tokens
+    /// bought and trade rewards going to the owner of the plan.
+    pub fn execute_dca(
+        &self,
+        dca_plan: &DcaPlan,
+        owner_token_pubkey: Pubkey,
+        reward_token_pubkey: Pubkey,
+        keeper_token_pubkey: Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (dca_plan_pubkey, _) =
+            find_dca_plan_address(&self.program_id, &self.swap_pubkey, &dca_plan.owner);
+        let swap_direction = dca_plan.swap_direction()?;
+        let (base_pubkey, quote_pubkey) = match swap_direction {
+            SwapDirection::SellBase => (dca_plan.escrow, owner_token_pubkey),
+            SwapDirection::SellQuote => (owner_token_pubkey, dca_plan.escrow),
+        };
+        let swap = self.swap(
+            dca_plan_pubkey,
+            base_pubkey,
+            quote_pubkey,
+            reward_token_pubkey,
+            SwapData {
+                amount_in: dca_plan.amount_per_period(),
+                minimum_amount_out: 0,
+                swap_direction,
+                max_price_impact_bps: dca_plan.max_price_impact_bps(),
+            },
+        )?;
+        instruction::execute_dca(swap, dca_plan_pubkey, keeper_token_pubkey)
+    }
+
+    /// Creates a 'cancel_dca' instruction, refunding the amount left in the
+    /// escrow of the plan to the destination account of the owner.
+    pub fn cancel_dca(
+        &self,
+        dca_plan: &DcaPlan,
+        destination_pubkey: Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::cancel_dca(
+            self.program_id,
+            self.swap_pubkey,
+            dca_plan.owner,
+            destination_pubkey,
+            self.mint_sold(dca_plan.swap_direction()?),
+        )
+    }
+
+    /// Orders the temporary wrapped SOL account and the user token account of
+    /// the other side as token a and token b accounts
+    fn native_sol_accounts(
+        &self,
+        native_account_pubkey: Pubkey,
+        token_pubkey: Pubkey,
+    ) -> Result<(Pubkey, Pubkey), ProgramError> {
+        let native_mint = spl_token::native_mint::id();
+        if self.swap.token_a_mint == native_mint {
+            Ok((native_account_pubkey, token_pubkey))
+        } else if self.swap.token_b_mint == native_mint {
+            Ok((token_pubkey, native_account_pubkey))
+        } else {
+            Err(SwapError::IncorrectMint.into())
+        }
+    }
+
+    /// Creates a 'swap_native' instruction, trading lamports of the wallet
+    /// through the temporary wrapped SOL account.
+    ///
+    /// The token account is the user account of the other side of the pool.
+    pub fn swap_native(
+        &self,
+        wallet_pubkey: Pubkey,
+        native_account_pubkey: Pubkey,
+        token_pubkey: Pubkey,
+        reward_token_pubkey: Pubkey,
+        swap_data: SwapData,
+    ) -> Result<Instruction, ProgramError> {
+        let (base_pubkey, quote_pubkey) =
+            self.native_sol_accounts(native_account_pubkey, token_pubkey)?;
+        instruction::wrap_native_sol(
+            self.swap(
+                wallet_pubkey,
+                base_pubkey,
+                quote_pubkey,
+                reward_token_pubkey,
+                swap_data,
+            )?,
+            wallet_pubkey,
+            native_account_pubkey,
+        )
+    }
+
+    /// Creates a 'quote_swap' instruction, the output amount is set as the return data.
+    pub fn quote_swap(&self, quote_swap_data: QuoteSwapData) -> Result<Instruction, ProgramError> {
+        self.append_openbook_market(instruction::quote_swap(
+            self.program_id,
+            self.swap_pubkey,
+            self.swap.token_a,
+            self.swap.token_b,
+            self.swap.pyth_a,
+            self.swap.pyth_b,
+            quote_swap_data,
+        )?)
+    }
+
+    /// Creates a 'calc_receive_amount' instruction, the quote is stored in the
+    /// quote account of the owner.
+    pub fn calc_receive_amount(
+        &self,
+        owner_pubkey: Pubkey,
+        quote_swap_data: QuoteSwapData,
+    ) -> Result<Instruction, ProgramError> {
+        self.append_openbook_market(instruction::calc_receive_amount(
+            self.program_id,
+            owner_pubkey,
+            self.swap_pubkey,
+            self.swap.token_a,
+            self.swap.token_b,
+            self.swap.pyth_a,
+            self.swap.pyth_b,
+            quote_swap_data,
+        )?)
+    }
+
+    /// Creates a 'deposit' instruction.
+    pub fn deposit(
+        &self,
+        user_transfer_authority_pubkey: Pubkey,
+        deposit_token_a_pubkey: Pubkey,
+        deposit_token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        liquidity_provider_pubkey: Pubkey,
+        liquidity_owner_pubkey: Pubkey,
+        deposit_data: DepositData,
+    ) -> Result<Instruction, ProgramError> {
+        let instruction = instruction::deposit(
+            self.program_id,
+            self.swap_pubkey,
+            self.swap_authority()?,
+            user_transfer_authority_pubkey,
+            deposit_token_a_pubkey,
+            deposit_token_b_pubkey,
+            self.swap.token_a,
+            self.swap.token_b,
+            self.swap.pool_mint,
+            pool_token_pubkey,
+            liquidity_provider_pubkey,
+            liquidity_owner_pubkey,
+            self.swap.pyth_a,
+            self.swap.pyth_b,
+            deposit_data,
+        )?;
+        Ok(instruction::append_transfer_hook(
+            self.append_openbook_market(instruction)?,
+            self.swap.transfer_hook_program,
+        ))
+    }
+
+    /// Creates a 'deposit_native' instruction, depositing lamports of the
+    /// wallet through the temporary wrapped SOL account.
+    ///
+    /// The token account is the user account of the other side of the pool.
+    pub fn deposit_native(
+        &self,
+        wallet_pubkey: Pubkey,
+        native_account_pubkey: Pubkey,
+        token_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        liquidity_provider_pubkey: Pubkey,
+        liquidity_owner_pubkey: Pubkey,
+        deposit_data: DepositData,
+    ) -> Result<Instruction, ProgramError> {
+        let (deposit_token_a_pubkey, deposit_token_b_pubkey) =
+            self.native_sol_accounts(native_account_pubkey, token_pubkey)?;
+        instruction::wrap_native_sol(
+            self.deposit(
+                wallet_pubkey,
+                deposit_token_a_pubkey,
+                deposit_token_b_pubkey,
+                pool_token_pubkey,
+                liquidity_provider_pubkey,
+                liquidity_owner_pubkey,
+                deposit_data,
+            )?,
+            wallet_pubkey,
+            native_account_pubkey,
+        )
+    }
+
+    /// Creates a 'deposit_imbalanced' instruction.
+    pub fn deposit_imbalanced(
+        &self,
+        user_transfer_authority_pubkey: Pubkey,
+        deposit_token_a_pubkey: Pubkey,
+        deposit_token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        liquidity_provider_pubkey: Pubkey,
+        liquidity_owner_pubkey: Pubkey,
+        deposit_data: DepositData,
+    ) -> Result<Instruction, ProgramError> {
+        let instruction = instruction::deposit_imbalanced(
+            self.program_id,
+            self.swap_pubkey,
+            self.swap_authority()?,
+            user_transfer_authority_pubkey,
+            deposit_token_a_pubkey,
+            deposit_token_b_pubkey,
+            self.swap.token_a,
+            self.swap.token_b,
+            self.swap.pool_mint,
+            pool_token_pubkey,
+            self.swap.admin_fee_key_a,
+            self.swap.admin_fee_key_b,
+            liquidity_provider_pubkey,
+            liquidity_owner_pubkey,
+            self.swap.pyth_a,
+            self.swap.pyth_b,
+            deposit_data,
+        )?;
+        Ok(instruction::append_transfer_hook(
+            self.append_openbook_market(instruction)?,
+            self.swap.transfer_hook_program,
+        ))
+    }
+
+    /// Creates a 'withdraw' instruction.
+    pub fn withdraw(
+        &self,
+        user_transfer_authority_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        destination_token_a_pubkey: Pubkey,
+        destination_token_b_pubkey: Pubkey,
+        liquidity_provider_pubkey: Pubkey,
+        liquidity_owner_pubkey: Pubkey,
+        withdraw_data: WithdrawData,
+    ) -> Result<Instruction, ProgramError> {
+        self.append_openbook_market(instruction::withdraw(
+            self.program_id,
+            self.swap_pubkey,
+            self.swap_authority()?,
+            user_transfer_authority_pubkey,
+            self.swap.pool_mint,
+            pool_token_pubkey,
+            self.swap.token_a,
+            self.swap.token_b,
+            destination_token_a_pubkey,
+            destination_token_b_pubkey,
+            self.swap.admin_fee_key_a,
+            self.swap.admin_fee_key_b,
+            liquidity_provider_pubkey,
+            liquidity_owner_pubkey,
+            self.swap.pyth_a,
+            self.swap.pyth_b,
+            withdraw_data,
+        )?)
+    }
+
+    /// Creates a 'withdraw' instruction crediting the associated token accounts
+    /// of the liquidity provider owner, created by the payer when missing.
+    pub fn withdraw_to_associated_token_accounts(
+        &self,
+        user_transfer_authority_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        liquidity_provider_pubkey: Pubkey,
+        liquidity_owner_pubkey: Pubkey,
+        payer_pubkey: Pubkey,
+        withdraw_data: WithdrawData,
+    ) -> Result<Instruction, ProgramError> {
+        let instruction = self.withdraw(
+            user_transfer_authority_pubkey,
+            pool_token_pubkey,
+            get_associated_token_address(&liquidity_owner_pubkey, &self.swap.token_a_mint),
+            get_associated_token_address(&liquidity_owner_pubkey, &self.swap.token_b_mint),
+            liquidity_provider_pubkey,
+            liquidity_owner_pubkey,
+            withdraw_data,
+        )?;
+        Ok(instruction::append_associated_token_accounts(
+            instruction,
+            payer_pubkey,
+            vec![self.swap.token_a_mint, self.swap.token_b_mint],
+        ))
+    }
+
+    /// Creates an 'emergency_withdraw' instruction.
+    pub fn emergency_withdraw(
+        &self,
+        user_transfer_authority_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        destination_token_a_pubkey: Pubkey,
+        destination_token_b_pubkey: Pubkey,
+        liquidity_provider_pubkey: Pubkey,
+        liquidity_owner_pubkey: Pubkey,
+        withdraw_data: EmergencyWithdrawData,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::emergency_withdraw(
+            self.program_id,
+            self.swap_pubkey,
+            self.swap_authority()?,
+            user_transfer_authority_pubkey,
+            self.swap.pool_mint,
+            pool_token_pubkey,
+            self.swap.token_a,
+            self.swap.token_b,
+            destination_token_a_pubkey,
+            destination_token_b_pubkey,
+            self.swap.admin_fee_key_a,
+            self.swap.admin_fee_key_b,
+            liquidity_provider_pubkey,
+            liquidity_owner_pubkey,
+            withdraw_data,
+        )
+    }
+
+    /// Creates a 'withdraw_exact' instruction.
+    pub fn withdraw_exact(
+        &self,
+        user_transfer_authority_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        destination_token_a_pubkey: Pubkey,
+        destination_token_b_pubkey: Pubkey,
+        liquidity_provider_pubkey: Pubkey,
+        liquidity_owner_pubkey: Pubkey,
+        withdraw_data: WithdrawExactData,
+    ) -> Result<Instruction, ProgramError> {
+        self.append_openbook_market(instruction::withdraw_exact(
+            self.program_id,
+            self.swap_pubkey,
+            self.swap_authority()?,
+            user_transfer_authority_pubkey,
+            self.swap.pool_mint,
+            pool_token_pubkey,
+            self.swap.token_a,
+            self.swap.token_b,
+            destination_token_a_pubkey,
+            destination_token_b_pubkey,
+            self.swap.admin_fee_key_a,
+            self.swap.admin_fee_key_b,
+            liquidity_provider_pubkey,
+            liquidity_owner_pubkey,
+            self.swap.pyth_a,
+            self.swap.pyth_b,
+            withdraw_data,
+        )?)
+    }
+
+    /// Creates a 'claim_liquidity_rewards' instruction.
+    pub fn claim_liquidity_rewards(
+        &self,
+        liquidity_provider_pubkey: Pubkey,
+        liquidity_owner_pubkey: Pubkey,
+        claim_destination_pubkey: Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::claim_liquidity_rewards(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.market_authority()?,
+            liquidity_provider_pubkey,
+            liquidity_owner_pubkey,
+            claim_destination_pubkey,
+            self.config.deltafi_mint,
+        )
+    }
+
+    /// Creates a 'claim_liquidity_rewards' instruction minting to the associated
+    /// token account of the liquidity provider owner, created by the payer when missing.
+    pub fn claim_liquidity_rewards_to_associated_token_account(
+        &self,
+        liquidity_provider_pubkey: Pubkey,
+        liquidity_owner_pubkey: Pubkey,
+        payer_pubkey: Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let instruction = self.claim_liquidity_rewards(
+            liquidity_provider_pubkey,
+            liquidity_owner_pubkey,
+            get_associated_token_address(&liquidity_owner_pubkey, &self.config.deltafi_mint),
+        )?;
+        Ok(instruction::append_associated_token_accounts(
+            instruction,
+            payer_pubkey,
+            vec![self.config.deltafi_mint],
+        ))
+    }
+
+    /// Creates a 'poke' instruction minting the keeper incentive, if any, to
+    /// `reward_destination_pubkey`.
+    pub fn poke(&self, reward_destination_pubkey: Pubkey) -> Result<Instruction, ProgramError> {
+        instruction::poke(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.market_authority()?,
+            reward_destination_pubkey,
+            self.config.deltafi_mint,
+        )
+    }
+
+    /// Creates a 'sync' instruction folding the surplus of the swap token
+    /// accounts into the pool.
+    pub fn sync(&self) -> Result<Instruction, ProgramError> {
+        instruction::sync(
+            self.program_id,
+            self.swap_pubkey,
+            self.swap.token_a,
+            self.swap.token_b,
+            self.swap.pool_mint,
+        )
+    }
+
+    /// Creates a 'skim' instruction sending the surplus of the swap token
+    /// accounts to the admin fee accounts.
+    pub fn skim(&self) -> Result<Instruction, ProgramError> {
+        instruction::skim(
+            self.program_id,
+            self.swap_pubkey,
+            self.swap_authority()?,
+            self.swap.token_a,
+            self.swap.token_b,
+            self.swap.admin_fee_key_a,
+            self.swap.admin_fee_key_b,
+        )
+    }
+
+    /// Creates a 'get_virtual_price' instruction returning the value of a
+    /// pool token.
+    pub fn get_virtual_price(&self) -> Result<Instruction, ProgramError> {
+        instruction::get_virtual_price(self.program_id, self.swap_pubkey, self.swap.pool_mint)
+    }
+
+    /// Creates a 'refresh_liquidity_obligation' instruction.
+    pub fn refresh_liquidity_obligation(&self) -> Result<Instruction, ProgramError> {
+        instruction::refresh_liquidity_obligation(
+            self.program_id,
+            self.swap_pubkey,
+            self.config_pubkey,
+            self.config.deltafi_price_oracle,
+        )
+    }
+
+    /// Creates an 'init_observation_buffer' instruction.
+    pub fn init_observation_buffer(
+        &self,
+        payer_pubkey: Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::init_observation_buffer(self.program_id, self.swap_pubkey, payer_pubkey)
+    }
+
+    /// Creates an 'init_price_feed' instruction.
+    pub fn init_price_feed(&self, payer_pubkey: Pubkey) -> Result<Instruction, ProgramError> {
+        instruction::init_price_feed(
+            self.program_id,
+            self.swap_pubkey,
+            self.swap.pool_mint,
+            payer_pubkey,
+        )
+    }
+
+    /// Creates a 'pause' instruction signed by the config admin.
+    pub fn pause(&self) -> Result<Instruction, ProgramError> {
+        instruction::pause(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+        )
+    }
+
+    /// Creates a 'unpause' instruction signed by the config admin.
+    pub fn unpause(&self) -> Result<Instruction, ProgramError> {
+        instruction::unpause(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+        )
+    }
+
+    /// Creates a 'set_fee_account' instruction signed by the config admin.
+    pub fn set_fee_account(
+        &self,
+        new_fee_account_pubkey: Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_fee_account(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.swap_authority()?,
+            self.config.admin_key,
+            new_fee_account_pubkey,
+        )
+    }
+
+    /// Creates a 'set_new_fees' instruction signed by the config admin.
+    pub fn set_new_fees(&self, new_fees: Fees) -> Result<Instruction, ProgramError> {
+        instruction::set_new_fees(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            new_fees,
+        )
+    }
+
+    /// Creates a 'set_new_rewards' instruction signed by the config admin.
+    pub fn set_new_rewards(&self, new_rewards: Rewards) -> Result<Instruction, ProgramError> {
+        instruction::set_new_rewards(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            new_rewards,
+        )
+    }
+
+    /// Creates a 'set_halving_schedule' instruction signed by the config admin.
+    pub fn set_halving_schedule(
+        &self,
+        halving_schedule: HalvingSchedule,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_halving_schedule(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            halving_schedule,
+        )
+    }
+
+    /// Creates a 'buyback_and_burn' instruction signed by the config admin,
+    /// selling an admin fee account of this pool through the DELFI pool.
+    pub fn buyback_and_burn(
+        &self,
+        admin_fee_source_pubkey: Pubkey,
+        deltafi_pool: &SwapClient,
+        buyback_data: BuybackAndBurn,
+    ) -> Result<Instruction, ProgramError> {
+        // the DELFI side of the pool receives the admin fee of the trade
+        let swap_direction = if deltafi_pool.swap.token_b_mint == self.config.deltafi_mint {
+            SwapDirection::SellBase
+        } else {
+            SwapDirection::SellQuote
+        };
+        deltafi_pool.append_openbook_market(instruction::buyback_and_burn(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.swap_authority()?,
+            self.config.admin_key,
+            admin_fee_source_pubkey,
+            deltafi_pool.swap_pubkey,
+            deltafi_pool.swap_authority()?,
+            deltafi_pool.swap.token_a,
+            deltafi_pool.swap.token_b,
+            deltafi_pool.admin_fee_destination(swap_direction),
+            self.config.deltafi_mint,
+            deltafi_pool.swap.pyth_a,
+            deltafi_pool.swap.pyth_b,
+            deltafi_pool.swap.pool_mint,
+            buyback_data,
+        )?)
+    }
+
+    /// Creates a 'set_max_price_impact' instruction signed by the config admin.
+    pub fn set_max_price_impact(
+        &self,
+        max_price_impact_bps: u64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_max_price_impact(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            max_price_impact_bps,
+        )
+    }
+
+    /// Creates a 'set_max_trade_size' instruction signed by the config admin.
+    pub fn set_max_trade_size(
+        &self,
+        max_amount_in: u64,
+        max_amount_in_reserve_bps: u64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_max_trade_size(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            max_amount_in,
+            max_amount_in_reserve_bps,
+        )
+    }
+
+    /// Creates a 'set_slot_throttle' instruction signed by the config admin.
+    pub fn set_slot_throttle(
+        &self,
+        max_swaps_per_slot: u64,
+        max_volume_per_slot: u64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_slot_throttle(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            max_swaps_per_slot,
+            max_volume_per_slot,
+        )
+    }
+
+    /// Creates a 'set_fee_compounding' instruction signed by the config admin.
+    pub fn set_fee_compounding(
+        &self,
+        is_fee_compounding: bool,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_fee_compounding(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            is_fee_compounding,
+        )
+    }
+
+    /// Creates a 'set_fee_in_quote' instruction signed by the config admin.
+    pub fn set_fee_in_quote(&self, is_fee_in_quote: bool) -> Result<Instruction, ProgramError> {
+        instruction::set_fee_in_quote(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            is_fee_in_quote,
+        )
+    }
+
+    /// Creates an 'initialize_whitelist' instruction signed by the config admin.
+    pub fn initialize_whitelist(&self, payer: Pubkey) -> Result<Instruction, ProgramError> {
+        instruction::initialize_whitelist(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            payer,
+        )
+    }
+
+    /// Creates a 'set_whitelist_trader' instruction signed by the config admin.
+    pub fn set_whitelist_trader(
+        &self,
+        trader: Pubkey,
+        is_allowed: bool,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_whitelist_trader(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            trader,
+            is_allowed,
+        )
+    }
+
+    /// Creates a 'set_whitelist_only' instruction signed by the config admin.
+    pub fn set_whitelist_only(&self, is_whitelist_only: bool) -> Result<Instruction, ProgramError> {
+        instruction::set_whitelist_only(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            is_whitelist_only,
+        )
+    }
+
+    /// Creates a 'set_transfer_hook' instruction signed by the config admin.
+    pub fn set_transfer_hook(
+        &self,
+        transfer_hook_program: Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_transfer_hook(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            transfer_hook_program,
+        )
+    }
+
+    /// Creates a 'set_deltafi_price_oracle' instruction signed by the config admin.
+    pub fn set_deltafi_price_oracle(
+        &self,
+        deltafi_price_oracle: Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_deltafi_price_oracle(
+            self.program_id,
+            self.config_pubkey,
+            self.config.admin_key,
+            deltafi_price_oracle,
+        )
+    }
+
+    /// Creates a 'set_epoch_reward_budget' instruction signed by the config admin.
+    pub fn set_epoch_reward_budget(
+        &self,
+        epoch_reward_budget: u64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_epoch_reward_budget(
+            self.program_id,
+            self.config_pubkey,
+            self.config.admin_key,
+            epoch_reward_budget,
+        )
+    }
+
+    /// Creates a 'set_poke_reward' instruction signed by the config admin.
+    pub fn set_poke_reward(
+        &self,
+        poke_reward: u64,
+        min_poke_interval: i64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_poke_reward(
+            self.program_id,
+            self.config_pubkey,
+            self.config.admin_key,
+            poke_reward,
+            min_poke_interval,
+        )
+    }
+
+    /// Creates a 'set_proposal_quorum' instruction signed by the config admin.
+    pub fn set_proposal_quorum(&self, proposal_quorum: u64) -> Result<Instruction, ProgramError> {
+        instruction::set_proposal_quorum(
+            self.program_id,
+            self.config_pubkey,
+            self.config.admin_key,
+            proposal_quorum,
+        )
+    }
+
+    /// Creates a 'snapshot' instruction signed by the config admin.
+    pub fn snapshot(
+        &self,
+        payer: Pubkey,
+        snapshot_id: u64,
+        merkle_root: [u8; 32],
+    ) -> Result<Instruction, ProgramError> {
+        instruction::snapshot(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            self.swap.pool_mint,
+            payer,
+            snapshot_id,
+            merkle_root,
+        )
+    }
+
+    /// Creates a 'set_reserve_caps' instruction signed by the config admin.
+    pub fn set_reserve_caps(
+        &self,
+        max_base_reserve: u64,
+        max_quote_reserve: u64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_reserve_caps(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            max_base_reserve,
+            max_quote_reserve,
+        )
+    }
+
+    /// Creates a 'set_user_liquidity_cap' instruction signed by the config admin.
+    pub fn set_user_liquidity_cap(
+        &self,
+        max_user_liquidity: u64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_user_liquidity_cap(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            max_user_liquidity,
+        )
+    }
+
+    /// Creates a 'make_immutable' instruction signed by the config admin.
+    pub fn make_immutable(&self) -> Result<Instruction, ProgramError> {
+        instruction::make_immutable(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+        )
+    }
+
+    /// Creates a 'set_directional_fees' instruction signed by the config admin.
+    pub fn set_directional_fees(
+        &self,
+        directional_fees: DirectionalFees,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_directional_fees(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            directional_fees,
+        )
+    }
+
+    /// Creates a 'set_min_trade_fee' instruction signed by the config admin.
+    pub fn set_min_trade_fee(&self, min_trade_fee: u64) -> Result<Instruction, ProgramError> {
+        instruction::set_min_trade_fee(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            min_trade_fee,
+        )
+    }
+
+    /// Creates a 'set_fee_discount' instruction signed by the config admin.
+    pub fn set_fee_discount(&self, fee_discount: FeeDiscount) -> Result<Instruction, ProgramError> {
+        instruction::set_fee_discount(
+            self.program_id,
+            self.config_pubkey,
+            self.config.admin_key,
+            fee_discount,
+        )
+    }
+
+    /// Creates a 'set_referral_reward' instruction signed by the config admin.
+    pub fn set_referral_reward(
+        &self,
+        referral_reward_bps: u64,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_referral_reward(
+            self.program_id,
+            self.config_pubkey,
+            self.config.admin_key,
+            referral_reward_bps,
+        )
+    }
+
+    /// Creates an 'initialize_deny_list' instruction signed by the config admin.
+    pub fn initialize_deny_list(&self, payer: Pubkey) -> Result<Instruction, ProgramError> {
+        instruction::initialize_deny_list(
+            self.program_id,
+            self.config_pubkey,
+            self.config.admin_key,
+            payer,
+        )
+    }
+
+    /// Creates a 'set_denied_mint' instruction signed by the config admin.
+    pub fn set_denied_mint(
+        &self,
+        mint: Pubkey,
+        is_denied: bool,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_denied_mint(
+            self.program_id,
+            self.config_pubkey,
+            self.config.admin_key,
+            mint,
+            is_denied,
+        )
+    }
+
+    /// Creates a 'set_mid_price' instruction signed by the config admin.
+    pub fn set_mid_price(&self, mid_price: u128) -> Result<Instruction, ProgramError> {
+        instruction::set_mid_price(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            self.swap.pyth_a,
+            self.swap.pyth_b,
+            self.swap.pool_mint,
+            mid_price,
+        )
+    }
+
+    /// Creates a 'recalibrate' instruction signed by the config admin.
+    pub fn recalibrate(&self) -> Result<Instruction, ProgramError> {
+        instruction::recalibrate(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            self.swap.token_a,
+            self.swap.token_b,
+            self.swap.pyth_a,
+            self.swap.pyth_b,
+            self.swap.pool_mint,
+        )
+    }
+
+    /// Creates a 'set_openbook_market' instruction signed by the config admin.
+    pub fn set_openbook_market(
+        &self,
+        openbook_market: Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        instruction::set_openbook_market(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            openbook_market,
+        )
+    }
+
+    /// Creates a 'set_curve_type' instruction signed by the config admin.
+    pub fn set_curve_type(&self, curve_type: CurveType) -> Result<Instruction, ProgramError> {
+        instruction::set_curve_type(
+            self.program_id,
+            self.config_pubkey,
+            self.swap_pubkey,
+            self.config.admin_key,
+            curve_type,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::PROGRAM_VERSION;
+    use solana_program::instruction::AccountMeta;
+
+    fn test_client() -> SwapClient {
+        let program_id = crate::id();
+        let config_pubkey = Pubkey::new_unique();
+        let swap_pubkey = Pubkey::new_unique();
+        let (_, bump_seed) = find_market_authority(&program_id, &config_pubkey);
+        let (_, nonce) = find_swap_authority(&program_id, &swap_pubkey);
+
+        SwapClient::new(
+            program_id,
+            config_pubkey,
+            ConfigInfo {
+                version: PROGRAM_VERSION,
+                bump_seed,
+                admin_key: Pubkey::new_unique(),
+                deltafi_mint: Pubkey::new_unique(),
+                ..ConfigInfo::default()
+            },
+            swap_pubkey,
+            SwapInfo {
+                is_initialized: true,
+                nonce,
+                token_a: Pubkey::new_unique(),
+                token_b: Pubkey::new_unique(),
+                pool_mint: Pubkey::new_unique(),
+                admin_fee_key_a: Pubkey::new_unique(),
+                admin_fee_key_b: Pubkey::new_unique(),
+                pyth_a: Pubkey::new_unique(),
+                pyth_b: Pubkey::new_unique(),
+                config_key: config_pubkey,
+                ..SwapInfo::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_authorities() {
+        let client = test_client();
+        assert_eq!(
+            client.market_authority().unwrap(),
+            find_market_authority(&client.program_id, &client.config_pubkey).0
+        );
+        assert_eq!(
+            client.swap_authority().unwrap(),
+            find_swap_authority(&client.program_id, &client.swap_pubkey).0
+        );
+    }
+
+    #[test]
+    fn test_admin_fee_destination() {
+        let mut client = test_client();
+        assert_eq!(
+            client.admin_fee_destination(SwapDirection::SellQuote),
+            client.swap.admin_fee_key_a
+        );
+
+        // the base trade fee is sold for quote
+        client.swap.is_fee_in_quote = true;
+        for swap_direction in [SwapDirection::SellBase, SwapDirection::SellQuote] {
+            assert_eq!(
+                client.admin_fee_destination(swap_direction),
+                client.swap.admin_fee_key_b
+            );
+        }
+    }
+
+    #[test]
+    fn test_swap_accounts() {
+        let client = test_client();
+        let user_transfer_authority = Pubkey::new_unique();
+        let base = Pubkey::new_unique();
+        let quote = Pubkey::new_unique();
+        let reward_token = Pubkey::new_unique();
+
+        for (swap_direction, admin_fee_key) in [
+            (SwapDirection::SellBase, client.swap.admin_fee_key_b),
+            (SwapDirection::SellQuote, client.swap.admin_fee_key_a),
+        ] {
+            let swap_data = SwapData {
+                amount_in: 1_000,
+                minimum_amount_out: 900,
+                swap_direction,
+                max_price_impact_bps: 0,
+            };
+            let ix = client
+                .swap(
+                    user_transfer_authority,
+                    base,
+                    quote,
+                    reward_token,
+                    swap_data.clone(),
+                )
+                .unwrap();
+            let expected = instruction::swap(
+                client.program_id,
+                client.config_pubkey,
+                client.swap_pubkey,
+                client.market_authority().unwrap(),
+                client.swap_authority().unwrap(),
+                user_transfer_authority,
+                base,
+                client.swap.token_a,
+                client.swap.token_b,
+                quote,
+                reward_token,
+                client.config.deltafi_mint,
+                admin_fee_key,
+                client.swap.pyth_a,
+                client.swap.pyth_b,
+                client.swap.pool_mint,
+                swap_data,
+            )
+            .unwrap();
+            assert_eq!(ix, expected);
+        }
+    }
+
+    #[test]
+    fn test_buyback_and_burn_accounts() {
+        let client = test_client();
+        let mut deltafi_pool = test_client();
+        let buyback_data = BuybackAndBurn {
+            amount_in: 1_000,
+            minimum_amount_out: 900,
+        };
+
+        for (deltafi_is_quote, admin_fee_key) in [
+            (true, deltafi_pool.swap.admin_fee_key_b),
+            (false, deltafi_pool.swap.admin_fee_key_a),
+        ] {
+            if deltafi_is_quote {
+                deltafi_pool.swap.token_b_mint = client.config.deltafi_mint;
+            } else {
+                deltafi_pool.swap.token_a_mint = client.config.deltafi_mint;
+                deltafi_pool.swap.token_b_mint = Pubkey::new_unique();
+            }
+            let ix = client
+                .buyback_and_burn(
+                    client.swap.admin_fee_key_a,
+                    &deltafi_pool,
+                    buyback_data.clone(),
+                )
+                .unwrap();
+            let expected = instruction::buyback_and_burn(
+                client.program_id,
+                client.config_pubkey,
+                client.swap_pubkey,
+                client.swap_authority().unwrap(),
+                client.config.admin_key,
+                client.swap.admin_fee_key_a,
+                deltafi_pool.swap_pubkey,
+                deltafi_pool.swap_authority().unwrap(),
+                deltafi_pool.swap.token_a,
+                deltafi_pool.swap.token_b,
+                admin_fee_key,
+                client.config.deltafi_mint,
+                deltafi_pool.swap.pyth_a,
+                deltafi_pool.swap.pyth_b,
+                deltafi_pool.swap.pool_mint,
+                buyback_data.clone(),
+            )
+            .unwrap();
+            assert_eq!(ix, expected);
+        }
+    }
+
+    #[test]
+    fn test_native_sol_accounts() {
+        let mut client = test_client();
+        let wallet = Pubkey::new_unique();
+        let native_account = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let reward_token = Pubkey::new_unique();
+        let swap_data = SwapData {
+            amount_in: 1_000,
+            minimum_amount_out: 900,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        };
+        assert_eq!(
+            client
+                .swap_native(
+                    wallet,
+                    native_account,
+                    token,
+                    reward_token,
+                    swap_data.clone()
+                )
+                .err(),
+            Some(SwapError::IncorrectMint.into())
+        );
+
+        client.swap.token_b_mint = spl_token::native_mint::id();
+        let ix = client
+            .swap_native(
+                wallet,
+                native_account,
+                token,
+                reward_token,
+                swap_data.clone(),
+            )
+            .unwrap();
+        let expected = instruction::wrap_native_sol(
+            client
+                .swap(wallet, token, native_account, reward_token, swap_data)
+                .unwrap(),
+            wallet,
+            native_account,
+        )
+        .unwrap();
+        assert_eq!(ix, expected);
+
+        let deposit_data = DepositData {
+            token_a_amount: 1_000,
+            token_b_amount: 1_000,
+            min_mint_amount: 0,
+        };
+        client.swap.token_a_mint = spl_token::native_mint::id();
+        client.swap.token_b_mint = Pubkey::new_unique();
+        let ix = client
+            .deposit_native(
+                wallet,
+                native_account,
+                token,
+                token,
+                token,
+                wallet,
+                deposit_data.clone(),
+            )
+            .unwrap();
+        let expected = instruction::wrap_native_sol(
+            client
+                .deposit(
+                    wallet,
+                    native_account,
+                    token,
+                    token,
+                    token,
+                    wallet,
+                    deposit_data,
+                )
+                .unwrap(),
+            wallet,
+            native_account,
+        )
+        .unwrap();
+        assert_eq!(ix, expected);
+    }
+
+    #[test]
+    fn test_associated_token_accounts() {
+        let mut client = test_client();
+        client.swap.token_a_mint = Pubkey::new_unique();
+        client.swap.token_b_mint = Pubkey::new_unique();
+        let liquidity_provider = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+
+        let ix = client
+            .claim_liquidity_rewards_to_associated_token_account(liquidity_provider, owner, payer)
+            .unwrap();
+        let expected = client
+            .claim_liquidity_rewards(
+                liquidity_provider,
+                owner,
+                get_associated_token_address(&owner, &client.config.deltafi_mint),
+            )
+            .unwrap();
+        assert_eq!(
+            ix.accounts[..expected.accounts.len()],
+            expected.accounts[..]
+        );
+        assert_eq!(
+            ix.accounts[expected.accounts.len()..]
+                .iter()
+                .map(|meta| meta.pubkey)
+                .collect::<Vec<_>>(),
+            vec![
+                spl_associated_token_account::id(),
+                payer,
+                solana_program::system_program::id(),
+                client.config.deltafi_mint,
+            ]
+        );
+
+        let withdraw_data = WithdrawData {
+            pool_token_amount: 1_000,
+            minimum_token_a_amount: 0,
+            minimum_token_b_amount: 0,
+        };
+        let ix = client
+            .withdraw_to_associated_token_accounts(
+                owner,
+                Pubkey::new_unique(),
+                liquidity_provider,
+                owner,
+                payer,
+                withdraw_data,
+            )
+            .unwrap();
+        assert_eq!(
+            ix.accounts[7].pubkey,
+            get_associated_token_address(&owner, &client.swap.token_a_mint)
+        );
+        assert_eq!(
+            ix.accounts[8].pubkey,
+            get_associated_token_address(&owner, &client.swap.token_b_mint)
+        );
+        assert_eq!(ix.accounts[19].pubkey, spl_associated_token_account::id());
+        assert_eq!(ix.accounts[20], AccountMeta::new(payer, true));
+        assert_eq!(ix.accounts[22].pubkey, client.swap.token_a_mint);
+        assert_eq!(ix.accounts[23].pubkey, client.swap.token_b_mint);
+        assert_eq!(ix.accounts.len(), 24);
+    }
+
+    #[test]
+    fn test_transfer_hook_accounts() {
+        let mut client = test_client();
+        let swap_data = SwapData {
+            amount_in: 1_000,
+            minimum_amount_out: 900,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        };
+        let deposit_data = DepositData {
+            token_a_amount: 1_000,
+            token_b_amount: 1_000,
+            min_mint_amount: 0,
+        };
+        let keys: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let swap = |client: &SwapClient| {
+            client
+                .swap(keys[0], keys[1], keys[2], keys[3], swap_data.clone())
+                .unwrap()
+        };
+        let deposit = |client: &SwapClient| {
+            client
+                .deposit(
+                    keys[0],
+                    keys[1],
+                    keys[2],
+                    keys[3],
+                    keys[4],
+                    keys[4],
+                    deposit_data.clone(),
+                )
+                .unwrap()
+        };
+        let swap_len = swap(&client).accounts.len();
+        let deposit_len = deposit(&client).accounts.len();
+
+        client.swap.transfer_hook_program = Pubkey::new_unique();
+        for (ix, len) in [(swap(&client), swap_len), (deposit(&client), deposit_len)] {
+            assert_eq!(ix.accounts.len(), len + 1);
+            let hook = ix.accounts.last().unwrap();
+            assert_eq!(hook.pubkey, client.swap.transfer_hook_program);
+            assert!(!hook.is_writable && !hook.is_signer);
+        }
+    }
+
+    #[test]
+    fn test_openbook_market_accounts() {
+        let mut client = test_client();
+        let swap_data = SwapData {
+            amount_in: 1_000,
+            minimum_amount_out: 900,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        };
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let swap = |client: &SwapClient| {
+            client.swap(keys[0], keys[1], keys[2], keys[3], swap_data.clone())
+        };
+        let swap_len = swap(&client).unwrap().accounts.len();
+
+        // the market is not passed without its bids and asks
+        client.swap.openbook_market = Pubkey::new_unique();
+        client.swap.transfer_hook_program = Pubkey::new_unique();
+        assert_eq!(
+            swap(&client).unwrap_err(),
+            ProgramError::from(SwapError::IncorrectOracleAccount)
+        );
+
+        let book = (Pubkey::new_unique(), Pubkey::new_unique());
+        client.openbook_book = Some(book);
+        let ix = swap(&client).unwrap();
+        assert_eq!(ix.accounts.len(), swap_len + 4);
+        let pubkeys: Vec<Pubkey> = ix.accounts[swap_len..].iter().map(|a| a.pubkey).collect();
+        assert_eq!(
+            pubkeys,
+            vec![
+                client.swap.openbook_market,
+                book.0,
+                book.1,
+                client.swap.transfer_hook_program
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dca_accounts() {
+        let mut client = test_client();
+        client.swap.token_a_mint = Pubkey::new_unique();
+        client.swap.token_b_mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let owner_token = Pubkey::new_unique();
+        let reward_token = Pubkey::new_unique();
+        let keeper_token = Pubkey::new_unique();
+        let (dca_plan_pubkey, bump_seed) =
+            find_dca_plan_address(&client.program_id, &client.swap_pubkey, &owner);
+
+        for swap_direction in [SwapDirection::SellBase, SwapDirection::SellQuote] {
+            let mint = client.mint_sold(swap_direction);
+            let escrow = get_associated_token_address(&dca_plan_pubkey, &mint);
+            let plan_data = CreateDcaPlanData {
+                amount: 100_000,
+                amount_per_period: 10_000,
+                interval: 86_400,
+                swap_direction,
+                max_price_impact_bps: 50,
+                keeper_reward_bps: 10,
+            };
+            let ix = client
+                .create_dca_plan(owner, owner_token, owner, plan_data.clone())
+                .unwrap();
+            assert_eq!(ix.accounts[1].pubkey, escrow);
+            assert_eq!(ix.accounts[5].pubkey, mint);
+
+            let mut data = vec![0u8; crate::state::DCA_PLAN_SIZE];
+            DcaPlan::init(
+                &mut data,
+                bump_seed,
+                client.swap_pubkey,
+                owner,
+                escrow,
+                &plan_data,
+                0,
+            )
+            .unwrap();
+            let ix = client
+                .execute_dca(
+                    DcaPlan::load(&data).unwrap(),
+                    owner_token,
+                    reward_token,
+                    keeper_token,
+                )
+                .unwrap();
+            assert_eq!(ix.accounts[0].pubkey, dca_plan_pubkey);
+            assert_eq!(ix.accounts[1].pubkey, keeper_token);
+            // the escrow is sold from, the owner token account receives
+            let (base, quote) = match swap_direction {
+                SwapDirection::SellBase => (escrow, owner_token),
+                SwapDirection::SellQuote => (owner_token, escrow),
+            };
+            assert_eq!(ix.accounts[6].pubkey, dca_plan_pubkey);
+            assert!(!ix.accounts[6].is_signer);
+            assert_eq!(ix.accounts[7].pubkey, base);
+            assert_eq!(ix.accounts[10].pubkey, quote);
+            assert_eq!(ix.accounts[11].pubkey, reward_token);
+
+            let ix = client
+                .cancel_dca(DcaPlan::load(&data).unwrap(), owner_token)
+                .unwrap();
+            assert_eq!(ix.accounts[0].pubkey, dca_plan_pubkey);
+            assert_eq!(ix.accounts[1].pubkey, escrow);
+            assert_eq!(ix.accounts[2].pubkey, owner);
+            assert_eq!(ix.accounts[3].pubkey, owner_token);
+        }
+    }
+}
@@ -148,7 +148,8 @@ pub fn get_target_amount_reverse_direction(
         adjusted_reserve.try_add(square_root)?
     };
 
-    let candidate_reserve = numerator.try_div(denominator)?;
+    // round the remaining reserve up, so the amount out is rounded down
+    let candidate_reserve = numerator.try_div_ceil(denominator)?;
     if candidate_reserve > current_reserve {
         Ok(Decimal::zero())
     } else {
@@ -293,7 +294,7 @@ mod tests {
                             adjusted_reserve.try_add(square_root)?
                         };
 
-                        let target_reserve = numerator.try_div(denominator)?;
+                        let target_reserve = numerator.try_div_ceil(denominator)?;
                         if target_reserve > current_reserve {
                             Decimal::zero()
                         } else {
@@ -0,0 +1,262 @@
+//! V3 curve, widening the slope as the inventory deviates from its target
+
+use super::*;
+use crate::{
+    error::SwapError,
+    math::{Decimal, TryAdd, TryDiv, TryMul, TrySub},
+    state::{pack_decimal, unpack_decimal},
+};
+
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{Pack, Sealed},
+};
+
+/// Shape of the v3 curve, the slope of the pool growing from its own value at
+/// the peg to the max slope once the short token is fully drained
+///
+/// slope = pool_slope + (max_slope - pool_slope) * deviation^exponent
+/// where deviation = (target - reserve) / target of the short token.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DynamicSlope {
+    /// Slope reached when the short token reserve is empty
+    pub max_slope: Decimal,
+    /// The higher the exponent is, the longer the slope stays flat near the peg
+    pub exponent: u64,
+}
+
+impl DynamicSlope {
+    /// Create new dynamic slope
+    pub fn new(max_slope: Decimal, exponent: u64) -> Result<Self, ProgramError> {
+        if max_slope > Decimal::one() || exponent == 0 {
+            return Err(SwapError::InvalidSlope.into());
+        }
+        Ok(Self {
+            max_slope,
+            exponent,
+        })
+    }
+
+    /// Get the slope of the pool at its current inventory.
+    ///
+    /// # Arguments
+    ///
+    /// * pool_state - pool state quoted with its own slope at the peg.
+    ///
+    /// # Return value
+    ///
+    /// widened slope.
+    pub fn get_slope(&self, pool_state: &PoolState) -> Result<Decimal, ProgramError> {
+        if self.max_slope <= pool_state.slope {
+            return Ok(pool_state.slope);
+        }
+        let (target, reserve) = match pool_state.multiplier {
+            Multiplier::One => return Ok(pool_state.slope),
+            Multiplier::AboveOne => (pool_state.base_target, pool_state.base_reserve),
+            Multiplier::BelowOne => (pool_state.quote_target, pool_state.quote_reserve),
+        };
+        if target.is_zero() || reserve >= target {
+            return Ok(pool_state.slope);
+        }
+        let deviation = target.try_sub(reserve)?.try_div(target)?;
+        self.max_slope
+            .try_sub(pool_state.slope)?
+            .try_mul(deviation.try_pow(self.exponent)?)?
+            .try_add(pool_state.slope)
+    }
+
+    /// Get the pool state to quote a trade on the v3 curve, the slope being
+    /// set from the inventory before the trade.
+    ///
+    /// # Arguments
+    ///
+    /// * pool_state - pool state quoted with its own slope at the peg.
+    ///
+    /// # Return value
+    ///
+    /// pool state with the widened slope.
+    pub fn apply(&self, pool_state: &PoolState) -> Result<PoolState, ProgramError> {
+        let slope = self.get_slope(pool_state)?;
+        Ok(PoolState {
+            slope,
+            ..pool_state.clone()
+        })
+    }
+}
+
+/// Curve a token-swap quotes its swaps on
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CurveType {
+    /// Slope of the pool at any inventory
+    Standard,
+    /// Slope widened as the inventory deviates from its target
+    DynamicSlope(DynamicSlope),
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for CurveType {
+    fn default() -> Self {
+        Self::Standard
+    }
+}
+
+impl CurveType {
+    /// Get the pool state to quote a trade on the curve, the stored pool
+    /// state being left as is.
+    ///
+    /// # Arguments
+    ///
+    /// * pool_state - pool state quoted with its own slope at the peg.
+    ///
+    /// # Return value
+    ///
+    /// pool state with the slope of the curve.
+    pub fn apply(&self, pool_state: &PoolState) -> Result<PoolState, ProgramError> {
+        match self {
+            Self::Standard => Ok(pool_state.clone()),
+            Self::DynamicSlope(dynamic_slope) => dynamic_slope.apply(pool_state),
+        }
+    }
+}
+
+impl Sealed for CurveType {}
+
+/// CurveType packed size
+pub const CURVE_TYPE_SIZE: usize = 25; // 1 + 16 + 8
+impl Pack for CurveType {
+    const LEN: usize = CURVE_TYPE_SIZE;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, CURVE_TYPE_SIZE];
+        let (tag, max_slope, exponent) = mut_array_refs![output, 1, 16, 8];
+        match self {
+            Self::Standard => {
+                tag[0] = 0;
+                pack_decimal(Decimal::zero(), max_slope);
+                *exponent = 0u64.to_le_bytes();
+            }
+            Self::DynamicSlope(dynamic_slope) => {
+                tag[0] = 1;
+                pack_decimal(dynamic_slope.max_slope, max_slope);
+                *exponent = dynamic_slope.exponent.to_le_bytes();
+            }
+        }
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, CURVE_TYPE_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (tag, max_slope, exponent) = array_refs![input, 1, 16, 8];
+        match tag[0] {
+            0 => Ok(Self::Standard),
+            1 => Ok(Self::DynamicSlope(DynamicSlope::new(
+                unpack_decimal(max_slope),
+                u64::from_le_bytes(*exponent),
+            )?)),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool_state(multiplier: Multiplier, quote_reserve: u64) -> PoolState {
+        PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000u64),
+            quote_target: Decimal::from(100_000u64),
+            base_reserve: Decimal::from(1_000u64),
+            quote_reserve: Decimal::from(quote_reserve),
+            multiplier,
+            ..PoolState::default()
+        }
+    }
+
+    #[test]
+    fn test_get_slope() {
+        let curve = DynamicSlope::new(Decimal::from_percent(90), 2).unwrap();
+
+        // the pool slope applies at the peg
+        let pool = pool_state(Multiplier::One, 100_000);
+        assert_eq!(curve.get_slope(&pool).unwrap(), default_slope());
+
+        // a quarter of the quote target drained widens the slope by 0.4 / 16
+        let pool = pool_state(Multiplier::BelowOne, 75_000);
+        assert_eq!(
+            curve.get_slope(&pool).unwrap(),
+            Decimal::from(525u64).try_div(1_000).unwrap()
+        );
+
+        // the max slope is reached on an empty reserve
+        let pool = pool_state(Multiplier::BelowOne, 0);
+        assert_eq!(curve.get_slope(&pool).unwrap(), Decimal::from_percent(90));
+
+        // a max slope below the pool slope never narrows it
+        let curve = DynamicSlope::new(Decimal::from_percent(10), 1).unwrap();
+        let pool = pool_state(Multiplier::BelowOne, 75_000);
+        assert_eq!(curve.get_slope(&pool).unwrap(), default_slope());
+
+        assert_eq!(
+            DynamicSlope::new(Decimal::from(2u64), 1).unwrap_err(),
+            SwapError::InvalidSlope.into()
+        );
+        assert_eq!(
+            DynamicSlope::new(Decimal::one(), 0).unwrap_err(),
+            SwapError::InvalidSlope.into()
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let curve = DynamicSlope::new(Decimal::from_percent(90), 1).unwrap();
+        let pool = pool_state(Multiplier::BelowOne, 50_000);
+        let widened = curve.apply(&pool).unwrap();
+        assert_eq!(widened.slope, Decimal::from_percent(70));
+
+        // further sales of the long token get less on the widened curve
+        let (amount_out, _) = pool.sell_base_token(100).unwrap();
+        let (widened_amount_out, _) = widened.sell_base_token(100).unwrap();
+        assert!(widened_amount_out < amount_out);
+
+        // the standard curve quotes on the pool slope
+        assert_eq!(CurveType::Standard.apply(&pool).unwrap(), pool);
+        assert_eq!(
+            CurveType::DynamicSlope(curve).apply(&pool).unwrap(),
+            widened
+        );
+    }
+
+    #[test]
+    fn test_pack_curve_type() {
+        let mut packed = [0u8; CURVE_TYPE_SIZE];
+        CurveType::default().pack_into_slice(&mut packed);
+        assert_eq!(packed, [0u8; CURVE_TYPE_SIZE]);
+        assert_eq!(
+            CurveType::unpack_from_slice(&packed).unwrap(),
+            CurveType::Standard
+        );
+
+        let curve_type =
+            CurveType::DynamicSlope(DynamicSlope::new(Decimal::from_percent(90), 2).unwrap());
+        curve_type.pack_into_slice(&mut packed);
+        assert_eq!(packed[0], 1);
+        assert_eq!(CurveType::unpack_from_slice(&packed).unwrap(), curve_type);
+
+        // a zero exponent is not a dynamic slope
+        packed[17..].copy_from_slice(&0u64.to_le_bytes());
+        assert_eq!(
+            CurveType::unpack_from_slice(&packed).unwrap_err(),
+            SwapError::InvalidSlope.into()
+        );
+        packed[0] = 2;
+        assert_eq!(
+            CurveType::unpack_from_slice(&packed).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+}
@@ -1,10 +1,14 @@
 //! Curve modules
 
 mod calc;
+mod curve_v3;
 mod pool;
+mod quote;
 
 pub use calc::*;
+pub use curve_v3::*;
 pub use pool::*;
+pub use quote::*;
 
 #[cfg(test)]
 use crate::math::{Decimal, HALF_WAD};
@@ -21,6 +21,7 @@ use std::{
 
 /// Multiplier status enum
 #[derive(Clone, Copy, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Multiplier {
     /// multiplier = 1
     One,
@@ -52,6 +53,7 @@ impl TryFrom<u8> for Multiplier {
 /// PoolState struct
 #[repr(C)]
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PoolState {
     /// market price
     pub market_price: Decimal,
@@ -67,6 +69,10 @@ pub struct PoolState {
     pub quote_reserve: Decimal,
     /// Multiplier status
     pub multiplier: Multiplier,
+    /// Decimals of the base token mint, packed by the token-swap
+    pub base_decimals: u8,
+    /// Decimals of the quote token mint, packed by the token-swap
+    pub quote_decimals: u8,
 }
 
 impl PoolState {
@@ -87,6 +93,58 @@ impl PoolState {
         self.quote_target = params.quote_target;
         self.quote_reserve = params.quote_reserve;
         self.multiplier = params.multiplier;
+        self.base_decimals = params.base_decimals;
+        self.quote_decimals = params.quote_decimals;
+    }
+
+    /// Price of whole tokens in token amounts, so the curve compares the
+    /// amounts of tokens with different mint decimals on a common scale.
+    ///
+    /// # Arguments
+    ///
+    /// * price - quote tokens per base token.
+    ///
+    /// # Return value
+    ///
+    /// quote token amount per base token amount.
+    pub fn amount_price(&self, price: Decimal) -> Result<Decimal, ProgramError> {
+        let scale = |decimals: u8| {
+            10u64
+                .checked_pow(decimals.into())
+                .ok_or(SwapError::CalculationFailure)
+        };
+        match self.quote_decimals.cmp(&self.base_decimals) {
+            Ordering::Equal => Ok(price),
+            Ordering::Greater => price.try_mul(scale(self.quote_decimals - self.base_decimals)?),
+            Ordering::Less => price.try_div(scale(self.base_decimals - self.quote_decimals)?),
+        }
+    }
+
+    /// Price of whole tokens from a price of token amounts, the inverse of
+    /// [amount_price](#method.amount_price).
+    ///
+    /// # Arguments
+    ///
+    /// * amount_price - quote token amount per base token amount.
+    ///
+    /// # Return value
+    ///
+    /// quote tokens per base token.
+    pub fn token_price(&self, amount_price: Decimal) -> Result<Decimal, ProgramError> {
+        let scale = |decimals: u8| {
+            10u64
+                .checked_pow(decimals.into())
+                .ok_or(SwapError::CalculationFailure)
+        };
+        match self.quote_decimals.cmp(&self.base_decimals) {
+            Ordering::Equal => Ok(amount_price),
+            Ordering::Greater => {
+                amount_price.try_div(scale(self.quote_decimals - self.base_decimals)?)
+            }
+            Ordering::Less => {
+                amount_price.try_mul(scale(self.base_decimals - self.quote_decimals)?)
+            }
+        }
     }
 
     /// Adjust pool token target.
@@ -100,7 +158,7 @@ impl PoolState {
                 self.quote_target = get_target_reserve(
                     self.quote_reserve,
                     self.base_reserve.try_sub(self.base_target)?,
-                    self.market_price,
+                    self.amount_price(self.market_price)?,
                     self.slope,
                 )?
             }
@@ -108,7 +166,7 @@ impl PoolState {
                 self.base_target = get_target_reserve(
                     self.base_reserve,
                     self.quote_reserve.try_sub(self.quote_target)?,
-                    self.market_price.reciprocal()?,
+                    self.amount_price(self.market_price)?.reciprocal()?,
                     self.slope,
                 )?
             }
@@ -168,26 +226,27 @@ impl PoolState {
         base_amount: Decimal,
         multiplier: Multiplier,
     ) -> Result<Decimal, ProgramError> {
+        let price = self.amount_price(self.market_price)?;
         match multiplier {
             Multiplier::One => get_target_amount_reverse_direction(
                 self.quote_target,
                 self.quote_target,
                 base_amount,
-                self.market_price,
+                price,
                 self.slope,
             ),
             Multiplier::AboveOne => get_target_amount(
                 self.base_target,
                 self.base_reserve.try_add(base_amount)?,
                 self.base_reserve,
-                self.market_price,
+                price,
                 self.slope,
             ),
             Multiplier::BelowOne => get_target_amount_reverse_direction(
                 self.quote_target,
                 self.quote_reserve,
                 base_amount,
-                self.market_price,
+                price,
                 self.slope,
             ),
         }
@@ -255,26 +314,27 @@ impl PoolState {
         quote_amount: Decimal,
         multiplier: Multiplier,
     ) -> Result<Decimal, ProgramError> {
+        let price = self.amount_price(self.market_price)?.reciprocal()?;
         match multiplier {
             Multiplier::One => get_target_amount_reverse_direction(
                 self.base_target,
                 self.base_target,
                 quote_amount,
-                self.market_price.reciprocal()?,
+                price,
                 self.slope,
             ),
             Multiplier::AboveOne => get_target_amount_reverse_direction(
                 self.base_target,
                 self.base_reserve,
                 quote_amount,
-                self.market_price.reciprocal()?,
+                price,
                 self.slope,
             ),
             Multiplier::BelowOne => get_target_amount(
                 self.quote_target,
                 self.quote_reserve.try_add(quote_amount)?,
                 self.quote_reserve,
-                self.market_price.reciprocal()?,
+                price,
                 self.slope,
             ),
         }
@@ -355,13 +415,14 @@ impl PoolState {
 
         let shares = if total_supply == 0 {
             // case 1. initial supply
-            let shares = if self.market_price.try_mul(base_balance)? > quote_balance {
-                quote_balance.try_div(self.market_price)?
+            let price = self.amount_price(self.market_price)?;
+            let shares = if price.try_mul(base_balance)? > quote_balance {
+                quote_balance.try_div(price)?
             } else {
                 base_balance
             };
             self.base_target = shares;
-            self.quote_target = shares.try_mul(self.market_price)?;
+            self.quote_target = shares.try_mul(price)?;
             shares
         } else if self.base_reserve > Decimal::zero() && self.quote_reserve > Decimal::zero() {
             // case 2. normal case
@@ -410,8 +471,16 @@ impl PoolState {
         let base_balance = self.base_reserve;
         let quote_balance = self.quote_reserve;
 
-        let base_amount = base_balance.try_mul(share_amount)?.try_div(total_supply)?;
-        let quote_amount = quote_balance.try_mul(share_amount)?.try_div(total_supply)?;
+        // payouts are rounded down and taken as is from the reserves, so the
+        // rounding dust stays in the pool
+        let base_amount = base_balance
+            .try_mul(share_amount)?
+            .try_div(total_supply)?
+            .try_floor_u64()?;
+        let quote_amount = quote_balance
+            .try_mul(share_amount)?
+            .try_div(total_supply)?
+            .try_floor_u64()?;
 
         self.base_target = self.base_target.try_sub(
             self.base_target
@@ -424,16 +493,14 @@ impl PoolState {
                 .try_div(total_supply)?,
         )?;
 
-        if base_amount < Decimal::from(base_min_amount)
-            || quote_amount < Decimal::from(quote_min_amount)
-        {
+        if base_amount < base_min_amount || quote_amount < quote_min_amount {
             return Err(SwapError::WithdrawNotEnough.into());
         }
 
-        self.base_reserve = self.base_reserve.try_sub(base_amount)?;
-        self.quote_reserve = self.quote_reserve.try_sub(quote_amount)?;
+        self.base_reserve = self.base_reserve.try_sub(Decimal::from(base_amount))?;
+        self.quote_reserve = self.quote_reserve.try_sub(Decimal::from(quote_amount))?;
 
-        Ok((base_amount.try_floor_u64()?, quote_amount.try_floor_u64()?))
+        Ok((base_amount, quote_amount))
     }
 
     /// Calculate deposit amount according to the reserve.
@@ -460,24 +527,31 @@ impl PoolState {
 
         let (base_in_amount, quote_in_amount) =
             if self.base_reserve.is_zero() && self.quote_reserve.is_zero() {
-                let shares = if self.market_price.try_mul(base_in_amount)? > quote_in_amount {
-                    quote_in_amount.try_div(self.market_price)?
+                let price = self.amount_price(self.market_price)?;
+                let shares = if price.try_mul(base_in_amount)? > quote_in_amount {
+                    quote_in_amount.try_div(price)?
                 } else {
                     base_in_amount
                 };
-                (shares, shares.try_mul(self.market_price)?)
+                (shares, shares.try_mul(price)?)
             } else if self.base_reserve > Decimal::zero() && self.quote_reserve > Decimal::zero() {
                 let base_increase_ratio = base_in_amount.try_div(self.base_reserve)?;
                 let quote_increase_ratio = quote_in_amount.try_div(self.quote_reserve)?;
 
+                // the matching amount is rounded up in favor of the pool,
+                // never above what the user offered
                 if base_increase_ratio < quote_increase_ratio {
                     (
                         base_in_amount,
-                        self.quote_reserve.try_mul(base_increase_ratio)?,
+                        self.quote_reserve
+                            .try_mul_ceil(base_in_amount.try_div_ceil(self.base_reserve)?)?
+                            .min(quote_in_amount),
                     )
                 } else {
                     (
-                        self.base_reserve.try_mul(quote_increase_ratio)?,
+                        self.base_reserve
+                            .try_mul_ceil(quote_in_amount.try_div_ceil(self.quote_reserve)?)?
+                            .min(base_in_amount),
                         quote_in_amount,
                     )
                 }
@@ -486,8 +560,8 @@ impl PoolState {
             };
 
         Ok((
-            base_in_amount.try_floor_u64()?,
-            quote_in_amount.try_floor_u64()?,
+            base_in_amount.try_ceil_u64()?,
+            quote_in_amount.try_ceil_u64()?,
         ))
     }
 }
@@ -538,6 +612,8 @@ impl Pack for PoolState {
             base_target: unpack_decimal(base_target),
             quote_target: unpack_decimal(quote_target),
             multiplier: multiplier[0].try_into()?,
+            // the mint decimals are packed by the token-swap
+            ..Self::default()
         })
     }
 }
@@ -647,6 +723,7 @@ mod tests {
                 base_reserve,
                 quote_reserve,
                 multiplier,
+                ..PoolState::default()
             };
             let mut new_pool_state = PoolState::default();
             new_pool_state.init(initial_state.clone());
@@ -665,6 +742,7 @@ mod tests {
                 base_reserve,
                 quote_reserve,
                 multiplier,
+                ..PoolState::default()
             };
             let pool_state = PoolState::new(initial_state.clone()).unwrap();
             initial_state.adjust_target()?;
@@ -684,6 +762,7 @@ mod tests {
                 base_reserve,
                 quote_reserve,
                 multiplier,
+                ..PoolState::default()
             };
             let mut pool_state = initial_state.clone();
             pool_state.adjust_target()?;
@@ -729,6 +808,7 @@ mod tests {
                 base_reserve,
                 quote_reserve,
                 multiplier,
+                ..PoolState::default()
             };
             let mut pool_state = initial_state.clone();
             pool_state.adjust_target().unwrap();
@@ -773,6 +853,7 @@ mod tests {
                 base_reserve,
                 quote_reserve,
                 multiplier,
+                ..PoolState::default()
             };
 
             if new_multiplier != Multiplier::AboveOne || initial_state.base_reserve.try_add(base_amount)? < initial_state.base_target {
@@ -819,6 +900,7 @@ mod tests {
                 base_reserve,
                 quote_reserve,
                 multiplier,
+                ..PoolState::default()
             };
             if new_multiplier != Multiplier::BelowOne || initial_state.quote_reserve.try_add(quote_amount)? < initial_state.quote_target {
                 let purchased_base_token_amount = initial_state.sell_quote_token_with_multiplier(quote_amount, new_multiplier).unwrap();
@@ -864,6 +946,7 @@ mod tests {
                 base_reserve,
                 quote_reserve,
                 multiplier,
+                ..PoolState::default()
             };
             let mut pool_state = initial_state.clone();
             let buy_shares_amount = pool_state.buy_shares(base_balance, quote_balance, total_supply)?;
@@ -901,6 +984,7 @@ mod tests {
                 base_reserve,
                 quote_reserve,
                 multiplier,
+                ..PoolState::default()
             };
             let mut pool_state = initial_state.clone();
             let base_balance = initial_state.base_reserve;
@@ -927,6 +1011,7 @@ mod tests {
                 base_reserve,
                 quote_reserve,
                 multiplier,
+                ..PoolState::default()
             };
             let calculate_deposit_amount = initial_state.calculate_deposit_amount(base_in_amount, quote_in_amount)?;
             let base_in_amount = Decimal::from(base_in_amount);
@@ -946,11 +1031,11 @@ mod tests {
                     if base_increase_ratio < quote_increase_ratio {
                         (
                             base_in_amount,
-                            initial_state.quote_reserve.try_mul(base_increase_ratio)?,
+                            initial_state.quote_reserve.try_mul_ceil(base_in_amount.try_div_ceil(initial_state.base_reserve)?)?.min(quote_in_amount),
                         )
                     } else {
                         (
-                            initial_state.base_reserve.try_mul(quote_increase_ratio)?,
+                            initial_state.base_reserve.try_mul_ceil(quote_in_amount.try_div_ceil(initial_state.quote_reserve)?)?.min(base_in_amount),
                             quote_in_amount,
                         )
                     }
@@ -960,11 +1045,36 @@ mod tests {
 
             assert_eq!(
                 calculate_deposit_amount,
-                (base_in_amount.try_floor_u64()?,quote_in_amount.try_floor_u64()?)
+                (base_in_amount.try_ceil_u64()?,quote_in_amount.try_ceil_u64()?)
             );
         }
     }
 
+    #[test]
+    fn test_rounding_in_favor_of_pool() {
+        let mut pool_state = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000u64),
+            quote_target: Decimal::from(1_001u64),
+            base_reserve: Decimal::from(1_000u64),
+            quote_reserve: Decimal::from(1_001u64),
+            multiplier: Multiplier::One,
+            ..PoolState::default()
+        };
+
+        // 1 base matches 1.001 quote, rounded up
+        assert_eq!(
+            pool_state.calculate_deposit_amount(1, 1_000).unwrap(),
+            (1, 2)
+        );
+
+        // payouts are rounded down and the dust stays in the reserves
+        assert_eq!(pool_state.sell_shares(1, 0, 0, 3).unwrap(), (333, 333));
+        assert_eq!(pool_state.base_reserve, Decimal::from(667u64));
+        assert_eq!(pool_state.quote_reserve, Decimal::from(668u64));
+    }
+
     #[test]
     fn test_one_sell_token() {
         let pool_state = PoolState {
@@ -975,6 +1085,7 @@ mod tests {
             base_reserve: Decimal::from(1_000_000_000u64),
             quote_reserve: Decimal::from(1_000_000_000u64),
             multiplier: Multiplier::One,
+            ..PoolState::default()
         };
 
         let quote_token = pool_state.sell_base_token(100u64).unwrap();
@@ -999,6 +1110,7 @@ mod tests {
             base_reserve: Decimal::from(100_000u64),
             quote_reserve: Decimal::from(100_000u64),
             multiplier: Multiplier::BelowOne,
+            ..PoolState::default()
         };
         assert!(pool_state.get_mid_price().is_err());
         assert!(pool_state.adjust_target().is_err());
@@ -1012,6 +1124,7 @@ mod tests {
             base_reserve: Decimal::from(1_000_000_000u64),
             quote_reserve: Decimal::from(500_000_000u64),
             multiplier: Multiplier::One,
+            ..PoolState::default()
         };
         assert_eq!(
             pool_state.buy_shares(1_000_000_000u64, 500_000_000u64, 1_000_000_000u64),
@@ -1046,6 +1159,7 @@ mod tests {
             base_reserve: Decimal::from(1_000_000_000u64),
             quote_reserve: Decimal::from(500_000_000u64),
             multiplier: Multiplier::One,
+            ..PoolState::default()
         };
 
         let mut packed = [0u8; PoolState::LEN];
@@ -1053,4 +1167,83 @@ mod tests {
         let unpacked = PoolState::unpack_from_slice(&packed).unwrap();
         assert_eq!(pool_state, unpacked);
     }
+
+    #[test]
+    fn test_amount_price() {
+        let mut pool_state = PoolState::default();
+        let price = Decimal::from(100u64);
+        assert_eq!(pool_state.amount_price(price).unwrap(), price);
+
+        pool_state.base_decimals = 9;
+        pool_state.quote_decimals = 6;
+        assert_eq!(
+            pool_state.amount_price(price).unwrap(),
+            Decimal::one().try_div(10).unwrap()
+        );
+
+        pool_state.base_decimals = 6;
+        pool_state.quote_decimals = 9;
+        assert_eq!(
+            pool_state.amount_price(price).unwrap(),
+            Decimal::from(100_000u64)
+        );
+
+        pool_state.quote_decimals = 255;
+        assert_eq!(
+            pool_state.amount_price(price),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_token_price() {
+        let mut pool_state = PoolState::default();
+        let price = Decimal::from(100u64);
+        assert_eq!(pool_state.token_price(price).unwrap(), price);
+
+        for (base_decimals, quote_decimals) in [(9, 6), (6, 9), (6, 6)] {
+            pool_state.base_decimals = base_decimals;
+            pool_state.quote_decimals = quote_decimals;
+            assert_eq!(
+                pool_state
+                    .token_price(pool_state.amount_price(price).unwrap())
+                    .unwrap(),
+                price
+            );
+        }
+
+        pool_state.base_decimals = 255;
+        assert_eq!(
+            pool_state.token_price(price),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_mismatched_decimals() {
+        // 1 base token of 9 decimals is worth 100 quote tokens of 6 decimals
+        let mut pool_state = PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            multiplier: Multiplier::One,
+            base_decimals: 9,
+            quote_decimals: 6,
+            ..PoolState::default()
+        })
+        .unwrap();
+        pool_state
+            .buy_shares(1_000_000_000_000, 100_000_000_000, 0)
+            .unwrap();
+        assert_eq!(pool_state.base_target, Decimal::from(1_000_000_000_000u64));
+        assert_eq!(pool_state.quote_target, Decimal::from(100_000_000_000u64));
+        assert_eq!(pool_state.get_mid_price().unwrap(), default_market_price());
+
+        let (quote_amount, _) = pool_state.sell_base_token(1_000_000_000).unwrap();
+        assert!(quote_amount < 100_000_000);
+        assert!(quote_amount > 99_000_000);
+
+        let (base_amount, _) = pool_state.sell_quote_token(100_000_000).unwrap();
+        assert!(base_amount < 1_000_000_000);
+        assert!(base_amount > 990_000_000);
+    }
 }
@@ -0,0 +1,1197 @@
+//! Quotes of swap, deposit and withdraw, computed with the on-chain math
+
+use super::*;
+use crate::{
+    error::SwapError,
+    instruction::SwapDirection,
+    math::{Decimal, TryAdd, TryDiv, TryMul, TrySub, BPS_DENOMINATOR},
+    state::{DirectionalFees, Fees, Rewards},
+};
+
+use solana_program::program_error::ProgramError;
+
+/// Swap quote
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapQuote {
+    /// Amount sent to the user, after trade fee
+    pub amount_out: u64,
+    /// Trade fee charged on the output amount
+    pub trade_fee: u64,
+    /// Part of the trade fee sent to the admin fee account
+    pub admin_fee: u64,
+    /// Deltafi reward minted to the user
+    pub reward_amount: u64,
+    /// Multiplier status after the swap
+    pub multiplier: Multiplier,
+}
+
+/// Withdraw quote
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WithdrawQuote {
+    /// Token A amount sent to the user, after withdraw fee
+    pub token_a_amount: u64,
+    /// Token B amount sent to the user, after withdraw fee
+    pub token_b_amount: u64,
+    /// Withdraw fee charged on token A
+    pub withdraw_fee_a: u64,
+    /// Withdraw fee charged on token B
+    pub withdraw_fee_b: u64,
+    /// Part of the token A withdraw fee sent to the admin fee account
+    pub admin_fee_a: u64,
+    /// Part of the token B withdraw fee sent to the admin fee account
+    pub admin_fee_b: u64,
+}
+
+/// Exact withdraw quote
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithdrawExactQuote {
+    /// Pool token amount to burn
+    pub pool_token_amount: u64,
+    /// Withdraw fee charged on token A
+    pub withdraw_fee_a: u64,
+    /// Withdraw fee charged on token B
+    pub withdraw_fee_b: u64,
+    /// Part of the token A withdraw fee sent to the admin fee account
+    pub admin_fee_a: u64,
+    /// Part of the token B withdraw fee sent to the admin fee account
+    pub admin_fee_b: u64,
+    /// Pool state after the withdraw
+    pub pool_state: PoolState,
+}
+
+/// Imbalanced deposit quote
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepositImbalancedQuote {
+    /// Pool token amount minted
+    pub pool_token_amount: u64,
+    /// Direction of the internal swap rebalancing the deposit
+    pub swap_direction: SwapDirection,
+    /// Amount of the excess token swapped, zero if the deposit is balanced
+    pub swap_amount_in: u64,
+    /// Trade fee charged on the swapped output
+    pub trade_fee: u64,
+    /// Part of the trade fee sent to the admin fee account of the output token
+    pub admin_fee: u64,
+    /// Pool state after the deposit
+    pub pool_state: PoolState,
+}
+
+impl SwapQuote {
+    /// Discount the trade fee of a swap, the discount going to the user.
+    ///
+    /// # Arguments
+    ///
+    /// * discount_bps - discount of the trade fee, in basis points.
+    /// * fees - pool fees.
+    ///
+    /// # Return value
+    ///
+    /// discounted swap quote.
+    pub fn discount_trade_fee(&self, discount_bps: u64, fees: &Fees) -> Result<Self, ProgramError> {
+        let discount = (self.trade_fee as u128 * discount_bps.min(BPS_DENOMINATOR) as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        let trade_fee = self.trade_fee - discount;
+        Ok(Self {
+            amount_out: self
+                .amount_out
+                .checked_add(discount)
+                .ok_or(SwapError::CalculationFailure)?,
+            trade_fee,
+            admin_fee: fees.admin_trade_fee(trade_fee)?,
+            ..self.clone()
+        })
+    }
+}
+
+impl PoolState {
+    /// Quote a swap against the pool.
+    /// Pool state is expected to be adjusted, as returned by [`PoolState::new`].
+    ///
+    /// # Arguments
+    ///
+    /// * amount_in - amount of token sold.
+    /// * swap_direction - sell base or sell quote.
+    /// * fees - pool fees.
+    /// * rewards - pool rewards.
+    ///
+    /// # Return value
+    ///
+    /// swap quote.
+    pub fn quote_swap(
+        &self,
+        amount_in: u64,
+        swap_direction: SwapDirection,
+        fees: &Fees,
+        rewards: &Rewards,
+    ) -> Result<SwapQuote, ProgramError> {
+        let (receive_amount, multiplier) = match swap_direction {
+            SwapDirection::SellBase => self.sell_base_token(amount_in)?,
+            SwapDirection::SellQuote => self.sell_quote_token(amount_in)?,
+        };
+        let trade_fee = fees.trade_fee(receive_amount)?;
+        let admin_fee = fees.admin_trade_fee(trade_fee)?;
+        let amount_out = receive_amount
+            .checked_sub(trade_fee)
+            .ok_or(SwapError::CalculationFailure)?;
+        // rewards follow the traded value in the quote token, whatever the token sold
+        let traded_value = match swap_direction {
+            SwapDirection::SellBase => Decimal::from(amount_in)
+                .try_mul(self.amount_price(self.market_price)?)?
+                .try_floor_u64()?,
+            SwapDirection::SellQuote => amount_in,
+        };
+
+        Ok(SwapQuote {
+            amount_out,
+            trade_fee,
+            admin_fee,
+            reward_amount: rewards.trade_reward_u64(traded_value)?,
+            multiplier,
+        })
+    }
+
+    /// Keep the lp share of a swap trade fee in the pool.
+    /// The output reserve is expected to still hold the whole trade fee: the
+    /// admin fee leaves it, and the lp fee is added to the output target so it
+    /// grows the value of the pool tokens instead of skewing the curve.
+    ///
+    /// # Arguments
+    ///
+    /// * swap_direction - sell base or sell quote.
+    /// * trade_fee - trade fee charged on the swap output.
+    /// * admin_fee - part of the trade fee sent to the admin fee account.
+    ///
+    /// # Return value
+    ///
+    /// adjusted pool state.
+    pub fn compound_trade_fee(
+        &self,
+        swap_direction: SwapDirection,
+        trade_fee: u64,
+        admin_fee: u64,
+    ) -> Result<Self, ProgramError> {
+        let lp_fee = trade_fee
+            .checked_sub(admin_fee)
+            .ok_or(SwapError::CalculationFailure)?;
+        let mut pool_state = self.clone();
+        match swap_direction {
+            SwapDirection::SellBase => {
+                pool_state.quote_reserve = self.quote_reserve.try_sub(Decimal::from(admin_fee))?;
+                pool_state.quote_target = self.quote_target.try_add(Decimal::from(lp_fee))?;
+            }
+            SwapDirection::SellQuote => {
+                pool_state.base_reserve = self.base_reserve.try_sub(Decimal::from(admin_fee))?;
+                pool_state.base_target = self.base_target.try_add(Decimal::from(lp_fee))?;
+            }
+        }
+        PoolState::new(pool_state)
+    }
+
+    /// Balances of the swap token accounts beyond the pool reserves, left by
+    /// tokens transferred to them directly.
+    ///
+    /// # Arguments
+    ///
+    /// * base_balance - balance of the base token swap account.
+    /// * quote_balance - balance of the quote token swap account.
+    ///
+    /// # Return value
+    ///
+    /// base and quote surplus.
+    pub fn surplus(
+        &self,
+        base_balance: u64,
+        quote_balance: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        Ok((
+            base_balance.saturating_sub(self.base_reserve.try_ceil_u64()?),
+            quote_balance.saturating_sub(self.quote_reserve.try_ceil_u64()?),
+        ))
+    }
+
+    /// Fold a surplus into the pool. Like a compounded trade fee, it is added
+    /// to both the reserve and the target of its token so it grows the value
+    /// of the pool tokens instead of skewing the curve.
+    ///
+    /// # Arguments
+    ///
+    /// * base_surplus - base tokens held beyond the base reserve.
+    /// * quote_surplus - quote tokens held beyond the quote reserve.
+    ///
+    /// # Return value
+    ///
+    /// adjusted pool state.
+    pub fn fold_surplus(
+        &self,
+        base_surplus: u64,
+        quote_surplus: u64,
+    ) -> Result<Self, ProgramError> {
+        let (base_surplus, quote_surplus) =
+            (Decimal::from(base_surplus), Decimal::from(quote_surplus));
+        PoolState::new(PoolState {
+            base_reserve: self.base_reserve.try_add(base_surplus)?,
+            quote_reserve: self.quote_reserve.try_add(quote_surplus)?,
+            base_target: self.base_target.try_add(base_surplus)?,
+            quote_target: self.quote_target.try_add(quote_surplus)?,
+            ..self.clone()
+        })
+    }
+
+    /// Reset the pool to the token balances at the market price. The targets
+    /// are the balances, so the multiplier is back to one and the mid price
+    /// is the market price.
+    ///
+    /// # Arguments
+    ///
+    /// * base_balance - base tokens held by the pool.
+    /// * quote_balance - quote tokens held by the pool.
+    /// * market_price - market price the pool is reset at.
+    ///
+    /// # Return value
+    ///
+    /// recalibrated pool state.
+    pub fn recalibrate(
+        &self,
+        base_balance: u64,
+        quote_balance: u64,
+        market_price: Decimal,
+    ) -> Result<Self, ProgramError> {
+        PoolState::new(PoolState {
+            market_price,
+            base_reserve: Decimal::from(base_balance),
+            quote_reserve: Decimal::from(quote_balance),
+            base_target: Decimal::from(base_balance),
+            quote_target: Decimal::from(quote_balance),
+            multiplier: Multiplier::One,
+            ..self.clone()
+        })
+    }
+
+    /// Trade fee of a swap selling the quote token, paid in the base token,
+    /// converted to the quote token by selling it to the pool.
+    /// Pool state is expected to be the one the swap was quoted on.
+    ///
+    /// # Arguments
+    ///
+    /// * trade_fee - trade fee in the base token.
+    /// * fees - fees of the swap, for the admin share.
+    ///
+    /// # Return value
+    ///
+    /// trade fee and admin fee in the quote token.
+    pub fn quote_trade_fee_in_quote(
+        &self,
+        trade_fee: u64,
+        fees: &Fees,
+    ) -> Result<(u64, u64), ProgramError> {
+        let (trade_fee, _) = self.sell_base_token(trade_fee)?;
+        Ok((trade_fee, fees.admin_trade_fee(trade_fee)?))
+    }
+
+    /// Shortfall of a swap output against the output at the mid price.
+    /// Pool state is expected to be adjusted, as returned by [`PoolState::new`].
+    ///
+    /// # Arguments
+    ///
+    /// * amount_in - amount of token sold.
+    /// * receive_amount - amount of token bought, before trade fee.
+    /// * swap_direction - sell base or sell quote.
+    ///
+    /// # Return value
+    ///
+    /// price impact in basis points, rounded down.
+    pub fn price_impact_bps(
+        &self,
+        amount_in: u64,
+        receive_amount: u64,
+        swap_direction: SwapDirection,
+    ) -> Result<u64, ProgramError> {
+        let mid_price = self.amount_price(self.clone().get_mid_price()?)?;
+        let expected_amount = match swap_direction {
+            SwapDirection::SellBase => mid_price.try_mul(amount_in)?,
+            SwapDirection::SellQuote => Decimal::from(amount_in).try_div(mid_price)?,
+        };
+        let receive_amount = Decimal::from(receive_amount);
+        if expected_amount.is_zero() || receive_amount >= expected_amount {
+            return Ok(0);
+        }
+        Decimal::one()
+            .try_sub(receive_amount.try_div(expected_amount)?)?
+            .try_mul(BPS_DENOMINATOR)?
+            .try_floor_u64()
+    }
+
+    /// Quote a deposit into the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * token_a_amount - token a amount to deposit.
+    /// * token_b_amount - token b amount to deposit.
+    /// * total_supply - pool token supply.
+    ///
+    /// # Return value
+    ///
+    /// pool token amount minted.
+    pub fn quote_deposit(
+        &self,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        total_supply: u64,
+    ) -> Result<u64, ProgramError> {
+        let base_balance = self
+            .base_reserve
+            .try_floor_u64()?
+            .checked_add(token_a_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        let quote_balance = self
+            .quote_reserve
+            .try_floor_u64()?
+            .checked_add(token_b_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+
+        self.clone()
+            .buy_shares(base_balance, quote_balance, total_supply)
+    }
+
+    /// Amount of the excess token to sell so that an imbalanced deposit
+    /// matches the reserve ratio, priced at the mid price.
+    ///
+    /// Selling x base at price p for deposit amounts (a, b) and reserves
+    /// (Ra, Rb) solves (a - x) / (Ra + x) = (b + px) / (Rb - px):
+    ///
+    /// x = (a * Rb - b * Ra) / (p * (a + Ra) + Rb + b)
+    ///
+    /// and symmetrically for the quote token.
+    ///
+    /// # Arguments
+    ///
+    /// * base_amount - base amount to deposit.
+    /// * quote_amount - quote amount to deposit.
+    ///
+    /// # Return value
+    ///
+    /// swap direction, amount to sell.
+    pub fn get_rebalance_amount(
+        &self,
+        base_amount: u64,
+        quote_amount: u64,
+    ) -> Result<(SwapDirection, u64), ProgramError> {
+        let base_amount = Decimal::from(base_amount);
+        let quote_amount = Decimal::from(quote_amount);
+        let base_weight = base_amount.try_mul(self.quote_reserve)?;
+        let quote_weight = quote_amount.try_mul(self.base_reserve)?;
+        if base_weight == quote_weight {
+            return Ok((SwapDirection::SellBase, 0));
+        }
+
+        let mid_price = self.amount_price(self.clone().get_mid_price()?)?;
+        let denominator = base_amount
+            .try_add(self.base_reserve)?
+            .try_mul(mid_price)?
+            .try_add(self.quote_reserve)?
+            .try_add(quote_amount)?;
+        if base_weight > quote_weight {
+            Ok((
+                SwapDirection::SellBase,
+                base_weight
+                    .try_sub(quote_weight)?
+                    .try_div(denominator)?
+                    .try_floor_u64()?,
+            ))
+        } else {
+            Ok((
+                SwapDirection::SellQuote,
+                quote_weight
+                    .try_sub(base_weight)?
+                    .try_mul(mid_price)?
+                    .try_div(denominator)?
+                    .try_floor_u64()?,
+            ))
+        }
+    }
+
+    /// Quote a deposit of arbitrary amounts. The excess token is swapped
+    /// through the curve first, charging the trade fee, and the result is
+    /// deposited at the pool ratio.
+    ///
+    /// # Arguments
+    ///
+    /// * base_amount - base amount to deposit.
+    /// * quote_amount - quote amount to deposit.
+    /// * total_supply - pool token supply.
+    /// * fees - pool fees.
+    /// * directional_fees - pool trade fees per swap direction.
+    ///
+    /// # Return value
+    ///
+    /// imbalanced deposit quote.
+    pub fn quote_deposit_imbalanced(
+        &self,
+        base_amount: u64,
+        quote_amount: u64,
+        total_supply: u64,
+        fees: &Fees,
+        directional_fees: &DirectionalFees,
+    ) -> Result<DepositImbalancedQuote, ProgramError> {
+        let base_reserve = self.base_reserve.try_floor_u64()?;
+        let quote_reserve = self.quote_reserve.try_floor_u64()?;
+        let (swap_direction, swap_amount_in) =
+            self.get_rebalance_amount(base_amount, quote_amount)?;
+
+        let mut pool_state = self.clone();
+        let (mut trade_fee, mut admin_fee) = (0, 0);
+        let (mut base_balance, mut quote_balance) = (
+            base_reserve
+                .checked_add(base_amount)
+                .ok_or(SwapError::CalculationFailure)?,
+            quote_reserve
+                .checked_add(quote_amount)
+                .ok_or(SwapError::CalculationFailure)?,
+        );
+        if swap_amount_in > 0 {
+            let (receive_amount, multiplier) = match swap_direction {
+                SwapDirection::SellBase => self.sell_base_token(swap_amount_in)?,
+                SwapDirection::SellQuote => self.sell_quote_token(swap_amount_in)?,
+            };
+            let fees = directional_fees.apply(fees, swap_direction);
+            trade_fee = fees.trade_fee(receive_amount)?;
+            admin_fee = fees.admin_trade_fee(trade_fee)?;
+            let pool_out = receive_amount
+                .checked_sub(trade_fee)
+                .and_then(|amount_out| amount_out.checked_add(admin_fee))
+                .ok_or(SwapError::CalculationFailure)?;
+
+            let (base_reserve, quote_reserve) = match swap_direction {
+                SwapDirection::SellBase => {
+                    quote_balance = quote_balance
+                        .checked_sub(admin_fee)
+                        .ok_or(SwapError::CalculationFailure)?;
+                    (
+                        base_reserve.checked_add(swap_amount_in),
+                        quote_reserve.checked_sub(pool_out),
+                    )
+                }
+                SwapDirection::SellQuote => {
+                    base_balance = base_balance
+                        .checked_sub(admin_fee)
+                        .ok_or(SwapError::CalculationFailure)?;
+                    (
+                        base_reserve.checked_sub(pool_out),
+                        quote_reserve.checked_add(swap_amount_in),
+                    )
+                }
+            };
+            pool_state = PoolState::new(PoolState {
+                base_reserve: Decimal::from(base_reserve.ok_or(SwapError::CalculationFailure)?),
+                quote_reserve: Decimal::from(quote_reserve.ok_or(SwapError::CalculationFailure)?),
+                multiplier,
+                ..pool_state
+            })?;
+        }
+
+        let pool_token_amount = pool_state.buy_shares(base_balance, quote_balance, total_supply)?;
+        Ok(DepositImbalancedQuote {
+            pool_token_amount,
+            swap_direction,
+            swap_amount_in,
+            trade_fee,
+            admin_fee,
+            pool_state,
+        })
+    }
+
+    /// Quote a withdraw from the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * pool_token_amount - pool token amount to burn.
+    /// * total_supply - pool token supply.
+    /// * fees - pool fees.
+    ///
+    /// # Return value
+    ///
+    /// withdraw quote.
+    pub fn quote_withdraw(
+        &self,
+        pool_token_amount: u64,
+        total_supply: u64,
+        fees: &Fees,
+    ) -> Result<WithdrawQuote, ProgramError> {
+        let (base_out_amount, quote_out_amount) =
+            self.clone()
+                .sell_shares(pool_token_amount, 0, 0, total_supply)?;
+
+        let withdraw_fee_a = fees.withdraw_fee(base_out_amount)?;
+        let withdraw_fee_b = fees.withdraw_fee(quote_out_amount)?;
+
+        Ok(WithdrawQuote {
+            token_a_amount: base_out_amount
+                .checked_sub(withdraw_fee_a)
+                .ok_or(SwapError::CalculationFailure)?,
+            token_b_amount: quote_out_amount
+                .checked_sub(withdraw_fee_b)
+                .ok_or(SwapError::CalculationFailure)?,
+            withdraw_fee_a,
+            withdraw_fee_b,
+            admin_fee_a: fees.admin_withdraw_fee(withdraw_fee_a)?,
+            admin_fee_b: fees.admin_withdraw_fee(withdraw_fee_b)?,
+        })
+    }
+
+    /// Quote a withdraw of exact token amounts from the pool. Burns the
+    /// fewest pool tokens whose share covers both amounts after the withdraw
+    /// fee. Amounts off the pool ratio leave the surplus of the other token in
+    /// the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * base_amount - base amount sent to the user.
+    /// * quote_amount - quote amount sent to the user.
+    /// * total_supply - pool token supply.
+    /// * fees - pool fees.
+    ///
+    /// # Return value
+    ///
+    /// exact withdraw quote.
+    pub fn quote_withdraw_exact(
+        &self,
+        base_amount: u64,
+        quote_amount: u64,
+        total_supply: u64,
+        fees: &Fees,
+    ) -> Result<WithdrawExactQuote, ProgramError> {
+        if base_amount == 0 && quote_amount == 0 {
+            return Err(SwapError::InvalidInput.into());
+        }
+        let base_gross = fees.withdraw_amount_before_fee(base_amount)?;
+        let quote_gross = fees.withdraw_amount_before_fee(quote_amount)?;
+        let shares_for = |gross: u64, reserve: Decimal| -> Result<u64, ProgramError> {
+            if gross == 0 {
+                return Ok(0);
+            }
+            if reserve.is_zero() {
+                return Err(SwapError::InsufficientFunds.into());
+            }
+            Decimal::from(gross)
+                .try_mul(total_supply)?
+                .try_div_ceil(reserve)?
+                .try_ceil_u64()
+        };
+        let pool_token_amount = shares_for(base_gross, self.base_reserve)?
+            .max(shares_for(quote_gross, self.quote_reserve)?);
+        if pool_token_amount > total_supply {
+            return Err(SwapError::InsufficientFunds.into());
+        }
+
+        let mut pool_state = self.clone();
+        let (base_out_amount, quote_out_amount) =
+            pool_state.sell_shares(pool_token_amount, base_gross, quote_gross, total_supply)?;
+
+        let withdraw_fee_a = fees.withdraw_fee(base_gross)?;
+        let withdraw_fee_b = fees.withdraw_fee(quote_gross)?;
+        let admin_fee_a = fees.admin_withdraw_fee(withdraw_fee_a)?;
+        let admin_fee_b = fees.admin_withdraw_fee(withdraw_fee_b)?;
+
+        // only the user amounts and the admin fees leave the pool
+        let base_surplus = base_out_amount
+            .checked_sub(base_amount)
+            .and_then(|surplus| surplus.checked_sub(admin_fee_a))
+            .ok_or(SwapError::CalculationFailure)?;
+        let quote_surplus = quote_out_amount
+            .checked_sub(quote_amount)
+            .and_then(|surplus| surplus.checked_sub(admin_fee_b))
+            .ok_or(SwapError::CalculationFailure)?;
+        pool_state.base_reserve = pool_state
+            .base_reserve
+            .try_add(Decimal::from(base_surplus))?;
+        pool_state.quote_reserve = pool_state
+            .quote_reserve
+            .try_add(Decimal::from(quote_surplus))?;
+
+        Ok(WithdrawExactQuote {
+            pool_token_amount,
+            withdraw_fee_a,
+            withdraw_fee_b,
+            admin_fee_a,
+            admin_fee_b,
+            pool_state,
+        })
+    }
+
+    /// Value of a pool token, the reserves being valued at the mid price.
+    ///
+    /// # Arguments
+    ///
+    /// * total_supply - pool token supply.
+    ///
+    /// # Return value
+    ///
+    /// quote token amount per pool token amount.
+    pub fn lp_virtual_price(&self, total_supply: u64) -> Result<Decimal, ProgramError> {
+        let mid_price = self.amount_price(self.clone().get_mid_price()?)?;
+        self.base_reserve
+            .try_mul(mid_price)?
+            .try_add(self.quote_reserve)?
+            .try_div(total_supply)
+    }
+
+    /// Value of a pool token, the targets being valued at the market price.
+    /// Swaps move the reserves but not the targets, so the value only follows
+    /// the market price, deposits, withdraws and the fees.
+    ///
+    /// # Arguments
+    ///
+    /// * total_supply - pool token supply.
+    ///
+    /// # Return value
+    ///
+    /// quote token amount per pool token amount.
+    pub fn lp_target_price(&self, total_supply: u64) -> Result<Decimal, ProgramError> {
+        self.base_target
+            .try_mul(self.amount_price(self.market_price)?)?
+            .try_add(self.quote_target)?
+            .try_div(total_supply)
+    }
+
+    /// Lp share of a trade fee per base target, valued at the market price.
+    /// Summed over the trades it tracks the fees earned by a pool token
+    /// without the pool token supply, shares being minted and burned with the
+    /// targets.
+    ///
+    /// # Arguments
+    ///
+    /// * swap_direction - direction of the swap paying the fee.
+    /// * trade_fee - trade fee, in the token received by the trader.
+    /// * admin_fee - admin share of the trade fee.
+    ///
+    /// # Return value
+    ///
+    /// quote token amount per base target, zero for a pool without targets.
+    pub fn lp_fee_per_target(
+        &self,
+        swap_direction: SwapDirection,
+        trade_fee: u64,
+        admin_fee: u64,
+    ) -> Result<Decimal, ProgramError> {
+        let lp_fee = Decimal::from(
+            trade_fee
+                .checked_sub(admin_fee)
+                .ok_or(SwapError::CalculationFailure)?,
+        );
+        if self.base_target == Decimal::zero() {
+            return Ok(Decimal::zero());
+        }
+        let lp_fee = match swap_direction {
+            SwapDirection::SellBase => lp_fee,
+            SwapDirection::SellQuote => lp_fee.try_mul(self.amount_price(self.market_price)?)?,
+        };
+        lp_fee.try_div(self.base_target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::Decimal,
+        state::{DEFAULT_TEST_FEES, DEFAULT_TEST_REWARDS},
+    };
+
+    fn test_pool() -> (PoolState, u64) {
+        let mut pool = PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            ..PoolState::default()
+        })
+        .unwrap();
+        let total_supply = pool.buy_shares(1_000_000, 100_000_000, 0).unwrap();
+        (pool, total_supply)
+    }
+
+    #[test]
+    fn test_quote_swap() {
+        let (pool, _) = test_pool();
+        let fees = DEFAULT_TEST_FEES;
+        let rewards = DEFAULT_TEST_REWARDS;
+
+        let quote = pool
+            .quote_swap(1_000, SwapDirection::SellBase, &fees, &rewards)
+            .unwrap();
+        let (receive_amount, multiplier) = pool.sell_base_token(1_000).unwrap();
+        assert_eq!(quote.trade_fee, fees.trade_fee(receive_amount).unwrap());
+        assert_eq!(
+            quote.admin_fee,
+            fees.admin_trade_fee(quote.trade_fee).unwrap()
+        );
+        assert_eq!(quote.amount_out, receive_amount - quote.trade_fee);
+        let traded_value = Decimal::from(1_000u64)
+            .try_mul(pool.amount_price(pool.market_price).unwrap())
+            .unwrap()
+            .try_floor_u64()
+            .unwrap();
+        assert_eq!(
+            quote.reward_amount,
+            rewards.trade_reward_u64(traded_value).unwrap()
+        );
+        assert_eq!(quote.multiplier, multiplier);
+
+        let quote = pool
+            .quote_swap(100_000, SwapDirection::SellQuote, &fees, &rewards)
+            .unwrap();
+        let (receive_amount, _) = pool.sell_quote_token(100_000).unwrap();
+        assert_eq!(quote.amount_out, receive_amount - quote.trade_fee);
+        assert_eq!(
+            quote.reward_amount,
+            rewards.trade_reward_u64(100_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_discount_trade_fee() {
+        let (pool, _) = test_pool();
+        let fees = DEFAULT_TEST_FEES;
+        let quote = pool
+            .quote_swap(
+                1_000_000,
+                SwapDirection::SellQuote,
+                &fees,
+                &DEFAULT_TEST_REWARDS,
+            )
+            .unwrap();
+        assert!(quote.trade_fee > 0);
+
+        assert_eq!(quote.discount_trade_fee(0, &fees).unwrap(), quote);
+
+        let discounted = quote.discount_trade_fee(2_500, &fees).unwrap();
+        let discount = quote.trade_fee / 4;
+        assert_eq!(discounted.trade_fee, quote.trade_fee - discount);
+        assert_eq!(discounted.amount_out, quote.amount_out + discount);
+        assert_eq!(
+            discounted.admin_fee,
+            fees.admin_trade_fee(discounted.trade_fee).unwrap()
+        );
+        assert_eq!(discounted.reward_amount, quote.reward_amount);
+
+        // the whole fee at most
+        let discounted = quote.discount_trade_fee(u64::MAX, &fees).unwrap();
+        assert_eq!(discounted.trade_fee, 0);
+        assert_eq!(discounted.admin_fee, 0);
+        assert_eq!(discounted.amount_out, quote.amount_out + quote.trade_fee);
+    }
+
+    #[test]
+    fn test_compound_trade_fee() {
+        // targets of a balanced pool are not adjusted from the reserves
+        let (pool, _) = test_pool();
+        assert_eq!(pool.multiplier, Multiplier::One);
+
+        let compounded = pool
+            .compound_trade_fee(SwapDirection::SellBase, 1_000, 400)
+            .unwrap();
+        assert_eq!(
+            compounded,
+            PoolState {
+                quote_reserve: pool.quote_reserve.try_sub(Decimal::from(400u64)).unwrap(),
+                quote_target: pool.quote_target.try_add(Decimal::from(600u64)).unwrap(),
+                ..pool.clone()
+            }
+        );
+
+        let compounded = pool
+            .compound_trade_fee(SwapDirection::SellQuote, 10, 0)
+            .unwrap();
+        assert_eq!(
+            compounded,
+            PoolState {
+                base_target: pool.base_target.try_add(Decimal::from(10u64)).unwrap(),
+                ..pool.clone()
+            }
+        );
+
+        assert_eq!(
+            pool.compound_trade_fee(SwapDirection::SellBase, 1, 2)
+                .unwrap_err(),
+            SwapError::CalculationFailure.into()
+        );
+    }
+
+    #[test]
+    fn test_surplus() {
+        let (pool, _) = test_pool();
+        let base_reserve = pool.base_reserve.try_floor_u64().unwrap();
+        let quote_reserve = pool.quote_reserve.try_floor_u64().unwrap();
+
+        assert_eq!(pool.surplus(base_reserve, quote_reserve).unwrap(), (0, 0));
+        assert_eq!(
+            pool.surplus(base_reserve + 10, quote_reserve - 1).unwrap(),
+            (10, 0)
+        );
+
+        let folded = pool.fold_surplus(10, 200).unwrap();
+        assert_eq!(
+            folded,
+            PoolState {
+                base_reserve: pool.base_reserve.try_add(Decimal::from(10u64)).unwrap(),
+                quote_reserve: pool.quote_reserve.try_add(Decimal::from(200u64)).unwrap(),
+                base_target: pool.base_target.try_add(Decimal::from(10u64)).unwrap(),
+                quote_target: pool.quote_target.try_add(Decimal::from(200u64)).unwrap(),
+                ..pool.clone()
+            }
+        );
+        assert_eq!(
+            folded
+                .surplus(base_reserve + 10, quote_reserve + 200)
+                .unwrap(),
+            (0, 0)
+        );
+        assert_eq!(pool.fold_surplus(0, 0).unwrap(), pool);
+    }
+
+    #[test]
+    fn test_recalibrate() {
+        let (pool, _) = test_pool();
+        let (receive_amount, multiplier) = pool.sell_base_token(10_000).unwrap();
+        let mut drifted = PoolState::new(PoolState {
+            base_reserve: pool.base_reserve.try_add(Decimal::from(10_000u64)).unwrap(),
+            quote_reserve: pool
+                .quote_reserve
+                .try_sub(Decimal::from(receive_amount))
+                .unwrap(),
+            multiplier,
+            ..pool.clone()
+        })
+        .unwrap();
+        assert_eq!(drifted.multiplier, Multiplier::BelowOne);
+
+        let market_price = pool.market_price.try_mul(2).unwrap();
+        let mut recalibrated = drifted.recalibrate(1_000, 30_000, market_price).unwrap();
+        assert_eq!(
+            recalibrated,
+            PoolState {
+                market_price,
+                base_reserve: Decimal::from(1_000u64),
+                quote_reserve: Decimal::from(30_000u64),
+                base_target: Decimal::from(1_000u64),
+                quote_target: Decimal::from(30_000u64),
+                multiplier: Multiplier::One,
+                ..pool
+            }
+        );
+        assert_eq!(recalibrated.get_mid_price().unwrap(), market_price);
+        assert_ne!(drifted.get_mid_price().unwrap(), market_price);
+    }
+
+    #[test]
+    fn test_quote_trade_fee_in_quote() {
+        let (pool, _) = test_pool();
+        let fees = DEFAULT_TEST_FEES;
+        let quote = pool
+            .quote_swap(
+                10_000_000,
+                SwapDirection::SellQuote,
+                &fees,
+                &DEFAULT_TEST_REWARDS,
+            )
+            .unwrap();
+        assert!(quote.trade_fee > 0);
+
+        let (trade_fee, admin_fee) = pool
+            .quote_trade_fee_in_quote(quote.trade_fee, &fees)
+            .unwrap();
+        let (receive_amount, _) = pool.sell_base_token(quote.trade_fee).unwrap();
+        assert_eq!(trade_fee, receive_amount);
+        assert_eq!(admin_fee, fees.admin_trade_fee(trade_fee).unwrap());
+        // sold at no better than the mid price
+        let mid_price = pool.clone().get_mid_price().unwrap();
+        assert!(trade_fee > 0);
+        assert!(Decimal::from(trade_fee) <= mid_price.try_mul(quote.trade_fee).unwrap());
+
+        assert_eq!(pool.quote_trade_fee_in_quote(0, &fees).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_price_impact_bps() {
+        let (pool, _) = test_pool();
+
+        let (receive_amount, _) = pool.sell_base_token(100).unwrap();
+        assert_eq!(
+            pool.price_impact_bps(100, receive_amount, SwapDirection::SellBase)
+                .unwrap(),
+            0
+        );
+        let (receive_amount, _) = pool.sell_base_token(100_000).unwrap();
+        let large_impact = pool
+            .price_impact_bps(100_000, receive_amount, SwapDirection::SellBase)
+            .unwrap();
+        assert!(large_impact > 0);
+        assert!(large_impact < BPS_DENOMINATOR);
+
+        // half of the output at the mid price
+        let mid_price = pool.clone().get_mid_price().unwrap();
+        let expected_amount = mid_price
+            .try_mul(100_000u64)
+            .unwrap()
+            .try_floor_u64()
+            .unwrap();
+        assert_eq!(
+            pool.price_impact_bps(100_000, expected_amount / 2, SwapDirection::SellBase)
+                .unwrap(),
+            5_000
+        );
+        // output above the mid price has no impact
+        assert_eq!(
+            pool.price_impact_bps(100_000, expected_amount * 2, SwapDirection::SellBase)
+                .unwrap(),
+            0
+        );
+
+        let (receive_amount, _) = pool.sell_quote_token(10_000_000).unwrap();
+        assert!(
+            pool.price_impact_bps(10_000_000, receive_amount, SwapDirection::SellQuote)
+                .unwrap()
+                > 0
+        );
+    }
+
+    #[test]
+    fn test_quote_deposit_withdraw() {
+        let (pool, total_supply) = test_pool();
+        let fees = DEFAULT_TEST_FEES;
+
+        let minted = pool.quote_deposit(10_000, 1_000_000, total_supply).unwrap();
+        assert_eq!(minted, total_supply / 100);
+        // quoting does not change the pool
+        assert_eq!(pool.base_reserve, Decimal::from(1_000_000u64));
+
+        let quote = pool.quote_withdraw(minted, total_supply, &fees).unwrap();
+        assert_eq!(quote.token_a_amount + quote.withdraw_fee_a, 10_000);
+        assert_eq!(quote.token_b_amount + quote.withdraw_fee_b, 1_000_000);
+        assert_eq!(
+            quote.admin_fee_a,
+            fees.admin_withdraw_fee(quote.withdraw_fee_a).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_quote_withdraw_exact() {
+        let (pool, total_supply) = test_pool();
+        let fees = DEFAULT_TEST_FEES;
+
+        // at the pool ratio, burns what a share-first withdraw would
+        let quote = pool
+            .quote_withdraw_exact(9_000, 900_000, total_supply, &fees)
+            .unwrap();
+        let withdraw = pool
+            .quote_withdraw(quote.pool_token_amount, total_supply, &fees)
+            .unwrap();
+        assert!(withdraw.token_a_amount >= 9_000);
+        assert!(withdraw.token_b_amount >= 900_000);
+        let withdraw = pool
+            .quote_withdraw(quote.pool_token_amount - 1, total_supply, &fees)
+            .unwrap();
+        assert!(withdraw.token_a_amount < 9_000 || withdraw.token_b_amount < 900_000);
+        assert_eq!(
+            quote.pool_state.base_reserve,
+            Decimal::from(1_000_000u64 - 9_000 - quote.admin_fee_a)
+        );
+        assert_eq!(
+            quote.pool_state.quote_reserve,
+            Decimal::from(100_000_000u64 - 900_000 - quote.admin_fee_b)
+        );
+
+        // base only, the quote share stays in the pool
+        let quote = pool
+            .quote_withdraw_exact(9_000, 0, total_supply, &fees)
+            .unwrap();
+        assert_eq!((quote.withdraw_fee_b, quote.admin_fee_b), (0, 0));
+        assert_eq!(
+            quote.pool_state.quote_reserve,
+            Decimal::from(100_000_000u64)
+        );
+
+        assert!(pool
+            .quote_withdraw_exact(0, 0, total_supply, &fees)
+            .is_err());
+        assert!(pool
+            .quote_withdraw_exact(1_000_000, 0, total_supply, &fees)
+            .is_err());
+    }
+
+    #[test]
+    fn test_lp_virtual_price() {
+        let (pool, total_supply) = test_pool();
+
+        // 1_000_000 base at 100 plus 100_000_000 quote
+        let virtual_price = pool.lp_virtual_price(total_supply).unwrap();
+        assert_eq!(
+            virtual_price,
+            Decimal::from(200_000_000u64).try_div(total_supply).unwrap()
+        );
+        // a deposit at the pool ratio keeps the price of a pool token
+        let minted = pool.quote_deposit(10_000, 1_000_000, total_supply).unwrap();
+        let mut deposited = pool.clone();
+        deposited
+            .buy_shares(1_010_000, 101_000_000, total_supply)
+            .unwrap();
+        assert_eq!(
+            deposited.lp_virtual_price(total_supply + minted).unwrap(),
+            virtual_price
+        );
+
+        // the reserves sold to the pool are valued at the mid price
+        let (receive_amount, multiplier) = pool.sell_quote_token(10_000_000).unwrap();
+        let mut sold = pool.clone();
+        sold.base_reserve = sold
+            .base_reserve
+            .try_sub(Decimal::from(receive_amount))
+            .unwrap();
+        sold.quote_reserve = sold
+            .quote_reserve
+            .try_add(Decimal::from(10_000_000u64))
+            .unwrap();
+        sold.multiplier = multiplier;
+        let mid_price = sold.clone().get_mid_price().unwrap();
+        assert_eq!(
+            sold.lp_virtual_price(total_supply).unwrap(),
+            sold.base_reserve
+                .try_mul(mid_price)
+                .unwrap()
+                .try_add(sold.quote_reserve)
+                .unwrap()
+                .try_div(total_supply)
+                .unwrap()
+        );
+
+        assert_eq!(
+            pool.lp_virtual_price(0),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_lp_target_price() {
+        let (pool, total_supply) = test_pool();
+        let target_price = pool.lp_target_price(total_supply).unwrap();
+        assert_eq!(target_price, pool.lp_virtual_price(total_supply).unwrap());
+
+        // a swap moves the reserves and the mid price, not the targets
+        let (receive_amount, multiplier) = pool.sell_quote_token(10_000_000).unwrap();
+        let mut sold = pool.clone();
+        sold.base_reserve = sold
+            .base_reserve
+            .try_sub(Decimal::from(receive_amount))
+            .unwrap();
+        sold.quote_reserve = sold
+            .quote_reserve
+            .try_add(Decimal::from(10_000_000u64))
+            .unwrap();
+        sold.multiplier = multiplier;
+        assert_ne!(sold.lp_virtual_price(total_supply).unwrap(), target_price);
+        assert_eq!(sold.lp_target_price(total_supply).unwrap(), target_price);
+
+        assert_eq!(
+            pool.lp_target_price(0),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_lp_fee_per_target() {
+        let (pool, _) = test_pool();
+
+        // fees in quote are counted as is
+        assert_eq!(
+            pool.lp_fee_per_target(SwapDirection::SellBase, 1_000, 400)
+                .unwrap(),
+            Decimal::from(600u64).try_div(pool.base_target).unwrap()
+        );
+
+        // fees in base are valued at the market price
+        assert_eq!(
+            pool.lp_fee_per_target(SwapDirection::SellQuote, 10, 0)
+                .unwrap(),
+            Decimal::from(10u64)
+                .try_mul(pool.amount_price(pool.market_price).unwrap())
+                .unwrap()
+                .try_div(pool.base_target)
+                .unwrap()
+        );
+
+        let empty = PoolState {
+            base_target: Decimal::zero(),
+            ..pool.clone()
+        };
+        assert_eq!(
+            empty
+                .lp_fee_per_target(SwapDirection::SellBase, 1_000, 400)
+                .unwrap(),
+            Decimal::zero()
+        );
+
+        assert_eq!(
+            pool.lp_fee_per_target(SwapDirection::SellBase, 1, 2)
+                .unwrap_err(),
+            SwapError::CalculationFailure.into()
+        );
+    }
+
+    #[test]
+    fn test_quote_deposit_imbalanced() {
+        let (pool, total_supply) = test_pool();
+        let fees = DEFAULT_TEST_FEES;
+
+        // a balanced deposit does not swap
+        let quote = pool
+            .quote_deposit_imbalanced(
+                10_000,
+                1_000_000,
+                total_supply,
+                &fees,
+                &DirectionalFees::default(),
+            )
+            .unwrap();
+        assert_eq!(quote.swap_amount_in, 0);
+        assert_eq!((quote.trade_fee, quote.admin_fee), (0, 0));
+        assert_eq!(
+            quote.pool_token_amount,
+            pool.quote_deposit(10_000, 1_000_000, total_supply).unwrap()
+        );
+
+        // base only deposit sells part of the base
+        let quote = pool
+            .quote_deposit_imbalanced(20_000, 0, total_supply, &fees, &DirectionalFees::default())
+            .unwrap();
+        assert_eq!(quote.swap_direction, SwapDirection::SellBase);
+        assert!(quote.swap_amount_in > 9_000 && quote.swap_amount_in < 11_000);
+        let (receive_amount, _) = pool.sell_base_token(quote.swap_amount_in).unwrap();
+        assert_eq!(quote.trade_fee, fees.trade_fee(receive_amount).unwrap());
+        assert_eq!(
+            quote.admin_fee,
+            fees.admin_trade_fee(quote.trade_fee).unwrap()
+        );
+        // the swapped deposit is worth less than a balanced one of the same value
+        assert!(quote.pool_token_amount > 0);
+        assert!(quote.pool_token_amount < total_supply / 50);
+        // quoting does not change the pool
+        assert_eq!(pool.base_reserve, Decimal::from(1_000_000u64));
+
+        // quote only deposit sells part of the quote
+        let quote = pool
+            .quote_deposit_imbalanced(
+                0,
+                2_000_000,
+                total_supply,
+                &fees,
+                &DirectionalFees::default(),
+            )
+            .unwrap();
+        assert_eq!(quote.swap_direction, SwapDirection::SellQuote);
+        assert!(quote.swap_amount_in > 900_000 && quote.swap_amount_in < 1_100_000);
+        assert!(quote.pool_token_amount > 0);
+        assert!(quote.pool_token_amount < total_supply / 50);
+
+        assert!(pool
+            .quote_deposit_imbalanced(0, 0, total_supply, &fees, &DirectionalFees::default())
+            .is_err());
+    }
+}
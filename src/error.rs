@@ -6,157 +6,357 @@ use solana_program::{
     msg,
     program_error::{PrintProgramError, ProgramError},
 };
+use std::convert::TryFrom;
 use thiserror::Error;
 
 /// Errors that may be returned by the TokenSwap program.
+///
+/// Each variant has a fixed custom error number, new variants take the next
+/// free number.
 #[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
 pub enum SwapError {
     // 0
     /// The account cannot be initialized because it is already being used.
     #[error("Swap account already in use")]
-    AlreadyInUse,
+    AlreadyInUse = 0,
     /// The address of the admin fee account is incorrect.
     #[error("Address of the admin fee account is incorrect")]
-    InvalidAdmin,
+    InvalidAdmin = 1,
     /// Active admin transfer in progress
     #[error("Active admin transfer in progress")]
-    ActiveTransfer,
+    ActiveTransfer = 2,
     /// No active admin transfer in progress
     #[error("No active admin transfer in progress")]
-    NoActiveTransfer,
+    NoActiveTransfer = 3,
     /// Admin transfer deadline exceeded
     #[error("Admin transfer deadline exceeded")]
-    AdminDeadlineExceeded,
+    AdminDeadlineExceeded = 4,
 
     // 5
     /// Unauthorized
     #[error("Account is not authorized to execute this instruction")]
-    Unauthorized,
+    Unauthorized = 5,
     /// The account is not owned by program
     #[error("Input account owner is not the program")]
-    InvalidAccountOwner,
+    InvalidAccountOwner = 6,
     /// The owner of the input isn't set to the program address generated by the program.
     #[error("Input account owner is not the program address")]
-    InvalidOwner,
+    InvalidOwner = 7,
     /// The input account must be a signer.
     #[error("Input account must be signer")]
-    InvalidSigner,
+    InvalidSigner = 8,
     /// The owner of the pool token output is set to the program address generated by the program.
     #[error("Output pool account owner cannot be the program address")]
-    InvalidOutputOwner,
+    InvalidOutputOwner = 9,
 
     // 10
     /// Address of the provided swap token account is incorrect.
     #[error("Address of the provided swap token account is incorrect")]
-    IncorrectSwapAccount,
+    IncorrectSwapAccount = 10,
     /// The program address provided doesn't match the value generated by the program.
     #[error("Invalid program address generated from nonce and key")]
-    InvalidProgramAddress,
+    InvalidProgramAddress = 11,
     /// The provided token account has a close authority.
     #[error("Token account has a close authority")]
-    InvalidCloseAuthority,
+    InvalidCloseAuthority = 12,
     /// The pool token mint has a freeze authority.
     #[error("Pool token mint has a freeze authority")]
-    InvalidFreezeAuthority,
+    InvalidFreezeAuthority = 13,
     /// Incorrect token program ID
     #[error("Incorrect token program ID")]
-    IncorrectTokenProgramId,
+    IncorrectTokenProgramId = 14,
 
     // 15
     /// Address of the provided token mint is incorrect
     #[error("Address of the provided token mint is incorrect")]
-    IncorrectMint,
+    IncorrectMint = 15,
     /// The deserialization of the account returned something besides State::Mint.
     #[error("Deserialized account is not an SPL Token mint")]
-    ExpectedMint,
+    ExpectedMint = 16,
     /// Swap input token accounts have the same mint
     #[error("Swap input token accounts have the same mint")]
-    RepeatedMint,
+    RepeatedMint = 17,
     /// The deserialization of the account returned something besides State::Account.
     #[error("Deserialized account is not an SPL Token account")]
-    ExpectedAccount,
+    ExpectedAccount = 18,
     /// Invalid instruction number passed in.
     #[error("Invalid instruction")]
-    InvalidInstruction,
+    InvalidInstruction = 19,
 
     // 20
     /// Instruction unpack failed.
     #[error("Instruction unpack is failed")]
-    InstructionUnpackError,
+    InstructionUnpackError = 20,
     /// The pool supply is empty.
     #[error("Pool token supply is 0")]
-    EmptyPool,
+    EmptyPool = 21,
     /// The input token account is empty.
     #[error("Input token account empty")]
-    EmptySupply,
+    EmptySupply = 22,
     /// The pool token mint has a non-zero supply.
     #[error("Pool token mint has a non-zero supply")]
-    InvalidSupply,
+    InvalidSupply = 23,
     /// The provided token account has a delegate.
     #[error("Token account has a delegate")]
-    InvalidDelegate,
+    InvalidDelegate = 24,
 
     // 25
     /// The input token is invalid for swap.
     #[error("InvalidInput")]
-    InvalidInput,
+    InvalidInput = 25,
     /// Swap pool is paused
     #[error("Swap pool is paused")]
-    IsPaused,
+    IsPaused = 26,
     /// Lamport balance below rent-exempt threshold.
     #[error("Lamport balance below rent-exempt threshold")]
-    NotRentExempt,
+    NotRentExempt = 27,
     /// The calculation failed.
     #[error("CalculationFailure")]
-    CalculationFailure,
+    CalculationFailure = 28,
     /// Swap instruction exceeds desired slippage limit
     #[error("Swap instruction exceeds desired slippage limit")]
-    ExceededSlippage,
+    ExceededSlippage = 29,
 
     // 30
     /// Token mint decimals must be the same.
     #[error("Token mints must have same decimals")]
-    MismatchedDecimals,
+    MismatchedDecimals = 30,
     /// Oracle config is invalid
     #[error("Input oracle config is invalid")]
-    InvalidOracleConfig,
+    InvalidOracleConfig = 31,
     /// Insufficient liquidity amount to withdraw
     #[error("Insufficient liquidity available")]
-    InsufficientLiquidity,
+    InsufficientLiquidity = 32,
     /// User has no liquidity position
     #[error("User has no liquidity position")]
-    LiquidityPositionEmpty,
+    LiquidityPositionEmpty = 33,
     /// Invalid position key
     #[error("Invalid position key")]
-    InvalidPositionKey,
+    InvalidPositionKey = 34,
 
     // 35
     /// Invalid claim timestamp
     #[error("Invalid claim timestamp")]
-    InvalidClaimTime,
+    InvalidClaimTime = 35,
     /// Insufficient claim amount
     #[error("Insufficient claim amount")]
-    InsufficientClaimAmount,
+    InsufficientClaimAmount = 36,
     /// Insufficient funds
     #[error("Insufficient funds")]
-    InsufficientFunds,
+    InsufficientFunds = 37,
     /// Withdraw tokens not enough
     #[error("Withdraw not enough")]
-    WithdrawNotEnough,
+    WithdrawNotEnough = 38,
     /// Mint initialization failed
     #[error("Mint initialization failed")]
-    TokenInitializeMintFailed,
+    TokenInitializeMintFailed = 39,
 
     // 40
     /// Invalid slope, slope must be in range [0.0,1.0]
     #[error("Invalid slope")]
-    InvalidSlope,
+    InvalidSlope = 40,
+    /// Mid price update exceeds the allowed deviation
+    #[error("Mid price deviation exceeded")]
+    MidPriceDeviationExceeded = 41,
+    /// Mid price update requested before the minimum interval elapsed
+    #[error("Mid price update too frequent")]
+    MidPriceUpdateTooFrequent = 42,
+    /// Pool price is driven by a live oracle
+    #[error("Oracle is active")]
+    OracleActive = 43,
+    /// Oracle account does not match the one stored in the pool
+    #[error("Incorrect oracle account")]
+    IncorrectOracleAccount = 44,
+
+    // 45
+    /// Liquidity provider account has no room for another position
+    #[error("Liquidity positions full")]
+    LiquidityPositionsFull = 45,
+    /// Liquidity provider still holds liquidity or unclaimed rewards
+    #[error("Liquidity provider not empty")]
+    LiquidityProviderNotEmpty = 46,
+    /// Position pages passed are not the full chain of the liquidity provider
+    #[error("Position page missing")]
+    PositionPageMissing = 47,
+    /// Swap moves the price beyond the pool max price impact
+    #[error("Price impact exceeded")]
+    PriceImpactExceeded = 48,
+    /// Swap sells more than the pool max trade size
+    #[error("Max trade size exceeded")]
+    MaxTradeSizeExceeded = 49,
+    /// Swap goes over the pool swap count or volume of the slot
+    #[error("Slot throttle exceeded")]
+    SlotThrottleExceeded = 50,
+    /// Trader is not on the whitelist of a whitelist only token-swap
+    #[error("Trader not whitelisted")]
+    TraderNotWhitelisted = 51,
+    /// Whitelist has no room for another trader
+    #[error("Whitelist full")]
+    WhitelistFull = 52,
+    /// Transfer hook program does not match the one of the token-swap
+    #[error("Incorrect transfer hook program")]
+    IncorrectTransferHookProgram = 53,
+    /// Registry page has no room for another pool
+    #[error("Registry full")]
+    RegistryFull = 54,
+    /// Merged liquidity positions were last refreshed at different times
+    #[error("Positions not refreshed together")]
+    PositionsNotRefreshed = 55,
+    /// Rewards minted in the epoch would exceed the epoch budget
+    #[error("Epoch reward budget exceeded")]
+    EpochRewardBudgetExceeded = 56,
+    /// Token-swap does not accumulate a twap
+    #[error("Twap not open")]
+    TwapNotOpen = 57,
+    /// DELFI lock already voted in the epoch
+    #[error("Gauge vote already cast")]
+    GaugeVoteCast = 58,
+    /// DELFI lock cannot be unlocked yet
+    #[error("DELFI locked")]
+    DelfiLocked = 59,
+    /// DELFI lock past its unlock timestamp has no voting power
+    #[error("DELFI lock expired")]
+    DelfiLockExpired = 60,
+    /// Proposal no longer takes votes
+    #[error("Proposal voting closed")]
+    ProposalVotingClosed = 61,
+    /// Proposal still takes votes
+    #[error("Proposal voting open")]
+    ProposalVotingOpen = 62,
+    /// Proposal did not pass the vote
+    #[error("Proposal rejected")]
+    ProposalRejected = 63,
+    /// Proposal was already executed
+    #[error("Proposal executed")]
+    ProposalExecuted = 64,
+    /// Config takes no proposals without a quorum
+    #[error("Governance disabled")]
+    GovernanceDisabled = 65,
+    /// Flash swap callback did not pay back the input
+    #[error("Flash swap not repaid")]
+    FlashSwapNotRepaid = 66,
+    /// Deposit fills the pool beyond its reserve caps
+    #[error("Reserve cap exceeded")]
+    ReserveCapExceeded = 67,
+    /// Deposit takes a liquidity position beyond the user cap
+    #[error("User liquidity cap exceeded")]
+    UserLiquidityCapExceeded = 68,
+    /// Parameters of the token-swap can no longer be changed
+    #[error("Pool is immutable")]
+    ImmutablePool = 69,
+    /// Referrer account does not match the referrer of the liquidity provider
+    #[error("Invalid referrer")]
+    InvalidReferrer = 70,
+    /// Token metadata program is not the one naming the pool mints
+    #[error("Incorrect token metadata program")]
+    IncorrectMetadataProgram = 71,
+    /// Deny-list has no room for another mint
+    #[error("Deny-list full")]
+    DenyListFull = 72,
+    /// Token mint is on the deny-list of the config
+    #[error("Denied mint")]
+    DeniedMint = 73,
+    /// Both token accounts of the swap are frozen by their mints
+    #[error("Frozen swap accounts")]
+    FrozenSwapAccounts = 74,
+    /// Reserved, no longer returned: instruction data has no layout version
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion = 75,
+    /// Instruction data has bytes left after the instruction
+    #[error("Invalid instruction data")]
+    InvalidInstructionData = 76,
+    /// Signed order not verified by the ed25519 instruction before it
+    #[error("Invalid order signature")]
+    InvalidOrderSignature = 77,
+    /// Signed order settled after its expiry
+    #[error("Order expired")]
+    OrderExpired = 78,
+    /// Signed order nonce below the next nonce of the maker
+    #[error("Invalid order nonce")]
+    InvalidOrderNonce = 79,
+    /// DCA plan executed before its next period is due
+    #[error("DCA period not due")]
+    DcaNotDue = 80,
+    /// DCA plan executed with nothing left to sell
+    #[error("DCA plan completed")]
+    DcaPlanCompleted = 81,
+    /// Config account other than the one of the token-swap
+    #[error("Incorrect config account")]
+    IncorrectConfigAccount = 82,
 }
 impl From<SwapError> for ProgramError {
     fn from(e: SwapError) -> Self {
         ProgramError::Custom(e as u32)
     }
 }
+impl From<SwapError> for u32 {
+    fn from(e: SwapError) -> Self {
+        e as u32
+    }
+}
+impl TryFrom<u32> for SwapError {
+    type Error = ProgramError;
+    /// Maps a custom program error number back to the error,
+    /// fails on numbers not assigned to any variant
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        num_traits::FromPrimitive::from_u32(code).ok_or(ProgramError::InvalidArgument)
+    }
+}
+
+/// Custom error number, name and message of an error, for the error tables of
+/// the clients
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapErrorInfo {
+    /// Custom program error number
+    pub code: u32,
+    /// Name of the variant
+    pub name: String,
+    /// Message of the error
+    pub msg: String,
+}
+
+impl SwapError {
+    /// Custom program error number, never reassigned
+    pub fn code(&self) -> u32 {
+        self.clone().into()
+    }
+
+    /// Name of the variant
+    pub fn name(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    /// All the errors, ordered by number
+    pub fn table() -> Vec<SwapErrorInfo> {
+        (0..)
+            .map_while(|code| SwapError::try_from(code).ok())
+            .map(|error| SwapErrorInfo {
+                code: error.code(),
+                name: error.name(),
+                msg: error.to_string(),
+            })
+            .collect()
+    }
+
+    /// All the errors as the `errors` array of an Anchor IDL, so the clients
+    /// show the message of a custom program error
+    pub fn idl_errors_json() -> String {
+        let escape = |val: &str| val.replace('\\', "\\\\").replace('"', "\\\"");
+        let errors = Self::table()
+            .iter()
+            .map(|info| {
+                format!(
+                    r#"{{"code":{},"name":"{}","msg":"{}"}}"#,
+                    info.code,
+                    escape(&info.name),
+                    escape(&info.msg)
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]", errors.join(","))
+    }
+}
+
 impl<T> DecodeError<T> for SwapError {
     fn type_of() -> &'static str {
         "Swap Error"
@@ -244,6 +444,255 @@ impl PrintProgramError for SwapError {
             SwapError::InvalidSlope => {
                 msg!("Error: Invalid slope. Slope must be in range [0.0,1.0]")
             }
+            SwapError::MidPriceDeviationExceeded => {
+                msg!("Error: Mid price change exceeds the max deviation per update")
+            }
+            SwapError::MidPriceUpdateTooFrequent => {
+                msg!("Error: Mid price cannot be updated before the minimum interval")
+            }
+            SwapError::OracleActive => {
+                msg!("Error: Mid price cannot be overridden while the oracle is active")
+            }
+            SwapError::IncorrectOracleAccount => {
+                msg!("Error: Oracle account does not match the pool oracle")
+            }
+            SwapError::LiquidityPositionsFull => {
+                msg!("Error: Liquidity provider account is full, grow it to add positions")
+            }
+            SwapError::PositionPageMissing => {
+                msg!("Error: All position pages of the liquidity provider must be passed in order")
+            }
+            SwapError::LiquidityProviderNotEmpty => {
+                msg!("Error: Withdraw liquidity and claim rewards before closing the liquidity provider")
+            }
+            SwapError::PriceImpactExceeded => {
+                msg!(
+                    "Error: Swap execution price deviates from the mid price beyond the pool limit"
+                )
+            }
+            SwapError::MaxTradeSizeExceeded => {
+                msg!("Error: Swap amount in is above the pool max trade size")
+            }
+            SwapError::TraderNotWhitelisted => {
+                msg!("Error: Owner of the source account is not on the whitelist of the pool")
+            }
+            SwapError::WhitelistFull => msg!("Error: Whitelist is full, remove a trader first"),
+            SwapError::IncorrectTransferHookProgram => {
+                msg!("Error: Transfer hook program missing or not the one of the pool")
+            }
+            SwapError::RegistryFull => {
+                msg!("Error: Registry page is full, initialize the next page")
+            }
+            SwapError::PositionsNotRefreshed => {
+                msg!("Error: Refresh both liquidity positions in the same slot before merging")
+            }
+            SwapError::EpochRewardBudgetExceeded => {
+                msg!("Error: Rewards of the epoch are all minted, claim in a later epoch")
+            }
+            SwapError::TwapNotOpen => msg!("Error: Twap of the token-swap is not open"),
+            SwapError::GaugeVoteCast => {
+                msg!("Error: Gauge votes of the DELFI lock already cast, vote in a later epoch")
+            }
+            SwapError::DelfiLocked => {
+                msg!("Error: DELFI is locked until the unlock timestamp and out of the epoch voted in")
+            }
+            SwapError::DelfiLockExpired => {
+                msg!("Error: DELFI lock is expired, extend it to vote")
+            }
+            SwapError::ProposalVotingClosed => {
+                msg!("Error: Voting period of the proposal is over")
+            }
+            SwapError::ProposalVotingOpen => {
+                msg!("Error: Proposal cannot be executed before the end of its voting period")
+            }
+            SwapError::ProposalRejected => {
+                msg!("Error: Proposal did not get a majority of at least the quorum")
+            }
+            SwapError::ProposalExecuted => msg!("Error: Proposal was already executed"),
+            SwapError::GovernanceDisabled => {
+                msg!("Error: Config has no proposal quorum set, governance is disabled")
+            }
+            SwapError::FlashSwapNotRepaid => {
+                msg!("Error: Swap account did not receive the input after the flash swap callback")
+            }
+            SwapError::ReserveCapExceeded => {
+                msg!("Error: Deposit takes the pool reserves above the pool reserve caps")
+            }
+            SwapError::UserLiquidityCapExceeded => {
+                msg!("Error: Deposit takes the liquidity position above the pool user cap")
+            }
+            SwapError::ImmutablePool => {
+                msg!("Error: Parameters of the pool were made immutable by the admin")
+            }
+            SwapError::InvalidReferrer => {
+                msg!("Error: Referrer is the liquidity provider owner or is not its referrer")
+            }
+            SwapError::IncorrectMetadataProgram => {
+                msg!("Error: Token metadata program is not the Metaplex one")
+            }
+            SwapError::DenyListFull => msg!("Error: Deny-list is full, remove a mint first"),
+            SwapError::DeniedMint => {
+                msg!("Error: Token mint of the pool is on the deny-list of the config")
+            }
+            SwapError::FrozenSwapAccounts => {
+                msg!("Error: Both token accounts of the swap are frozen")
+            }
+            SwapError::UnsupportedInstructionVersion => {
+                msg!("Error: Instruction data layout version is not supported")
+            }
+            SwapError::InvalidInstructionData => {
+                msg!("Error: Instruction data has bytes left after the instruction")
+            }
+            SwapError::InvalidOrderSignature => {
+                msg!("Error: Order is not signed by its maker in the previous instruction")
+            }
+            SwapError::OrderExpired => msg!("Error: Order expired"),
+            SwapError::InvalidOrderNonce => {
+                msg!("Error: Order nonce already settled or cancelled by the maker")
+            }
+            SwapError::DcaNotDue => msg!("Error: DCA plan period not due yet"),
+            SwapError::DcaPlanCompleted => msg!("Error: DCA plan escrow fully swapped"),
+            SwapError::IncorrectConfigAccount => {
+                msg!("Error: Config account is not the one the token-swap was initialized with")
+            }
+            SwapError::SlotThrottleExceeded => {
+                msg!("Error: Pool max swaps or volume of the slot reached, retry in a later slot")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        // error numbers are part of the client interface, never change them
+        let codes = [
+            (SwapError::AlreadyInUse, 0),
+            (SwapError::InvalidAdmin, 1),
+            (SwapError::ActiveTransfer, 2),
+            (SwapError::NoActiveTransfer, 3),
+            (SwapError::AdminDeadlineExceeded, 4),
+            (SwapError::Unauthorized, 5),
+            (SwapError::InvalidAccountOwner, 6),
+            (SwapError::InvalidOwner, 7),
+            (SwapError::InvalidSigner, 8),
+            (SwapError::InvalidOutputOwner, 9),
+            (SwapError::IncorrectSwapAccount, 10),
+            (SwapError::InvalidProgramAddress, 11),
+            (SwapError::InvalidCloseAuthority, 12),
+            (SwapError::InvalidFreezeAuthority, 13),
+            (SwapError::IncorrectTokenProgramId, 14),
+            (SwapError::IncorrectMint, 15),
+            (SwapError::ExpectedMint, 16),
+            (SwapError::RepeatedMint, 17),
+            (SwapError::ExpectedAccount, 18),
+            (SwapError::InvalidInstruction, 19),
+            (SwapError::InstructionUnpackError, 20),
+            (SwapError::EmptyPool, 21),
+            (SwapError::EmptySupply, 22),
+            (SwapError::InvalidSupply, 23),
+            (SwapError::InvalidDelegate, 24),
+            (SwapError::InvalidInput, 25),
+            (SwapError::IsPaused, 26),
+            (SwapError::NotRentExempt, 27),
+            (SwapError::CalculationFailure, 28),
+            (SwapError::ExceededSlippage, 29),
+            (SwapError::MismatchedDecimals, 30),
+            (SwapError::InvalidOracleConfig, 31),
+            (SwapError::InsufficientLiquidity, 32),
+            (SwapError::LiquidityPositionEmpty, 33),
+            (SwapError::InvalidPositionKey, 34),
+            (SwapError::InvalidClaimTime, 35),
+            (SwapError::InsufficientClaimAmount, 36),
+            (SwapError::InsufficientFunds, 37),
+            (SwapError::WithdrawNotEnough, 38),
+            (SwapError::TokenInitializeMintFailed, 39),
+            (SwapError::InvalidSlope, 40),
+            (SwapError::MidPriceDeviationExceeded, 41),
+            (SwapError::MidPriceUpdateTooFrequent, 42),
+            (SwapError::OracleActive, 43),
+            (SwapError::IncorrectOracleAccount, 44),
+            (SwapError::LiquidityPositionsFull, 45),
+            (SwapError::LiquidityProviderNotEmpty, 46),
+            (SwapError::PositionPageMissing, 47),
+            (SwapError::PriceImpactExceeded, 48),
+            (SwapError::MaxTradeSizeExceeded, 49),
+            (SwapError::SlotThrottleExceeded, 50),
+            (SwapError::TraderNotWhitelisted, 51),
+            (SwapError::WhitelistFull, 52),
+            (SwapError::IncorrectTransferHookProgram, 53),
+            (SwapError::RegistryFull, 54),
+            (SwapError::PositionsNotRefreshed, 55),
+            (SwapError::EpochRewardBudgetExceeded, 56),
+            (SwapError::TwapNotOpen, 57),
+            (SwapError::GaugeVoteCast, 58),
+            (SwapError::DelfiLocked, 59),
+            (SwapError::DelfiLockExpired, 60),
+            (SwapError::ProposalVotingClosed, 61),
+            (SwapError::ProposalVotingOpen, 62),
+            (SwapError::ProposalRejected, 63),
+            (SwapError::ProposalExecuted, 64),
+            (SwapError::GovernanceDisabled, 65),
+            (SwapError::FlashSwapNotRepaid, 66),
+            (SwapError::ReserveCapExceeded, 67),
+            (SwapError::UserLiquidityCapExceeded, 68),
+            (SwapError::ImmutablePool, 69),
+            (SwapError::InvalidReferrer, 70),
+            (SwapError::IncorrectMetadataProgram, 71),
+            (SwapError::DenyListFull, 72),
+            (SwapError::DeniedMint, 73),
+            (SwapError::FrozenSwapAccounts, 74),
+            (SwapError::UnsupportedInstructionVersion, 75),
+            (SwapError::InvalidInstructionData, 76),
+            (SwapError::InvalidOrderSignature, 77),
+            (SwapError::OrderExpired, 78),
+            (SwapError::InvalidOrderNonce, 79),
+            (SwapError::DcaNotDue, 80),
+            (SwapError::DcaPlanCompleted, 81),
+            (SwapError::IncorrectConfigAccount, 82),
+        ];
+        for (error, code) in codes.iter() {
+            assert_eq!(u32::from(error.clone()), *code);
+            assert_eq!(
+                ProgramError::from(error.clone()),
+                ProgramError::Custom(*code)
+            );
+            assert_eq!(SwapError::try_from(*code).unwrap(), *error);
+        }
+        assert_eq!(
+            SwapError::try_from(codes.len() as u32).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+
+        let table = SwapError::table();
+        assert_eq!(table.len(), codes.len());
+        for ((error, code), info) in codes.iter().zip(table.iter()) {
+            assert_eq!(info.code, *code);
+            assert_eq!(info.name, error.name());
+            assert_eq!(info.msg, error.to_string());
         }
     }
+
+    #[test]
+    fn test_error_table() {
+        assert_eq!(
+            SwapError::table()[0x12],
+            SwapErrorInfo {
+                code: 18,
+                name: "ExpectedAccount".to_string(),
+                msg: "Deserialized account is not an SPL Token account".to_string(),
+            }
+        );
+
+        let json = SwapError::idl_errors_json();
+        assert!(json.starts_with(
+            r#"[{"code":0,"name":"AlreadyInUse","msg":"Swap account already in use"},"#
+        ));
+        assert!(json.ends_with(
+            r#"{"code":82,"name":"IncorrectConfigAccount","msg":"Incorrect config account"}]"#
+        ));
+    }
 }
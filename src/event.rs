@@ -0,0 +1,287 @@
+//! Program events
+//!
+//! Events are logged with `sol_log_data` as a single base64 encoded field:
+//! a one byte discriminator followed by the packed event struct.
+
+use std::mem::size_of;
+
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    log::sol_log_data,
+    program_error::ProgramError,
+    program_pack::{Pack, Sealed},
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use crate::{error::SwapError, instruction::SwapDirection};
+
+/// Discriminator of [SwapEvent](struct.SwapEvent.html)
+pub const SWAP_EVENT_TAG: u8 = 0;
+/// Discriminator of deposit [LiquidityEvent](struct.LiquidityEvent.html)
+pub const DEPOSIT_EVENT_TAG: u8 = 1;
+/// Discriminator of withdraw [LiquidityEvent](struct.LiquidityEvent.html)
+pub const WITHDRAW_EVENT_TAG: u8 = 2;
+
+/// Swap event
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapEvent {
+    /// Token-swap
+    pub swap: Pubkey,
+    /// Swap direction
+    pub swap_direction: SwapDirection,
+    /// Amount transferred into the pool
+    pub amount_in: u64,
+    /// Amount transferred to the user
+    pub amount_out: u64,
+    /// Trade fee charged on the output amount
+    pub trade_fee: u64,
+    /// Part of the trade fee sent to the admin fee account
+    pub admin_fee: u64,
+    /// Base token reserve after the swap
+    pub base_reserve: u64,
+    /// Quote token reserve after the swap
+    pub quote_reserve: u64,
+    /// Market price used for the swap - real value * 10**9
+    pub market_price: u128,
+}
+
+impl Sealed for SwapEvent {}
+
+const SWAP_EVENT_SIZE: usize = 97;
+impl Pack for SwapEvent {
+    const LEN: usize = SWAP_EVENT_SIZE;
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, SWAP_EVENT_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            swap,
+            swap_direction,
+            amount_in,
+            amount_out,
+            trade_fee,
+            admin_fee,
+            base_reserve,
+            quote_reserve,
+            market_price,
+        ) = array_refs![input, PUBKEY_BYTES, 1, 8, 8, 8, 8, 8, 8, 16];
+        Ok(Self {
+            swap: Pubkey::new_from_array(*swap),
+            swap_direction: match swap_direction[0] {
+                0 => SwapDirection::SellBase,
+                1 => SwapDirection::SellQuote,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            amount_in: u64::from_le_bytes(*amount_in),
+            amount_out: u64::from_le_bytes(*amount_out),
+            trade_fee: u64::from_le_bytes(*trade_fee),
+            admin_fee: u64::from_le_bytes(*admin_fee),
+            base_reserve: u64::from_le_bytes(*base_reserve),
+            quote_reserve: u64::from_le_bytes(*quote_reserve),
+            market_price: u128::from_le_bytes(*market_price),
+        })
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, SWAP_EVENT_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            swap,
+            swap_direction,
+            amount_in,
+            amount_out,
+            trade_fee,
+            admin_fee,
+            base_reserve,
+            quote_reserve,
+            market_price,
+        ) = mut_array_refs![output, PUBKEY_BYTES, 1, 8, 8, 8, 8, 8, 8, 16];
+        swap.copy_from_slice(self.swap.as_ref());
+        swap_direction[0] = self.swap_direction as u8;
+        *amount_in = self.amount_in.to_le_bytes();
+        *amount_out = self.amount_out.to_le_bytes();
+        *trade_fee = self.trade_fee.to_le_bytes();
+        *admin_fee = self.admin_fee.to_le_bytes();
+        *base_reserve = self.base_reserve.to_le_bytes();
+        *quote_reserve = self.quote_reserve.to_le_bytes();
+        *market_price = self.market_price.to_le_bytes();
+    }
+}
+
+/// Deposit or withdraw event
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiquidityEvent {
+    /// Token-swap
+    pub swap: Pubkey,
+    /// Token A amount deposited, or sent to the user after fees on withdraw
+    pub token_a_amount: u64,
+    /// Token B amount deposited, or sent to the user after fees on withdraw
+    pub token_b_amount: u64,
+    /// LP tokens minted or burned
+    pub pool_token_amount: u64,
+    /// Base token reserve after the instruction
+    pub base_reserve: u64,
+    /// Quote token reserve after the instruction
+    pub quote_reserve: u64,
+}
+
+impl Sealed for LiquidityEvent {}
+
+const LIQUIDITY_EVENT_SIZE: usize = 72;
+impl Pack for LiquidityEvent {
+    const LEN: usize = LIQUIDITY_EVENT_SIZE;
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, LIQUIDITY_EVENT_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (swap, token_a_amount, token_b_amount, pool_token_amount, base_reserve, quote_reserve) =
+            array_refs![input, PUBKEY_BYTES, 8, 8, 8, 8, 8];
+        Ok(Self {
+            swap: Pubkey::new_from_array(*swap),
+            token_a_amount: u64::from_le_bytes(*token_a_amount),
+            token_b_amount: u64::from_le_bytes(*token_b_amount),
+            pool_token_amount: u64::from_le_bytes(*pool_token_amount),
+            base_reserve: u64::from_le_bytes(*base_reserve),
+            quote_reserve: u64::from_le_bytes(*quote_reserve),
+        })
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, LIQUIDITY_EVENT_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (swap, token_a_amount, token_b_amount, pool_token_amount, base_reserve, quote_reserve) =
+            mut_array_refs![output, PUBKEY_BYTES, 8, 8, 8, 8, 8];
+        swap.copy_from_slice(self.swap.as_ref());
+        *token_a_amount = self.token_a_amount.to_le_bytes();
+        *token_b_amount = self.token_b_amount.to_le_bytes();
+        *pool_token_amount = self.pool_token_amount.to_le_bytes();
+        *base_reserve = self.base_reserve.to_le_bytes();
+        *quote_reserve = self.quote_reserve.to_le_bytes();
+    }
+}
+
+/// Events emitted by the program.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// Tokens swapped
+    Swap(SwapEvent),
+    /// Liquidity deposited
+    Deposit(LiquidityEvent),
+    /// Liquidity withdrawn
+    Withdraw(LiquidityEvent),
+}
+
+impl Event {
+    /// Unpacks a byte buffer into an [Event](enum.Event.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or(SwapError::InstructionUnpackError)?;
+        Ok(match tag {
+            SWAP_EVENT_TAG => Self::Swap(SwapEvent::unpack_unchecked(rest)?),
+            DEPOSIT_EVENT_TAG => Self::Deposit(LiquidityEvent::unpack_unchecked(rest)?),
+            WITHDRAW_EVENT_TAG => Self::Withdraw(LiquidityEvent::unpack_unchecked(rest)?),
+            _ => return Err(SwapError::InstructionUnpackError.into()),
+        })
+    }
+
+    /// Packs an [Event](enum.Event.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::Swap(event) => {
+                buf.push(SWAP_EVENT_TAG);
+                let mut event_slice = [0u8; SwapEvent::LEN];
+                event.pack_into_slice(&mut event_slice);
+                buf.extend_from_slice(&event_slice);
+            }
+            Self::Deposit(event) => {
+                buf.push(DEPOSIT_EVENT_TAG);
+                let mut event_slice = [0u8; LiquidityEvent::LEN];
+                event.pack_into_slice(&mut event_slice);
+                buf.extend_from_slice(&event_slice);
+            }
+            Self::Withdraw(event) => {
+                buf.push(WITHDRAW_EVENT_TAG);
+                let mut event_slice = [0u8; LiquidityEvent::LEN];
+                event.pack_into_slice(&mut event_slice);
+                buf.extend_from_slice(&event_slice);
+            }
+        }
+        buf
+    }
+
+    /// Logs the packed event with `sol_log_data`.
+    pub fn emit(&self) {
+        sol_log_data(&[&self.pack()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_swap_event() {
+        let event = SwapEvent {
+            swap: Pubkey::new_from_array([1u8; 32]),
+            swap_direction: SwapDirection::SellQuote,
+            amount_in: 1_000_000,
+            amount_out: 990_000,
+            trade_fee: 5_000,
+            admin_fee: 2_000,
+            base_reserve: 100_000_000,
+            quote_reserve: 200_000_000,
+            market_price: 2_000_000_000,
+        };
+        let check = Event::Swap(event.clone());
+        let packed = check.pack();
+        let mut expect = vec![SWAP_EVENT_TAG];
+        expect.extend_from_slice(&[1u8; 32]);
+        expect.push(1);
+        expect.extend_from_slice(&event.amount_in.to_le_bytes());
+        expect.extend_from_slice(&event.amount_out.to_le_bytes());
+        expect.extend_from_slice(&event.trade_fee.to_le_bytes());
+        expect.extend_from_slice(&event.admin_fee.to_le_bytes());
+        expect.extend_from_slice(&event.base_reserve.to_le_bytes());
+        expect.extend_from_slice(&event.quote_reserve.to_le_bytes());
+        expect.extend_from_slice(&event.market_price.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = Event::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_liquidity_events() {
+        let event = LiquidityEvent {
+            swap: Pubkey::new_from_array([2u8; 32]),
+            token_a_amount: 1_000,
+            token_b_amount: 2_000,
+            pool_token_amount: 3_000,
+            base_reserve: 4_000,
+            quote_reserve: 5_000,
+        };
+        let check = Event::Deposit(event.clone());
+        let packed = check.pack();
+        let mut expect = vec![DEPOSIT_EVENT_TAG];
+        expect.extend_from_slice(&[2u8; 32]);
+        expect.extend_from_slice(&event.token_a_amount.to_le_bytes());
+        expect.extend_from_slice(&event.token_b_amount.to_le_bytes());
+        expect.extend_from_slice(&event.pool_token_amount.to_le_bytes());
+        expect.extend_from_slice(&event.base_reserve.to_le_bytes());
+        expect.extend_from_slice(&event.quote_reserve.to_le_bytes());
+        assert_eq!(packed, expect);
+        assert_eq!(Event::unpack(&expect).unwrap(), check);
+
+        let check = Event::Withdraw(event);
+        let packed = check.pack();
+        expect[0] = WITHDRAW_EVENT_TAG;
+        assert_eq!(packed, expect);
+        assert_eq!(Event::unpack(&expect).unwrap(), check);
+
+        assert_eq!(
+            Event::unpack(&[3u8]).unwrap_err(),
+            SwapError::InstructionUnpackError.into()
+        );
+    }
+}
@@ -2,19 +2,34 @@
 
 #![allow(clippy::too_many_arguments)]
 
-use std::{convert::TryInto, mem::size_of};
+use std::{
+    convert::{TryFrom, TryInto},
+    mem::size_of,
+};
 
 use solana_program::{
+    ed25519_program,
     instruction::{AccountMeta, Instruction},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::{Pubkey, PUBKEY_BYTES},
-    sysvar::{clock, rent},
+    system_program,
+    sysvar::{clock, instructions, rent},
 };
 
 use crate::{
+    curve::CurveType,
     error::SwapError,
-    state::{Fees, Rewards},
+    metadata,
+    state::{
+        ed25519_instruction_data, find_dca_plan_address, find_delfi_lock_address,
+        find_deny_list_address, find_liquidity_provider_address, find_observation_buffer_address,
+        find_order_nonce_address, find_position_page_address, find_price_feed_address,
+        find_proposal_address, find_quote_account_address, find_registry_page_address,
+        find_snapshot_address, find_vote_record_address, find_whitelist_address, DirectionalFees,
+        FeeDiscount, Fees, HalvingSchedule, Rewards, SignedOrder, PROGRAM_VERSION,
+        SIGNED_ORDER_SIZE,
+    },
 };
 
 /// Instruction Type
@@ -31,8 +46,8 @@ impl InstructionType {
     pub fn check(input: &[u8]) -> Option<Self> {
         let (&tag, _rest) = input.split_first()?;
         match tag {
-            100..=106 => Some(Self::Admin),
-            0..=7 => Some(Self::Swap),
+            100..=137 => Some(Self::Admin),
+            0..=44 => Some(Self::Swap),
             _ => None,
         }
     }
@@ -51,6 +66,11 @@ pub struct InitializeData {
     pub mid_price: u128,
     /// flag to know about twap open
     pub is_open_twap: bool,
+    /// Seconds of observations averaged by the twap price, zero averages all of it
+    pub twap_window_secs: u64,
+    /// Symbol of the token pair, like `SOL-USDC`, naming the pool mint in the
+    /// token metadata when set. Left out by older clients
+    pub pair_symbol: Option<String>,
 }
 
 /// Swap direction
@@ -73,6 +93,19 @@ pub struct SwapData {
     pub minimum_amount_out: u64,
     /// Swap direction 0 -> Sell Base Token, 1 -> Sell Quote Token
     pub swap_direction: SwapDirection,
+    /// Maximum price impact of the swap against the mid price in basis points,
+    /// 0 for no limit. Optional in the packed instruction
+    pub max_price_impact_bps: u64,
+}
+
+/// Quote swap instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuoteSwapData {
+    /// SOURCE amount to quote the output of
+    pub amount_in: u64,
+    /// Swap direction 0 -> Sell Base Token, 1 -> Sell Quote Token
+    pub swap_direction: SwapDirection,
 }
 
 /// Deposit instruction data
@@ -100,6 +133,18 @@ pub struct WithdrawData {
     pub minimum_token_b_amount: u64,
 }
 
+/// Exact withdraw instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithdrawExactData {
+    /// Amount of token A to receive
+    pub token_a_amount: u64,
+    /// Amount of token B to receive
+    pub token_b_amount: u64,
+    /// Maximum amount of pool tokens to burn, prevents excessive slippage
+    pub max_pool_token_amount: u64,
+}
+
 /// Withdraw instruction data
 #[repr(C)]
 #[derive(Clone, Debug, PartialEq)]
@@ -111,6 +156,254 @@ pub struct WithdrawOneData {
     pub minimum_token_amount: u64,
 }
 
+/// Initialize position page instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InitializePositionPageData {
+    /// Index of the page in the chain of the liquidity provider
+    pub page_index: u8,
+}
+
+/// Transfer position instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferPositionData {
+    /// Number of position pages of the source liquidity provider, passed
+    /// before the ones of the destination
+    pub source_page_count: u8,
+}
+
+/// Set liquidity provider delegate instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetLiquidityProviderDelegateData {
+    /// New delegate, default pubkey to remove it
+    pub delegate: Pubkey,
+}
+
+/// Claim all rewards instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClaimAllRewardsData {
+    /// Number of token-swaps to claim the rewards of
+    pub swap_count: u8,
+}
+
+/// Lock DELFI instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockDelfiData {
+    /// DELFI locked
+    pub amount: u64,
+    /// Timestamp the DELFI can be unlocked from, not before the current one
+    /// of the lock
+    pub unlock_ts: i64,
+}
+
+/// Vote gauge instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoteGaugeData {
+    /// Share of the voting power for each token-swap, in basis points
+    pub weights_bps: Vec<u16>,
+}
+
+/// Create proposal instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateProposalData {
+    /// Fees of the config once executed
+    pub fees: Fees,
+    /// Rewards of the config once executed
+    pub rewards: Rewards,
+}
+
+/// Vote proposal instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoteProposalData {
+    /// Votes for the proposal, or against it
+    pub approve: bool,
+}
+
+/// Flash swap instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlashSwapData {
+    /// SOURCE amount the callback pays back, output to DESTINATION is based on the exchange rate
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token to output, prevents excessive slippage
+    pub minimum_amount_out: u64,
+    /// Swap direction 0 -> Sell Base Token, 1 -> Sell Quote Token
+    pub swap_direction: SwapDirection,
+    /// Maximum price impact of the swap against the mid price in basis points,
+    /// 0 for no limit
+    pub max_price_impact_bps: u64,
+    /// Data passed as is to the callback program
+    pub callback_data: Vec<u8>,
+}
+
+/// Relayed swap instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelayedSwapData {
+    /// SOURCE amount to transfer, output to DESTINATION is based on the exchange rate
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token to output, prevents excessive slippage
+    pub minimum_amount_out: u64,
+    /// Swap direction 0 -> Sell Base Token, 1 -> Sell Quote Token
+    pub swap_direction: SwapDirection,
+    /// Maximum price impact of the swap against the mid price in basis points,
+    /// 0 for no limit
+    pub max_price_impact_bps: u64,
+    /// SOURCE amount paid to the relayer on top of the amount swapped, signed
+    /// by the owner along with the swap
+    pub relayer_fee: u64,
+}
+
+/// Batch swap instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchSwapData {
+    /// Swaps in order, one per token-swap
+    pub swaps: Vec<SwapData>,
+    /// Minimum sum of the outputs of the swaps, prevents excessive slippage
+    /// of the whole batch
+    pub minimum_total_amount_out: u64,
+}
+
+/// Split swap instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitSwapData {
+    /// SOURCE amount to transfer, split between the token-swaps
+    pub amount_in: u64,
+    /// Minimum combined amount of DESTINATION token to output, prevents excessive slippage
+    pub minimum_amount_out: u64,
+    /// Swap direction 0 -> Sell Base Token, 1 -> Sell Quote Token
+    pub swap_direction: SwapDirection,
+    /// Fraction of the input sold on each token-swap in basis points,
+    /// adding up to 10000
+    pub fractions_bps: Vec<u16>,
+}
+
+/// Emergency withdraw instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmergencyWithdrawData {
+    /// Minimum amount of token A to receive, prevents excessive slippage
+    pub minimum_token_a_amount: u64,
+    /// Minimum amount of token B to receive, prevents excessive slippage
+    pub minimum_token_b_amount: u64,
+}
+
+/// Cancel signed orders instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CancelSignedOrdersData {
+    /// Lowest nonce of the orders still valid, above the current one
+    pub next_nonce: u64,
+}
+
+/// Create DCA plan instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateDcaPlanData {
+    /// Amount escrowed, sold over the periods
+    pub amount: u64,
+    /// Amount sold per period, the last one selling what is left
+    pub amount_per_period: u64,
+    /// Seconds between two periods
+    pub interval: i64,
+    /// Swap direction of the periods
+    pub swap_direction: SwapDirection,
+    /// Limit of the price impact of each period swap, required as the periods
+    /// are swapped without a minimum amount out
+    pub max_price_impact_bps: u64,
+    /// Share of each period amount paid to the keeper executing it, at most
+    /// `MAX_DCA_KEEPER_REWARD_BPS`
+    pub keeper_reward_bps: u64,
+}
+
+/// Compiled feature of the anchor CPI bindings
+pub const FEATURE_ANCHOR_CPI: u8 = 1 << 0;
+/// Compiled feature of the borsh serialization of the state
+pub const FEATURE_BORSH: u8 = 1 << 1;
+/// Compiled feature of the serde serialization of the math types
+pub const FEATURE_SERDE: u8 = 1 << 2;
+/// Compiled feature of the program-test builds
+pub const FEATURE_TEST_BPF: u8 = 1 << 3;
+/// Compiled feature of the wasm bindings
+pub const FEATURE_WASM: u8 = 1 << 4;
+
+/// Program build returned by `GetVersion`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProgramBuild {
+    /// Program version, the version of the accounts it creates
+    pub version: u8,
+    /// Compiled features, `FEATURE_*` flags
+    pub features: u8,
+}
+
+impl ProgramBuild {
+    /// Packed length of the program build
+    pub const LEN: usize = 2;
+
+    /// Build of the running program
+    pub fn current() -> Self {
+        let mut features = 0;
+        if cfg!(feature = "anchor-cpi") {
+            features |= FEATURE_ANCHOR_CPI;
+        }
+        if cfg!(feature = "borsh") {
+            features |= FEATURE_BORSH;
+        }
+        if cfg!(feature = "serde") {
+            features |= FEATURE_SERDE;
+        }
+        if cfg!(feature = "test-bpf") {
+            features |= FEATURE_TEST_BPF;
+        }
+        if cfg!(feature = "wasm") {
+            features |= FEATURE_WASM;
+        }
+        Self {
+            version: PROGRAM_VERSION,
+            features,
+        }
+    }
+
+    /// Packs the program build into return data
+    pub fn pack(&self) -> [u8; Self::LEN] {
+        [self.version, self.features]
+    }
+
+    /// Unpacks the program build from return data
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        match *input {
+            [version, features] => Ok(Self { version, features }),
+            _ => Err(SwapError::InstructionUnpackError.into()),
+        }
+    }
+}
+
+/// Initialize registry page instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InitializeRegistryPageData {
+    /// Index of the page in the registry of the config
+    pub page_index: u8,
+}
+
+/// Grow liquidity provider account instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrowProviderAccountData {
+    /// Number of positions the account can hold after growing
+    pub max_positions: u8,
+}
+
 /// ADMIN INSTRUCTION PARAMS
 /// Admin initialize config data
 #[repr(C)]
@@ -120,6 +413,10 @@ pub struct AdminInitializeData {
     pub fees: Fees,
     /// Default rewards
     pub rewards: Rewards,
+    /// Max change of the mid price per admin update, in basis points
+    pub max_mid_price_deviation_bps: u64,
+    /// Min seconds between two admin mid price updates of a pool
+    pub min_mid_price_update_interval: i64,
 }
 
 /// Set new admin key
@@ -130,6 +427,203 @@ pub struct CommitNewAdmin {
     pub new_admin_key: Pubkey,
 }
 
+/// Set new mid price
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetMidPrice {
+    /// New mid price - real value * 10**9
+    pub mid_price: u128,
+}
+
+/// Set new mid price bounds
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetMidPriceBounds {
+    /// Max change of the mid price per admin update, in basis points
+    pub max_mid_price_deviation_bps: u64,
+    /// Min seconds between two admin mid price updates of a pool
+    pub min_mid_price_update_interval: i64,
+}
+
+/// Set new max price impact
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetMaxPriceImpact {
+    /// Max price impact of a swap in basis points, zero disables the check
+    pub max_price_impact_bps: u64,
+}
+
+/// Set new max trade size
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetMaxTradeSize {
+    /// Max amount sold by a swap, zero disables the check
+    pub max_amount_in: u64,
+    /// Max amount sold by a swap in basis points of the pool reserve of the
+    /// token sold, zero disables the check
+    pub max_amount_in_reserve_bps: u64,
+}
+
+/// Set new per slot throttle
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetSlotThrottle {
+    /// Max number of swaps in a slot, zero disables the check
+    pub max_swaps_per_slot: u64,
+    /// Max base token volume of the swaps in a slot, zero disables the check
+    pub max_volume_per_slot: u64,
+}
+
+/// Set new fee compounding mode
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetFeeCompounding {
+    /// Whether the lp share of the trade fees is added to the pool targets
+    pub is_fee_compounding: bool,
+}
+
+/// Set new fee collection token
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetFeeInQuote {
+    /// Whether the trade fees are collected in the quote token
+    pub is_fee_in_quote: bool,
+}
+
+/// Add or remove a trader of the whitelist
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetWhitelistTrader {
+    /// Owner of the token accounts swapped from
+    pub trader: Pubkey,
+    /// Whether the trader is added or removed
+    pub is_allowed: bool,
+}
+
+/// Set new whitelist only mode
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetWhitelistOnly {
+    /// Whether only the traders on the whitelist can swap
+    pub is_whitelist_only: bool,
+}
+
+/// Set new transfer hook program
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetTransferHook {
+    /// Program approving the swaps and deposits, default to remove the hook
+    pub transfer_hook_program: Pubkey,
+}
+
+/// Set new OpenBook market
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetOpenbookMarket {
+    /// Market pricing the pool when the oracles are stale and the twap is
+    /// unavailable, default to remove it
+    pub openbook_market: Pubkey,
+}
+
+/// Set new DELFI price oracle
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetDeltafiPriceOracle {
+    /// Pyth price account of the DELFI token, default to keep the fixed price
+    pub deltafi_price_oracle: Pubkey,
+}
+
+/// Set new epoch reward budget
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetEpochRewardBudget {
+    /// Rewards mintable per epoch, 0 to remove the limit
+    pub epoch_reward_budget: u64,
+}
+
+/// Set new keeper incentive of `Poke`
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetPokeReward {
+    /// DELFI minted to the keeper of a `Poke`, 0 for no incentive
+    pub poke_reward: u64,
+    /// Min seconds since the last update of a pool for a `Poke` to be rewarded
+    pub min_poke_interval: i64,
+}
+
+/// Set new proposal quorum
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetProposalQuorum {
+    /// Min votes for a proposal to pass, 0 to disable the governance
+    pub proposal_quorum: u64,
+}
+
+/// Snapshot the liquidity of a token-swap
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    /// Id of the snapshot, unique per token-swap
+    pub snapshot_id: u64,
+    /// Merkle root of the liquidity provider balances at the slot of the
+    /// snapshot
+    pub merkle_root: [u8; 32],
+}
+
+/// Set new reserve caps
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetReserveCaps {
+    /// Max base token reserve after a deposit, zero disables the check
+    pub max_base_reserve: u64,
+    /// Max quote token reserve after a deposit, zero disables the check
+    pub max_quote_reserve: u64,
+}
+
+/// Set new user liquidity cap
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetUserLiquidityCap {
+    /// Max liquidity position of a user after a deposit, zero disables the check
+    pub max_user_liquidity: u64,
+}
+
+/// Set new min trade fee
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetMinTradeFee {
+    /// Smallest trade fee charged on a swap, zero disables the floor
+    pub min_trade_fee: u64,
+}
+
+/// Set new referral reward
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetReferralReward {
+    /// Slice of the liquidity rewards claimed minted to the referrer, in basis points
+    pub referral_reward_bps: u64,
+}
+
+/// Add or remove a mint of the deny-list
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetDeniedMint {
+    /// Token mint no pool can be created for
+    pub mint: Pubkey,
+    /// Whether the mint is added or removed
+    pub is_denied: bool,
+}
+
+/// Buy DELFI with admin fees and burn it
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BuybackAndBurn {
+    /// Admin fees sold for DELFI
+    pub amount_in: u64,
+    /// Minimum DELFI bought and burned
+    pub minimum_amount_out: u64,
+}
+
 /// Admin only instructions.
 #[repr(C)]
 #[derive(Debug, PartialEq)]
@@ -144,10 +638,267 @@ pub enum AdminInstruction {
     SetFeeAccount,
     /// TODO: Docs
     CommitNewAdmin(CommitNewAdmin),
-    /// TODO: Docs
+    /// Retune the fees of a pool, the min trade fee left unchanged.
+    /// Every denominator must be set and no fee can take the whole amount
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Swap info
+    ///   2. `[signer]` Admin account
     SetNewFees(Fees),
-    /// TODO: Docs
+    /// Retune the rewards of a pool, both reward denominators must be set
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Swap info
+    ///   2. `[signer]` Admin account
     SetNewRewards(Rewards),
+    /// Override the mid price of a pool without a live oracle
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    ///   3. `[]` Pyth price account for token a
+    ///   4. `[]` Pyth price account for token b
+    ///   5. `[]` Clock sysvar
+    ///   6. `[writable]` Observation buffer of the token-swap
+    ///   7. `[writable]` Price feed of the token-swap, not written until it is created
+    ///   8. `[]` Pool mint of the token-swap
+    SetMidPrice(SetMidPrice),
+    /// Rewrite the config, and optionally a token-swap, into the current
+    /// layout and bump their version
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[signer]` Admin
+    ///   2. `[writable, signer]` Payer of the rent for the grown accounts
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    ///   5. `[writable]` Optional token-swap to migrate
+    ///   6. `[]` Pyth price account for token a, required with the token-swap,
+    ///      stored by version 1 token-swaps only
+    ///   7. `[]` Pyth price account for token b, required with the token-swap,
+    ///      stored by version 1 token-swaps only
+    MigrateState,
+    /// Set the max price impact of the swaps of a pool
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetMaxPriceImpact(SetMaxPriceImpact),
+    /// Set the max amount sold by the swaps of a pool
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetMaxTradeSize(SetMaxTradeSize),
+    /// Set the max swaps and volume in a slot of a pool
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetSlotThrottle(SetSlotThrottle),
+    /// Turn the compounding of the lp trade fees into the pool targets on or off
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetFeeCompounding(SetFeeCompounding),
+    /// Create the empty trader whitelist of a pool
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[]` Token-swap
+    ///   2. `[signer]` Admin
+    ///   3. `[writable]` Whitelist, derived from `[token-swap, "whitelist"]`
+    ///   4. `[writable, signer]` Payer of the rent
+    ///   5. `[]` Rent sysvar
+    ///   6. `[]` System program
+    InitializeWhitelist,
+    /// Add or remove a trader of the whitelist of a pool
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[]` Token-swap
+    ///   2. `[signer]` Admin
+    ///   3. `[writable]` Whitelist of the token-swap
+    SetWhitelistTrader(SetWhitelistTrader),
+    /// Restrict the swaps of a pool to the traders on its whitelist, or lift
+    /// the restriction
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    ///   3. `[]` Whitelist of the token-swap
+    SetWhitelistOnly(SetWhitelistOnly),
+    /// Set the program called to approve the swaps and deposits of a pool,
+    /// or remove it
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetTransferHook(SetTransferHook),
+    /// Set the Pyth price account the liquidity rewards of every pool are
+    /// valued at, or go back to the fixed price
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[signer]` Admin
+    SetDeltafiPriceOracle(SetDeltafiPriceOracle),
+    /// Set the rewards mintable per epoch by swaps and claims of every pool
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[signer]` Admin
+    SetEpochRewardBudget(SetEpochRewardBudget),
+    /// Set the halving schedule of the trade and liquidity rewards of a pool
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetHalvingSchedule(HalvingSchedule),
+    /// Sell the admin fees of a token-swap for DELFI through a DELFI pool,
+    /// burning the DELFI bought straight from the reserve of that pool
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[]` Token-swap of the admin fees
+    ///   2. `[]` $authority of the token-swap of the admin fees
+    ///   3. `[signer]` Admin
+    ///   4. `[writable]` Admin fee account sold, owned by the admin or the $authority
+    ///   5. `[writable]` DELFI token-swap, pairing DELFI with the admin fee token
+    ///   6. `[]` $authority of the DELFI token-swap
+    ///   7. `[writable]` token_a Base Account of the DELFI token-swap
+    ///   8. `[writable]` token_b Quote Account of the DELFI token-swap
+    ///   9. `[writable]` DELFI admin fee Account of the DELFI token-swap
+    ///   10. `[writable]` Rewards mint deltafi
+    ///   11. `[]` Pyth price account of token_a
+    ///   12. `[]` Pyth price account of token_b
+    ///   13. `[]` Clock sysvar
+    ///   14. `[]` Token program id
+    ///   15. `[writable]` Observation buffer of the DELFI token-swap
+    ///   16. `[writable]` Price feed of the DELFI token-swap, not written until it is created
+    ///   17. `[]` Pool mint of the DELFI token-swap
+    ///
+    ///   .. `[]` OpenBook market of the DELFI token-swap, then its bids and
+    ///      asks, only when the DELFI token-swap has a market
+    BuybackAndBurn(BuybackAndBurn),
+    /// Set the DELFI minted to the keepers poking the twap of quiet pools
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[signer]` Admin
+    SetPokeReward(SetPokeReward),
+    /// Set the min votes for a proposal to pass, enabling the governance
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[signer]` Admin
+    SetProposalQuorum(SetProposalQuorum),
+    /// Record the pool mint supply and reserves of a token-swap at the current
+    /// block, with the merkle root of its liquidity provider balances
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[]` Token-swap
+    ///   2. `[signer]` Admin
+    ///   3. `[]` Pool mint
+    ///   4. `[writable]` Snapshot, derived from `[token-swap, snapshot_id, "snapshot"]`
+    ///   5. `[writable, signer]` Payer of the rent
+    ///   6. `[]` Rent sysvar
+    ///   7. `[]` System program
+    Snapshot(Snapshot),
+    /// Set the max reserves the deposits can fill a pool to
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetReserveCaps(SetReserveCaps),
+    /// Set the max liquidity position a user can deposit to
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetUserLiquidityCap(SetUserLiquidityCap),
+    /// Make the parameters of the token-swap final, the admin can no longer
+    /// pause it or change its fees, rewards and limits
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    MakeImmutable,
+    /// Set the trade fee discount tiers of the traders holding DELFI
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[signer]` Admin
+    SetFeeDiscount(FeeDiscount),
+    /// Set the trade fee rates of a pool per swap direction, skewing the fees
+    /// to push the pool back toward the oracle price
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetDirectionalFees(DirectionalFees),
+    /// Set the smallest trade fee charged on a swap of the pool, so the dust
+    /// trades can not round the fee down to zero
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetMinTradeFee(SetMinTradeFee),
+    /// Turn the collection of the trade fees in the quote token on or off, the
+    /// base trade fee of the swaps selling quote is then sold to the pool
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetFeeInQuote(SetFeeInQuote),
+    /// Set the slice of the liquidity rewards claimed additionally minted to
+    /// the referrer of the liquidity provider
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[signer]` Admin
+    SetReferralReward(SetReferralReward),
+    /// Create the empty deny-list of the mints of a config
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[signer]` Admin
+    ///   2. `[writable]` Deny-list, derived from `[config, "deny_list"]`
+    ///   3. `[writable, signer]` Payer of the rent
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` System program
+    InitializeDenyList,
+    /// Add or remove a mint of the deny-list of a config
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[signer]` Admin
+    ///   2. `[writable]` Deny-list of the config
+    SetDeniedMint(SetDeniedMint),
+    /// Reset the pool to the balances of the swap accounts, the targets being
+    /// the balances at the market price, read from the oracles when they are
+    /// live. Recovers a pool state drifted from the token balances.
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    ///   3. `[]` token_a Swap Account
+    ///   4. `[]` token_b Swap Account
+    ///   5. `[]` Token program id
+    ///   6. `[]` Pyth price account for token a
+    ///   7. `[]` Pyth price account for token b
+    ///   8. `[]` Clock sysvar
+    ///   9. `[writable]` Price feed of the token-swap, not written until it is created
+    ///   10. `[]` Pool mint of the token-swap
+    Recalibrate,
+    /// Set the OpenBook market pricing a pool when its oracles are stale and
+    /// its twap is unavailable, or remove it. The market trades token a for
+    /// token b.
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    ///   3. `[]` OpenBook market, unless removing it
+    SetOpenbookMarket(SetOpenbookMarket),
+    /// Set the curve the swaps of a pool are quoted on
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetCurveType(CurveType),
+    /// Set the bounds of the admin mid price updates of every pool
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[signer]` Admin
+    SetMidPriceBounds(SetMidPriceBounds),
 }
 
 impl AdminInstruction {
@@ -160,9 +911,16 @@ impl AdminInstruction {
             100 => {
                 let (fees, rest) = rest.split_at(Fees::LEN);
                 let fees = Fees::unpack_unchecked(fees)?;
-                let (rewards, _rest) = rest.split_at(Rewards::LEN);
+                let (rewards, rest) = rest.split_at(Rewards::LEN);
                 let rewards = Rewards::unpack_unchecked(rewards)?;
-                Self::Initialize(AdminInitializeData { fees, rewards })
+                let (max_mid_price_deviation_bps, rest) = unpack_u64(rest)?;
+                let (min_mid_price_update_interval, _) = unpack_i64(rest)?;
+                Self::Initialize(AdminInitializeData {
+                    fees,
+                    rewards,
+                    max_mid_price_deviation_bps,
+                    min_mid_price_update_interval,
+                })
             }
             101 => Self::Pause,
             102 => Self::Unpause,
@@ -179,15 +937,172 @@ impl AdminInstruction {
                 let rewards = Rewards::unpack_unchecked(rest)?;
                 Self::SetNewRewards(rewards)
             }
-            _ => return Err(SwapError::InvalidInstruction.into()),
-        })
-    }
+            107 => {
+                let (mid_price, _) = unpack_u128(rest)?;
+                Self::SetMidPrice(SetMidPrice { mid_price })
+            }
+            108 => Self::MigrateState,
+            109 => {
+                let (max_price_impact_bps, _) = unpack_u64(rest)?;
+                Self::SetMaxPriceImpact(SetMaxPriceImpact {
+                    max_price_impact_bps,
+                })
+            }
+            110 => {
+                let (max_amount_in, rest) = unpack_u64(rest)?;
+                let (max_amount_in_reserve_bps, _) = unpack_u64(rest)?;
+                Self::SetMaxTradeSize(SetMaxTradeSize {
+                    max_amount_in,
+                    max_amount_in_reserve_bps,
+                })
+            }
+            111 => {
+                let (max_swaps_per_slot, rest) = unpack_u64(rest)?;
+                let (max_volume_per_slot, _) = unpack_u64(rest)?;
+                Self::SetSlotThrottle(SetSlotThrottle {
+                    max_swaps_per_slot,
+                    max_volume_per_slot,
+                })
+            }
+            112 => {
+                let (is_fee_compounding, _) = unpack_bool(rest)?;
+                Self::SetFeeCompounding(SetFeeCompounding { is_fee_compounding })
+            }
+            113 => Self::InitializeWhitelist,
+            114 => {
+                let (trader, rest) = unpack_pubkey(rest)?;
+                let (is_allowed, _) = unpack_bool(rest)?;
+                Self::SetWhitelistTrader(SetWhitelistTrader { trader, is_allowed })
+            }
+            115 => {
+                let (is_whitelist_only, _) = unpack_bool(rest)?;
+                Self::SetWhitelistOnly(SetWhitelistOnly { is_whitelist_only })
+            }
+            116 => {
+                let (transfer_hook_program, _) = unpack_pubkey(rest)?;
+                Self::SetTransferHook(SetTransferHook {
+                    transfer_hook_program,
+                })
+            }
+            117 => {
+                let (deltafi_price_oracle, _) = unpack_pubkey(rest)?;
+                Self::SetDeltafiPriceOracle(SetDeltafiPriceOracle {
+                    deltafi_price_oracle,
+                })
+            }
+            118 => {
+                let (epoch_reward_budget, _) = unpack_u64(rest)?;
+                Self::SetEpochRewardBudget(SetEpochRewardBudget {
+                    epoch_reward_budget,
+                })
+            }
+            119 => {
+                let halving_schedule = HalvingSchedule::unpack_unchecked(rest)?;
+                Self::SetHalvingSchedule(halving_schedule)
+            }
+            120 => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (minimum_amount_out, _) = unpack_u64(rest)?;
+                Self::BuybackAndBurn(BuybackAndBurn {
+                    amount_in,
+                    minimum_amount_out,
+                })
+            }
+            121 => {
+                let (poke_reward, rest) = unpack_u64(rest)?;
+                let (min_poke_interval, _) = unpack_i64(rest)?;
+                Self::SetPokeReward(SetPokeReward {
+                    poke_reward,
+                    min_poke_interval,
+                })
+            }
+            122 => {
+                let (proposal_quorum, _) = unpack_u64(rest)?;
+                Self::SetProposalQuorum(SetProposalQuorum { proposal_quorum })
+            }
+            123 => {
+                let (snapshot_id, rest) = unpack_u64(rest)?;
+                let merkle_root = rest
+                    .get(..32)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(SwapError::InstructionUnpackError)?;
+                Self::Snapshot(Snapshot {
+                    snapshot_id,
+                    merkle_root,
+                })
+            }
+            124 => {
+                let (max_base_reserve, rest) = unpack_u64(rest)?;
+                let (max_quote_reserve, _) = unpack_u64(rest)?;
+                Self::SetReserveCaps(SetReserveCaps {
+                    max_base_reserve,
+                    max_quote_reserve,
+                })
+            }
+            125 => {
+                let (max_user_liquidity, _) = unpack_u64(rest)?;
+                Self::SetUserLiquidityCap(SetUserLiquidityCap { max_user_liquidity })
+            }
+            126 => Self::MakeImmutable,
+            127 => {
+                let fee_discount = FeeDiscount::unpack_unchecked(rest)?;
+                Self::SetFeeDiscount(fee_discount)
+            }
+            128 => {
+                let directional_fees = DirectionalFees::unpack_unchecked(rest)?;
+                Self::SetDirectionalFees(directional_fees)
+            }
+            129 => {
+                let (min_trade_fee, _) = unpack_u64(rest)?;
+                Self::SetMinTradeFee(SetMinTradeFee { min_trade_fee })
+            }
+            130 => {
+                let (is_fee_in_quote, _) = unpack_bool(rest)?;
+                Self::SetFeeInQuote(SetFeeInQuote { is_fee_in_quote })
+            }
+            131 => {
+                let (referral_reward_bps, _) = unpack_u64(rest)?;
+                Self::SetReferralReward(SetReferralReward {
+                    referral_reward_bps,
+                })
+            }
+            132 => Self::InitializeDenyList,
+            133 => {
+                let (mint, rest) = unpack_pubkey(rest)?;
+                let (is_denied, _) = unpack_bool(rest)?;
+                Self::SetDeniedMint(SetDeniedMint { mint, is_denied })
+            }
+            134 => Self::Recalibrate,
+            135 => {
+                let (openbook_market, _) = unpack_pubkey(rest)?;
+                Self::SetOpenbookMarket(SetOpenbookMarket { openbook_market })
+            }
+            136 => {
+                let curve_type = CurveType::unpack_unchecked(rest)?;
+                Self::SetCurveType(curve_type)
+            }
+            137 => {
+                let (max_mid_price_deviation_bps, rest) = unpack_u64(rest)?;
+                let (min_mid_price_update_interval, _) = unpack_i64(rest)?;
+                Self::SetMidPriceBounds(SetMidPriceBounds {
+                    max_mid_price_deviation_bps,
+                    min_mid_price_update_interval,
+                })
+            }
+            _ => return Err(SwapError::InvalidInstruction.into()),
+        })
+    }
 
     /// Packs a [AdminInstruction](enum.AdminInstruciton.html) into a byte buffer.
     pub fn pack(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(size_of::<Self>());
         match &*self {
-            Self::Initialize(AdminInitializeData { fees, rewards }) => {
+            Self::Initialize(AdminInitializeData {
+                fees,
+                rewards,
+                max_mid_price_deviation_bps,
+                min_mid_price_update_interval,
+            }) => {
                 buf.push(100);
                 let mut fees_slice = [0u8; Fees::LEN];
                 Pack::pack_into_slice(fees, &mut fees_slice[..]);
@@ -195,6 +1110,8 @@ impl AdminInstruction {
                 let mut rewards_slice = [0u8; Rewards::LEN];
                 Pack::pack_into_slice(rewards, &mut rewards_slice[..]);
                 buf.extend_from_slice(&rewards_slice);
+                buf.extend_from_slice(&max_mid_price_deviation_bps.to_le_bytes());
+                buf.extend_from_slice(&min_mid_price_update_interval.to_le_bytes());
             }
             Self::Pause => buf.push(101),
             Self::Unpause => buf.push(102),
@@ -215,6 +1132,163 @@ impl AdminInstruction {
                 Pack::pack_into_slice(rewards, &mut rewards_slice[..]);
                 buf.extend_from_slice(&rewards_slice);
             }
+            Self::SetMidPrice(SetMidPrice { mid_price }) => {
+                buf.push(107);
+                buf.extend_from_slice(&mid_price.to_le_bytes());
+            }
+            Self::MigrateState => buf.push(108),
+            Self::SetMaxPriceImpact(SetMaxPriceImpact {
+                max_price_impact_bps,
+            }) => {
+                buf.push(109);
+                buf.extend_from_slice(&max_price_impact_bps.to_le_bytes());
+            }
+            Self::SetMaxTradeSize(SetMaxTradeSize {
+                max_amount_in,
+                max_amount_in_reserve_bps,
+            }) => {
+                buf.push(110);
+                buf.extend_from_slice(&max_amount_in.to_le_bytes());
+                buf.extend_from_slice(&max_amount_in_reserve_bps.to_le_bytes());
+            }
+            Self::SetSlotThrottle(SetSlotThrottle {
+                max_swaps_per_slot,
+                max_volume_per_slot,
+            }) => {
+                buf.push(111);
+                buf.extend_from_slice(&max_swaps_per_slot.to_le_bytes());
+                buf.extend_from_slice(&max_volume_per_slot.to_le_bytes());
+            }
+            Self::SetFeeCompounding(SetFeeCompounding { is_fee_compounding }) => {
+                buf.push(112);
+                buf.extend_from_slice(&(*is_fee_compounding as u8).to_le_bytes());
+            }
+            Self::InitializeWhitelist => buf.push(113),
+            Self::SetWhitelistTrader(SetWhitelistTrader { trader, is_allowed }) => {
+                buf.push(114);
+                buf.extend_from_slice(trader.as_ref());
+                buf.extend_from_slice(&(*is_allowed as u8).to_le_bytes());
+            }
+            Self::SetWhitelistOnly(SetWhitelistOnly { is_whitelist_only }) => {
+                buf.push(115);
+                buf.extend_from_slice(&(*is_whitelist_only as u8).to_le_bytes());
+            }
+            Self::SetTransferHook(SetTransferHook {
+                transfer_hook_program,
+            }) => {
+                buf.push(116);
+                buf.extend_from_slice(transfer_hook_program.as_ref());
+            }
+            Self::SetDeltafiPriceOracle(SetDeltafiPriceOracle {
+                deltafi_price_oracle,
+            }) => {
+                buf.push(117);
+                buf.extend_from_slice(deltafi_price_oracle.as_ref());
+            }
+            Self::SetEpochRewardBudget(SetEpochRewardBudget {
+                epoch_reward_budget,
+            }) => {
+                buf.push(118);
+                buf.extend_from_slice(&epoch_reward_budget.to_le_bytes());
+            }
+            Self::SetHalvingSchedule(halving_schedule) => {
+                buf.push(119);
+                let mut halving_schedule_slice = [0u8; HalvingSchedule::LEN];
+                Pack::pack_into_slice(halving_schedule, &mut halving_schedule_slice[..]);
+                buf.extend_from_slice(&halving_schedule_slice);
+            }
+            Self::BuybackAndBurn(BuybackAndBurn {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                buf.push(120);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+            }
+            Self::SetPokeReward(SetPokeReward {
+                poke_reward,
+                min_poke_interval,
+            }) => {
+                buf.push(121);
+                buf.extend_from_slice(&poke_reward.to_le_bytes());
+                buf.extend_from_slice(&min_poke_interval.to_le_bytes());
+            }
+            Self::SetProposalQuorum(SetProposalQuorum { proposal_quorum }) => {
+                buf.push(122);
+                buf.extend_from_slice(&proposal_quorum.to_le_bytes());
+            }
+            Self::Snapshot(Snapshot {
+                snapshot_id,
+                merkle_root,
+            }) => {
+                buf.push(123);
+                buf.extend_from_slice(&snapshot_id.to_le_bytes());
+                buf.extend_from_slice(merkle_root);
+            }
+            Self::SetReserveCaps(SetReserveCaps {
+                max_base_reserve,
+                max_quote_reserve,
+            }) => {
+                buf.push(124);
+                buf.extend_from_slice(&max_base_reserve.to_le_bytes());
+                buf.extend_from_slice(&max_quote_reserve.to_le_bytes());
+            }
+            Self::SetUserLiquidityCap(SetUserLiquidityCap { max_user_liquidity }) => {
+                buf.push(125);
+                buf.extend_from_slice(&max_user_liquidity.to_le_bytes());
+            }
+            Self::MakeImmutable => buf.push(126),
+            Self::SetFeeDiscount(fee_discount) => {
+                buf.push(127);
+                let mut fee_discount_slice = [0u8; FeeDiscount::LEN];
+                Pack::pack_into_slice(fee_discount, &mut fee_discount_slice[..]);
+                buf.extend_from_slice(&fee_discount_slice);
+            }
+            Self::SetDirectionalFees(directional_fees) => {
+                buf.push(128);
+                let mut directional_fees_slice = [0u8; DirectionalFees::LEN];
+                Pack::pack_into_slice(directional_fees, &mut directional_fees_slice[..]);
+                buf.extend_from_slice(&directional_fees_slice);
+            }
+            Self::SetMinTradeFee(SetMinTradeFee { min_trade_fee }) => {
+                buf.push(129);
+                buf.extend_from_slice(&min_trade_fee.to_le_bytes());
+            }
+            Self::SetFeeInQuote(SetFeeInQuote { is_fee_in_quote }) => {
+                buf.push(130);
+                buf.extend_from_slice(&(*is_fee_in_quote as u8).to_le_bytes());
+            }
+            Self::SetReferralReward(SetReferralReward {
+                referral_reward_bps,
+            }) => {
+                buf.push(131);
+                buf.extend_from_slice(&referral_reward_bps.to_le_bytes());
+            }
+            Self::InitializeDenyList => buf.push(132),
+            Self::SetDeniedMint(SetDeniedMint { mint, is_denied }) => {
+                buf.push(133);
+                buf.extend_from_slice(mint.as_ref());
+                buf.extend_from_slice(&(*is_denied as u8).to_le_bytes());
+            }
+            Self::Recalibrate => buf.push(134),
+            Self::SetOpenbookMarket(SetOpenbookMarket { openbook_market }) => {
+                buf.push(135);
+                buf.extend_from_slice(openbook_market.as_ref());
+            }
+            Self::SetCurveType(curve_type) => {
+                buf.push(136);
+                let mut curve_type_slice = [0u8; CurveType::LEN];
+                Pack::pack_into_slice(curve_type, &mut curve_type_slice[..]);
+                buf.extend_from_slice(&curve_type_slice);
+            }
+            Self::SetMidPriceBounds(SetMidPriceBounds {
+                max_mid_price_deviation_bps,
+                min_mid_price_update_interval,
+            }) => {
+                buf.push(137);
+                buf.extend_from_slice(&max_mid_price_deviation_bps.to_le_bytes());
+                buf.extend_from_slice(&min_mid_price_update_interval.to_le_bytes());
+            }
         }
         buf
     }
@@ -229,8 +1303,16 @@ pub fn initialize_config(
     admin_pubkey: Pubkey,
     fees: Fees,
     rewards: Rewards,
+    max_mid_price_deviation_bps: u64,
+    min_mid_price_update_interval: i64,
 ) -> Result<Instruction, ProgramError> {
-    let data = AdminInstruction::Initialize(AdminInitializeData { fees, rewards }).pack();
+    let data = AdminInstruction::Initialize(AdminInitializeData {
+        fees,
+        rewards,
+        max_mid_price_deviation_bps,
+        min_mid_price_update_interval,
+    })
+    .pack();
 
     let accounts = vec![
         AccountMeta::new(config_pubkey, false),
@@ -389,269 +1471,109 @@ pub fn set_new_rewards(
     })
 }
 
-/// Instructions supported by the pool SwapInfo program.
-#[repr(C)]
-#[derive(Debug, PartialEq)]
-pub enum SwapInstruction {
-    ///   Initializes a new SwapInfo.
-    ///
-    ///   0. `[writable, signer]` New Token-swap to create.
-    ///   1. `[]` $authority derived from `create_program_address(&[Token-swap account])`
-    ///   2. `[]` admin Account.
-    ///   3. `[]` admin_fee_a admin fee Account for token_a.
-    ///   4. `[]` admin_fee_b admin fee Account for token_b.
-    ///   5. `[]` token_a Account. Must be non zero, owned by $authority.
-    ///   6. `[]` token_b Account. Must be non zero, owned by $authority.
-    ///   7. `[writable]` Pool Token Mint. Must be empty, owned by $authority.
-    Initialize(InitializeData),
-
-    ///   Swap the tokens in the pool.
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` $authority
-    ///   2. `[writable]` token_(A|B) SOURCE Account, amount is transferable by $authority,
-    ///   3. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
-    ///   4. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
-    ///   5. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
-    ///   6. `[writable]` token_(A|B) admin fee Account. Must have same mint as DESTINATION token.
-    ///   7. `[]` Token program id
-    ///   8. `[]` Clock sysvar
-    Swap(SwapData),
+/// Creates a 'set_halving_schedule' instruction.
+pub fn set_halving_schedule(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    halving_schedule: HalvingSchedule,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetHalvingSchedule(halving_schedule).pack();
 
-    ///   Deposit some tokens into the pool.  The output is a "pool" token representing ownership
-    ///   into the pool. Inputs are converted to the current ratio.
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` $authority
-    ///   2. `[writable]` token_a $authority can transfer amount,
-    ///   3. `[writable]` token_b $authority can transfer amount,
-    ///   4. `[writable]` token_a Base Account to deposit into.
-    ///   5. `[writable]` token_b Base Account to deposit into.
-    ///   6. `[writable]` Pool MINT account, $authority is the owner.
-    ///   7. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
-    ///   8. `[]` Token program id
-    ///   9. `[]` Clock sysvar
-    Deposit(DepositData),
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
 
-    ///   Withdraw tokens from the pool at the current ratio.
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` $authority
-    ///   2. `[writable]` Pool mint account, $authority is the owner
-    ///   3. `[writable]` SOURCE Pool account, amount is transferable by $authority.
-    ///   4. `[writable]` token_a Swap Account to withdraw FROM.
-    ///   5. `[writable]` token_b Swap Account to withdraw FROM.
-    ///   6. `[writable]` token_a user Account to credit.
-    ///   7. `[writable]` token_b user Account to credit.
-    ///   8. `[writable]` admin_fee_a admin fee Account for token_a.
-    ///   9. `[writable]` admin_fee_b admin fee Account for token_b.
-    ///   10. `[]` Token program id
-    Withdraw(WithdrawData),
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
 
-    // ///   Withdraw one token from the pool at the current ratio.
-    // ///
-    // ///   0. `[]` Token-swap
-    // ///   1. `[]` $authority
-    // ///   2. `[writable]` Pool mint account, $authority is the owner
-    // ///   3. `[writable]` SOURCE Pool account, amount is transferable by $authority.
-    // ///   4. `[writable]` token_(A|B) BASE token Swap Account to withdraw FROM.
-    // ///   5. `[writable]` token_(A|B) QUOTE token Swap Account to exchange to base token.
-    // ///   6. `[writable]` token_(A|B) BASE token user Account to credit.
-    // ///   7. `[writable]` token_(A|B) admin fee Account. Must have same mint as BASE token.
-    // ///   8. `[]` Token program id
-    // ///   9. `[]` Clock sysvar
-    // WithdrawOne(WithdrawOneData),
+/// Creates a 'buyback_and_burn' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn buyback_and_burn(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    swap_authority_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    admin_fee_source_pubkey: Pubkey,
+    deltafi_swap_pubkey: Pubkey,
+    deltafi_swap_authority_pubkey: Pubkey,
+    token_a_pubkey: Pubkey,
+    token_b_pubkey: Pubkey,
+    admin_fee_destination_pubkey: Pubkey,
+    deltafi_mint_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    deltafi_pool_mint_pubkey: Pubkey,
+    buyback_data: BuybackAndBurn,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::BuybackAndBurn(buyback_data).pack();
 
-    // ///   Calc the receive amount in the pool.
-    // ///
-    // ///   0. `[]` Token-swap
-    // ///   1. `[]` $authority
-    // ///   2. `[writable]` token_(A|B) SOURCE Account, amount is transferable by $authority,
-    // ///   3. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
-    // ///   4. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
-    // ///   5. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
-    // ///   6. `[writable]` token_(A|B) admin fee Account. Must have same mint as DESTINATION token.
-    // ///   7. `[]` Token program id
-    // ///   8. `[]` Clock sysvar
-    // CalcReceiveAmount(SwapData),
-    /// Initialize liquidity provider account
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[writable]` liquidity provider info
-    ///   2. `[signer]` liquidity provider owner
-    ///   3. `[]` Token program id
-    ///   4. `[]` Clock sysvar
-    InitializeLiquidityProvider,
+    let (observation_buffer_pubkey, _) =
+        find_observation_buffer_address(&program_id, &deltafi_swap_pubkey);
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &deltafi_swap_pubkey);
 
-    /// Claim deltafi reward of liquidity provider
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` $authority
-    ///   2. `[writable]` Liquidity provider info
-    ///   3. `[signer]` Liquidity provider owner
-    ///   4. `[writable]` Rewards receiver
-    ///   5. `[writable]` Rewards mint deltafi
-    ///   6. `[]` Token program id
-    ClaimLiquidityRewards,
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(swap_authority_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new(admin_fee_source_pubkey, false),
+        AccountMeta::new(deltafi_swap_pubkey, false),
+        AccountMeta::new_readonly(deltafi_swap_authority_pubkey, false),
+        AccountMeta::new(token_a_pubkey, false),
+        AccountMeta::new(token_b_pubkey, false),
+        AccountMeta::new(admin_fee_destination_pubkey, false),
+        AccountMeta::new(deltafi_mint_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(observation_buffer_pubkey, false),
+        AccountMeta::new(price_feed_pubkey, false),
+        AccountMeta::new_readonly(deltafi_pool_mint_pubkey, false),
+    ];
 
-    /// Refresh liquidity obligation
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` Clock sysvar
-    ///   .. `[]` Liquidity provider accounts - refreshed, all, in order.
-    RefreshLiquidityObligation,
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-impl SwapInstruction {
-    /// Unpacks a byte buffer into a [SwapInstruction](enum.SwapInstruction.html).
-    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        let (&tag, rest) = input
-            .split_first()
-            .ok_or(SwapError::InstructionUnpackError)?;
-        Ok(match tag {
-            0x0 => {
-                let (&nonce, rest) = rest
-                    .split_first()
-                    .ok_or(SwapError::InstructionUnpackError)?;
-                let (slope, rest) = unpack_u64(rest)?;
-                let (mid_price, rest) = unpack_u128(rest)?;
-                let (is_open_twap, _) = unpack_bool(rest)?;
-                Self::Initialize(InitializeData {
-                    nonce,
-                    slope,
-                    mid_price,
-                    is_open_twap,
-                })
-            }
-            0x1 => {
-                let (amount_in, rest) = unpack_u64(rest)?;
-                let (minimum_amount_out, rest) = unpack_u64(rest)?;
-                let (swap_direction, _) = unpack_swap_direction(rest)?;
-                Self::Swap(SwapData {
-                    amount_in,
-                    minimum_amount_out,
-                    swap_direction,
-                })
-            }
-            0x2 => {
-                let (token_a_amount, rest) = unpack_u64(rest)?;
-                let (token_b_amount, rest) = unpack_u64(rest)?;
-                let (min_mint_amount, _) = unpack_u64(rest)?;
-                Self::Deposit(DepositData {
-                    token_a_amount,
-                    token_b_amount,
-                    min_mint_amount,
-                })
-            }
-            0x3 => {
-                let (pool_token_amount, rest) = unpack_u64(rest)?;
-                let (minimum_token_a_amount, rest) = unpack_u64(rest)?;
-                let (minimum_token_b_amount, _) = unpack_u64(rest)?;
-                Self::Withdraw(WithdrawData {
-                    pool_token_amount,
-                    minimum_token_a_amount,
-                    minimum_token_b_amount,
-                })
-            }
-            0x4 => Self::InitializeLiquidityProvider,
-            0x5 => Self::ClaimLiquidityRewards,
-            0x6 => Self::RefreshLiquidityObligation,
-            _ => return Err(SwapError::InvalidInstruction.into()),
-        })
-    }
+/// Creates a 'set_mid_price' instruction.
+pub fn set_mid_price(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    mid_price: u128,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetMidPrice(SetMidPrice { mid_price }).pack();
 
-    /// Packs a [SwapInstruction](enum.SwapInstruction.html) into a byte buffer.
-    pub fn pack(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(size_of::<Self>());
-        match *self {
-            Self::Initialize(InitializeData {
-                nonce,
-                slope,
-                mid_price,
-                is_open_twap,
-            }) => {
-                buf.push(0x0);
-                buf.push(nonce);
-                buf.extend_from_slice(&slope.to_le_bytes());
-                buf.extend_from_slice(&mid_price.to_le_bytes());
-                buf.extend_from_slice(&(is_open_twap as u8).to_le_bytes());
-            }
-            Self::Swap(SwapData {
-                amount_in,
-                minimum_amount_out,
-                swap_direction,
-            }) => {
-                buf.push(0x1);
-                buf.extend_from_slice(&amount_in.to_le_bytes());
-                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
-                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
-            }
-            Self::Deposit(DepositData {
-                token_a_amount,
-                token_b_amount,
-                min_mint_amount,
-            }) => {
-                buf.push(0x2);
-                buf.extend_from_slice(&token_a_amount.to_le_bytes());
-                buf.extend_from_slice(&token_b_amount.to_le_bytes());
-                buf.extend_from_slice(&min_mint_amount.to_le_bytes());
-            }
-            Self::Withdraw(WithdrawData {
-                pool_token_amount,
-                minimum_token_a_amount,
-                minimum_token_b_amount,
-            }) => {
-                buf.push(0x3);
-                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
-                buf.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
-                buf.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
-            }
-            Self::InitializeLiquidityProvider => {
-                buf.push(0x4);
-            }
-            Self::ClaimLiquidityRewards => {
-                buf.push(0x5);
-            }
-            Self::RefreshLiquidityObligation => {
-                buf.push(0x6);
-            }
-        }
-        buf
-    }
-}
-
-/// Creates an 'initialize' instruction.
-pub fn initialize(
-    program_id: Pubkey,
-    config_pubkey: Pubkey,
-    swap_pubkey: Pubkey,
-    authority_pubkey: Pubkey,
-    admin_fee_a_pubkey: Pubkey,
-    admin_fee_b_pubkey: Pubkey,
-    token_a_pubkey: Pubkey,
-    token_b_pubkey: Pubkey,
-    pool_mint_pubkey: Pubkey,
-    destination_pubkey: Pubkey,
-    pyth_a_pubkey: Pubkey,
-    pyth_b_pubkey: Pubkey,
-    init_data: InitializeData,
-) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::Initialize(init_data).pack();
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
 
     let accounts = vec![
         AccountMeta::new_readonly(config_pubkey, false),
         AccountMeta::new(swap_pubkey, false),
-        AccountMeta::new_readonly(authority_pubkey, false),
-        AccountMeta::new_readonly(admin_fee_a_pubkey, false),
-        AccountMeta::new_readonly(admin_fee_b_pubkey, false),
-        AccountMeta::new_readonly(token_a_pubkey, false),
-        AccountMeta::new_readonly(token_b_pubkey, false),
-        AccountMeta::new(pool_mint_pubkey, false),
-        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
         AccountMeta::new_readonly(pyth_a_pubkey, false),
         AccountMeta::new_readonly(pyth_b_pubkey, false),
         AccountMeta::new_readonly(clock::id(), false),
-        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(observation_buffer_pubkey, false),
+        AccountMeta::new(price_feed_pubkey, false),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
     ];
 
     Ok(Instruction {
@@ -661,44 +1583,49 @@ pub fn initialize(
     })
 }
 
-/// Creates a 'swap' instruction.
-pub fn swap(
+/// Creates a 'set_mid_price_bounds' instruction.
+pub fn set_mid_price_bounds(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    max_mid_price_deviation_bps: u64,
+    min_mid_price_update_interval: i64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetMidPriceBounds(SetMidPriceBounds {
+        max_mid_price_deviation_bps,
+        min_mid_price_update_interval,
+    })
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_max_price_impact' instruction.
+pub fn set_max_price_impact(
     program_id: Pubkey,
     config_pubkey: Pubkey,
     swap_pubkey: Pubkey,
-    market_authority_pubkey: Pubkey,
-    swap_authority_pubkey: Pubkey,
-    user_transfer_authority_pubkey: Pubkey,
-    source_pubkey: Pubkey,
-    swap_source_pubkey: Pubkey,
-    swap_destination_pubkey: Pubkey,
-    destination_pubkey: Pubkey,
-    reward_token_pubkey: Pubkey,
-    reward_mint_pubkey: Pubkey,
-    admin_fee_destination_pubkey: Pubkey,
-    pyth_a_pubkey: Pubkey,
-    pyth_b_pubkey: Pubkey,
-    swap_data: SwapData,
+    admin_pubkey: Pubkey,
+    max_price_impact_bps: u64,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::Swap(swap_data).pack();
+    let data = AdminInstruction::SetMaxPriceImpact(SetMaxPriceImpact {
+        max_price_impact_bps,
+    })
+    .pack();
 
     let accounts = vec![
         AccountMeta::new_readonly(config_pubkey, false),
         AccountMeta::new(swap_pubkey, false),
-        AccountMeta::new_readonly(market_authority_pubkey, false),
-        AccountMeta::new_readonly(swap_authority_pubkey, false),
-        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
-        AccountMeta::new(source_pubkey, false),
-        AccountMeta::new(swap_source_pubkey, false),
-        AccountMeta::new(swap_destination_pubkey, false),
-        AccountMeta::new(destination_pubkey, false),
-        AccountMeta::new(reward_token_pubkey, false),
-        AccountMeta::new(reward_mint_pubkey, false),
-        AccountMeta::new(admin_fee_destination_pubkey, false),
-        AccountMeta::new_readonly(pyth_a_pubkey, false),
-        AccountMeta::new_readonly(pyth_b_pubkey, false),
-        AccountMeta::new_readonly(clock::id(), false),
-        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
@@ -708,42 +1635,25 @@ pub fn swap(
     })
 }
 
-/// Creates a 'deposit' instruction.
-pub fn deposit(
+/// Creates a 'set_max_trade_size' instruction.
+pub fn set_max_trade_size(
     program_id: Pubkey,
+    config_pubkey: Pubkey,
     swap_pubkey: Pubkey,
-    authority_pubkey: Pubkey,
-    user_transfer_authority_pubkey: Pubkey,
-    deposit_token_a_pubkey: Pubkey,
-    deposit_token_b_pubkey: Pubkey,
-    swap_token_a_pubkey: Pubkey,
-    swap_token_b_pubkey: Pubkey,
-    pool_mint_pubkey: Pubkey,
-    destination_pubkey: Pubkey,
-    liquidity_provider_pubkey: Pubkey,
-    liquidity_owner_pubkey: Pubkey,
-    pyth_a_pubkey: Pubkey,
-    pyth_b_pubkey: Pubkey,
-    deposit_data: DepositData,
+    admin_pubkey: Pubkey,
+    max_amount_in: u64,
+    max_amount_in_reserve_bps: u64,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::Deposit(deposit_data).pack();
+    let data = AdminInstruction::SetMaxTradeSize(SetMaxTradeSize {
+        max_amount_in,
+        max_amount_in_reserve_bps,
+    })
+    .pack();
 
     let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
         AccountMeta::new(swap_pubkey, false),
-        AccountMeta::new_readonly(authority_pubkey, false),
-        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
-        AccountMeta::new(deposit_token_a_pubkey, false),
-        AccountMeta::new(deposit_token_b_pubkey, false),
-        AccountMeta::new(swap_token_a_pubkey, false),
-        AccountMeta::new(swap_token_b_pubkey, false),
-        AccountMeta::new(pool_mint_pubkey, false),
-        AccountMeta::new(destination_pubkey, false),
-        AccountMeta::new(liquidity_provider_pubkey, false),
-        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
-        AccountMeta::new_readonly(pyth_a_pubkey, false),
-        AccountMeta::new_readonly(pyth_b_pubkey, false),
-        AccountMeta::new_readonly(clock::id(), false),
-        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
@@ -753,46 +1663,25 @@ pub fn deposit(
     })
 }
 
-/// Creates a 'withdraw' instruction.
-pub fn withdraw(
+/// Creates a 'set_slot_throttle' instruction.
+pub fn set_slot_throttle(
     program_id: Pubkey,
+    config_pubkey: Pubkey,
     swap_pubkey: Pubkey,
-    authority_pubkey: Pubkey,
-    user_transfer_authority_pubkey: Pubkey,
-    pool_mint_pubkey: Pubkey,
-    source_pubkey: Pubkey,
-    swap_token_a_pubkey: Pubkey,
-    swap_token_b_pubkey: Pubkey,
-    destination_token_a_pubkey: Pubkey,
-    destination_token_b_pubkey: Pubkey,
-    admin_fee_a_pubkey: Pubkey,
-    admin_fee_b_pubkey: Pubkey,
-    liquidity_provider_pubkey: Pubkey,
-    liquidity_owner_pubkey: Pubkey,
-    pyth_a_pubkey: Pubkey,
-    pyth_b_pubkey: Pubkey,
-    withdraw_data: WithdrawData,
+    admin_pubkey: Pubkey,
+    max_swaps_per_slot: u64,
+    max_volume_per_slot: u64,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::Withdraw(withdraw_data).pack();
+    let data = AdminInstruction::SetSlotThrottle(SetSlotThrottle {
+        max_swaps_per_slot,
+        max_volume_per_slot,
+    })
+    .pack();
 
     let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
         AccountMeta::new(swap_pubkey, false),
-        AccountMeta::new_readonly(authority_pubkey, false),
-        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
-        AccountMeta::new(pool_mint_pubkey, false),
-        AccountMeta::new(source_pubkey, false),
-        AccountMeta::new(swap_token_a_pubkey, false),
-        AccountMeta::new(swap_token_b_pubkey, false),
-        AccountMeta::new(destination_token_a_pubkey, false),
-        AccountMeta::new(destination_token_b_pubkey, false),
-        AccountMeta::new(admin_fee_a_pubkey, false),
-        AccountMeta::new(admin_fee_b_pubkey, false),
-        AccountMeta::new(liquidity_provider_pubkey, false),
-        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
-        AccountMeta::new_readonly(pyth_a_pubkey, false),
-        AccountMeta::new_readonly(pyth_b_pubkey, false),
-        AccountMeta::new_readonly(clock::id(), false),
-        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
@@ -802,292 +1691,5788 @@ pub fn withdraw(
     })
 }
 
-/// Creates `InitializeLiquidityProvider` instruction
-pub fn init_liquidity_provider(
+/// Creates a 'set_fee_compounding' instruction.
+pub fn set_fee_compounding(
     program_id: Pubkey,
-    liquidity_provider_pubkey: Pubkey,
-    liquidity_owner_pubkey: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    is_fee_compounding: bool,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::InitializeLiquidityProvider.pack();
+    let data = AdminInstruction::SetFeeCompounding(SetFeeCompounding { is_fee_compounding }).pack();
 
     let accounts = vec![
-        AccountMeta::new(liquidity_provider_pubkey, false),
-        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
-        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
         program_id,
-        data,
         accounts,
+        data,
     })
 }
 
-/// Creates `ClaimLiquidityRewards` instruction
-pub fn claim_liquidity_rewards(
+/// Creates a 'set_fee_in_quote' instruction.
+pub fn set_fee_in_quote(
     program_id: Pubkey,
     config_pubkey: Pubkey,
     swap_pubkey: Pubkey,
-    market_authority_info: Pubkey,
-    liquidity_provider_pubkey: Pubkey,
-    liquidity_owner_pubkey: Pubkey,
-    claim_destination_pubkey: Pubkey,
-    claim_mint_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    is_fee_in_quote: bool,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::ClaimLiquidityRewards.pack();
+    let data = AdminInstruction::SetFeeInQuote(SetFeeInQuote { is_fee_in_quote }).pack();
 
     let accounts = vec![
         AccountMeta::new_readonly(config_pubkey, false),
-        AccountMeta::new_readonly(swap_pubkey, false),
-        AccountMeta::new_readonly(market_authority_info, false),
-        AccountMeta::new(liquidity_provider_pubkey, false),
-        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
-        AccountMeta::new(claim_destination_pubkey, false),
-        AccountMeta::new(claim_mint_pubkey, false),
-        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
         program_id,
-        data,
         accounts,
+        data,
     })
 }
 
-/// Creates `RefreshLiquidityObligation` instruction
-pub fn refresh_liquidity_obligation(
+/// Creates an 'initialize_whitelist' instruction.
+pub fn initialize_whitelist(
     program_id: Pubkey,
+    config_pubkey: Pubkey,
     swap_pubkey: Pubkey,
-    liquidity_provider_pubkeys: Vec<Pubkey>,
+    admin_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::RefreshLiquidityObligation.pack();
+    let data = AdminInstruction::InitializeWhitelist.pack();
 
-    let mut accounts = vec![
+    let (whitelist_pubkey, _) = find_whitelist_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
         AccountMeta::new_readonly(swap_pubkey, false),
-        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new(whitelist_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
     ];
-    accounts.extend(
-        liquidity_provider_pubkeys
-            .into_iter()
-            .map(|pubkey| AccountMeta::new(pubkey, false)),
-    );
 
     Ok(Instruction {
         program_id,
-        data,
         accounts,
+        data,
     })
 }
 
-fn unpack_u128(input: &[u8]) -> Result<(u128, &[u8]), ProgramError> {
-    if input.len() < 16 {
-        return Err(SwapError::InstructionUnpackError.into());
-    }
-    let (amount, rest) = input.split_at(16);
-    let amount = amount
-        .get(..16)
-        .and_then(|slice| slice.try_into().ok())
-        .map(u128::from_le_bytes)
-        .ok_or(SwapError::InstructionUnpackError)?;
-    Ok((amount, rest))
+/// Creates a 'set_whitelist_trader' instruction.
+pub fn set_whitelist_trader(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    trader: Pubkey,
+    is_allowed: bool,
+) -> Result<Instruction, ProgramError> {
+    let data =
+        AdminInstruction::SetWhitelistTrader(SetWhitelistTrader { trader, is_allowed }).pack();
+
+    let (whitelist_pubkey, _) = find_whitelist_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new(whitelist_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-#[allow(dead_code)]
-fn unpack_i64(input: &[u8]) -> Result<(i64, &[u8]), ProgramError> {
-    if input.len() < 8 {
-        return Err(SwapError::InstructionUnpackError.into());
-    }
-    let (amount, rest) = input.split_at(8);
-    let amount = amount
-        .get(..8)
-        .and_then(|slice| slice.try_into().ok())
-        .map(i64::from_le_bytes)
-        .ok_or(SwapError::InstructionUnpackError)?;
-    Ok((amount, rest))
+/// Creates a 'set_whitelist_only' instruction.
+pub fn set_whitelist_only(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    is_whitelist_only: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetWhitelistOnly(SetWhitelistOnly { is_whitelist_only }).pack();
+
+    let (whitelist_pubkey, _) = find_whitelist_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new_readonly(whitelist_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
-    if input.len() < 8 {
-        return Err(SwapError::InstructionUnpackError.into());
-    }
-    let (amount, rest) = input.split_at(8);
-    let amount = amount
-        .get(..8)
-        .and_then(|slice| slice.try_into().ok())
-        .map(u64::from_le_bytes)
-        .ok_or(SwapError::InstructionUnpackError)?;
-    Ok((amount, rest))
+/// Creates a 'set_transfer_hook' instruction.
+pub fn set_transfer_hook(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    transfer_hook_program: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetTransferHook(SetTransferHook {
+        transfer_hook_program,
+    })
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-fn unpack_u8(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
-    if input.is_empty() {
-        return Err(SwapError::InstructionUnpackError.into());
-    }
-    let (bytes, rest) = input.split_at(1);
-    let value = bytes
-        .get(..1)
-        .and_then(|slice| slice.try_into().ok())
-        .map(u8::from_le_bytes)
-        .ok_or(SwapError::InstructionUnpackError)?;
-    Ok((value, rest))
+/// Creates a 'set_deltafi_price_oracle' instruction.
+pub fn set_deltafi_price_oracle(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    deltafi_price_oracle: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetDeltafiPriceOracle(SetDeltafiPriceOracle {
+        deltafi_price_oracle,
+    })
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-fn unpack_bool(input: &[u8]) -> Result<(bool, &[u8]), ProgramError> {
-    let (value, rest) = unpack_u8(input)?;
-    let value = match u8::from_le(value) {
-        0 => false,
-        1 => true,
-        _ => return Err(SwapError::InstructionUnpackError.into()),
-    };
-    Ok((value, rest))
+/// Creates a 'set_epoch_reward_budget' instruction.
+pub fn set_epoch_reward_budget(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    epoch_reward_budget: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetEpochRewardBudget(SetEpochRewardBudget {
+        epoch_reward_budget,
+    })
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_poke_reward' instruction.
+pub fn set_poke_reward(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    poke_reward: u64,
+    min_poke_interval: i64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetPokeReward(SetPokeReward {
+        poke_reward,
+        min_poke_interval,
+    })
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_proposal_quorum' instruction.
+pub fn set_proposal_quorum(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    proposal_quorum: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetProposalQuorum(SetProposalQuorum { proposal_quorum }).pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_referral_reward' instruction.
+pub fn set_referral_reward(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    referral_reward_bps: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetReferralReward(SetReferralReward {
+        referral_reward_bps,
+    })
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-fn unpack_swap_direction(input: &[u8]) -> Result<(SwapDirection, &[u8]), ProgramError> {
-    let (value, rest) = unpack_u8(input)?;
-    let value = match u8::from_le(value) {
-        0 => SwapDirection::SellBase,
-        1 => SwapDirection::SellQuote,
-        _ => return Err(SwapError::InstructionUnpackError.into()),
-    };
-    Ok((value, rest))
-}
+/// Creates an 'initialize_deny_list' instruction.
+pub fn initialize_deny_list(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::InitializeDenyList.pack();
+
+    let (deny_list_pubkey, _) = find_deny_list_address(&program_id, &config_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new(deny_list_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_denied_mint' instruction.
+pub fn set_denied_mint(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    mint: Pubkey,
+    is_denied: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetDeniedMint(SetDeniedMint { mint, is_denied }).pack();
+
+    let (deny_list_pubkey, _) = find_deny_list_address(&program_id, &config_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new(deny_list_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'recalibrate' instruction.
+pub fn recalibrate(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    token_a_pubkey: Pubkey,
+    token_b_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::Recalibrate.pack();
+
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new_readonly(token_a_pubkey, false),
+        AccountMeta::new_readonly(token_b_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new(price_feed_pubkey, false),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_openbook_market' instruction.
+pub fn set_openbook_market(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    openbook_market: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetOpenbookMarket(SetOpenbookMarket { openbook_market }).pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+    if openbook_market != Pubkey::default() {
+        accounts.push(AccountMeta::new_readonly(openbook_market, false));
+    }
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_curve_type' instruction.
+pub fn set_curve_type(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    curve_type: CurveType,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetCurveType(curve_type).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'snapshot' instruction.
+pub fn snapshot(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    snapshot_id: u64,
+    merkle_root: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::Snapshot(Snapshot {
+        snapshot_id,
+        merkle_root,
+    })
+    .pack();
+
+    let (snapshot_pubkey, _) = find_snapshot_address(&program_id, &swap_pubkey, snapshot_id);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
+        AccountMeta::new(snapshot_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_reserve_caps' instruction.
+pub fn set_reserve_caps(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    max_base_reserve: u64,
+    max_quote_reserve: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetReserveCaps(SetReserveCaps {
+        max_base_reserve,
+        max_quote_reserve,
+    })
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_user_liquidity_cap' instruction.
+pub fn set_user_liquidity_cap(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    max_user_liquidity: u64,
+) -> Result<Instruction, ProgramError> {
+    let data =
+        AdminInstruction::SetUserLiquidityCap(SetUserLiquidityCap { max_user_liquidity }).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'make_immutable' instruction.
+pub fn make_immutable(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::MakeImmutable.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_fee_discount' instruction.
+pub fn set_fee_discount(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    fee_discount: FeeDiscount,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetFeeDiscount(fee_discount).pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_directional_fees' instruction.
+pub fn set_directional_fees(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    directional_fees: DirectionalFees,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetDirectionalFees(directional_fees).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_min_trade_fee' instruction.
+pub fn set_min_trade_fee(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    min_trade_fee: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetMinTradeFee(SetMinTradeFee { min_trade_fee }).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'migrate_state' instruction for the config only.
+pub fn migrate_config(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::MigrateState.pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'migrate_state' instruction for the config and a token-swap.
+pub fn migrate_swap(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = migrate_config(program_id, config_pubkey, admin_pubkey, payer_pubkey)?;
+    instruction.accounts.extend_from_slice(&[
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+    ]);
+    Ok(instruction)
+}
+
+/// Instructions supported by the pool SwapInfo program.
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub enum SwapInstruction {
+    ///   Initializes a new SwapInfo.
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` New Token-swap to create.
+    ///   2. `[]` $authority derived from `create_program_address(&[Token-swap account])`
+    ///   3. `[]` admin_fee_a admin fee Account for token_a.
+    ///   4. `[]` admin_fee_b admin fee Account for token_b.
+    ///   5. `[]` token_a Account. Must be non zero, owned by $authority.
+    ///   6. `[]` token_b Account. Must be non zero, owned by $authority.
+    ///   7. `[writable]` Pool Token Mint. Must be empty, owned by $authority.
+    ///   8. `[writable]` Pool Account to deposit the generated tokens.
+    ///   9. `[]` Pyth price account of token_a
+    ///   10. `[]` Pyth price account of token_b
+    ///   11. `[]` Clock sysvar
+    ///   12. `[]` Token program id
+    ///   13. `[writable]` Registry page of the config the pool is appended to,
+    ///       the last one, which must not be full
+    ///   14. `[]` token_a Mint, whose decimals the pool prices are scaled by.
+    ///   15. `[]` token_b Mint, whose decimals the pool prices are scaled by.
+    ///   16. `[]` Deny-list of the config, derived from `[config, "deny_list"]`,
+    ///       neither mint can be on it. Not checked until it is created
+    ///
+    ///   Only with a pair symbol, to name the pool mint:
+    ///   17. `[]` Token metadata program
+    ///   18. `[writable]` Metadata account of the pool mint
+    ///   19. `[writable, signer]` Payer of the metadata account
+    ///   20. `[]` System program
+    Initialize(InitializeData),
+
+    ///   Swap the tokens in the pool.
+    ///
+    ///   0. `[writable]` Config info, written when an epoch reward budget is set
+    ///   1. `[writable]` Token-swap
+    ///   2. `[]` Market $authority
+    ///   3. `[]` Swap $authority
+    ///   4. `[signer]` User transfer authority
+    ///   5. `[writable]` token_(A|B) SOURCE Account, amount is transferable by $authority,
+    ///   6. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
+    ///   7. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
+    ///   8. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
+    ///   9. `[writable]` Rewards receiver, its DELFI discounts the trade fee if the trader owns it
+    ///   10. `[writable]` Rewards mint deltafi
+    ///   11. `[writable]` token_(A|B) admin fee Account. Must have same mint as DESTINATION token, or as token_b when the fees are collected in quote.
+    ///   12. `[]` Pyth price account of token_a
+    ///   13. `[]` Pyth price account of token_b
+    ///   14. `[]` Clock sysvar
+    ///   15. `[]` Token program id
+    ///   16. `[writable]` Observation buffer of the token-swap
+    ///   17. `[]` Whitelist of the token-swap, read by whitelist only token-swaps
+    ///   18. `[writable]` Price feed of the token-swap, not written until it is created
+    ///   19. `[]` Pool mint of the token-swap
+    ///
+    ///   .. `[]` OpenBook market of the token-swap, then its bids and asks, only
+    ///      when the token-swap has a market
+    ///   .. `[]` Transfer hook program, only when the token-swap has one
+    Swap(SwapData),
+
+    ///   Deposit some tokens into the pool.  The output is a "pool" token representing ownership
+    ///   into the pool. Inputs are converted to the current ratio.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` $authority
+    ///   2. `[signer]` User transfer authority
+    ///   3. `[writable]` token_a $authority can transfer amount,
+    ///   4. `[writable]` token_b $authority can transfer amount,
+    ///   5. `[writable]` token_a Base Account to deposit into.
+    ///   6. `[writable]` token_b Base Account to deposit into.
+    ///   7. `[writable]` Pool MINT account, $authority is the owner.
+    ///   8. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
+    ///   9. `[writable]` Liquidity provider info
+    ///   10. `[signer]` Liquidity provider owner or delegate
+    ///   11. `[]` Pyth price account of token_a
+    ///   12. `[]` Pyth price account of token_b
+    ///   13. `[]` Clock sysvar
+    ///   14. `[]` Token program id
+    ///   15. `[writable]` Observation buffer of the token-swap
+    ///   16. `[writable]` Price feed of the token-swap, not written until it is created
+    ///
+    ///   .. `[]` OpenBook market of the token-swap, then its bids and asks, only
+    ///      when the token-swap has a market
+    ///   .. `[]` Transfer hook program, only when the token-swap has one
+    ///   .. `[writable]` Position pages of the liquidity provider, all, in order
+    Deposit(DepositData),
+
+    ///   Withdraw tokens from the pool at the current ratio.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` $authority
+    ///   2. `[signer]` User transfer authority
+    ///   3. `[writable]` Pool mint account, $authority is the owner
+    ///   4. `[writable]` SOURCE Pool account, amount is transferable by $authority.
+    ///   5. `[writable]` token_a Swap Account to withdraw FROM.
+    ///   6. `[writable]` token_b Swap Account to withdraw FROM.
+    ///   7. `[writable]` token_a user Account to credit.
+    ///   8. `[writable]` token_b user Account to credit.
+    ///   9. `[writable]` admin_fee_a admin fee Account for token_a.
+    ///   10. `[writable]` admin_fee_b admin fee Account for token_b.
+    ///   11. `[writable]` Liquidity provider info
+    ///   12. `[signer]` Liquidity provider owner or delegate
+    ///   13. `[]` Pyth price account of token_a
+    ///   14. `[]` Pyth price account of token_b
+    ///   15. `[]` Clock sysvar
+    ///   16. `[]` Token program id
+    ///   17. `[writable]` Observation buffer of the token-swap
+    ///   18. `[writable]` Price feed of the token-swap, not written until it is created
+    ///
+    ///   .. `[]` OpenBook market of the token-swap, then its bids and asks, only
+    ///      when the token-swap has a market
+    ///   .. `[]` Associated token program, only to create missing token accounts
+    ///   .. `[writable, signer]` Payer of the created token accounts
+    ///   .. `[]` System program
+    ///   .. `[]` token_a mint
+    ///   .. `[]` token_b mint
+    ///   .. `[writable]` Position pages of the liquidity provider, all, in order
+    Withdraw(WithdrawData),
+
+    // ///   Withdraw one token from the pool at the current ratio.
+    // ///
+    // ///   0. `[]` Token-swap
+    // ///   1. `[]` $authority
+    // ///   2. `[writable]` Pool mint account, $authority is the owner
+    // ///   3. `[writable]` SOURCE Pool account, amount is transferable by $authority.
+    // ///   4. `[writable]` token_(A|B) BASE token Swap Account to withdraw FROM.
+    // ///   5. `[writable]` token_(A|B) QUOTE token Swap Account to exchange to base token.
+    // ///   6. `[writable]` token_(A|B) BASE token user Account to credit.
+    // ///   7. `[writable]` token_(A|B) admin fee Account. Must have same mint as BASE token.
+    // ///   8. `[]` Token program id
+    // ///   9. `[]` Clock sysvar
+    // WithdrawOne(WithdrawOneData),
+    /// Create and initialize liquidity provider account,
+    /// derived from `[owner, "liquidity_provider"]`
+    ///
+    ///   0. `[writable]` liquidity provider info
+    ///   1. `[writable, signer]` liquidity provider owner, pays the rent
+    ///   2. `[]` Rent sysvar
+    ///   3. `[]` System program
+    ///   4. `[]` Optional referrer, minted a slice of the liquidity rewards
+    ///      claimed. Can not be the owner
+    InitializeLiquidityProvider,
+
+    /// Claim deltafi reward of liquidity provider
+    ///
+    ///   0. `[writable]` Config info, written when an epoch reward budget is set
+    ///   1. `[]` Token-swap
+    ///   2. `[]` $authority
+    ///   3. `[writable]` Liquidity provider info
+    ///   4. `[signer]` Liquidity provider owner or delegate
+    ///   5. `[writable]` Rewards receiver
+    ///   6. `[writable]` Rewards mint deltafi
+    ///   7. `[]` Token program id
+    ///
+    ///   .. `[writable]` Rewards receiver of the referrer, only when the
+    ///      liquidity provider has one
+    ///   .. `[]` Associated token program, only to create a missing rewards receiver
+    ///   .. `[writable, signer]` Payer of the created token account
+    ///   .. `[]` System program
+    ///   .. `[]` Rewards mint deltafi
+    ///   .. `[writable]` Position pages of the liquidity provider, all, in order
+    ClaimLiquidityRewards,
+
+    /// Refresh liquidity obligation, accumulating the rewards per pool token
+    /// of the pool up to the current time. Positions settle against it on
+    /// their next deposit, withdraw or claim
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` Clock sysvar
+    ///   2. `[]` Config info
+    ///   3. `[]` DELFI price oracle of the config, only when it has one.
+    ///      Its price values the rewards accumulated from then on
+    RefreshLiquidityObligation,
+
+    /// Grow liquidity provider account to hold more positions
+    ///
+    ///   0. `[writable]` Liquidity provider info
+    ///   1. `[signer]` Liquidity provider owner
+    ///   2. `[writable, signer]` Payer of the rent for the extra space
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    GrowProviderAccount(GrowProviderAccountData),
+
+    /// Close liquidity provider account with no liquidity and no unclaimed
+    /// rewards left, sending its rent to the owner
+    ///
+    ///   0. `[writable]` Liquidity provider info
+    ///   1. `[writable, signer]` Liquidity provider owner, receives the rent
+    ///
+    ///   .. `[writable]` Position pages of the liquidity provider, closed too, in order
+    CloseLiquidityProvider,
+
+    /// Create and initialize an overflow position page of a liquidity provider,
+    /// derived from `[liquidity_provider, "position_page", page_index]`
+    ///
+    ///   0. `[writable]` Position page
+    ///   1. `[]` Liquidity provider info
+    ///   2. `[writable, signer]` Liquidity provider owner, pays the rent
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    ///   5. `[writable]` Previous position page, linked to the new one. Not needed for page 0
+    InitializePositionPage(InitializePositionPageData),
+
+    ///   Deposit arbitrary amounts of both tokens into the pool. The excess token is
+    ///   swapped through the curve to match the pool ratio, paying the trade fee on
+    ///   the swapped part, and pool tokens are minted for the combined value.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` $authority
+    ///   2. `[signer]` User transfer authority
+    ///   3. `[writable]` token_a $authority can transfer amount,
+    ///   4. `[writable]` token_b $authority can transfer amount,
+    ///   5. `[writable]` token_a Base Account to deposit into.
+    ///   6. `[writable]` token_b Base Account to deposit into.
+    ///   7. `[writable]` Pool MINT account, $authority is the owner.
+    ///   8. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
+    ///   9. `[writable]` admin_fee_a admin fee Account for token_a.
+    ///   10. `[writable]` admin_fee_b admin fee Account for token_b.
+    ///   11. `[writable]` Liquidity provider info
+    ///   12. `[signer]` Liquidity provider owner or delegate
+    ///   13. `[]` Pyth price account of token_a
+    ///   14. `[]` Pyth price account of token_b
+    ///   15. `[]` Clock sysvar
+    ///   16. `[]` Token program id
+    ///   17. `[writable]` Observation buffer of the token-swap
+    ///   18. `[writable]` Price feed of the token-swap, not written until it is created
+    ///
+    ///   .. `[]` OpenBook market of the token-swap, then its bids and asks, only
+    ///      when the token-swap has a market
+    ///   .. `[]` Transfer hook program, only when the token-swap has one
+    ///   .. `[writable]` Position pages of the liquidity provider, all, in order
+    DepositImbalanced(DepositData),
+
+    ///   Withdraw exact amounts of both tokens from the pool, burning only the pool
+    ///   tokens needed to cover them after the withdraw fee.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` $authority
+    ///   2. `[signer]` User transfer authority
+    ///   3. `[writable]` Pool mint account, $authority is the owner
+    ///   4. `[writable]` SOURCE Pool account, amount is transferable by $authority.
+    ///   5. `[writable]` token_a Swap Account to withdraw FROM.
+    ///   6. `[writable]` token_b Swap Account to withdraw FROM.
+    ///   7. `[writable]` token_a user Account to credit.
+    ///   8. `[writable]` token_b user Account to credit.
+    ///   9. `[writable]` admin_fee_a admin fee Account for token_a.
+    ///   10. `[writable]` admin_fee_b admin fee Account for token_b.
+    ///   11. `[writable]` Liquidity provider info
+    ///   12. `[signer]` Liquidity provider owner or delegate
+    ///   13. `[]` Pyth price account of token_a
+    ///   14. `[]` Pyth price account of token_b
+    ///   15. `[]` Clock sysvar
+    ///   16. `[]` Token program id
+    ///   17. `[writable]` Observation buffer of the token-swap
+    ///   18. `[writable]` Price feed of the token-swap, not written until it is created
+    ///
+    ///   .. `[]` OpenBook market of the token-swap, then its bids and asks, only
+    ///      when the token-swap has a market
+    ///   .. `[]` Associated token program, only to create missing token accounts
+    ///   .. `[writable, signer]` Payer of the created token accounts
+    ///   .. `[]` System program
+    ///   .. `[]` token_a mint
+    ///   .. `[]` token_b mint
+    ///   .. `[writable]` Position pages of the liquidity provider, all, in order
+    WithdrawExact(WithdrawExactData),
+
+    ///   Create and initialize the observation buffer of a token-swap,
+    ///   derived from `[swap, "observations"]`. Every instruction moving the
+    ///   pool price requires it afterwards.
+    ///
+    ///   0. `[writable]` Observation buffer
+    ///   1. `[]` Token-swap
+    ///   2. `[writable, signer]` Payer of the rent
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    InitializeObservationBuffer,
+
+    ///   Create and initialize a registry page of a config, derived from
+    ///   `[config, "registry", page_index]`. Pages are created in order, once
+    ///   the previous one is full.
+    ///
+    ///   0. `[writable]` Registry page
+    ///   1. `[]` Config info
+    ///   2. `[writable, signer]` Payer of the rent
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    ///   5. `[]` Previous registry page, full, unless the page index is 0
+    InitializeRegistryPage(InitializeRegistryPageData),
+
+    ///   Quote a swap at the current market price without changing any
+    ///   account. The amount the user would receive, after trade fee, is set
+    ///   as the return data, a little endian u64. With the pool mint, the
+    ///   value of a pool token in quote token amounts follows, the scaled
+    ///   value of the decimal as a little endian u128.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` token_a Base Account of the swap
+    ///   2. `[]` token_b Base Account of the swap
+    ///   3. `[]` Pyth price account of token_a
+    ///   4. `[]` Pyth price account of token_b
+    ///   5. `[]` Clock sysvar
+    ///   6. `[]` Token program id
+    ///   7. `[]` Observation buffer of the token-swap
+    ///
+    ///   .. `[]` OpenBook market of the token-swap, then its bids and asks, only
+    ///      when the token-swap has a market
+    ///   .. `[]` Optional pool token mint
+    QuoteSwap(QuoteSwapData),
+
+    ///   Create and initialize the quote account of an owner, derived from
+    ///   `[owner, "quote"]`, holding the quotes of `CalcReceiveAmount`.
+    ///
+    ///   0. `[writable]` Quote account
+    ///   1. `[]` Quote account owner
+    ///   2. `[writable, signer]` Payer of the rent
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    InitializeQuoteAccount,
+
+    ///   Quote a swap like `QuoteSwap`, storing the output amount, trade fee,
+    ///   admin fee and reward in the quote account of the owner, for programs
+    ///   without return data support.
+    ///
+    ///   0. `[writable]` Quote account
+    ///   1. `[signer]` Quote account owner
+    ///   2. `[]` Token-swap
+    ///   3. `[]` token_a Base Account of the swap
+    ///   4. `[]` token_b Base Account of the swap
+    ///   5. `[]` Pyth price account of token_a
+    ///   6. `[]` Pyth price account of token_b
+    ///   7. `[]` Clock sysvar
+    ///   8. `[]` Token program id
+    ///   9. `[]` Observation buffer of the token-swap
+    ///
+    ///   .. `[]` OpenBook market of the token-swap, then its bids and asks, only
+    ///      when the token-swap has a market
+    CalcReceiveAmount(QuoteSwapData),
+
+    ///   Swap with lamports on the SOL side of the pool. A temporary wrapped
+    ///   SOL account is created for the wallet, funded with `amount_in` when
+    ///   selling SOL, and closed to the wallet after the swap, unwrapping the
+    ///   output when buying SOL.
+    ///
+    ///   0. `[writable, signer]` Wallet, pays and receives the lamports
+    ///   1. `[writable, signer]` Temporary wrapped SOL account, uninitialized
+    ///   2. `[]` Native mint
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    ///
+    ///   .. The `Swap` accounts, with the wallet as user transfer authority
+    ///      and the temporary account as the SOURCE or DESTINATION account
+    SwapNative(SwapData),
+
+    ///   Deposit with lamports on the SOL side of the pool. A temporary
+    ///   wrapped SOL account is created for the wallet, funded with the SOL
+    ///   amount deposited and closed to the wallet after the deposit.
+    ///
+    ///   0. `[writable, signer]` Wallet, pays and receives the lamports
+    ///   1. `[writable, signer]` Temporary wrapped SOL account, uninitialized
+    ///   2. `[]` Native mint
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    ///
+    ///   .. The `Deposit` accounts, with the wallet as user transfer authority
+    ///      and the temporary account as the token_a or token_b account
+    DepositNative(DepositData),
+
+    ///   Move the liquidity position of a pool, with its accrued rewards, to
+    ///   another liquidity provider. It is merged into the position of the
+    ///   destination in the same pool if there is one, both must then have
+    ///   been refreshed in the same slot.
+    ///
+    ///   0. `[]` Token-swap of the position
+    ///   1. `[writable]` Source liquidity provider info
+    ///   2. `[signer]` Source liquidity provider owner
+    ///   3. `[writable]` Destination liquidity provider info
+    ///   4. `[signer]` Destination liquidity provider owner
+    ///
+    ///   .. `[writable]` Position pages of the source liquidity provider, all, in order
+    ///   .. `[writable]` Position pages of the destination liquidity provider, all, in order
+    TransferPosition(TransferPositionData),
+
+    ///   Set the delegate allowed to deposit, withdraw and claim on behalf of
+    ///   the owner of a liquidity provider, or remove it with the default pubkey.
+    ///   Only the owner can change the delegate.
+    ///
+    ///   0. `[writable]` Liquidity provider info
+    ///   1. `[signer]` Liquidity provider owner
+    SetLiquidityProviderDelegate(SetLiquidityProviderDelegateData),
+
+    ///   Claim the rewards owed by the positions of several pools, minting a
+    ///   single payout. Pools without rewards owed are skipped.
+    ///
+    ///   0. `[writable]` Config info, written when an epoch reward budget is set
+    ///   1. `[]` $authority
+    ///   2. `[writable]` Liquidity provider info
+    ///   3. `[signer]` Liquidity provider owner or delegate
+    ///   4. `[writable]` Rewards receiver
+    ///   5. `[writable]` Rewards mint deltafi
+    ///   6. `[]` Token program id
+    ///
+    ///   .. `[]` Token-swaps to claim the rewards of, `swap_count` of them
+    ///   .. `[writable]` Rewards receiver of the referrer, only when the
+    ///      liquidity provider has one
+    ///   .. `[]` Associated token program, only to create a missing rewards receiver
+    ///   .. `[writable, signer]` Payer of the created token account
+    ///   .. `[]` System program
+    ///   .. `[]` Rewards mint deltafi
+    ///   .. `[writable]` Position pages of the liquidity provider, all, in order
+    ClaimAllRewards(ClaimAllRewardsData),
+
+    ///   Accumulate the mid price a quiet pool held since its last update into
+    ///   its twap. Anyone can call it, the keeper is minted the poke reward of
+    ///   the config when the poke accumulates at least its min poke interval.
+    ///
+    ///   0. `[writable]` Config info, written when an epoch reward budget is set
+    ///   1. `[writable]` Token-swap
+    ///   2. `[]` $authority
+    ///   3. `[writable]` Observation buffer of the token-swap
+    ///   4. `[writable]` Rewards receiver of the keeper
+    ///   5. `[writable]` Rewards mint deltafi
+    ///   6. `[]` Token program id
+    Poke,
+
+    ///   Create and initialize the DELFI lock of an owner in a config, derived
+    ///   from `[config, owner, "delfi_lock"]`. The lock only holds the DELFI
+    ///   of the vault of that config and only votes in that config.
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[writable]` DELFI lock
+    ///   2. `[writable, signer]` DELFI lock owner, pays the rent
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    InitializeDelfiLock,
+
+    ///   Lock DELFI for at least `MIN_LOCK_DURATION`, adding to the voting
+    ///   power of the DELFI lock
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[]` $authority
+    ///   2. `[writable]` DELFI lock
+    ///   3. `[signer]` DELFI lock owner
+    ///   4. `[writable]` DELFI source account, owner can transfer amount
+    ///   5. `[writable]` DELFI vault, associated token account of the $authority
+    ///   6. `[]` Token program id
+    LockDelfi(LockDelfiData),
+
+    ///   Unlock all the DELFI of a lock past its unlock timestamp, out of the
+    ///   epoch the lock voted in
+    ///
+    ///   0. `[]` Config info
+    ///   1. `[]` $authority
+    ///   2. `[writable]` DELFI lock
+    ///   3. `[signer]` DELFI lock owner
+    ///   4. `[writable]` DELFI vault, associated token account of the $authority
+    ///   5. `[writable]` DELFI destination account
+    ///   6. `[]` Token program id
+    UnlockDelfi,
+
+    ///   Cast the gauge votes of a DELFI lock for the epoch, splitting its
+    ///   voting power between token-swaps. The votes weight the liquidity
+    ///   rewards of the token-swaps in the next epoch.
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[writable]` DELFI lock
+    ///   2. `[signer]` DELFI lock owner
+    ///
+    ///   .. `[writable]` Token-swaps voted for, one per weight, in order
+    VoteGauge(VoteGaugeData),
+
+    ///   Propose new fees and rewards for the config, open to the votes of
+    ///   the DELFI locks for the voting period. The proposal is derived from
+    ///   `[config, proposal_count, "proposal"]`
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[writable]` Proposal
+    ///   2. `[writable, signer]` Proposer, pays the rent
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    CreateProposal(CreateProposalData),
+
+    ///   Vote on a proposal with the DELFI locked until the end of its voting
+    ///   period, once per DELFI lock owner. The vote record is derived from
+    ///   `[proposal, owner, "vote_record"]`
+    ///
+    ///   0. `[writable]` Proposal
+    ///   1. `[writable]` Vote record
+    ///   2. `[]` DELFI lock in the config of the proposal
+    ///   3. `[writable, signer]` DELFI lock owner, pays the rent
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` System program
+    VoteProposal(VoteProposalData),
+
+    ///   Set the fees and rewards of a proposal passed by the vote on its
+    ///   config. Anyone can execute a passed proposal once its voting closed.
+    ///
+    ///   0. `[writable]` Config info
+    ///   1. `[writable]` Proposal
+    ExecuteProposal,
+
+    ///   Swap paying the output first. The callback program is then invoked
+    ///   with `ReceiveFlashSwap` and must have transferred `amount_in` to the
+    ///   swap account of the SOURCE token when it returns, or the whole swap
+    ///   fails.
+    ///
+    ///   .. The `Swap` accounts, with the transfer hook program if any
+    ///
+    ///   0. `[]` Callback program
+    ///
+    ///   .. Accounts passed as is to the callback program
+    FlashSwap(FlashSwapData),
+
+    ///   Swap on several token-swaps in one instruction. The swaps are
+    ///   independent, each with its own minimum output, and the sum of their
+    ///   outputs must reach `minimum_total_amount_out`. Each token-swap
+    ///   appears once.
+    ///
+    ///   .. The `Swap` accounts of each swap, with the transfer hook program
+    ///      of its token-swap if any, in order
+    BatchSwap(BatchSwapData),
+
+    ///   Swap an input split between token-swaps of the same pair, whatever
+    ///   their curve, by the fractions of the caller. The last token-swap
+    ///   gets what rounding leaves of the input, and the combined output must
+    ///   reach `minimum_amount_out`.
+    ///
+    ///   .. The `Swap` accounts of each token-swap, with its transfer hook
+    ///      program if any, in the order of the fractions. The user SOURCE
+    ///      and DESTINATION accounts are the same for all
+    SplitSwap(SplitSwapData),
+
+    ///   Withdraw the whole liquidity position of the pool at the current
+    ///   ratio, forfeiting the rewards of the position. Neither the oracles
+    ///   nor the rewards are read, so the liquidity can always be withdrawn.
+    ///   When one swap account is frozen by its mint, only the other token is
+    ///   paid out and the minimum amount of the frozen one must be zero.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` $authority
+    ///   2. `[signer]` User transfer authority
+    ///   3. `[writable]` Pool mint account, $authority is the owner
+    ///   4. `[writable]` SOURCE Pool account, amount is transferable by $authority.
+    ///   5. `[writable]` token_a Swap Account to withdraw FROM.
+    ///   6. `[writable]` token_b Swap Account to withdraw FROM.
+    ///   7. `[writable]` token_a user Account to credit.
+    ///   8. `[writable]` token_b user Account to credit.
+    ///   9. `[writable]` admin_fee_a admin fee Account for token_a.
+    ///   10. `[writable]` admin_fee_b admin fee Account for token_b.
+    ///   11. `[writable]` Liquidity provider info
+    ///   12. `[signer]` Liquidity provider owner or delegate
+    ///   13. `[]` Token program id
+    ///   14. `[writable]` Price feed of the token-swap, not written until it is created
+    ///   15. `[]` Clock sysvar
+    ///
+    ///   .. `[writable]` Position pages of the liquidity provider, all, in order
+    EmergencyWithdraw(EmergencyWithdrawData),
+
+    ///   Fold the tokens transferred directly to the swap accounts into the
+    ///   reserves and targets of the pool, the surplus going to the liquidity
+    ///   providers. Anyone can call it while the pool is not paused.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` token_a Swap Account
+    ///   2. `[]` token_b Swap Account
+    ///   3. `[]` Token program id
+    ///   4. `[writable]` Price feed of the token-swap, not written until it is created
+    ///   5. `[]` Pool mint of the token-swap
+    ///   6. `[]` Clock sysvar
+    Sync,
+
+    ///   Send the tokens transferred directly to the swap accounts, beyond the
+    ///   reserves of the pool, to the admin fee accounts. Anyone can call it.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` $authority
+    ///   2. `[writable]` token_a Swap Account to skim FROM.
+    ///   3. `[writable]` token_b Swap Account to skim FROM.
+    ///   4. `[writable]` admin_fee_a admin fee Account for token_a.
+    ///   5. `[writable]` admin_fee_b admin fee Account for token_b.
+    ///   6. `[]` Token program id
+    Skim,
+
+    ///   Return the version and compiled features of the program, packed as a
+    ///   [ProgramBuild](struct.ProgramBuild.html), in the return data. Takes
+    ///   no accounts.
+    GetVersion,
+
+    ///   Return the value of a pool token in quote token amounts, the targets
+    ///   of the pool valued at its market price, as the scaled value of the
+    ///   decimal in a little endian u128 of the return data. Unlike the
+    ///   reserves, the targets do not move with swaps, so lending protocols
+    ///   can value the pool tokens pledged as collateral.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Pool token mint
+    ///   2. `[]` Token program id
+    GetVirtualPrice,
+
+    ///   Create and initialize the price feed of a token-swap, derived from
+    ///   `[swap, "price_feed"]`, storing the mid price and the lp virtual
+    ///   price of the pool for other programs. The instructions moving the
+    ///   pool refresh it when the feed and the pool token mint are passed as
+    ///   their last two accounts.
+    ///
+    ///   0. `[writable]` Price feed
+    ///   1. `[]` Token-swap
+    ///   2. `[]` Pool token mint
+    ///   3. `[writable, signer]` Payer of the rent
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` System program
+    InitializePriceFeed,
+
+    ///   Swap signed by the owner of the user token account the tokens are
+    ///   sold from and submitted by a relayer paying the transaction fees,
+    ///   for wallets without SOL. The user transfer authority must be that
+    ///   owner rather than a delegate approved beforehand, which would take a
+    ///   transaction paid by the owner.
+    ///
+    ///   The owner pays the relayer fee out of the account sold from, the fee
+    ///   being part of the instruction data it signs.
+    ///
+    ///   0. `[signer]` Fee payer, the relayer
+    ///   1. `[writable]` Token account of the relayer receiving the relayer fee, of the token sold
+    ///
+    ///   .. The `Swap` accounts, with the owner of the user token account
+    ///      sold from as user transfer authority
+    RelayedSwap(RelayedSwapData),
+
+    ///   Settle a swap order signed off-chain by its maker, verified by the
+    ///   ed25519 program instruction right before this one. The order
+    ///   authority, approved by the maker as delegate of the account sold
+    ///   from, debits it. The order must be signed for this program and the
+    ///   config of the swap, not be expired at the time of the clock sysvar
+    ///   of the swap, and its nonce not below the next nonce of the maker,
+    ///   moved past it so the order is settled once at most.
+    ///
+    ///   0. `[]` Instructions sysvar
+    ///   1. `[writable]` Order nonce of the maker, created on the first order
+    ///   2. `[writable, signer]` Payer of the rent, the settler
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    ///
+    ///   .. The `Swap` accounts, with the order authority as user transfer
+    ///      authority and the user token accounts and reward account owned
+    ///      by the maker
+    SettleSignedOrder(SignedOrder),
+
+    ///   Cancel the signed orders of the maker with a nonce below the one
+    ///   given, moving the next nonce of the maker forward.
+    ///
+    ///   0. `[writable]` Order nonce of the maker, created if needed
+    ///   1. `[signer]` Maker
+    ///   2. `[writable, signer]` Payer of the rent
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    CancelSignedOrders(CancelSignedOrdersData),
+
+    ///   Create the DCA plan of the owner in a token-swap, escrowing the
+    ///   amount to sell over the periods in the associated token account of
+    ///   the plan. The first period is due right away.
+    ///
+    ///   0. `[writable]` DCA plan of the owner, uninitialized
+    ///   1. `[writable]` Escrow, the associated token account of the plan for
+    ///      the mint sold, created if needed
+    ///   2. `[]` Token-swap
+    ///   3. `[signer]` Owner
+    ///   4. `[writable]` Owner token account of the mint sold
+    ///   5. `[]` Mint sold, the base mint of the swap selling base, the quote
+    ///      mint otherwise
+    ///   6. `[writable, signer]` Payer of the rent
+    ///   7. `[]` Rent sysvar
+    ///   8. `[]` System program
+    ///   9. `[]` Token program id
+    ///   10. `[]` Associated token account program id
+    CreateDcaPlan(CreateDcaPlanData),
+
+    ///   Execute the period due of a DCA plan, swapping the period amount
+    ///   from the escrow for the owner. Anyone may execute it, the keeper
+    ///   receiving the keeper reward of the plan out of the period amount.
+    ///
+    ///   0. `[writable]` DCA plan
+    ///   1. `[writable]` Keeper token account of the mint sold
+    ///
+    ///   .. The `Swap` accounts, with the plan as user transfer authority,
+    ///      the escrow as the user token account sold from and the other user
+    ///      token account and reward account owned by the plan owner
+    ExecuteDca,
+
+    ///   Cancel the DCA plan of the owner, refunding the amount left in the
+    ///   escrow and closing the plan and the escrow.
+    ///
+    ///   0. `[writable]` DCA plan of the owner
+    ///   1. `[writable]` Escrow of the plan
+    ///   2. `[writable, signer]` Owner, receiving the rent of the plan and the
+    ///      escrow
+    ///   3. `[writable]` Owner token account of the mint sold, refunded
+    ///   4. `[]` Token program id
+    CancelDca,
+}
+
+impl SwapInstruction {
+    /// Unpacks a byte buffer into a [SwapInstruction](enum.SwapInstruction.html).
+    ///
+    /// Fields added to an instruction are optional trailing ones, so data
+    /// packed by older clients keeps unpacking. Data too short for the
+    /// instruction fails to unpack, and bytes left after it are rejected.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or(SwapError::InstructionUnpackError)?;
+        let (instruction, rest) = match tag {
+            0x0 => {
+                let (&nonce, rest) = rest
+                    .split_first()
+                    .ok_or(SwapError::InstructionUnpackError)?;
+                let (slope, rest) = unpack_u64(rest)?;
+                let (mid_price, rest) = unpack_u128(rest)?;
+                let (is_open_twap, rest) = unpack_bool(rest)?;
+                let (twap_window_secs, rest) = unpack_u64(rest)?;
+                let (pair_symbol, rest) = if rest.is_empty() {
+                    (None, rest)
+                } else {
+                    let (len, rest) = unpack_u8(rest)?;
+                    if rest.len() < len as usize {
+                        return Err(SwapError::InstructionUnpackError.into());
+                    }
+                    let (pair_symbol, rest) = rest.split_at(len as usize);
+                    let pair_symbol = String::from_utf8(pair_symbol.to_vec())
+                        .map_err(|_| SwapError::InstructionUnpackError)?;
+                    (Some(pair_symbol), rest)
+                };
+                (
+                    Self::Initialize(InitializeData {
+                        nonce,
+                        slope,
+                        mid_price,
+                        is_open_twap,
+                        twap_window_secs,
+                        pair_symbol,
+                    }),
+                    rest,
+                )
+            }
+            0x1 => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                let (max_price_impact_bps, rest) = unpack_optional_u64(rest)?;
+                (
+                    Self::Swap(SwapData {
+                        amount_in,
+                        minimum_amount_out,
+                        swap_direction,
+                        max_price_impact_bps,
+                    }),
+                    rest,
+                )
+            }
+            0x2 => {
+                let (token_a_amount, rest) = unpack_u64(rest)?;
+                let (token_b_amount, rest) = unpack_u64(rest)?;
+                let (min_mint_amount, rest) = unpack_u64(rest)?;
+                (
+                    Self::Deposit(DepositData {
+                        token_a_amount,
+                        token_b_amount,
+                        min_mint_amount,
+                    }),
+                    rest,
+                )
+            }
+            0x3 => {
+                let (pool_token_amount, rest) = unpack_u64(rest)?;
+                let (minimum_token_a_amount, rest) = unpack_u64(rest)?;
+                let (minimum_token_b_amount, rest) = unpack_u64(rest)?;
+                (
+                    Self::Withdraw(WithdrawData {
+                        pool_token_amount,
+                        minimum_token_a_amount,
+                        minimum_token_b_amount,
+                    }),
+                    rest,
+                )
+            }
+            0x4 => (Self::InitializeLiquidityProvider, rest),
+            0x5 => (Self::ClaimLiquidityRewards, rest),
+            0x6 => (Self::RefreshLiquidityObligation, rest),
+            0x7 => {
+                let (&max_positions, rest) = rest
+                    .split_first()
+                    .ok_or(SwapError::InstructionUnpackError)?;
+                (
+                    Self::GrowProviderAccount(GrowProviderAccountData { max_positions }),
+                    rest,
+                )
+            }
+            0x8 => (Self::CloseLiquidityProvider, rest),
+            0x9 => {
+                let (&page_index, rest) = rest
+                    .split_first()
+                    .ok_or(SwapError::InstructionUnpackError)?;
+                (
+                    Self::InitializePositionPage(InitializePositionPageData { page_index }),
+                    rest,
+                )
+            }
+            0xA => {
+                let (token_a_amount, rest) = unpack_u64(rest)?;
+                let (token_b_amount, rest) = unpack_u64(rest)?;
+                let (min_mint_amount, rest) = unpack_u64(rest)?;
+                (
+                    Self::DepositImbalanced(DepositData {
+                        token_a_amount,
+                        token_b_amount,
+                        min_mint_amount,
+                    }),
+                    rest,
+                )
+            }
+            0xB => {
+                let (token_a_amount, rest) = unpack_u64(rest)?;
+                let (token_b_amount, rest) = unpack_u64(rest)?;
+                let (max_pool_token_amount, rest) = unpack_u64(rest)?;
+                (
+                    Self::WithdrawExact(WithdrawExactData {
+                        token_a_amount,
+                        token_b_amount,
+                        max_pool_token_amount,
+                    }),
+                    rest,
+                )
+            }
+            0xC => (Self::InitializeObservationBuffer, rest),
+            0xD => {
+                let (page_index, rest) = unpack_u8(rest)?;
+                (
+                    Self::InitializeRegistryPage(InitializeRegistryPageData { page_index }),
+                    rest,
+                )
+            }
+            0xE => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                (
+                    Self::QuoteSwap(QuoteSwapData {
+                        amount_in,
+                        swap_direction,
+                    }),
+                    rest,
+                )
+            }
+            0xF => (Self::InitializeQuoteAccount, rest),
+            0x10 => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                (
+                    Self::CalcReceiveAmount(QuoteSwapData {
+                        amount_in,
+                        swap_direction,
+                    }),
+                    rest,
+                )
+            }
+            0x11 => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                let (max_price_impact_bps, rest) = unpack_optional_u64(rest)?;
+                (
+                    Self::SwapNative(SwapData {
+                        amount_in,
+                        minimum_amount_out,
+                        swap_direction,
+                        max_price_impact_bps,
+                    }),
+                    rest,
+                )
+            }
+            0x12 => {
+                let (token_a_amount, rest) = unpack_u64(rest)?;
+                let (token_b_amount, rest) = unpack_u64(rest)?;
+                let (min_mint_amount, rest) = unpack_u64(rest)?;
+                (
+                    Self::DepositNative(DepositData {
+                        token_a_amount,
+                        token_b_amount,
+                        min_mint_amount,
+                    }),
+                    rest,
+                )
+            }
+            0x13 => {
+                let (source_page_count, rest) = unpack_u8(rest)?;
+                (
+                    Self::TransferPosition(TransferPositionData { source_page_count }),
+                    rest,
+                )
+            }
+            0x14 => {
+                let (delegate, rest) = unpack_pubkey(rest)?;
+                (
+                    Self::SetLiquidityProviderDelegate(SetLiquidityProviderDelegateData {
+                        delegate,
+                    }),
+                    rest,
+                )
+            }
+            0x15 => {
+                let (swap_count, rest) = unpack_u8(rest)?;
+                (
+                    Self::ClaimAllRewards(ClaimAllRewardsData { swap_count }),
+                    rest,
+                )
+            }
+            0x16 => (Self::Poke, rest),
+            0x17 => (Self::InitializeDelfiLock, rest),
+            0x18 => {
+                let (amount, rest) = unpack_u64(rest)?;
+                let (unlock_ts, rest) = unpack_i64(rest)?;
+                (Self::LockDelfi(LockDelfiData { amount, unlock_ts }), rest)
+            }
+            0x19 => (Self::UnlockDelfi, rest),
+            0x1A => {
+                let (weight_count, mut rest) = unpack_u8(rest)?;
+                let mut weights_bps = Vec::with_capacity(weight_count as usize);
+                for _ in 0..weight_count {
+                    let (weight_bps, next) = unpack_u16(rest)?;
+                    weights_bps.push(weight_bps);
+                    rest = next;
+                }
+                (Self::VoteGauge(VoteGaugeData { weights_bps }), rest)
+            }
+            0x1B => {
+                if rest.len() < Fees::LEN + Rewards::LEN {
+                    return Err(SwapError::InstructionUnpackError.into());
+                }
+                let (fees, rest) = rest.split_at(Fees::LEN);
+                let fees = Fees::unpack_unchecked(fees)?;
+                let (rewards, rest) = rest.split_at(Rewards::LEN);
+                let rewards = Rewards::unpack_unchecked(rewards)?;
+                (
+                    Self::CreateProposal(CreateProposalData { fees, rewards }),
+                    rest,
+                )
+            }
+            0x1C => {
+                let (approve, rest) = unpack_bool(rest)?;
+                (Self::VoteProposal(VoteProposalData { approve }), rest)
+            }
+            0x1D => (Self::ExecuteProposal, rest),
+            0x1E => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                let (max_price_impact_bps, rest) = unpack_u64(rest)?;
+                // the callback data takes the rest of the input
+                let (callback_data, rest) = rest.split_at(rest.len());
+                (
+                    Self::FlashSwap(FlashSwapData {
+                        amount_in,
+                        minimum_amount_out,
+                        swap_direction,
+                        max_price_impact_bps,
+                        callback_data: callback_data.to_vec(),
+                    }),
+                    rest,
+                )
+            }
+            0x1F => {
+                let (minimum_total_amount_out, rest) = unpack_u64(rest)?;
+                let (swap_count, mut rest) = unpack_u8(rest)?;
+                let mut swaps = Vec::with_capacity(swap_count as usize);
+                for _ in 0..swap_count {
+                    let (amount_in, next) = unpack_u64(rest)?;
+                    let (minimum_amount_out, next) = unpack_u64(next)?;
+                    let (swap_direction, next) = unpack_swap_direction(next)?;
+                    let (max_price_impact_bps, next) = unpack_u64(next)?;
+                    swaps.push(SwapData {
+                        amount_in,
+                        minimum_amount_out,
+                        swap_direction,
+                        max_price_impact_bps,
+                    });
+                    rest = next;
+                }
+                (
+                    Self::BatchSwap(BatchSwapData {
+                        swaps,
+                        minimum_total_amount_out,
+                    }),
+                    rest,
+                )
+            }
+            0x20 => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                let (fraction_count, mut rest) = unpack_u8(rest)?;
+                let mut fractions_bps = Vec::with_capacity(fraction_count as usize);
+                for _ in 0..fraction_count {
+                    let (fraction_bps, next) = unpack_u16(rest)?;
+                    fractions_bps.push(fraction_bps);
+                    rest = next;
+                }
+                (
+                    Self::SplitSwap(SplitSwapData {
+                        amount_in,
+                        minimum_amount_out,
+                        swap_direction,
+                        fractions_bps,
+                    }),
+                    rest,
+                )
+            }
+            0x21 => {
+                let (minimum_token_a_amount, rest) = unpack_u64(rest)?;
+                let (minimum_token_b_amount, rest) = unpack_u64(rest)?;
+                (
+                    Self::EmergencyWithdraw(EmergencyWithdrawData {
+                        minimum_token_a_amount,
+                        minimum_token_b_amount,
+                    }),
+                    rest,
+                )
+            }
+            0x22 => (Self::Sync, rest),
+            0x23 => (Self::Skim, rest),
+            0x24 => (Self::GetVersion, rest),
+            0x25 => (Self::GetVirtualPrice, rest),
+            0x26 => (Self::InitializePriceFeed, rest),
+            0x27 => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                let (max_price_impact_bps, rest) = unpack_u64(rest)?;
+                let (relayer_fee, rest) = unpack_u64(rest)?;
+                (
+                    Self::RelayedSwap(RelayedSwapData {
+                        amount_in,
+                        minimum_amount_out,
+                        swap_direction,
+                        max_price_impact_bps,
+                        relayer_fee,
+                    }),
+                    rest,
+                )
+            }
+            0x28 => {
+                if rest.len() < SIGNED_ORDER_SIZE {
+                    return Err(SwapError::InstructionUnpackError.into());
+                }
+                let (order, rest) = rest.split_at(SIGNED_ORDER_SIZE);
+                (Self::SettleSignedOrder(SignedOrder::unpack(order)?), rest)
+            }
+            0x29 => {
+                let (next_nonce, rest) = unpack_u64(rest)?;
+                (
+                    Self::CancelSignedOrders(CancelSignedOrdersData { next_nonce }),
+                    rest,
+                )
+            }
+            0x2A => {
+                let (amount, rest) = unpack_u64(rest)?;
+                let (amount_per_period, rest) = unpack_u64(rest)?;
+                let (interval, rest) = unpack_i64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                let (max_price_impact_bps, rest) = unpack_u64(rest)?;
+                let (keeper_reward_bps, rest) = unpack_u64(rest)?;
+                (
+                    Self::CreateDcaPlan(CreateDcaPlanData {
+                        amount,
+                        amount_per_period,
+                        interval,
+                        swap_direction,
+                        max_price_impact_bps,
+                        keeper_reward_bps,
+                    }),
+                    rest,
+                )
+            }
+            0x2B => (Self::ExecuteDca, rest),
+            0x2C => (Self::CancelDca, rest),
+            _ => return Err(SwapError::InvalidInstruction.into()),
+        };
+        if !rest.is_empty() {
+            return Err(SwapError::InvalidInstructionData.into());
+        }
+        Ok(instruction)
+    }
+
+    /// Packs a [SwapInstruction](enum.SwapInstruction.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match *self {
+            Self::Initialize(InitializeData {
+                nonce,
+                slope,
+                mid_price,
+                is_open_twap,
+                twap_window_secs,
+                ref pair_symbol,
+            }) => {
+                buf.push(0x0);
+                buf.push(nonce);
+                buf.extend_from_slice(&slope.to_le_bytes());
+                buf.extend_from_slice(&mid_price.to_le_bytes());
+                buf.extend_from_slice(&(is_open_twap as u8).to_le_bytes());
+                buf.extend_from_slice(&twap_window_secs.to_le_bytes());
+                if let Some(pair_symbol) = pair_symbol {
+                    buf.push(pair_symbol.len() as u8);
+                    buf.extend_from_slice(pair_symbol.as_bytes());
+                }
+            }
+            Self::Swap(SwapData {
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                max_price_impact_bps,
+            }) => {
+                buf.push(0x1);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+                // left out without a limit, as packed by older clients
+                if max_price_impact_bps > 0 {
+                    buf.extend_from_slice(&max_price_impact_bps.to_le_bytes());
+                }
+            }
+            Self::Deposit(DepositData {
+                token_a_amount,
+                token_b_amount,
+                min_mint_amount,
+            }) => {
+                buf.push(0x2);
+                buf.extend_from_slice(&token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&token_b_amount.to_le_bytes());
+                buf.extend_from_slice(&min_mint_amount.to_le_bytes());
+            }
+            Self::Withdraw(WithdrawData {
+                pool_token_amount,
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+            }) => {
+                buf.push(0x3);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
+            }
+            Self::InitializeLiquidityProvider => {
+                buf.push(0x4);
+            }
+            Self::ClaimLiquidityRewards => {
+                buf.push(0x5);
+            }
+            Self::RefreshLiquidityObligation => {
+                buf.push(0x6);
+            }
+            Self::GrowProviderAccount(GrowProviderAccountData { max_positions }) => {
+                buf.push(0x7);
+                buf.push(max_positions);
+            }
+            Self::CloseLiquidityProvider => {
+                buf.push(0x8);
+            }
+            Self::InitializePositionPage(InitializePositionPageData { page_index }) => {
+                buf.push(0x9);
+                buf.push(page_index);
+            }
+            Self::DepositImbalanced(DepositData {
+                token_a_amount,
+                token_b_amount,
+                min_mint_amount,
+            }) => {
+                buf.push(0xA);
+                buf.extend_from_slice(&token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&token_b_amount.to_le_bytes());
+                buf.extend_from_slice(&min_mint_amount.to_le_bytes());
+            }
+            Self::WithdrawExact(WithdrawExactData {
+                token_a_amount,
+                token_b_amount,
+                max_pool_token_amount,
+            }) => {
+                buf.push(0xB);
+                buf.extend_from_slice(&token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&token_b_amount.to_le_bytes());
+                buf.extend_from_slice(&max_pool_token_amount.to_le_bytes());
+            }
+            Self::InitializeObservationBuffer => {
+                buf.push(0xC);
+            }
+            Self::InitializeRegistryPage(InitializeRegistryPageData { page_index }) => {
+                buf.push(0xD);
+                buf.extend_from_slice(&page_index.to_le_bytes());
+            }
+            Self::QuoteSwap(QuoteSwapData {
+                amount_in,
+                swap_direction,
+            }) => {
+                buf.push(0xE);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+            }
+            Self::InitializeQuoteAccount => {
+                buf.push(0xF);
+            }
+            Self::CalcReceiveAmount(QuoteSwapData {
+                amount_in,
+                swap_direction,
+            }) => {
+                buf.push(0x10);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+            }
+            Self::SwapNative(SwapData {
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                max_price_impact_bps,
+            }) => {
+                buf.push(0x11);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+                // left out without a limit, as packed by older clients
+                if max_price_impact_bps > 0 {
+                    buf.extend_from_slice(&max_price_impact_bps.to_le_bytes());
+                }
+            }
+            Self::DepositNative(DepositData {
+                token_a_amount,
+                token_b_amount,
+                min_mint_amount,
+            }) => {
+                buf.push(0x12);
+                buf.extend_from_slice(&token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&token_b_amount.to_le_bytes());
+                buf.extend_from_slice(&min_mint_amount.to_le_bytes());
+            }
+            Self::TransferPosition(TransferPositionData { source_page_count }) => {
+                buf.push(0x13);
+                buf.push(source_page_count);
+            }
+            Self::SetLiquidityProviderDelegate(SetLiquidityProviderDelegateData { delegate }) => {
+                buf.push(0x14);
+                buf.extend_from_slice(delegate.as_ref());
+            }
+            Self::ClaimAllRewards(ClaimAllRewardsData { swap_count }) => {
+                buf.push(0x15);
+                buf.push(swap_count);
+            }
+            Self::Poke => {
+                buf.push(0x16);
+            }
+            Self::InitializeDelfiLock => {
+                buf.push(0x17);
+            }
+            Self::LockDelfi(LockDelfiData { amount, unlock_ts }) => {
+                buf.push(0x18);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&unlock_ts.to_le_bytes());
+            }
+            Self::UnlockDelfi => {
+                buf.push(0x19);
+            }
+            Self::VoteGauge(VoteGaugeData { ref weights_bps }) => {
+                buf.push(0x1A);
+                buf.push(u8::try_from(weights_bps.len()).unwrap());
+                for weight_bps in weights_bps {
+                    buf.extend_from_slice(&weight_bps.to_le_bytes());
+                }
+            }
+            Self::CreateProposal(CreateProposalData {
+                ref fees,
+                ref rewards,
+            }) => {
+                buf.push(0x1B);
+                let mut fees_slice = [0u8; Fees::LEN];
+                Pack::pack_into_slice(fees, &mut fees_slice[..]);
+                buf.extend_from_slice(&fees_slice);
+                let mut rewards_slice = [0u8; Rewards::LEN];
+                Pack::pack_into_slice(rewards, &mut rewards_slice[..]);
+                buf.extend_from_slice(&rewards_slice);
+            }
+            Self::VoteProposal(VoteProposalData { approve }) => {
+                buf.push(0x1C);
+                buf.push(approve as u8);
+            }
+            Self::ExecuteProposal => {
+                buf.push(0x1D);
+            }
+            Self::FlashSwap(FlashSwapData {
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                max_price_impact_bps,
+                ref callback_data,
+            }) => {
+                buf.push(0x1E);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+                buf.extend_from_slice(&max_price_impact_bps.to_le_bytes());
+                buf.extend_from_slice(callback_data);
+            }
+            Self::BatchSwap(BatchSwapData {
+                ref swaps,
+                minimum_total_amount_out,
+            }) => {
+                buf.push(0x1F);
+                buf.extend_from_slice(&minimum_total_amount_out.to_le_bytes());
+                buf.push(swaps.len() as u8);
+                for swap_data in swaps {
+                    buf.extend_from_slice(&swap_data.amount_in.to_le_bytes());
+                    buf.extend_from_slice(&swap_data.minimum_amount_out.to_le_bytes());
+                    buf.extend_from_slice(&(swap_data.swap_direction as u8).to_le_bytes());
+                    buf.extend_from_slice(&swap_data.max_price_impact_bps.to_le_bytes());
+                }
+            }
+            Self::SplitSwap(SplitSwapData {
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                ref fractions_bps,
+            }) => {
+                buf.push(0x20);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+                buf.push(fractions_bps.len() as u8);
+                for fraction_bps in fractions_bps {
+                    buf.extend_from_slice(&fraction_bps.to_le_bytes());
+                }
+            }
+            Self::EmergencyWithdraw(EmergencyWithdrawData {
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+            }) => {
+                buf.push(0x21);
+                buf.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
+            }
+            Self::Sync => {
+                buf.push(0x22);
+            }
+            Self::Skim => {
+                buf.push(0x23);
+            }
+            Self::GetVersion => {
+                buf.push(0x24);
+            }
+            Self::GetVirtualPrice => {
+                buf.push(0x25);
+            }
+            Self::InitializePriceFeed => {
+                buf.push(0x26);
+            }
+            Self::RelayedSwap(RelayedSwapData {
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                max_price_impact_bps,
+                relayer_fee,
+            }) => {
+                buf.push(0x27);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+                buf.extend_from_slice(&max_price_impact_bps.to_le_bytes());
+                buf.extend_from_slice(&relayer_fee.to_le_bytes());
+            }
+            Self::SettleSignedOrder(ref order) => {
+                buf.push(0x28);
+                buf.extend_from_slice(&order.pack());
+            }
+            Self::CancelSignedOrders(CancelSignedOrdersData { next_nonce }) => {
+                buf.push(0x29);
+                buf.extend_from_slice(&next_nonce.to_le_bytes());
+            }
+            Self::CreateDcaPlan(CreateDcaPlanData {
+                amount,
+                amount_per_period,
+                interval,
+                swap_direction,
+                max_price_impact_bps,
+                keeper_reward_bps,
+            }) => {
+                buf.push(0x2A);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&amount_per_period.to_le_bytes());
+                buf.extend_from_slice(&interval.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+                buf.extend_from_slice(&max_price_impact_bps.to_le_bytes());
+                buf.extend_from_slice(&keeper_reward_bps.to_le_bytes());
+            }
+            Self::ExecuteDca => buf.push(0x2B),
+            Self::CancelDca => buf.push(0x2C),
+        }
+        buf
+    }
+}
+
+/// Creates an 'initialize' instruction.
+pub fn initialize(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    admin_fee_a_pubkey: Pubkey,
+    admin_fee_b_pubkey: Pubkey,
+    token_a_pubkey: Pubkey,
+    token_b_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    registry_page_index: u8,
+    token_a_mint_pubkey: Pubkey,
+    token_b_mint_pubkey: Pubkey,
+    init_data: InitializeData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Initialize(init_data).pack();
+
+    let (registry_page_pubkey, _) =
+        find_registry_page_address(&program_id, &config_pubkey, registry_page_index);
+    let (deny_list_pubkey, _) = find_deny_list_address(&program_id, &config_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(admin_fee_a_pubkey, false),
+        AccountMeta::new_readonly(admin_fee_b_pubkey, false),
+        AccountMeta::new_readonly(token_a_pubkey, false),
+        AccountMeta::new_readonly(token_b_pubkey, false),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(registry_page_pubkey, false),
+        AccountMeta::new_readonly(token_a_mint_pubkey, false),
+        AccountMeta::new_readonly(token_b_mint_pubkey, false),
+        AccountMeta::new_readonly(deny_list_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'swap' instruction.
+pub fn swap(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    market_authority_pubkey: Pubkey,
+    swap_authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    source_pubkey: Pubkey,
+    swap_source_pubkey: Pubkey,
+    swap_destination_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    reward_token_pubkey: Pubkey,
+    reward_mint_pubkey: Pubkey,
+    admin_fee_destination_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    swap_data: SwapData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Swap(swap_data).pack();
+
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+    let (whitelist_pubkey, _) = find_whitelist_address(&program_id, &swap_pubkey);
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(market_authority_pubkey, false),
+        AccountMeta::new_readonly(swap_authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new(swap_source_pubkey, false),
+        AccountMeta::new(swap_destination_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new(reward_token_pubkey, false),
+        AccountMeta::new(reward_mint_pubkey, false),
+        AccountMeta::new(admin_fee_destination_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(observation_buffer_pubkey, false),
+        AccountMeta::new_readonly(whitelist_pubkey, false),
+        AccountMeta::new(price_feed_pubkey, false),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'quote_swap' instruction.
+pub fn quote_swap(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    token_a_pubkey: Pubkey,
+    token_b_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    quote_swap_data: QuoteSwapData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::QuoteSwap(quote_swap_data).pack();
+
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(token_a_pubkey, false),
+        AccountMeta::new_readonly(token_b_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(observation_buffer_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'calc_receive_amount' instruction.
+pub fn calc_receive_amount(
+    program_id: Pubkey,
+    owner_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    token_a_pubkey: Pubkey,
+    token_b_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    quote_swap_data: QuoteSwapData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CalcReceiveAmount(quote_swap_data).pack();
+
+    let (quote_account_pubkey, _) = find_quote_account_address(&program_id, &owner_pubkey);
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(quote_account_pubkey, false),
+        AccountMeta::new_readonly(owner_pubkey, true),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(token_a_pubkey, false),
+        AccountMeta::new_readonly(token_b_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(observation_buffer_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'deposit' instruction.
+pub fn deposit(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    deposit_token_a_pubkey: Pubkey,
+    deposit_token_b_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    deposit_data: DepositData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Deposit(deposit_data).pack();
+
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(deposit_token_a_pubkey, false),
+        AccountMeta::new(deposit_token_b_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(observation_buffer_pubkey, false),
+        AccountMeta::new(price_feed_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'deposit_imbalanced' instruction.
+pub fn deposit_imbalanced(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    deposit_token_a_pubkey: Pubkey,
+    deposit_token_b_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    admin_fee_a_pubkey: Pubkey,
+    admin_fee_b_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    deposit_data: DepositData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::DepositImbalanced(deposit_data).pack();
+
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(deposit_token_a_pubkey, false),
+        AccountMeta::new(deposit_token_b_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new(admin_fee_a_pubkey, false),
+        AccountMeta::new(admin_fee_b_pubkey, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(observation_buffer_pubkey, false),
+        AccountMeta::new(price_feed_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'withdraw' instruction.
+pub fn withdraw(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    source_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    destination_token_a_pubkey: Pubkey,
+    destination_token_b_pubkey: Pubkey,
+    admin_fee_a_pubkey: Pubkey,
+    admin_fee_b_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    withdraw_data: WithdrawData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Withdraw(withdraw_data).pack();
+
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(destination_token_a_pubkey, false),
+        AccountMeta::new(destination_token_b_pubkey, false),
+        AccountMeta::new(admin_fee_a_pubkey, false),
+        AccountMeta::new(admin_fee_b_pubkey, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(observation_buffer_pubkey, false),
+        AccountMeta::new(price_feed_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'emergency_withdraw' instruction.
+pub fn emergency_withdraw(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    source_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    destination_token_a_pubkey: Pubkey,
+    destination_token_b_pubkey: Pubkey,
+    admin_fee_a_pubkey: Pubkey,
+    admin_fee_b_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    withdraw_data: EmergencyWithdrawData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::EmergencyWithdraw(withdraw_data).pack();
+
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(destination_token_a_pubkey, false),
+        AccountMeta::new(destination_token_b_pubkey, false),
+        AccountMeta::new(admin_fee_a_pubkey, false),
+        AccountMeta::new(admin_fee_b_pubkey, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(price_feed_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'withdraw_exact' instruction.
+pub fn withdraw_exact(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    source_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    destination_token_a_pubkey: Pubkey,
+    destination_token_b_pubkey: Pubkey,
+    admin_fee_a_pubkey: Pubkey,
+    admin_fee_b_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    withdraw_data: WithdrawExactData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::WithdrawExact(withdraw_data).pack();
+
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(destination_token_a_pubkey, false),
+        AccountMeta::new(destination_token_b_pubkey, false),
+        AccountMeta::new(admin_fee_a_pubkey, false),
+        AccountMeta::new(admin_fee_b_pubkey, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(observation_buffer_pubkey, false),
+        AccountMeta::new(price_feed_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates `InitializeLiquidityProvider` instruction
+pub fn init_liquidity_provider(
+    program_id: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitializeLiquidityProvider.pack();
+    let (liquidity_provider_pubkey, _) =
+        find_liquidity_provider_address(&program_id, &liquidity_owner_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `InitializeLiquidityProvider` instruction for a referred owner
+pub fn init_liquidity_provider_with_referrer(
+    program_id: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    referrer_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = init_liquidity_provider(program_id, liquidity_owner_pubkey)?;
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(referrer_pubkey, false));
+    Ok(instruction)
+}
+
+/// Creates `ClaimLiquidityRewards` instruction
+pub fn claim_liquidity_rewards(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    market_authority_info: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    claim_destination_pubkey: Pubkey,
+    claim_mint_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ClaimLiquidityRewards.pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(market_authority_info, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new(claim_destination_pubkey, false),
+        AccountMeta::new(claim_mint_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `ClaimAllRewards` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn claim_all_rewards(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    market_authority_info: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    claim_destination_pubkey: Pubkey,
+    claim_mint_pubkey: Pubkey,
+    swap_pubkeys: Vec<Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let swap_count = u8::try_from(swap_pubkeys.len()).map_err(|_| SwapError::InvalidInput)?;
+    let data = SwapInstruction::ClaimAllRewards(ClaimAllRewardsData { swap_count }).pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(market_authority_info, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new(claim_destination_pubkey, false),
+        AccountMeta::new(claim_mint_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(
+        swap_pubkeys
+            .into_iter()
+            .map(|pubkey| AccountMeta::new_readonly(pubkey, false)),
+    );
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `Sync` instruction
+pub fn sync(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Sync.pack();
+
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(swap_token_a_pubkey, false),
+        AccountMeta::new_readonly(swap_token_b_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(price_feed_pubkey, false),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates `Skim` instruction
+pub fn skim(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    admin_fee_a_pubkey: Pubkey,
+    admin_fee_b_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Skim.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(admin_fee_a_pubkey, false),
+        AccountMeta::new(admin_fee_b_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates `GetVersion` instruction
+pub fn get_version(program_id: Pubkey) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id,
+        accounts: vec![],
+        data: SwapInstruction::GetVersion.pack(),
+    })
+}
+
+/// Creates `GetVirtualPrice` instruction
+pub fn get_virtual_price(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(swap_pubkey, false),
+            AccountMeta::new_readonly(pool_mint_pubkey, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: SwapInstruction::GetVirtualPrice.pack(),
+    })
+}
+
+/// Creates `InitializePriceFeed` instruction
+pub fn init_price_feed(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitializePriceFeed.pack();
+    let (price_feed_pubkey, _) = find_price_feed_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(price_feed_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates a `CreateDcaPlan` instruction.
+pub fn create_dca_plan(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    source_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    plan_data: CreateDcaPlanData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CreateDcaPlan(plan_data).pack();
+    let (dca_plan_pubkey, _) = find_dca_plan_address(&program_id, &swap_pubkey, &owner_pubkey);
+    let escrow_pubkey =
+        spl_associated_token_account::get_associated_token_address(&dca_plan_pubkey, &mint_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(dca_plan_pubkey, false),
+        AccountMeta::new(escrow_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(owner_pubkey, true),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new_readonly(mint_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates a `CancelDca` instruction.
+pub fn cancel_dca(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CancelDca.pack();
+    let (dca_plan_pubkey, _) = find_dca_plan_address(&program_id, &swap_pubkey, &owner_pubkey);
+    let escrow_pubkey =
+        spl_associated_token_account::get_associated_token_address(&dca_plan_pubkey, &mint_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(dca_plan_pubkey, false),
+        AccountMeta::new(escrow_pubkey, false),
+        AccountMeta::new(owner_pubkey, true),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates a `CancelSignedOrders` instruction.
+pub fn cancel_signed_orders(
+    program_id: Pubkey,
+    maker_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    next_nonce: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CancelSignedOrders(CancelSignedOrdersData { next_nonce }).pack();
+    let (order_nonce_pubkey, _) = find_order_nonce_address(&program_id, &maker_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(order_nonce_pubkey, false),
+        AccountMeta::new_readonly(maker_pubkey, true),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `Poke` instruction
+pub fn poke(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    market_authority_pubkey: Pubkey,
+    reward_destination_pubkey: Pubkey,
+    deltafi_mint_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Poke.pack();
+
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(market_authority_pubkey, false),
+        AccountMeta::new(observation_buffer_pubkey, false),
+        AccountMeta::new(reward_destination_pubkey, false),
+        AccountMeta::new(deltafi_mint_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `InitializeDelfiLock` instruction
+pub fn initialize_delfi_lock(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitializeDelfiLock.pack();
+
+    let (delfi_lock_pubkey, _) =
+        find_delfi_lock_address(&program_id, &config_pubkey, &owner_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(delfi_lock_pubkey, false),
+        AccountMeta::new(owner_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `LockDelfi` instruction
+pub fn lock_delfi(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    market_authority_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    source_pubkey: Pubkey,
+    deltafi_mint_pubkey: Pubkey,
+    lock_data: LockDelfiData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::LockDelfi(lock_data).pack();
+
+    let (delfi_lock_pubkey, _) =
+        find_delfi_lock_address(&program_id, &config_pubkey, &owner_pubkey);
+    let vault_pubkey = spl_associated_token_account::get_associated_token_address(
+        &market_authority_pubkey,
+        &deltafi_mint_pubkey,
+    );
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(market_authority_pubkey, false),
+        AccountMeta::new(delfi_lock_pubkey, false),
+        AccountMeta::new_readonly(owner_pubkey, true),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new(vault_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `UnlockDelfi` instruction
+pub fn unlock_delfi(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    market_authority_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    deltafi_mint_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::UnlockDelfi.pack();
+
+    let (delfi_lock_pubkey, _) =
+        find_delfi_lock_address(&program_id, &config_pubkey, &owner_pubkey);
+    let vault_pubkey = spl_associated_token_account::get_associated_token_address(
+        &market_authority_pubkey,
+        &deltafi_mint_pubkey,
+    );
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(market_authority_pubkey, false),
+        AccountMeta::new(delfi_lock_pubkey, false),
+        AccountMeta::new_readonly(owner_pubkey, true),
+        AccountMeta::new(vault_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `VoteGauge` instruction, voting for each token-swap with its weight
+pub fn vote_gauge(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    swap_weights_bps: Vec<(Pubkey, u16)>,
+) -> Result<Instruction, ProgramError> {
+    if swap_weights_bps.len() > u8::MAX as usize {
+        return Err(SwapError::InvalidInput.into());
+    }
+    let (swap_pubkeys, weights_bps): (Vec<Pubkey>, Vec<u16>) = swap_weights_bps.into_iter().unzip();
+    let data = SwapInstruction::VoteGauge(VoteGaugeData { weights_bps }).pack();
+
+    let (delfi_lock_pubkey, _) =
+        find_delfi_lock_address(&program_id, &config_pubkey, &owner_pubkey);
+
+    let mut accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new(delfi_lock_pubkey, false),
+        AccountMeta::new_readonly(owner_pubkey, true),
+    ];
+    accounts.extend(
+        swap_pubkeys
+            .into_iter()
+            .map(|swap_pubkey| AccountMeta::new(swap_pubkey, false)),
+    );
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `CreateProposal` instruction for the next proposal of the config
+pub fn create_proposal(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    proposal_index: u64,
+    proposer_pubkey: Pubkey,
+    proposal_data: CreateProposalData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CreateProposal(proposal_data).pack();
+
+    let (proposal_pubkey, _) = find_proposal_address(&program_id, &config_pubkey, proposal_index);
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new(proposal_pubkey, false),
+        AccountMeta::new(proposer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `VoteProposal` instruction, with the DELFI lock of the owner in the
+/// config of the proposal
+pub fn vote_proposal(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    proposal_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    approve: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::VoteProposal(VoteProposalData { approve }).pack();
+
+    let (vote_record_pubkey, _) =
+        find_vote_record_address(&program_id, &proposal_pubkey, &owner_pubkey);
+    let (delfi_lock_pubkey, _) =
+        find_delfi_lock_address(&program_id, &config_pubkey, &owner_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(proposal_pubkey, false),
+        AccountMeta::new(vote_record_pubkey, false),
+        AccountMeta::new_readonly(delfi_lock_pubkey, false),
+        AccountMeta::new(owner_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `ExecuteProposal` instruction
+pub fn execute_proposal(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    proposal_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ExecuteProposal.pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new(proposal_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Appends the position pages of a liquidity provider to a
+/// `Deposit`, `DepositImbalanced`, `Withdraw`, `WithdrawExact`, `ClaimLiquidityRewards`
+/// or `ClaimAllRewards` instruction
+pub fn append_position_pages(
+    mut instruction: Instruction,
+    position_page_pubkeys: Vec<Pubkey>,
+) -> Instruction {
+    instruction.accounts.extend(
+        position_page_pubkeys
+            .into_iter()
+            .map(|pubkey| AccountMeta::new(pubkey, false)),
+    );
+    instruction
+}
+
+/// Appends the accounts creating the missing associated token accounts of the
+/// liquidity provider owner to a `Withdraw`, `WithdrawExact`,
+/// `ClaimLiquidityRewards` or `ClaimAllRewards` instruction, before the position pages.
+/// The mints are the ones of the destinations, in order
+pub fn append_associated_token_accounts(
+    mut instruction: Instruction,
+    payer_pubkey: Pubkey,
+    mint_pubkeys: Vec<Pubkey>,
+) -> Instruction {
+    instruction.accounts.extend(
+        vec![
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new(payer_pubkey, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ]
+        .into_iter()
+        .chain(
+            mint_pubkeys
+                .into_iter()
+                .map(|pubkey| AccountMeta::new_readonly(pubkey, false)),
+        ),
+    );
+    instruction
+}
+
+/// Appends the accounts naming the pool mint to an `Initialize` instruction
+/// with a pair symbol
+pub fn append_pool_mint_metadata(
+    mut instruction: Instruction,
+    pool_mint_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+) -> Instruction {
+    let (metadata_pubkey, _) = metadata::find_metadata_address(&pool_mint_pubkey);
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(metadata::id(), false),
+        AccountMeta::new(metadata_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ]);
+    instruction
+}
+
+/// Appends the pool token mint to a `QuoteSwap` instruction, after any OpenBook
+/// market, returning the value of a pool token after the quote
+pub fn append_pool_mint(mut instruction: Instruction, pool_mint_pubkey: Pubkey) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(pool_mint_pubkey, false));
+    instruction
+}
+
+/// Appends the rewards receiver of the referrer to a `ClaimLiquidityRewards`
+/// or `ClaimAllRewards` instruction of a referred liquidity provider, before
+/// the associated token accounts and the position pages
+pub fn append_referrer_destination(
+    mut instruction: Instruction,
+    referrer_destination_pubkey: Pubkey,
+) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new(referrer_destination_pubkey, false));
+    instruction
+}
+
+/// Appends the transfer hook program of a token-swap to a `Swap`, `Deposit`
+/// or `DepositImbalanced` instruction, before the position pages.
+/// Nothing is appended for token-swaps without a hook
+pub fn append_transfer_hook(
+    mut instruction: Instruction,
+    transfer_hook_program: Pubkey,
+) -> Instruction {
+    if transfer_hook_program != Pubkey::default() {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(transfer_hook_program, false));
+    }
+    instruction
+}
+
+/// Appends the OpenBook market of a token-swap along with its bids and asks to
+/// an instruction pricing the pool, right after its fixed accounts. Only for
+/// token-swaps with a market, before any transfer hook, associated token
+/// accounts, pool mint of a quote or position pages.
+/// The market prices the pool when its oracles are stale and its twap is
+/// unavailable.
+pub fn append_openbook_market(
+    mut instruction: Instruction,
+    market_pubkey: Pubkey,
+    bids_pubkey: Pubkey,
+    asks_pubkey: Pubkey,
+) -> Instruction {
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(market_pubkey, false),
+        AccountMeta::new_readonly(bids_pubkey, false),
+        AccountMeta::new_readonly(asks_pubkey, false),
+    ]);
+    instruction
+}
+
+/// Turns a `Swap` or `Deposit` instruction into its native SOL variant,
+/// wrapping lamports of the wallet in a temporary account. The wallet must be
+/// the user transfer authority and the temporary account the user token
+/// account of the SOL side.
+pub fn wrap_native_sol(
+    mut instruction: Instruction,
+    wallet_pubkey: Pubkey,
+    native_account_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    instruction.data = match SwapInstruction::unpack(&instruction.data)? {
+        SwapInstruction::Swap(swap_data) => SwapInstruction::SwapNative(swap_data),
+        SwapInstruction::Deposit(deposit_data) => SwapInstruction::DepositNative(deposit_data),
+        _ => return Err(SwapError::InvalidInstruction.into()),
+    }
+    .pack();
+    instruction.accounts.splice(
+        0..0,
+        vec![
+            AccountMeta::new(wallet_pubkey, true),
+            AccountMeta::new(native_account_pubkey, true),
+            AccountMeta::new_readonly(spl_token::native_mint::id(), false),
+            AccountMeta::new_readonly(rent::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    Ok(instruction)
+}
+
+/// Turns a `Swap` instruction, with its transfer hook if any, into a swap
+/// submitted by a relayer, paid the relayer fee in the token sold. The user
+/// transfer authority must be the owner of the user token account sold from
+/// and sign it along with the fee payer.
+pub fn relay_swap(
+    mut instruction: Instruction,
+    fee_payer_pubkey: Pubkey,
+    relayer_fee_token_pubkey: Pubkey,
+    relayer_fee: u64,
+) -> Result<Instruction, ProgramError> {
+    instruction.data = match SwapInstruction::unpack(&instruction.data)? {
+        SwapInstruction::Swap(SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps,
+        }) => SwapInstruction::RelayedSwap(RelayedSwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps,
+            relayer_fee,
+        }),
+        _ => return Err(SwapError::InvalidInstruction.into()),
+    }
+    .pack();
+    instruction.accounts.splice(
+        0..0,
+        [
+            AccountMeta::new_readonly(fee_payer_pubkey, true),
+            AccountMeta::new(relayer_fee_token_pubkey, false),
+        ],
+    );
+    Ok(instruction)
+}
+
+/// Creates the ed25519 program instruction verifying the signature of the
+/// order by its maker, to be right before the settlement of the order.
+pub fn verify_signed_order(order: &SignedOrder, signature: &[u8; 64]) -> Instruction {
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data: ed25519_instruction_data(&order.maker, signature, &order.pack()),
+    }
+}
+
+/// Turns a `Swap` instruction, with its transfer hook if any, into the
+/// settlement of the signed order. The user transfer authority of the swap
+/// must be the order authority, it does not sign the transaction.
+pub fn settle_signed_order(
+    mut instruction: Instruction,
+    order: SignedOrder,
+    payer_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    match SwapInstruction::unpack(&instruction.data)? {
+        SwapInstruction::Swap(_) => {}
+        _ => return Err(SwapError::InvalidInstruction.into()),
+    }
+    let (order_nonce_pubkey, _) = find_order_nonce_address(&instruction.program_id, &order.maker);
+    instruction.data = SwapInstruction::SettleSignedOrder(order).pack();
+    instruction.accounts[4].is_signer = false;
+    instruction.accounts.splice(
+        0..0,
+        vec![
+            AccountMeta::new_readonly(instructions::id(), false),
+            AccountMeta::new(order_nonce_pubkey, false),
+            AccountMeta::new(payer_pubkey, true),
+            AccountMeta::new_readonly(rent::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    Ok(instruction)
+}
+
+/// Turns a `Swap` instruction, with its transfer hook if any, into the
+/// execution of the period due of a DCA plan. The user transfer authority of
+/// the swap must be the plan, it does not sign the transaction, and the
+/// period amount and price impact limit are the ones of the plan.
+pub fn execute_dca(
+    mut instruction: Instruction,
+    dca_plan_pubkey: Pubkey,
+    keeper_token_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    match SwapInstruction::unpack(&instruction.data)? {
+        SwapInstruction::Swap(_) => {}
+        _ => return Err(SwapError::InvalidInstruction.into()),
+    }
+    instruction.data = SwapInstruction::ExecuteDca.pack();
+    instruction.accounts[4].is_signer = false;
+    instruction.accounts.splice(
+        0..0,
+        vec![
+            AccountMeta::new(dca_plan_pubkey, false),
+            AccountMeta::new(keeper_token_pubkey, false),
+        ],
+    );
+    Ok(instruction)
+}
+
+/// Turns a `Swap` instruction, with its transfer hook if any, into a flash
+/// swap paid back by the callback program. The callback accounts are passed
+/// as is to the callback program.
+pub fn flash_swap(
+    mut instruction: Instruction,
+    callback_program: Pubkey,
+    callback_accounts: Vec<AccountMeta>,
+    callback_data: Vec<u8>,
+) -> Result<Instruction, ProgramError> {
+    instruction.data = match SwapInstruction::unpack(&instruction.data)? {
+        SwapInstruction::Swap(SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps,
+        }) => SwapInstruction::FlashSwap(FlashSwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps,
+            callback_data,
+        }),
+        _ => return Err(SwapError::InvalidInstruction.into()),
+    }
+    .pack();
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(callback_program, false));
+    instruction.accounts.extend(callback_accounts);
+    Ok(instruction)
+}
+
+/// Merges `Swap` instructions, with their transfer hook if any, into a single
+/// batch swap. The token-swaps of the swaps must be distinct.
+pub fn batch_swap(
+    swaps: Vec<Instruction>,
+    minimum_total_amount_out: u64,
+) -> Result<Instruction, ProgramError> {
+    let program_id = swaps
+        .first()
+        .map(|instruction| instruction.program_id)
+        .ok_or(SwapError::InvalidInput)?;
+    let mut swap_datas = Vec::with_capacity(swaps.len());
+    let mut accounts = vec![];
+    for instruction in swaps {
+        if instruction.program_id != program_id {
+            return Err(SwapError::InvalidInput.into());
+        }
+        match SwapInstruction::unpack(&instruction.data)? {
+            SwapInstruction::Swap(swap_data) => swap_datas.push(swap_data),
+            _ => return Err(SwapError::InvalidInstruction.into()),
+        }
+        accounts.extend(instruction.accounts);
+    }
+    if swap_datas.len() > u8::MAX as usize {
+        return Err(SwapError::InvalidInput.into());
+    }
+    let data = SwapInstruction::BatchSwap(BatchSwapData {
+        swaps: swap_datas,
+        minimum_total_amount_out,
+    })
+    .pack();
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Merges the accounts of `Swap` instructions on token-swaps of the same pair,
+/// with their transfer hook if any, into a split swap. There is one swap per
+/// fraction, their amounts are replaced by the split of the input.
+pub fn split_swap(
+    swaps: Vec<Instruction>,
+    split_swap_data: SplitSwapData,
+) -> Result<Instruction, ProgramError> {
+    if swaps.is_empty() || swaps.len() != split_swap_data.fractions_bps.len() {
+        return Err(SwapError::InvalidInput.into());
+    }
+    let program_id = swaps[0].program_id;
+    let mut accounts = vec![];
+    for instruction in swaps {
+        if instruction.program_id != program_id {
+            return Err(SwapError::InvalidInput.into());
+        }
+        if !matches!(
+            SwapInstruction::unpack(&instruction.data)?,
+            SwapInstruction::Swap(_)
+        ) {
+            return Err(SwapError::InvalidInstruction.into());
+        }
+        accounts.extend(instruction.accounts);
+    }
+    let data = SwapInstruction::SplitSwap(split_swap_data).pack();
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates `RefreshLiquidityObligation` instruction
+pub fn refresh_liquidity_obligation(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    config_pubkey: Pubkey,
+    deltafi_price_oracle: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::RefreshLiquidityObligation.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(config_pubkey, false),
+    ];
+    if deltafi_price_oracle != Pubkey::default() {
+        accounts.push(AccountMeta::new_readonly(deltafi_price_oracle, false));
+    }
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GrowProviderAccount` instruction
+pub fn grow_provider_account(
+    program_id: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    max_positions: u8,
+) -> Result<Instruction, ProgramError> {
+    let data =
+        SwapInstruction::GrowProviderAccount(GrowProviderAccountData { max_positions }).pack();
+
+    let accounts = vec![
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `CloseLiquidityProvider` instruction
+pub fn close_liquidity_provider(
+    program_id: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    position_page_pubkeys: Vec<Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CloseLiquidityProvider.pack();
+    let (liquidity_provider_pubkey, _) =
+        find_liquidity_provider_address(&program_id, &liquidity_owner_pubkey);
+
+    let mut accounts = vec![
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new(liquidity_owner_pubkey, true),
+    ];
+    accounts.extend(
+        position_page_pubkeys
+            .into_iter()
+            .map(|pubkey| AccountMeta::new(pubkey, false)),
+    );
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `TransferPosition` instruction
+pub fn transfer_position(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    source_owner_pubkey: Pubkey,
+    destination_owner_pubkey: Pubkey,
+    source_position_page_pubkeys: Vec<Pubkey>,
+    destination_position_page_pubkeys: Vec<Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let source_page_count =
+        u8::try_from(source_position_page_pubkeys.len()).map_err(|_| SwapError::InvalidInput)?;
+    let data = SwapInstruction::TransferPosition(TransferPositionData { source_page_count }).pack();
+    let (source_pubkey, _) = find_liquidity_provider_address(&program_id, &source_owner_pubkey);
+    let (destination_pubkey, _) =
+        find_liquidity_provider_address(&program_id, &destination_owner_pubkey);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new_readonly(source_owner_pubkey, true),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new_readonly(destination_owner_pubkey, true),
+    ];
+    accounts.extend(
+        source_position_page_pubkeys
+            .into_iter()
+            .chain(destination_position_page_pubkeys)
+            .map(|pubkey| AccountMeta::new(pubkey, false)),
+    );
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `SetLiquidityProviderDelegate` instruction
+pub fn set_liquidity_provider_delegate(
+    program_id: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    delegate_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetLiquidityProviderDelegate(SetLiquidityProviderDelegateData {
+        delegate: delegate_pubkey,
+    })
+    .pack();
+    let (liquidity_provider_pubkey, _) =
+        find_liquidity_provider_address(&program_id, &liquidity_owner_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `InitializePositionPage` instruction
+pub fn init_position_page(
+    program_id: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    page_index: u8,
+) -> Result<Instruction, ProgramError> {
+    let data =
+        SwapInstruction::InitializePositionPage(InitializePositionPageData { page_index }).pack();
+    let (liquidity_provider_pubkey, _) =
+        find_liquidity_provider_address(&program_id, &liquidity_owner_pubkey);
+    let (position_page_pubkey, _) =
+        find_position_page_address(&program_id, &liquidity_provider_pubkey, page_index);
+
+    let mut accounts = vec![
+        AccountMeta::new(position_page_pubkey, false),
+        AccountMeta::new_readonly(liquidity_provider_pubkey, false),
+        AccountMeta::new(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(previous_page_index) = page_index.checked_sub(1) {
+        let (previous_page_pubkey, _) = find_position_page_address(
+            &program_id,
+            &liquidity_provider_pubkey,
+            previous_page_index,
+        );
+        accounts.push(AccountMeta::new(previous_page_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `InitializeObservationBuffer` instruction
+pub fn init_observation_buffer(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitializeObservationBuffer.pack();
+    let (observation_buffer_pubkey, _) = find_observation_buffer_address(&program_id, &swap_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(observation_buffer_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `InitializeQuoteAccount` instruction
+pub fn init_quote_account(
+    program_id: Pubkey,
+    owner_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitializeQuoteAccount.pack();
+    let (quote_account_pubkey, _) = find_quote_account_address(&program_id, &owner_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new(quote_account_pubkey, false),
+        AccountMeta::new_readonly(owner_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `InitializeRegistryPage` instruction
+pub fn init_registry_page(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    page_index: u8,
+) -> Result<Instruction, ProgramError> {
+    let data =
+        SwapInstruction::InitializeRegistryPage(InitializeRegistryPageData { page_index }).pack();
+    let (registry_page_pubkey, _) =
+        find_registry_page_address(&program_id, &config_pubkey, page_index);
+
+    let mut accounts = vec![
+        AccountMeta::new(registry_page_pubkey, false),
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(previous_page_index) = page_index.checked_sub(1) {
+        let (previous_page_pubkey, _) =
+            find_registry_page_address(&program_id, &config_pubkey, previous_page_index);
+        accounts.push(AccountMeta::new_readonly(previous_page_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Approve a swap
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckSwapData {
+    /// Owner of the token account the tokens sold come from
+    pub trader: Pubkey,
+    /// Amount of tokens sold
+    pub amount_in: u64,
+    /// Amount of tokens bought
+    pub amount_out: u64,
+    /// Swap direction
+    pub swap_direction: SwapDirection,
+}
+
+/// Approve a deposit
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckDepositData {
+    /// Owner of the liquidity provider
+    pub trader: Pubkey,
+    /// Amount of token a deposited
+    pub token_a_amount: u64,
+    /// Amount of token b deposited
+    pub token_b_amount: u64,
+}
+
+/// Instructions sent by a token-swap to its transfer hook program before the
+/// transfers of a swap or deposit. The hook program vetoes the operation by
+/// returning an error.
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub enum TransferHookInstruction {
+    ///   Approve a swap
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[signer]` User transfer authority
+    CheckSwap(CheckSwapData),
+
+    ///   Approve a deposit
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[signer]` Liquidity provider owner
+    CheckDeposit(CheckDepositData),
+}
+
+impl TransferHookInstruction {
+    /// Unpacks a byte buffer into a [TransferHookInstruction](enum.TransferHookInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or(SwapError::InstructionUnpackError)?;
+        Ok(match tag {
+            0x0 => {
+                let (trader, rest) = unpack_pubkey(rest)?;
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (amount_out, rest) = unpack_u64(rest)?;
+                let (swap_direction, _) = unpack_swap_direction(rest)?;
+                Self::CheckSwap(CheckSwapData {
+                    trader,
+                    amount_in,
+                    amount_out,
+                    swap_direction,
+                })
+            }
+            0x1 => {
+                let (trader, rest) = unpack_pubkey(rest)?;
+                let (token_a_amount, rest) = unpack_u64(rest)?;
+                let (token_b_amount, _) = unpack_u64(rest)?;
+                Self::CheckDeposit(CheckDepositData {
+                    trader,
+                    token_a_amount,
+                    token_b_amount,
+                })
+            }
+            _ => return Err(SwapError::InvalidInstruction.into()),
+        })
+    }
+
+    /// Packs a [TransferHookInstruction](enum.TransferHookInstruction.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::CheckSwap(CheckSwapData {
+                trader,
+                amount_in,
+                amount_out,
+                swap_direction,
+            }) => {
+                buf.push(0x0);
+                buf.extend_from_slice(trader.as_ref());
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&amount_out.to_le_bytes());
+                buf.extend_from_slice(&(*swap_direction as u8).to_le_bytes());
+            }
+            Self::CheckDeposit(CheckDepositData {
+                trader,
+                token_a_amount,
+                token_b_amount,
+            }) => {
+                buf.push(0x1);
+                buf.extend_from_slice(trader.as_ref());
+                buf.extend_from_slice(&token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&token_b_amount.to_le_bytes());
+            }
+        }
+        buf
+    }
+}
+
+/// Creates a 'check_swap' instruction of a transfer hook program.
+pub fn check_swap(
+    transfer_hook_program: Pubkey,
+    swap_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    check_swap_data: CheckSwapData,
+) -> Instruction {
+    let data = TransferHookInstruction::CheckSwap(check_swap_data).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+    ];
+
+    Instruction {
+        program_id: transfer_hook_program,
+        accounts,
+        data,
+    }
+}
+
+/// Tokens owed for a flash swap
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceiveFlashSwapData {
+    /// Amount of tokens to pay back to the token-swap
+    pub amount_in: u64,
+    /// Amount of tokens received
+    pub amount_out: u64,
+    /// Swap direction
+    pub swap_direction: SwapDirection,
+    /// Callback data of the flash swap
+    pub data: Vec<u8>,
+}
+
+/// Instruction sent by a token-swap to the callback program of a flash swap,
+/// once the output of the swap is transferred.
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub enum FlashSwapCallbackInstruction {
+    ///   Pay back a flash swap
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[signer]` User transfer authority
+    ///   2. `[writable]` Swap account to pay `amount_in` back to
+    ///   3. `[writable]` DESTINATION account, holding the output
+    ///   4. `[]` Token program id
+    ///
+    ///   .. Accounts of the flash swap passed to the callback program
+    ReceiveFlashSwap(ReceiveFlashSwapData),
+}
+
+impl FlashSwapCallbackInstruction {
+    /// Unpacks a byte buffer into a [FlashSwapCallbackInstruction](enum.FlashSwapCallbackInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or(SwapError::InstructionUnpackError)?;
+        Ok(match tag {
+            0x0 => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (amount_out, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                Self::ReceiveFlashSwap(ReceiveFlashSwapData {
+                    amount_in,
+                    amount_out,
+                    swap_direction,
+                    data: rest.to_vec(),
+                })
+            }
+            _ => return Err(SwapError::InvalidInstruction.into()),
+        })
+    }
+
+    /// Packs a [FlashSwapCallbackInstruction](enum.FlashSwapCallbackInstruction.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::ReceiveFlashSwap(ReceiveFlashSwapData {
+                amount_in,
+                amount_out,
+                swap_direction,
+                data,
+            }) => {
+                buf.push(0x0);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&amount_out.to_le_bytes());
+                buf.extend_from_slice(&(*swap_direction as u8).to_le_bytes());
+                buf.extend_from_slice(data);
+            }
+        }
+        buf
+    }
+}
+
+/// Creates a 'receive_flash_swap' instruction of a flash swap callback program.
+pub fn receive_flash_swap(
+    callback_program: Pubkey,
+    swap_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    swap_in_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    token_program_id: Pubkey,
+    callback_accounts: Vec<AccountMeta>,
+    receive_flash_swap_data: ReceiveFlashSwapData,
+) -> Instruction {
+    let data = FlashSwapCallbackInstruction::ReceiveFlashSwap(receive_flash_swap_data).pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(swap_in_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new_readonly(token_program_id, false),
+    ];
+    accounts.extend(callback_accounts);
+
+    Instruction {
+        program_id: callback_program,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a 'check_deposit' instruction of a transfer hook program.
+pub fn check_deposit(
+    transfer_hook_program: Pubkey,
+    swap_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    check_deposit_data: CheckDepositData,
+) -> Instruction {
+    let data = TransferHookInstruction::CheckDeposit(check_deposit_data).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+    ];
+
+    Instruction {
+        program_id: transfer_hook_program,
+        accounts,
+        data,
+    }
+}
+
+fn unpack_u128(input: &[u8]) -> Result<(u128, &[u8]), ProgramError> {
+    if input.len() < 16 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (amount, rest) = input.split_at(16);
+    let amount = amount
+        .get(..16)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u128::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((amount, rest))
+}
+
+fn unpack_i64(input: &[u8]) -> Result<(i64, &[u8]), ProgramError> {
+    if input.len() < 8 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (amount, rest) = input.split_at(8);
+    let amount = amount
+        .get(..8)
+        .and_then(|slice| slice.try_into().ok())
+        .map(i64::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((amount, rest))
+}
+
+fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+    if input.len() < 8 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (amount, rest) = input.split_at(8);
+    let amount = amount
+        .get(..8)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((amount, rest))
+}
+
+fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+    if input.len() < 2 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (value, rest) = input.split_at(2);
+    let value = value
+        .get(..2)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u16::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((value, rest))
+}
+
+/// Unpacks a trailing u64 left out by older clients, 0 when absent
+fn unpack_optional_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+    if input.is_empty() {
+        return Ok((0, input));
+    }
+    unpack_u64(input)
+}
+
+fn unpack_u8(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
+    if input.is_empty() {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (bytes, rest) = input.split_at(1);
+    let value = bytes
+        .get(..1)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u8::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((value, rest))
+}
+
+fn unpack_bool(input: &[u8]) -> Result<(bool, &[u8]), ProgramError> {
+    let (value, rest) = unpack_u8(input)?;
+    let value = match u8::from_le(value) {
+        0 => false,
+        1 => true,
+        _ => return Err(SwapError::InstructionUnpackError.into()),
+    };
+    Ok((value, rest))
+}
+
+fn unpack_swap_direction(input: &[u8]) -> Result<(SwapDirection, &[u8]), ProgramError> {
+    let (value, rest) = unpack_u8(input)?;
+    let value = match u8::from_le(value) {
+        0 => SwapDirection::SellBase,
+        1 => SwapDirection::SellQuote,
+        _ => return Err(SwapError::InstructionUnpackError.into()),
+    };
+    Ok((value, rest))
+}
+
+#[allow(dead_code)]
+fn unpack_bytes32(input: &[u8]) -> Result<(&[u8; 32], &[u8]), ProgramError> {
+    if input.len() < 32 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (bytes, rest) = input.split_at(32);
+    Ok((
+        bytes
+            .try_into()
+            .map_err(|_| SwapError::InstructionUnpackError)?,
+        rest,
+    ))
+}
+
+#[allow(dead_code)]
+fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+    if input.len() < PUBKEY_BYTES {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (key, rest) = input.split_at(PUBKEY_BYTES);
+    let pk = Pubkey::new(key);
+    Ok((pk, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        curve::{default_market_price, default_slope, DynamicSlope},
+        math::Decimal,
+        state::{
+            check_ed25519_instruction_data, find_order_authority_address, FeeDiscountTier,
+            DEFAULT_TEST_FEES, DEFAULT_TEST_REWARDS,
+        },
+    };
+
+    #[test]
+    fn test_pack_admin_init_config() {
+        let fees = DEFAULT_TEST_FEES;
+        let rewards = DEFAULT_TEST_REWARDS;
+        let max_mid_price_deviation_bps: u64 = 500;
+        let min_mid_price_update_interval: i64 = 3600;
+        let check = AdminInstruction::Initialize(AdminInitializeData {
+            fees: fees.clone(),
+            rewards: rewards.clone(),
+            max_mid_price_deviation_bps,
+            min_mid_price_update_interval,
+        });
+        let packed = check.pack();
+        let mut expect = vec![100];
+        expect.extend_from_slice(&fees.admin_trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_withdraw_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_withdraw_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.withdraw_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.withdraw_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_denominator.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_cap.to_le_bytes());
+        expect.extend_from_slice(&rewards.liquidity_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.liquidity_reward_denominator.to_le_bytes());
+        expect.extend_from_slice(&max_mid_price_deviation_bps.to_le_bytes());
+        expect.extend_from_slice(&min_mid_price_update_interval.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_new_fees() {
+        let fees = DEFAULT_TEST_FEES;
+        let check = AdminInstruction::SetNewFees(fees.clone());
+        let packed = check.pack();
+        let mut expect = vec![105];
+        expect.extend_from_slice(&fees.admin_trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_withdraw_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_withdraw_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.withdraw_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.withdraw_fee_denominator.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_new_rewards() {
+        let rewards = DEFAULT_TEST_REWARDS;
+        let check = AdminInstruction::SetNewRewards(rewards.clone());
+        let packed = check.pack();
+        let mut expect = vec![106];
+        expect.extend_from_slice(&rewards.trade_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_denominator.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_cap.to_le_bytes());
+        expect.extend_from_slice(&rewards.liquidity_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.liquidity_reward_denominator.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_mid_price() {
+        let mid_price = default_market_price().to_scaled_val().unwrap();
+        let check = AdminInstruction::SetMidPrice(SetMidPrice { mid_price });
+        let packed = check.pack();
+        let mut expect = vec![107];
+        expect.extend_from_slice(&mid_price.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_migrate_state() {
+        let check = AdminInstruction::MigrateState;
+        let packed = check.pack();
+        let expect = vec![108];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_max_price_impact() {
+        let max_price_impact_bps: u64 = 500;
+        let check = AdminInstruction::SetMaxPriceImpact(SetMaxPriceImpact {
+            max_price_impact_bps,
+        });
+        let packed = check.pack();
+        let mut expect = vec![109];
+        expect.extend_from_slice(&max_price_impact_bps.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_max_trade_size() {
+        let max_amount_in: u64 = 1_000_000;
+        let max_amount_in_reserve_bps: u64 = 1_000;
+        let check = AdminInstruction::SetMaxTradeSize(SetMaxTradeSize {
+            max_amount_in,
+            max_amount_in_reserve_bps,
+        });
+        let packed = check.pack();
+        let mut expect = vec![110];
+        expect.extend_from_slice(&max_amount_in.to_le_bytes());
+        expect.extend_from_slice(&max_amount_in_reserve_bps.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_buyback_and_burn() {
+        let amount_in: u64 = 1_000_000;
+        let minimum_amount_out: u64 = 500_000;
+        let check = AdminInstruction::BuybackAndBurn(BuybackAndBurn {
+            amount_in,
+            minimum_amount_out,
+        });
+        let packed = check.pack();
+        let mut expect = vec![120];
+        expect.extend_from_slice(&amount_in.to_le_bytes());
+        expect.extend_from_slice(&minimum_amount_out.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_slot_throttle() {
+        let max_swaps_per_slot: u64 = 4;
+        let max_volume_per_slot: u64 = 10_000_000;
+        let check = AdminInstruction::SetSlotThrottle(SetSlotThrottle {
+            max_swaps_per_slot,
+            max_volume_per_slot,
+        });
+        let packed = check.pack();
+        let mut expect = vec![111];
+        expect.extend_from_slice(&max_swaps_per_slot.to_le_bytes());
+        expect.extend_from_slice(&max_volume_per_slot.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_whitelist() {
+        let check = AdminInstruction::InitializeWhitelist;
+        let packed = check.pack();
+        let expect = vec![113];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let trader = Pubkey::new_unique();
+        let check = AdminInstruction::SetWhitelistTrader(SetWhitelistTrader {
+            trader,
+            is_allowed: true,
+        });
+        let packed = check.pack();
+        let mut expect = vec![114];
+        expect.extend_from_slice(trader.as_ref());
+        expect.push(1);
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = AdminInstruction::SetWhitelistOnly(SetWhitelistOnly {
+            is_whitelist_only: false,
+        });
+        let packed = check.pack();
+        let expect = vec![115, 0];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_transfer_hook() {
+        let transfer_hook_program = Pubkey::new_unique();
+        let check = AdminInstruction::SetTransferHook(SetTransferHook {
+            transfer_hook_program,
+        });
+        let packed = check.pack();
+        let mut expect = vec![116];
+        expect.extend_from_slice(transfer_hook_program.as_ref());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_deltafi_price_oracle() {
+        let deltafi_price_oracle = Pubkey::new_unique();
+        let check = AdminInstruction::SetDeltafiPriceOracle(SetDeltafiPriceOracle {
+            deltafi_price_oracle,
+        });
+        let packed = check.pack();
+        let mut expect = vec![117];
+        expect.extend_from_slice(deltafi_price_oracle.as_ref());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_epoch_reward_budget() {
+        let epoch_reward_budget = 1_000_000_000u64;
+        let check = AdminInstruction::SetEpochRewardBudget(SetEpochRewardBudget {
+            epoch_reward_budget,
+        });
+        let packed = check.pack();
+        let mut expect = vec![118];
+        expect.extend_from_slice(&epoch_reward_budget.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_poke_reward() {
+        let poke_reward = 1_000u64;
+        let min_poke_interval = 600i64;
+        let check = AdminInstruction::SetPokeReward(SetPokeReward {
+            poke_reward,
+            min_poke_interval,
+        });
+        let packed = check.pack();
+        let mut expect = vec![121];
+        expect.extend_from_slice(&poke_reward.to_le_bytes());
+        expect.extend_from_slice(&min_poke_interval.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_proposal_quorum() {
+        let proposal_quorum = 10_000_000u64;
+        let check = AdminInstruction::SetProposalQuorum(SetProposalQuorum { proposal_quorum });
+        let packed = check.pack();
+        let mut expect = vec![122];
+        expect.extend_from_slice(&proposal_quorum.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_deny_list() {
+        let check = AdminInstruction::InitializeDenyList;
+        let packed = check.pack();
+        let expect = vec![132];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let mint = Pubkey::new_unique();
+        let check = AdminInstruction::SetDeniedMint(SetDeniedMint {
+            mint,
+            is_denied: true,
+        });
+        let packed = check.pack();
+        let mut expect = vec![133];
+        expect.extend_from_slice(mint.as_ref());
+        expect.push(1);
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_recalibrate() {
+        let check = AdminInstruction::Recalibrate;
+        let packed = check.pack();
+        let expect = vec![134];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_set_openbook_market() {
+        let openbook_market = Pubkey::new_unique();
+        let check = AdminInstruction::SetOpenbookMarket(SetOpenbookMarket { openbook_market });
+        let packed = check.pack();
+        let mut expect = vec![135];
+        expect.extend_from_slice(openbook_market.as_ref());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_set_curve_type() {
+        let max_slope = Decimal::from_percent(90);
+        let curve_type = CurveType::DynamicSlope(DynamicSlope::new(max_slope, 2).unwrap());
+        let check = AdminInstruction::SetCurveType(curve_type);
+        let packed = check.pack();
+        let mut expect = vec![136, 1];
+        expect.extend_from_slice(&max_slope.to_scaled_val().unwrap().to_le_bytes());
+        expect.extend_from_slice(&2u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+
+        let check = AdminInstruction::SetCurveType(CurveType::Standard);
+        let mut expect = vec![136];
+        expect.extend_from_slice(&[0u8; CurveType::LEN]);
+        assert_eq!(check.pack(), expect);
+        assert_eq!(AdminInstruction::unpack(&expect).unwrap(), check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_mid_price_bounds() {
+        let max_mid_price_deviation_bps = 500u64;
+        let min_mid_price_update_interval = 3600i64;
+        let check = AdminInstruction::SetMidPriceBounds(SetMidPriceBounds {
+            max_mid_price_deviation_bps,
+            min_mid_price_update_interval,
+        });
+        let packed = check.pack();
+        let mut expect = vec![137];
+        expect.extend_from_slice(&max_mid_price_deviation_bps.to_le_bytes());
+        expect.extend_from_slice(&min_mid_price_update_interval.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_set_referral_reward() {
+        let referral_reward_bps = 1_000u64;
+        let check = AdminInstruction::SetReferralReward(SetReferralReward {
+            referral_reward_bps,
+        });
+        let packed = check.pack();
+        let mut expect = vec![131];
+        expect.extend_from_slice(&referral_reward_bps.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_snapshot() {
+        let snapshot_id = 7u64;
+        let merkle_root = [9u8; 32];
+        let check = AdminInstruction::Snapshot(Snapshot {
+            snapshot_id,
+            merkle_root,
+        });
+        let packed = check.pack();
+        let mut expect = vec![123];
+        expect.extend_from_slice(&snapshot_id.to_le_bytes());
+        expect.extend_from_slice(&merkle_root);
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert_eq!(
+            AdminInstruction::unpack(&expect[..40]).unwrap_err(),
+            SwapError::InstructionUnpackError.into()
+        );
+    }
+
+    #[test]
+    fn test_pack_admin_set_reserve_caps() {
+        let max_base_reserve: u64 = 100_000_000_000;
+        let max_quote_reserve: u64 = 2_000_000_000_000;
+        let check = AdminInstruction::SetReserveCaps(SetReserveCaps {
+            max_base_reserve,
+            max_quote_reserve,
+        });
+        let packed = check.pack();
+        let mut expect = vec![124];
+        expect.extend_from_slice(&max_base_reserve.to_le_bytes());
+        expect.extend_from_slice(&max_quote_reserve.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_set_user_liquidity_cap() {
+        let max_user_liquidity: u64 = 5_000_000_000;
+        let check =
+            AdminInstruction::SetUserLiquidityCap(SetUserLiquidityCap { max_user_liquidity });
+        let packed = check.pack();
+        let mut expect = vec![125];
+        expect.extend_from_slice(&max_user_liquidity.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_set_min_trade_fee() {
+        let min_trade_fee: u64 = 1_000;
+        let check = AdminInstruction::SetMinTradeFee(SetMinTradeFee { min_trade_fee });
+        let packed = check.pack();
+        let mut expect = vec![129];
+        expect.extend_from_slice(&min_trade_fee.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_make_immutable() {
+        let check = AdminInstruction::MakeImmutable;
+        let packed = check.pack();
+        let expect = vec![126];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_set_fee_discount() {
+        let fee_discount = FeeDiscount {
+            tiers: [
+                FeeDiscountTier {
+                    min_balance: 1_000,
+                    discount_bps: 1_000,
+                },
+                FeeDiscountTier {
+                    min_balance: 10_000,
+                    discount_bps: 2_500,
+                },
+                FeeDiscountTier::default(),
+            ],
+        };
+        let check = AdminInstruction::SetFeeDiscount(fee_discount);
+        let packed = check.pack();
+        let mut expect = vec![127];
+        for tier in fee_discount.tiers.iter() {
+            expect.extend_from_slice(&tier.min_balance.to_le_bytes());
+            expect.extend_from_slice(&tier.discount_bps.to_le_bytes());
+        }
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_set_directional_fees() {
+        let directional_fees = DirectionalFees {
+            sell_base_fee_numerator: 1,
+            sell_base_fee_denominator: 1_000,
+            sell_quote_fee_numerator: 5,
+            sell_quote_fee_denominator: 1_000,
+        };
+        let check = AdminInstruction::SetDirectionalFees(directional_fees);
+        let packed = check.pack();
+        let mut expect = vec![128];
+        expect.extend_from_slice(&directional_fees.sell_base_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&directional_fees.sell_base_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&directional_fees.sell_quote_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&directional_fees.sell_quote_fee_denominator.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+    }
+
+    #[test]
+    fn test_pack_admin_set_halving_schedule() {
+        let halving_schedule = HalvingSchedule {
+            start_ts: 1_650_000_000,
+            period_secs: 31_536_000,
+            halving_count: 4,
+        };
+        let check = AdminInstruction::SetHalvingSchedule(halving_schedule);
+        let packed = check.pack();
+        let mut expect = vec![119];
+        expect.extend_from_slice(&halving_schedule.start_ts.to_le_bytes());
+        expect.extend_from_slice(&halving_schedule.period_secs.to_le_bytes());
+        expect.push(halving_schedule.halving_count);
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_transfer_hook() {
+        let trader = Pubkey::new_unique();
+        let check = TransferHookInstruction::CheckSwap(CheckSwapData {
+            trader,
+            amount_in: 1_000_000,
+            amount_out: 500_000,
+            swap_direction: SwapDirection::SellQuote,
+        });
+        let packed = check.pack();
+        let mut expect = vec![0];
+        expect.extend_from_slice(trader.as_ref());
+        expect.extend_from_slice(&1_000_000u64.to_le_bytes());
+        expect.extend_from_slice(&500_000u64.to_le_bytes());
+        expect.push(SwapDirection::SellQuote as u8);
+        assert_eq!(packed, expect);
+        let unpacked = TransferHookInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TransferHookInstruction::CheckDeposit(CheckDepositData {
+            trader,
+            token_a_amount: 1_000_000,
+            token_b_amount: 500_000,
+        });
+        let packed = check.pack();
+        let mut expect = vec![1];
+        expect.extend_from_slice(trader.as_ref());
+        expect.extend_from_slice(&1_000_000u64.to_le_bytes());
+        expect.extend_from_slice(&500_000u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TransferHookInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(TransferHookInstruction::unpack(&[2]).is_err());
+    }
+
+    #[test]
+    fn test_pack_admin_set_fee_compounding() {
+        let check = AdminInstruction::SetFeeCompounding(SetFeeCompounding {
+            is_fee_compounding: true,
+        });
+        let packed = check.pack();
+        let expect = vec![112, 1];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(AdminInstruction::unpack(&[112, 2]).is_err());
+    }
+
+    #[test]
+    fn test_pack_admin_set_fee_in_quote() {
+        let check = AdminInstruction::SetFeeInQuote(SetFeeInQuote {
+            is_fee_in_quote: true,
+        });
+        let packed = check.pack();
+        let expect = vec![130, 1];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Admin)
+        ));
+        assert!(AdminInstruction::unpack(&[130, 2]).is_err());
+    }
+
+    #[test]
+    fn test_pack_swap_initialization() {
+        let nonce: u8 = 255;
+        let slope: u64 = default_slope().to_scaled_val().unwrap().try_into().unwrap();
+        let mid_price = default_market_price().to_scaled_val().unwrap();
+        let is_open_twap = true;
+        let twap_window_secs: u64 = 3_600;
+        let check = SwapInstruction::Initialize(InitializeData {
+            nonce,
+            slope,
+            mid_price,
+            is_open_twap,
+            twap_window_secs,
+            pair_symbol: None,
+        });
+        let packed = check.pack();
+        let mut expect = vec![0];
+        expect.extend_from_slice(&nonce.to_le_bytes());
+        expect.extend_from_slice(&slope.to_le_bytes());
+        expect.extend_from_slice(&mid_price.to_le_bytes());
+        expect.extend_from_slice(&(is_open_twap as u8).to_le_bytes());
+        expect.extend_from_slice(&twap_window_secs.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        // with the pair symbol naming the pool mint
+        let check = SwapInstruction::Initialize(InitializeData {
+            nonce,
+            slope,
+            mid_price,
+            is_open_twap,
+            twap_window_secs,
+            pair_symbol: Some("SOL-USDC".to_string()),
+        });
+        let packed = check.pack();
+        expect.push(8);
+        expect.extend_from_slice(b"SOL-USDC");
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert_eq!(
+            SwapInstruction::unpack(&expect[..expect.len() - 1]).unwrap_err(),
+            SwapError::InstructionUnpackError.into()
+        );
+    }
+
+    #[test]
+    fn test_pack_swap() {
+        let amount_in: u64 = 1_000_000;
+        let minimum_amount_out: u64 = 500_000;
+        let swap_direction: SwapDirection = SwapDirection::SellBase;
+        let check = SwapInstruction::Swap(SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps: 0,
+        });
+        let packed = check.pack();
+        let mut expect = vec![1];
+        expect.extend_from_slice(&amount_in.to_le_bytes());
+        expect.extend_from_slice(&minimum_amount_out.to_le_bytes());
+        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        // the price impact limit follows when set
+        let check = SwapInstruction::Swap(SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps: 100,
+        });
+        expect.extend_from_slice(&100u64.to_le_bytes());
+        assert_eq!(check.pack(), expect);
+        assert_eq!(SwapInstruction::unpack(&expect).unwrap(), check);
+        assert!(SwapInstruction::unpack(&expect[..expect.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_round_trip_every_swap_instruction() {
+        let swap_data = SwapData {
+            amount_in: 1_000,
+            minimum_amount_out: 900,
+            swap_direction: SwapDirection::SellQuote,
+            max_price_impact_bps: 50,
+        };
+        let deposit_data = DepositData {
+            token_a_amount: 1_000,
+            token_b_amount: 2_000,
+            min_mint_amount: 3_000,
+        };
+        let quote_swap_data = QuoteSwapData {
+            amount_in: 1_000,
+            swap_direction: SwapDirection::SellBase,
+        };
+        let instructions = vec![
+            SwapInstruction::Initialize(InitializeData {
+                nonce: 255,
+                slope: 500_000_000,
+                mid_price: 100_000_000_000,
+                is_open_twap: true,
+                twap_window_secs: 3_600,
+                pair_symbol: Some("SOL-USDC".to_string()),
+            }),
+            SwapInstruction::Initialize(InitializeData {
+                nonce: 255,
+                slope: 500_000_000,
+                mid_price: 100_000_000_000,
+                is_open_twap: false,
+                twap_window_secs: 0,
+                pair_symbol: None,
+            }),
+            SwapInstruction::Swap(swap_data.clone()),
+            SwapInstruction::Deposit(deposit_data.clone()),
+            SwapInstruction::Withdraw(WithdrawData {
+                pool_token_amount: 1_000,
+                minimum_token_a_amount: 10,
+                minimum_token_b_amount: 20,
+            }),
+            SwapInstruction::InitializeLiquidityProvider,
+            SwapInstruction::ClaimLiquidityRewards,
+            SwapInstruction::RefreshLiquidityObligation,
+            SwapInstruction::GrowProviderAccount(GrowProviderAccountData { max_positions: 8 }),
+            SwapInstruction::CloseLiquidityProvider,
+            SwapInstruction::InitializePositionPage(InitializePositionPageData { page_index: 2 }),
+            SwapInstruction::DepositImbalanced(deposit_data.clone()),
+            SwapInstruction::WithdrawExact(WithdrawExactData {
+                token_a_amount: 10,
+                token_b_amount: 20,
+                max_pool_token_amount: 1_000,
+            }),
+            SwapInstruction::InitializeObservationBuffer,
+            SwapInstruction::InitializeRegistryPage(InitializeRegistryPageData { page_index: 3 }),
+            SwapInstruction::QuoteSwap(quote_swap_data.clone()),
+            SwapInstruction::InitializeQuoteAccount,
+            SwapInstruction::CalcReceiveAmount(quote_swap_data),
+            SwapInstruction::SwapNative(swap_data.clone()),
+            SwapInstruction::DepositNative(deposit_data),
+            SwapInstruction::TransferPosition(TransferPositionData {
+                source_page_count: 2,
+            }),
+            SwapInstruction::SetLiquidityProviderDelegate(SetLiquidityProviderDelegateData {
+                delegate: Pubkey::new_unique(),
+            }),
+            SwapInstruction::ClaimAllRewards(ClaimAllRewardsData { swap_count: 4 }),
+            SwapInstruction::Poke,
+            SwapInstruction::InitializeDelfiLock,
+            SwapInstruction::LockDelfi(LockDelfiData {
+                amount: 1_000,
+                unlock_ts: 1_700_000_000,
+            }),
+            SwapInstruction::UnlockDelfi,
+            SwapInstruction::VoteGauge(VoteGaugeData {
+                weights_bps: vec![6_000, 4_000],
+            }),
+            SwapInstruction::CreateProposal(CreateProposalData {
+                fees: Fees::default(),
+                rewards: Rewards::default(),
+            }),
+            SwapInstruction::VoteProposal(VoteProposalData { approve: true }),
+            SwapInstruction::ExecuteProposal,
+            SwapInstruction::FlashSwap(FlashSwapData {
+                amount_in: 1_000,
+                minimum_amount_out: 900,
+                swap_direction: SwapDirection::SellBase,
+                max_price_impact_bps: 0,
+                callback_data: vec![],
+            }),
+            SwapInstruction::BatchSwap(BatchSwapData {
+                swaps: vec![swap_data.clone(), swap_data],
+                minimum_total_amount_out: 1_800,
+            }),
+            SwapInstruction::SplitSwap(SplitSwapData {
+                amount_in: 1_000,
+                minimum_amount_out: 900,
+                swap_direction: SwapDirection::SellQuote,
+                fractions_bps: vec![5_000, 5_000],
+            }),
+            SwapInstruction::EmergencyWithdraw(EmergencyWithdrawData {
+                minimum_token_a_amount: 10,
+                minimum_token_b_amount: 20,
+            }),
+            SwapInstruction::Sync,
+            SwapInstruction::Skim,
+            SwapInstruction::GetVersion,
+            SwapInstruction::GetVirtualPrice,
+            SwapInstruction::InitializePriceFeed,
+            SwapInstruction::RelayedSwap(RelayedSwapData {
+                amount_in: 1_000,
+                minimum_amount_out: 900,
+                swap_direction: SwapDirection::SellQuote,
+                max_price_impact_bps: 100,
+                relayer_fee: 10,
+            }),
+            SwapInstruction::SettleSignedOrder(SignedOrder {
+                program_id: Pubkey::new_unique(),
+                config: Pubkey::new_unique(),
+                maker: Pubkey::new_unique(),
+                swap: Pubkey::new_unique(),
+                amount_in: 1_000,
+                minimum_amount_out: 900,
+                swap_direction: SwapDirection::SellBase,
+                expiry: 1_700_000_000,
+                nonce: 7,
+            }),
+            SwapInstruction::CancelSignedOrders(CancelSignedOrdersData { next_nonce: 8 }),
+            SwapInstruction::CreateDcaPlan(CreateDcaPlanData {
+                amount: 100_000,
+                amount_per_period: 10_000,
+                interval: 86_400,
+                swap_direction: SwapDirection::SellQuote,
+                max_price_impact_bps: 50,
+                keeper_reward_bps: 10,
+            }),
+            SwapInstruction::ExecuteDca,
+            SwapInstruction::CancelDca,
+        ];
+
+        for instruction in instructions {
+            let mut packed = instruction.pack();
+            assert_eq!(SwapInstruction::unpack(&packed).unwrap(), instruction);
+
+            if packed.len() > 1 {
+                assert!(SwapInstruction::unpack(&packed[..packed.len() - 1]).is_err());
+            }
+
+            // trailing bytes are read as the callback data of flash swaps or an
+            // optional field left out, rejected otherwise
+            packed.push(0);
+            match instruction {
+                SwapInstruction::FlashSwap(_)
+                | SwapInstruction::Initialize(InitializeData {
+                    pair_symbol: None, ..
+                }) => {
+                    assert_ne!(SwapInstruction::unpack(&packed).unwrap(), instruction)
+                }
+                _ => assert_eq!(
+                    SwapInstruction::unpack(&packed).unwrap_err(),
+                    SwapError::InvalidInstructionData.into()
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_pack_get_version() {
+        let check = SwapInstruction::GetVersion;
+        let expect = vec![0x24];
+        assert_eq!(check.pack(), expect);
+        assert_eq!(SwapInstruction::unpack(&expect).unwrap(), check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+
+        let build = ProgramBuild::current();
+        assert_eq!(build.version, PROGRAM_VERSION);
+        assert_eq!(
+            build.features & FEATURE_ANCHOR_CPI != 0,
+            cfg!(feature = "anchor-cpi")
+        );
+        assert_eq!(ProgramBuild::unpack(&build.pack()).unwrap(), build);
+        assert_eq!(
+            ProgramBuild::unpack(&[PROGRAM_VERSION]).unwrap_err(),
+            SwapError::InstructionUnpackError.into()
+        );
+    }
+
+    #[test]
+    fn test_pack_quote_swap() {
+        let amount_in: u64 = 1_000_000;
+        let swap_direction: SwapDirection = SwapDirection::SellQuote;
+        let check = SwapInstruction::QuoteSwap(QuoteSwapData {
+            amount_in,
+            swap_direction,
+        });
+        let packed = check.pack();
+        let mut expect = vec![0xE];
+        expect.extend_from_slice(&amount_in.to_le_bytes());
+        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
+
+    #[test]
+    fn test_pack_calc_receive_amount() {
+        let amount_in: u64 = 1_000_000;
+        let swap_direction: SwapDirection = SwapDirection::SellBase;
+        let check = SwapInstruction::CalcReceiveAmount(QuoteSwapData {
+            amount_in,
+            swap_direction,
+        });
+        let packed = check.pack();
+        let mut expect = vec![0x10];
+        expect.extend_from_slice(&amount_in.to_le_bytes());
+        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
+
+    #[test]
+    fn test_pack_init_quote_account() {
+        let check = SwapInstruction::InitializeQuoteAccount;
+        let packed = check.pack();
+        let expect = vec![0xF];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_swap_native() {
+        let amount_in: u64 = 1_000_000;
+        let minimum_amount_out: u64 = 500_000;
+        let swap_direction: SwapDirection = SwapDirection::SellQuote;
+        let check = SwapInstruction::SwapNative(SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps: 0,
+        });
+        let packed = check.pack();
+        let mut expect = vec![0x11];
+        expect.extend_from_slice(&amount_in.to_le_bytes());
+        expect.extend_from_slice(&minimum_amount_out.to_le_bytes());
+        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
+
+    #[test]
+    fn test_pack_deposit_native() {
+        let token_a_amount: u64 = 1_000_000;
+        let token_b_amount: u64 = 500_000;
+        let min_mint_amount: u64 = 500_000;
+        let check = SwapInstruction::DepositNative(DepositData {
+            token_a_amount,
+            token_b_amount,
+            min_mint_amount,
+        });
+        let packed = check.pack();
+        let mut expect = vec![0x12];
+        expect.extend_from_slice(&token_a_amount.to_le_bytes());
+        expect.extend_from_slice(&token_b_amount.to_le_bytes());
+        expect.extend_from_slice(&min_mint_amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
+
+    #[test]
+    fn test_wrap_native_sol() {
+        let program_id = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let native_account = Pubkey::new_unique();
+        let swap_data = SwapData {
+            amount_in: 1_000_000,
+            minimum_amount_out: 500_000,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        };
+        let instruction = swap(
+            program_id,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            wallet,
+            native_account,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            swap_data.clone(),
+        )
+        .unwrap();
+
+        let native = wrap_native_sol(instruction.clone(), wallet, native_account).unwrap();
+        assert_eq!(native.program_id, program_id);
+        assert_eq!(native.data, SwapInstruction::SwapNative(swap_data).pack());
+        assert_eq!(native.accounts.len(), instruction.accounts.len() + 5);
+        assert_eq!(native.accounts[0], AccountMeta::new(wallet, true));
+        assert_eq!(native.accounts[1], AccountMeta::new(native_account, true));
+        assert_eq!(native.accounts[2].pubkey, spl_token::native_mint::id());
+        assert_eq!(native.accounts[5..], instruction.accounts[..]);
+
+        assert_eq!(
+            wrap_native_sol(native, wallet, native_account).err(),
+            Some(SwapError::InvalidInstruction.into())
+        );
+    }
+
+    #[test]
+    fn test_relay_swap() {
+        let program_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let fee_payer = Pubkey::new_unique();
+        let relayer_fee_token = Pubkey::new_unique();
+        let swap_data = SwapData {
+            amount_in: 1_000_000,
+            minimum_amount_out: 500_000,
+            swap_direction: SwapDirection::SellQuote,
+            max_price_impact_bps: 50,
+        };
+        let instruction = swap(
+            program_id,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            owner,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            swap_data.clone(),
+        )
+        .unwrap();
+
+        let relayed = relay_swap(instruction.clone(), fee_payer, relayer_fee_token, 1_000).unwrap();
+        assert_eq!(relayed.program_id, program_id);
+        assert_eq!(
+            relayed.data,
+            SwapInstruction::RelayedSwap(RelayedSwapData {
+                amount_in: swap_data.amount_in,
+                minimum_amount_out: swap_data.minimum_amount_out,
+                swap_direction: swap_data.swap_direction,
+                max_price_impact_bps: swap_data.max_price_impact_bps,
+                relayer_fee: 1_000,
+            })
+            .pack()
+        );
+        assert_eq!(relayed.data[0], 0x27);
+        assert_eq!(
+            relayed.accounts[0],
+            AccountMeta::new_readonly(fee_payer, true)
+        );
+        assert_eq!(
+            relayed.accounts[1],
+            AccountMeta::new(relayer_fee_token, false)
+        );
+        assert_eq!(relayed.accounts[2..], instruction.accounts[..]);
+        assert_eq!(relayed.accounts[6], AccountMeta::new_readonly(owner, true));
+        assert!(matches!(
+            InstructionType::check(&relayed.data),
+            Some(InstructionType::Swap)
+        ));
+
+        assert_eq!(
+            relay_swap(relayed, fee_payer, relayer_fee_token, 1_000).err(),
+            Some(SwapError::InvalidInstruction.into())
+        );
+    }
+
+    #[test]
+    fn test_settle_signed_order() {
+        let program_id = Pubkey::new_unique();
+        let config_pubkey = Pubkey::new_unique();
+        let swap_pubkey = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let (order_authority, _) = find_order_authority_address(&program_id);
+        let order = SignedOrder {
+            program_id,
+            config: config_pubkey,
+            maker: Pubkey::new_unique(),
+            swap: swap_pubkey,
+            amount_in: 1_000_000,
+            minimum_amount_out: 500_000,
+            swap_direction: SwapDirection::SellBase,
+            expiry: 1_700_000_000,
+            nonce: 3,
+        };
+        let instruction = swap(
+            program_id,
+            config_pubkey,
+            swap_pubkey,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            order_authority,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            SwapData {
+                amount_in: order.amount_in,
+                minimum_amount_out: order.minimum_amount_out,
+                swap_direction: order.swap_direction,
+                max_price_impact_bps: 0,
+            },
+        )
+        .unwrap();
+
+        let settle = settle_signed_order(instruction.clone(), order.clone(), payer).unwrap();
+        assert_eq!(settle.program_id, program_id);
+        assert_eq!(
+            settle.data,
+            SwapInstruction::SettleSignedOrder(order.clone()).pack()
+        );
+        assert_eq!(settle.accounts.len(), instruction.accounts.len() + 5);
+        assert_eq!(settle.accounts[0].pubkey, instructions::id());
+        assert_eq!(
+            settle.accounts[1],
+            AccountMeta::new(find_order_nonce_address(&program_id, &order.maker).0, false)
+        );
+        assert_eq!(settle.accounts[2], AccountMeta::new(payer, true));
+        // the order authority does not sign
+        assert_eq!(
+            settle.accounts[9],
+            AccountMeta::new_readonly(order_authority, false)
+        );
+        assert_eq!(settle.accounts[10..], instruction.accounts[5..]);
+        assert!(matches!(
+            InstructionType::check(&settle.data),
+            Some(InstructionType::Swap)
+        ));
+        assert_eq!(
+            settle_signed_order(settle, order.clone(), payer).err(),
+            Some(SwapError::InvalidInstruction.into())
+        );
+
+        let verify = verify_signed_order(&order, &[1u8; 64]);
+        assert_eq!(verify.program_id, ed25519_program::id());
+        assert!(verify.accounts.is_empty());
+        assert_eq!(
+            check_ed25519_instruction_data(&verify.data, &order.maker, &order.pack()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_cancel_signed_orders() {
+        let program_id = Pubkey::new_unique();
+        let maker = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let instruction = cancel_signed_orders(program_id, maker, payer, 12).unwrap();
+
+        let mut expect = vec![0x29];
+        expect.extend_from_slice(&12u64.to_le_bytes());
+        assert_eq!(instruction.data, expect);
+        assert_eq!(
+            SwapInstruction::unpack(&expect).unwrap(),
+            SwapInstruction::CancelSignedOrders(CancelSignedOrdersData { next_nonce: 12 })
+        );
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+        assert_eq!(
+            instruction.accounts[0],
+            AccountMeta::new(find_order_nonce_address(&program_id, &maker).0, false)
+        );
+        assert_eq!(
+            instruction.accounts[1],
+            AccountMeta::new_readonly(maker, true)
+        );
+        assert_eq!(instruction.accounts[2], AccountMeta::new(payer, true));
+    }
+
+    #[test]
+    fn test_create_dca_plan() {
+        let program_id = Pubkey::new_unique();
+        let swap_pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let plan_data = CreateDcaPlanData {
+            amount: 100_000,
+            amount_per_period: 10_000,
+            interval: 86_400,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 50,
+            keeper_reward_bps: 10,
+        };
+        let instruction = create_dca_plan(
+            program_id,
+            swap_pubkey,
+            owner,
+            source,
+            mint,
+            payer,
+            plan_data.clone(),
+        )
+        .unwrap();
+
+        let mut expect = vec![0x2A];
+        expect.extend_from_slice(&100_000u64.to_le_bytes());
+        expect.extend_from_slice(&10_000u64.to_le_bytes());
+        expect.extend_from_slice(&86_400i64.to_le_bytes());
+        expect.push(SwapDirection::SellBase as u8);
+        expect.extend_from_slice(&50u64.to_le_bytes());
+        expect.extend_from_slice(&10u64.to_le_bytes());
+        assert_eq!(instruction.data, expect);
+        assert_eq!(
+            SwapInstruction::unpack(&expect).unwrap(),
+            SwapInstruction::CreateDcaPlan(plan_data)
+        );
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+
+        let (dca_plan, _) = find_dca_plan_address(&program_id, &swap_pubkey, &owner);
+        assert_eq!(instruction.accounts[0], AccountMeta::new(dca_plan, false));
+        assert_eq!(
+            instruction.accounts[1],
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(&dca_plan, &mint),
+                false
+            )
+        );
+        assert_eq!(
+            instruction.accounts[3],
+            AccountMeta::new_readonly(owner, true)
+        );
+        assert_eq!(instruction.accounts[4], AccountMeta::new(source, false));
+        assert_eq!(instruction.accounts[6], AccountMeta::new(payer, true));
+    }
+
+    #[test]
+    fn test_execute_dca() {
+        let program_id = Pubkey::new_unique();
+        let swap_pubkey = Pubkey::new_unique();
+        let keeper_token = Pubkey::new_unique();
+        let (dca_plan, _) = find_dca_plan_address(&program_id, &swap_pubkey, &Pubkey::new_unique());
+        let instruction = swap(
+            program_id,
+            Pubkey::new_unique(),
+            swap_pubkey,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            dca_plan,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            SwapData {
+                amount_in: 0,
+                minimum_amount_out: 0,
+                swap_direction: SwapDirection::SellBase,
+                max_price_impact_bps: 0,
+            },
+        )
+        .unwrap();
+
+        let execute = execute_dca(instruction.clone(), dca_plan, keeper_token).unwrap();
+        assert_eq!(execute.data, vec![0x2B]);
+        assert_eq!(
+            SwapInstruction::unpack(&execute.data).unwrap(),
+            SwapInstruction::ExecuteDca
+        );
+        assert!(matches!(
+            InstructionType::check(&execute.data),
+            Some(InstructionType::Swap)
+        ));
+        assert_eq!(execute.accounts.len(), instruction.accounts.len() + 2);
+        assert_eq!(execute.accounts[0], AccountMeta::new(dca_plan, false));
+        assert_eq!(execute.accounts[1], AccountMeta::new(keeper_token, false));
+        // the plan does not sign
+        assert_eq!(
+            execute.accounts[6],
+            AccountMeta::new_readonly(dca_plan, false)
+        );
+        assert_eq!(execute.accounts[7..], instruction.accounts[5..]);
+        assert_eq!(
+            execute_dca(execute, dca_plan, keeper_token).err(),
+            Some(SwapError::InvalidInstruction.into())
+        );
+    }
+
+    #[test]
+    fn test_cancel_dca() {
+        let program_id = Pubkey::new_unique();
+        let swap_pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let instruction = cancel_dca(program_id, swap_pubkey, owner, destination, mint).unwrap();
 
-#[allow(dead_code)]
-fn unpack_bytes32(input: &[u8]) -> Result<(&[u8; 32], &[u8]), ProgramError> {
-    if input.len() < 32 {
-        return Err(SwapError::InstructionUnpackError.into());
+        assert_eq!(instruction.data, vec![0x2C]);
+        assert_eq!(
+            SwapInstruction::unpack(&instruction.data).unwrap(),
+            SwapInstruction::CancelDca
+        );
+        assert!(matches!(
+            InstructionType::check(&instruction.data),
+            Some(InstructionType::Swap)
+        ));
+
+        let (dca_plan, _) = find_dca_plan_address(&program_id, &swap_pubkey, &owner);
+        assert_eq!(instruction.accounts[0], AccountMeta::new(dca_plan, false));
+        assert_eq!(
+            instruction.accounts[1],
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(&dca_plan, &mint),
+                false
+            )
+        );
+        assert_eq!(instruction.accounts[2], AccountMeta::new(owner, true));
+        assert_eq!(
+            instruction.accounts[3],
+            AccountMeta::new(destination, false)
+        );
     }
-    let (bytes, rest) = input.split_at(32);
-    Ok((
-        bytes
-            .try_into()
-            .map_err(|_| SwapError::InstructionUnpackError)?,
-        rest,
-    ))
-}
 
-#[allow(dead_code)]
-fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
-    if input.len() < PUBKEY_BYTES {
-        return Err(SwapError::InstructionUnpackError.into());
+    #[test]
+    fn test_pack_transfer_position() {
+        let check = SwapInstruction::TransferPosition(TransferPositionData {
+            source_page_count: 2,
+        });
+        let packed = check.pack();
+        let expect = vec![0x13, 2];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+        assert!(SwapInstruction::unpack(&[0x13]).is_err());
     }
-    let (key, rest) = input.split_at(PUBKEY_BYTES);
-    let pk = Pubkey::new(key);
-    Ok((pk, rest))
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        curve::{default_market_price, default_slope},
-        state::{DEFAULT_TEST_FEES, DEFAULT_TEST_REWARDS},
-    };
+    #[test]
+    fn test_pack_set_liquidity_provider_delegate() {
+        let delegate = Pubkey::new_unique();
+        let check =
+            SwapInstruction::SetLiquidityProviderDelegate(SetLiquidityProviderDelegateData {
+                delegate,
+            });
+        let packed = check.pack();
+        let mut expect = vec![0x14];
+        expect.extend_from_slice(delegate.as_ref());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
 
     #[test]
-    fn test_pack_admin_init_config() {
+    fn test_pack_claim_all_rewards() {
+        let check = SwapInstruction::ClaimAllRewards(ClaimAllRewardsData { swap_count: 3 });
+        let packed = check.pack();
+        let expect = vec![0x15, 3];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
+
+    #[test]
+    fn test_pack_delfi_lock() {
+        let check = SwapInstruction::InitializeDelfiLock;
+        let packed = check.pack();
+        let expect = vec![0x17];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let amount = 1_000_000u64;
+        let unlock_ts = 1_700_000_000i64;
+        let check = SwapInstruction::LockDelfi(LockDelfiData { amount, unlock_ts });
+        let packed = check.pack();
+        let mut expect = vec![0x18];
+        expect.extend_from_slice(&amount.to_le_bytes());
+        expect.extend_from_slice(&unlock_ts.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = SwapInstruction::UnlockDelfi;
+        let packed = check.pack();
+        let expect = vec![0x19];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_proposal() {
         let fees = DEFAULT_TEST_FEES;
         let rewards = DEFAULT_TEST_REWARDS;
-        let check = AdminInstruction::Initialize(AdminInitializeData {
+        let check = SwapInstruction::CreateProposal(CreateProposalData {
             fees: fees.clone(),
             rewards: rewards.clone(),
         });
         let packed = check.pack();
-        let mut expect = vec![100];
-        expect.extend_from_slice(&fees.admin_trade_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_trade_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_withdraw_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_withdraw_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.trade_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.withdraw_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.withdraw_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_numerator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_denominator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_cap.to_le_bytes());
-        expect.extend_from_slice(&rewards.liquidity_reward_numerator.to_le_bytes());
-        expect.extend_from_slice(&rewards.liquidity_reward_denominator.to_le_bytes());
+        let mut expect = vec![0x1B];
+        let mut fees_slice = [0u8; Fees::LEN];
+        fees.pack_into_slice(&mut fees_slice);
+        expect.extend_from_slice(&fees_slice);
+        let mut rewards_slice = [0u8; Rewards::LEN];
+        rewards.pack_into_slice(&mut rewards_slice);
+        expect.extend_from_slice(&rewards_slice);
         assert_eq!(packed, expect);
-        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert_eq!(
+            SwapInstruction::unpack(&expect[..Fees::LEN + 1]).unwrap_err(),
+            SwapError::InstructionUnpackError.into()
+        );
+
+        let check = SwapInstruction::VoteProposal(VoteProposalData { approve: true });
+        let packed = check.pack();
+        let expect = vec![0x1C, 1];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = SwapInstruction::ExecuteProposal;
+        let packed = check.pack();
+        let expect = vec![0x1D];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
     }
 
     #[test]
-    fn test_pack_admin_set_new_fees() {
-        let fees = DEFAULT_TEST_FEES;
-        let check = AdminInstruction::SetNewFees(fees.clone());
+    fn test_pack_flash_swap() {
+        let check = SwapInstruction::FlashSwap(FlashSwapData {
+            amount_in: 1_000_000,
+            minimum_amount_out: 500_000,
+            swap_direction: SwapDirection::SellQuote,
+            max_price_impact_bps: 300,
+            callback_data: vec![7, 8, 9],
+        });
         let packed = check.pack();
-        let mut expect = vec![105];
-        expect.extend_from_slice(&fees.admin_trade_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_trade_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_withdraw_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_withdraw_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.trade_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.withdraw_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.withdraw_fee_denominator.to_le_bytes());
+        let mut expect = vec![0x1E];
+        expect.extend_from_slice(&1_000_000u64.to_le_bytes());
+        expect.extend_from_slice(&500_000u64.to_le_bytes());
+        expect.push(SwapDirection::SellQuote as u8);
+        expect.extend_from_slice(&300u64.to_le_bytes());
+        expect.extend_from_slice(&[7, 8, 9]);
         assert_eq!(packed, expect);
-        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+
+        let receive = FlashSwapCallbackInstruction::ReceiveFlashSwap(ReceiveFlashSwapData {
+            amount_in: 1_000_000,
+            amount_out: 500_000,
+            swap_direction: SwapDirection::SellQuote,
+            data: vec![],
+        });
+        let mut expect = vec![0];
+        expect.extend_from_slice(&1_000_000u64.to_le_bytes());
+        expect.extend_from_slice(&500_000u64.to_le_bytes());
+        expect.push(SwapDirection::SellQuote as u8);
+        assert_eq!(receive.pack(), expect);
+        assert_eq!(
+            FlashSwapCallbackInstruction::unpack(&expect).unwrap(),
+            receive
+        );
+        assert!(FlashSwapCallbackInstruction::unpack(&[1]).is_err());
     }
 
     #[test]
-    fn test_pack_admin_set_new_rewards() {
-        let rewards = DEFAULT_TEST_REWARDS;
-        let check = AdminInstruction::SetNewRewards(rewards.clone());
+    fn test_flash_swap() {
+        let program_id = Pubkey::new_unique();
+        let callback_program = Pubkey::new_unique();
+        let callback_account = AccountMeta::new(Pubkey::new_unique(), false);
+        let swap_data = SwapData {
+            amount_in: 1_000_000,
+            minimum_amount_out: 500_000,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 250,
+        };
+        let instruction = swap(
+            program_id,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            swap_data,
+        )
+        .unwrap();
+
+        let flash = flash_swap(
+            instruction.clone(),
+            callback_program,
+            vec![callback_account.clone()],
+            vec![1, 2],
+        )
+        .unwrap();
+        assert_eq!(
+            SwapInstruction::unpack(&flash.data).unwrap(),
+            SwapInstruction::FlashSwap(FlashSwapData {
+                amount_in: 1_000_000,
+                minimum_amount_out: 500_000,
+                swap_direction: SwapDirection::SellBase,
+                max_price_impact_bps: 250,
+                callback_data: vec![1, 2],
+            })
+        );
+        let len = instruction.accounts.len();
+        assert_eq!(flash.accounts[..len], instruction.accounts[..]);
+        assert_eq!(
+            flash.accounts[len],
+            AccountMeta::new_readonly(callback_program, false)
+        );
+        assert_eq!(flash.accounts[len + 1], callback_account);
+
+        assert_eq!(
+            flash_swap(flash, callback_program, vec![], vec![]).err(),
+            Some(SwapError::InvalidInstruction.into())
+        );
+    }
+
+    #[test]
+    fn test_pack_batch_swap() {
+        let swaps = vec![
+            SwapData {
+                amount_in: 1_000_000,
+                minimum_amount_out: 0,
+                swap_direction: SwapDirection::SellBase,
+                max_price_impact_bps: 0,
+            },
+            SwapData {
+                amount_in: 2_000_000,
+                minimum_amount_out: 10,
+                swap_direction: SwapDirection::SellQuote,
+                max_price_impact_bps: 100,
+            },
+        ];
+        let check = SwapInstruction::BatchSwap(BatchSwapData {
+            swaps: swaps.clone(),
+            minimum_total_amount_out: 500_000,
+        });
         let packed = check.pack();
-        let mut expect = vec![106];
-        expect.extend_from_slice(&rewards.trade_reward_numerator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_denominator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_cap.to_le_bytes());
-        expect.extend_from_slice(&rewards.liquidity_reward_numerator.to_le_bytes());
-        expect.extend_from_slice(&rewards.liquidity_reward_denominator.to_le_bytes());
+        let mut expect = vec![0x1F];
+        expect.extend_from_slice(&500_000u64.to_le_bytes());
+        expect.push(2);
+        for swap_data in swaps {
+            expect.extend_from_slice(&swap_data.amount_in.to_le_bytes());
+            expect.extend_from_slice(&swap_data.minimum_amount_out.to_le_bytes());
+            expect.push(swap_data.swap_direction as u8);
+            expect.extend_from_slice(&swap_data.max_price_impact_bps.to_le_bytes());
+        }
         assert_eq!(packed, expect);
-        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+        assert!(SwapInstruction::unpack(&expect[..expect.len() - 1]).is_err());
     }
 
     #[test]
-    fn test_pack_swap_initialization() {
-        let nonce: u8 = 255;
-        let slope: u64 = default_slope().to_scaled_val().unwrap().try_into().unwrap();
-        let mid_price = default_market_price().to_scaled_val().unwrap();
-        let is_open_twap = true;
-        let check = SwapInstruction::Initialize(InitializeData {
-            nonce,
-            slope,
-            mid_price,
-            is_open_twap,
+    fn test_batch_swap() {
+        let program_id = Pubkey::new_unique();
+        let swap_instruction = |swap_data: SwapData| {
+            swap(
+                program_id,
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                swap_data,
+            )
+            .unwrap()
+        };
+        let swap_data = SwapData {
+            amount_in: 1_000_000,
+            minimum_amount_out: 0,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        };
+        let first = swap_instruction(swap_data.clone());
+        let second =
+            append_transfer_hook(swap_instruction(swap_data.clone()), Pubkey::new_unique());
+
+        let batch = batch_swap(vec![first.clone(), second.clone()], 500_000).unwrap();
+        assert_eq!(batch.program_id, program_id);
+        assert_eq!(
+            SwapInstruction::unpack(&batch.data).unwrap(),
+            SwapInstruction::BatchSwap(BatchSwapData {
+                swaps: vec![swap_data.clone(), swap_data],
+                minimum_total_amount_out: 500_000,
+            })
+        );
+        assert_eq!(batch.accounts[..first.accounts.len()], first.accounts[..]);
+        assert_eq!(batch.accounts[first.accounts.len()..], second.accounts[..]);
+
+        assert_eq!(
+            batch_swap(vec![], 0).err(),
+            Some(SwapError::InvalidInput.into())
+        );
+        assert_eq!(
+            batch_swap(vec![first, batch], 0).err(),
+            Some(SwapError::InvalidInstruction.into())
+        );
+    }
+
+    #[test]
+    fn test_pack_split_swap() {
+        let check = SwapInstruction::SplitSwap(SplitSwapData {
+            amount_in: 1_000_000,
+            minimum_amount_out: 500_000,
+            swap_direction: SwapDirection::SellQuote,
+            fractions_bps: vec![7_000, 3_000],
         });
         let packed = check.pack();
-        let mut expect = vec![0];
-        expect.extend_from_slice(&nonce.to_le_bytes());
-        expect.extend_from_slice(&slope.to_le_bytes());
-        expect.extend_from_slice(&mid_price.to_le_bytes());
-        expect.extend_from_slice(&(is_open_twap as u8).to_le_bytes());
+        let mut expect = vec![0x20];
+        expect.extend_from_slice(&1_000_000u64.to_le_bytes());
+        expect.extend_from_slice(&500_000u64.to_le_bytes());
+        expect.push(SwapDirection::SellQuote as u8);
+        expect.push(2);
+        expect.extend_from_slice(&7_000u16.to_le_bytes());
+        expect.extend_from_slice(&3_000u16.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+        assert!(SwapInstruction::unpack(&expect[..expect.len() - 1]).is_err());
     }
 
     #[test]
-    fn test_pack_swap() {
-        let amount_in: u64 = 1_000_000;
-        let minimum_amount_out: u64 = 500_000;
-        let swap_direction: SwapDirection = SwapDirection::SellBase;
-        let check = SwapInstruction::Swap(SwapData {
-            amount_in,
-            minimum_amount_out,
-            swap_direction,
+    fn test_pack_emergency_withdraw() {
+        let minimum_token_a_amount: u64 = 1_000;
+        let minimum_token_b_amount: u64 = 2_000;
+        let check = SwapInstruction::EmergencyWithdraw(EmergencyWithdrawData {
+            minimum_token_a_amount,
+            minimum_token_b_amount,
         });
         let packed = check.pack();
-        let mut expect = vec![1];
-        expect.extend_from_slice(&amount_in.to_le_bytes());
-        expect.extend_from_slice(&minimum_amount_out.to_le_bytes());
-        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        let mut expect = vec![0x21];
+        expect.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
+        expect.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
+
+    #[test]
+    fn test_pack_sync_skim() {
+        for (check, tag) in [
+            (SwapInstruction::Sync, 0x22),
+            (SwapInstruction::Skim, 0x23),
+            (SwapInstruction::GetVirtualPrice, 0x25),
+            (SwapInstruction::InitializePriceFeed, 0x26),
+        ] {
+            let packed = check.pack();
+            let expect = vec![tag];
+            assert_eq!(packed, expect);
+            let unpacked = SwapInstruction::unpack(&expect).unwrap();
+            assert_eq!(unpacked, check);
+            assert!(matches!(
+                InstructionType::check(&expect),
+                Some(InstructionType::Swap)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_pack_vote_gauge() {
+        let weights_bps = vec![6_000u16, 4_000];
+        let check = SwapInstruction::VoteGauge(VoteGaugeData {
+            weights_bps: weights_bps.clone(),
+        });
+        let packed = check.pack();
+        let mut expect = vec![0x1A, 2];
+        expect.extend_from_slice(&weights_bps[0].to_le_bytes());
+        expect.extend_from_slice(&weights_bps[1].to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+        assert_eq!(
+            SwapInstruction::unpack(&expect[..4]).unwrap_err(),
+            SwapError::InstructionUnpackError.into()
+        );
+    }
+
+    #[test]
+    fn test_pack_poke() {
+        let check = SwapInstruction::Poke;
+        let packed = check.pack();
+        let expect = vec![0x16];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
     }
 
     #[test]
@@ -1110,6 +7495,80 @@ mod tests {
         assert_eq!(unpacked, check);
     }
 
+    #[test]
+    fn test_pack_deposit_imbalanced() {
+        let token_a_amount: u64 = 1_000_000;
+        let token_b_amount: u64 = 0;
+        let min_mint_amount: u64 = 500_000;
+        let check = SwapInstruction::DepositImbalanced(DepositData {
+            token_a_amount,
+            token_b_amount,
+            min_mint_amount,
+        });
+        let packed = check.pack();
+        let mut expect = vec![0xA];
+        expect.extend_from_slice(&token_a_amount.to_le_bytes());
+        expect.extend_from_slice(&token_b_amount.to_le_bytes());
+        expect.extend_from_slice(&min_mint_amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
+
+    #[test]
+    fn test_pack_withdraw_exact() {
+        let token_a_amount: u64 = 1_000_000;
+        let token_b_amount: u64 = 500_000;
+        let max_pool_token_amount: u64 = 500_000;
+        let check = SwapInstruction::WithdrawExact(WithdrawExactData {
+            token_a_amount,
+            token_b_amount,
+            max_pool_token_amount,
+        });
+        let packed = check.pack();
+        let mut expect = vec![0xB];
+        expect.extend_from_slice(&token_a_amount.to_le_bytes());
+        expect.extend_from_slice(&token_b_amount.to_le_bytes());
+        expect.extend_from_slice(&max_pool_token_amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_init_registry_page() {
+        let page_index = 3u8;
+        let check =
+            SwapInstruction::InitializeRegistryPage(InitializeRegistryPageData { page_index });
+        let packed = check.pack();
+        let expect = vec![0xD, page_index];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
+
+    #[test]
+    fn test_pack_init_observation_buffer() {
+        let check = SwapInstruction::InitializeObservationBuffer;
+        let packed = check.pack();
+        let expect = vec![0xC];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert!(matches!(
+            InstructionType::check(&expect),
+            Some(InstructionType::Swap)
+        ));
+    }
+
     #[test]
     fn test_pack_withdraw() {
         let minimum_token_a_amount: u64 = 1_000_000;
@@ -1129,4 +7588,37 @@ mod tests {
         let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
     }
+
+    #[test]
+    fn test_pack_grow_provider_account() {
+        let max_positions = 20u8;
+        let check = SwapInstruction::GrowProviderAccount(GrowProviderAccountData { max_positions });
+        let packed = check.pack();
+        let expect = vec![7, max_positions];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_init_position_page() {
+        let page_index = 2u8;
+        let check =
+            SwapInstruction::InitializePositionPage(InitializePositionPageData { page_index });
+        let packed = check.pack();
+        let expect = vec![9, page_index];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_close_liquidity_provider() {
+        let check = SwapInstruction::CloseLiquidityProvider;
+        let packed = check.pack();
+        let expect = vec![8];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
 }
@@ -0,0 +1,915 @@
+//! Typed cross-program invocations of the swap program
+//!
+//! Programs composing with the swap depend on this crate with the
+//! `no-entrypoint` feature and call these wrappers with the account infos of
+//! an instruction. The instruction is built by the matching builder of
+//! [instruction](../instruction/index.html), and the account infos are passed
+//! in the same order, so the accounts always reach the processor where it
+//! expects them. Signer seeds sign for program addresses of the caller, empty
+//! for none.
+
+#![allow(clippy::too_many_arguments)]
+
+use std::convert::TryInto;
+
+use arrayref::{array_ref, array_refs};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{get_return_data, invoke_signed},
+    program_error::ProgramError,
+};
+
+use crate::{
+    instruction::{
+        self, DepositData, ProgramBuild, QuoteSwapData, SwapData, WithdrawData, WithdrawExactData,
+    },
+    math::Decimal,
+};
+
+/// Accounts of a `Swap` instruction
+pub struct Swap<'a> {
+    /// Config info, writable
+    pub config: AccountInfo<'a>,
+    /// Token-swap
+    pub swap: AccountInfo<'a>,
+    /// Market authority
+    pub market_authority: AccountInfo<'a>,
+    /// Swap authority
+    pub swap_authority: AccountInfo<'a>,
+    /// User transfer authority, signer
+    pub user_transfer_authority: AccountInfo<'a>,
+    /// User token account of token a
+    pub source: AccountInfo<'a>,
+    /// Pool token account of token a
+    pub swap_source: AccountInfo<'a>,
+    /// Pool token account of token b
+    pub swap_destination: AccountInfo<'a>,
+    /// User token account of token b
+    pub destination: AccountInfo<'a>,
+    /// User deltafi token account receiving the reward
+    pub reward_token: AccountInfo<'a>,
+    /// Deltafi mint
+    pub reward_mint: AccountInfo<'a>,
+    /// Admin fee account of the token bought
+    pub admin_fee_destination: AccountInfo<'a>,
+    /// Pyth price account of token a
+    pub pyth_a: AccountInfo<'a>,
+    /// Pyth price account of token b
+    pub pyth_b: AccountInfo<'a>,
+    /// Clock sysvar
+    pub clock: AccountInfo<'a>,
+    /// Token program
+    pub token_program: AccountInfo<'a>,
+    /// Observation buffer of the token-swap
+    pub observation_buffer: AccountInfo<'a>,
+    /// Whitelist of the token-swap
+    pub whitelist: AccountInfo<'a>,
+    /// Price feed of the token-swap, writable
+    pub price_feed: AccountInfo<'a>,
+    /// Pool mint
+    pub pool_mint: AccountInfo<'a>,
+}
+
+/// Accounts of a `Deposit` or `DepositImbalanced` instruction, the admin fee
+/// accounts are only used by the latter
+pub struct Deposit<'a> {
+    /// Token-swap
+    pub swap: AccountInfo<'a>,
+    /// Swap authority
+    pub swap_authority: AccountInfo<'a>,
+    /// User transfer authority, signer
+    pub user_transfer_authority: AccountInfo<'a>,
+    /// User token account of token a
+    pub source_a: AccountInfo<'a>,
+    /// User token account of token b
+    pub source_b: AccountInfo<'a>,
+    /// Pool token account of token a
+    pub swap_token_a: AccountInfo<'a>,
+    /// Pool token account of token b
+    pub swap_token_b: AccountInfo<'a>,
+    /// Pool mint
+    pub pool_mint: AccountInfo<'a>,
+    /// User pool token account
+    pub destination: AccountInfo<'a>,
+    /// Admin fee account of token a
+    pub admin_fee_a: AccountInfo<'a>,
+    /// Admin fee account of token b
+    pub admin_fee_b: AccountInfo<'a>,
+    /// Liquidity provider
+    pub liquidity_provider: AccountInfo<'a>,
+    /// Liquidity provider owner or delegate, signer
+    pub liquidity_owner: AccountInfo<'a>,
+    /// Pyth price account of token a
+    pub pyth_a: AccountInfo<'a>,
+    /// Pyth price account of token b
+    pub pyth_b: AccountInfo<'a>,
+    /// Clock sysvar
+    pub clock: AccountInfo<'a>,
+    /// Token program
+    pub token_program: AccountInfo<'a>,
+    /// Observation buffer of the token-swap
+    pub observation_buffer: AccountInfo<'a>,
+    /// Price feed of the token-swap, writable
+    pub price_feed: AccountInfo<'a>,
+}
+
+/// Accounts of a `Withdraw` or `WithdrawExact` instruction
+pub struct Withdraw<'a> {
+    /// Token-swap
+    pub swap: AccountInfo<'a>,
+    /// Swap authority
+    pub swap_authority: AccountInfo<'a>,
+    /// User transfer authority, signer
+    pub user_transfer_authority: AccountInfo<'a>,
+    /// Pool mint
+    pub pool_mint: AccountInfo<'a>,
+    /// User pool token account
+    pub source: AccountInfo<'a>,
+    /// Pool token account of token a
+    pub swap_token_a: AccountInfo<'a>,
+    /// Pool token account of token b
+    pub swap_token_b: AccountInfo<'a>,
+    /// User token account of token a
+    pub destination_a: AccountInfo<'a>,
+    /// User token account of token b
+    pub destination_b: AccountInfo<'a>,
+    /// Admin fee account of token a
+    pub admin_fee_a: AccountInfo<'a>,
+    /// Admin fee account of token b
+    pub admin_fee_b: AccountInfo<'a>,
+    /// Liquidity provider
+    pub liquidity_provider: AccountInfo<'a>,
+    /// Liquidity provider owner or delegate, signer
+    pub liquidity_owner: AccountInfo<'a>,
+    /// Pyth price account of token a
+    pub pyth_a: AccountInfo<'a>,
+    /// Pyth price account of token b
+    pub pyth_b: AccountInfo<'a>,
+    /// Clock sysvar
+    pub clock: AccountInfo<'a>,
+    /// Token program
+    pub token_program: AccountInfo<'a>,
+    /// Observation buffer of the token-swap
+    pub observation_buffer: AccountInfo<'a>,
+    /// Price feed of the token-swap, writable
+    pub price_feed: AccountInfo<'a>,
+}
+
+/// Accounts of a `QuoteSwap` instruction
+pub struct QuoteSwap<'a> {
+    /// Token-swap
+    pub swap: AccountInfo<'a>,
+    /// Pool token account of token a
+    pub swap_token_a: AccountInfo<'a>,
+    /// Pool token account of token b
+    pub swap_token_b: AccountInfo<'a>,
+    /// Pyth price account of token a
+    pub pyth_a: AccountInfo<'a>,
+    /// Pyth price account of token b
+    pub pyth_b: AccountInfo<'a>,
+    /// Clock sysvar
+    pub clock: AccountInfo<'a>,
+    /// Token program
+    pub token_program: AccountInfo<'a>,
+    /// Observation buffer of the token-swap
+    pub observation_buffer: AccountInfo<'a>,
+}
+
+/// Accounts of a `CalcReceiveAmount` instruction
+pub struct CalcReceiveAmount<'a> {
+    /// Quote account of the owner
+    pub quote_account: AccountInfo<'a>,
+    /// Quote account owner, signer
+    pub owner: AccountInfo<'a>,
+    /// Accounts quoting the swap
+    pub quote: QuoteSwap<'a>,
+}
+
+/// Accounts of a `ClaimLiquidityRewards` instruction
+pub struct ClaimLiquidityRewards<'a> {
+    /// Config info, writable
+    pub config: AccountInfo<'a>,
+    /// Token-swap
+    pub swap: AccountInfo<'a>,
+    /// Market authority
+    pub market_authority: AccountInfo<'a>,
+    /// Liquidity provider
+    pub liquidity_provider: AccountInfo<'a>,
+    /// Liquidity provider owner or delegate, signer
+    pub liquidity_owner: AccountInfo<'a>,
+    /// Deltafi token account receiving the rewards
+    pub claim_destination: AccountInfo<'a>,
+    /// Deltafi mint
+    pub claim_mint: AccountInfo<'a>,
+    /// Token program
+    pub token_program: AccountInfo<'a>,
+}
+
+/// Swap tokens through the swap program.
+/// Remaining accounts are the OpenBook market, bids and asks, when the
+/// token-swap has a market, then the transfer hook program, when it has one.
+pub fn swap<'a>(
+    program: &AccountInfo<'a>,
+    accounts: Swap<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+    swap_data: SwapData,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::swap(
+        *program.key,
+        *accounts.config.key,
+        *accounts.swap.key,
+        *accounts.market_authority.key,
+        *accounts.swap_authority.key,
+        *accounts.user_transfer_authority.key,
+        *accounts.source.key,
+        *accounts.swap_source.key,
+        *accounts.swap_destination.key,
+        *accounts.destination.key,
+        *accounts.reward_token.key,
+        *accounts.reward_mint.key,
+        *accounts.admin_fee_destination.key,
+        *accounts.pyth_a.key,
+        *accounts.pyth_b.key,
+        *accounts.pool_mint.key,
+        swap_data,
+    )?;
+    invoke_with_remaining_accounts(
+        instruction,
+        vec![
+            accounts.config,
+            accounts.swap,
+            accounts.market_authority,
+            accounts.swap_authority,
+            accounts.user_transfer_authority,
+            accounts.source,
+            accounts.swap_source,
+            accounts.swap_destination,
+            accounts.destination,
+            accounts.reward_token,
+            accounts.reward_mint,
+            accounts.admin_fee_destination,
+            accounts.pyth_a,
+            accounts.pyth_b,
+            accounts.clock,
+            accounts.token_program,
+            accounts.observation_buffer,
+            accounts.whitelist,
+            accounts.price_feed,
+            accounts.pool_mint,
+        ],
+        remaining_accounts,
+        program,
+        signers_seeds,
+    )
+}
+
+/// Deposit tokens at the current pool ratio through the swap program.
+/// Remaining accounts are the OpenBook market, bids and asks, when the
+/// token-swap has a market, then the transfer hook program, when it has one,
+/// then the position pages of the liquidity provider.
+pub fn deposit<'a>(
+    program: &AccountInfo<'a>,
+    accounts: Deposit<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+    deposit_data: DepositData,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::deposit(
+        *program.key,
+        *accounts.swap.key,
+        *accounts.swap_authority.key,
+        *accounts.user_transfer_authority.key,
+        *accounts.source_a.key,
+        *accounts.source_b.key,
+        *accounts.swap_token_a.key,
+        *accounts.swap_token_b.key,
+        *accounts.pool_mint.key,
+        *accounts.destination.key,
+        *accounts.liquidity_provider.key,
+        *accounts.liquidity_owner.key,
+        *accounts.pyth_a.key,
+        *accounts.pyth_b.key,
+        deposit_data,
+    )?;
+    invoke_with_remaining_accounts(
+        instruction,
+        vec![
+            accounts.swap,
+            accounts.swap_authority,
+            accounts.user_transfer_authority,
+            accounts.source_a,
+            accounts.source_b,
+            accounts.swap_token_a,
+            accounts.swap_token_b,
+            accounts.pool_mint,
+            accounts.destination,
+            accounts.liquidity_provider,
+            accounts.liquidity_owner,
+            accounts.pyth_a,
+            accounts.pyth_b,
+            accounts.clock,
+            accounts.token_program,
+            accounts.observation_buffer,
+            accounts.price_feed,
+        ],
+        remaining_accounts,
+        program,
+        signers_seeds,
+    )
+}
+
+/// Deposit tokens in any ratio through the swap program.
+/// Remaining accounts are the OpenBook market, bids and asks, when the
+/// token-swap has a market, then the transfer hook program, when it has one,
+/// then the position pages of the liquidity provider.
+pub fn deposit_imbalanced<'a>(
+    program: &AccountInfo<'a>,
+    accounts: Deposit<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+    deposit_data: DepositData,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::deposit_imbalanced(
+        *program.key,
+        *accounts.swap.key,
+        *accounts.swap_authority.key,
+        *accounts.user_transfer_authority.key,
+        *accounts.source_a.key,
+        *accounts.source_b.key,
+        *accounts.swap_token_a.key,
+        *accounts.swap_token_b.key,
+        *accounts.pool_mint.key,
+        *accounts.destination.key,
+        *accounts.admin_fee_a.key,
+        *accounts.admin_fee_b.key,
+        *accounts.liquidity_provider.key,
+        *accounts.liquidity_owner.key,
+        *accounts.pyth_a.key,
+        *accounts.pyth_b.key,
+        deposit_data,
+    )?;
+    invoke_with_remaining_accounts(
+        instruction,
+        vec![
+            accounts.swap,
+            accounts.swap_authority,
+            accounts.user_transfer_authority,
+            accounts.source_a,
+            accounts.source_b,
+            accounts.swap_token_a,
+            accounts.swap_token_b,
+            accounts.pool_mint,
+            accounts.destination,
+            accounts.admin_fee_a,
+            accounts.admin_fee_b,
+            accounts.liquidity_provider,
+            accounts.liquidity_owner,
+            accounts.pyth_a,
+            accounts.pyth_b,
+            accounts.clock,
+            accounts.token_program,
+            accounts.observation_buffer,
+            accounts.price_feed,
+        ],
+        remaining_accounts,
+        program,
+        signers_seeds,
+    )
+}
+
+/// Withdraw tokens at the current pool ratio through the swap program.
+/// Remaining accounts are the OpenBook market, bids and asks, when the
+/// token-swap has a market, then the position pages of the liquidity provider.
+pub fn withdraw<'a>(
+    program: &AccountInfo<'a>,
+    accounts: Withdraw<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+    withdraw_data: WithdrawData,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::withdraw(
+        *program.key,
+        *accounts.swap.key,
+        *accounts.swap_authority.key,
+        *accounts.user_transfer_authority.key,
+        *accounts.pool_mint.key,
+        *accounts.source.key,
+        *accounts.swap_token_a.key,
+        *accounts.swap_token_b.key,
+        *accounts.destination_a.key,
+        *accounts.destination_b.key,
+        *accounts.admin_fee_a.key,
+        *accounts.admin_fee_b.key,
+        *accounts.liquidity_provider.key,
+        *accounts.liquidity_owner.key,
+        *accounts.pyth_a.key,
+        *accounts.pyth_b.key,
+        withdraw_data,
+    )?;
+    invoke_with_remaining_accounts(
+        instruction,
+        withdraw_account_infos(accounts),
+        remaining_accounts,
+        program,
+        signers_seeds,
+    )
+}
+
+/// Withdraw exact token amounts through the swap program.
+/// Remaining accounts are the OpenBook market, bids and asks, when the
+/// token-swap has a market, then the position pages of the liquidity provider.
+pub fn withdraw_exact<'a>(
+    program: &AccountInfo<'a>,
+    accounts: Withdraw<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+    withdraw_data: WithdrawExactData,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::withdraw_exact(
+        *program.key,
+        *accounts.swap.key,
+        *accounts.swap_authority.key,
+        *accounts.user_transfer_authority.key,
+        *accounts.pool_mint.key,
+        *accounts.source.key,
+        *accounts.swap_token_a.key,
+        *accounts.swap_token_b.key,
+        *accounts.destination_a.key,
+        *accounts.destination_b.key,
+        *accounts.admin_fee_a.key,
+        *accounts.admin_fee_b.key,
+        *accounts.liquidity_provider.key,
+        *accounts.liquidity_owner.key,
+        *accounts.pyth_a.key,
+        *accounts.pyth_b.key,
+        withdraw_data,
+    )?;
+    invoke_with_remaining_accounts(
+        instruction,
+        withdraw_account_infos(accounts),
+        remaining_accounts,
+        program,
+        signers_seeds,
+    )
+}
+
+/// Quote a swap through the swap program, returns the amount the user would
+/// receive, after trade fee.
+/// Remaining accounts are the OpenBook market, bids and asks, when the
+/// token-swap has a market.
+pub fn quote_swap<'a>(
+    program: &AccountInfo<'a>,
+    accounts: QuoteSwap<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+    quote_swap_data: QuoteSwapData,
+) -> Result<u64, ProgramError> {
+    let instruction = instruction::quote_swap(
+        *program.key,
+        *accounts.swap.key,
+        *accounts.swap_token_a.key,
+        *accounts.swap_token_b.key,
+        *accounts.pyth_a.key,
+        *accounts.pyth_b.key,
+        quote_swap_data,
+    )?;
+    invoke_with_remaining_accounts(
+        instruction,
+        quote_swap_account_infos(accounts),
+        remaining_accounts,
+        program,
+        &[],
+    )?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key => data
+            .get(..8)
+            .and_then(|data| data.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(ProgramError::InvalidAccountData),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Quote a swap through the swap program along with the value of a pool token
+/// of the pool mint, in quote token amounts per pool token amount.
+/// Remaining accounts are the OpenBook market, bids and asks, when the
+/// token-swap has a market.
+pub fn quote_swap_with_lp_virtual_price<'a>(
+    program: &AccountInfo<'a>,
+    accounts: QuoteSwap<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+    pool_mint: &AccountInfo<'a>,
+    quote_swap_data: QuoteSwapData,
+) -> Result<(u64, Decimal), ProgramError> {
+    let instruction = instruction::quote_swap(
+        *program.key,
+        *accounts.swap.key,
+        *accounts.swap_token_a.key,
+        *accounts.swap_token_b.key,
+        *accounts.pyth_a.key,
+        *accounts.pyth_b.key,
+        quote_swap_data,
+    )?;
+    // the pool mint follows the market
+    let mut remaining_accounts = remaining_accounts.to_vec();
+    remaining_accounts.push(pool_mint.clone());
+    invoke_with_remaining_accounts(
+        instruction,
+        quote_swap_account_infos(accounts),
+        &remaining_accounts,
+        program,
+        &[],
+    )?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key && data.len() >= 24 => {
+            let data = array_ref![data, 0, 24];
+            let (amount_out, lp_virtual_price) = array_refs![data, 8, 16];
+            Ok((
+                u64::from_le_bytes(*amount_out),
+                Decimal::from_scaled_val(u128::from_le_bytes(*lp_virtual_price)),
+            ))
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Get the version and compiled features of the swap program
+pub fn get_version(program: &AccountInfo<'_>) -> Result<ProgramBuild, ProgramError> {
+    let instruction = instruction::get_version(*program.key)?;
+    invoke_with_remaining_accounts(instruction, vec![], &[], program, &[])?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key => ProgramBuild::unpack(&data),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Get the value of a pool token in quote token amounts, the targets of the
+/// pool valued at its market price
+pub fn get_virtual_price<'a>(
+    program: &AccountInfo<'a>,
+    swap: &AccountInfo<'a>,
+    pool_mint: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+) -> Result<Decimal, ProgramError> {
+    let instruction = instruction::get_virtual_price(*program.key, *swap.key, *pool_mint.key)?;
+    invoke_with_remaining_accounts(
+        instruction,
+        vec![swap.clone(), pool_mint.clone(), token_program.clone()],
+        &[],
+        program,
+        &[],
+    )?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *program.key && data.len() >= 16 => {
+            Ok(Decimal::from_scaled_val(u128::from_le_bytes(*array_ref![
+                data, 0, 16
+            ])))
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Quote a swap through the swap program, storing it in the quote account of
+/// the owner.
+/// Remaining accounts are the OpenBook market, bids and asks, when the
+/// token-swap has a market.
+pub fn calc_receive_amount<'a>(
+    program: &AccountInfo<'a>,
+    accounts: CalcReceiveAmount<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+    quote_swap_data: QuoteSwapData,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::calc_receive_amount(
+        *program.key,
+        *accounts.owner.key,
+        *accounts.quote.swap.key,
+        *accounts.quote.swap_token_a.key,
+        *accounts.quote.swap_token_b.key,
+        *accounts.quote.pyth_a.key,
+        *accounts.quote.pyth_b.key,
+        quote_swap_data,
+    )?;
+    let mut account_infos = vec![accounts.quote_account, accounts.owner];
+    account_infos.extend(quote_swap_account_infos(accounts.quote));
+    invoke_with_remaining_accounts(
+        instruction,
+        account_infos,
+        remaining_accounts,
+        program,
+        signers_seeds,
+    )
+}
+
+/// Claim the liquidity rewards of a liquidity provider through the swap program.
+/// Remaining accounts are the rewards receiver of the referrer, when the
+/// liquidity provider has one, then its position pages.
+pub fn claim_liquidity_rewards<'a>(
+    program: &AccountInfo<'a>,
+    accounts: ClaimLiquidityRewards<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let instruction = instruction::claim_liquidity_rewards(
+        *program.key,
+        *accounts.config.key,
+        *accounts.swap.key,
+        *accounts.market_authority.key,
+        *accounts.liquidity_provider.key,
+        *accounts.liquidity_owner.key,
+        *accounts.claim_destination.key,
+        *accounts.claim_mint.key,
+    )?;
+    invoke_with_remaining_accounts(
+        instruction,
+        vec![
+            accounts.config,
+            accounts.swap,
+            accounts.market_authority,
+            accounts.liquidity_provider,
+            accounts.liquidity_owner,
+            accounts.claim_destination,
+            accounts.claim_mint,
+            accounts.token_program,
+        ],
+        remaining_accounts,
+        program,
+        signers_seeds,
+    )
+}
+
+fn withdraw_account_infos(accounts: Withdraw<'_>) -> Vec<AccountInfo<'_>> {
+    vec![
+        accounts.swap,
+        accounts.swap_authority,
+        accounts.user_transfer_authority,
+        accounts.pool_mint,
+        accounts.source,
+        accounts.swap_token_a,
+        accounts.swap_token_b,
+        accounts.destination_a,
+        accounts.destination_b,
+        accounts.admin_fee_a,
+        accounts.admin_fee_b,
+        accounts.liquidity_provider,
+        accounts.liquidity_owner,
+        accounts.pyth_a,
+        accounts.pyth_b,
+        accounts.clock,
+        accounts.token_program,
+        accounts.observation_buffer,
+        accounts.price_feed,
+    ]
+}
+
+fn quote_swap_account_infos(accounts: QuoteSwap<'_>) -> Vec<AccountInfo<'_>> {
+    vec![
+        accounts.swap,
+        accounts.swap_token_a,
+        accounts.swap_token_b,
+        accounts.pyth_a,
+        accounts.pyth_b,
+        accounts.clock,
+        accounts.token_program,
+        accounts.observation_buffer,
+    ]
+}
+
+/// Append the remaining accounts, as passed to the caller, and invoke the
+/// instruction with the account infos in the order of its accounts
+fn invoke_with_remaining_accounts<'a>(
+    mut instruction: Instruction,
+    mut account_infos: Vec<AccountInfo<'a>>,
+    remaining_accounts: &[AccountInfo<'a>],
+    program: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    for account_info in remaining_accounts {
+        instruction.accounts.push(if account_info.is_writable {
+            AccountMeta::new(*account_info.key, false)
+        } else {
+            AccountMeta::new_readonly(*account_info.key, false)
+        });
+        account_infos.push(account_info.clone());
+    }
+    if instruction.accounts.len() != account_infos.len()
+        || instruction
+            .accounts
+            .iter()
+            .zip(account_infos.iter())
+            .any(|(meta, account_info)| meta.pubkey != *account_info.key)
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    account_infos.push(program.clone());
+    invoke_signed(&instruction, &account_infos, signers_seeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        instruction::SwapDirection,
+        state::{find_observation_buffer_address, find_price_feed_address, find_whitelist_address},
+    };
+    use solana_program::{clock::Epoch, pubkey::Pubkey, sysvar::clock};
+
+    struct TestAccount {
+        key: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey) -> Self {
+            Self {
+                key,
+                lamports: 0,
+                data: vec![],
+            }
+        }
+
+        fn info(&mut self, owner: &'static Pubkey) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                false,
+                true,
+                &mut self.lamports,
+                &mut self.data,
+                owner,
+                false,
+                Epoch::default(),
+            )
+        }
+    }
+
+    static OWNER: Pubkey = Pubkey::new_from_array([0; 32]);
+
+    fn swap_accounts(accounts: &mut [TestAccount]) -> Swap<'_> {
+        let mut infos = accounts.iter_mut().map(|account| account.info(&OWNER));
+        let mut next = || infos.next().unwrap();
+        Swap {
+            config: next(),
+            swap: next(),
+            market_authority: next(),
+            swap_authority: next(),
+            user_transfer_authority: next(),
+            source: next(),
+            swap_source: next(),
+            swap_destination: next(),
+            destination: next(),
+            reward_token: next(),
+            reward_mint: next(),
+            admin_fee_destination: next(),
+            pyth_a: next(),
+            pyth_b: next(),
+            clock: next(),
+            token_program: next(),
+            observation_buffer: next(),
+            whitelist: next(),
+            price_feed: next(),
+            pool_mint: next(),
+        }
+    }
+
+    fn quote_swap_test_accounts(program_id: &Pubkey) -> Vec<TestAccount> {
+        let swap_key = Pubkey::new_unique();
+        vec![
+            swap_key,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            clock::id(),
+            spl_token::id(),
+            find_observation_buffer_address(program_id, &swap_key).0,
+        ]
+        .into_iter()
+        .map(TestAccount::new)
+        .collect()
+    }
+
+    fn quote_swap_accounts(accounts: &mut [TestAccount]) -> QuoteSwap<'_> {
+        let mut infos = accounts.iter_mut().map(|account| account.info(&OWNER));
+        let mut next = || infos.next().unwrap();
+        QuoteSwap {
+            swap: next(),
+            swap_token_a: next(),
+            swap_token_b: next(),
+            pyth_a: next(),
+            pyth_b: next(),
+            clock: next(),
+            token_program: next(),
+            observation_buffer: next(),
+        }
+    }
+
+    #[test]
+    fn test_quote_swap_accounts() {
+        let program_id = crate::id();
+        let mut accounts = quote_swap_test_accounts(&program_id);
+        let mut program = TestAccount::new(program_id);
+
+        // no return data off chain
+        assert_eq!(
+            quote_swap(
+                &program.info(&OWNER),
+                quote_swap_accounts(&mut accounts),
+                &[],
+                QuoteSwapData {
+                    amount_in: 1_000,
+                    swap_direction: SwapDirection::SellBase,
+                },
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_quote_swap_with_lp_virtual_price_accounts() {
+        let program_id = crate::id();
+        let mut accounts = quote_swap_test_accounts(&program_id);
+        let mut program = TestAccount::new(program_id);
+        let mut pool_mint = TestAccount::new(Pubkey::new_unique());
+
+        // no return data off chain
+        assert_eq!(
+            quote_swap_with_lp_virtual_price(
+                &program.info(&OWNER),
+                quote_swap_accounts(&mut accounts),
+                &[],
+                &pool_mint.info(&OWNER),
+                QuoteSwapData {
+                    amount_in: 1_000,
+                    swap_direction: SwapDirection::SellBase,
+                },
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_get_virtual_price_accounts() {
+        let mut program = TestAccount::new(crate::id());
+        let mut swap = TestAccount::new(Pubkey::new_unique());
+        let mut pool_mint = TestAccount::new(Pubkey::new_unique());
+        let mut token_program = TestAccount::new(spl_token::id());
+
+        // no return data off chain
+        assert_eq!(
+            get_virtual_price(
+                &program.info(&OWNER),
+                &swap.info(&OWNER),
+                &pool_mint.info(&OWNER),
+                &token_program.info(&OWNER),
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_account_order() {
+        let program_id = crate::id();
+        let swap_key = Pubkey::new_unique();
+        let mut keys: Vec<Pubkey> = (0..20).map(|_| Pubkey::new_unique()).collect();
+        keys[1] = swap_key;
+        keys[14] = clock::id();
+        keys[15] = spl_token::id();
+        keys[16] = find_observation_buffer_address(&program_id, &swap_key).0;
+        keys[17] = find_whitelist_address(&program_id, &swap_key).0;
+        keys[18] = find_price_feed_address(&program_id, &swap_key).0;
+        let mut accounts: Vec<TestAccount> = keys.into_iter().map(TestAccount::new).collect();
+        let mut hook = TestAccount::new(Pubkey::new_unique());
+        let mut program = TestAccount::new(program_id);
+        let swap_data = SwapData {
+            amount_in: 1_000,
+            minimum_amount_out: 900,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        };
+
+        swap(
+            &program.info(&OWNER),
+            swap_accounts(&mut accounts),
+            &[hook.info(&OWNER)],
+            swap_data.clone(),
+            &[],
+        )
+        .unwrap();
+
+        // an observation buffer not derived from the token-swap
+        accounts[16].key = Pubkey::new_unique();
+        assert_eq!(
+            swap(
+                &program.info(&OWNER),
+                swap_accounts(&mut accounts),
+                &[],
+                swap_data,
+                &[]
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
@@ -0,0 +1,656 @@
+//! Quoting interface in the shape of the Jupiter aggregator
+//!
+//! Mirrors the `Amm` trait the aggregator lists pools with, without depending
+//! on its crate: the accounts a quote reads, a quote computed from their
+//! serialized data by the same code as the program, and the accounts of the
+//! swap instruction it routes through.
+
+use std::collections::HashMap;
+
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::{self, Sysvar},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account;
+
+use crate::{
+    client::SwapClient,
+    error::SwapError,
+    instruction::{SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    openbook,
+    processor::{collected_trade_fee, quote_market_swap},
+    state::{find_observation_buffer_address, SwapInfo, SwapInfoData},
+};
+
+/// Quote request of the aggregator, an exact amount in
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuoteParams {
+    /// Amount of the input token sold
+    pub amount: u64,
+    /// Mint of the token sold
+    pub input_mint: Pubkey,
+    /// Mint of the token bought
+    pub output_mint: Pubkey,
+}
+
+/// Quote returned to the aggregator
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    /// Amount of the input token sold
+    pub in_amount: u64,
+    /// Amount of the output token sent to the user, after trade fee
+    pub out_amount: u64,
+    /// Trade fee, in the token the pool collects it in
+    pub fee_amount: u64,
+    /// Mint of the token the trade fee is collected in
+    pub fee_mint: Pubkey,
+    /// Trade fee over the output before fee
+    pub fee_pct: Decimal,
+}
+
+/// Swap routed by the aggregator
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapParams {
+    /// Mint of the token sold
+    pub source_mint: Pubkey,
+    /// Mint of the token bought
+    pub destination_mint: Pubkey,
+    /// User token account debited
+    pub source_token_account: Pubkey,
+    /// User token account credited
+    pub destination_token_account: Pubkey,
+    /// Authority over the source token account, signing the swap
+    pub token_transfer_authority: Pubkey,
+}
+
+/// Copy of an account read by a quote, lent to the program code
+struct QuotedAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+impl QuotedAccount {
+    fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        Self {
+            key,
+            owner,
+            lamports: 0,
+            data,
+        }
+    }
+
+    fn account_info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            false,
+            false,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+/// Swap pool as the aggregator lists it
+///
+/// The aggregator polls the accounts of
+/// [get_accounts_to_update](#method.get_accounts_to_update) and passes their
+/// data to [update](#method.update). Quotes are then computed offline, pricing
+/// the pool the way a swap in the same slot would.
+pub struct DeltafiAmm {
+    client: SwapClient,
+    account_data: HashMap<Pubkey, Vec<u8>>,
+}
+
+impl DeltafiAmm {
+    /// Create new listing of a pool from its client
+    pub fn new(client: SwapClient) -> Self {
+        Self {
+            client,
+            account_data: HashMap::new(),
+        }
+    }
+
+    /// Name of the pools
+    pub fn label(&self) -> String {
+        "Deltafi".to_string()
+    }
+
+    /// Token-swap account
+    pub fn key(&self) -> Pubkey {
+        self.client.swap_pubkey
+    }
+
+    /// Mints of the tokens of the pool
+    pub fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![self.client.swap.token_a_mint, self.client.swap.token_b_mint]
+    }
+
+    /// Accounts read by a quote. The bids and asks of the OpenBook market of
+    /// the pool are only known once the market itself is updated.
+    pub fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        let swap = &self.client.swap;
+        let mut keys = vec![
+            self.client.swap_pubkey,
+            swap.token_a,
+            swap.token_b,
+            swap.pyth_a,
+            swap.pyth_b,
+            find_observation_buffer_address(&self.client.program_id, &self.client.swap_pubkey).0,
+            sysvar::clock::id(),
+        ];
+        if swap.openbook_market != Pubkey::default() {
+            keys.push(swap.openbook_market);
+            if let Some(market) = self.openbook_market() {
+                keys.extend([market.bids, market.asks]);
+            }
+        }
+        keys
+    }
+
+    /// Store the data of the accounts read by a quote, the token-swap first
+    pub fn update(&mut self, accounts: &HashMap<Pubkey, Vec<u8>>) -> Result<(), ProgramError> {
+        if let Some(data) = accounts.get(&self.client.swap_pubkey) {
+            self.client.swap = SwapInfo::unpack(data)?;
+        }
+        for key in self.get_accounts_to_update() {
+            if let Some(data) = accounts.get(&key) {
+                self.account_data.insert(key, data.clone());
+            }
+        }
+        self.client.openbook_book = self
+            .openbook_market()
+            .map(|market| (market.bids, market.asks));
+        Ok(())
+    }
+
+    /// Quote a swap selling an exact amount, as the program would in the slot
+    /// of the clock last updated. Trader fee discounts are left out.
+    pub fn quote(&self, params: &QuoteParams) -> Result<Quote, ProgramError> {
+        let swap_direction = self.swap_direction(&params.input_mint, &params.output_mint)?;
+        if self.client.swap.is_paused {
+            return Err(SwapError::IsPaused.into());
+        }
+
+        let mut swap_data = vec![0; SwapInfo::LEN];
+        self.client.swap.pack_into_slice(&mut swap_data);
+        let token_swap = SwapInfoData::load(&swap_data)?;
+        let reserve_in = Account::unpack(self.data(&match swap_direction {
+            SwapDirection::SellBase => token_swap.token_a,
+            SwapDirection::SellQuote => token_swap.token_b,
+        })?)?
+        .amount;
+
+        let mut clock = self.quoted_account(sysvar::clock::id(), sysvar::id())?;
+        let clock = Clock::from_account_info(&clock.account_info())?;
+        let observation_buffer_pubkey =
+            find_observation_buffer_address(&self.client.program_id, &self.client.swap_pubkey).0;
+        let mut observation_buffer =
+            self.quoted_account(observation_buffer_pubkey, self.client.program_id)?;
+        let mut pyth_a = self.quoted_account(token_swap.pyth_a, Pubkey::default())?;
+        let mut pyth_b = self.quoted_account(token_swap.pyth_b, Pubkey::default())?;
+        // the market prices the pool once its bids and asks are updated too
+        let mut openbook_accounts = self.openbook_market().and_then(|market| {
+            Some([
+                self.quoted_account(token_swap.openbook_market, openbook::id())
+                    .ok()?,
+                self.quoted_account(market.bids, openbook::id()).ok()?,
+                self.quoted_account(market.asks, openbook::id()).ok()?,
+            ])
+        });
+        let openbook_infos = openbook_accounts.as_mut().map(|[market, bids, asks]| {
+            (
+                market.account_info(),
+                bids.account_info(),
+                asks.account_info(),
+            )
+        });
+
+        let (state, _, quote) = quote_market_swap(
+            token_swap,
+            &observation_buffer.account_info(),
+            &pyth_a.account_info(),
+            &pyth_b.account_info(),
+            openbook_infos
+                .as_ref()
+                .map(|(market, bids, asks)| (market, bids, asks)),
+            &clock,
+            reserve_in,
+            params.amount,
+            swap_direction,
+        )?;
+        let (fee_direction, fee_amount, _) =
+            collected_trade_fee(token_swap, &state, swap_direction, &quote)?;
+        let receive_amount = quote
+            .amount_out
+            .checked_add(quote.trade_fee)
+            .ok_or(SwapError::CalculationFailure)?;
+        let fee_pct = if receive_amount == 0 {
+            Decimal::zero()
+        } else {
+            Decimal::from(quote.trade_fee).try_div(receive_amount)?
+        };
+
+        Ok(Quote {
+            in_amount: params.amount,
+            out_amount: quote.amount_out,
+            fee_amount,
+            fee_mint: match fee_direction {
+                SwapDirection::SellBase => token_swap.token_b_mint,
+                SwapDirection::SellQuote => token_swap.token_a_mint,
+            },
+            fee_pct,
+        })
+    }
+
+    /// Accounts of the swap instruction routed through the pool. The DELFI
+    /// reward goes to the associated token account of the transfer authority,
+    /// which must exist.
+    pub fn get_swap_and_account_metas(
+        &self,
+        params: &SwapParams,
+    ) -> Result<Vec<AccountMeta>, ProgramError> {
+        let swap_direction = self.swap_direction(&params.source_mint, &params.destination_mint)?;
+        let (base_pubkey, quote_pubkey) = match swap_direction {
+            SwapDirection::SellBase => (
+                params.source_token_account,
+                params.destination_token_account,
+            ),
+            SwapDirection::SellQuote => (
+                params.destination_token_account,
+                params.source_token_account,
+            ),
+        };
+        let reward_token_pubkey = get_associated_token_address(
+            &params.token_transfer_authority,
+            &self.client.config.deltafi_mint,
+        );
+        let instruction = self.client.swap(
+            params.token_transfer_authority,
+            base_pubkey,
+            quote_pubkey,
+            reward_token_pubkey,
+            SwapData {
+                amount_in: 0,
+                minimum_amount_out: 0,
+                swap_direction,
+                max_price_impact_bps: 0,
+            },
+        )?;
+        Ok(instruction.accounts)
+    }
+
+    fn swap_direction(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+    ) -> Result<SwapDirection, ProgramError> {
+        let swap = &self.client.swap;
+        if *input_mint == swap.token_a_mint && *output_mint == swap.token_b_mint {
+            Ok(SwapDirection::SellBase)
+        } else if *input_mint == swap.token_b_mint && *output_mint == swap.token_a_mint {
+            Ok(SwapDirection::SellQuote)
+        } else {
+            Err(SwapError::IncorrectMint.into())
+        }
+    }
+
+    fn data(&self, key: &Pubkey) -> Result<&[u8], ProgramError> {
+        self.account_data
+            .get(key)
+            .map(Vec::as_slice)
+            .ok_or(ProgramError::UninitializedAccount)
+    }
+
+    fn quoted_account(&self, key: Pubkey, owner: Pubkey) -> Result<QuotedAccount, ProgramError> {
+        Ok(QuotedAccount::new(key, owner, self.data(&key)?.to_vec()))
+    }
+
+    /// OpenBook market of the pool, once updated
+    fn openbook_market(&self) -> Option<openbook::MarketState> {
+        if self.client.swap.openbook_market == Pubkey::default() {
+            return None;
+        }
+        self.account_data
+            .get(&self.client.swap.openbook_market)
+            .and_then(|data| openbook::load_market(data).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::{find_market_authority, find_swap_authority},
+        curve::{default_market_price, default_slope, PoolState},
+        openbook::{
+            test::{market_data, slab_data},
+            Side,
+        },
+        pyth,
+        state::{ConfigInfo, DEFAULT_TEST_FEES, DEFAULT_TEST_REWARDS, PROGRAM_VERSION},
+    };
+    use spl_token::state::AccountState;
+
+    fn token_account_data(mint: Pubkey, amount: u64) -> Vec<u8> {
+        let mut data = vec![0; Account::LEN];
+        Account {
+            mint,
+            owner: Pubkey::new_unique(),
+            amount,
+            state: AccountState::Initialized,
+            ..Account::default()
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+
+    /// Pool of 1_000_000 base and 100_000_000 quote priced at 100 with stale
+    /// oracles, along with the data of the accounts its quotes read
+    fn test_amm() -> (DeltafiAmm, HashMap<Pubkey, Vec<u8>>) {
+        let program_id = crate::id();
+        let config_pubkey = Pubkey::new_unique();
+        let swap_pubkey = Pubkey::new_unique();
+        let (_, bump_seed) = find_market_authority(&program_id, &config_pubkey);
+        let (_, nonce) = find_swap_authority(&program_id, &swap_pubkey);
+
+        let mut pool_state = PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            ..PoolState::default()
+        })
+        .unwrap();
+        pool_state.buy_shares(1_000_000, 100_000_000, 0).unwrap();
+        let swap = SwapInfo {
+            is_initialized: true,
+            nonce,
+            token_a: Pubkey::new_unique(),
+            token_b: Pubkey::new_unique(),
+            pool_mint: Pubkey::new_unique(),
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            admin_fee_key_a: Pubkey::new_unique(),
+            admin_fee_key_b: Pubkey::new_unique(),
+            fees: DEFAULT_TEST_FEES,
+            rewards: DEFAULT_TEST_REWARDS,
+            pool_state,
+            pyth_a: Pubkey::new_unique(),
+            pyth_b: Pubkey::new_unique(),
+            config_key: config_pubkey,
+            version: PROGRAM_VERSION,
+            ..SwapInfo::default()
+        };
+        let client = SwapClient::new(
+            program_id,
+            config_pubkey,
+            ConfigInfo {
+                version: PROGRAM_VERSION,
+                bump_seed,
+                deltafi_mint: Pubkey::new_unique(),
+                ..ConfigInfo::default()
+            },
+            swap_pubkey,
+            swap.clone(),
+        );
+
+        let mut swap_data = vec![0; SwapInfo::LEN];
+        swap.pack_into_slice(&mut swap_data);
+        let mut clock =
+            QuotedAccount::new(sysvar::clock::id(), sysvar::id(), vec![0; Clock::size_of()]);
+        Clock {
+            slot: 100,
+            unix_timestamp: 1_000,
+            ..Clock::default()
+        }
+        .to_account_info(&mut clock.account_info())
+        .unwrap();
+
+        let mut accounts = HashMap::new();
+        accounts.insert(swap_pubkey, swap_data);
+        accounts.insert(
+            swap.token_a,
+            token_account_data(swap.token_a_mint, 1_000_000),
+        );
+        accounts.insert(
+            swap.token_b,
+            token_account_data(swap.token_b_mint, 100_000_000),
+        );
+        accounts.insert(
+            find_observation_buffer_address(&program_id, &swap_pubkey).0,
+            vec![],
+        );
+        accounts.insert(swap.pyth_a, pyth::test::price_data(1, 0, 0));
+        accounts.insert(swap.pyth_b, pyth::test::price_data(1, 0, 0));
+        accounts.insert(sysvar::clock::id(), clock.data);
+        (DeltafiAmm::new(client), accounts)
+    }
+
+    /// Set an OpenBook market quoting 149/151 on the pool, returning its
+    /// market, bids and asks
+    fn add_openbook_market(
+        amm: &DeltafiAmm,
+        accounts: &mut HashMap<Pubkey, Vec<u8>>,
+    ) -> (Pubkey, Pubkey, Pubkey) {
+        let (market, bids, asks) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut swap = amm.client.swap.clone();
+        swap.openbook_market = market;
+        swap.pack_into_slice(accounts.get_mut(&amm.key()).unwrap());
+        accounts.insert(
+            market,
+            market_data(
+                &market,
+                &swap.token_a_mint,
+                &swap.token_b_mint,
+                &bids,
+                &asks,
+                1,
+                1,
+            ),
+        );
+        accounts.insert(bids, slab_data(Side::Bids, &[149]));
+        accounts.insert(asks, slab_data(Side::Asks, &[151]));
+        (market, bids, asks)
+    }
+
+    #[test]
+    fn test_accounts_to_update() {
+        let (mut amm, mut accounts) = test_amm();
+        let swap = amm.client.swap.clone();
+        let observation_buffer = find_observation_buffer_address(&crate::id(), &amm.key()).0;
+        let keys = vec![
+            amm.key(),
+            swap.token_a,
+            swap.token_b,
+            swap.pyth_a,
+            swap.pyth_b,
+            observation_buffer,
+            sysvar::clock::id(),
+        ];
+        assert_eq!(amm.get_accounts_to_update(), keys);
+        assert_eq!(
+            amm.get_reserve_mints(),
+            vec![swap.token_a_mint, swap.token_b_mint]
+        );
+
+        // the slabs of the market are read once the market is
+        let (market, bids, asks) = add_openbook_market(&amm, &mut accounts);
+        amm.update(&accounts).unwrap();
+        assert_eq!(amm.client.swap.openbook_market, market);
+        let mut expect = keys.clone();
+        expect.extend([market, bids, asks]);
+        assert_eq!(amm.get_accounts_to_update(), expect);
+    }
+
+    #[test]
+    fn test_quote() {
+        let (mut amm, mut accounts) = test_amm();
+        assert_eq!(
+            amm.quote(&QuoteParams {
+                amount: 1_000,
+                input_mint: amm.client.swap.token_a_mint,
+                output_mint: amm.client.swap.token_b_mint,
+            }),
+            Err(ProgramError::UninitializedAccount)
+        );
+        amm.update(&accounts).unwrap();
+
+        // with stale oracles the pool is priced at its mid price
+        let swap = amm.client.swap.clone();
+        for (swap_direction, input_mint, output_mint) in [
+            (
+                SwapDirection::SellBase,
+                swap.token_a_mint,
+                swap.token_b_mint,
+            ),
+            (
+                SwapDirection::SellQuote,
+                swap.token_b_mint,
+                swap.token_a_mint,
+            ),
+        ] {
+            let expect = swap.quote_swap(1_000, swap_direction).unwrap();
+            let quote = amm
+                .quote(&QuoteParams {
+                    amount: 1_000,
+                    input_mint,
+                    output_mint,
+                })
+                .unwrap();
+            assert_eq!(
+                quote,
+                Quote {
+                    in_amount: 1_000,
+                    out_amount: expect.amount_out,
+                    fee_amount: expect.trade_fee,
+                    fee_mint: output_mint,
+                    fee_pct: Decimal::from(expect.trade_fee)
+                        .try_div(expect.amount_out + expect.trade_fee)
+                        .unwrap(),
+                }
+            );
+        }
+        assert_eq!(
+            amm.quote(&QuoteParams {
+                amount: 1_000,
+                input_mint: swap.token_a_mint,
+                output_mint: Pubkey::new_unique(),
+            }),
+            Err(SwapError::IncorrectMint.into())
+        );
+
+        // the OpenBook market prices the pool once all its accounts are read
+        add_openbook_market(&amm, &mut accounts);
+        let params = QuoteParams {
+            amount: 1_000,
+            input_mint: swap.token_a_mint,
+            output_mint: swap.token_b_mint,
+        };
+        amm.update(&accounts).unwrap();
+        assert_eq!(
+            amm.quote(&params).unwrap().out_amount,
+            swap.quote_swap(1_000, SwapDirection::SellBase)
+                .unwrap()
+                .amount_out
+        );
+        amm.update(&accounts).unwrap();
+        let repriced = SwapInfo {
+            pool_state: PoolState::new(PoolState {
+                market_price: Decimal::from(150u64),
+                ..swap.pool_state.clone()
+            })
+            .unwrap(),
+            ..swap
+        };
+        assert_eq!(
+            amm.quote(&params).unwrap().out_amount,
+            repriced
+                .quote_swap(1_000, SwapDirection::SellBase)
+                .unwrap()
+                .amount_out
+        );
+    }
+
+    #[test]
+    fn test_swap_account_metas() {
+        let (mut amm, mut accounts) = test_amm();
+        amm.update(&accounts).unwrap();
+        let swap = amm.client.swap.clone();
+        let (source, destination, authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let reward_token =
+            get_associated_token_address(&authority, &amm.client.config.deltafi_mint);
+        let swap_data = |swap_direction| SwapData {
+            amount_in: 0,
+            minimum_amount_out: 0,
+            swap_direction,
+            max_price_impact_bps: 0,
+        };
+
+        let params = SwapParams {
+            source_mint: swap.token_a_mint,
+            destination_mint: swap.token_b_mint,
+            source_token_account: source,
+            destination_token_account: destination,
+            token_transfer_authority: authority,
+        };
+        assert_eq!(
+            amm.get_swap_and_account_metas(&params).unwrap(),
+            amm.client
+                .swap(
+                    authority,
+                    source,
+                    destination,
+                    reward_token,
+                    swap_data(SwapDirection::SellBase)
+                )
+                .unwrap()
+                .accounts
+        );
+        let sell_quote = SwapParams {
+            source_mint: swap.token_b_mint,
+            destination_mint: swap.token_a_mint,
+            ..params.clone()
+        };
+        assert_eq!(
+            amm.get_swap_and_account_metas(&sell_quote).unwrap(),
+            amm.client
+                .swap(
+                    authority,
+                    destination,
+                    source,
+                    reward_token,
+                    swap_data(SwapDirection::SellQuote)
+                )
+                .unwrap()
+                .accounts
+        );
+
+        // the OpenBook market follows the accounts of the swap
+        let (market, bids, asks) = add_openbook_market(&amm, &mut accounts);
+        amm.update(&accounts).unwrap();
+        let metas = amm.get_swap_and_account_metas(&params).unwrap();
+        let keys: Vec<Pubkey> = metas[metas.len() - 3..]
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert_eq!(keys, vec![market, bids, asks]);
+    }
+}
@@ -3,14 +3,28 @@
 //! An Uniswap-like program for the Solana blockchain.
 
 pub mod admin;
+#[cfg(feature = "anchor-cpi")]
+pub mod anchor;
+#[cfg(not(target_arch = "bpf"))]
+pub mod client;
 pub mod curve;
 pub mod entrypoint;
 pub mod error;
+pub mod event;
 pub mod instruction;
+pub mod interface;
+#[cfg(not(target_arch = "bpf"))]
+pub mod jupiter;
 pub mod math;
+pub mod metadata;
+pub mod openbook;
 pub mod processor;
 pub mod pyth;
 pub mod state;
+#[cfg(feature = "test-sdk")]
+pub mod test_sdk;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // Export current solana-program types for downstream users who may also be
 // building with a different solana-program version
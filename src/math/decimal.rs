@@ -1,12 +1,12 @@
 //! Math for preserving precision of token amounts which are limited
 //! by the SPL Token program to be at most u64::MAX.
 //!
-//! Decimals are internally scaled by a WAD (10^18) to preserve
-//! precision up to 18 decimal places. Decimals are sized to support
-//! both serialization and precise math for the full range of
-//! unsigned 64-bit integers. The underlying representation is a
-//! u192 rather than u256 to reduce compute cost while losing
-//! support for arithmetic operations at the high end of u64 range.
+//! Decimals are internally scaled by a WAD (10^9) to preserve
+//! precision up to 9 decimal places. Decimals are serialized as u128
+//! scaled values, while intermediate math runs on a u192 so products
+//! of WAD-scaled reserves and prices do not overflow for the full
+//! range of unsigned 64-bit token amounts. A u192 rather than a u256
+//! keeps the compute cost down.
 
 #![allow(clippy::assign_op_pattern)]
 #![allow(clippy::ptr_offset_with_cast)]
@@ -15,7 +15,7 @@
 use super::*;
 use crate::error::SwapError;
 use solana_program::program_error::ProgramError;
-use std::{convert::TryFrom, fmt};
+use std::{convert::TryFrom, fmt, str::FromStr};
 
 use uint::construct_uint;
 
@@ -23,7 +23,32 @@ construct_uint! {
     pub struct U192(3);
 }
 
-/// Large decimal values, precise to 18 digits
+construct_uint! {
+    /// Intermediate of the fused multiply-divide, so products past u192 can
+    /// still be divided back into range
+    struct U256(4);
+}
+
+impl From<U192> for U256 {
+    fn from(val: U192) -> Self {
+        let U192([a, b, c]) = val;
+        Self([a, b, c, 0])
+    }
+}
+
+impl TryFrom<U256> for U192 {
+    type Error = ProgramError;
+
+    fn try_from(val: U256) -> Result<Self, Self::Error> {
+        let U256([a, b, c, d]) = val;
+        if d != 0 {
+            return Err(SwapError::CalculationFailure.into());
+        }
+        Ok(Self([a, b, c]))
+    }
+}
+
+/// Large decimal values, precise to 9 digits
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub struct Decimal(pub U192);
 
@@ -138,6 +163,110 @@ impl Decimal {
         ))
     }
 
+    /// Square root at full precision.
+    ///
+    /// The result is rounded down to 9 decimals: `r * r <= self` and
+    /// `(r + 0.000000001)^2 > self`. Unlike [sqrt](#method.sqrt), the radicand
+    /// is not rounded to an integer first.
+    pub fn try_sqrt(&self) -> Result<Self, ProgramError> {
+        Ok(Self(
+            self.0
+                .checked_mul(Self::wad())
+                .ok_or(SwapError::CalculationFailure)?
+                .integer_sqrt(),
+        ))
+    }
+
+    /// Calculates base^exp by squaring.
+    ///
+    /// Each multiplication is rounded down to 9 decimals, so the result never
+    /// exceeds the exact power. Fails when an intermediate product overflows.
+    pub fn try_pow(&self, mut exp: u64) -> Result<Self, ProgramError> {
+        let mut base = *self;
+        let mut ret = Self::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                ret = ret.try_mul(base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.try_mul(base)?;
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Multiply, rounding the product down to 9 decimals.
+    /// Same as `try_mul`, named for call sites where the rounding side matters.
+    pub fn try_mul_floor(self, rhs: Self) -> Result<Self, ProgramError> {
+        self.try_mul(rhs)
+    }
+
+    /// Multiply, rounding the product up to 9 decimals
+    pub fn try_mul_ceil(self, rhs: Self) -> Result<Self, ProgramError> {
+        Ok(Self(
+            self.0
+                .checked_mul(rhs.0)
+                .ok_or(SwapError::CalculationFailure)?
+                .checked_add(Self::wad() - U192::one())
+                .ok_or(SwapError::CalculationFailure)?
+                .checked_div(Self::wad())
+                .ok_or(SwapError::CalculationFailure)?,
+        ))
+    }
+
+    /// Divide, rounding the quotient down to 9 decimals.
+    /// Same as `try_div`, named for call sites where the rounding side matters.
+    pub fn try_div_floor(self, rhs: Self) -> Result<Self, ProgramError> {
+        self.try_div(rhs)
+    }
+
+    /// Divide, rounding the quotient up to 9 decimals
+    pub fn try_div_ceil(self, rhs: Self) -> Result<Self, ProgramError> {
+        if rhs.is_zero() {
+            return Err(SwapError::CalculationFailure.into());
+        }
+        Ok(Self(
+            self.0
+                .checked_mul(Self::wad())
+                .ok_or(SwapError::CalculationFailure)?
+                .checked_add(rhs.0 - U192::one())
+                .ok_or(SwapError::CalculationFailure)?
+                .checked_div(rhs.0)
+                .ok_or(SwapError::CalculationFailure)?,
+        ))
+    }
+
+    fn try_mul_div(self, mul: Self, div: Self, round_up: bool) -> Result<Self, ProgramError> {
+        if div.is_zero() {
+            return Err(SwapError::CalculationFailure.into());
+        }
+        let product = U256::from(self.0)
+            .checked_mul(U256::from(mul.0))
+            .ok_or(SwapError::CalculationFailure)?;
+        let div = U256::from(div.0);
+        let quotient = product / div;
+        let quotient = if round_up && !(product % div).is_zero() {
+            quotient + U256::one()
+        } else {
+            quotient
+        };
+        Ok(Self(U192::try_from(quotient)?))
+    }
+
+    /// Calculates self * mul / div with a single rounding down to 9 decimals.
+    ///
+    /// The product is kept on 256 bits, so it fails only when the result
+    /// does not fit, not when `self * mul` alone would overflow.
+    pub fn try_mul_div_floor(self, mul: Self, div: Self) -> Result<Self, ProgramError> {
+        self.try_mul_div(mul, div, false)
+    }
+
+    /// Calculates self * mul / div with a single rounding up to 9 decimals
+    pub fn try_mul_div_ceil(self, mul: Self, div: Self) -> Result<Self, ProgramError> {
+        self.try_mul_div(mul, div, true)
+    }
+
     /// Reciprocal decimal
     pub fn reciprocal(&self) -> Result<Self, ProgramError> {
         Ok(Self(
@@ -163,6 +292,18 @@ impl fmt::Display for Decimal {
     }
 }
 
+impl FromStr for Decimal {
+    type Err = ProgramError;
+
+    /// Parse a decimal written like its `Display`, up to 9 decimals
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        let digits = scaled_digits(val).ok_or(ProgramError::InvalidArgument)?;
+        Ok(Self(
+            U192::from_dec_str(&digits).map_err(|_| SwapError::CalculationFailure)?,
+        ))
+    }
+}
+
 impl Default for Decimal {
     fn default() -> Self {
         Self::zero()
@@ -454,4 +595,178 @@ mod test {
 
         assert_eq!(U192::exp10(SCALE), Decimal::wad());
     }
+
+    #[test]
+    fn test_try_sqrt() {
+        assert_eq!(Decimal::zero().try_sqrt().unwrap(), Decimal::zero());
+        assert_eq!(Decimal::one().try_sqrt().unwrap(), Decimal::one());
+        assert_eq!(Decimal::from(4u64).try_sqrt().unwrap(), Decimal::from(2u64));
+        assert_eq!(
+            Decimal::from(2u64).try_sqrt().unwrap(),
+            Decimal::from_scaled_val(1_414_213_562)
+        );
+        // smallest decimal, 0.000000001
+        assert_eq!(
+            Decimal::from_scaled_val(1).try_sqrt().unwrap(),
+            Decimal::from_scaled_val(31_622)
+        );
+        assert_eq!(
+            Decimal::from(u64::MAX).try_sqrt().unwrap(),
+            Decimal::from_scaled_val(4_294_967_295_999_999_999)
+        );
+        assert_eq!(
+            Decimal::from(u128::MAX).try_sqrt().unwrap(),
+            Decimal::from_scaled_val(18_446_744_073_709_551_615_999_999_999)
+        );
+        assert!(Decimal(U192::MAX).try_sqrt().is_err());
+
+        let unit = Decimal::from_scaled_val(1);
+        for scaled_val in [
+            2,
+            3,
+            HALF_WAD as u128,
+            WAD as u128 - 1,
+            WAD as u128 + 1,
+            123_456_789_012_345,
+            u64::MAX as u128,
+            u128::MAX,
+        ] {
+            let value = Decimal::from_scaled_val(scaled_val);
+            let root = value.try_sqrt().unwrap();
+            assert!(root.try_mul(root).unwrap() <= value);
+            let next = root.try_add(unit).unwrap();
+            assert!(next.0 * next.0 > value.0 * Decimal::wad());
+        }
+    }
+
+    #[test]
+    fn test_try_pow() {
+        let two = Decimal::from(2u64);
+        assert_eq!(two.try_pow(0).unwrap(), Decimal::one());
+        assert_eq!(two.try_pow(1).unwrap(), two);
+        assert_eq!(two.try_pow(10).unwrap(), Decimal::from(1024u64));
+        assert_eq!(two.try_pow(63).unwrap(), Decimal::from(1u64 << 63));
+        assert_eq!(two.try_pow(127).unwrap(), Decimal::from(1u128 << 127));
+        assert!(two.try_pow(192).is_err());
+
+        assert_eq!(Decimal::zero().try_pow(0).unwrap(), Decimal::one());
+        assert_eq!(Decimal::zero().try_pow(5).unwrap(), Decimal::zero());
+        assert_eq!(Decimal::one().try_pow(u64::MAX).unwrap(), Decimal::one());
+
+        let half = Decimal::one().try_div(2).unwrap();
+        assert_eq!(
+            half.try_pow(3).unwrap(),
+            Decimal::from_scaled_val(125_000_000)
+        );
+        // rounded down at every step, past the precision it floors to zero
+        assert_eq!(half.try_pow(30).unwrap(), Decimal::from_scaled_val(0));
+        assert_eq!(half.try_pow(u64::MAX).unwrap(), Decimal::zero());
+
+        let value = Decimal::from_scaled_val(1_100_000_000);
+        assert_eq!(
+            value.try_pow(7).unwrap(),
+            Decimal::from_scaled_val(1_948_717_100)
+        );
+        assert_eq!(value.try_pow(2).unwrap().try_sqrt().unwrap(), value);
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let third = Decimal::one().try_div(3).unwrap();
+        let two = Decimal::from(2u64);
+        let three = Decimal::from(3u64);
+
+        assert_eq!(
+            third.try_mul_floor(third).unwrap(),
+            Decimal::from_scaled_val(111_111_110)
+        );
+        assert_eq!(
+            third.try_mul_ceil(third).unwrap(),
+            Decimal::from_scaled_val(111_111_111)
+        );
+        assert_eq!(two.try_mul_ceil(three).unwrap(), Decimal::from(6u64));
+        assert_eq!(
+            Decimal::zero().try_mul_ceil(three).unwrap(),
+            Decimal::zero()
+        );
+        assert_eq!(
+            Decimal::from_scaled_val(1)
+                .try_mul_ceil(Decimal::from_scaled_val(1))
+                .unwrap(),
+            Decimal::from_scaled_val(1)
+        );
+        assert!(Decimal(U192::MAX).try_mul_ceil(two).is_err());
+
+        assert_eq!(
+            two.try_div_floor(three).unwrap(),
+            Decimal::from_scaled_val(666_666_666)
+        );
+        assert_eq!(
+            two.try_div_ceil(three).unwrap(),
+            Decimal::from_scaled_val(666_666_667)
+        );
+        assert_eq!(Decimal::from(6u64).try_div_ceil(three).unwrap(), two);
+        assert_eq!(
+            Decimal::zero().try_div_ceil(three).unwrap(),
+            Decimal::zero()
+        );
+        assert!(two.try_div_ceil(Decimal::zero()).is_err());
+        assert!(two.try_div_floor(Decimal::zero()).is_err());
+    }
+
+    #[test]
+    fn test_large_reserves() {
+        // max token amount priced at one trillion, the scaled product is past u128
+        let reserve = Decimal::from(u64::MAX);
+        let price = Decimal::from(1_000_000_000_000u64);
+        let value = reserve.try_mul(price).unwrap();
+        assert!(value.to_scaled_val().is_err());
+        assert_eq!(value.try_div(price).unwrap(), reserve);
+        assert_eq!(value.try_div(reserve).unwrap(), price);
+    }
+
+    #[test]
+    fn test_from_str() {
+        let value = Decimal::from(1_234u64)
+            .try_add(Decimal::from_scaled_val(5))
+            .unwrap();
+        assert_eq!(value.to_string(), "1234.000000005");
+        assert_eq!(value.to_string().parse::<Decimal>().unwrap(), value);
+        assert_eq!("0.5".parse::<Decimal>().unwrap(), Decimal::from_percent(50));
+        assert_eq!(
+            "0.0000000001".parse::<Decimal>().unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn test_try_mul_div() {
+        let two = Decimal::from(2u64);
+        let three = Decimal::from(3u64);
+
+        assert_eq!(
+            two.try_mul_div_floor(two, three).unwrap(),
+            Decimal::from_scaled_val(1_333_333_333)
+        );
+        assert_eq!(
+            two.try_mul_div_ceil(two, three).unwrap(),
+            Decimal::from_scaled_val(1_333_333_334)
+        );
+        assert_eq!(three.try_mul_div_ceil(two, three).unwrap(), two);
+
+        // a single rounding keeps the digits two roundings lose
+        let third = Decimal::one().try_div(3).unwrap();
+        assert_eq!(
+            third.try_mul(third).unwrap().try_div(third).unwrap(),
+            Decimal::from_scaled_val(333_333_330)
+        );
+        assert_eq!(third.try_mul_div_floor(third, third).unwrap(), third);
+
+        // the product may overflow u192 as long as the result fits
+        let max = Decimal(U192::MAX);
+        assert!(max.try_mul(two).is_err());
+        assert_eq!(max.try_mul_div_floor(two, two).unwrap(), max);
+        assert!(max.try_mul_div_floor(three, two).is_err());
+        assert!(two.try_mul_div_floor(two, Decimal::zero()).is_err());
+    }
 }
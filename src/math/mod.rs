@@ -8,10 +8,12 @@
 mod approximations;
 mod decimal;
 mod rate;
+mod sdecimal;
 
 pub use approximations::*;
 pub use decimal::*;
 pub use rate::*;
+pub use sdecimal::*;
 
 use solana_program::program_error::ProgramError;
 
@@ -23,6 +25,8 @@ pub const WAD: u64 = 1_000_000_000;
 pub const HALF_WAD: u64 = 500_000_000;
 /// Scale for percentages
 pub const PERCENT_SCALER: u64 = 10_000_000;
+/// Basis points denominator
+pub const BPS_DENOMINATOR: u64 = 10_000;
 
 /// Try to subtract, return an error on underflow
 pub trait TrySub: Sized {
@@ -48,6 +52,46 @@ pub trait TryMul<RHS>: Sized {
     fn try_mul(self, rhs: RHS) -> Result<Self, ProgramError>;
 }
 
+/// Digits of a decimal string scaled by a WAD, none when the string is not a
+/// plain decimal or has more digits than the precision
+fn scaled_digits(val: &str) -> Option<String> {
+    let (integer, fraction) = val.split_once('.').unwrap_or((val, ""));
+    if integer.is_empty()
+        || fraction.len() > SCALE
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|digit| digit.is_ascii_digit())
+    {
+        return None;
+    }
+    Some(format!("{}{:0<width$}", integer, fraction, width = SCALE))
+}
+
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_with_display {
+    ($($math:ty),*) => {
+        $(
+            impl serde::Serialize for $math {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for $math {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let val = String::deserialize(deserializer)?;
+                    val.parse()
+                        .map_err(|_| serde::de::Error::custom(format!("invalid decimal {}", val)))
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+impl_serde_with_display!(Decimal, Rate);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,4 +104,37 @@ mod tests {
         assert_eq!(base_num.pow(base_scale) / 2, HALF_WAD);
         assert_eq!(base_num.pow(base_scale - 2), PERCENT_SCALER);
     }
+
+    #[test]
+    fn test_scaled_digits() {
+        assert_eq!(scaled_digits("12").unwrap(), "12000000000");
+        assert_eq!(scaled_digits("0.5").unwrap(), "0500000000");
+        assert_eq!(scaled_digits("1.").unwrap(), "1000000000");
+        assert_eq!(scaled_digits("3.000000001").unwrap(), "3000000001");
+        assert_eq!(scaled_digits("3.0000000001"), None);
+        assert_eq!(scaled_digits(".5"), None);
+        assert_eq!(scaled_digits("-1"), None);
+        assert_eq!(scaled_digits("1e9"), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let decimal = Decimal::from(u64::MAX)
+            .try_add(Decimal::from_scaled_val(1))
+            .unwrap();
+        let serialized = serde_yaml::to_string(&decimal).unwrap();
+        assert!(serialized.contains("18446744073709551615.000000001"));
+        assert_eq!(
+            serde_yaml::from_str::<Decimal>(&serialized).unwrap(),
+            decimal
+        );
+
+        let rate = Rate::from_scaled_val(1);
+        let serialized = serde_yaml::to_string(&rate).unwrap();
+        assert!(serialized.contains("0.000000001"));
+        assert_eq!(serde_yaml::from_str::<Rate>(&serialized).unwrap(), rate);
+
+        assert!(serde_yaml::from_str::<Decimal>("1.0000000001").is_err());
+    }
 }
@@ -21,7 +21,7 @@
 use super::*;
 use crate::error::SwapError;
 use solana_program::program_error::ProgramError;
-use std::{convert::TryFrom, fmt};
+use std::{convert::TryFrom, fmt, str::FromStr};
 
 use uint::construct_uint;
 
@@ -80,26 +80,35 @@ impl Rate {
         Ok(u64::try_from(rounded_val).map_err(|_| SwapError::CalculationFailure)?)
     }
 
-    /// Calculates base^exp
+    /// Calculates base^exp by squaring.
+    ///
+    /// Each multiplication is rounded down to 9 decimals, so the result never
+    /// exceeds the exact power. Fails when an intermediate product overflows.
     pub fn try_pow(&self, mut exp: u64) -> Result<Rate, ProgramError> {
         let mut base = *self;
-        let mut ret = if exp % 2 != 0 {
-            base
-        } else {
-            Rate(Self::wad())
-        };
-
+        let mut ret = Self::one();
         while exp > 0 {
-            exp /= 2;
-            base = base.try_mul(base)?;
-
-            if exp % 2 != 0 {
+            if exp & 1 == 1 {
                 ret = ret.try_mul(base)?;
             }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.try_mul(base)?;
+            }
         }
-
         Ok(ret)
     }
+
+    /// Square root, rounded down to 9 decimals: `r * r <= self` and
+    /// `(r + 0.000000001)^2 > self`.
+    pub fn try_sqrt(&self) -> Result<Rate, ProgramError> {
+        Ok(Self(
+            self.0
+                .checked_mul(Self::wad())
+                .ok_or(SwapError::CalculationFailure)?
+                .integer_sqrt(),
+        ))
+    }
 }
 
 impl fmt::Display for Rate {
@@ -115,6 +124,18 @@ impl fmt::Display for Rate {
     }
 }
 
+impl FromStr for Rate {
+    type Err = ProgramError;
+
+    /// Parse a decimal written like its `Display`, up to 9 decimals
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        let digits = scaled_digits(val).ok_or(ProgramError::InvalidArgument)?;
+        Ok(Self(
+            U128::from_dec_str(&digits).map_err(|_| SwapError::CalculationFailure)?,
+        ))
+    }
+}
+
 impl TryFrom<Decimal> for Rate {
     type Error = ProgramError;
     fn try_from(decimal: Decimal) -> Result<Self, Self::Error> {
@@ -327,4 +348,46 @@ mod test {
             Rate::from_scaled_val(2)
         );
     }
+
+    #[test]
+    fn test_try_pow_and_sqrt() {
+        let two = Rate::from_scaled_val(WAD as u128 * 2);
+        assert_eq!(two.try_pow(1).unwrap(), two);
+        assert_eq!(
+            two.try_pow(64).unwrap(),
+            Rate::from_scaled_val(WAD as u128 * (1u128 << 64))
+        );
+        assert!(two.try_pow(80).is_err());
+        assert_eq!(Rate::zero().try_pow(0).unwrap(), Rate::one());
+        assert_eq!(Rate::zero().try_pow(3).unwrap(), Rate::zero());
+        assert_eq!(Rate::one().try_pow(u64::MAX).unwrap(), Rate::one());
+        assert_eq!(
+            Rate::from_percent(50).try_pow(u64::MAX).unwrap(),
+            Rate::zero()
+        );
+
+        assert_eq!(Rate::zero().try_sqrt().unwrap(), Rate::zero());
+        assert_eq!(Rate::one().try_sqrt().unwrap(), Rate::one());
+        assert_eq!(
+            Rate::from_percent(25).try_sqrt().unwrap(),
+            Rate::from_percent(50)
+        );
+        assert_eq!(
+            two.try_sqrt().unwrap(),
+            Rate::from_scaled_val(1_414_213_562)
+        );
+        assert_eq!(
+            Rate::from_scaled_val(1).try_sqrt().unwrap(),
+            Rate::from_scaled_val(31_622)
+        );
+        assert!(Rate::from_scaled_val(u128::MAX).try_sqrt().is_err());
+
+        for scaled_val in [2, 3, WAD as u128 - 1, WAD as u128 + 1, 987_654_321_987] {
+            let value = Rate::from_scaled_val(scaled_val);
+            let root = value.try_sqrt().unwrap();
+            assert!(root.try_mul(root).unwrap() <= value);
+            let next = root.0 + 1;
+            assert!(next * next > value.0 * Rate::wad());
+        }
+    }
 }
@@ -0,0 +1,222 @@
+//! Signed decimals for intermediate values that can go below zero, like price
+//! deviations or profit and loss.
+//!
+//! A signed decimal is a [Decimal](struct.Decimal.html) magnitude with a sign,
+//! so it keeps the same WAD scale and precision. Zero is never negative.
+
+use super::*;
+use crate::error::SwapError;
+use solana_program::program_error::ProgramError;
+use std::{cmp::Ordering, convert::TryFrom, fmt, ops::Neg};
+
+/// Signed decimal values, precise to 9 digits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SDecimal {
+    value: Decimal,
+    is_negative: bool,
+}
+
+impl SDecimal {
+    fn new(value: Decimal, is_negative: bool) -> Self {
+        Self {
+            value,
+            is_negative: is_negative && !value.is_zero(),
+        }
+    }
+
+    /// One
+    pub fn one() -> Self {
+        Self::from(Decimal::one())
+    }
+
+    /// Zero
+    pub fn zero() -> Self {
+        Self::from(Decimal::zero())
+    }
+
+    /// Check if zero
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    /// Check if below zero
+    pub fn is_negative(&self) -> bool {
+        self.is_negative
+    }
+
+    /// Absolute value
+    pub fn abs(&self) -> Decimal {
+        self.value
+    }
+}
+
+impl From<Decimal> for SDecimal {
+    fn from(value: Decimal) -> Self {
+        Self::new(value, false)
+    }
+}
+
+impl From<u64> for SDecimal {
+    fn from(value: u64) -> Self {
+        Self::from(Decimal::from(value))
+    }
+}
+
+impl TryFrom<SDecimal> for Decimal {
+    type Error = ProgramError;
+    fn try_from(value: SDecimal) -> Result<Self, Self::Error> {
+        if value.is_negative {
+            Err(SwapError::CalculationFailure.into())
+        } else {
+            Ok(value.value)
+        }
+    }
+}
+
+impl Neg for SDecimal {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(self.value, !self.is_negative)
+    }
+}
+
+impl Ord for SDecimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_negative, other.is_negative) {
+            (false, false) => self.value.cmp(&other.value),
+            (true, true) => other.value.cmp(&self.value),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for SDecimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for SDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_negative {
+            f.write_str("-")?;
+        }
+        fmt::Display::fmt(&self.value, f)
+    }
+}
+
+impl TryAdd for SDecimal {
+    fn try_add(self, rhs: Self) -> Result<Self, ProgramError> {
+        if self.is_negative == rhs.is_negative {
+            return Ok(Self::new(self.value.try_add(rhs.value)?, self.is_negative));
+        }
+        if self.value >= rhs.value {
+            Ok(Self::new(self.value.try_sub(rhs.value)?, self.is_negative))
+        } else {
+            Ok(Self::new(rhs.value.try_sub(self.value)?, rhs.is_negative))
+        }
+    }
+}
+
+impl TrySub for SDecimal {
+    fn try_sub(self, rhs: Self) -> Result<Self, ProgramError> {
+        self.try_add(-rhs)
+    }
+}
+
+impl TryMul<u64> for SDecimal {
+    fn try_mul(self, rhs: u64) -> Result<Self, ProgramError> {
+        Ok(Self::new(self.value.try_mul(rhs)?, self.is_negative))
+    }
+}
+
+impl TryMul<Decimal> for SDecimal {
+    fn try_mul(self, rhs: Decimal) -> Result<Self, ProgramError> {
+        Ok(Self::new(self.value.try_mul(rhs)?, self.is_negative))
+    }
+}
+
+impl TryMul<SDecimal> for SDecimal {
+    fn try_mul(self, rhs: Self) -> Result<Self, ProgramError> {
+        Ok(Self::new(
+            self.value.try_mul(rhs.value)?,
+            self.is_negative != rhs.is_negative,
+        ))
+    }
+}
+
+impl TryDiv<u64> for SDecimal {
+    fn try_div(self, rhs: u64) -> Result<Self, ProgramError> {
+        Ok(Self::new(self.value.try_div(rhs)?, self.is_negative))
+    }
+}
+
+impl TryDiv<Decimal> for SDecimal {
+    fn try_div(self, rhs: Decimal) -> Result<Self, ProgramError> {
+        Ok(Self::new(self.value.try_div(rhs)?, self.is_negative))
+    }
+}
+
+impl TryDiv<SDecimal> for SDecimal {
+    fn try_div(self, rhs: Self) -> Result<Self, ProgramError> {
+        Ok(Self::new(
+            self.value.try_div(rhs.value)?,
+            self.is_negative != rhs.is_negative,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sdecimal() {
+        let one = SDecimal::one();
+        let two = SDecimal::from(2u64);
+        let minus_one = -one;
+
+        assert_eq!(SDecimal::default(), SDecimal::zero());
+        assert_eq!(-SDecimal::zero(), SDecimal::zero());
+        assert!(!(-SDecimal::zero()).is_negative());
+        assert!(minus_one.is_negative());
+        assert_eq!(minus_one.abs(), Decimal::one());
+
+        assert_eq!(one.try_sub(two).unwrap(), minus_one);
+        assert_eq!(minus_one.try_add(two).unwrap(), one);
+        assert_eq!(minus_one.try_add(one).unwrap(), SDecimal::zero());
+        assert!(!minus_one.try_add(one).unwrap().is_negative());
+        assert_eq!(minus_one.try_sub(one).unwrap(), -two);
+        assert_eq!((-two).try_sub(minus_one).unwrap(), minus_one);
+
+        assert_eq!(minus_one.try_mul(minus_one).unwrap(), one);
+        assert_eq!(minus_one.try_mul(two).unwrap(), -two);
+        assert_eq!(minus_one.try_mul(2u64).unwrap(), -two);
+        assert_eq!((-two).try_mul(Decimal::one()).unwrap(), -two);
+        assert_eq!((-two).try_div(minus_one).unwrap(), two);
+        assert_eq!((-two).try_div(2u64).unwrap(), minus_one);
+        assert_eq!((-two).try_div(Decimal::from(2u64)).unwrap(), minus_one);
+        assert!(one.try_div(SDecimal::zero()).is_err());
+        // rounding a negative product to zero drops the sign
+        let tiny = -SDecimal::from(Decimal::from_scaled_val(1));
+        assert_eq!(tiny.try_mul(tiny).unwrap(), SDecimal::zero());
+        assert!(!tiny.try_mul(tiny).unwrap().is_negative());
+        assert!(!tiny
+            .try_mul(one.try_div(2u64).unwrap())
+            .unwrap()
+            .is_negative());
+
+        assert!(-two < minus_one);
+        assert!(minus_one < SDecimal::zero());
+        assert!(SDecimal::zero() < one);
+        assert!(two > one);
+        assert_eq!(minus_one.max(-two), minus_one);
+
+        assert_eq!(Decimal::try_from(two).unwrap(), Decimal::from(2u64));
+        assert!(Decimal::try_from(minus_one).is_err());
+
+        assert_eq!(&format!("{}", minus_one), "-1.000000000");
+        assert_eq!(&format!("{}", two), "2.000000000");
+    }
+}
@@ -0,0 +1,118 @@
+//! Token metadata program interface, enough to name the pool mints
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+solana_program::declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Prefix of the name of the pool mints, followed by the pair symbol
+pub const LP_NAME_PREFIX: &str = "deltafi LP ";
+/// Symbol of the pool mints
+pub const LP_SYMBOL: &str = "DLP";
+/// Max length of a token metadata name
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// Instruction tag of `CreateMetadataAccountV3`
+const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+
+/// Name of the pool mint of a pair, none when too long for the metadata
+///
+/// # Arguments
+///
+/// * pair_symbol - symbol of the token pair, like `SOL-USDC`.
+///
+/// # Return value
+///
+/// pool mint name.
+pub fn lp_name(pair_symbol: &str) -> Option<String> {
+    let name = format!("{}{}", LP_NAME_PREFIX, pair_symbol);
+    if pair_symbol.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return None;
+    }
+    Some(name)
+}
+
+/// Metadata account of a mint
+pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"metadata", id().as_ref(), mint.as_ref()], &id())
+}
+
+fn pack_string(buf: &mut Vec<u8>, value: &str) {
+    buf.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+/// Creates a `CreateMetadataAccountV3` instruction without uri, creators,
+/// collection nor uses. The mint authority is the update authority.
+pub fn create_metadata_account(
+    metadata_pubkey: Pubkey,
+    mint_pubkey: Pubkey,
+    mint_authority_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    name: &str,
+    symbol: &str,
+) -> Instruction {
+    let mut data = vec![CREATE_METADATA_ACCOUNT_V3];
+    pack_string(&mut data, name);
+    pack_string(&mut data, symbol);
+    pack_string(&mut data, "");
+    // no seller fee, creators, collection nor uses
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&[0, 0, 0]);
+    // mutable, without collection details
+    data.extend_from_slice(&[1, 0]);
+
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(metadata_pubkey, false),
+            AccountMeta::new_readonly(mint_pubkey, false),
+            AccountMeta::new_readonly(mint_authority_pubkey, true),
+            AccountMeta::new(payer_pubkey, true),
+            AccountMeta::new_readonly(mint_authority_pubkey, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lp_name() {
+        assert_eq!(lp_name("SOL-USDC").unwrap(), "deltafi LP SOL-USDC");
+        assert_eq!(lp_name(&"A".repeat(21)).unwrap().len(), MAX_NAME_LENGTH);
+        assert_eq!(lp_name(&"A".repeat(22)), None);
+        assert_eq!(lp_name(""), None);
+    }
+
+    #[test]
+    fn test_create_metadata_account() {
+        let mint_authority = Pubkey::new_unique();
+        let instruction = create_metadata_account(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            mint_authority,
+            Pubkey::new_unique(),
+            "deltafi LP SOL-USDC",
+            LP_SYMBOL,
+        );
+
+        let mut expect = vec![33];
+        expect.extend_from_slice(&19u32.to_le_bytes());
+        expect.extend_from_slice(b"deltafi LP SOL-USDC");
+        expect.extend_from_slice(&3u32.to_le_bytes());
+        expect.extend_from_slice(b"DLP");
+        expect.extend_from_slice(&0u32.to_le_bytes());
+        expect.extend_from_slice(&[0, 0, 0, 0, 0, 1, 0]);
+        assert_eq!(instruction.data, expect);
+        assert_eq!(instruction.program_id, id());
+        assert_eq!(instruction.accounts[4].pubkey, mint_authority);
+        assert!(instruction.accounts[4].is_signer);
+    }
+}
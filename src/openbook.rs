@@ -0,0 +1,314 @@
+//! OpenBook market interface, enough to read the best bid and ask of a market
+
+use arrayref::{array_ref, array_refs};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+solana_program::declare_id!("srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX");
+
+/// Padding before the accounts of the market program, "serum"
+const HEAD_PADDING: &[u8; 5] = b"serum";
+/// Padding after the accounts of the market program, "padding"
+const TAIL_PADDING: &[u8; 7] = b"padding";
+
+/// Account flags
+const INITIALIZED: u64 = 1;
+const MARKET: u64 = 1 << 1;
+const BIDS: u64 = 1 << 5;
+const ASKS: u64 = 1 << 6;
+
+/// Market account size
+pub const MARKET_SIZE: usize = 388; // 5 + 376 + 7
+
+/// Slab header size, after the head padding and the account flags
+const SLAB_HEADER_SIZE: usize = 32; // 8 + 8 + 4 + 4 + 8
+/// Slab node size
+const SLAB_NODE_SIZE: usize = 72;
+
+/// Slab node tags
+const INNER_NODE: u32 = 1;
+const LEAF_NODE: u32 = 2;
+
+/// Market state, the fields needed to price the market
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketState {
+    /// Address of the market account
+    pub own_address: Pubkey,
+    /// Base token mint
+    pub coin_mint: Pubkey,
+    /// Quote token mint
+    pub pc_mint: Pubkey,
+    /// Bids slab account
+    pub bids: Pubkey,
+    /// Asks slab account
+    pub asks: Pubkey,
+    /// Base token amount per lot
+    pub coin_lot_size: u64,
+    /// Quote token amount per lot
+    pub pc_lot_size: u64,
+}
+
+/// Side of an order book
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    /// Buy orders, best at the highest price
+    Bids,
+    /// Sell orders, best at the lowest price
+    Asks,
+}
+
+fn check_padding(data: &[u8]) -> Result<&[u8], ProgramError> {
+    if data.len() < HEAD_PADDING.len() + 8 + TAIL_PADDING.len()
+        || !data.starts_with(HEAD_PADDING)
+        || !data.ends_with(TAIL_PADDING)
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(&data[HEAD_PADDING.len()..data.len() - TAIL_PADDING.len()])
+}
+
+fn check_flags(data: &[u8], flags: u64) -> Result<(), ProgramError> {
+    let account_flags = u64::from_le_bytes(*array_ref![data, 0, 8]);
+    if account_flags & (INITIALIZED | flags) != INITIALIZED | flags {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+/// Read the state of a market account
+pub fn load_market(data: &[u8]) -> Result<MarketState, ProgramError> {
+    if data.len() != MARKET_SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let data = check_padding(data)?;
+    check_flags(data, MARKET)?;
+    let data = array_ref![data, 0, 376];
+    #[rustfmt::skip]
+    let (
+        _account_flags,
+        own_address,
+        _vault_signer_nonce,
+        coin_mint,
+        pc_mint,
+        _coin_vault_and_totals,
+        _pc_vault_and_totals,
+        _pc_dust_threshold,
+        _req_q,
+        _event_q,
+        bids,
+        asks,
+        coin_lot_size,
+        pc_lot_size,
+        _fee_rate_bps_and_rebates,
+    ) = array_refs![data, 8, 32, 8, 32, 32, 48, 48, 8, 32, 32, 32, 32, 8, 8, 16];
+    Ok(MarketState {
+        own_address: Pubkey::new_from_array(*own_address),
+        coin_mint: Pubkey::new_from_array(*coin_mint),
+        pc_mint: Pubkey::new_from_array(*pc_mint),
+        bids: Pubkey::new_from_array(*bids),
+        asks: Pubkey::new_from_array(*asks),
+        coin_lot_size: u64::from_le_bytes(*coin_lot_size),
+        pc_lot_size: u64::from_le_bytes(*pc_lot_size),
+    })
+}
+
+/// Price of the best order of a slab account, quote lots per base lot. None
+/// when the side is empty.
+pub fn best_price(data: &[u8], side: Side) -> Result<Option<u64>, ProgramError> {
+    let data = check_padding(data)?;
+    check_flags(
+        data,
+        match side {
+            Side::Bids => BIDS,
+            Side::Asks => ASKS,
+        },
+    )?;
+    let data = &data[8..];
+    if data.len() < SLAB_HEADER_SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let header = array_ref![data, 0, SLAB_HEADER_SIZE];
+    let (_bump_index, _free_list_len, _free_list_head, root_node, leaf_count) =
+        array_refs![header, 8, 8, 4, 4, 8];
+    if u64::from_le_bytes(*leaf_count) == 0 {
+        return Ok(None);
+    }
+
+    let nodes = &data[SLAB_HEADER_SIZE..];
+    let node_count = nodes.len() / SLAB_NODE_SIZE;
+    let mut index = u32::from_le_bytes(*root_node) as usize;
+    // the best order is the highest key of the bids, the lowest of the asks,
+    // and the path to it is at most as long as the slab
+    for _ in 0..node_count {
+        let node = nodes
+            .get(index * SLAB_NODE_SIZE..(index + 1) * SLAB_NODE_SIZE)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let node = array_ref![node, 0, SLAB_NODE_SIZE];
+        let (tag, _prefix_len, key, children, _rest) = array_refs![node, 4, 4, 16, 8, 40];
+        match u32::from_le_bytes(*tag) {
+            INNER_NODE => {
+                let (left, right) = array_refs![children, 4, 4];
+                index = u32::from_le_bytes(*match side {
+                    Side::Bids => right,
+                    Side::Asks => left,
+                }) as usize;
+            }
+            LEAF_NODE => return Ok(Some((u128::from_le_bytes(*key) >> 64) as u64)),
+            _ => return Err(ProgramError::InvalidAccountData),
+        }
+    }
+    Err(ProgramError::InvalidAccountData)
+}
+
+/// Account data of mock markets, for tests exercising the OpenBook path
+#[cfg(any(test, feature = "test-sdk"))]
+pub mod test {
+    use super::*;
+
+    fn account_data(account_flags: u64, body: &[u8]) -> Vec<u8> {
+        let mut data = HEAD_PADDING.to_vec();
+        data.extend_from_slice(&account_flags.to_le_bytes());
+        data.extend_from_slice(body);
+        data.extend_from_slice(TAIL_PADDING);
+        data
+    }
+
+    /// Market account data of the mints, bids and asks
+    pub fn market_data(
+        own_address: &Pubkey,
+        coin_mint: &Pubkey,
+        pc_mint: &Pubkey,
+        bids: &Pubkey,
+        asks: &Pubkey,
+        coin_lot_size: u64,
+        pc_lot_size: u64,
+    ) -> Vec<u8> {
+        let mut body = own_address.to_bytes().to_vec();
+        body.extend_from_slice(&0u64.to_le_bytes());
+        body.extend_from_slice(coin_mint.as_ref());
+        body.extend_from_slice(pc_mint.as_ref());
+        body.extend_from_slice(&[0u8; 48 + 48 + 8 + 32 + 32]);
+        body.extend_from_slice(bids.as_ref());
+        body.extend_from_slice(asks.as_ref());
+        body.extend_from_slice(&coin_lot_size.to_le_bytes());
+        body.extend_from_slice(&pc_lot_size.to_le_bytes());
+        body.extend_from_slice(&[0u8; 16]);
+        account_data(INITIALIZED | MARKET, &body)
+    }
+
+    /// Slab account data of the side holding one order per price, quote lots
+    /// per base lot
+    pub fn slab_data(side: Side, prices: &[u64]) -> Vec<u8> {
+        let mut prices = prices.to_vec();
+        prices.sort_unstable();
+        let leaf = |price: u64| {
+            let mut node = LEAF_NODE.to_le_bytes().to_vec();
+            node.extend_from_slice(&[0u8; 4]);
+            node.extend_from_slice(&((price as u128) << 64).to_le_bytes());
+            node.extend_from_slice(&[0u8; 48]);
+            node
+        };
+        let inner = |left: u32, right: u32| {
+            let mut node = INNER_NODE.to_le_bytes().to_vec();
+            node.extend_from_slice(&[0u8; 20]);
+            node.extend_from_slice(&left.to_le_bytes());
+            node.extend_from_slice(&right.to_le_bytes());
+            node.extend_from_slice(&[0u8; 40]);
+            node
+        };
+
+        // leaves first, then a chain of inner nodes each holding the lowest
+        // leaf left and the higher ones right
+        let mut nodes: Vec<Vec<u8>> = prices.iter().map(|price| leaf(*price)).collect();
+        let leaf_count = prices.len() as u32;
+        let mut root = leaf_count.saturating_sub(1);
+        for index in (0..leaf_count.saturating_sub(1)).rev() {
+            nodes.push(inner(index, root));
+            root = nodes.len() as u32 - 1;
+        }
+
+        let mut body = (nodes.len() as u64).to_le_bytes().to_vec();
+        body.extend_from_slice(&0u64.to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&root.to_le_bytes());
+        body.extend_from_slice(&(leaf_count as u64).to_le_bytes());
+        body.extend(nodes.concat());
+        account_data(
+            INITIALIZED
+                | match side {
+                    Side::Bids => BIDS,
+                    Side::Asks => ASKS,
+                },
+            &body,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{test::*, *};
+
+    #[test]
+    fn test_load_market() {
+        let (own_address, coin_mint, pc_mint, bids, asks) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let data = market_data(&own_address, &coin_mint, &pc_mint, &bids, &asks, 100, 10);
+        assert_eq!(data.len(), MARKET_SIZE);
+        assert_eq!(
+            load_market(&data).unwrap(),
+            MarketState {
+                own_address,
+                coin_mint,
+                pc_mint,
+                bids,
+                asks,
+                coin_lot_size: 100,
+                pc_lot_size: 10,
+            }
+        );
+
+        // slabs are not markets
+        assert_eq!(
+            load_market(&slab_data(Side::Bids, &[1])),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut uninitialized = data.clone();
+        uninitialized[5] = 0;
+        assert_eq!(
+            load_market(&uninitialized),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_best_price() {
+        let bids = slab_data(Side::Bids, &[98, 95, 99, 90]);
+        let asks = slab_data(Side::Asks, &[103, 101, 110]);
+        assert_eq!(best_price(&bids, Side::Bids).unwrap(), Some(99));
+        assert_eq!(best_price(&asks, Side::Asks).unwrap(), Some(101));
+
+        let single = slab_data(Side::Asks, &[105]);
+        assert_eq!(best_price(&single, Side::Asks).unwrap(), Some(105));
+        let empty = slab_data(Side::Bids, &[]);
+        assert_eq!(best_price(&empty, Side::Bids).unwrap(), None);
+
+        // the side of the slab must match
+        assert_eq!(
+            best_price(&bids, Side::Asks),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        // a node pointing out of the slab
+        let mut broken = bids.clone();
+        let root_offset = 5 + 8 + 20;
+        broken[root_offset..root_offset + 4].copy_from_slice(&1_000u32.to_le_bytes());
+        assert_eq!(
+            best_price(&broken, Side::Bids),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -6,14 +6,24 @@ use std::convert::TryInto;
 
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::{Epoch, UnixTimestamp},
+    ed25519_program,
     entrypoint::ProgramResult,
+    instruction::AccountMeta,
     msg,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
-    sysvar::{clock::Clock, rent::Rent, Sysvar},
+    system_instruction,
+    sysvar::{
+        clock::Clock,
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        rent::Rent,
+        Sysvar,
+    },
 };
+use spl_associated_token_account::instruction::create_associated_token_account;
 use spl_token::{
     instruction::AuthorityType,
     state::{Account, Mint},
@@ -21,15 +31,37 @@ use spl_token::{
 
 use crate::{
     admin::process_admin_instruction,
-    curve::{Multiplier, PoolState},
+    curve::{CurveType, Multiplier, PoolState, SwapQuote},
     error::SwapError,
+    event::{Event, LiquidityEvent, SwapEvent},
     instruction::{
-        DepositData, InitializeData, InstructionType, SwapData, SwapDirection, SwapInstruction,
-        WithdrawData,
+        check_deposit, check_swap, receive_flash_swap, BatchSwapData, CancelSignedOrdersData,
+        CheckDepositData, CheckSwapData, ClaimAllRewardsData, CreateDcaPlanData,
+        CreateProposalData, DepositData, EmergencyWithdrawData, FlashSwapData,
+        GrowProviderAccountData, InitializeData, InitializePositionPageData,
+        InitializeRegistryPageData, InstructionType, LockDelfiData, ProgramBuild, QuoteSwapData,
+        ReceiveFlashSwapData, RelayedSwapData, SetLiquidityProviderDelegateData, SplitSwapData,
+        SwapData, SwapDirection, SwapInstruction, TransferPositionData, VoteGaugeData,
+        VoteProposalData, WithdrawData, WithdrawExactData,
+    },
+    math::{Decimal, SDecimal, TryAdd, TryDiv, TryMul, TrySub, BPS_DENOMINATOR},
+    metadata, openbook, pyth,
+    state::{
+        check_ed25519_instruction_data, find_dca_plan_address, find_delfi_lock_address,
+        find_deny_list_address, find_liquidity_provider_address, find_observation_buffer_address,
+        find_order_authority_address, find_order_nonce_address, find_position_page_address,
+        find_price_feed_address, find_proposal_address, find_quote_account_address,
+        find_registry_page_address, find_vote_record_address, ConfigInfo, DcaPlan, DelfiLock,
+        DenyList, DirectionalFees, Fees, GaugeVotes, GaugeWeight, HalvingSchedule,
+        LiquidityPositionPage, LiquidityProvider, Observation, ObservationBuffer, OrderNonce,
+        PriceFeed, Proposal, QuoteAccount, RegistryEntry, RegistryPage, Rewards, SignedOrder,
+        SlotVolume, SwapInfo, SwapInfoData, Twap, VoteRecord, Whitelist, DCA_PLAN_SEED,
+        DCA_PLAN_SIZE, DELFI_LOCK_SEED, LIQUIDITY_PROVIDER_SEED, MAX_PAGE_POSITIONS,
+        OBSERVATION_BUFFER_SEED, OBSERVATION_BUFFER_SIZE, ORDER_AUTHORITY_SEED, ORDER_NONCE_SEED,
+        ORDER_NONCE_SIZE, POSITION_PAGE_SEED, PRICE_FEED_SEED, PRICE_FEED_SIZE, PROGRAM_VERSION,
+        PROPOSAL_SEED, QUOTE_ACCOUNT_SEED, QUOTE_ACCOUNT_SIZE, REGISTRY_PAGE_SEED,
+        REGISTRY_PAGE_SIZE, VOTE_RECORD_SEED,
     },
-    math::{Decimal, TryAdd, TryDiv, TryMul, TrySub},
-    pyth,
-    state::{ConfigInfo, LiquidityProvider, SwapInfo},
 };
 
 /// Processes an [Instruction](enum.Instruction.html).
@@ -53,14 +85,26 @@ fn process_swap_instruction(
             slope,
             mid_price,
             is_open_twap,
+            twap_window_secs,
+            pair_symbol,
         }) => {
             msg!("Instruction: Initialize");
-            process_initialize(program_id, nonce, slope, mid_price, is_open_twap, accounts)
+            process_initialize(
+                program_id,
+                nonce,
+                slope,
+                mid_price,
+                is_open_twap,
+                twap_window_secs,
+                pair_symbol,
+                accounts,
+            )
         }
         SwapInstruction::Swap(SwapData {
             amount_in,
             minimum_amount_out,
             swap_direction,
+            max_price_impact_bps,
         }) => {
             msg!("Instruction: Swap");
             process_swap(
@@ -68,6 +112,9 @@ fn process_swap_instruction(
                 amount_in,
                 minimum_amount_out,
                 swap_direction,
+                max_price_impact_bps,
+                None,
+                None,
                 accounts,
             )
         }
@@ -111,6 +158,260 @@ fn process_swap_instruction(
             msg!("Instruction: Claim Liquidity Rewards");
             process_claim_liquidity_rewards(program_id, accounts)
         }
+        SwapInstruction::GrowProviderAccount(GrowProviderAccountData { max_positions }) => {
+            msg!("Instruction: Grow liquidity provider account");
+            process_grow_provider_account(program_id, max_positions, accounts)
+        }
+        SwapInstruction::InitializePositionPage(InitializePositionPageData { page_index }) => {
+            msg!("Instruction: Initialize position page");
+            process_init_position_page(program_id, page_index, accounts)
+        }
+        SwapInstruction::CloseLiquidityProvider => {
+            msg!("Instruction: Close liquidity provider");
+            process_close_liquidity_provider(program_id, accounts)
+        }
+        SwapInstruction::DepositImbalanced(DepositData {
+            token_a_amount,
+            token_b_amount,
+            min_mint_amount,
+        }) => {
+            msg!("Instruction: Deposit imbalanced");
+            process_deposit_imbalanced(
+                program_id,
+                token_a_amount,
+                token_b_amount,
+                min_mint_amount,
+                accounts,
+            )
+        }
+        SwapInstruction::WithdrawExact(WithdrawExactData {
+            token_a_amount,
+            token_b_amount,
+            max_pool_token_amount,
+        }) => {
+            msg!("Instruction: Withdraw exact");
+            process_withdraw_exact(
+                program_id,
+                token_a_amount,
+                token_b_amount,
+                max_pool_token_amount,
+                accounts,
+            )
+        }
+        SwapInstruction::InitializeObservationBuffer => {
+            msg!("Instruction: Initialize observation buffer");
+            process_init_observation_buffer(program_id, accounts)
+        }
+        SwapInstruction::InitializeRegistryPage(InitializeRegistryPageData { page_index }) => {
+            msg!("Instruction: Initialize registry page");
+            process_init_registry_page(program_id, page_index, accounts)
+        }
+        SwapInstruction::QuoteSwap(QuoteSwapData {
+            amount_in,
+            swap_direction,
+        }) => {
+            msg!("Instruction: Quote swap");
+            process_quote_swap(program_id, amount_in, swap_direction, accounts)
+        }
+        SwapInstruction::InitializeQuoteAccount => {
+            msg!("Instruction: Initialize quote account");
+            process_init_quote_account(program_id, accounts)
+        }
+        SwapInstruction::CalcReceiveAmount(QuoteSwapData {
+            amount_in,
+            swap_direction,
+        }) => {
+            msg!("Instruction: Calc receive amount");
+            process_calc_receive_amount(program_id, amount_in, swap_direction, accounts)
+        }
+        SwapInstruction::SwapNative(SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps,
+        }) => {
+            msg!("Instruction: Swap native");
+            process_swap_native(
+                program_id,
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                max_price_impact_bps,
+                accounts,
+            )
+        }
+        SwapInstruction::DepositNative(DepositData {
+            token_a_amount,
+            token_b_amount,
+            min_mint_amount,
+        }) => {
+            msg!("Instruction: Deposit native");
+            process_deposit_native(
+                program_id,
+                token_a_amount,
+                token_b_amount,
+                min_mint_amount,
+                accounts,
+            )
+        }
+        SwapInstruction::TransferPosition(TransferPositionData { source_page_count }) => {
+            msg!("Instruction: Transfer position");
+            process_transfer_position(program_id, source_page_count, accounts)
+        }
+        SwapInstruction::SetLiquidityProviderDelegate(SetLiquidityProviderDelegateData {
+            delegate,
+        }) => {
+            msg!("Instruction: Set liquidity provider delegate");
+            process_set_liquidity_provider_delegate(program_id, delegate, accounts)
+        }
+        SwapInstruction::ClaimAllRewards(ClaimAllRewardsData { swap_count }) => {
+            msg!("Instruction: Claim all rewards");
+            process_claim_all_rewards(program_id, swap_count, accounts)
+        }
+        SwapInstruction::Poke => {
+            msg!("Instruction: Poke");
+            process_poke(program_id, accounts)
+        }
+        SwapInstruction::InitializeDelfiLock => {
+            msg!("Instruction: Initialize DELFI lock");
+            process_initialize_delfi_lock(program_id, accounts)
+        }
+        SwapInstruction::LockDelfi(LockDelfiData { amount, unlock_ts }) => {
+            msg!("Instruction: Lock DELFI");
+            process_lock_delfi(program_id, amount, unlock_ts, accounts)
+        }
+        SwapInstruction::UnlockDelfi => {
+            msg!("Instruction: Unlock DELFI");
+            process_unlock_delfi(program_id, accounts)
+        }
+        SwapInstruction::VoteGauge(VoteGaugeData { weights_bps }) => {
+            msg!("Instruction: Vote gauge");
+            process_vote_gauge(program_id, &weights_bps, accounts)
+        }
+        SwapInstruction::CreateProposal(CreateProposalData { fees, rewards }) => {
+            msg!("Instruction: Create proposal");
+            process_create_proposal(program_id, &fees, &rewards, accounts)
+        }
+        SwapInstruction::VoteProposal(VoteProposalData { approve }) => {
+            msg!("Instruction: Vote proposal");
+            process_vote_proposal(program_id, approve, accounts)
+        }
+        SwapInstruction::ExecuteProposal => {
+            msg!("Instruction: Execute proposal");
+            process_execute_proposal(program_id, accounts)
+        }
+        SwapInstruction::FlashSwap(FlashSwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps,
+            callback_data,
+        }) => {
+            msg!("Instruction: Flash swap");
+            process_swap(
+                program_id,
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                max_price_impact_bps,
+                Some(&callback_data),
+                None,
+                accounts,
+            )
+        }
+        SwapInstruction::BatchSwap(BatchSwapData {
+            swaps,
+            minimum_total_amount_out,
+        }) => {
+            msg!("Instruction: Batch swap");
+            process_batch_swap(program_id, &swaps, minimum_total_amount_out, accounts)
+        }
+        SwapInstruction::SplitSwap(SplitSwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            fractions_bps,
+        }) => {
+            msg!("Instruction: Split swap");
+            process_split_swap(
+                program_id,
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                &fractions_bps,
+                accounts,
+            )
+        }
+        SwapInstruction::EmergencyWithdraw(EmergencyWithdrawData {
+            minimum_token_a_amount,
+            minimum_token_b_amount,
+        }) => {
+            msg!("Instruction: Emergency withdraw");
+            process_emergency_withdraw(
+                program_id,
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+                accounts,
+            )
+        }
+        SwapInstruction::Sync => {
+            msg!("Instruction: Sync");
+            process_sync(program_id, accounts)
+        }
+        SwapInstruction::Skim => {
+            msg!("Instruction: Skim");
+            process_skim(program_id, accounts)
+        }
+        SwapInstruction::GetVersion => {
+            msg!("Instruction: GetVersion");
+            process_get_version()
+        }
+        SwapInstruction::GetVirtualPrice => {
+            msg!("Instruction: GetVirtualPrice");
+            process_get_virtual_price(program_id, accounts)
+        }
+        SwapInstruction::InitializePriceFeed => {
+            msg!("Instruction: Initialize price feed");
+            process_init_price_feed(program_id, accounts)
+        }
+        SwapInstruction::RelayedSwap(RelayedSwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            max_price_impact_bps,
+            relayer_fee,
+        }) => {
+            msg!("Instruction: Relayed swap");
+            process_relayed_swap(
+                program_id,
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                max_price_impact_bps,
+                relayer_fee,
+                accounts,
+            )
+        }
+        SwapInstruction::SettleSignedOrder(order) => {
+            msg!("Instruction: Settle signed order");
+            process_settle_signed_order(program_id, order, accounts)
+        }
+        SwapInstruction::CancelSignedOrders(CancelSignedOrdersData { next_nonce }) => {
+            msg!("Instruction: Cancel signed orders");
+            process_cancel_signed_orders(program_id, next_nonce, accounts)
+        }
+        SwapInstruction::CreateDcaPlan(plan_data) => {
+            msg!("Instruction: Create DCA plan");
+            process_create_dca_plan(program_id, plan_data, accounts)
+        }
+        SwapInstruction::ExecuteDca => {
+            msg!("Instruction: Execute DCA");
+            process_execute_dca(program_id, accounts)
+        }
+        SwapInstruction::CancelDca => {
+            msg!("Instruction: Cancel DCA");
+            process_cancel_dca(program_id, accounts)
+        }
     }
 }
 
@@ -120,6 +421,8 @@ fn process_initialize(
     slope: u64,
     mid_price: u128,
     is_open_twap: bool,
+    twap_window_secs: u64,
+    pair_symbol: Option<String>,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -136,6 +439,10 @@ fn process_initialize(
     let pyth_b_price_info = next_account_info(account_info_iter)?;
     let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
     let token_program_info = next_account_info(account_info_iter)?;
+    let registry_page_info = next_account_info(account_info_iter)?;
+    let token_a_mint_info = next_account_info(account_info_iter)?;
+    let token_b_mint_info = next_account_info(account_info_iter)?;
+    let deny_list_info = next_account_info(account_info_iter)?;
 
     assert_uninitialized::<SwapInfo>(swap_info)?;
     if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
@@ -173,6 +480,20 @@ fn process_initialize(
     if token_b.mint != admin_fee_key_b.mint {
         return Err(SwapError::InvalidAdmin.into());
     }
+    if *token_a_mint_info.key != token_a.mint || *token_b_mint_info.key != token_b.mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    check_deny_list(program_id, config_info, deny_list_info)?;
+    // pools can be created before the config has a deny-list
+    if deny_list_info.owner == program_id {
+        let deny_list_data = deny_list_info.data.borrow();
+        let deny_list = DenyList::load(&deny_list_data)?;
+        if deny_list.contains(&token_a.mint) || deny_list.contains(&token_b.mint) {
+            return Err(SwapError::DeniedMint.into());
+        }
+    }
+    let token_a_mint = unpack_mint(token_a_mint_info, &token_program_id)?;
+    let token_b_mint = unpack_mint(token_b_mint_info, &token_program_id)?;
     if token_b.amount == 0 {
         return Err(SwapError::EmptySupply.into());
     }
@@ -208,6 +529,8 @@ fn process_initialize(
         return Err(SwapError::InvalidSlope.into());
     }
 
+    check_pyth_price_account(pyth_a_price_info)?;
+    check_pyth_price_account(pyth_b_price_info)?;
     // getting price from pyth or initial mid_price
     let market_price = get_market_price_from_pyth(pyth_a_price_info, pyth_b_price_info, clock)
         .unwrap_or_else(|_| Decimal::from_scaled_val(mid_price));
@@ -220,6 +543,8 @@ fn process_initialize(
         base_reserve: Decimal::zero(),
         quote_reserve: Decimal::zero(),
         multiplier: Multiplier::One,
+        base_decimals: token_a_mint.decimals,
+        quote_decimals: token_b_mint.decimals,
     })?;
 
     let mint_amount = pool_state.buy_shares(token_a.amount, token_b.amount, pool_mint.supply)?;
@@ -227,6 +552,21 @@ fn process_initialize(
     let block_timestamp_last: u64 = clock.unix_timestamp.try_into().unwrap();
     let config = ConfigInfo::unpack(&config_info.data.borrow())?;
 
+    if registry_page_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let mut registry_data = registry_page_info.data.borrow_mut();
+    let registry_page = RegistryPage::load_mut(&mut registry_data)?;
+    if registry_page.config != *config_info.key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    registry_page.push(RegistryEntry {
+        swap: *swap_info.key,
+        token_a_mint: token_a.mint,
+        token_b_mint: token_b.mint,
+    })?;
+    drop(registry_data);
+
     SwapInfo::pack(
         SwapInfo {
             is_initialized: true,
@@ -246,6 +586,40 @@ fn process_initialize(
             block_timestamp_last,
             cumulative_ticks: 0,
             base_price_cumulative_last: Decimal::zero(),
+            last_mid_price_update_ts: 0,
+            pyth_a: *pyth_a_price_info.key,
+            pyth_b: *pyth_b_price_info.key,
+            twap_window_secs,
+            max_price_impact_bps: 0,
+            max_amount_in: 0,
+            max_amount_in_reserve_bps: 0,
+            max_swaps_per_slot: 0,
+            max_volume_per_slot: 0,
+            last_swap_slot: 0,
+            slot_swap_count: 0,
+            slot_swap_volume: 0,
+            is_fee_compounding: false,
+            is_whitelist_only: false,
+            transfer_hook_program: Pubkey::default(),
+            reward_per_share: Decimal::zero(),
+            reward_last_update_ts: clock.unix_timestamp,
+            deltafi_price: Decimal::zero(),
+            halving_schedule: HalvingSchedule::default(),
+            gauge_votes: GaugeVotes::default(),
+            gauge_weight: GaugeWeight::default(),
+            max_base_reserve: 0,
+            max_quote_reserve: 0,
+            max_user_liquidity: 0,
+            is_immutable: false,
+            directional_fees: DirectionalFees::default(),
+            is_fee_in_quote: false,
+            has_freezable_mint: token_a_mint.freeze_authority.is_some()
+                || token_b_mint.freeze_authority.is_some(),
+            cumulative_fee_per_lp: Decimal::zero(),
+            openbook_market: Pubkey::default(),
+            config_key: *config_info.key,
+            curve_type: CurveType::Standard,
+            version: PROGRAM_VERSION,
         },
         &mut swap_info.data.borrow_mut(),
     )?;
@@ -260,14 +634,52 @@ fn process_initialize(
         mint_amount,
     )?;
 
+    if let Some(pair_symbol) = pair_symbol {
+        let metadata_program_info = next_account_info(account_info_iter)?;
+        let metadata_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        // the swap authority signs, so no other program can be invoked
+        if *metadata_program_info.key != metadata::id() {
+            return Err(SwapError::IncorrectMetadataProgram.into());
+        }
+        let name = metadata::lp_name(&pair_symbol).ok_or(SwapError::InvalidInput)?;
+        let swap_bytes = swap_info.key.to_bytes();
+        let authority_signature_seeds = [&swap_bytes[..32], &[nonce]];
+        invoke_signed(
+            &metadata::create_metadata_account(
+                *metadata_info.key,
+                *pool_mint_info.key,
+                *authority_info.key,
+                *payer_info.key,
+                &name,
+                metadata::LP_SYMBOL,
+            ),
+            &[
+                metadata_info.clone(),
+                pool_mint_info.clone(),
+                authority_info.clone(),
+                payer_info.clone(),
+                system_program_info.clone(),
+                metadata_program_info.clone(),
+            ],
+            &[&authority_signature_seeds[..]],
+        )?;
+    }
+
     Ok(())
 }
 
+/// Swap, as a flash swap paid back by the callback program after the output
+/// transfers when there is callback data
 fn process_swap(
     program_id: &Pubkey,
     amount_in: u64,
     minimum_amount_out: u64,
     swap_direction: SwapDirection,
+    max_price_impact_bps: u64,
+    callback_data: Option<&[u8]>,
+    user_authority_seeds: Option<&[&[u8]]>,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -287,21 +699,38 @@ fn process_swap(
     let pyth_b_price_info = next_account_info(account_info_iter)?;
     let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
     let token_program_info = next_account_info(account_info_iter)?;
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+    let whitelist_info = next_account_info(account_info_iter)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
 
     if swap_info.owner != program_id || config_info.owner != program_id {
         return Err(ProgramError::IncorrectProgramId);
     }
+    check_observation_buffer(program_id, swap_info, observation_buffer_info)?;
 
-    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
-    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
-    if token_swap.is_paused {
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let mut swap_data = swap_info.data.borrow_mut();
+    let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+    check_swap_config(&token_swap.config_key, config_info)?;
+    if token_swap.is_paused()? {
         return Err(SwapError::IsPaused.into());
     }
-    let swap_nonce = token_swap.nonce;
-    if *swap_authority_info.key != authority_id(program_id, swap_info.key, swap_nonce)? {
-        return Err(SwapError::InvalidProgramAddress.into());
-    }
+    let (openbook_infos, rest) =
+        split_openbook_market(&token_swap.openbook_market, account_info_iter.as_slice())?;
+    let (transfer_hook_info, callback_infos) =
+        split_transfer_hook(&token_swap.transfer_hook_program, rest)?;
+    let flash_callback = match callback_data {
+        Some(data) => match callback_infos.split_first() {
+            Some((callback_program_info, callback_infos)) => {
+                Some((callback_program_info, callback_infos, data))
+            }
+            None => return Err(ProgramError::NotEnoughAccountKeys),
+        },
+        None => None,
+    };
 
+    // key checks first, so invalid accounts fail before any account is unpacked
     if !(*swap_source_info.key == token_swap.token_a || *swap_source_info.key == token_swap.token_b)
     {
         return Err(SwapError::IncorrectSwapAccount.into());
@@ -318,21 +747,56 @@ fn process_swap(
     {
         return Err(SwapError::InvalidInput.into());
     }
+    if *pyth_a_price_info.key != token_swap.pyth_a || *pyth_b_price_info.key != token_swap.pyth_b {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+    if config.deltafi_mint != *reward_mint_info.key {
+        return Err(SwapError::IncorrectMint.into());
+    }
 
-    let token_program_id = *token_program_info.key;
-    let token_a = unpack_token_account(swap_source_info, &token_program_id)?;
-    let token_b = unpack_token_account(swap_destination_info, &token_program_id)?;
-    let reward_token = unpack_token_account(reward_token_info, &token_program_id)?;
-    let reward_mint = unpack_mint(reward_mint_info, &token_program_id)?;
+    let admin_fee_key = match (
+        swap_direction,
+        *swap_destination_info.key == token_swap.token_a,
+    ) {
+        _ if token_swap.is_fee_in_quote()? => token_swap.admin_fee_key_b,
+        (SwapDirection::SellBase, true) | (SwapDirection::SellQuote, false) => {
+            token_swap.admin_fee_key_a
+        }
+        (SwapDirection::SellBase, false) | (SwapDirection::SellQuote, true) => {
+            token_swap.admin_fee_key_b
+        }
+    };
+    if *admin_destination_info.key != admin_fee_key {
+        return Err(SwapError::InvalidAdmin.into());
+    }
 
-    // TODO: ======== Need check more =========
+    let swap_nonce = token_swap.nonce;
+    if *swap_authority_info.key != authority_id(program_id, swap_info.key, swap_nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
     let market_nonce = config.bump_seed;
     if *market_authority_info.key != authority_id(program_id, config_info.key, market_nonce)? {
         return Err(SwapError::InvalidProgramAddress.into());
     }
-    if config.deltafi_mint != *reward_mint_info.key {
-        return Err(SwapError::IncorrectMint.into());
+
+    let token_program_id = *token_program_info.key;
+    if token_swap.is_whitelist_only()? {
+        check_whitelist(program_id, swap_info, whitelist_info)?;
+        // the trader owns the account the tokens sold come from
+        let user_source_info = match swap_direction {
+            SwapDirection::SellBase => source_info,
+            SwapDirection::SellQuote => destination_info,
+        };
+        let trader = unpack_token_account(user_source_info, &token_program_id)?.owner;
+        if !Whitelist::load(&whitelist_info.data.borrow())?.contains(&trader) {
+            return Err(SwapError::TraderNotWhitelisted.into());
+        }
     }
+    let token_a = unpack_token_account(swap_source_info, &token_program_id)?;
+    let token_b = unpack_token_account(swap_destination_info, &token_program_id)?;
+    let reward_token = unpack_token_account(reward_token_info, &token_program_id)?;
+    let reward_mint = unpack_mint(reward_mint_info, &token_program_id)?;
+
     if reward_token.owner == *market_authority_info.key {
         return Err(SwapError::InvalidOwner.into());
     }
@@ -345,655 +809,3974 @@ fn process_swap(
         return Err(SwapError::IncorrectMint.into());
     }
 
-    match swap_direction {
-        SwapDirection::SellBase => {
-            if *swap_destination_info.key == token_swap.token_a
-                && *admin_destination_info.key != token_swap.admin_fee_key_a
-            {
-                return Err(SwapError::InvalidAdmin.into());
-            }
-            if *swap_destination_info.key == token_swap.token_b
-                && *admin_destination_info.key != token_swap.admin_fee_key_b
-            {
-                return Err(SwapError::InvalidAdmin.into());
-            }
-            if token_a.amount < amount_in {
-                return Err(SwapError::InsufficientFunds.into());
-            }
-        }
-        SwapDirection::SellQuote => {
-            if *swap_destination_info.key == token_swap.token_a
-                && *admin_destination_info.key != token_swap.admin_fee_key_b
-            {
-                return Err(SwapError::InvalidAdmin.into());
-            }
-            if *swap_destination_info.key == token_swap.token_b
-                && *admin_destination_info.key != token_swap.admin_fee_key_a
-            {
-                return Err(SwapError::InvalidAdmin.into());
-            }
-            if token_b.amount < amount_in {
-                return Err(SwapError::InsufficientFunds.into());
-            }
-        }
-    }
-
-    let (new_market_price, base_price_cumulative_last) =
-        get_new_market_price(&mut token_swap, pyth_a_price_info, pyth_b_price_info, clock)?;
-
-    let state = PoolState::new(PoolState {
-        market_price: new_market_price,
-        ..token_swap.pool_state
-    })?;
-
-    let (receive_amount, new_multiplier) = match swap_direction {
-        SwapDirection::SellBase => state.sell_base_token(amount_in)?,
-        SwapDirection::SellQuote => state.sell_quote_token(amount_in)?,
+    let reserve_in = match swap_direction {
+        SwapDirection::SellBase => token_a.amount,
+        SwapDirection::SellQuote => token_b.amount,
     };
-    let fees = &token_swap.fees;
-    let trade_fee = fees.trade_fee(receive_amount)?;
-    let admin_fee = fees.admin_trade_fee(trade_fee)?;
-    let rewards = &token_swap.rewards;
-    let amount_to_reward = rewards.trade_reward_u64(amount_in)?;
-    let amount_out = receive_amount
-        .checked_sub(trade_fee)
-        .ok_or(SwapError::CalculationFailure)?;
+    let (state, twap, quote) = quote_market_swap(
+        token_swap,
+        observation_buffer_info,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        openbook_infos,
+        clock,
+        reserve_in,
+        amount_in,
+        swap_direction,
+    )?;
+    let discount_bps = fee_discount_bps(
+        &config,
+        &reward_token,
+        match swap_direction {
+            SwapDirection::SellBase => source_info,
+            SwapDirection::SellQuote => destination_info,
+        },
+        &token_program_id,
+    )?;
+    let quote = quote.discount_trade_fee(discount_bps, &token_swap.swap_fees(swap_direction)?)?;
+    let SwapQuote {
+        amount_out,
+        reward_amount: amount_to_reward,
+        multiplier: new_multiplier,
+        ..
+    } = quote;
+    let new_market_price = state.market_price;
 
     if amount_out < minimum_amount_out {
         return Err(SwapError::ExceededSlippage.into());
     }
+    // the limit of the user, on top of the one of the pool
+    if max_price_impact_bps > 0 {
+        check_price_impact(
+            &token_swap.curve_type()?.apply(&state)?,
+            &quote,
+            amount_in,
+            swap_direction,
+            max_price_impact_bps,
+        )?;
+    }
 
-    let (base_balance, quote_balance) = match swap_direction {
-        SwapDirection::SellBase => (
-            token_a
-                .amount
-                .checked_add(amount_in)
-                .ok_or(SwapError::CalculationFailure)?,
-            token_b
-                .amount
-                .checked_sub(amount_out)
-                .ok_or(SwapError::CalculationFailure)?,
-        ),
-        SwapDirection::SellQuote => (
-            token_a
-                .amount
-                .checked_sub(amount_out)
-                .ok_or(SwapError::CalculationFailure)?,
-            token_b
-                .amount
-                .checked_add(amount_in)
-                .ok_or(SwapError::CalculationFailure)?,
-        ),
-    };
+    let slot_volume = throttle_swap(token_swap, clock, &quote, amount_in, swap_direction)?;
+    let (base_balance, quote_balance) = swapped_balances(
+        token_a.amount,
+        token_b.amount,
+        amount_in,
+        amount_out,
+        swap_direction,
+    )?;
 
-    token_swap.pool_state = PoolState::new(PoolState {
+    let (fee_direction, trade_fee, admin_fee) =
+        collected_trade_fee(token_swap, &state, swap_direction, &quote)?;
+
+    // all checks passed, write the pool state once
+    accumulate_rewards(token_swap, clock)?;
+    let mut new_pool_state = PoolState::new(PoolState {
         base_reserve: Decimal::from(base_balance),
         quote_reserve: Decimal::from(quote_balance),
         multiplier: new_multiplier,
         ..state
     })?;
+    if token_swap.is_fee_compounding()? {
+        new_pool_state = new_pool_state.compound_trade_fee(fee_direction, trade_fee, admin_fee)?;
+    }
+    accumulate_trade_fee(token_swap, &state, fee_direction, trade_fee, admin_fee)?;
+    token_swap.set_pool_state(&new_pool_state);
+    token_swap.set_slot_volume(&slot_volume);
+    update_twap(token_swap, observation_buffer_info, &twap, clock)?;
+    drop(swap_data);
 
-    token_swap.cumulative_ticks = token_swap
-        .cumulative_ticks
-        .checked_add(clock.unix_timestamp.try_into().unwrap())
-        .ok_or(SwapError::CalculationFailure)?
-        .checked_sub(token_swap.block_timestamp_last)
-        .ok_or(SwapError::CalculationFailure)?;
-    token_swap.block_timestamp_last = clock.unix_timestamp.try_into().unwrap();
-    token_swap.base_price_cumulative_last = base_price_cumulative_last;
-    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
-
-    match swap_direction {
-        SwapDirection::SellBase => {
-            token_transfer(
-                swap_info.key,
-                token_program_info.clone(),
-                source_info.clone(),
-                swap_source_info.clone(),
+    let (user_source_info, swap_in_info, swap_out_info, user_destination_info) =
+        match swap_direction {
+            SwapDirection::SellBase => (
+                source_info,
+                swap_source_info,
+                swap_destination_info,
+                destination_info,
+            ),
+            SwapDirection::SellQuote => (
+                destination_info,
+                swap_destination_info,
+                swap_source_info,
+                source_info,
+            ),
+        };
+    if let Some(transfer_hook_info) = transfer_hook_info {
+        let trader = unpack_token_account(user_source_info, &token_program_id)?.owner;
+        invoke(
+            &check_swap(
+                *transfer_hook_info.key,
+                *swap_info.key,
+                *user_transfer_authority_info.key,
+                CheckSwapData {
+                    trader,
+                    amount_in,
+                    amount_out,
+                    swap_direction,
+                },
+            ),
+            &[
+                swap_info.clone(),
                 user_transfer_authority_info.clone(),
-                swap_nonce,
+                transfer_hook_info.clone(),
+            ],
+        )?;
+    }
+    match (flash_callback.is_none(), user_authority_seeds) {
+        // the user transfer authority is an address of the program
+        (true, Some(user_authority_seeds)) => invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                user_source_info.key,
+                swap_in_info.key,
+                user_transfer_authority_info.key,
+                &[],
                 amount_in,
-            )?;
-            token_transfer(
-                swap_info.key,
-                token_program_info.clone(),
-                swap_destination_info.clone(),
-                destination_info.clone(),
-                swap_authority_info.clone(),
-                swap_nonce,
-                amount_out,
-            )?;
-            token_transfer(
-                swap_info.key,
-                token_program_info.clone(),
-                swap_destination_info.clone(),
-                admin_destination_info.clone(),
-                swap_authority_info.clone(),
-                swap_nonce,
-                admin_fee,
-            )?;
-            token_mint_to(
-                config_info.key,
-                token_program_info.clone(),
-                reward_mint_info.clone(),
-                reward_token_info.clone(),
-                market_authority_info.clone(),
-                market_nonce,
-                amount_to_reward,
-            )?;
-        }
-        SwapDirection::SellQuote => {
-            token_transfer(
-                swap_info.key,
-                token_program_info.clone(),
-                destination_info.clone(),
-                swap_destination_info.clone(),
+            )?,
+            &[
+                user_source_info.clone(),
+                swap_in_info.clone(),
                 user_transfer_authority_info.clone(),
-                swap_nonce,
-                amount_in,
-            )?;
-            token_transfer(
-                swap_info.key,
-                token_program_info.clone(),
-                swap_source_info.clone(),
-                source_info.clone(),
-                swap_authority_info.clone(),
-                swap_nonce,
-                amount_out,
-            )?;
-            token_transfer(
-                swap_info.key,
                 token_program_info.clone(),
-                swap_source_info.clone(),
-                admin_destination_info.clone(),
-                swap_authority_info.clone(),
-                swap_nonce,
-                admin_fee,
-            )?;
-            token_mint_to(
-                config_info.key,
-                token_program_info.clone(),
-                reward_mint_info.clone(),
-                reward_token_info.clone(),
-                market_authority_info.clone(),
-                market_nonce,
-                amount_to_reward,
-            )?;
-        }
+            ],
+            &[user_authority_seeds],
+        )?,
+        (true, None) => token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            user_source_info.clone(),
+            swap_in_info.clone(),
+            user_transfer_authority_info.clone(),
+            swap_nonce,
+            amount_in,
+        )?,
+        (false, _) => {}
+    }
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        swap_out_info.clone(),
+        user_destination_info.clone(),
+        swap_authority_info.clone(),
+        swap_nonce,
+        amount_out,
+    )?;
+    // the fee is paid from the reserve of the token it is collected in
+    let admin_fee_source_info = if fee_direction == swap_direction {
+        swap_out_info
+    } else {
+        swap_in_info
     };
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        admin_fee_source_info.clone(),
+        admin_destination_info.clone(),
+        swap_authority_info.clone(),
+        swap_nonce,
+        admin_fee,
+    )?;
+    if let Some((callback_program_info, callback_infos, data)) = flash_callback {
+        // the pool state is already written with the input paid back, the
+        // runtime keeps the callback from reentering the swap meanwhile
+        invoke(
+            &receive_flash_swap(
+                *callback_program_info.key,
+                *swap_info.key,
+                *user_transfer_authority_info.key,
+                *swap_in_info.key,
+                *user_destination_info.key,
+                token_program_id,
+                callback_infos
+                    .iter()
+                    .map(|info| AccountMeta {
+                        pubkey: *info.key,
+                        is_signer: info.is_signer,
+                        is_writable: info.is_writable,
+                    })
+                    .collect(),
+                ReceiveFlashSwapData {
+                    amount_in,
+                    amount_out,
+                    swap_direction,
+                    data: data.to_vec(),
+                },
+            ),
+            &[
+                &[
+                    swap_info.clone(),
+                    user_transfer_authority_info.clone(),
+                    swap_in_info.clone(),
+                    user_destination_info.clone(),
+                    token_program_info.clone(),
+                    callback_program_info.clone(),
+                ],
+                callback_infos,
+            ]
+            .concat(),
+        )?;
+        // the admin fee paid out of the input reserve is not paid back
+        let admin_fee_in = if admin_fee_source_info.key == swap_in_info.key {
+            admin_fee
+        } else {
+            0
+        };
+        let swap_in = unpack_token_account(swap_in_info, &token_program_id)?;
+        let repaid_amount = reserve_in
+            .checked_add(amount_in)
+            .and_then(|amount| amount.checked_sub(admin_fee_in))
+            .ok_or(SwapError::CalculationFailure)?;
+        if swap_in.amount < repaid_amount {
+            return Err(SwapError::FlashSwapNotRepaid.into());
+        }
+    }
+    // trade rewards are prorated to what is left of the epoch budget
+    let amount_to_reward = amount_to_reward.min(config.epoch_rewards_left(clock.epoch));
+    record_rewards_minted(config_info, &mut config, clock.epoch, amount_to_reward)?;
+    token_mint_to(
+        config_info.key,
+        token_program_info.clone(),
+        reward_mint_info.clone(),
+        reward_token_info.clone(),
+        market_authority_info.clone(),
+        market_nonce,
+        amount_to_reward,
+    )?;
 
+    Event::Swap(SwapEvent {
+        swap: *swap_info.key,
+        swap_direction,
+        amount_in,
+        amount_out,
+        trade_fee,
+        admin_fee,
+        base_reserve: base_balance,
+        quote_reserve: quote_balance,
+        market_price: new_market_price.to_scaled_val()?,
+    })
+    .emit();
+
+    update_price_feed(
+        program_id,
+        swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
     Ok(())
 }
 
+/// Accounts of a `Swap` before the OpenBook market and transfer hook program
+const SWAP_ACCOUNTS_LEN: usize = 20;
+
+/// Split the `Swap` accounts of several swaps, with the OpenBook market and
+/// transfer hook program of their token-swap if any. Each token-swap appears
+/// once
+fn split_swap_accounts<'a, 'b>(
+    program_id: &Pubkey,
+    swap_count: usize,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<Vec<&'a [AccountInfo<'b>]>, ProgramError> {
+    if swap_count == 0 {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let mut rest = accounts;
+    let mut swap_accounts = Vec::with_capacity(swap_count);
+    let mut swap_keys = Vec::with_capacity(swap_count);
+    for _ in 0..swap_count {
+        let swap_info = rest.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if swap_keys.contains(swap_info.key) {
+            return Err(SwapError::InvalidInput.into());
+        }
+        swap_keys.push(*swap_info.key);
+        let (has_openbook_market, has_transfer_hook) = {
+            let swap_data = swap_info.data.borrow();
+            let token_swap = SwapInfoData::load(&swap_data)?;
+            (
+                token_swap.openbook_market != Pubkey::default(),
+                token_swap.transfer_hook_program != Pubkey::default(),
+            )
+        };
+        let len = SWAP_ACCOUNTS_LEN + 3 * has_openbook_market as usize + has_transfer_hook as usize;
+        if rest.len() < len {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (leg_accounts, next) = rest.split_at(len);
+        swap_accounts.push(leg_accounts);
+        rest = next;
+    }
+    Ok(swap_accounts)
+}
+
+/// Swap with the `Swap` accounts, returning the output received by the user
+fn process_swap_leg(
+    program_id: &Pubkey,
+    swap_data: &SwapData,
+    swap_accounts: &[AccountInfo],
+) -> Result<u64, ProgramError> {
+    let user_destination_info = match swap_data.swap_direction {
+        SwapDirection::SellBase => &swap_accounts[8],
+        SwapDirection::SellQuote => &swap_accounts[5],
+    };
+    let token_program_id = *swap_accounts[15].key;
+    let balance_before = unpack_token_account(user_destination_info, &token_program_id)?.amount;
+    process_swap(
+        program_id,
+        swap_data.amount_in,
+        swap_data.minimum_amount_out,
+        swap_data.swap_direction,
+        swap_data.max_price_impact_bps,
+        None,
+        None,
+        swap_accounts,
+    )?;
+    let balance_after = unpack_token_account(user_destination_info, &token_program_id)?.amount;
+    balance_after
+        .checked_sub(balance_before)
+        .ok_or_else(|| SwapError::CalculationFailure.into())
+}
+
+fn process_batch_swap(
+    program_id: &Pubkey,
+    swaps: &[SwapData],
+    minimum_total_amount_out: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let swap_accounts = split_swap_accounts(program_id, swaps.len(), accounts)?;
+
+    let mut total_amount_out = 0u64;
+    for (swap_data, swap_accounts) in swaps.iter().zip(swap_accounts) {
+        let amount_out = process_swap_leg(program_id, swap_data, swap_accounts)?;
+        total_amount_out = total_amount_out
+            .checked_add(amount_out)
+            .ok_or(SwapError::CalculationFailure)?;
+    }
+
+    if total_amount_out < minimum_total_amount_out {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    Ok(())
+}
+
+fn process_split_swap(
+    program_id: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    swap_direction: SwapDirection,
+    fractions_bps: &[u16],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    if fractions_bps.contains(&0)
+        || fractions_bps
+            .iter()
+            .map(|fraction_bps| *fraction_bps as u64)
+            .sum::<u64>()
+            != BPS_DENOMINATOR
+    {
+        return Err(SwapError::InvalidInput.into());
+    }
+    let swap_accounts = split_swap_accounts(program_id, fractions_bps.len(), accounts)?;
+
+    // the same pair, sold from and bought into the same user accounts
+    let swap_pair = |swap_accounts: &[AccountInfo]| -> Result<(Pubkey, Pubkey), ProgramError> {
+        let swap_data = swap_accounts[1].data.borrow();
+        let token_swap = SwapInfoData::load(&swap_data)?;
+        Ok((token_swap.token_a_mint, token_swap.token_b_mint))
+    };
+    let first_accounts = swap_accounts[0];
+    let pair = swap_pair(first_accounts)?;
+    for leg_accounts in &swap_accounts[1..] {
+        if swap_pair(leg_accounts)? != pair
+            || leg_accounts[5].key != first_accounts[5].key
+            || leg_accounts[8].key != first_accounts[8].key
+        {
+            return Err(SwapError::InvalidInput.into());
+        }
+    }
+
+    let mut amount_left = amount_in;
+    let mut total_amount_out = 0u64;
+    let last_index = fractions_bps.len() - 1;
+    for (index, (fraction_bps, leg_accounts)) in fractions_bps.iter().zip(swap_accounts).enumerate()
+    {
+        // the last token-swap gets what rounding leaves
+        let leg_amount_in = if index == last_index {
+            amount_left
+        } else {
+            (amount_in as u128 * *fraction_bps as u128 / BPS_DENOMINATOR as u128) as u64
+        };
+        amount_left -= leg_amount_in;
+        let amount_out = process_swap_leg(
+            program_id,
+            &SwapData {
+                amount_in: leg_amount_in,
+                minimum_amount_out: 0,
+                swap_direction,
+                max_price_impact_bps: 0,
+            },
+            leg_accounts,
+        )?;
+        total_amount_out = total_amount_out
+            .checked_add(amount_out)
+            .ok_or(SwapError::CalculationFailure)?;
+    }
+
+    if total_amount_out < minimum_amount_out {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+    Ok(())
+}
+
+fn process_swap_native(
+    program_id: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    swap_direction: SwapDirection,
+    max_price_impact_bps: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let wallet_info = next_account_info(account_info_iter)?;
+    let native_account_info = next_account_info(account_info_iter)?;
+    let native_mint_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let swap_accounts = account_info_iter.as_slice();
+
+    // user token accounts and token program of the `Swap` accounts
+    let (source_info, destination_info, token_program_info) = match (
+        swap_accounts.get(5),
+        swap_accounts.get(8),
+        swap_accounts.get(15),
+    ) {
+        (Some(source_info), Some(destination_info), Some(token_program_info)) => {
+            (source_info, destination_info, token_program_info)
+        }
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+    let (user_source_info, user_destination_info) = match swap_direction {
+        SwapDirection::SellBase => (source_info, destination_info),
+        SwapDirection::SellQuote => (destination_info, source_info),
+    };
+    let wrap_amount = if native_account_info.key == user_source_info.key {
+        amount_in
+    } else if native_account_info.key == user_destination_info.key {
+        0
+    } else {
+        return Err(SwapError::InvalidInput.into());
+    };
+
+    wrap_native_sol(
+        wallet_info,
+        native_account_info,
+        native_mint_info,
+        rent_info,
+        system_program_info,
+        token_program_info,
+        wrap_amount,
+    )?;
+    process_swap(
+        program_id,
+        amount_in,
+        minimum_amount_out,
+        swap_direction,
+        max_price_impact_bps,
+        None,
+        None,
+        swap_accounts,
+    )?;
+    unwrap_native_sol(wallet_info, native_account_info, token_program_info)
+}
+
+fn process_relayed_swap(
+    program_id: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    swap_direction: SwapDirection,
+    max_price_impact_bps: u64,
+    relayer_fee: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let relayer_fee_info = next_account_info(account_info_iter)?;
+    let swap_accounts = account_info_iter.as_slice();
+    if !fee_payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // user transfer authority, user token accounts and token program of the
+    // `Swap` accounts
+    let (user_transfer_authority_info, source_info, destination_info, token_program_info) = match (
+        swap_accounts.get(4),
+        swap_accounts.get(5),
+        swap_accounts.get(8),
+        swap_accounts.get(15),
+    ) {
+        (
+            Some(user_transfer_authority_info),
+            Some(source_info),
+            Some(destination_info),
+            Some(token_program_info),
+        ) => (
+            user_transfer_authority_info,
+            source_info,
+            destination_info,
+            token_program_info,
+        ),
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+    if !user_transfer_authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // the trader is the signing owner of the account sold from, whoever pays
+    // for the transaction
+    let user_source_info = match swap_direction {
+        SwapDirection::SellBase => source_info,
+        SwapDirection::SellQuote => destination_info,
+    };
+    if unpack_token_account(user_source_info, token_program_info.key)?.owner
+        != *user_transfer_authority_info.key
+    {
+        return Err(SwapError::InvalidOwner.into());
+    }
+
+    // the relayer fee is signed by the owner along with the swap
+    if relayer_fee > 0 {
+        invoke(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                user_source_info.key,
+                relayer_fee_info.key,
+                user_transfer_authority_info.key,
+                &[],
+                relayer_fee,
+            )?,
+            &[
+                user_source_info.clone(),
+                relayer_fee_info.clone(),
+                user_transfer_authority_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+    }
+
+    process_swap(
+        program_id,
+        amount_in,
+        minimum_amount_out,
+        swap_direction,
+        max_price_impact_bps,
+        None,
+        None,
+        swap_accounts,
+    )
+}
+
+fn process_settle_signed_order(
+    program_id: &Pubkey,
+    order: SignedOrder,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let instructions_info = next_account_info(account_info_iter)?;
+    let order_nonce_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let swap_accounts = account_info_iter.as_slice();
+
+    if !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    if order.program_id != *program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    // the clock sysvar passed to the swap
+    let clock = &Clock::from_account_info(
+        swap_accounts
+            .get(14)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?,
+    )?;
+    if clock.unix_timestamp > order.expiry {
+        return Err(SwapError::OrderExpired.into());
+    }
+
+    // the runtime verifies the signatures of the ed25519 instruction before
+    // the transaction executes, it is left to check they are over this order
+    // by its maker
+    let verify_instruction = match load_current_index_checked(instructions_info)?.checked_sub(1) {
+        Some(index) => load_instruction_at_checked(index as usize, instructions_info)?,
+        None => return Err(SwapError::InvalidOrderSignature.into()),
+    };
+    if verify_instruction.program_id != ed25519_program::id() {
+        return Err(SwapError::InvalidOrderSignature.into());
+    }
+    check_ed25519_instruction_data(&verify_instruction.data, &order.maker, &order.pack())?;
+
+    // config, token-swap, user transfer authority, user token accounts, reward
+    // account and token program of the `Swap` accounts, the swap checking the
+    // token-swap belongs to the config
+    let (config_info, swap_info, user_transfer_authority_info, token_program_info) = match (
+        swap_accounts.first(),
+        swap_accounts.get(1),
+        swap_accounts.get(4),
+        swap_accounts.get(15),
+    ) {
+        (
+            Some(config_info),
+            Some(swap_info),
+            Some(user_transfer_authority_info),
+            Some(token_program_info),
+        ) => (
+            config_info,
+            swap_info,
+            user_transfer_authority_info,
+            token_program_info,
+        ),
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+    if *config_info.key != order.config {
+        return Err(SwapError::IncorrectConfigAccount.into());
+    }
+    if *swap_info.key != order.swap {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    let (order_authority_key, order_authority_bump) = find_order_authority_address(program_id);
+    if *user_transfer_authority_info.key != order_authority_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    // the output and the trade rewards go to the maker
+    for index in [5, 8, 9] {
+        if unpack_token_account(&swap_accounts[index], token_program_info.key)?.owner != order.maker
+        {
+            return Err(SwapError::InvalidOwner.into());
+        }
+    }
+
+    init_order_nonce(
+        program_id,
+        order_nonce_info,
+        &order.maker,
+        payer_info,
+        system_program_info,
+        rent,
+    )?;
+    OrderNonce::load_mut(&mut order_nonce_info.data.borrow_mut())?.use_nonce(order.nonce)?;
+
+    process_swap(
+        program_id,
+        order.amount_in,
+        order.minimum_amount_out,
+        order.swap_direction,
+        0,
+        None,
+        Some(&[ORDER_AUTHORITY_SEED, &[order_authority_bump]]),
+        swap_accounts,
+    )
+}
+
+fn process_cancel_signed_orders(
+    program_id: &Pubkey,
+    next_nonce: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let order_nonce_info = next_account_info(account_info_iter)?;
+    let maker_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if !maker_info.is_signer || !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    init_order_nonce(
+        program_id,
+        order_nonce_info,
+        maker_info.key,
+        payer_info,
+        system_program_info,
+        rent,
+    )?;
+    OrderNonce::load_mut(&mut order_nonce_info.data.borrow_mut())?.cancel_below(next_nonce)
+}
+
+/// Create and initialize the order nonce of the maker if it does not exist yet
+fn init_order_nonce<'a>(
+    program_id: &Pubkey,
+    order_nonce_info: &AccountInfo<'a>,
+    maker: &Pubkey,
+    payer_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    rent: &Rent,
+) -> ProgramResult {
+    let (order_nonce_key, bump_seed) = find_order_nonce_address(program_id, maker);
+    if *order_nonce_info.key != order_nonce_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if order_nonce_info.owner == program_id {
+        return Ok(());
+    }
+
+    create_program_account(
+        program_id,
+        order_nonce_info,
+        payer_info,
+        system_program_info,
+        rent,
+        ORDER_NONCE_SIZE,
+        &[maker.as_ref(), ORDER_NONCE_SEED, &[bump_seed]],
+    )?;
+    OrderNonce::init(&mut order_nonce_info.data.borrow_mut(), *maker, bump_seed)?;
+    Ok(())
+}
+
+fn process_create_dca_plan(
+    program_id: &Pubkey,
+    plan_data: CreateDcaPlanData,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let dca_plan_info = next_account_info(account_info_iter)?;
+    let escrow_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(rent_info)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let associated_token_program_info = next_account_info(account_info_iter)?;
+
+    if !owner_info.is_signer || !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    if swap_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *token_program_info.key != spl_token::id() {
+        return Err(SwapError::IncorrectTokenProgramId.into());
+    }
+    let mint = {
+        let swap_data = swap_info.data.borrow();
+        let swap_data = SwapInfoData::load(&swap_data)?;
+        match plan_data.swap_direction {
+            SwapDirection::SellBase => swap_data.token_a_mint,
+            SwapDirection::SellQuote => swap_data.token_b_mint,
+        }
+    };
+    if *mint_info.key != mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+
+    let (dca_plan_key, bump_seed) =
+        find_dca_plan_address(program_id, swap_info.key, owner_info.key);
+    if *dca_plan_info.key != dca_plan_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if dca_plan_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+    let escrow_key =
+        spl_associated_token_account::get_associated_token_address(&dca_plan_key, &mint);
+    if *escrow_info.key != escrow_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+
+    create_program_account(
+        program_id,
+        dca_plan_info,
+        payer_info,
+        system_program_info,
+        rent,
+        DCA_PLAN_SIZE,
+        &[
+            swap_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            DCA_PLAN_SEED,
+            &[bump_seed],
+        ],
+    )?;
+    DcaPlan::init(
+        &mut dca_plan_info.data.borrow_mut(),
+        bump_seed,
+        *swap_info.key,
+        *owner_info.key,
+        escrow_key,
+        &plan_data,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    if escrow_info.data_is_empty() {
+        invoke(
+            &create_associated_token_account(payer_info.key, dca_plan_info.key, mint_info.key),
+            &[
+                payer_info.clone(),
+                escrow_info.clone(),
+                dca_plan_info.clone(),
+                mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                rent_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+    }
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program_info.key,
+            source_info.key,
+            escrow_info.key,
+            owner_info.key,
+            &[],
+            plan_data.amount,
+        )?,
+        &[
+            source_info.clone(),
+            escrow_info.clone(),
+            owner_info.clone(),
+            token_program_info.clone(),
+        ],
+    )
+}
+
+fn process_execute_dca(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let dca_plan_info = next_account_info(account_info_iter)?;
+    let keeper_token_info = next_account_info(account_info_iter)?;
+    let swap_accounts = account_info_iter.as_slice();
+
+    if dca_plan_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    // token-swap, user transfer authority, user token accounts, reward
+    // account, clock sysvar and token program of the `Swap` accounts
+    let (
+        swap_info,
+        user_transfer_authority_info,
+        source_info,
+        destination_info,
+        reward_token_info,
+        clock_info,
+        token_program_info,
+    ) = match (
+        swap_accounts.get(1),
+        swap_accounts.get(4),
+        swap_accounts.get(5),
+        swap_accounts.get(8),
+        swap_accounts.get(9),
+        swap_accounts.get(14),
+        swap_accounts.get(15),
+    ) {
+        (
+            Some(swap_info),
+            Some(user_transfer_authority_info),
+            Some(source_info),
+            Some(destination_info),
+            Some(reward_token_info),
+            Some(clock_info),
+            Some(token_program_info),
+        ) => (
+            swap_info,
+            user_transfer_authority_info,
+            source_info,
+            destination_info,
+            reward_token_info,
+            clock_info,
+            token_program_info,
+        ),
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+    let clock = &Clock::from_account_info(clock_info)?;
+
+    let (owner, bump_seed, swap_direction, max_price_impact_bps, amount_in, keeper_reward) = {
+        let mut dca_plan_data = dca_plan_info.data.borrow_mut();
+        let dca_plan = DcaPlan::load_mut(&mut dca_plan_data)?;
+        if *swap_info.key != dca_plan.swap {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        if user_transfer_authority_info.key != dca_plan_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        let swap_direction = dca_plan.swap_direction()?;
+        let (escrow_info, owner_token_info) = match swap_direction {
+            SwapDirection::SellBase => (source_info, destination_info),
+            SwapDirection::SellQuote => (destination_info, source_info),
+        };
+        if *escrow_info.key != dca_plan.escrow {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        // the output and the trade rewards go to the owner
+        for info in [owner_token_info, reward_token_info] {
+            if unpack_token_account(info, token_program_info.key)?.owner != dca_plan.owner {
+                return Err(SwapError::InvalidOwner.into());
+            }
+        }
+
+        let (amount_in, keeper_reward) = dca_plan.execute_period(clock.unix_timestamp)?;
+        (
+            dca_plan.owner,
+            dca_plan.bump_seed,
+            swap_direction,
+            dca_plan.max_price_impact_bps(),
+            amount_in,
+            keeper_reward,
+        )
+    };
+    let dca_plan_seeds: &[&[u8]] = &[
+        swap_info.key.as_ref(),
+        owner.as_ref(),
+        DCA_PLAN_SEED,
+        &[bump_seed],
+    ];
+
+    if keeper_reward > 0 {
+        let escrow_info = match swap_direction {
+            SwapDirection::SellBase => source_info,
+            SwapDirection::SellQuote => destination_info,
+        };
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                escrow_info.key,
+                keeper_token_info.key,
+                dca_plan_info.key,
+                &[],
+                keeper_reward,
+            )?,
+            &[
+                escrow_info.clone(),
+                keeper_token_info.clone(),
+                dca_plan_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[dca_plan_seeds],
+        )?;
+    }
+
+    // periods are swapped at any price within the price impact limit of the
+    // plan, their amount is not known to the owner in advance. The limit is
+    // never 0, so the keeper cannot sell the period at any price.
+    process_swap(
+        program_id,
+        amount_in,
+        0,
+        swap_direction,
+        max_price_impact_bps,
+        None,
+        Some(dca_plan_seeds),
+        swap_accounts,
+    )
+}
+
+fn process_cancel_dca(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let dca_plan_info = next_account_info(account_info_iter)?;
+    let escrow_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if dca_plan_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    if *token_program_info.key != spl_token::id() {
+        return Err(SwapError::IncorrectTokenProgramId.into());
+    }
+    let (swap, bump_seed) = {
+        let dca_plan_data = dca_plan_info.data.borrow();
+        let dca_plan = DcaPlan::load(&dca_plan_data)?;
+        if dca_plan.owner != *owner_info.key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if dca_plan.escrow != *escrow_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        (dca_plan.swap, dca_plan.bump_seed)
+    };
+    let dca_plan_seeds: &[&[u8]] = &[
+        swap.as_ref(),
+        owner_info.key.as_ref(),
+        DCA_PLAN_SEED,
+        &[bump_seed],
+    ];
+
+    let escrow = unpack_token_account(escrow_info, token_program_info.key)?;
+    if escrow.amount > 0 {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                escrow_info.key,
+                destination_info.key,
+                dca_plan_info.key,
+                &[],
+                escrow.amount,
+            )?,
+            &[
+                escrow_info.clone(),
+                destination_info.clone(),
+                dca_plan_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[dca_plan_seeds],
+        )?;
+    }
+    invoke_signed(
+        &spl_token::instruction::close_account(
+            token_program_info.key,
+            escrow_info.key,
+            owner_info.key,
+            dca_plan_info.key,
+            &[],
+        )?,
+        &[
+            escrow_info.clone(),
+            owner_info.clone(),
+            dca_plan_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[dca_plan_seeds],
+    )?;
+
+    let owner_lamports = owner_info
+        .lamports()
+        .checked_add(dca_plan_info.lamports())
+        .ok_or(SwapError::CalculationFailure)?;
+    **owner_info.lamports.borrow_mut() = owner_lamports;
+    **dca_plan_info.lamports.borrow_mut() = 0;
+    dca_plan_info.data.borrow_mut().fill(0);
+
+    Ok(())
+}
+
+fn process_quote_swap(
+    program_id: &Pubkey,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let (quote, pool_state, _, rest) =
+        quote_swap_from_accounts(program_id, amount_in, swap_direction, accounts)?;
+    let mut return_data = quote.amount_out.to_le_bytes().to_vec();
+
+    // the pool mint passed after the quote accounts appends the value of a
+    // pool token at the repriced mid price
+    if let (Some(swap_info), Some(token_program_info), Some(pool_mint_info)) =
+        (accounts.first(), accounts.get(6), rest.first())
+    {
+        if *pool_mint_info.key != SwapInfoData::load(&swap_info.data.borrow())?.pool_mint {
+            return Err(SwapError::IncorrectMint.into());
+        }
+        let pool_mint = unpack_mint(pool_mint_info, token_program_info.key)?;
+        let lp_virtual_price = pool_state.lp_virtual_price(pool_mint.supply)?;
+        return_data.extend_from_slice(&lp_virtual_price.to_scaled_val()?.to_le_bytes());
+    }
+    set_return_data(&return_data);
+    Ok(())
+}
+
+fn process_init_quote_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let quote_account_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    let (quote_account_key, bump_seed) = find_quote_account_address(program_id, owner_info.key);
+    if *quote_account_info.key != quote_account_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if quote_account_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    create_program_account(
+        program_id,
+        quote_account_info,
+        payer_info,
+        system_program_info,
+        rent,
+        QUOTE_ACCOUNT_SIZE,
+        &[owner_info.key.as_ref(), QUOTE_ACCOUNT_SEED, &[bump_seed]],
+    )?;
+    QuoteAccount::init(
+        &mut quote_account_info.data.borrow_mut(),
+        *owner_info.key,
+        bump_seed,
+    )?;
+
+    Ok(())
+}
+
+fn process_calc_receive_amount(
+    program_id: &Pubkey,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let quote_account_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if quote_account_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if !owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    if QuoteAccount::load(&quote_account_info.data.borrow())?.owner != *owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+
+    let (quote, _, slot, _) =
+        quote_swap_from_accounts(program_id, amount_in, swap_direction, &accounts[2..])?;
+    QuoteAccount::load_mut(&mut quote_account_info.data.borrow_mut())?.record(
+        *swap_info.key,
+        slot,
+        amount_in,
+        swap_direction,
+        &quote,
+    );
+
+    Ok(())
+}
+
+/// Quote a swap from the accounts of a `QuoteSwap` instruction, along with the
+/// pool state repriced to the market, the slot the quote was taken at and the
+/// accounts following the quote accounts
+fn quote_swap_from_accounts<'a, 'b>(
+    program_id: &Pubkey,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<(SwapQuote, PoolState, u64, &'a [AccountInfo<'b>]), ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let swap_source_info = next_account_info(account_info_iter)?;
+    let swap_destination_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    check_observation_buffer(program_id, swap_info, observation_buffer_info)?;
+
+    let swap_data = swap_info.data.borrow();
+    let token_swap = SwapInfoData::load(&swap_data)?;
+    if token_swap.is_paused()? {
+        return Err(SwapError::IsPaused.into());
+    }
+    let (openbook_infos, rest) =
+        split_openbook_market(&token_swap.openbook_market, account_info_iter.as_slice())?;
+    if !(*swap_source_info.key == token_swap.token_a || *swap_source_info.key == token_swap.token_b)
+    {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if !(*swap_destination_info.key == token_swap.token_a
+        || *swap_destination_info.key == token_swap.token_b)
+    {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *swap_source_info.key == *swap_destination_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if *pyth_a_price_info.key != token_swap.pyth_a || *pyth_b_price_info.key != token_swap.pyth_b {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+
+    let reserve_in = match swap_direction {
+        SwapDirection::SellBase => {
+            unpack_token_account(swap_source_info, token_program_info.key)?.amount
+        }
+        SwapDirection::SellQuote => {
+            unpack_token_account(swap_destination_info, token_program_info.key)?.amount
+        }
+    };
+    let (pool_state, _, quote) = quote_market_swap(
+        token_swap,
+        observation_buffer_info,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        openbook_infos,
+        clock,
+        reserve_in,
+        amount_in,
+        swap_direction,
+    )?;
+
+    Ok((quote, pool_state, clock.slot, rest))
+}
+
 fn process_deposit(
     program_id: &Pubkey,
-    token_a_amount: u64,
-    token_b_amount: u64,
-    min_mint_amount: u64,
+    token_a_amount: u64,
+    token_b_amount: u64,
+    min_mint_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let user_transfer_authority_info = next_account_info(account_info_iter)?;
+    let source_a_info = next_account_info(account_info_iter)?;
+    let source_b_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    check_observation_buffer(program_id, swap_info, observation_buffer_info)?;
+
+    let mut swap_data = swap_info.data.borrow_mut();
+    let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+    if token_swap.is_paused()? {
+        return Err(SwapError::IsPaused.into());
+    }
+    let (openbook_infos, rest) =
+        split_openbook_market(&token_swap.openbook_market, account_info_iter.as_slice())?;
+    let (transfer_hook_info, position_page_infos) =
+        split_transfer_hook(&token_swap.transfer_hook_program, rest)?;
+
+    let nonce = token_swap.nonce;
+    if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if *token_a_info.key != token_swap.token_a {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if token_a_info.key == source_a_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if token_b_info.key == source_b_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if *pyth_a_price_info.key != token_swap.pyth_a || *pyth_b_price_info.key != token_swap.pyth_b {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let (mut liquidity_provider, position_pages) =
+        unpack_liquidity_positions(program_id, liquidity_provider_info, position_page_infos)?;
+    check_liquidity_provider_authority(
+        program_id,
+        &liquidity_provider,
+        liquidity_provider_info,
+        liquidity_owner_info,
+    )?;
+
+    let token_program_id = *token_program_info.key;
+    let token_a = unpack_token_account(token_a_info, &token_program_id)?;
+    let token_b = unpack_token_account(token_b_info, &token_program_id)?;
+    let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
+
+    // updating price from pyth price
+    let mut pool_state = token_swap.pool_state()?;
+    let (new_market_price, twap) = get_new_market_price(
+        token_swap,
+        observation_buffer_info,
+        &mut pool_state,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        openbook_infos,
+        clock,
+    )?;
+
+    let mut state = PoolState::new(PoolState {
+        market_price: new_market_price,
+        ..pool_state
+    })?;
+
+    let base_balance = token_a_amount
+        .checked_add(token_a.amount)
+        .ok_or(SwapError::CalculationFailure)?;
+    let quote_balance = token_b_amount
+        .checked_add(token_b.amount)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    check_reserve_caps(token_swap, base_balance, quote_balance)?;
+
+    let pool_mint_amount = state.buy_shares(base_balance, quote_balance, pool_mint.supply)?;
+
+    if pool_mint_amount < min_mint_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+
+    let reward_per_share = accumulate_rewards(token_swap, clock)?;
+    let position = liquidity_provider.find_or_add_position(*swap_info.key, clock.unix_timestamp)?;
+    position.calc_and_update_rewards(reward_per_share, clock.unix_timestamp)?;
+    position.deposit(pool_mint_amount)?;
+    check_user_liquidity_cap(token_swap, position.liquidity_amount)?;
+    pack_liquidity_positions(
+        liquidity_provider,
+        position_pages,
+        liquidity_provider_info,
+        position_page_infos,
+    )?;
+
+    token_swap.set_pool_state(&state);
+    update_twap(token_swap, observation_buffer_info, &twap, clock)?;
+    drop(swap_data);
+
+    if let Some(transfer_hook_info) = transfer_hook_info {
+        invoke(
+            &check_deposit(
+                *transfer_hook_info.key,
+                *swap_info.key,
+                *liquidity_owner_info.key,
+                CheckDepositData {
+                    trader: *liquidity_owner_info.key,
+                    token_a_amount,
+                    token_b_amount,
+                },
+            ),
+            &[
+                swap_info.clone(),
+                liquidity_owner_info.clone(),
+                transfer_hook_info.clone(),
+            ],
+        )?;
+    }
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        source_a_info.clone(),
+        token_a_info.clone(),
+        user_transfer_authority_info.clone(),
+        nonce,
+        token_a_amount,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        source_b_info.clone(),
+        token_b_info.clone(),
+        user_transfer_authority_info.clone(),
+        nonce,
+        token_b_amount,
+    )?;
+    token_mint_to(
+        swap_info.key,
+        token_program_info.clone(),
+        pool_mint_info.clone(),
+        destination_info.clone(),
+        authority_info.clone(),
+        nonce,
+        pool_mint_amount,
+    )?;
+
+    Event::Deposit(LiquidityEvent {
+        swap: *swap_info.key,
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount: pool_mint_amount,
+        base_reserve: base_balance,
+        quote_reserve: quote_balance,
+    })
+    .emit();
+
+    update_price_feed(
+        program_id,
+        swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
+    Ok(())
+}
+
+/// Fails when a deposit takes a pool reserve above its cap
+fn check_reserve_caps(
+    token_swap: &SwapInfoData,
+    base_reserve: u64,
+    quote_reserve: u64,
+) -> ProgramResult {
+    let max_base_reserve = token_swap.max_base_reserve();
+    let max_quote_reserve = token_swap.max_quote_reserve();
+    if (max_base_reserve > 0 && base_reserve > max_base_reserve)
+        || (max_quote_reserve > 0 && quote_reserve > max_quote_reserve)
+    {
+        return Err(SwapError::ReserveCapExceeded.into());
+    }
+    Ok(())
+}
+
+/// Fails when a deposit takes a liquidity position above the user cap
+fn check_user_liquidity_cap(token_swap: &SwapInfoData, liquidity_amount: u64) -> ProgramResult {
+    let max_user_liquidity = token_swap.max_user_liquidity();
+    if max_user_liquidity > 0 && liquidity_amount > max_user_liquidity {
+        return Err(SwapError::UserLiquidityCapExceeded.into());
+    }
+    Ok(())
+}
+
+fn process_deposit_native(
+    program_id: &Pubkey,
+    token_a_amount: u64,
+    token_b_amount: u64,
+    min_mint_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let wallet_info = next_account_info(account_info_iter)?;
+    let native_account_info = next_account_info(account_info_iter)?;
+    let native_mint_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let deposit_accounts = account_info_iter.as_slice();
+
+    // user token accounts and token program of the `Deposit` accounts
+    let (source_a_info, source_b_info, token_program_info) = match (
+        deposit_accounts.get(3),
+        deposit_accounts.get(4),
+        deposit_accounts.get(14),
+    ) {
+        (Some(source_a_info), Some(source_b_info), Some(token_program_info)) => {
+            (source_a_info, source_b_info, token_program_info)
+        }
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+    let wrap_amount = if native_account_info.key == source_a_info.key {
+        token_a_amount
+    } else if native_account_info.key == source_b_info.key {
+        token_b_amount
+    } else {
+        return Err(SwapError::InvalidInput.into());
+    };
+
+    wrap_native_sol(
+        wallet_info,
+        native_account_info,
+        native_mint_info,
+        rent_info,
+        system_program_info,
+        token_program_info,
+        wrap_amount,
+    )?;
+    process_deposit(
+        program_id,
+        token_a_amount,
+        token_b_amount,
+        min_mint_amount,
+        deposit_accounts,
+    )?;
+    unwrap_native_sol(wallet_info, native_account_info, token_program_info)
+}
+
+fn process_deposit_imbalanced(
+    program_id: &Pubkey,
+    token_a_amount: u64,
+    token_b_amount: u64,
+    min_mint_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let user_transfer_authority_info = next_account_info(account_info_iter)?;
+    let source_a_info = next_account_info(account_info_iter)?;
+    let source_b_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_a_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_b_info = next_account_info(account_info_iter)?;
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    check_observation_buffer(program_id, swap_info, observation_buffer_info)?;
+
+    let mut swap_data = swap_info.data.borrow_mut();
+    let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+    if token_swap.is_paused()? {
+        return Err(SwapError::IsPaused.into());
+    }
+    let (openbook_infos, rest) =
+        split_openbook_market(&token_swap.openbook_market, account_info_iter.as_slice())?;
+    let (transfer_hook_info, position_page_infos) =
+        split_transfer_hook(&token_swap.transfer_hook_program, rest)?;
+
+    let nonce = token_swap.nonce;
+    if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if *token_a_info.key != token_swap.token_a {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if token_a_info.key == source_a_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if token_b_info.key == source_b_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if *admin_fee_dest_a_info.key != token_swap.admin_fee_key_a {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+    if *admin_fee_dest_b_info.key != token_swap.admin_fee_key_b {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+    if *pyth_a_price_info.key != token_swap.pyth_a || *pyth_b_price_info.key != token_swap.pyth_b {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let (mut liquidity_provider, position_pages) =
+        unpack_liquidity_positions(program_id, liquidity_provider_info, position_page_infos)?;
+    check_liquidity_provider_authority(
+        program_id,
+        &liquidity_provider,
+        liquidity_provider_info,
+        liquidity_owner_info,
+    )?;
+
+    let token_program_id = *token_program_info.key;
+    let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
+
+    // updating price from pyth price
+    let mut pool_state = token_swap.pool_state()?;
+    let (new_market_price, twap) = get_new_market_price(
+        token_swap,
+        observation_buffer_info,
+        &mut pool_state,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        openbook_infos,
+        clock,
+    )?;
+
+    let state = PoolState::new(PoolState {
+        market_price: new_market_price,
+        ..pool_state
+    })?;
+
+    let quote = state.quote_deposit_imbalanced(
+        token_a_amount,
+        token_b_amount,
+        pool_mint.supply,
+        &token_swap.fees()?,
+        &token_swap.directional_fees()?,
+    )?;
+    if quote.pool_token_amount < min_mint_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+
+    let reward_per_share = accumulate_rewards(token_swap, clock)?;
+    let position = liquidity_provider.find_or_add_position(*swap_info.key, clock.unix_timestamp)?;
+    position.calc_and_update_rewards(reward_per_share, clock.unix_timestamp)?;
+    position.deposit(quote.pool_token_amount)?;
+    check_user_liquidity_cap(token_swap, position.liquidity_amount)?;
+    pack_liquidity_positions(
+        liquidity_provider,
+        position_pages,
+        liquidity_provider_info,
+        position_page_infos,
+    )?;
+
+    let base_reserve = quote.pool_state.base_reserve.try_floor_u64()?;
+    let quote_reserve = quote.pool_state.quote_reserve.try_floor_u64()?;
+    check_reserve_caps(token_swap, base_reserve, quote_reserve)?;
+    token_swap.set_pool_state(&quote.pool_state);
+    update_twap(token_swap, observation_buffer_info, &twap, clock)?;
+    drop(swap_data);
+
+    if let Some(transfer_hook_info) = transfer_hook_info {
+        invoke(
+            &check_deposit(
+                *transfer_hook_info.key,
+                *swap_info.key,
+                *liquidity_owner_info.key,
+                CheckDepositData {
+                    trader: *liquidity_owner_info.key,
+                    token_a_amount,
+                    token_b_amount,
+                },
+            ),
+            &[
+                swap_info.clone(),
+                liquidity_owner_info.clone(),
+                transfer_hook_info.clone(),
+            ],
+        )?;
+    }
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        source_a_info.clone(),
+        token_a_info.clone(),
+        user_transfer_authority_info.clone(),
+        nonce,
+        token_a_amount,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        source_b_info.clone(),
+        token_b_info.clone(),
+        user_transfer_authority_info.clone(),
+        nonce,
+        token_b_amount,
+    )?;
+    // the admin share of the trade fee leaves in the swapped out token
+    let (swap_out_info, admin_fee_dest_info) = match quote.swap_direction {
+        SwapDirection::SellBase => (token_b_info, admin_fee_dest_b_info),
+        SwapDirection::SellQuote => (token_a_info, admin_fee_dest_a_info),
+    };
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        swap_out_info.clone(),
+        admin_fee_dest_info.clone(),
+        authority_info.clone(),
+        nonce,
+        quote.admin_fee,
+    )?;
+    token_mint_to(
+        swap_info.key,
+        token_program_info.clone(),
+        pool_mint_info.clone(),
+        destination_info.clone(),
+        authority_info.clone(),
+        nonce,
+        quote.pool_token_amount,
+    )?;
+
+    Event::Deposit(LiquidityEvent {
+        swap: *swap_info.key,
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount: quote.pool_token_amount,
+        base_reserve,
+        quote_reserve,
+    })
+    .emit();
+
+    update_price_feed(
+        program_id,
+        swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
+    Ok(())
+}
+
+fn process_withdraw(
+    program_id: &Pubkey,
+    pool_token_amount: u64,
+    minimum_token_a_amount: u64,
+    minimum_token_b_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let user_transfer_authority_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let dest_token_a_info = next_account_info(account_info_iter)?;
+    let dest_token_b_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_a_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_b_info = next_account_info(account_info_iter)?;
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    check_observation_buffer(program_id, swap_info, observation_buffer_info)?;
+
+    let mut swap_data = swap_info.data.borrow_mut();
+    let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+    let (openbook_infos, rest) =
+        split_openbook_market(&token_swap.openbook_market, account_info_iter.as_slice())?;
+    let (associated_token_infos, position_page_infos) = split_associated_token_accounts(rest, 2)?;
+    let nonce = token_swap.nonce;
+    if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if *token_a_info.key != token_swap.token_a {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if token_a_info.key == dest_token_a_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if token_b_info.key == dest_token_b_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if *admin_fee_dest_a_info.key != token_swap.admin_fee_key_a {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+    if *admin_fee_dest_b_info.key != token_swap.admin_fee_key_b {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+    if *pyth_a_price_info.key != token_swap.pyth_a || *pyth_b_price_info.key != token_swap.pyth_b {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+
+    let token_program_id = *token_program_info.key;
+    let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
+    if pool_mint.supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let (mut liquidity_provider, position_pages) =
+        unpack_liquidity_positions(program_id, liquidity_provider_info, position_page_infos)?;
+    check_liquidity_provider_authority(
+        program_id,
+        &liquidity_provider,
+        liquidity_provider_info,
+        liquidity_owner_info,
+    )?;
+
+    let mut pool_state = token_swap.pool_state()?;
+    let (new_market_price, twap) = get_new_market_price(
+        token_swap,
+        observation_buffer_info,
+        &mut pool_state,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        openbook_infos,
+        clock,
+    )?;
+
+    let mut state = PoolState::new(PoolState {
+        market_price: new_market_price,
+        ..pool_state
+    })?;
+
+    let (base_out_amount, quote_out_amount) = state.sell_shares(
+        pool_token_amount,
+        minimum_token_a_amount,
+        minimum_token_b_amount,
+        pool_mint.supply,
+    )?;
+
+    let fees = &token_swap.fees()?;
+    let withdraw_fee_base = fees.withdraw_fee(base_out_amount)?;
+    let admin_fee_base = fees.admin_withdraw_fee(withdraw_fee_base)?;
+    let base_out_amount = base_out_amount
+        .checked_sub(withdraw_fee_base)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let withdraw_fee_quote = fees.withdraw_fee(quote_out_amount)?;
+    let admin_fee_quote = fees.admin_withdraw_fee(withdraw_fee_quote)?;
+    let quote_out_amount = quote_out_amount
+        .checked_sub(withdraw_fee_quote)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let reward_per_share = accumulate_rewards(token_swap, clock)?;
+    let (position, position_index) = liquidity_provider.find_position(*swap_info.key)?;
+    position.calc_and_update_rewards(reward_per_share, clock.unix_timestamp)?;
+    liquidity_provider.withdraw(pool_token_amount, position_index)?;
+    pack_liquidity_positions(
+        liquidity_provider,
+        position_pages,
+        liquidity_provider_info,
+        position_page_infos,
+    )?;
+
+    let base_reserve = state.base_reserve.try_floor_u64()?;
+    let quote_reserve = state.quote_reserve.try_floor_u64()?;
+    token_swap.set_pool_state(&state);
+    update_twap(token_swap, observation_buffer_info, &twap, clock)?;
+    let token_mints = [token_swap.token_a_mint, token_swap.token_b_mint];
+    drop(swap_data);
+
+    if let Some(associated_token_infos) = associated_token_infos {
+        create_associated_token_accounts(
+            associated_token_infos,
+            &[dest_token_a_info, dest_token_b_info],
+            &token_mints,
+            liquidity_owner_info,
+            token_program_info,
+        )?;
+    }
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_a_info.clone(),
+        dest_token_a_info.clone(),
+        authority_info.clone(),
+        nonce,
+        base_out_amount,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_a_info.clone(),
+        admin_fee_dest_a_info.clone(),
+        authority_info.clone(),
+        nonce,
+        admin_fee_base,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_b_info.clone(),
+        dest_token_b_info.clone(),
+        authority_info.clone(),
+        nonce,
+        quote_out_amount,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_b_info.clone(),
+        admin_fee_dest_b_info.clone(),
+        authority_info.clone(),
+        nonce,
+        admin_fee_quote,
+    )?;
+    token_burn(
+        swap_info.key,
+        token_program_info.clone(),
+        source_info.clone(),
+        pool_mint_info.clone(),
+        user_transfer_authority_info.clone(),
+        nonce,
+        pool_token_amount,
+    )?;
+
+    Event::Withdraw(LiquidityEvent {
+        swap: *swap_info.key,
+        token_a_amount: base_out_amount,
+        token_b_amount: quote_out_amount,
+        pool_token_amount,
+        base_reserve,
+        quote_reserve,
+    })
+    .emit();
+
+    update_price_feed(
+        program_id,
+        swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
+    Ok(())
+}
+
+/// Withdraw a whole position with the pool state as stored, reading neither
+/// the oracles nor the rewards of the pool. The share of a frozen swap account
+/// stays in it, a surplus folded back into the pool by `Sync`.
+fn process_emergency_withdraw(
+    program_id: &Pubkey,
+    minimum_token_a_amount: u64,
+    minimum_token_b_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let user_transfer_authority_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let dest_token_a_info = next_account_info(account_info_iter)?;
+    let dest_token_b_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_a_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_b_info = next_account_info(account_info_iter)?;
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let position_page_infos = account_info_iter.as_slice();
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut swap_data = swap_info.data.borrow_mut();
+    let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+    let nonce = token_swap.nonce;
+    if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if *token_a_info.key != token_swap.token_a {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if token_a_info.key == dest_token_a_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if token_b_info.key == dest_token_b_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if *admin_fee_dest_a_info.key != token_swap.admin_fee_key_a {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+    if *admin_fee_dest_b_info.key != token_swap.admin_fee_key_b {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+
+    let token_program_id = *token_program_info.key;
+    let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
+    if pool_mint.supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+
+    let is_token_a_frozen = unpack_token_account(token_a_info, &token_program_id)?.is_frozen();
+    let is_token_b_frozen = unpack_token_account(token_b_info, &token_program_id)?.is_frozen();
+    if is_token_a_frozen && is_token_b_frozen {
+        return Err(SwapError::FrozenSwapAccounts.into());
+    }
+    if (is_token_a_frozen && minimum_token_a_amount > 0)
+        || (is_token_b_frozen && minimum_token_b_amount > 0)
+    {
+        return Err(SwapError::WithdrawNotEnough.into());
+    }
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let (mut liquidity_provider, position_pages) =
+        unpack_liquidity_positions(program_id, liquidity_provider_info, position_page_infos)?;
+    check_liquidity_provider_authority(
+        program_id,
+        &liquidity_provider,
+        liquidity_provider_info,
+        liquidity_owner_info,
+    )?;
+    let pool_token_amount = liquidity_provider.emergency_withdraw(*swap_info.key)?;
+    pack_liquidity_positions(
+        liquidity_provider,
+        position_pages,
+        liquidity_provider_info,
+        position_page_infos,
+    )?;
+
+    let mut state = token_swap.pool_state()?;
+    let (base_out_amount, quote_out_amount) = state.sell_shares(
+        pool_token_amount,
+        minimum_token_a_amount,
+        minimum_token_b_amount,
+        pool_mint.supply,
+    )?;
+
+    let fees = &token_swap.fees()?;
+    let withdraw_fee_base = fees.withdraw_fee(base_out_amount)?;
+    let admin_fee_base = fees.admin_withdraw_fee(withdraw_fee_base)?;
+    let base_out_amount = base_out_amount
+        .checked_sub(withdraw_fee_base)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let withdraw_fee_quote = fees.withdraw_fee(quote_out_amount)?;
+    let admin_fee_quote = fees.admin_withdraw_fee(withdraw_fee_quote)?;
+    let quote_out_amount = quote_out_amount
+        .checked_sub(withdraw_fee_quote)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    // the frozen swap account pays out nothing, the other one its share
+    let (base_out_amount, admin_fee_base) = if is_token_a_frozen {
+        (0, 0)
+    } else {
+        (base_out_amount, admin_fee_base)
+    };
+    let (quote_out_amount, admin_fee_quote) = if is_token_b_frozen {
+        (0, 0)
+    } else {
+        (quote_out_amount, admin_fee_quote)
+    };
+
+    let base_reserve = state.base_reserve.try_floor_u64()?;
+    let quote_reserve = state.quote_reserve.try_floor_u64()?;
+    token_swap.set_pool_state(&state);
+    drop(swap_data);
+
+    if !is_token_a_frozen {
+        token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            token_a_info.clone(),
+            dest_token_a_info.clone(),
+            authority_info.clone(),
+            nonce,
+            base_out_amount,
+        )?;
+        token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            token_a_info.clone(),
+            admin_fee_dest_a_info.clone(),
+            authority_info.clone(),
+            nonce,
+            admin_fee_base,
+        )?;
+    }
+    if !is_token_b_frozen {
+        token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            token_b_info.clone(),
+            dest_token_b_info.clone(),
+            authority_info.clone(),
+            nonce,
+            quote_out_amount,
+        )?;
+        token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            token_b_info.clone(),
+            admin_fee_dest_b_info.clone(),
+            authority_info.clone(),
+            nonce,
+            admin_fee_quote,
+        )?;
+    }
+    token_burn(
+        swap_info.key,
+        token_program_info.clone(),
+        source_info.clone(),
+        pool_mint_info.clone(),
+        user_transfer_authority_info.clone(),
+        nonce,
+        pool_token_amount,
+    )?;
+
+    Event::Withdraw(LiquidityEvent {
+        swap: *swap_info.key,
+        token_a_amount: base_out_amount,
+        token_b_amount: quote_out_amount,
+        pool_token_amount,
+        base_reserve,
+        quote_reserve,
+    })
+    .emit();
+
+    update_price_feed(
+        program_id,
+        swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
+    Ok(())
+}
+
+fn process_sync(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut swap_data = swap_info.data.borrow_mut();
+    let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+    if token_swap.is_paused()? {
+        return Err(SwapError::IsPaused.into());
+    }
+    if *token_a_info.key != token_swap.token_a {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+
+    let token_a = unpack_token_account(token_a_info, token_program_info.key)?;
+    let token_b = unpack_token_account(token_b_info, token_program_info.key)?;
+    let state = token_swap.pool_state()?;
+    let (base_surplus, quote_surplus) = state.surplus(token_a.amount, token_b.amount)?;
+    token_swap.set_pool_state(&state.fold_surplus(base_surplus, quote_surplus)?);
+    drop(swap_data);
+    update_price_feed(
+        program_id,
+        swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
+    Ok(())
+}
+
+fn process_skim(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_a_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_b_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let swap_data = swap_info.data.borrow();
+    let token_swap = SwapInfoData::load(&swap_data)?;
+    let nonce = token_swap.nonce;
+    if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if *token_a_info.key != token_swap.token_a {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *admin_fee_dest_a_info.key != token_swap.admin_fee_key_a {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+    if *admin_fee_dest_b_info.key != token_swap.admin_fee_key_b {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+
+    let token_program_id = *token_program_info.key;
+    let token_a = unpack_token_account(token_a_info, &token_program_id)?;
+    let token_b = unpack_token_account(token_b_info, &token_program_id)?;
+    let (base_surplus, quote_surplus) = token_swap
+        .pool_state()?
+        .surplus(token_a.amount, token_b.amount)?;
+    drop(swap_data);
+
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_a_info.clone(),
+        admin_fee_dest_a_info.clone(),
+        authority_info.clone(),
+        nonce,
+        base_surplus,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_b_info.clone(),
+        admin_fee_dest_b_info.clone(),
+        authority_info.clone(),
+        nonce,
+        quote_surplus,
+    )
+}
+
+fn process_get_version() -> ProgramResult {
+    set_return_data(&ProgramBuild::current().pack());
+    Ok(())
+}
+
+fn process_get_virtual_price(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let swap_data = swap_info.data.borrow();
+    let token_swap = SwapInfoData::load(&swap_data)?;
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+
+    let pool_mint = unpack_mint(pool_mint_info, token_program_info.key)?;
+    let lp_target_price = token_swap.pool_state()?.lp_target_price(pool_mint.supply)?;
+    set_return_data(&lp_target_price.to_scaled_val()?.to_le_bytes());
+    Ok(())
+}
+
+fn process_withdraw_exact(
+    program_id: &Pubkey,
+    token_a_amount: u64,
+    token_b_amount: u64,
+    max_pool_token_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let user_transfer_authority_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let dest_token_a_info = next_account_info(account_info_iter)?;
+    let dest_token_b_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_a_info = next_account_info(account_info_iter)?;
+    let admin_fee_dest_b_info = next_account_info(account_info_iter)?;
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+    let price_feed_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    check_observation_buffer(program_id, swap_info, observation_buffer_info)?;
+
+    let mut swap_data = swap_info.data.borrow_mut();
+    let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+    let (openbook_infos, rest) =
+        split_openbook_market(&token_swap.openbook_market, account_info_iter.as_slice())?;
+    let (associated_token_infos, position_page_infos) = split_associated_token_accounts(rest, 2)?;
+    let nonce = token_swap.nonce;
+    if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if *token_a_info.key != token_swap.token_a {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if token_a_info.key == dest_token_a_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if token_b_info.key == dest_token_b_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if *admin_fee_dest_a_info.key != token_swap.admin_fee_key_a {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+    if *admin_fee_dest_b_info.key != token_swap.admin_fee_key_b {
+        return Err(SwapError::InvalidAdmin.into());
+    }
+    if *pyth_a_price_info.key != token_swap.pyth_a || *pyth_b_price_info.key != token_swap.pyth_b {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+
+    let token_program_id = *token_program_info.key;
+    let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
+    if pool_mint.supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let (mut liquidity_provider, position_pages) =
+        unpack_liquidity_positions(program_id, liquidity_provider_info, position_page_infos)?;
+    check_liquidity_provider_authority(
+        program_id,
+        &liquidity_provider,
+        liquidity_provider_info,
+        liquidity_owner_info,
+    )?;
+
+    let mut pool_state = token_swap.pool_state()?;
+    let (new_market_price, twap) = get_new_market_price(
+        token_swap,
+        observation_buffer_info,
+        &mut pool_state,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        openbook_infos,
+        clock,
+    )?;
+
+    let state = PoolState::new(PoolState {
+        market_price: new_market_price,
+        ..pool_state
+    })?;
+
+    let quote = state.quote_withdraw_exact(
+        token_a_amount,
+        token_b_amount,
+        pool_mint.supply,
+        &token_swap.fees()?,
+    )?;
+    if quote.pool_token_amount > max_pool_token_amount {
+        return Err(SwapError::ExceededSlippage.into());
+    }
+
+    let reward_per_share = accumulate_rewards(token_swap, clock)?;
+    let (position, position_index) = liquidity_provider.find_position(*swap_info.key)?;
+    position.calc_and_update_rewards(reward_per_share, clock.unix_timestamp)?;
+    liquidity_provider.withdraw(quote.pool_token_amount, position_index)?;
+    pack_liquidity_positions(
+        liquidity_provider,
+        position_pages,
+        liquidity_provider_info,
+        position_page_infos,
+    )?;
+
+    let base_reserve = quote.pool_state.base_reserve.try_floor_u64()?;
+    let quote_reserve = quote.pool_state.quote_reserve.try_floor_u64()?;
+    token_swap.set_pool_state(&quote.pool_state);
+    update_twap(token_swap, observation_buffer_info, &twap, clock)?;
+    let token_mints = [token_swap.token_a_mint, token_swap.token_b_mint];
+    drop(swap_data);
+
+    if let Some(associated_token_infos) = associated_token_infos {
+        create_associated_token_accounts(
+            associated_token_infos,
+            &[dest_token_a_info, dest_token_b_info],
+            &token_mints,
+            liquidity_owner_info,
+            token_program_info,
+        )?;
+    }
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_a_info.clone(),
+        dest_token_a_info.clone(),
+        authority_info.clone(),
+        nonce,
+        token_a_amount,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_a_info.clone(),
+        admin_fee_dest_a_info.clone(),
+        authority_info.clone(),
+        nonce,
+        quote.admin_fee_a,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_b_info.clone(),
+        dest_token_b_info.clone(),
+        authority_info.clone(),
+        nonce,
+        token_b_amount,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_b_info.clone(),
+        admin_fee_dest_b_info.clone(),
+        authority_info.clone(),
+        nonce,
+        quote.admin_fee_b,
+    )?;
+    token_burn(
+        swap_info.key,
+        token_program_info.clone(),
+        source_info.clone(),
+        pool_mint_info.clone(),
+        user_transfer_authority_info.clone(),
+        nonce,
+        quote.pool_token_amount,
+    )?;
+
+    Event::Withdraw(LiquidityEvent {
+        swap: *swap_info.key,
+        token_a_amount,
+        token_b_amount,
+        pool_token_amount: quote.pool_token_amount,
+        base_reserve,
+        quote_reserve,
+    })
+    .emit();
+
+    update_price_feed(
+        program_id,
+        swap_info,
+        price_feed_info,
+        pool_mint_info,
+        clock,
+    )?;
+    Ok(())
+}
+
+fn process_init_liquidity_provider(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let referrer_info = next_account_info(account_info_iter).ok();
+
+    if !liquidity_owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    if matches!(referrer_info, Some(referrer_info) if referrer_info.key == liquidity_owner_info.key)
+    {
+        return Err(SwapError::InvalidReferrer.into());
+    }
+
+    let (liquidity_provider_key, bump_seed) =
+        find_liquidity_provider_address(program_id, liquidity_owner_info.key);
+    if *liquidity_provider_info.key != liquidity_provider_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if liquidity_provider_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    create_program_account(
+        program_id,
+        liquidity_provider_info,
+        liquidity_owner_info,
+        system_program_info,
+        rent,
+        LiquidityProvider::LEN,
+        &[
+            liquidity_owner_info.key.as_ref(),
+            LIQUIDITY_PROVIDER_SEED,
+            &[bump_seed],
+        ],
+    )?;
+    let mut liquidity_provider =
+        assert_uninitialized::<LiquidityProvider>(liquidity_provider_info)?;
+
+    liquidity_provider.init(*liquidity_owner_info.key, vec![]);
+    if let Some(referrer_info) = referrer_info {
+        liquidity_provider.referrer = *referrer_info.key;
+    }
+    LiquidityProvider::pack(
+        liquidity_provider,
+        &mut liquidity_provider_info.data.borrow_mut(),
+    )?;
+
+    Ok(())
+}
+
+fn process_grow_provider_account(
+    program_id: &Pubkey,
+    max_positions: u8,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let liquidity_provider =
+        LiquidityProvider::unpack_account(&liquidity_provider_info.data.borrow())?;
+    if liquidity_provider.owner != *liquidity_owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if !liquidity_owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+
+    let new_len = LiquidityProvider::account_len(max_positions as usize);
+    if new_len <= liquidity_provider_info.data_len() {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    grow_account(
+        liquidity_provider_info,
+        payer_info,
+        system_program_info,
+        rent,
+        new_len,
+    )
+}
+
+fn process_close_liquidity_provider(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let position_page_infos = account_info_iter.as_slice();
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let (liquidity_provider, _) =
+        unpack_liquidity_positions(program_id, liquidity_provider_info, position_page_infos)?;
+    if liquidity_provider.owner != *liquidity_owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if !liquidity_owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    check_liquidity_provider_address(program_id, liquidity_provider_info, liquidity_owner_info)?;
+
+    if !liquidity_provider.is_empty() {
+        return Err(SwapError::LiquidityProviderNotEmpty.into());
+    }
+
+    for account_info in std::iter::once(liquidity_provider_info).chain(position_page_infos) {
+        let owner_lamports = liquidity_owner_info
+            .lamports()
+            .checked_add(account_info.lamports())
+            .ok_or(SwapError::CalculationFailure)?;
+        **liquidity_owner_info.lamports.borrow_mut() = owner_lamports;
+        **account_info.lamports.borrow_mut() = 0;
+        account_info.data.borrow_mut().fill(0);
+    }
+
+    Ok(())
+}
+
+fn process_transfer_position(
+    program_id: &Pubkey,
+    source_page_count: u8,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let source_info = next_account_info(account_info_iter)?;
+    let source_owner_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let destination_owner_info = next_account_info(account_info_iter)?;
+    let position_page_infos = account_info_iter.as_slice();
+    if position_page_infos.len() < source_page_count as usize {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (source_page_infos, destination_page_infos) =
+        position_page_infos.split_at(source_page_count as usize);
+
+    if swap_info.owner != program_id
+        || source_info.owner != program_id
+        || destination_info.owner != program_id
+    {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if source_info.key == destination_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let (mut source, source_pages) =
+        unpack_liquidity_positions(program_id, source_info, source_page_infos)?;
+    if source.owner != *source_owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if !source_owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    check_liquidity_provider_address(program_id, source_info, source_owner_info)?;
+
+    let (mut destination, destination_pages) =
+        unpack_liquidity_positions(program_id, destination_info, destination_page_infos)?;
+    if destination.owner != *destination_owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if !destination_owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    check_liquidity_provider_address(program_id, destination_info, destination_owner_info)?;
+
+    // both positions are settled at the same rewards per share before merging
+    let reward_per_share = SwapInfoData::load(&swap_info.data.borrow())?
+        .reward_accumulator()
+        .reward_per_share;
+    let (position, position_index) = source.find_position(*swap_info.key)?;
+    position.settle_rewards(reward_per_share)?;
+    let position = source.positions.remove(position_index);
+    if let Ok((destination_position, _)) = destination.find_position(*swap_info.key) {
+        destination_position.settle_rewards(reward_per_share)?;
+    }
+    destination.add_position(position)?;
+
+    pack_liquidity_positions(source, source_pages, source_info, source_page_infos)?;
+    pack_liquidity_positions(
+        destination,
+        destination_pages,
+        destination_info,
+        destination_page_infos,
+    )
+}
+
+fn process_set_liquidity_provider_delegate(
+    program_id: &Pubkey,
+    delegate: Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut liquidity_provider =
+        LiquidityProvider::unpack_account(&liquidity_provider_info.data.borrow())?;
+    if liquidity_provider.owner != *liquidity_owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if !liquidity_owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    check_liquidity_provider_address(program_id, liquidity_provider_info, liquidity_owner_info)?;
+    if delegate == liquidity_provider.owner {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    liquidity_provider.delegate = delegate;
+    LiquidityProvider::pack_account(
+        liquidity_provider,
+        &mut liquidity_provider_info.data.borrow_mut(),
+    )
+}
+
+fn process_init_position_page(
+    program_id: &Pubkey,
+    page_index: u8,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let position_page_info = next_account_info(account_info_iter)?;
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let liquidity_provider =
+        LiquidityProvider::unpack_account(&liquidity_provider_info.data.borrow())?;
+    if liquidity_provider.owner != *liquidity_owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if !liquidity_owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    check_liquidity_provider_address(program_id, liquidity_provider_info, liquidity_owner_info)?;
+
+    let (position_page_key, bump_seed) =
+        find_position_page_address(program_id, liquidity_provider_info.key, page_index);
+    if *position_page_info.key != position_page_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if position_page_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    if let Some(previous_page_index) = page_index.checked_sub(1) {
+        let previous_page_info = next_account_info(account_info_iter)?;
+        let mut previous_page =
+            unpack_position_page(program_id, liquidity_provider_info, previous_page_info)?;
+        if previous_page.page_index != previous_page_index || !previous_page.is_last() {
+            return Err(SwapError::PositionPageMissing.into());
+        }
+        previous_page.next_page = position_page_key;
+        LiquidityPositionPage::pack(previous_page, &mut previous_page_info.data.borrow_mut())?;
+    }
+
+    create_program_account(
+        program_id,
+        position_page_info,
+        liquidity_owner_info,
+        system_program_info,
+        rent,
+        LiquidityPositionPage::LEN,
+        &[
+            liquidity_provider_info.key.as_ref(),
+            POSITION_PAGE_SEED,
+            &[page_index],
+            &[bump_seed],
+        ],
+    )?;
+    let mut position_page = assert_uninitialized::<LiquidityPositionPage>(position_page_info)?;
+    position_page.init(*liquidity_provider_info.key, page_index, bump_seed);
+    LiquidityPositionPage::pack(position_page, &mut position_page_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+fn process_init_registry_page(
+    program_id: &Pubkey,
+    page_index: u8,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let registry_page_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    ConfigInfo::unpack(&config_info.data.borrow())?;
+    if !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+
+    let (registry_page_key, bump_seed) =
+        find_registry_page_address(program_id, config_info.key, page_index);
+    if *registry_page_info.key != registry_page_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if registry_page_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    // pages fill up in order, so routers can stop at the first missing page
+    if let Some(previous_page_index) = page_index.checked_sub(1) {
+        let previous_page_info = next_account_info(account_info_iter)?;
+        if previous_page_info.owner != program_id {
+            return Err(SwapError::InvalidAccountOwner.into());
+        }
+        let previous_page_data = previous_page_info.data.borrow();
+        let previous_page = RegistryPage::load(&previous_page_data)?;
+        if previous_page.config != *config_info.key
+            || previous_page.page_index != previous_page_index
+        {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        if !previous_page.is_full() {
+            return Err(SwapError::InvalidInput.into());
+        }
+    }
+
+    create_program_account(
+        program_id,
+        registry_page_info,
+        payer_info,
+        system_program_info,
+        rent,
+        REGISTRY_PAGE_SIZE,
+        &[
+            config_info.key.as_ref(),
+            REGISTRY_PAGE_SEED,
+            &[page_index],
+            &[bump_seed],
+        ],
+    )?;
+    RegistryPage::init(
+        &mut registry_page_info.data.borrow_mut(),
+        *config_info.key,
+        page_index,
+        bump_seed,
+    )?;
+
+    Ok(())
+}
+
+fn process_init_observation_buffer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    let (observation_buffer_key, bump_seed) =
+        find_observation_buffer_address(program_id, swap_info.key);
+    if *observation_buffer_info.key != observation_buffer_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if observation_buffer_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    // the twap stored in the token-swap is the first observation
+    let first_observation = {
+        let swap_data = swap_info.data.borrow();
+        let token_swap = SwapInfoData::load(&swap_data)?;
+        Observation::new(
+            token_swap.block_timestamp_last(),
+            token_swap.base_price_cumulative_last(),
+        )
+    };
+
+    create_program_account(
+        program_id,
+        observation_buffer_info,
+        payer_info,
+        system_program_info,
+        rent,
+        OBSERVATION_BUFFER_SIZE,
+        &[
+            swap_info.key.as_ref(),
+            OBSERVATION_BUFFER_SEED,
+            &[bump_seed],
+        ],
+    )?;
+    ObservationBuffer::init(
+        &mut observation_buffer_info.data.borrow_mut(),
+        *swap_info.key,
+        bump_seed,
+    )?
+    .record(first_observation);
+
+    Ok(())
+}
+
+fn process_init_price_feed(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let price_feed_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if !payer_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    let (price_feed_key, bump_seed) = find_price_feed_address(program_id, swap_info.key);
+    if *price_feed_info.key != price_feed_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if price_feed_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+    let (mid_price, lp_virtual_price) = feed_prices(swap_info, pool_mint_info)?;
+
+    create_program_account(
+        program_id,
+        price_feed_info,
+        payer_info,
+        system_program_info,
+        rent,
+        PRICE_FEED_SIZE,
+        &[swap_info.key.as_ref(), PRICE_FEED_SEED, &[bump_seed]],
+    )?;
+    PriceFeed::init(
+        &mut price_feed_info.data.borrow_mut(),
+        *swap_info.key,
+        bump_seed,
+    )?
+    .update(mid_price, lp_virtual_price, Clock::get()?.slot);
+
+    Ok(())
+}
+
+fn process_claim_liquidity_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let market_authority_info = next_account_info(account_info_iter)?;
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let claim_destination_info = next_account_info(account_info_iter)?;
+    let claim_mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let referrer_destination_info =
+        next_referrer_destination(program_id, liquidity_provider_info, account_info_iter)?;
+    let (associated_token_infos, position_page_infos) =
+        split_associated_token_accounts(account_info_iter.as_slice(), 1)?;
+
+    if swap_info.owner != program_id || config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let market_nonce = config.bump_seed;
+    if *market_authority_info.key != authority_id(program_id, config_info.key, market_nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+
+    if config.deltafi_mint != *claim_mint_info.key {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if claim_destination_info.owner == market_authority_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let (mut liquidity_provider, position_pages) =
+        unpack_liquidity_positions(program_id, liquidity_provider_info, position_page_infos)?;
+    check_liquidity_provider_authority(
+        program_id,
+        &liquidity_provider,
+        liquidity_provider_info,
+        liquidity_owner_info,
+    )?;
+
+    // rewards accumulated by the pool up to its last deposit, withdraw or swap
+    let swap_data = swap_info.data.borrow();
+    let token_swap = SwapInfoData::load(&swap_data)?;
+    check_swap_config(&token_swap.config_key, config_info)?;
+    let reward_per_share = token_swap.reward_accumulator().reward_per_share;
+    drop(swap_data);
+    let clock = Clock::get()?;
+    let (position, _) = liquidity_provider.find_position(*swap_info.key)?;
+    position.calc_and_update_rewards(reward_per_share, clock.unix_timestamp)?;
+    let reward_amount = liquidity_provider.claim(*swap_info.key)?;
+    let referral_reward = referral_reward(
+        &config,
+        &liquidity_provider,
+        referrer_destination_info,
+        token_program_info,
+        reward_amount,
+    )?;
+    record_rewards_minted(
+        config_info,
+        &mut config,
+        clock.epoch,
+        reward_amount
+            .checked_add(referral_reward)
+            .ok_or(SwapError::CalculationFailure)?,
+    )?;
+    pack_liquidity_positions(
+        liquidity_provider,
+        position_pages,
+        liquidity_provider_info,
+        position_page_infos,
+    )?;
+
+    if let Some(associated_token_infos) = associated_token_infos {
+        create_associated_token_accounts(
+            associated_token_infos,
+            &[claim_destination_info],
+            &[config.deltafi_mint],
+            liquidity_owner_info,
+            token_program_info,
+        )?;
+    }
+    token_mint_to(
+        config_info.key,
+        token_program_info.clone(),
+        claim_mint_info.clone(),
+        claim_destination_info.clone(),
+        market_authority_info.clone(),
+        market_nonce,
+        reward_amount,
+    )?;
+    if let Some(referrer_destination_info) = referrer_destination_info {
+        token_mint_to(
+            config_info.key,
+            token_program_info.clone(),
+            claim_mint_info.clone(),
+            referrer_destination_info.clone(),
+            market_authority_info.clone(),
+            market_nonce,
+            referral_reward,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn process_claim_all_rewards(
+    program_id: &Pubkey,
+    swap_count: u8,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
-    let swap_info = next_account_info(account_info_iter)?;
-    let authority_info = next_account_info(account_info_iter)?;
-    let user_transfer_authority_info = next_account_info(account_info_iter)?;
-    let source_a_info = next_account_info(account_info_iter)?;
-    let source_b_info = next_account_info(account_info_iter)?;
-    let token_a_info = next_account_info(account_info_iter)?;
-    let token_b_info = next_account_info(account_info_iter)?;
-    let pool_mint_info = next_account_info(account_info_iter)?;
-    let destination_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let market_authority_info = next_account_info(account_info_iter)?;
     let liquidity_provider_info = next_account_info(account_info_iter)?;
     let liquidity_owner_info = next_account_info(account_info_iter)?;
-    let pyth_a_price_info = next_account_info(account_info_iter)?;
-    let pyth_b_price_info = next_account_info(account_info_iter)?;
-    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let claim_destination_info = next_account_info(account_info_iter)?;
+    let claim_mint_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
+    let rest = account_info_iter.as_slice();
+    if rest.len() < swap_count as usize {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (swap_infos, rest) = rest.split_at(swap_count as usize);
+    let account_info_iter = &mut rest.iter();
+    let referrer_destination_info =
+        next_referrer_destination(program_id, liquidity_provider_info, account_info_iter)?;
+    let (associated_token_infos, position_page_infos) =
+        split_associated_token_accounts(account_info_iter.as_slice(), 1)?;
 
-    if swap_info.owner != program_id {
+    if config_info.owner != program_id
+        || swap_infos
+            .iter()
+            .any(|swap_info| swap_info.owner != program_id)
+    {
         return Err(SwapError::InvalidAccountOwner.into());
     }
 
-    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
-    if token_swap.is_paused {
-        return Err(SwapError::IsPaused.into());
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let market_nonce = config.bump_seed;
+    if *market_authority_info.key != authority_id(program_id, config_info.key, market_nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
     }
 
-    let nonce = token_swap.nonce;
-    if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
-        return Err(SwapError::InvalidProgramAddress.into());
+    if config.deltafi_mint != *claim_mint_info.key {
+        return Err(SwapError::IncorrectMint.into());
     }
-    if *token_a_info.key != token_swap.token_a {
-        return Err(SwapError::IncorrectSwapAccount.into());
+    if claim_destination_info.owner == market_authority_info.key {
+        return Err(SwapError::InvalidOwner.into());
     }
-    if *token_b_info.key != token_swap.token_b {
-        return Err(SwapError::IncorrectSwapAccount.into());
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
     }
-    if *pool_mint_info.key != token_swap.pool_mint {
-        return Err(SwapError::IncorrectMint.into());
+    let (mut liquidity_provider, position_pages) =
+        unpack_liquidity_positions(program_id, liquidity_provider_info, position_page_infos)?;
+    check_liquidity_provider_authority(
+        program_id,
+        &liquidity_provider,
+        liquidity_provider_info,
+        liquidity_owner_info,
+    )?;
+
+    let clock = Clock::get()?;
+    let mut pools = Vec::with_capacity(swap_infos.len());
+    for swap_info in swap_infos {
+        let swap_data = swap_info.data.borrow();
+        let token_swap = SwapInfoData::load(&swap_data)?;
+        check_swap_config(&token_swap.config_key, config_info)?;
+        let reward_per_share = token_swap.reward_accumulator().reward_per_share;
+        drop(swap_data);
+        if let Ok((position, _)) = liquidity_provider.find_position(*swap_info.key) {
+            position.calc_and_update_rewards(reward_per_share, clock.unix_timestamp)?;
+        }
+        pools.push(*swap_info.key);
     }
-    if token_a_info.key == source_a_info.key {
-        return Err(SwapError::InvalidInput.into());
+    let reward_amount = liquidity_provider.claim_all(&pools)?;
+    let referral_reward = referral_reward(
+        &config,
+        &liquidity_provider,
+        referrer_destination_info,
+        token_program_info,
+        reward_amount,
+    )?;
+    record_rewards_minted(
+        config_info,
+        &mut config,
+        clock.epoch,
+        reward_amount
+            .checked_add(referral_reward)
+            .ok_or(SwapError::CalculationFailure)?,
+    )?;
+    pack_liquidity_positions(
+        liquidity_provider,
+        position_pages,
+        liquidity_provider_info,
+        position_page_infos,
+    )?;
+
+    if let Some(associated_token_infos) = associated_token_infos {
+        create_associated_token_accounts(
+            associated_token_infos,
+            &[claim_destination_info],
+            &[config.deltafi_mint],
+            liquidity_owner_info,
+            token_program_info,
+        )?;
     }
-    if token_b_info.key == source_b_info.key {
-        return Err(SwapError::InvalidInput.into());
+    token_mint_to(
+        config_info.key,
+        token_program_info.clone(),
+        claim_mint_info.clone(),
+        claim_destination_info.clone(),
+        market_authority_info.clone(),
+        market_nonce,
+        reward_amount,
+    )?;
+    if let Some(referrer_destination_info) = referrer_destination_info {
+        token_mint_to(
+            config_info.key,
+            token_program_info.clone(),
+            claim_mint_info.clone(),
+            referrer_destination_info.clone(),
+            market_authority_info.clone(),
+            market_nonce,
+            referral_reward,
+        )?;
     }
 
-    let mut liquidity_provider =
-        LiquidityProvider::unpack(&liquidity_provider_info.data.borrow_mut())?;
-    if liquidity_provider_info.owner != program_id {
+    Ok(())
+}
+
+fn process_poke(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let market_authority_info = next_account_info(account_info_iter)?;
+    let observation_buffer_info = next_account_info(account_info_iter)?;
+    let reward_destination_info = next_account_info(account_info_iter)?;
+    let reward_mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id || config_info.owner != program_id {
         return Err(SwapError::InvalidAccountOwner.into());
     }
-    if &liquidity_provider.owner != liquidity_owner_info.key {
-        return Err(SwapError::InvalidOwner.into());
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let market_nonce = config.bump_seed;
+    if *market_authority_info.key != authority_id(program_id, config_info.key, market_nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
     }
-    if !liquidity_owner_info.is_signer {
-        return Err(SwapError::InvalidSigner.into());
+    if config.deltafi_mint != *reward_mint_info.key {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if reward_destination_info.owner == market_authority_info.key {
+        return Err(SwapError::InvalidOwner.into());
     }
+    check_observation_buffer(program_id, swap_info, observation_buffer_info)?;
 
-    let token_program_id = *token_program_info.key;
-    let token_a = unpack_token_account(token_a_info, &token_program_id)?;
-    let token_b = unpack_token_account(token_b_info, &token_program_id)?;
-    let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
+    let clock = Clock::get()?;
+    let mut swap_data = swap_info.data.borrow_mut();
+    let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+    check_swap_config(&token_swap.config_key, config_info)?;
+    if !token_swap.is_open_twap()? {
+        return Err(SwapError::TwapNotOpen.into());
+    }
 
-    // updating price from pyth price
-    let (new_market_price, base_price_cumulative_last) =
-        get_new_market_price(&mut token_swap, pyth_a_price_info, pyth_b_price_info, clock)?;
+    let mut pool_state = token_swap.pool_state()?;
+    let pool_mid_price = pool_state.get_mid_price()?;
+    let block_timestamp_last: u64 = clock
+        .unix_timestamp
+        .try_into()
+        .map_err(|_| SwapError::CalculationFailure)?;
+    let last_twap = token_swap.twap();
+    let twap = accumulate_twap(
+        token_swap,
+        &pool_state,
+        pool_mid_price,
+        block_timestamp_last,
+    )?;
+    update_twap(token_swap, observation_buffer_info, &twap, &clock)?;
+    drop(swap_data);
 
-    let mut state = PoolState::new(PoolState {
-        market_price: new_market_price,
-        ..token_swap.pool_state
-    })?;
+    // only the seconds accumulated by this poke count, so that a keeper is
+    // rewarded at most once per min poke interval of a quiet pool
+    let ticks_accumulated = twap.cumulative_ticks - last_twap.cumulative_ticks;
+    if config.poke_reward == 0 || ticks_accumulated < config.min_poke_interval.max(1) as u64 {
+        return Ok(());
+    }
+    let reward_amount = config
+        .poke_reward
+        .min(config.epoch_rewards_left(clock.epoch));
+    if reward_amount == 0 {
+        return Ok(());
+    }
+    record_rewards_minted(config_info, &mut config, clock.epoch, reward_amount)?;
+    token_mint_to(
+        config_info.key,
+        token_program_info.clone(),
+        reward_mint_info.clone(),
+        reward_destination_info.clone(),
+        market_authority_info.clone(),
+        market_nonce,
+        reward_amount,
+    )
+}
 
-    let base_balance = token_a_amount
-        .checked_add(token_a.amount)
-        .ok_or(SwapError::CalculationFailure)?;
-    let quote_balance = token_b_amount
-        .checked_add(token_b.amount)
-        .ok_or(SwapError::CalculationFailure)?;
+fn process_initialize_delfi_lock(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let delfi_lock_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
 
-    let pool_mint_amount = state.buy_shares(base_balance, quote_balance, pool_mint.supply)?;
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    ConfigInfo::unpack(&config_info.data.borrow())?;
+    if !owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
 
-    if pool_mint_amount < min_mint_amount {
-        return Err(SwapError::ExceededSlippage.into());
+    let (delfi_lock_key, bump_seed) =
+        find_delfi_lock_address(program_id, config_info.key, owner_info.key);
+    if *delfi_lock_info.key != delfi_lock_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if delfi_lock_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
     }
 
-    liquidity_provider
-        .find_or_add_position(*swap_info.key, clock.unix_timestamp)?
-        .deposit(pool_mint_amount)?;
-    LiquidityProvider::pack(
-        liquidity_provider,
-        &mut liquidity_provider_info.data.borrow_mut(),
+    create_program_account(
+        program_id,
+        delfi_lock_info,
+        owner_info,
+        system_program_info,
+        rent,
+        DelfiLock::LEN,
+        &[
+            config_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            DELFI_LOCK_SEED,
+            &[bump_seed],
+        ],
     )?;
+    let mut delfi_lock = assert_uninitialized::<DelfiLock>(delfi_lock_info)?;
 
-    token_swap.pool_state = state;
-    token_swap.cumulative_ticks = token_swap
-        .cumulative_ticks
-        .checked_add(clock.unix_timestamp.try_into().unwrap())
-        .ok_or(SwapError::CalculationFailure)?
-        .checked_sub(token_swap.block_timestamp_last)
-        .ok_or(SwapError::CalculationFailure)?;
+    delfi_lock.init(*config_info.key, *owner_info.key);
+    DelfiLock::pack(delfi_lock, &mut delfi_lock_info.data.borrow_mut())?;
 
-    token_swap.block_timestamp_last = clock.unix_timestamp.try_into().unwrap();
-    token_swap.base_price_cumulative_last = base_price_cumulative_last;
-    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
 
-    token_transfer(
-        swap_info.key,
-        token_program_info.clone(),
-        source_a_info.clone(),
-        token_a_info.clone(),
-        user_transfer_authority_info.clone(),
-        nonce,
-        token_a_amount,
-    )?;
-    token_transfer(
-        swap_info.key,
-        token_program_info.clone(),
-        source_b_info.clone(),
-        token_b_info.clone(),
-        user_transfer_authority_info.clone(),
-        nonce,
-        token_b_amount,
-    )?;
-    token_mint_to(
-        swap_info.key,
-        token_program_info.clone(),
-        pool_mint_info.clone(),
-        destination_info.clone(),
-        authority_info.clone(),
-        nonce,
-        pool_mint_amount,
-    )?;
+/// Check the DELFI lock account belongs to the signing owner in the config,
+/// its DELFI only being held by the vault of that config
+fn check_delfi_lock(
+    program_id: &Pubkey,
+    config_key: &Pubkey,
+    delfi_lock_info: &AccountInfo,
+    owner_info: &AccountInfo,
+) -> Result<DelfiLock, ProgramError> {
+    if delfi_lock_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if !owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    let delfi_lock = DelfiLock::unpack(&delfi_lock_info.data.borrow())?;
+    if delfi_lock.owner != *owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if delfi_lock.config != *config_key {
+        return Err(SwapError::IncorrectConfigAccount.into());
+    }
+    Ok(delfi_lock)
+}
 
-    Ok(())
+/// Check the DELFI vault is the associated token account of the $authority
+fn check_delfi_vault(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+    market_authority_info: &AccountInfo,
+    vault_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    if *market_authority_info.key != authority_id(program_id, config_info.key, config.bump_seed)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if *vault_info.key
+        != spl_associated_token_account::get_associated_token_address(
+            market_authority_info.key,
+            &config.deltafi_mint,
+        )
+    {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    Ok(config.bump_seed)
 }
 
-fn process_withdraw(
+fn process_lock_delfi(
     program_id: &Pubkey,
-    pool_token_amount: u64,
-    minimum_token_a_amount: u64,
-    minimum_token_b_amount: u64,
+    amount: u64,
+    unlock_ts: UnixTimestamp,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
-    let swap_info = next_account_info(account_info_iter)?;
-    let authority_info = next_account_info(account_info_iter)?;
-    let user_transfer_authority_info = next_account_info(account_info_iter)?;
-    let pool_mint_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let market_authority_info = next_account_info(account_info_iter)?;
+    let delfi_lock_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
     let source_info = next_account_info(account_info_iter)?;
-    let token_a_info = next_account_info(account_info_iter)?;
-    let token_b_info = next_account_info(account_info_iter)?;
-    let dest_token_a_info = next_account_info(account_info_iter)?;
-    let dest_token_b_info = next_account_info(account_info_iter)?;
-    let admin_fee_dest_a_info = next_account_info(account_info_iter)?;
-    let admin_fee_dest_b_info = next_account_info(account_info_iter)?;
-    let liquidity_provider_info = next_account_info(account_info_iter)?;
-    let liquidity_owner_info = next_account_info(account_info_iter)?;
-    let pyth_a_price_info = next_account_info(account_info_iter)?;
-    let pyth_b_price_info = next_account_info(account_info_iter)?;
-    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    let market_nonce =
+        check_delfi_vault(program_id, config_info, market_authority_info, vault_info)?;
+    let mut delfi_lock =
+        check_delfi_lock(program_id, config_info.key, delfi_lock_info, owner_info)?;
+
+    let clock = Clock::get()?;
+    delfi_lock.lock(amount, unlock_ts, clock.unix_timestamp)?;
+    DelfiLock::pack(delfi_lock, &mut delfi_lock_info.data.borrow_mut())?;
+
+    token_transfer(
+        config_info.key,
+        token_program_info.clone(),
+        source_info.clone(),
+        vault_info.clone(),
+        owner_info.clone(),
+        market_nonce,
+        amount,
+    )
+}
+
+fn process_unlock_delfi(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let market_authority_info = next_account_info(account_info_iter)?;
+    let delfi_lock_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let vault_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
 
-    if swap_info.owner != program_id {
+    let market_nonce =
+        check_delfi_vault(program_id, config_info, market_authority_info, vault_info)?;
+    let mut delfi_lock =
+        check_delfi_lock(program_id, config_info.key, delfi_lock_info, owner_info)?;
+
+    let clock = Clock::get()?;
+    let amount = delfi_lock.unlock(clock.unix_timestamp, clock.epoch)?;
+    DelfiLock::pack(delfi_lock, &mut delfi_lock_info.data.borrow_mut())?;
+
+    token_transfer(
+        config_info.key,
+        token_program_info.clone(),
+        vault_info.clone(),
+        destination_info.clone(),
+        market_authority_info.clone(),
+        market_nonce,
+        amount,
+    )
+}
+
+fn process_vote_gauge(
+    program_id: &Pubkey,
+    weights_bps: &[u16],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let delfi_lock_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
         return Err(SwapError::InvalidAccountOwner.into());
     }
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let mut delfi_lock =
+        check_delfi_lock(program_id, config_info.key, delfi_lock_info, owner_info)?;
 
-    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
-    let nonce = token_swap.nonce;
-    if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
-        return Err(SwapError::InvalidProgramAddress.into());
-    }
-    if *token_a_info.key != token_swap.token_a {
-        return Err(SwapError::IncorrectSwapAccount.into());
-    }
-    if *token_b_info.key != token_swap.token_b {
-        return Err(SwapError::IncorrectSwapAccount.into());
-    }
-    if token_a_info.key == dest_token_a_info.key {
-        return Err(SwapError::InvalidInput.into());
-    }
-    if token_b_info.key == dest_token_b_info.key {
-        return Err(SwapError::InvalidInput.into());
-    }
-    if *pool_mint_info.key != token_swap.pool_mint {
-        return Err(SwapError::IncorrectMint.into());
-    }
-    if *admin_fee_dest_a_info.key != token_swap.admin_fee_key_a {
-        return Err(SwapError::InvalidAdmin.into());
-    }
-    if *admin_fee_dest_b_info.key != token_swap.admin_fee_key_b {
-        return Err(SwapError::InvalidAdmin.into());
+    let clock = Clock::get()?;
+    let votes = delfi_lock.vote(weights_bps, clock.unix_timestamp, clock.epoch)?;
+    for &pool_votes in votes.iter() {
+        let swap_info = next_account_info(account_info_iter)?;
+        if swap_info.owner != program_id {
+            return Err(SwapError::InvalidAccountOwner.into());
+        }
+        let mut swap_data = swap_info.data.borrow_mut();
+        let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+        check_swap_config(&token_swap.config_key, config_info)?;
+        token_swap.set_gauge_votes(&token_swap.gauge_votes()?.add(clock.epoch, pool_votes)?);
+        config.gauge_votes = config.gauge_votes.add(clock.epoch, pool_votes)?;
     }
+    DelfiLock::pack(delfi_lock, &mut delfi_lock_info.data.borrow_mut())?;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())
+}
 
-    let token_program_id = *token_program_info.key;
-    let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
-    if pool_mint.supply == 0 {
-        return Err(SwapError::EmptySupply.into());
-    }
+fn process_create_proposal(
+    program_id: &Pubkey,
+    fees: &Fees,
+    rewards: &Rewards,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let proposal_info = next_account_info(account_info_iter)?;
+    let proposer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
 
-    let mut liquidity_provider =
-        LiquidityProvider::unpack(&liquidity_provider_info.data.borrow_mut())?;
-    if liquidity_provider_info.owner != program_id {
+    if config_info.owner != program_id {
         return Err(SwapError::InvalidAccountOwner.into());
     }
-    if &liquidity_provider.owner != liquidity_owner_info.key {
-        return Err(SwapError::InvalidOwner.into());
-    }
-    if !liquidity_owner_info.is_signer {
+    if !proposer_info.is_signer {
         return Err(SwapError::InvalidSigner.into());
     }
 
-    let (new_market_price, base_price_cumulative_last) =
-        get_new_market_price(&mut token_swap, pyth_a_price_info, pyth_b_price_info, clock)?;
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    if config.proposal_quorum == 0 {
+        return Err(SwapError::GovernanceDisabled.into());
+    }
 
-    let mut state = PoolState::new(PoolState {
-        market_price: new_market_price,
-        ..token_swap.pool_state
-    })?;
+    let proposal_index = config.proposal_count;
+    let (proposal_key, bump_seed) =
+        find_proposal_address(program_id, config_info.key, proposal_index);
+    if *proposal_info.key != proposal_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
 
-    let (base_out_amount, quote_out_amount) = state.sell_shares(
-        pool_token_amount,
-        minimum_token_a_amount,
-        minimum_token_b_amount,
-        pool_mint.supply,
+    create_program_account(
+        program_id,
+        proposal_info,
+        proposer_info,
+        system_program_info,
+        rent,
+        Proposal::LEN,
+        &[
+            config_info.key.as_ref(),
+            &proposal_index.to_le_bytes(),
+            PROPOSAL_SEED,
+            &[bump_seed],
+        ],
     )?;
+    let mut proposal = assert_uninitialized::<Proposal>(proposal_info)?;
 
-    let fees = &token_swap.fees;
-    let withdraw_fee_base = fees.withdraw_fee(base_out_amount)?;
-    let admin_fee_base = fees.admin_withdraw_fee(withdraw_fee_base)?;
-    let base_out_amount = base_out_amount
-        .checked_sub(withdraw_fee_base)
-        .ok_or(SwapError::CalculationFailure)?;
+    proposal.init(
+        *config_info.key,
+        *proposer_info.key,
+        fees,
+        rewards,
+        Clock::get()?.unix_timestamp,
+    )?;
+    Proposal::pack(proposal, &mut proposal_info.data.borrow_mut())?;
 
-    let withdraw_fee_quote = fees.withdraw_fee(quote_out_amount)?;
-    let admin_fee_quote = fees.admin_withdraw_fee(withdraw_fee_quote)?;
-    let quote_out_amount = quote_out_amount
-        .checked_sub(withdraw_fee_quote)
+    config.proposal_count = proposal_index
+        .checked_add(1)
         .ok_or(SwapError::CalculationFailure)?;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())
+}
 
-    let (_, position_index) = liquidity_provider.find_position(*swap_info.key)?;
-    liquidity_provider.withdraw(pool_token_amount, position_index)?;
-    LiquidityProvider::pack(
-        liquidity_provider,
-        &mut liquidity_provider_info.data.borrow_mut(),
-    )?;
+fn process_vote_proposal(
+    program_id: &Pubkey,
+    approve: bool,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let proposal_info = next_account_info(account_info_iter)?;
+    let vote_record_info = next_account_info(account_info_iter)?;
+    let delfi_lock_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
 
-    token_swap.pool_state = state;
-    token_swap.cumulative_ticks = token_swap
-        .cumulative_ticks
-        .checked_add(clock.unix_timestamp.try_into().unwrap())
-        .ok_or(SwapError::CalculationFailure)?
-        .checked_sub(token_swap.block_timestamp_last)
-        .ok_or(SwapError::CalculationFailure)?;
-    token_swap.block_timestamp_last = clock.unix_timestamp.try_into().unwrap();
-    token_swap.base_price_cumulative_last = base_price_cumulative_last;
-    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    if proposal_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let mut proposal = Proposal::unpack(&proposal_info.data.borrow())?;
+    // only the DELFI locked in the config of the proposal votes on it
+    let delfi_lock = check_delfi_lock(program_id, &proposal.config, delfi_lock_info, owner_info)?;
+    // the votes stay locked until the proposal can be executed, they cannot
+    // be unlocked and locked again by another owner to vote twice
+    if delfi_lock.unlock_ts < proposal.voting_end_ts {
+        return Err(SwapError::DelfiLockExpired.into());
+    }
 
-    token_transfer(
-        swap_info.key,
-        token_program_info.clone(),
-        token_a_info.clone(),
-        dest_token_a_info.clone(),
-        authority_info.clone(),
-        nonce,
-        base_out_amount,
-    )?;
-    token_transfer(
-        swap_info.key,
-        token_program_info.clone(),
-        token_a_info.clone(),
-        admin_fee_dest_a_info.clone(),
-        authority_info.clone(),
-        nonce,
-        admin_fee_base,
-    )?;
-    token_transfer(
-        swap_info.key,
-        token_program_info.clone(),
-        token_b_info.clone(),
-        dest_token_b_info.clone(),
-        authority_info.clone(),
-        nonce,
-        quote_out_amount,
-    )?;
-    token_transfer(
-        swap_info.key,
-        token_program_info.clone(),
-        token_b_info.clone(),
-        admin_fee_dest_b_info.clone(),
-        authority_info.clone(),
-        nonce,
-        admin_fee_quote,
+    let (vote_record_key, bump_seed) =
+        find_vote_record_address(program_id, proposal_info.key, owner_info.key);
+    if *vote_record_info.key != vote_record_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if vote_record_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    proposal.vote(
+        delfi_lock.locked_amount,
+        approve,
+        Clock::get()?.unix_timestamp,
     )?;
-    token_burn(
-        swap_info.key,
-        token_program_info.clone(),
-        source_info.clone(),
-        pool_mint_info.clone(),
-        user_transfer_authority_info.clone(),
-        nonce,
-        pool_token_amount,
+    Proposal::pack(proposal, &mut proposal_info.data.borrow_mut())?;
+
+    create_program_account(
+        program_id,
+        vote_record_info,
+        owner_info,
+        system_program_info,
+        rent,
+        VoteRecord::LEN,
+        &[
+            proposal_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            VOTE_RECORD_SEED,
+            &[bump_seed],
+        ],
     )?;
+    let mut vote_record = assert_uninitialized::<VoteRecord>(vote_record_info)?;
+
+    vote_record.init(
+        *proposal_info.key,
+        *owner_info.key,
+        delfi_lock.locked_amount,
+        approve,
+    );
+    VoteRecord::pack(vote_record, &mut vote_record_info.data.borrow_mut())?;
 
     Ok(())
 }
 
-fn process_init_liquidity_provider(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+fn process_execute_proposal(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
-    let liquidity_provider_info = next_account_info(account_info_iter)?;
-    let liquidity_owner_info = next_account_info(account_info_iter)?;
-    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let proposal_info = next_account_info(account_info_iter)?;
 
-    if liquidity_provider_info.owner != program_id {
+    if config_info.owner != program_id || proposal_info.owner != program_id {
         return Err(SwapError::InvalidAccountOwner.into());
     }
 
-    assert_rent_exempt(rent, liquidity_provider_info)?;
-    let mut liquidity_provider =
-        assert_uninitialized::<LiquidityProvider>(liquidity_provider_info)?;
-
-    if !liquidity_owner_info.is_signer {
-        return Err(SwapError::InvalidSigner.into());
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let mut proposal = Proposal::unpack(&proposal_info.data.borrow())?;
+    if proposal.config != *config_info.key {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if config.proposal_quorum == 0 {
+        return Err(SwapError::GovernanceDisabled.into());
     }
 
-    liquidity_provider.init(*liquidity_owner_info.key, vec![]);
-    LiquidityProvider::pack(
-        liquidity_provider,
-        &mut liquidity_provider_info.data.borrow_mut(),
-    )?;
-
-    Ok(())
+    proposal.execute(config.proposal_quorum, Clock::get()?.unix_timestamp)?;
+    config.fees = Fees::new(&proposal.fees);
+    config.rewards = Rewards::new(&proposal.rewards);
+    Proposal::pack(proposal, &mut proposal_info.data.borrow_mut())?;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())
 }
 
-fn process_claim_liquidity_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+fn process_refresh_liquidity_obligation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
-    let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
-    let market_authority_info = next_account_info(account_info_iter)?;
-    let liquidity_provider_info = next_account_info(account_info_iter)?;
-    let liquidity_owner_info = next_account_info(account_info_iter)?;
-    let claim_destination_info = next_account_info(account_info_iter)?;
-    let claim_mint_info = next_account_info(account_info_iter)?;
-    let token_program_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let config_info = next_account_info(account_info_iter)?;
 
     if swap_info.owner != program_id || config_info.owner != program_id {
+        msg!("Swap account is not owned by swap token program");
         return Err(SwapError::InvalidAccountOwner.into());
     }
 
     let config = ConfigInfo::unpack(&config_info.data.borrow())?;
-    let market_nonce = config.bump_seed;
-    if *market_authority_info.key != authority_id(program_id, config_info.key, market_nonce)? {
-        return Err(SwapError::InvalidProgramAddress.into());
+    let mut swap_data = swap_info.data.borrow_mut();
+    let token_swap = SwapInfoData::load_mut(&mut swap_data)?;
+    check_swap_config(&token_swap.config_key, config_info)?;
+    // the rewards accumulated so far are valued at the previous price and
+    // weighted by the previous gauge votes
+    accumulate_rewards(token_swap, clock)?;
+    token_swap.set_gauge_weight(&GaugeWeight::finalize(
+        clock.epoch,
+        &token_swap.gauge_votes()?,
+        &config.gauge_votes,
+    ));
+
+    if config.deltafi_price_oracle != Pubkey::default() {
+        let deltafi_price_info = next_account_info(account_info_iter)?;
+        if *deltafi_price_info.key != config.deltafi_price_oracle {
+            return Err(SwapError::IncorrectOracleAccount.into());
+        }
+        token_swap.set_deltafi_price(get_pyth_price(deltafi_price_info, clock)?);
     }
 
-    if config.deltafi_mint != *claim_mint_info.key {
-        return Err(SwapError::IncorrectMint.into());
+    Ok(())
+}
+
+/// Rewards per pool token over a claim period, at the mid price of the pool
+/// and the DELFI price last refreshed from the config oracle. No rewards
+/// accrue before the DELFI price is refreshed
+fn reward_ratio(
+    pool_state: &mut PoolState,
+    deltafi_price: Decimal,
+) -> Result<Decimal, ProgramError> {
+    if deltafi_price.is_zero() {
+        return Ok(Decimal::zero());
     }
-    if claim_destination_info.owner == market_authority_info.key {
-        return Err(SwapError::InvalidOwner.into());
+    let lp_price = pool_state.get_mid_price()?;
+    lp_price.try_div(deltafi_price)
+}
+
+/// Accumulate the liquidity rewards of the pool up to the current time, at the
+/// mid price the pool held since the last accumulation, halved by the halvings
+/// passed in each part of the period and weighted by the gauge votes last
+/// finalized for the pool.
+/// Returns the rewards per pool token to settle positions with
+pub fn accumulate_rewards(
+    token_swap: &mut SwapInfoData,
+    clock: &Clock,
+) -> Result<Decimal, ProgramError> {
+    let reward_ratio = token_swap.gauge_weight()?.apply(reward_ratio(
+        &mut token_swap.pool_state()?,
+        token_swap.deltafi_price(),
+    )?)?;
+    let halving_schedule = token_swap.halving_schedule()?;
+    let mut reward_accumulator = token_swap.reward_accumulator();
+    // the first accumulation only starts the period, there is nothing to split
+    while reward_accumulator.last_update_ts > 0 {
+        match halving_schedule.next_halving_ts(reward_accumulator.last_update_ts) {
+            Some(halving_ts) if halving_ts < clock.unix_timestamp => {
+                reward_accumulator = reward_accumulator.accumulate(
+                    halving_schedule.halve(reward_ratio, reward_accumulator.last_update_ts)?,
+                    halving_ts,
+                )?;
+            }
+            _ => break,
+        }
     }
+    let reward_accumulator = reward_accumulator.accumulate(
+        halving_schedule.halve(reward_ratio, reward_accumulator.last_update_ts)?,
+        clock.unix_timestamp,
+    )?;
+    token_swap.set_reward_accumulator(&reward_accumulator);
+    Ok(reward_accumulator.reward_per_share)
+}
 
-    let mut liquidity_provider =
-        LiquidityProvider::unpack(&liquidity_provider_info.data.borrow_mut())?;
-    if liquidity_provider.owner != *liquidity_owner_info.key {
-        return Err(SwapError::InvalidOwner.into());
+/// Count the rewards about to be minted against the epoch budget of the
+/// config, only written back when a budget is set so that swaps and claims
+/// of different pools do not contend on the config otherwise
+fn record_rewards_minted(
+    config_info: &AccountInfo,
+    config: &mut ConfigInfo,
+    epoch: Epoch,
+    amount: u64,
+) -> ProgramResult {
+    if config.epoch_reward_budget == 0 {
+        return Ok(());
     }
-    if !liquidity_owner_info.is_signer {
-        return Err(SwapError::InvalidSigner.into());
+    config.record_rewards_minted(epoch, amount)?;
+    ConfigInfo::pack(config.clone(), &mut config_info.data.borrow_mut())
+}
+
+/// DELFI account of the referrer following the fixed accounts of a claim,
+/// passed by the referred liquidity providers only
+fn next_referrer_destination<'a, 'b>(
+    program_id: &Pubkey,
+    liquidity_provider_info: &AccountInfo<'b>,
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+) -> Result<Option<&'a AccountInfo<'b>>, ProgramError> {
+    if liquidity_provider_info.owner == program_id
+        && LiquidityProvider::unpack_referrer(&liquidity_provider_info.data.borrow())?.is_some()
+    {
+        next_account_info(account_info_iter).map(Some)
+    } else {
+        Ok(None)
     }
+}
 
-    let reward_amount = liquidity_provider.claim(*swap_info.key)?;
-    LiquidityProvider::pack(
-        liquidity_provider,
-        &mut liquidity_provider_info.data.borrow_mut(),
-    )?;
+/// Referral reward of a claim, minted to a DELFI account of the referrer of
+/// the liquidity provider
+fn referral_reward(
+    config: &ConfigInfo,
+    liquidity_provider: &LiquidityProvider,
+    referrer_destination_info: Option<&AccountInfo>,
+    token_program_info: &AccountInfo,
+    reward_amount: u64,
+) -> Result<u64, ProgramError> {
+    let referrer_destination_info = match referrer_destination_info {
+        Some(referrer_destination_info) => referrer_destination_info,
+        None => return Ok(0),
+    };
+    let referrer_destination =
+        unpack_token_account(referrer_destination_info, token_program_info.key)?;
+    if referrer_destination.owner != liquidity_provider.referrer {
+        return Err(SwapError::InvalidReferrer.into());
+    }
+    if referrer_destination.mint != config.deltafi_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    Ok(config.referral_reward(reward_amount))
+}
+
+/// Record a swap in the volume of its slot, enforcing the per slot throttle
+/// of the pool. Returns the slot volume to store once the swap goes through
+pub fn throttle_swap(
+    token_swap: &SwapInfoData,
+    clock: &Clock,
+    quote: &SwapQuote,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+) -> Result<SlotVolume, ProgramError> {
+    let base_volume = match swap_direction {
+        SwapDirection::SellBase => amount_in,
+        SwapDirection::SellQuote => quote.amount_out,
+    };
+    let slot_volume = token_swap.slot_volume().record(clock.slot, base_volume)?;
+    let max_swaps_per_slot = token_swap.max_swaps_per_slot();
+    let max_volume_per_slot = token_swap.max_volume_per_slot();
+    if (max_swaps_per_slot > 0 && slot_volume.swap_count > max_swaps_per_slot)
+        || (max_volume_per_slot > 0 && slot_volume.volume > max_volume_per_slot)
+    {
+        return Err(SwapError::SlotThrottleExceeded.into());
+    }
+    Ok(slot_volume)
+}
+
+/// Base and quote balances of the pool after a swap
+pub fn swapped_balances(
+    token_a_amount: u64,
+    token_b_amount: u64,
+    amount_in: u64,
+    amount_out: u64,
+    swap_direction: SwapDirection,
+) -> Result<(u64, u64), ProgramError> {
+    let (base_balance, quote_balance) = match swap_direction {
+        SwapDirection::SellBase => (
+            token_a_amount.checked_add(amount_in),
+            token_b_amount.checked_sub(amount_out),
+        ),
+        SwapDirection::SellQuote => (
+            token_a_amount.checked_sub(amount_out),
+            token_b_amount.checked_add(amount_in),
+        ),
+    };
+    Ok((
+        base_balance.ok_or(SwapError::CalculationFailure)?,
+        quote_balance.ok_or(SwapError::CalculationFailure)?,
+    ))
+}
+
+/// Quote a swap at the current market price of the pool, on the curve of the
+/// token-swap, enforcing the trade size and price impact caps. Returns the pool state repriced to the market
+/// and the twap accumulated up to the current block along with the quote.
+pub fn quote_market_swap(
+    token_swap: &SwapInfoData,
+    observation_buffer_info: &AccountInfo,
+    pyth_a_price_info: &AccountInfo,
+    pyth_b_price_info: &AccountInfo,
+    openbook_infos: Option<(&AccountInfo, &AccountInfo, &AccountInfo)>,
+    clock: &Clock,
+    reserve_in: u64,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+) -> Result<(PoolState, Twap, SwapQuote), ProgramError> {
+    if reserve_in < amount_in {
+        return Err(SwapError::InsufficientFunds.into());
+    }
+    let max_amount_in = token_swap.max_amount_in();
+    if max_amount_in > 0 && amount_in > max_amount_in {
+        return Err(SwapError::MaxTradeSizeExceeded.into());
+    }
+    let max_amount_in_reserve_bps = token_swap.max_amount_in_reserve_bps();
+    if max_amount_in_reserve_bps > 0
+        && Decimal::from(amount_in).try_mul(BPS_DENOMINATOR)?
+            > Decimal::from(reserve_in).try_mul(max_amount_in_reserve_bps)?
+    {
+        return Err(SwapError::MaxTradeSizeExceeded.into());
+    }
 
-    token_mint_to(
-        config_info.key,
-        token_program_info.clone(),
-        claim_mint_info.clone(),
-        claim_destination_info.clone(),
-        market_authority_info.clone(),
-        market_nonce,
-        reward_amount,
+    let mut pool_state = token_swap.pool_state()?;
+    let (new_market_price, twap) = get_new_market_price(
+        token_swap,
+        observation_buffer_info,
+        &mut pool_state,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        openbook_infos,
+        clock,
     )?;
 
-    Ok(())
-}
+    // targets are already adjusted to the current market price by get_new_market_price
+    let state = if new_market_price == pool_state.market_price {
+        pool_state
+    } else {
+        PoolState::new(PoolState {
+            market_price: new_market_price,
+            ..pool_state
+        })?
+    };
 
-fn process_refresh_liquidity_obligation(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    let swap_info = next_account_info(account_info_iter)?;
-    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    // the curve only shapes the quote, the pool keeps its own slope
+    let curve_state = token_swap.curve_type()?.apply(&state)?;
+    let mut quote = curve_state.quote_swap(
+        amount_in,
+        swap_direction,
+        &token_swap.swap_fees(swap_direction)?,
+        &token_swap.rewards()?,
+    )?;
+    quote.reward_amount = token_swap
+        .halving_schedule()?
+        .halve_u64(quote.reward_amount, clock.unix_timestamp);
 
-    if swap_info.owner != program_id {
-        msg!("Swap account is not owned by swap token program");
-        return Err(SwapError::InvalidAccountOwner.into());
+    let max_price_impact_bps = token_swap.max_price_impact_bps();
+    if max_price_impact_bps > 0 {
+        check_price_impact(
+            &curve_state,
+            &quote,
+            amount_in,
+            swap_direction,
+            max_price_impact_bps,
+        )?;
     }
 
-    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    Ok((state, twap, quote))
+}
 
-    let lp_price = token_swap.pool_state.get_mid_price()?;
-    let _deltafi_price = Decimal::one().try_div(10)?; // Temp value
-    let reward_ratio = lp_price.try_div(_deltafi_price)?;
+/// Trade fee of a swap in the token the token-swap collects it in, the base
+/// trade fee of a swap selling quote being sold to the pool when the fees are
+/// collected in quote. Returns the direction of a swap paying its fee in that
+/// token, along with the trade fee and admin fee.
+pub fn collected_trade_fee(
+    token_swap: &SwapInfoData,
+    state: &PoolState,
+    swap_direction: SwapDirection,
+    quote: &SwapQuote,
+) -> Result<(SwapDirection, u64, u64), ProgramError> {
+    if swap_direction == SwapDirection::SellQuote && token_swap.is_fee_in_quote()? {
+        let (trade_fee, admin_fee) = state
+            .quote_trade_fee_in_quote(quote.trade_fee, &token_swap.swap_fees(swap_direction)?)?;
+        return Ok((SwapDirection::SellBase, trade_fee, admin_fee));
+    }
+    Ok((swap_direction, quote.trade_fee, quote.admin_fee))
+}
 
-    for liquidity_provider_info in account_info_iter {
-        let mut liquidity_provider =
-            LiquidityProvider::unpack(&liquidity_provider_info.data.borrow_mut())?;
-        let (position, _) = liquidity_provider.find_position(*swap_info.key)?;
-        position.calc_and_update_rewards(reward_ratio, clock.unix_timestamp)?;
+/// Add the lp share of a trade fee to the fee accumulator of the token-swap,
+/// valued with the pool state before the trade
+pub fn accumulate_trade_fee(
+    token_swap: &mut SwapInfoData,
+    state: &PoolState,
+    fee_direction: SwapDirection,
+    trade_fee: u64,
+    admin_fee: u64,
+) -> ProgramResult {
+    let fee_per_target = state.lp_fee_per_target(fee_direction, trade_fee, admin_fee)?;
+    token_swap
+        .set_cumulative_fee_per_lp(token_swap.cumulative_fee_per_lp().try_add(fee_per_target)?);
+    Ok(())
+}
 
-        LiquidityProvider::pack(
-            liquidity_provider,
-            &mut liquidity_provider_info.data.borrow_mut(),
-        )?;
+/// Trade fee discount of a trader holding DELFI in the rewards receiver of a
+/// swap, zero if the trader does not own it
+fn fee_discount_bps(
+    config: &ConfigInfo,
+    reward_token: &Account,
+    user_source_info: &AccountInfo,
+    token_program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+    if !config.fee_discount.is_enabled() {
+        return Ok(0);
     }
+    let trader = unpack_token_account(user_source_info, token_program_id)?.owner;
+    if reward_token.owner != trader {
+        return Ok(0);
+    }
+    Ok(config.fee_discount.discount_bps(reward_token.amount))
+}
 
+/// Check the price impact of a quote against the mid price of the pool
+pub fn check_price_impact(
+    state: &PoolState,
+    quote: &SwapQuote,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+    max_price_impact_bps: u64,
+) -> ProgramResult {
+    // fees are not price impact, so measure the output before the trade fee
+    let receive_amount = quote
+        .amount_out
+        .checked_add(quote.trade_fee)
+        .ok_or(SwapError::CalculationFailure)?;
+    if state.price_impact_bps(amount_in, receive_amount, swap_direction)? > max_price_impact_bps {
+        return Err(SwapError::PriceImpactExceeded.into());
+    }
     Ok(())
 }
 
 fn get_new_market_price(
-    token_swap: &mut SwapInfo,
+    token_swap: &SwapInfoData,
+    observation_buffer_info: &AccountInfo,
+    pool_state: &mut PoolState,
     pyth_a_price_info: &AccountInfo,
     pyth_b_price_info: &AccountInfo,
+    openbook_infos: Option<(&AccountInfo, &AccountInfo, &AccountInfo)>,
     clock: &Clock,
-) -> Result<(Decimal, Decimal), ProgramError> {
-    let pool_state = &mut token_swap.pool_state;
+) -> Result<(Decimal, Twap), ProgramError> {
     let pool_mid_price = pool_state.get_mid_price()?;
     let block_timestamp_last: u64 = clock.unix_timestamp.try_into().unwrap();
-    let mut base_price_cumulative_last = token_swap.base_price_cumulative_last;
-    if token_swap.is_open_twap {
-        let time_elapsed = block_timestamp_last - token_swap.block_timestamp_last;
-        if time_elapsed > 0
-            && !pool_state.base_reserve.is_zero()
-            && !pool_state.quote_reserve.is_zero()
-        {
-            base_price_cumulative_last =
-                base_price_cumulative_last.try_add(pool_mid_price.try_mul(time_elapsed as u64)?)?;
-        }
-    }
+    let twap = accumulate_twap(token_swap, pool_state, pool_mid_price, block_timestamp_last)?;
+    let is_open_twap = token_swap.is_open_twap()?;
 
     let market_price = if let Ok(market_price) =
         get_market_price_from_pyth(pyth_a_price_info, pyth_b_price_info, clock)
     {
         // pyth price
         market_price
-    } else if token_swap.is_open_twap {
+    } else if let (true, Some(average_price)) = (
+        is_open_twap,
+        get_twap_price(
+            token_swap,
+            observation_buffer_info,
+            &twap,
+            block_timestamp_last,
+        )?,
+    ) {
         // internal oracle price
-        base_price_cumulative_last.try_div(block_timestamp_last - token_swap.cumulative_ticks)?
+        average_price
+    } else if let Some(openbook_price) =
+        get_market_price_from_openbook(token_swap, pool_state, openbook_infos)?
+    {
+        // order book price
+        openbook_price
     } else {
         // current pool middle price
         pool_mid_price
     };
 
-    let deviation = if pool_mid_price > market_price {
-        pool_mid_price.try_sub(market_price)?
-    } else {
-        market_price.try_sub(pool_mid_price)?
-    };
+    let deviation = SDecimal::from(market_price)
+        .try_sub(SDecimal::from(pool_mid_price))?
+        .abs();
 
     Ok((
         if deviation.try_mul(100u64)? > pool_mid_price {
@@ -1001,11 +4784,322 @@ fn get_new_market_price(
         } else {
             pool_mid_price
         },
-        base_price_cumulative_last,
+        twap,
     ))
 }
 
-fn get_market_price_from_pyth(
+/// Accumulate the mid price the pool held since its last update into its twap,
+/// left as it is when the twap is not open or the pool is empty
+fn accumulate_twap(
+    token_swap: &SwapInfoData,
+    pool_state: &PoolState,
+    pool_mid_price: Decimal,
+    block_timestamp_last: u64,
+) -> Result<Twap, ProgramError> {
+    let twap = token_swap.twap();
+    if !token_swap.is_open_twap()? {
+        return Ok(twap);
+    }
+    let time_elapsed = block_timestamp_last
+        .checked_sub(token_swap.block_timestamp_last())
+        .ok_or(SwapError::CalculationFailure)?;
+    if time_elapsed > 0 && !pool_state.base_reserve.is_zero() && !pool_state.quote_reserve.is_zero()
+    {
+        twap.accumulate(pool_mid_price, time_elapsed)
+    } else {
+        Ok(twap)
+    }
+}
+
+/// Average mid price over the twap window of the pool, looked up in its
+/// observation buffer. Without a window the whole accumulated twap is averaged.
+fn get_twap_price(
+    token_swap: &SwapInfoData,
+    observation_buffer_info: &AccountInfo,
+    twap: &Twap,
+    block_timestamp_last: u64,
+) -> Result<Option<Decimal>, ProgramError> {
+    let twap_window_secs = token_swap.twap_window_secs();
+    if twap_window_secs == 0 {
+        return twap.average();
+    }
+    ObservationBuffer::load(&observation_buffer_info.data.borrow())?.average_price(
+        &Observation::new(block_timestamp_last, twap.base_price_cumulative_last),
+        twap_window_secs,
+    )
+}
+
+/// Store the twap accumulated up to the current block, and observe it when open
+pub fn update_twap(
+    token_swap: &mut SwapInfoData,
+    observation_buffer_info: &AccountInfo,
+    twap: &Twap,
+    clock: &Clock,
+) -> ProgramResult {
+    let block_timestamp_last: u64 = clock
+        .unix_timestamp
+        .try_into()
+        .map_err(|_| SwapError::CalculationFailure)?;
+    token_swap.set_twap(twap);
+    token_swap.set_block_timestamp_last(block_timestamp_last);
+    if token_swap.is_open_twap()? {
+        ObservationBuffer::load_mut(&mut observation_buffer_info.data.borrow_mut())?.record(
+            Observation::new(block_timestamp_last, twap.base_price_cumulative_last),
+        );
+    }
+    Ok(())
+}
+
+/// Check the config is the one the token-swap was initialized with
+pub fn check_swap_config(config_key: &Pubkey, config_info: &AccountInfo) -> ProgramResult {
+    if *config_info.key != *config_key {
+        return Err(SwapError::IncorrectConfigAccount.into());
+    }
+    Ok(())
+}
+
+/// Check the observation buffer passed along a token-swap belongs to it
+pub fn check_observation_buffer(
+    program_id: &Pubkey,
+    swap_info: &AccountInfo,
+    observation_buffer_info: &AccountInfo,
+) -> ProgramResult {
+    if observation_buffer_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if ObservationBuffer::load(&observation_buffer_info.data.borrow())?.swap != *swap_info.key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+/// Check the whitelist belongs to the token-swap
+pub fn check_whitelist(
+    program_id: &Pubkey,
+    swap_info: &AccountInfo,
+    whitelist_info: &AccountInfo,
+) -> ProgramResult {
+    if whitelist_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if Whitelist::load(&whitelist_info.data.borrow())?.swap != *swap_info.key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+/// Check the deny-list address of a config, the deny-list may not be created
+pub fn check_deny_list(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+    deny_list_info: &AccountInfo,
+) -> ProgramResult {
+    let (deny_list_key, _) = find_deny_list_address(program_id, config_info.key);
+    if *deny_list_info.key != deny_list_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+/// Split the transfer hook program off the accounts following the fixed ones,
+/// it leads them when the token-swap has a transfer hook
+pub fn split_transfer_hook<'a, 'b>(
+    transfer_hook_program: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<(Option<&'a AccountInfo<'b>>, &'a [AccountInfo<'b>]), ProgramError> {
+    if *transfer_hook_program == Pubkey::default() {
+        return Ok((None, accounts));
+    }
+    match accounts.split_first() {
+        Some((transfer_hook_info, rest)) if transfer_hook_info.key == transfer_hook_program => {
+            Ok((Some(transfer_hook_info), rest))
+        }
+        _ => Err(SwapError::IncorrectTransferHookProgram.into()),
+    }
+}
+
+/// Split the OpenBook market of a token-swap along with its bids and asks off
+/// the accounts following the fixed ones of an instruction pricing the pool,
+/// they lead them when the token-swap has a market
+#[allow(clippy::type_complexity)]
+pub fn split_openbook_market<'a, 'b>(
+    openbook_market: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<
+    (
+        Option<(
+            &'a AccountInfo<'b>,
+            &'a AccountInfo<'b>,
+            &'a AccountInfo<'b>,
+        )>,
+        &'a [AccountInfo<'b>],
+    ),
+    ProgramError,
+> {
+    if *openbook_market == Pubkey::default() {
+        return Ok((None, accounts));
+    }
+    match accounts {
+        [market_info, bids_info, asks_info, rest @ ..] if market_info.key == openbook_market => {
+            Ok((Some((market_info, bids_info, asks_info)), rest))
+        }
+        _ => Err(SwapError::IncorrectOracleAccount.into()),
+    }
+}
+
+/// Mid price of the best bid and ask of the OpenBook market of a token-swap,
+/// in quote tokens per base token. None when the market is not passed, or a
+/// side of its book is empty or the book is crossed.
+pub fn get_market_price_from_openbook(
+    token_swap: &SwapInfoData,
+    pool_state: &PoolState,
+    openbook_infos: Option<(&AccountInfo, &AccountInfo, &AccountInfo)>,
+) -> Result<Option<Decimal>, ProgramError> {
+    let (market_info, bids_info, asks_info) = match openbook_infos {
+        Some(infos) => infos,
+        None => return Ok(None),
+    };
+    if *market_info.key != token_swap.openbook_market {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+    let market = openbook::load_market(&market_info.data.borrow())?;
+    if *bids_info.key != market.bids
+        || *asks_info.key != market.asks
+        || *bids_info.owner != openbook::id()
+        || *asks_info.owner != openbook::id()
+    {
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+
+    let best_bid = openbook::best_price(&bids_info.data.borrow(), openbook::Side::Bids)?;
+    let best_ask = openbook::best_price(&asks_info.data.borrow(), openbook::Side::Asks)?;
+    let (best_bid, best_ask) = match (best_bid, best_ask) {
+        (Some(best_bid), Some(best_ask)) if best_bid <= best_ask => (best_bid, best_ask),
+        _ => return Ok(None),
+    };
+    // prices are quote lots per base lot
+    let amount_price = Decimal::from(best_bid)
+        .try_add(Decimal::from(best_ask))?
+        .try_div(2u64)?
+        .try_mul(market.pc_lot_size)?
+        .try_div(market.coin_lot_size)?;
+    pool_state.token_price(amount_price).map(Some)
+}
+
+/// Mid price and lp virtual price of a token-swap, the lp virtual price being
+/// zero without pool tokens
+fn feed_prices(
+    swap_info: &AccountInfo,
+    pool_mint_info: &AccountInfo,
+) -> Result<(Decimal, Decimal), ProgramError> {
+    let swap_data = swap_info.data.borrow();
+    let token_swap = SwapInfoData::load(&swap_data)?;
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    let supply = Mint::unpack(&pool_mint_info.data.borrow())
+        .map_err(|_| SwapError::ExpectedMint)?
+        .supply;
+    let mut pool_state = token_swap.pool_state()?;
+    let lp_virtual_price = if supply == 0 {
+        Decimal::zero()
+    } else {
+        pool_state.lp_virtual_price(supply)?
+    };
+    Ok((pool_state.get_mid_price()?, lp_virtual_price))
+}
+
+/// Store the prices of a token-swap in its price feed. The feed address is
+/// required from every instruction moving the pool, the feed may not be
+/// created yet
+pub fn update_price_feed(
+    program_id: &Pubkey,
+    swap_info: &AccountInfo,
+    price_feed_info: &AccountInfo,
+    pool_mint_info: &AccountInfo,
+    clock: &Clock,
+) -> ProgramResult {
+    let (price_feed_key, _) = find_price_feed_address(program_id, swap_info.key);
+    if *price_feed_info.key != price_feed_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if price_feed_info.owner != program_id {
+        return Ok(());
+    }
+    let (mid_price, lp_virtual_price) = feed_prices(swap_info, pool_mint_info)?;
+    let mut price_feed_data = price_feed_info.data.borrow_mut();
+    let price_feed = PriceFeed::load_mut(&mut price_feed_data)?;
+    if price_feed.swap != *swap_info.key {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    price_feed.update(mid_price, lp_virtual_price, clock.slot);
+    Ok(())
+}
+
+/// Accounts before the mints in the associated token accounts of an instruction
+const ASSOCIATED_TOKEN_ACCOUNTS_LEN: usize = 3;
+
+/// Split the accounts creating missing associated token accounts, the program,
+/// payer, system program and one mint per destination, from the
+/// position pages. They are only passed when starting with the program
+pub fn split_associated_token_accounts<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+    mint_count: usize,
+) -> Result<(Option<&'a [AccountInfo<'b>]>, &'a [AccountInfo<'b>]), ProgramError> {
+    match accounts.first() {
+        Some(program_info) if *program_info.key == spl_associated_token_account::id() => {
+            let len = ASSOCIATED_TOKEN_ACCOUNTS_LEN + mint_count;
+            if accounts.len() < len {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            }
+            let (associated_token_infos, rest) = accounts.split_at(len);
+            Ok((Some(associated_token_infos), rest))
+        }
+        _ => Ok((None, accounts)),
+    }
+}
+
+/// Create the associated token accounts of the wallet for the destinations
+/// which don't exist yet. Existing destinations are left as they are
+fn create_associated_token_accounts<'a>(
+    associated_token_infos: &[AccountInfo<'a>],
+    destination_infos: &[&AccountInfo<'a>],
+    mints: &[Pubkey],
+    wallet_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let (program_info, payer_info, system_program_info, mint_infos) = match associated_token_infos {
+        [program_info, payer_info, system_program_info, mint_infos @ ..] => {
+            (program_info, payer_info, system_program_info, mint_infos)
+        }
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+    for ((destination_info, mint), mint_info) in destination_infos.iter().zip(mints).zip(mint_infos)
+    {
+        if mint_info.key != mint {
+            return Err(SwapError::IncorrectMint.into());
+        }
+        if !destination_info.data_is_empty() {
+            continue;
+        }
+        invoke(
+            &create_associated_token_account(payer_info.key, wallet_info.key, mint_info.key),
+            &[
+                payer_info.clone(),
+                (*destination_info).clone(),
+                wallet_info.clone(),
+                mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                program_info.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Get market price of token a in token b from pyth price accounts
+pub fn get_market_price_from_pyth(
     pyth_a_price_info: &AccountInfo,
     pyth_b_price_info: &AccountInfo,
     clock: &Clock,
@@ -1064,9 +5158,33 @@ fn _get_pyth_product_quote_currency(
     Err(SwapError::InvalidOracleConfig.into())
 }
 
+/// Check the account is a price account of the Pyth program, whether its price
+/// is valid or not
+pub fn check_pyth_price_account(pyth_price_info: &AccountInfo) -> ProgramResult {
+    if *pyth_price_info.owner != pyth::id() {
+        msg!("Oracle account is not owned by the Pyth program");
+        return Err(SwapError::IncorrectOracleAccount.into());
+    }
+    let pyth_price_data = pyth_price_info.try_borrow_data()?;
+    match pyth::load::<pyth::Price>(&pyth_price_data) {
+        Ok(pyth_price)
+            if pyth_price.magic == pyth::MAGIC
+                && pyth_price.ver == pyth::VERSION
+                && pyth_price.atype == pyth::AccountType::Price as u32 =>
+        {
+            Ok(())
+        }
+        _ => {
+            msg!("Oracle account is not a Pyth price account");
+            Err(SwapError::IncorrectOracleAccount.into())
+        }
+    }
+}
+
 fn get_pyth_price(pyth_price_info: &AccountInfo, clock: &Clock) -> Result<Decimal, ProgramError> {
     const STALE_AFTER_SLOTS_ELAPSED: u64 = 5;
 
+    check_pyth_price_account(pyth_price_info)?;
     let pyth_price_data = pyth_price_info.try_borrow_data()?;
     let pyth_price = pyth::load::<pyth::Price>(&pyth_price_data)
         .map_err(|_| ProgramError::InvalidAccountData)?;
@@ -1133,6 +5251,211 @@ pub fn assert_uninitialized<T: Pack + IsInitialized>(
     }
 }
 
+/// Resize a program account, topping up its rent from the payer
+pub fn grow_account<'a>(
+    account_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    rent: &Rent,
+    new_len: usize,
+) -> ProgramResult {
+    let lamports = rent
+        .minimum_balance(new_len)
+        .saturating_sub(account_info.lamports());
+    if lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, lamports),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    account_info.realloc(new_len, true)
+}
+
+/// Check the liquidity provider is the account derived from its owner
+fn check_liquidity_provider_address(
+    program_id: &Pubkey,
+    liquidity_provider_info: &AccountInfo,
+    liquidity_owner_info: &AccountInfo,
+) -> ProgramResult {
+    let (liquidity_provider_key, _) =
+        find_liquidity_provider_address(program_id, liquidity_owner_info.key);
+    if *liquidity_provider_info.key != liquidity_provider_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+/// Check the signer is the owner or the delegate of the liquidity provider,
+/// and the liquidity provider is the account derived from its owner
+fn check_liquidity_provider_authority(
+    program_id: &Pubkey,
+    liquidity_provider: &LiquidityProvider,
+    liquidity_provider_info: &AccountInfo,
+    authority_info: &AccountInfo,
+) -> ProgramResult {
+    if !liquidity_provider.is_authority(authority_info.key) {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if !authority_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    let (liquidity_provider_key, _) =
+        find_liquidity_provider_address(program_id, &liquidity_provider.owner);
+    if *liquidity_provider_info.key != liquidity_provider_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
+
+/// Unpack a position page, checking it belongs to the liquidity provider
+fn unpack_position_page(
+    program_id: &Pubkey,
+    liquidity_provider_info: &AccountInfo,
+    position_page_info: &AccountInfo,
+) -> Result<LiquidityPositionPage, ProgramError> {
+    if position_page_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let position_page = LiquidityPositionPage::unpack(&position_page_info.data.borrow())?;
+    if position_page.liquidity_provider != *liquidity_provider_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    let position_page_key = Pubkey::create_program_address(
+        &[
+            liquidity_provider_info.key.as_ref(),
+            POSITION_PAGE_SEED,
+            &[position_page.page_index],
+            &[position_page.bump_seed],
+        ],
+        program_id,
+    )
+    .map_err(|_| SwapError::InvalidProgramAddress)?;
+    if *position_page_info.key != position_page_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    Ok(position_page)
+}
+
+/// Unpack a liquidity provider with the positions of its pages appended,
+/// so positions are looked up through the whole chain
+fn unpack_liquidity_positions(
+    program_id: &Pubkey,
+    liquidity_provider_info: &AccountInfo,
+    position_page_infos: &[AccountInfo],
+) -> Result<(LiquidityProvider, Vec<LiquidityPositionPage>), ProgramError> {
+    let mut liquidity_provider =
+        LiquidityProvider::unpack_account(&liquidity_provider_info.data.borrow())?;
+    let mut position_pages = Vec::with_capacity(position_page_infos.len());
+    for (page_index, position_page_info) in position_page_infos.iter().enumerate() {
+        let mut position_page =
+            unpack_position_page(program_id, liquidity_provider_info, position_page_info)?;
+        if position_page.page_index as usize != page_index {
+            return Err(SwapError::PositionPageMissing.into());
+        }
+        if let Some(next_page_info) = position_page_infos.get(page_index + 1) {
+            if position_page.next_page != *next_page_info.key {
+                return Err(SwapError::PositionPageMissing.into());
+            }
+        } else if !position_page.is_last() {
+            return Err(SwapError::PositionPageMissing.into());
+        }
+        liquidity_provider
+            .positions
+            .append(&mut position_page.positions);
+        position_pages.push(position_page);
+    }
+    Ok((liquidity_provider, position_pages))
+}
+
+/// Pack a liquidity provider, filling its own positions first
+/// and spilling the rest over its pages
+fn pack_liquidity_positions(
+    mut liquidity_provider: LiquidityProvider,
+    position_pages: Vec<LiquidityPositionPage>,
+    liquidity_provider_info: &AccountInfo,
+    position_page_infos: &[AccountInfo],
+) -> ProgramResult {
+    let max_positions = LiquidityProvider::max_positions(liquidity_provider_info.data_len());
+    let mut page_positions = if liquidity_provider.positions.len() > max_positions {
+        liquidity_provider.positions.split_off(max_positions)
+    } else {
+        vec![]
+    };
+    LiquidityProvider::pack_account(
+        liquidity_provider,
+        &mut liquidity_provider_info.data.borrow_mut(),
+    )?;
+
+    for (mut position_page, position_page_info) in
+        position_pages.into_iter().zip(position_page_infos)
+    {
+        let page_len = page_positions.len().min(MAX_PAGE_POSITIONS);
+        position_page.positions = page_positions.drain(..page_len).collect();
+        LiquidityPositionPage::pack(position_page, &mut position_page_info.data.borrow_mut())?;
+    }
+    if !page_positions.is_empty() {
+        return Err(SwapError::LiquidityPositionsFull.into());
+    }
+    Ok(())
+}
+
+/// Create a program derived account, funding its rent from the payer.
+/// Lamports already sent to the address are kept.
+pub fn create_program_account<'a>(
+    program_id: &Pubkey,
+    account_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    rent: &Rent,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let lamports = rent.minimum_balance(space);
+    if account_info.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                account_info.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[signer_seeds],
+        );
+    }
+
+    let lamports = lamports.saturating_sub(account_info.lamports());
+    if lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, lamports),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account_info.key, space as u64),
+        &[account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account_info.key, program_id),
+        &[account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )
+}
+
 /// Check if the account has enough lamports to be rent to store state
 pub fn assert_rent_exempt(rent: &Rent, account_info: &AccountInfo) -> ProgramResult {
     if !rent.is_exempt(account_info.lamports(), account_info.data_len()) {
@@ -1155,8 +5478,87 @@ pub fn unpack_mint(
     }
 }
 
+/// Create a wrapped SOL account owned by the wallet, funded with `amount`
+/// lamports of the wallet on top of its rent.
+fn wrap_native_sol<'a>(
+    wallet_info: &AccountInfo<'a>,
+    native_account_info: &AccountInfo<'a>,
+    native_mint_info: &AccountInfo<'a>,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    if !wallet_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+    if *native_mint_info.key != spl_token::native_mint::id() {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if *token_program_info.key != spl_token::id() {
+        return Err(SwapError::IncorrectTokenProgramId.into());
+    }
+    let rent = &Rent::from_account_info(rent_info)?;
+    let lamports = rent
+        .minimum_balance(Account::LEN)
+        .checked_add(amount)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    invoke(
+        &system_instruction::create_account(
+            wallet_info.key,
+            native_account_info.key,
+            lamports,
+            Account::LEN as u64,
+            token_program_info.key,
+        ),
+        &[
+            wallet_info.clone(),
+            native_account_info.clone(),
+            system_program_info.clone(),
+        ],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_account(
+            token_program_info.key,
+            native_account_info.key,
+            native_mint_info.key,
+            wallet_info.key,
+        )?,
+        &[
+            native_account_info.clone(),
+            native_mint_info.clone(),
+            wallet_info.clone(),
+            rent_info.clone(),
+            token_program_info.clone(),
+        ],
+    )
+}
+
+/// Close a wrapped SOL account of the wallet, sending its lamports back to it.
+fn unwrap_native_sol<'a>(
+    wallet_info: &AccountInfo<'a>,
+    native_account_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    invoke(
+        &spl_token::instruction::close_account(
+            token_program_info.key,
+            native_account_info.key,
+            wallet_info.key,
+            wallet_info.key,
+            &[],
+        )?,
+        &[
+            native_account_info.clone(),
+            wallet_info.clone(),
+            token_program_info.clone(),
+        ],
+    )
+}
+
 /// Issue a spl_token `Transfer` instruction.
-fn token_transfer<'a>(
+pub fn token_transfer<'a>(
     swap: &Pubkey,
     token_program: AccountInfo<'a>,
     source: AccountInfo<'a>,
@@ -1210,7 +5612,7 @@ fn token_mint_to<'a>(
 }
 
 /// Issue a spl_token `Burn` instruction.
-fn token_burn<'a>(
+pub fn token_burn<'a>(
     swap: &Pubkey,
     token_program: AccountInfo<'a>,
     burn_account: AccountInfo<'a>,
@@ -6,6 +6,9 @@ use bytemuck::{
 };
 use std::mem::size_of;
 
+// Pyth oracle program, the only owner of the price accounts the pools read
+solana_program::declare_id!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
 pub const MAGIC: u32 = 0xa1b2c3d4;
 pub const VERSION_2: u32 = 2;
 pub const VERSION: u32 = VERSION_2;
@@ -122,9 +125,8 @@ unsafe impl Pod for Product {}
 
 pub fn load<T: Pod>(data: &[u8]) -> Result<&T, PodCastError> {
     let size = size_of::<T>();
-    Ok(from_bytes(cast_slice::<u8, u8>(try_cast_slice(
-        &data[0..size],
-    )?)))
+    let data = data.get(0..size).ok_or(PodCastError::SizeMismatch)?;
+    Ok(from_bytes(cast_slice::<u8, u8>(try_cast_slice(data)?)))
 }
 
 pub fn load_mut<T: Pod>(data: &mut [u8]) -> Result<&mut T, PodCastError> {
@@ -133,3 +135,222 @@ pub fn load_mut<T: Pod>(data: &mut [u8]) -> Result<&mut T, PodCastError> {
         try_cast_slice_mut(&mut data[0..size])?,
     )))
 }
+
+/// Account data of mock oracles, for tests exercising the oracle path
+#[cfg(any(test, feature = "test-sdk"))]
+pub mod test {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+
+    /// Product account data quoted in the currency, its first price account
+    /// being the price key
+    pub fn product_data(price_pubkey: &Pubkey, quote_currency: &str) -> Vec<u8> {
+        // attributes are key/value pairs of length-prefixed strings
+        let key = b"quote_currency";
+        let mut attr = vec![key.len() as u8];
+        attr.extend_from_slice(key);
+        attr.push(quote_currency.len() as u8);
+        attr.extend_from_slice(quote_currency.as_bytes());
+
+        let mut data = vec![0u8; size_of::<Product>()];
+        let product = load_mut::<Product>(&mut data).unwrap();
+        product.magic = MAGIC;
+        product.ver = VERSION;
+        product.atype = AccountType::Product as u32;
+        product.size = (PROD_HDR_SIZE + attr.len()) as u32;
+        product.px_acc.val = price_pubkey.to_bytes();
+        product.attr[..attr.len()].copy_from_slice(&attr);
+        data
+    }
+
+    /// Price account data of the aggregate price, price * 10^expo, valid at the
+    /// slot
+    pub fn price_data(price: i64, expo: i32, valid_slot: u64) -> Vec<u8> {
+        let mut data = vec![0u8; size_of::<Price>()];
+        let pyth_price = load_mut::<Price>(&mut data).unwrap();
+        pyth_price.magic = MAGIC;
+        pyth_price.ver = VERSION;
+        pyth_price.atype = AccountType::Price as u32;
+        pyth_price.size = size_of::<Price>() as u32;
+        pyth_price.ptype = PriceType::Price;
+        pyth_price.expo = expo;
+        pyth_price.curr_slot = valid_slot;
+        pyth_price.valid_slot = valid_slot;
+        pyth_price.agg.price = price;
+        pyth_price.agg.status = PriceStatus::Trading;
+        pyth_price.agg.pub_slot = valid_slot;
+        data
+    }
+
+    /// Price accounts replaying a schedule of aggregate prices, each price
+    /// being published at its slot
+    pub struct PriceSimulator {
+        expo: i32,
+        schedule: Vec<(u64, i64)>,
+    }
+
+    impl PriceSimulator {
+        /// Create a simulator of the (slot, price) points, prices being
+        /// price * 10^expo
+        pub fn new(expo: i32, mut schedule: Vec<(u64, i64)>) -> Self {
+            schedule.sort_by_key(|(slot, _)| *slot);
+            Self { expo, schedule }
+        }
+
+        /// Price account data seen at the slot, valid at the slot of the last
+        /// price published by then. None before the first price.
+        pub fn price_data_at(&self, slot: u64) -> Option<Vec<u8>> {
+            self.schedule
+                .iter()
+                .rev()
+                .find(|(valid_slot, _)| *valid_slot <= slot)
+                .map(|(valid_slot, price)| price_data(*price, self.expo, *valid_slot))
+        }
+
+        /// Price account data of each step of the schedule, along with its slot
+        pub fn steps(&self) -> impl Iterator<Item = (u64, Vec<u8>)> + '_ {
+            self.schedule
+                .iter()
+                .map(move |(slot, price)| (*slot, price_data(*price, self.expo, *slot)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{test::*, *};
+    use crate::{
+        error::SwapError,
+        math::{Decimal, TryDiv},
+        processor::get_market_price_from_pyth,
+    };
+    use solana_program::{
+        account_info::AccountInfo, clock::Clock, program_error::ProgramError, pubkey::Pubkey,
+    };
+
+    fn market_price(data_a: Vec<u8>, data_b: Vec<u8>, slot: u64) -> Result<Decimal, ProgramError> {
+        market_price_owned_by(id(), data_a, data_b, slot)
+    }
+
+    fn market_price_owned_by(
+        owner: Pubkey,
+        mut data_a: Vec<u8>,
+        mut data_b: Vec<u8>,
+        slot: u64,
+    ) -> Result<Decimal, ProgramError> {
+        let (key_a, key_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut lamports_a, mut lamports_b) = (0, 0);
+        let price_a = AccountInfo::new(
+            &key_a,
+            false,
+            false,
+            &mut lamports_a,
+            &mut data_a,
+            &owner,
+            false,
+            0,
+        );
+        let price_b = AccountInfo::new(
+            &key_b,
+            false,
+            false,
+            &mut lamports_b,
+            &mut data_b,
+            &owner,
+            false,
+            0,
+        );
+        let clock = Clock {
+            slot,
+            ..Clock::default()
+        };
+        get_market_price_from_pyth(&price_a, &price_b, &clock)
+    }
+
+    #[test]
+    fn test_mock_accounts() {
+        let price_pubkey = Pubkey::new_unique();
+        let data = product_data(&price_pubkey, "USD");
+        let product = load::<Product>(&data).unwrap();
+        assert_eq!(product.magic, MAGIC);
+        assert_eq!(product.atype, AccountType::Product as u32);
+        assert_eq!(product.px_acc.val, price_pubkey.to_bytes());
+        assert_eq!(&product.attr[..19], b"\x0equote_currency\x03USD");
+
+        assert_eq!(
+            market_price(price_data(150_000_000, -6, 10), price_data(7, 0, 8), 12).unwrap(),
+            Decimal::from(150u64).try_div(7).unwrap()
+        );
+
+        // the price of b is stale 5 slots after its valid slot
+        assert_eq!(
+            market_price(price_data(150_000_000, -6, 10), price_data(7, 0, 8), 13).unwrap_err(),
+            ProgramError::from(SwapError::InvalidOracleConfig)
+        );
+
+        // as well as accounts of the program other than price accounts
+        assert_eq!(
+            market_price(
+                product_data(&Pubkey::new_unique(), "USD"),
+                price_data(7, 0, 8),
+                12
+            )
+            .unwrap_err(),
+            ProgramError::from(SwapError::IncorrectOracleAccount)
+        );
+
+        // price accounts of any other program are rejected
+        assert_eq!(
+            market_price_owned_by(
+                Pubkey::new_unique(),
+                price_data(150_000_000, -6, 10),
+                price_data(7, 0, 8),
+                12
+            )
+            .unwrap_err(),
+            ProgramError::from(SwapError::IncorrectOracleAccount)
+        );
+    }
+
+    #[test]
+    fn test_price_simulator() {
+        let sol = PriceSimulator::new(-2, vec![(20, 14_000), (10, 15_000), (12, 16_000)]);
+        let srm = PriceSimulator::new(0, vec![(10, 7), (16, 7), (20, 7)]);
+
+        let steps: Vec<u64> = sol.steps().map(|(slot, _)| slot).collect();
+        assert_eq!(steps, vec![10, 12, 20]);
+        for (slot, data) in sol.steps() {
+            assert_eq!(load::<Price>(&data).unwrap().valid_slot, slot);
+        }
+        assert!(sol.price_data_at(9).is_none());
+
+        let price = |slot| {
+            market_price(
+                sol.price_data_at(slot).unwrap(),
+                srm.price_data_at(slot).unwrap(),
+                slot,
+            )
+        };
+        assert_eq!(
+            price(11).unwrap(),
+            Decimal::from(150u64).try_div(7).unwrap()
+        );
+        assert_eq!(
+            price(12).unwrap(),
+            Decimal::from(160u64).try_div(7).unwrap()
+        );
+        assert_eq!(
+            price(16).unwrap(),
+            Decimal::from(160u64).try_div(7).unwrap()
+        );
+        assert_eq!(price(20).unwrap(), Decimal::from(20u64));
+
+        // either price going 5 slots without update is stale
+        for slot in [15, 17, 25] {
+            assert_eq!(
+                price(slot).unwrap_err(),
+                ProgramError::from(SwapError::InvalidOracleConfig)
+            );
+        }
+    }
+}
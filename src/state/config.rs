@@ -1,14 +1,21 @@
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::{
+    clock::{Epoch, UnixTimestamp},
+    entrypoint::ProgramResult,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::{Pubkey, PUBKEY_BYTES},
 };
 
 use super::*;
+use crate::{error::SwapError, math::BPS_DENOMINATOR};
 
 /// Current version of the program and all new accounts created
-pub const PROGRAM_VERSION: u8 = 1;
+pub const PROGRAM_VERSION: u8 = 2;
+
+/// Version of the accounts created by the first deployment of the program,
+/// upgraded to the current layout with `MigrateState`
+pub const PROGRAM_VERSION_1: u8 = 1;
 
 /// Accounts are created with data zeroed out, so uninitialized state instances
 /// will have the version set to 0.
@@ -17,6 +24,7 @@ pub const UNINITIALIZED_VERSION: u8 = 0;
 /// Dex Default Configuration information
 #[repr(C)]
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ConfigInfo {
     /// Version of DELTAFI
     pub version: u8,
@@ -35,6 +43,37 @@ pub struct ConfigInfo {
     pub fees: Fees,
     /// Rewards
     pub rewards: Rewards,
+
+    /// Max change of the mid price per admin update, in basis points.
+    /// Zero in configs migrated from version 1, which freezes the mid price
+    /// until the admin sets the bounds with `SetMidPriceBounds`
+    pub max_mid_price_deviation_bps: u64,
+    /// Min seconds between two admin mid price updates of a pool
+    pub min_mid_price_update_interval: UnixTimestamp,
+    /// Pyth price account of the DELFI token the liquidity rewards are valued
+    /// at, default to keep the fixed price
+    pub deltafi_price_oracle: Pubkey,
+    /// Max rewards minted in an epoch, zero for no limit
+    pub epoch_reward_budget: u64,
+    /// Epoch of the rewards minted
+    pub reward_epoch: Epoch,
+    /// Rewards minted in `reward_epoch`
+    pub epoch_rewards_minted: u64,
+    /// DELFI minted to the keeper of a `Poke`, zero for no incentive
+    pub poke_reward: u64,
+    /// Min seconds since the last update of a pool for a `Poke` to be rewarded
+    pub min_poke_interval: UnixTimestamp,
+    /// Gauge votes cast for all the pools
+    pub gauge_votes: GaugeVotes,
+    /// Min votes for a proposal to pass, zero to disable the governance
+    pub proposal_quorum: u64,
+    /// Proposals created, the index of the next proposal
+    pub proposal_count: u64,
+    /// Trade fee discount tiers of the traders holding DELFI
+    pub fee_discount: FeeDiscount,
+    /// Slice of the liquidity rewards claimed additionally minted to the
+    /// referrer of the liquidity provider, in basis points
+    pub referral_reward_bps: u64,
 }
 
 impl Sealed for ConfigInfo {}
@@ -45,21 +84,128 @@ impl IsInitialized for ConfigInfo {
 }
 
 #[doc(hidden)]
-pub const CONFIG_INFO_SIZE: usize = 170;
+pub const CONFIG_INFO_SIZE: usize = 354;
+/// Config size of version 1 accounts
+pub const CONFIG_INFO_SIZE_V1: usize = 170;
+
+impl ConfigInfo {
+    /// Unpacks a config stored in the layout of its version byte.
+    /// Fields added after that version are zeroed.
+    pub fn unpack_versioned(src: &[u8]) -> Result<Self, ProgramError> {
+        let len = match src.first() {
+            Some(&PROGRAM_VERSION_1) => CONFIG_INFO_SIZE_V1,
+            Some(&PROGRAM_VERSION) => CONFIG_INFO_SIZE,
+            Some(&UNINITIALIZED_VERSION) => return Err(ProgramError::UninitializedAccount),
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        if src.len() != len {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut packed = [0u8; CONFIG_INFO_SIZE];
+        packed[..len].copy_from_slice(src);
+        Self::unpack_from_slice(&packed)
+    }
+
+    /// Rewards left to mint in an epoch, `u64::MAX` without budget
+    pub fn epoch_rewards_left(&self, epoch: Epoch) -> u64 {
+        if self.epoch_reward_budget == 0 {
+            return u64::MAX;
+        }
+        let minted = if epoch == self.reward_epoch {
+            self.epoch_rewards_minted
+        } else {
+            0
+        };
+        self.epoch_reward_budget.saturating_sub(minted)
+    }
+
+    /// Count rewards minted in an epoch against the epoch budget
+    ///
+    /// # Arguments
+    ///
+    /// * epoch - current epoch.
+    /// * amount - rewards minted.
+    ///
+    /// # Return value
+    ///
+    /// mint status, failing above the rewards left in the epoch
+    pub fn record_rewards_minted(&mut self, epoch: Epoch, amount: u64) -> ProgramResult {
+        if amount > self.epoch_rewards_left(epoch) {
+            return Err(SwapError::EpochRewardBudgetExceeded.into());
+        }
+        if epoch != self.reward_epoch {
+            self.reward_epoch = epoch;
+            self.epoch_rewards_minted = 0;
+        }
+        self.epoch_rewards_minted = self
+            .epoch_rewards_minted
+            .checked_add(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Referral reward minted to the referrer on top of a liquidity reward claim
+    ///
+    /// # Arguments
+    ///
+    /// * reward_amount - rewards claimed by the referred liquidity provider.
+    ///
+    /// # Return value
+    ///
+    /// referral reward.
+    pub fn referral_reward(&self, reward_amount: u64) -> u64 {
+        (reward_amount as u128 * self.referral_reward_bps.min(BPS_DENOMINATOR) as u128
+            / BPS_DENOMINATOR as u128) as u64
+    }
+}
+
 impl Pack for ConfigInfo {
     const LEN: usize = CONFIG_INFO_SIZE;
     #[doc(hidden)]
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let src = array_ref![src, 0, CONFIG_INFO_SIZE];
         #[allow(clippy::ptr_offset_with_cast)]
-        let (version, bump_seed, admin_key, deltafi_mint, fees, rewards) = array_refs![
+        let (
+            version,
+            bump_seed,
+            admin_key,
+            deltafi_mint,
+            fees,
+            rewards,
+            max_mid_price_deviation_bps,
+            min_mid_price_update_interval,
+            deltafi_price_oracle,
+            epoch_reward_budget,
+            reward_epoch,
+            epoch_rewards_minted,
+            poke_reward,
+            min_poke_interval,
+            gauge_votes,
+            proposal_quorum,
+            proposal_count,
+            fee_discount,
+            referral_reward_bps,
+        ) = array_refs![
             src,
             1,
             1,
             PUBKEY_BYTES,
             PUBKEY_BYTES,
             Fees::LEN,
-            Rewards::LEN
+            Rewards::LEN,
+            8,
+            8,
+            PUBKEY_BYTES,
+            8,
+            8,
+            8,
+            8,
+            8,
+            GaugeVotes::LEN,
+            8,
+            8,
+            FeeDiscount::LEN,
+            8
         ];
 
         let version = u8::from_le_bytes(*version);
@@ -74,20 +220,66 @@ impl Pack for ConfigInfo {
             deltafi_mint: Pubkey::new_from_array(*deltafi_mint),
             fees: Fees::unpack_from_slice(fees)?,
             rewards: Rewards::unpack_from_slice(rewards)?,
+            max_mid_price_deviation_bps: u64::from_le_bytes(*max_mid_price_deviation_bps),
+            min_mid_price_update_interval: i64::from_le_bytes(*min_mid_price_update_interval),
+            deltafi_price_oracle: Pubkey::new_from_array(*deltafi_price_oracle),
+            epoch_reward_budget: u64::from_le_bytes(*epoch_reward_budget),
+            reward_epoch: u64::from_le_bytes(*reward_epoch),
+            epoch_rewards_minted: u64::from_le_bytes(*epoch_rewards_minted),
+            poke_reward: u64::from_le_bytes(*poke_reward),
+            min_poke_interval: i64::from_le_bytes(*min_poke_interval),
+            gauge_votes: GaugeVotes::unpack_from_slice(gauge_votes)?,
+            proposal_quorum: u64::from_le_bytes(*proposal_quorum),
+            proposal_count: u64::from_le_bytes(*proposal_count),
+            fee_discount: FeeDiscount::unpack_from_slice(fee_discount)?,
+            referral_reward_bps: u64::from_le_bytes(*referral_reward_bps),
         })
     }
     #[doc(hidden)]
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, CONFIG_INFO_SIZE];
         #[allow(clippy::ptr_offset_with_cast)]
-        let (version, bump_seed, admin_key, deltafi_mint, fees, rewards) = mut_array_refs![
+        let (
+            version,
+            bump_seed,
+            admin_key,
+            deltafi_mint,
+            fees,
+            rewards,
+            max_mid_price_deviation_bps,
+            min_mid_price_update_interval,
+            deltafi_price_oracle,
+            epoch_reward_budget,
+            reward_epoch,
+            epoch_rewards_minted,
+            poke_reward,
+            min_poke_interval,
+            gauge_votes,
+            proposal_quorum,
+            proposal_count,
+            fee_discount,
+            referral_reward_bps,
+        ) = mut_array_refs![
             dst,
             1,
             1,
             PUBKEY_BYTES,
             PUBKEY_BYTES,
             Fees::LEN,
-            Rewards::LEN
+            Rewards::LEN,
+            8,
+            8,
+            PUBKEY_BYTES,
+            8,
+            8,
+            8,
+            8,
+            8,
+            GaugeVotes::LEN,
+            8,
+            8,
+            FeeDiscount::LEN,
+            8
         ];
         *version = self.version.to_le_bytes();
         *bump_seed = self.bump_seed.to_le_bytes();
@@ -95,6 +287,19 @@ impl Pack for ConfigInfo {
         deltafi_mint.copy_from_slice(self.deltafi_mint.as_ref());
         self.fees.pack_into_slice(&mut fees[..]);
         self.rewards.pack_into_slice(&mut rewards[..]);
+        *max_mid_price_deviation_bps = self.max_mid_price_deviation_bps.to_le_bytes();
+        *min_mid_price_update_interval = self.min_mid_price_update_interval.to_le_bytes();
+        deltafi_price_oracle.copy_from_slice(self.deltafi_price_oracle.as_ref());
+        *epoch_reward_budget = self.epoch_reward_budget.to_le_bytes();
+        *reward_epoch = self.reward_epoch.to_le_bytes();
+        *epoch_rewards_minted = self.epoch_rewards_minted.to_le_bytes();
+        *poke_reward = self.poke_reward.to_le_bytes();
+        *min_poke_interval = self.min_poke_interval.to_le_bytes();
+        self.gauge_votes.pack_into_slice(&mut gauge_votes[..]);
+        *proposal_quorum = self.proposal_quorum.to_le_bytes();
+        *proposal_count = self.proposal_count.to_le_bytes();
+        self.fee_discount.pack_into_slice(&mut fee_discount[..]);
+        *referral_reward_bps = self.referral_reward_bps.to_le_bytes();
     }
 }
 
@@ -112,6 +317,36 @@ mod tests {
         let deltafi_mint = Pubkey::new_from_array(deltafi_mint_raw);
         let fees = DEFAULT_TEST_FEES;
         let rewards = DEFAULT_TEST_REWARDS;
+        let max_mid_price_deviation_bps = 500;
+        let min_mid_price_update_interval = 3600;
+        let deltafi_price_oracle_raw = [4u8; 32];
+        let deltafi_price_oracle = Pubkey::new_from_array(deltafi_price_oracle_raw);
+        let epoch_reward_budget = 1_000_000;
+        let reward_epoch = 42;
+        let epoch_rewards_minted = 300_000;
+        let poke_reward = 1_000;
+        let min_poke_interval = 600;
+        let gauge_votes = GaugeVotes {
+            epoch: 42,
+            votes: 5_000,
+            previous_votes: 8_000,
+        };
+        let proposal_quorum = 10_000;
+        let proposal_count = 3;
+        let fee_discount = FeeDiscount {
+            tiers: [
+                FeeDiscountTier {
+                    min_balance: 1_000,
+                    discount_bps: 1_000,
+                },
+                FeeDiscountTier {
+                    min_balance: 10_000,
+                    discount_bps: 2_500,
+                },
+                FeeDiscountTier::default(),
+            ],
+        };
+        let referral_reward_bps = 1_000;
 
         let config_info = ConfigInfo {
             version,
@@ -120,6 +355,19 @@ mod tests {
             deltafi_mint,
             fees,
             rewards,
+            max_mid_price_deviation_bps,
+            min_mid_price_update_interval,
+            deltafi_price_oracle,
+            epoch_reward_budget,
+            reward_epoch,
+            epoch_rewards_minted,
+            poke_reward,
+            min_poke_interval,
+            gauge_votes,
+            proposal_quorum,
+            proposal_count,
+            fee_discount,
+            referral_reward_bps,
         };
 
         let mut packed = [0u8; ConfigInfo::LEN];
@@ -156,6 +404,24 @@ mod tests {
                 .liquidity_reward_denominator
                 .to_le_bytes(),
         );
+        packed.extend_from_slice(&max_mid_price_deviation_bps.to_le_bytes());
+        packed.extend_from_slice(&min_mid_price_update_interval.to_le_bytes());
+        packed.extend_from_slice(&deltafi_price_oracle_raw);
+        packed.extend_from_slice(&epoch_reward_budget.to_le_bytes());
+        packed.extend_from_slice(&reward_epoch.to_le_bytes());
+        packed.extend_from_slice(&epoch_rewards_minted.to_le_bytes());
+        packed.extend_from_slice(&poke_reward.to_le_bytes());
+        packed.extend_from_slice(&min_poke_interval.to_le_bytes());
+        packed.extend_from_slice(&gauge_votes.epoch.to_le_bytes());
+        packed.extend_from_slice(&gauge_votes.votes.to_le_bytes());
+        packed.extend_from_slice(&gauge_votes.previous_votes.to_le_bytes());
+        packed.extend_from_slice(&proposal_quorum.to_le_bytes());
+        packed.extend_from_slice(&proposal_count.to_le_bytes());
+        for tier in fee_discount.tiers.iter() {
+            packed.extend_from_slice(&tier.min_balance.to_le_bytes());
+            packed.extend_from_slice(&tier.discount_bps.to_le_bytes());
+        }
+        packed.extend_from_slice(&referral_reward_bps.to_le_bytes());
         let unpacked = ConfigInfo::unpack(&packed).unwrap();
         assert_eq!(config_info, unpacked);
 
@@ -166,4 +432,118 @@ mod tests {
         let err = ConfigInfo::unpack(&packed).unwrap_err();
         assert_eq!(err, ProgramError::UninitializedAccount);
     }
+
+    #[test]
+    fn test_config_info_unpack_versioned() {
+        let config_info = ConfigInfo {
+            version: PROGRAM_VERSION,
+            bump_seed: 255,
+            admin_key: Pubkey::new_unique(),
+            deltafi_mint: Pubkey::new_unique(),
+            fees: DEFAULT_TEST_FEES,
+            rewards: DEFAULT_TEST_REWARDS,
+            max_mid_price_deviation_bps: 500,
+            min_mid_price_update_interval: 3600,
+            deltafi_price_oracle: Pubkey::new_unique(),
+            epoch_reward_budget: 1_000_000,
+            reward_epoch: 42,
+            epoch_rewards_minted: 300_000,
+            poke_reward: 1_000,
+            min_poke_interval: 600,
+            gauge_votes: GaugeVotes {
+                epoch: 42,
+                votes: 5_000,
+                previous_votes: 8_000,
+            },
+            proposal_quorum: 10_000,
+            proposal_count: 3,
+            fee_discount: FeeDiscount {
+                tiers: [
+                    FeeDiscountTier {
+                        min_balance: 1_000,
+                        discount_bps: 1_000,
+                    },
+                    FeeDiscountTier::default(),
+                    FeeDiscountTier::default(),
+                ],
+            },
+            referral_reward_bps: 1_000,
+        };
+        let mut packed = [0u8; ConfigInfo::LEN];
+        config_info.pack_into_slice(&mut packed);
+        assert_eq!(ConfigInfo::unpack_versioned(&packed).unwrap(), config_info);
+
+        // version 1 layout stops before the mid price bounds
+        packed[0] = PROGRAM_VERSION_1;
+        assert_eq!(
+            ConfigInfo::unpack_versioned(&packed[..CONFIG_INFO_SIZE_V1]).unwrap(),
+            ConfigInfo {
+                version: PROGRAM_VERSION_1,
+                max_mid_price_deviation_bps: 0,
+                min_mid_price_update_interval: 0,
+                deltafi_price_oracle: Pubkey::default(),
+                epoch_reward_budget: 0,
+                reward_epoch: 0,
+                epoch_rewards_minted: 0,
+                poke_reward: 0,
+                min_poke_interval: 0,
+                gauge_votes: GaugeVotes::default(),
+                proposal_quorum: 0,
+                proposal_count: 0,
+                fee_discount: FeeDiscount::default(),
+                referral_reward_bps: 0,
+                ..config_info
+            }
+        );
+        assert_eq!(
+            ConfigInfo::unpack_versioned(&packed).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+        assert_eq!(
+            ConfigInfo::unpack_versioned(&[0u8; CONFIG_INFO_SIZE_V1]).unwrap_err(),
+            ProgramError::UninitializedAccount
+        );
+    }
+
+    #[test]
+    fn test_epoch_reward_budget() {
+        let mut config_info = ConfigInfo::default();
+        // no budget, no limit
+        assert_eq!(config_info.epoch_rewards_left(1), u64::MAX);
+
+        config_info.epoch_reward_budget = 1_000;
+        config_info.record_rewards_minted(1, 600).unwrap();
+        assert_eq!(config_info.epoch_rewards_left(1), 400);
+        assert_eq!(
+            config_info.record_rewards_minted(1, 401),
+            Err(SwapError::EpochRewardBudgetExceeded.into())
+        );
+        config_info.record_rewards_minted(1, 400).unwrap();
+        assert_eq!(config_info.epoch_rewards_left(1), 0);
+
+        // a new epoch starts from the full budget
+        assert_eq!(config_info.epoch_rewards_left(2), 1_000);
+        config_info.record_rewards_minted(2, 100).unwrap();
+        assert_eq!(config_info.reward_epoch, 2);
+        assert_eq!(config_info.epoch_rewards_minted, 100);
+
+        // lowering the budget below the minted rewards stops the mints
+        config_info.epoch_reward_budget = 50;
+        assert_eq!(config_info.epoch_rewards_left(2), 0);
+    }
+
+    #[test]
+    fn test_referral_reward() {
+        let mut config_info = ConfigInfo::default();
+        assert_eq!(config_info.referral_reward(1_000), 0);
+
+        config_info.referral_reward_bps = 1_000;
+        assert_eq!(config_info.referral_reward(1_000), 100);
+        assert_eq!(config_info.referral_reward(9), 0);
+        assert_eq!(config_info.referral_reward(u64::MAX), u64::MAX / 10);
+
+        // never more than the claimed rewards
+        config_info.referral_reward_bps = 20_000;
+        assert_eq!(config_info.referral_reward(1_000), 1_000);
+    }
 }
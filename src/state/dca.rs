@@ -0,0 +1,283 @@
+use bytemuck::{try_from_bytes, try_from_bytes_mut, Pod, Zeroable};
+use solana_program::{clock::UnixTimestamp, program_error::ProgramError, pubkey::Pubkey};
+
+use super::*;
+use crate::{
+    error::SwapError,
+    instruction::{CreateDcaPlanData, SwapDirection},
+    math::BPS_DENOMINATOR,
+};
+
+/// Seed of the DCA plan addresses, derived with the token-swap and the owner
+pub const DCA_PLAN_SEED: &[u8] = b"dca_plan";
+
+/// DCA plan size
+pub const DCA_PLAN_SIZE: usize = 147; // 1 + 1 + 1 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8
+
+/// Max share of each period amount a plan pays to the keeper executing it, in
+/// bps
+pub const MAX_DCA_KEEPER_REWARD_BPS: u64 = 100;
+
+/// Find the address of the DCA plan of the owner in a token-swap
+pub fn find_dca_plan_address(program_id: &Pubkey, swap: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[swap.as_ref(), owner.as_ref(), DCA_PLAN_SEED], program_id)
+}
+
+/// Swaps of an escrowed amount split in periods, stored in a program address
+/// derived from `[swap, owner, "dca_plan"]`. The plan owns the escrow token
+/// account, the associated token account of the plan for the mint sold, and
+/// any keeper executes the periods as they come due.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct DcaPlan {
+    is_initialized: [u8; 1],
+    /// Bump seed of the plan address
+    pub bump_seed: u8,
+    swap_direction: [u8; 1],
+    /// Token-swap the periods are swapped in
+    pub swap: Pubkey,
+    /// Owner of the plan, receiving the tokens bought
+    pub owner: Pubkey,
+    /// Escrow token account of the amount left to sell
+    pub escrow: Pubkey,
+    remaining_amount: [u8; 8],
+    amount_per_period: [u8; 8],
+    interval: [u8; 8],
+    next_execution_ts: [u8; 8],
+    max_price_impact_bps: [u8; 8],
+    keeper_reward_bps: [u8; 8],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for DcaPlan {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for DcaPlan {}
+
+impl DcaPlan {
+    /// Initialize a DCA plan in freshly allocated account data, its first
+    /// period due right away
+    pub fn init(
+        data: &mut [u8],
+        bump_seed: u8,
+        swap: Pubkey,
+        owner: Pubkey,
+        escrow: Pubkey,
+        plan_data: &CreateDcaPlanData,
+        now: UnixTimestamp,
+    ) -> Result<(), ProgramError> {
+        if plan_data.amount == 0
+            || plan_data.amount_per_period == 0
+            || plan_data.interval <= 0
+            || plan_data.max_price_impact_bps == 0
+            || plan_data.keeper_reward_bps > MAX_DCA_KEEPER_REWARD_BPS
+        {
+            return Err(SwapError::InvalidInput.into());
+        }
+        let plan: &mut Self = data
+            .get_mut(..DCA_PLAN_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if plan.is_initialized()? {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        pack_bool(true, &mut plan.is_initialized);
+        plan.bump_seed = bump_seed;
+        plan.swap_direction = [plan_data.swap_direction as u8];
+        plan.swap = swap;
+        plan.owner = owner;
+        plan.escrow = escrow;
+        plan.remaining_amount = plan_data.amount.to_le_bytes();
+        plan.amount_per_period = plan_data.amount_per_period.to_le_bytes();
+        plan.interval = plan_data.interval.to_le_bytes();
+        plan.next_execution_ts = now.to_le_bytes();
+        plan.max_price_impact_bps = plan_data.max_price_impact_bps.to_le_bytes();
+        plan.keeper_reward_bps = plan_data.keeper_reward_bps.to_le_bytes();
+        Ok(())
+    }
+
+    /// Borrow an initialized DCA plan
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let plan: &Self = data
+            .get(..DCA_PLAN_SIZE)
+            .and_then(|data| try_from_bytes(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !plan.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(plan)
+    }
+
+    /// Mutably borrow an initialized DCA plan
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let plan: &mut Self = data
+            .get_mut(..DCA_PLAN_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !plan.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(plan)
+    }
+
+    /// Initialized state
+    pub fn is_initialized(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_initialized)
+    }
+
+    /// Swap direction of the periods
+    pub fn swap_direction(&self) -> Result<SwapDirection, ProgramError> {
+        match self.swap_direction {
+            [0] => Ok(SwapDirection::SellBase),
+            [1] => Ok(SwapDirection::SellQuote),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    /// Amount escrowed left to sell
+    pub fn remaining_amount(&self) -> u64 {
+        u64::from_le_bytes(self.remaining_amount)
+    }
+
+    /// Amount sold per period
+    pub fn amount_per_period(&self) -> u64 {
+        u64::from_le_bytes(self.amount_per_period)
+    }
+
+    /// Seconds between two periods
+    pub fn interval(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.interval)
+    }
+
+    /// Timestamp the next period is due at
+    pub fn next_execution_ts(&self) -> UnixTimestamp {
+        UnixTimestamp::from_le_bytes(self.next_execution_ts)
+    }
+
+    /// Limit of the price impact of each period swap
+    pub fn max_price_impact_bps(&self) -> u64 {
+        u64::from_le_bytes(self.max_price_impact_bps)
+    }
+
+    /// Share of each period amount paid to the keeper executing it, in bps
+    pub fn keeper_reward_bps(&self) -> u64 {
+        u64::from_le_bytes(self.keeper_reward_bps)
+    }
+
+    /// Execute the period due, returning the amount swapped and the keeper
+    /// reward taken out of the period amount. The next period is due an
+    /// interval after this execution.
+    pub fn execute_period(&mut self, now: UnixTimestamp) -> Result<(u64, u64), ProgramError> {
+        let remaining_amount = self.remaining_amount();
+        if remaining_amount == 0 {
+            return Err(SwapError::DcaPlanCompleted.into());
+        }
+        if now < self.next_execution_ts() {
+            return Err(SwapError::DcaNotDue.into());
+        }
+
+        let period_amount = self.amount_per_period().min(remaining_amount);
+        let keeper_reward =
+            period_amount as u128 * self.keeper_reward_bps() as u128 / BPS_DENOMINATOR as u128;
+        let keeper_reward = keeper_reward as u64;
+        self.remaining_amount = (remaining_amount - period_amount).to_le_bytes();
+        self.next_execution_ts = now
+            .checked_add(self.interval())
+            .ok_or(SwapError::CalculationFailure)?
+            .to_le_bytes();
+        Ok((period_amount - keeper_reward, keeper_reward))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dca_plan() {
+        assert_eq!(std::mem::size_of::<DcaPlan>(), DCA_PLAN_SIZE);
+
+        let (swap, owner, escrow) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let plan_data = CreateDcaPlanData {
+            swap_direction: SwapDirection::SellQuote,
+            amount: 25_000,
+            amount_per_period: 10_000,
+            interval: 3_600,
+            max_price_impact_bps: 50,
+            keeper_reward_bps: 10,
+        };
+        let mut data = vec![0u8; DCA_PLAN_SIZE];
+        assert_eq!(
+            DcaPlan::load(&data).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+        for invalid in [
+            CreateDcaPlanData {
+                amount: 0,
+                ..plan_data.clone()
+            },
+            CreateDcaPlanData {
+                amount_per_period: 0,
+                ..plan_data.clone()
+            },
+            CreateDcaPlanData {
+                interval: 0,
+                ..plan_data.clone()
+            },
+            CreateDcaPlanData {
+                max_price_impact_bps: 0,
+                ..plan_data.clone()
+            },
+            CreateDcaPlanData {
+                keeper_reward_bps: MAX_DCA_KEEPER_REWARD_BPS + 1,
+                ..plan_data.clone()
+            },
+        ] {
+            assert_eq!(
+                DcaPlan::init(&mut data, 255, swap, owner, escrow, &invalid, 1_000).err(),
+                Some(SwapError::InvalidInput.into())
+            );
+        }
+
+        DcaPlan::init(&mut data, 255, swap, owner, escrow, &plan_data, 1_000).unwrap();
+        let plan = DcaPlan::load_mut(&mut data).unwrap();
+        assert_eq!(plan.swap_direction().unwrap(), SwapDirection::SellQuote);
+        assert_eq!(plan.next_execution_ts(), 1_000);
+
+        // the first period is due right away, 10 bps of it to the keeper
+        assert_eq!(plan.execute_period(1_000).unwrap(), (9_990, 10));
+        assert_eq!(plan.remaining_amount(), 15_000);
+        assert_eq!(plan.next_execution_ts(), 4_600);
+        assert_eq!(
+            plan.execute_period(4_599).err(),
+            Some(SwapError::DcaNotDue.into())
+        );
+        // periods executed late are not caught up
+        assert_eq!(plan.execute_period(9_000).unwrap(), (9_990, 10));
+        assert_eq!(plan.next_execution_ts(), 12_600);
+        // the last one sells what is left
+        assert_eq!(plan.execute_period(12_600).unwrap(), (4_995, 5));
+        assert_eq!(plan.remaining_amount(), 0);
+        assert_eq!(
+            plan.execute_period(20_000).err(),
+            Some(SwapError::DcaPlanCompleted.into())
+        );
+
+        let plan = DcaPlan::load(&data).unwrap();
+        assert_eq!(plan.swap, swap);
+        assert_eq!(plan.owner, owner);
+        assert_eq!(plan.escrow, escrow);
+        assert_eq!(plan.amount_per_period(), 10_000);
+        assert_eq!(plan.interval(), 3_600);
+        assert_eq!(plan.max_price_impact_bps(), 50);
+        assert_eq!(plan.keeper_reward_bps(), 10);
+        assert_eq!(
+            DcaPlan::init(&mut data, 255, swap, owner, escrow, &plan_data, 1_000).err(),
+            Some(SwapError::AlreadyInUse.into())
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use bytemuck::{try_from_bytes, try_from_bytes_mut, Pod, Zeroable};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use super::*;
+use crate::error::SwapError;
+
+/// Max number of mints on a deny-list
+pub const MAX_DENIED_MINTS: usize = 64;
+/// Seed of the deny-list addresses, derived with the config key
+pub const DENY_LIST_SEED: &[u8] = b"deny_list";
+
+/// Deny-list size
+pub const DENY_LIST_SIZE: usize = 36 + PUBKEY_BYTES * MAX_DENIED_MINTS; // 1 + 1 + 32 + 2 + (32 * 64)
+
+/// Find the address of the deny-list of a config
+pub fn find_deny_list_address(program_id: &Pubkey, config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[config.as_ref(), DENY_LIST_SEED], program_id)
+}
+
+/// Token mints no pool of a config can be created for, stored in a program
+/// address derived from `[config, "deny_list"]`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct DenyList {
+    is_initialized: [u8; 1],
+    /// Bump seed of the deny-list address
+    pub bump_seed: u8,
+    /// Config the deny-list applies to
+    pub config: Pubkey,
+    len: [u8; 2],
+    mints: [Pubkey; MAX_DENIED_MINTS],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for DenyList {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for DenyList {}
+
+impl DenyList {
+    /// Initialize a deny-list in freshly allocated account data
+    ///
+    /// # Arguments
+    ///
+    /// * data - account data, all zero.
+    /// * config - config the deny-list applies to.
+    /// * bump_seed - bump seed of the deny-list address.
+    pub fn init(data: &mut [u8], config: Pubkey, bump_seed: u8) -> Result<&mut Self, ProgramError> {
+        let deny_list: &mut Self = data
+            .get_mut(..DENY_LIST_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if deny_list.is_initialized()? {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        pack_bool(true, &mut deny_list.is_initialized);
+        deny_list.bump_seed = bump_seed;
+        deny_list.config = config;
+        Ok(deny_list)
+    }
+
+    /// Borrow an initialized deny-list
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let deny_list: &Self = data
+            .get(..DENY_LIST_SIZE)
+            .and_then(|data| try_from_bytes(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !deny_list.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(deny_list)
+    }
+
+    /// Mutably borrow an initialized deny-list
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let deny_list: &mut Self = data
+            .get_mut(..DENY_LIST_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !deny_list.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(deny_list)
+    }
+
+    /// Initialized state
+    pub fn is_initialized(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_initialized)
+    }
+
+    /// Mints on the deny-list
+    pub fn mints(&self) -> &[Pubkey] {
+        let len = (u16::from_le_bytes(self.len) as usize).min(MAX_DENIED_MINTS);
+        &self.mints[..len]
+    }
+
+    /// Whether a mint is on the deny-list
+    pub fn contains(&self, mint: &Pubkey) -> bool {
+        self.mints().contains(mint)
+    }
+
+    /// Add a mint, nothing changes if the mint is already on the deny-list
+    pub fn add(&mut self, mint: Pubkey) -> Result<(), ProgramError> {
+        if self.contains(&mint) {
+            return Ok(());
+        }
+        let len = self.mints().len();
+        if len == MAX_DENIED_MINTS {
+            return Err(SwapError::DenyListFull.into());
+        }
+        self.mints[len] = mint;
+        self.len = ((len + 1) as u16).to_le_bytes();
+        Ok(())
+    }
+
+    /// Remove a mint, moving the last mint into its place
+    pub fn remove(&mut self, mint: &Pubkey) {
+        if let Some(index) = self.mints().iter().position(|key| key == mint) {
+            let last = self.mints().len() - 1;
+            self.mints[index] = self.mints[last];
+            self.mints[last] = Pubkey::default();
+            self.len = (last as u16).to_le_bytes();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deny_list_size() {
+        assert_eq!(std::mem::size_of::<DenyList>(), DENY_LIST_SIZE);
+    }
+
+    #[test]
+    fn test_add_remove() {
+        let mut data = vec![0u8; DENY_LIST_SIZE];
+        let config = Pubkey::new_unique();
+        let deny_list = DenyList::init(&mut data, config, 255).unwrap();
+        let mints: Vec<Pubkey> = (0..MAX_DENIED_MINTS)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+
+        for mint in mints.iter() {
+            deny_list.add(*mint).unwrap();
+        }
+        // already added
+        deny_list.add(mints[0]).unwrap();
+        assert_eq!(deny_list.mints(), &mints[..]);
+        assert_eq!(
+            deny_list.add(Pubkey::new_unique()).unwrap_err(),
+            SwapError::DenyListFull.into()
+        );
+
+        deny_list.remove(&mints[1]);
+        assert!(!deny_list.contains(&mints[1]));
+        assert_eq!(deny_list.mints().len(), MAX_DENIED_MINTS - 1);
+        assert_eq!(deny_list.mints()[1], mints[MAX_DENIED_MINTS - 1]);
+
+        let data = data.clone();
+        let deny_list = DenyList::load(&data).unwrap();
+        assert_eq!(deny_list.config, config);
+        assert!(deny_list.contains(&mints[0]));
+        assert_eq!(
+            DenyList::init(&mut data.clone(), config, 255).err(),
+            Some(SwapError::AlreadyInUse.into())
+        );
+        assert_eq!(
+            DenyList::load(&vec![0u8; DENY_LIST_SIZE]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+    }
+}
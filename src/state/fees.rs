@@ -6,10 +6,12 @@ use solana_program::{
     program_pack::{IsInitialized, Pack, Sealed},
 };
 
-use crate::error::SwapError;
+use crate::{error::SwapError, instruction::SwapDirection};
+use std::convert::TryFrom;
 
 /// Fees struct
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Fees {
     /// Admin trade fee numerator
     pub admin_trade_fee_numerator: u64,
@@ -27,6 +29,9 @@ pub struct Fees {
     pub withdraw_fee_numerator: u64,
     /// Withdraw fee denominator
     pub withdraw_fee_denominator: u64,
+    /// Smallest trade fee charged on a swap, in units of the token received.
+    /// Stored by the token-swap, out of the packed fees
+    pub min_trade_fee: u64,
 }
 
 impl Fees {
@@ -49,9 +54,21 @@ impl Fees {
             trade_fee_denominator: params.trade_fee_denominator,
             withdraw_fee_numerator: params.withdraw_fee_numerator,
             withdraw_fee_denominator: params.withdraw_fee_denominator,
+            min_trade_fee: params.min_trade_fee,
         }
     }
 
+    /// Whether every denominator is set and no fee takes the whole amount,
+    /// the admin share being at most the whole fee
+    pub fn is_valid(&self) -> bool {
+        self.trade_fee_numerator < self.trade_fee_denominator
+            && self.withdraw_fee_numerator < self.withdraw_fee_denominator
+            && self.admin_trade_fee_numerator <= self.admin_trade_fee_denominator
+            && self.admin_withdraw_fee_numerator <= self.admin_withdraw_fee_denominator
+            && self.admin_trade_fee_denominator > 0
+            && self.admin_withdraw_fee_denominator > 0
+    }
+
     /// Apply admin trade fee
     ///
     /// # Arguments
@@ -86,7 +103,7 @@ impl Fees {
             .ok_or_else(|| SwapError::CalculationFailure.into())
     }
 
-    /// Compute trade fee from amount
+    /// Compute trade fee from amount, no less than the min trade fee
     ///
     /// # Arguments
     ///
@@ -100,6 +117,7 @@ impl Fees {
             .checked_mul(self.trade_fee_numerator)
             .ok_or(SwapError::CalculationFailure)?
             .checked_div(self.trade_fee_denominator)
+            .map(|trade_fee| trade_fee.max(self.min_trade_fee))
             .ok_or_else(|| SwapError::CalculationFailure.into())
     }
 
@@ -119,6 +137,33 @@ impl Fees {
             .checked_div(self.withdraw_fee_denominator)
             .ok_or_else(|| SwapError::CalculationFailure.into())
     }
+
+    /// Compute the smallest withdraw amount left with at least the given
+    /// amount after the withdraw fee
+    ///
+    /// # Arguments
+    ///
+    /// * amount - amount wanted after the withdraw fee.
+    ///
+    /// # Return value
+    ///
+    /// withdraw amount before fee
+    pub fn withdraw_amount_before_fee(&self, amount: u64) -> Result<u64, ProgramError> {
+        let denominator = self.withdraw_fee_denominator as u128;
+        let net_denominator = denominator
+            .checked_sub(self.withdraw_fee_numerator as u128)
+            .filter(|net_denominator| *net_denominator > 0)
+            .ok_or(SwapError::CalculationFailure)?;
+        if amount == 0 {
+            return Ok(0);
+        }
+        // the fee rounds down, so gross - fee = ceil(gross * net_denominator / denominator)
+        ((amount - 1) as u128)
+            .checked_mul(denominator)
+            .map(|gross| gross / net_denominator + 1)
+            .and_then(|gross| u64::try_from(gross).ok())
+            .ok_or_else(|| SwapError::CalculationFailure.into())
+    }
 }
 
 impl Sealed for Fees {}
@@ -128,7 +173,8 @@ impl IsInitialized for Fees {
     }
 }
 
-const FEES_SIZE: usize = 64;
+/// Fees packed size, the min trade fee is left out
+pub const FEES_SIZE: usize = 64;
 impl Pack for Fees {
     const LEN: usize = FEES_SIZE;
     fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
@@ -153,6 +199,7 @@ impl Pack for Fees {
             trade_fee_denominator: u64::from_le_bytes(*trade_fee_denominator),
             withdraw_fee_numerator: u64::from_le_bytes(*withdraw_fee_numerator),
             withdraw_fee_denominator: u64::from_le_bytes(*withdraw_fee_denominator),
+            min_trade_fee: 0,
         })
     }
 
@@ -179,6 +226,185 @@ impl Pack for Fees {
     }
 }
 
+/// Trade fee rates of a pool per swap direction, each replacing the trade fee
+/// of the pool fees while its denominator is not zero
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DirectionalFees {
+    /// Trade fee numerator of the swaps selling the base token
+    pub sell_base_fee_numerator: u64,
+    /// Trade fee denominator of the swaps selling the base token
+    pub sell_base_fee_denominator: u64,
+    /// Trade fee numerator of the swaps selling the quote token
+    pub sell_quote_fee_numerator: u64,
+    /// Trade fee denominator of the swaps selling the quote token
+    pub sell_quote_fee_denominator: u64,
+}
+
+impl DirectionalFees {
+    /// Whether no rate charges more than the whole output
+    pub fn is_valid(&self) -> bool {
+        self.sell_base_fee_numerator <= self.sell_base_fee_denominator
+            && self.sell_quote_fee_numerator <= self.sell_quote_fee_denominator
+    }
+
+    /// Fees charged on a swap direction
+    ///
+    /// # Arguments
+    ///
+    /// * fees - pool fees.
+    /// * swap_direction - sell base or sell quote.
+    ///
+    /// # Return value
+    ///
+    /// pool fees with the trade fee of the direction, if set
+    pub fn apply(&self, fees: &Fees, swap_direction: SwapDirection) -> Fees {
+        let (trade_fee_numerator, trade_fee_denominator) = match swap_direction {
+            SwapDirection::SellBase => {
+                (self.sell_base_fee_numerator, self.sell_base_fee_denominator)
+            }
+            SwapDirection::SellQuote => (
+                self.sell_quote_fee_numerator,
+                self.sell_quote_fee_denominator,
+            ),
+        };
+        if trade_fee_denominator == 0 {
+            return Fees::new(fees);
+        }
+        Fees {
+            trade_fee_numerator,
+            trade_fee_denominator,
+            ..Fees::new(fees)
+        }
+    }
+}
+
+impl Sealed for DirectionalFees {}
+impl IsInitialized for DirectionalFees {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+
+/// Directional fees packed size
+pub const DIRECTIONAL_FEES_SIZE: usize = 32;
+impl Pack for DirectionalFees {
+    const LEN: usize = DIRECTIONAL_FEES_SIZE;
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, DIRECTIONAL_FEES_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            sell_base_fee_numerator,
+            sell_base_fee_denominator,
+            sell_quote_fee_numerator,
+            sell_quote_fee_denominator,
+        ) = array_refs![input, 8, 8, 8, 8];
+        Ok(Self {
+            sell_base_fee_numerator: u64::from_le_bytes(*sell_base_fee_numerator),
+            sell_base_fee_denominator: u64::from_le_bytes(*sell_base_fee_denominator),
+            sell_quote_fee_numerator: u64::from_le_bytes(*sell_quote_fee_numerator),
+            sell_quote_fee_denominator: u64::from_le_bytes(*sell_quote_fee_denominator),
+        })
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, DIRECTIONAL_FEES_SIZE];
+        let (
+            sell_base_fee_numerator,
+            sell_base_fee_denominator,
+            sell_quote_fee_numerator,
+            sell_quote_fee_denominator,
+        ) = mut_array_refs![output, 8, 8, 8, 8];
+        *sell_base_fee_numerator = self.sell_base_fee_numerator.to_le_bytes();
+        *sell_base_fee_denominator = self.sell_base_fee_denominator.to_le_bytes();
+        *sell_quote_fee_numerator = self.sell_quote_fee_numerator.to_le_bytes();
+        *sell_quote_fee_denominator = self.sell_quote_fee_denominator.to_le_bytes();
+    }
+}
+
+/// Number of fee discount tiers of the DELFI holders
+pub const FEE_DISCOUNT_TIERS: usize = 3;
+
+/// Trade fee discount of the traders holding at least a DELFI balance
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FeeDiscountTier {
+    /// Min DELFI balance of the trader
+    pub min_balance: u64,
+    /// Discount of the trade fee, in basis points, zero for an unused tier
+    pub discount_bps: u64,
+}
+
+/// Trade fee discount tiers of the DELFI holders
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FeeDiscount {
+    /// Discount tiers, in any order
+    pub tiers: [FeeDiscountTier; FEE_DISCOUNT_TIERS],
+}
+
+impl FeeDiscount {
+    /// Whether a tier is set
+    pub fn is_enabled(&self) -> bool {
+        self.tiers.iter().any(|tier| tier.discount_bps > 0)
+    }
+
+    /// Trade fee discount of a DELFI balance
+    ///
+    /// # Arguments
+    ///
+    /// * balance - DELFI balance of the trader.
+    ///
+    /// # Return value
+    ///
+    /// highest discount of the tiers reached, in basis points.
+    pub fn discount_bps(&self, balance: u64) -> u64 {
+        self.tiers
+            .iter()
+            .filter(|tier| tier.discount_bps > 0 && balance >= tier.min_balance)
+            .map(|tier| tier.discount_bps)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Sealed for FeeDiscount {}
+impl IsInitialized for FeeDiscount {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+
+/// Fee discount packed size
+pub const FEE_DISCOUNT_SIZE: usize = 48;
+impl Pack for FeeDiscount {
+    const LEN: usize = FEE_DISCOUNT_SIZE;
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, FEE_DISCOUNT_SIZE];
+        let mut tiers = [FeeDiscountTier::default(); FEE_DISCOUNT_TIERS];
+        for (tier, chunk) in tiers.iter_mut().zip(input.chunks_exact(16)) {
+            let chunk = array_ref![chunk, 0, 16];
+            #[allow(clippy::ptr_offset_with_cast)]
+            let (min_balance, discount_bps) = array_refs![chunk, 8, 8];
+            *tier = FeeDiscountTier {
+                min_balance: u64::from_le_bytes(*min_balance),
+                discount_bps: u64::from_le_bytes(*discount_bps),
+            };
+        }
+        Ok(Self { tiers })
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, FEE_DISCOUNT_SIZE];
+        for (tier, chunk) in self.tiers.iter().zip(output.chunks_exact_mut(16)) {
+            let chunk = array_mut_ref![chunk, 0, 16];
+            let (min_balance, discount_bps) = mut_array_refs![chunk, 8, 8];
+            *min_balance = tier.min_balance.to_le_bytes();
+            *discount_bps = tier.discount_bps.to_le_bytes();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,4 +460,226 @@ mod tests {
             expected_admin_withdraw_fee
         );
     }
+
+    #[test]
+    fn fees_validity() {
+        assert!(DEFAULT_TEST_FEES.is_valid());
+        assert!(Fees {
+            trade_fee_numerator: 0,
+            admin_trade_fee_numerator: 1,
+            admin_trade_fee_denominator: 1,
+            ..DEFAULT_TEST_FEES
+        }
+        .is_valid());
+
+        for fees in [
+            Fees {
+                trade_fee_numerator: 100,
+                ..DEFAULT_TEST_FEES
+            },
+            Fees {
+                withdraw_fee_numerator: 100,
+                ..DEFAULT_TEST_FEES
+            },
+            Fees {
+                admin_trade_fee_numerator: 3,
+                ..DEFAULT_TEST_FEES
+            },
+            Fees {
+                admin_withdraw_fee_numerator: 3,
+                ..DEFAULT_TEST_FEES
+            },
+            Fees {
+                trade_fee_numerator: 0,
+                trade_fee_denominator: 0,
+                ..DEFAULT_TEST_FEES
+            },
+            Fees {
+                admin_trade_fee_numerator: 0,
+                admin_trade_fee_denominator: 0,
+                ..DEFAULT_TEST_FEES
+            },
+            Fees {
+                admin_withdraw_fee_numerator: 0,
+                admin_withdraw_fee_denominator: 0,
+                ..DEFAULT_TEST_FEES
+            },
+        ] {
+            assert!(!fees.is_valid());
+        }
+    }
+
+    #[test]
+    fn min_trade_fee() {
+        let fees = Fees {
+            min_trade_fee: 10,
+            ..DEFAULT_TEST_FEES
+        };
+
+        // dust trades pay the floor instead of rounding the fee down to zero
+        assert_eq!(fees.trade_fee(1).unwrap(), 10);
+        assert_eq!(fees.trade_fee(100).unwrap(), 10);
+        assert_eq!(
+            fees.trade_fee(1_000_000).unwrap(),
+            DEFAULT_TEST_FEES.trade_fee(1_000_000).unwrap()
+        );
+
+        // the floor stays with the rates of a swap direction
+        let directional_fees = DirectionalFees {
+            sell_base_fee_numerator: 0,
+            sell_base_fee_denominator: 1_000,
+            ..DirectionalFees::default()
+        };
+        assert_eq!(
+            directional_fees
+                .apply(&fees, SwapDirection::SellBase)
+                .trade_fee(1_000_000)
+                .unwrap(),
+            10
+        );
+    }
+
+    #[test]
+    fn withdraw_amount_before_fee() {
+        let fees = DEFAULT_TEST_FEES;
+
+        for amount in [0, 1, 7, 999, 1_000_000, 123_456_789_012] {
+            let gross = fees.withdraw_amount_before_fee(amount).unwrap();
+            assert!(gross - fees.withdraw_fee(gross).unwrap() >= amount);
+            // one less does not cover the fee
+            if gross > amount {
+                assert!(gross - 1 - fees.withdraw_fee(gross - 1).unwrap() < amount);
+            }
+        }
+
+        let fees = Fees {
+            withdraw_fee_numerator: 1,
+            withdraw_fee_denominator: 1,
+            ..DEFAULT_TEST_FEES
+        };
+        assert!(fees.withdraw_amount_before_fee(1).is_err());
+    }
+
+    #[test]
+    fn pack_directional_fees() {
+        let directional_fees = DirectionalFees {
+            sell_base_fee_numerator: 1,
+            sell_base_fee_denominator: 1_000,
+            sell_quote_fee_numerator: 5,
+            sell_quote_fee_denominator: 1_000,
+        };
+
+        let mut packed = [0u8; DirectionalFees::LEN];
+        Pack::pack_into_slice(&directional_fees, &mut packed[..]);
+        let unpacked = DirectionalFees::unpack_from_slice(&packed).unwrap();
+        assert_eq!(directional_fees, unpacked);
+
+        let mut packed = vec![];
+        packed.extend_from_slice(&directional_fees.sell_base_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&directional_fees.sell_base_fee_denominator.to_le_bytes());
+        packed.extend_from_slice(&directional_fees.sell_quote_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&directional_fees.sell_quote_fee_denominator.to_le_bytes());
+        let unpacked = DirectionalFees::unpack_from_slice(&packed).unwrap();
+        assert_eq!(directional_fees, unpacked);
+    }
+
+    #[test]
+    fn directional_fee_results() {
+        let fees = DEFAULT_TEST_FEES;
+
+        // unset rates keep the pool trade fee
+        let directional_fees = DirectionalFees::default();
+        assert!(directional_fees.is_valid());
+        assert_eq!(directional_fees.apply(&fees, SwapDirection::SellBase), fees);
+        assert_eq!(
+            directional_fees.apply(&fees, SwapDirection::SellQuote),
+            fees
+        );
+
+        // a skew toward the quote token, free to sell base
+        let directional_fees = DirectionalFees {
+            sell_base_fee_numerator: 0,
+            sell_base_fee_denominator: 1_000,
+            ..DirectionalFees::default()
+        };
+        let sell_base_fees = directional_fees.apply(&fees, SwapDirection::SellBase);
+        assert_eq!(sell_base_fees.trade_fee(1_000_000).unwrap(), 0);
+        assert_eq!(
+            sell_base_fees.admin_trade_fee_numerator,
+            fees.admin_trade_fee_numerator
+        );
+        assert_eq!(
+            sell_base_fees.withdraw_fee_numerator,
+            fees.withdraw_fee_numerator
+        );
+        assert_eq!(
+            directional_fees.apply(&fees, SwapDirection::SellQuote),
+            fees
+        );
+
+        let directional_fees = DirectionalFees {
+            sell_quote_fee_numerator: 1_001,
+            sell_quote_fee_denominator: 1_000,
+            ..DirectionalFees::default()
+        };
+        assert!(!directional_fees.is_valid());
+    }
+
+    #[test]
+    fn pack_fee_discount() {
+        let fee_discount = FeeDiscount {
+            tiers: [
+                FeeDiscountTier {
+                    min_balance: 1_000,
+                    discount_bps: 1_000,
+                },
+                FeeDiscountTier {
+                    min_balance: 10_000,
+                    discount_bps: 2_500,
+                },
+                FeeDiscountTier::default(),
+            ],
+        };
+
+        let mut packed = [0u8; FeeDiscount::LEN];
+        Pack::pack_into_slice(&fee_discount, &mut packed[..]);
+        let unpacked = FeeDiscount::unpack_from_slice(&packed).unwrap();
+        assert_eq!(fee_discount, unpacked);
+
+        let mut packed = vec![];
+        for tier in fee_discount.tiers.iter() {
+            packed.extend_from_slice(&tier.min_balance.to_le_bytes());
+            packed.extend_from_slice(&tier.discount_bps.to_le_bytes());
+        }
+        let unpacked = FeeDiscount::unpack_from_slice(&packed).unwrap();
+        assert_eq!(fee_discount, unpacked);
+    }
+
+    #[test]
+    fn fee_discount_tiers() {
+        let fee_discount = FeeDiscount::default();
+        assert!(!fee_discount.is_enabled());
+        assert_eq!(fee_discount.discount_bps(u64::MAX), 0);
+
+        let fee_discount = FeeDiscount {
+            tiers: [
+                FeeDiscountTier {
+                    min_balance: 10_000,
+                    discount_bps: 2_500,
+                },
+                FeeDiscountTier {
+                    min_balance: 1_000,
+                    discount_bps: 1_000,
+                },
+                // an unused tier does not apply from a zero balance
+                FeeDiscountTier::default(),
+            ],
+        };
+        assert!(fee_discount.is_enabled());
+        assert_eq!(fee_discount.discount_bps(0), 0);
+        assert_eq!(fee_discount.discount_bps(999), 0);
+        assert_eq!(fee_discount.discount_bps(1_000), 1_000);
+        assert_eq!(fee_discount.discount_bps(9_999), 1_000);
+        assert_eq!(fee_discount.discount_bps(10_000), 2_500);
+    }
 }
@@ -0,0 +1,265 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    clock::{Epoch, UnixTimestamp},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use super::*;
+use crate::{error::SwapError, math::BPS_DENOMINATOR};
+
+/// Min seconds DELFI is locked for, from the time it is locked
+pub const MIN_LOCK_DURATION: UnixTimestamp = 604800;
+/// Seed of the DELFI lock address, derived with the config and owner keys
+pub const DELFI_LOCK_SEED: &[u8] = b"delfi_lock";
+
+/// Find the DELFI lock address of an owner in a config
+pub fn find_delfi_lock_address(
+    program_id: &Pubkey,
+    config: &Pubkey,
+    owner: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[config.as_ref(), owner.as_ref(), DELFI_LOCK_SEED],
+        program_id,
+    )
+}
+
+/// DELFI locked by an owner in the vault of a config, voting on the gauge
+/// weights of the pools of that config once per epoch until it unlocks
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DelfiLock {
+    /// Initialization status
+    pub is_initialized: bool,
+    /// Config the DELFI is locked in the vault of
+    pub config: Pubkey,
+    /// Owner authority
+    pub owner: Pubkey,
+    /// DELFI locked, the voting power of the lock
+    pub locked_amount: u64,
+    /// Timestamp the DELFI can be unlocked from
+    pub unlock_ts: UnixTimestamp,
+    /// First epoch the lock can vote in again
+    pub next_vote_epoch: Epoch,
+}
+
+impl DelfiLock {
+    /// Initialize a DELFI lock
+    pub fn init(&mut self, config: Pubkey, owner: Pubkey) {
+        self.is_initialized = true;
+        self.config = config;
+        self.owner = owner;
+    }
+
+    /// Lock more DELFI, the lock can only be extended
+    ///
+    /// # Arguments
+    ///
+    /// * amount - DELFI locked.
+    /// * unlock_ts - timestamp the DELFI can be unlocked from.
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// lock status.
+    pub fn lock(
+        &mut self,
+        amount: u64,
+        unlock_ts: UnixTimestamp,
+        current_ts: UnixTimestamp,
+    ) -> ProgramResult {
+        let min_unlock_ts = current_ts
+            .checked_add(MIN_LOCK_DURATION)
+            .ok_or(SwapError::CalculationFailure)?;
+        if unlock_ts < min_unlock_ts || unlock_ts < self.unlock_ts {
+            return Err(SwapError::InvalidInput.into());
+        }
+        self.locked_amount = self
+            .locked_amount
+            .checked_add(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.unlock_ts = unlock_ts;
+        Ok(())
+    }
+
+    /// Unlock all the DELFI, not before the unlock timestamp nor in an epoch
+    /// the lock voted in
+    ///
+    /// # Arguments
+    ///
+    /// * current_ts - current unix timestamp.
+    /// * epoch - current epoch.
+    ///
+    /// # Return value
+    ///
+    /// DELFI unlocked.
+    pub fn unlock(&mut self, current_ts: UnixTimestamp, epoch: Epoch) -> Result<u64, ProgramError> {
+        if current_ts < self.unlock_ts || epoch < self.next_vote_epoch {
+            return Err(SwapError::DelfiLocked.into());
+        }
+        let amount = self.locked_amount;
+        self.locked_amount = 0;
+        Ok(amount)
+    }
+
+    /// Cast the votes of the lock in an epoch
+    ///
+    /// # Arguments
+    ///
+    /// * weights_bps - share of the voting power for each pool, in basis points.
+    /// * current_ts - current unix timestamp.
+    /// * epoch - current epoch.
+    ///
+    /// # Return value
+    ///
+    /// votes for each pool.
+    pub fn vote(
+        &mut self,
+        weights_bps: &[u16],
+        current_ts: UnixTimestamp,
+        epoch: Epoch,
+    ) -> Result<Vec<u64>, ProgramError> {
+        if epoch < self.next_vote_epoch {
+            return Err(SwapError::GaugeVoteCast.into());
+        }
+        if current_ts >= self.unlock_ts {
+            return Err(SwapError::DelfiLockExpired.into());
+        }
+        let total_bps = weights_bps
+            .iter()
+            .map(|&weight_bps| weight_bps as u64)
+            .sum::<u64>();
+        if total_bps > BPS_DENOMINATOR {
+            return Err(SwapError::InvalidInput.into());
+        }
+        self.next_vote_epoch = epoch.checked_add(1).ok_or(SwapError::CalculationFailure)?;
+        Ok(weights_bps
+            .iter()
+            .map(|&weight_bps| {
+                (self.locked_amount as u128 * weight_bps as u128 / BPS_DENOMINATOR as u128) as u64
+            })
+            .collect())
+    }
+}
+
+impl Sealed for DelfiLock {}
+impl IsInitialized for DelfiLock {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+const DELFI_LOCK_SIZE: usize = 89; // 1 + 32 + 32 + 8 + 8 + 8
+
+impl Pack for DelfiLock {
+    const LEN: usize = DELFI_LOCK_SIZE;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, DELFI_LOCK_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, config, owner, locked_amount, unlock_ts, next_vote_epoch) =
+            mut_array_refs![output, 1, PUBKEY_BYTES, PUBKEY_BYTES, 8, 8, 8];
+        pack_bool(self.is_initialized, is_initialized);
+        config.copy_from_slice(self.config.as_ref());
+        owner.copy_from_slice(self.owner.as_ref());
+        *locked_amount = self.locked_amount.to_le_bytes();
+        *unlock_ts = self.unlock_ts.to_le_bytes();
+        *next_vote_epoch = self.next_vote_epoch.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, DELFI_LOCK_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, config, owner, locked_amount, unlock_ts, next_vote_epoch) =
+            array_refs![input, 1, PUBKEY_BYTES, PUBKEY_BYTES, 8, 8, 8];
+        Ok(Self {
+            is_initialized: unpack_bool(is_initialized)?,
+            config: Pubkey::new_from_array(*config),
+            owner: Pubkey::new_from_array(*owner),
+            locked_amount: u64::from_le_bytes(*locked_amount),
+            unlock_ts: i64::from_le_bytes(*unlock_ts),
+            next_vote_epoch: u64::from_le_bytes(*next_vote_epoch),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delfi_lock_packing() {
+        let delfi_lock = DelfiLock {
+            is_initialized: true,
+            config: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            locked_amount: 1_000_000,
+            unlock_ts: 1_700_000_000,
+            next_vote_epoch: 42,
+        };
+
+        let mut packed = vec![1u8];
+        packed.extend_from_slice(delfi_lock.config.as_ref());
+        packed.extend_from_slice(delfi_lock.owner.as_ref());
+        packed.extend_from_slice(&delfi_lock.locked_amount.to_le_bytes());
+        packed.extend_from_slice(&delfi_lock.unlock_ts.to_le_bytes());
+        packed.extend_from_slice(&delfi_lock.next_vote_epoch.to_le_bytes());
+        assert_eq!(DelfiLock::unpack(&packed).unwrap(), delfi_lock);
+
+        let mut repacked = [0u8; DelfiLock::LEN];
+        DelfiLock::pack(delfi_lock, &mut repacked).unwrap();
+        assert_eq!(repacked.to_vec(), packed);
+    }
+
+    #[test]
+    fn test_lock_vote_unlock() {
+        let mut delfi_lock = DelfiLock::default();
+        delfi_lock.init(Pubkey::new_unique(), Pubkey::new_unique());
+
+        // locks last at least the min lock duration
+        assert_eq!(
+            delfi_lock.lock(1_000, MIN_LOCK_DURATION, 1),
+            Err(SwapError::InvalidInput.into())
+        );
+        delfi_lock.lock(1_000, MIN_LOCK_DURATION, 0).unwrap();
+        // and cannot be shortened
+        assert_eq!(
+            delfi_lock.lock(1_000, MIN_LOCK_DURATION - 1, -1),
+            Err(SwapError::InvalidInput.into())
+        );
+        delfi_lock.lock(1_000, MIN_LOCK_DURATION, 0).unwrap();
+        assert_eq!(delfi_lock.locked_amount, 2_000);
+
+        assert_eq!(
+            delfi_lock.vote(&[6_000, 5_000], 0, 3),
+            Err(SwapError::InvalidInput.into())
+        );
+        assert_eq!(
+            delfi_lock.vote(&[6_000, 3_000], 0, 3).unwrap(),
+            vec![1_200, 600]
+        );
+        assert_eq!(
+            delfi_lock.vote(&[10_000], 0, 3),
+            Err(SwapError::GaugeVoteCast.into())
+        );
+        assert_eq!(delfi_lock.vote(&[10_000], 0, 4).unwrap(), vec![2_000]);
+
+        // locked until the unlock timestamp, and out of the epoch voted in
+        assert_eq!(
+            delfi_lock.unlock(MIN_LOCK_DURATION - 1, 5),
+            Err(SwapError::DelfiLocked.into())
+        );
+        assert_eq!(
+            delfi_lock.unlock(MIN_LOCK_DURATION, 4),
+            Err(SwapError::DelfiLocked.into())
+        );
+        assert_eq!(
+            delfi_lock.vote(&[10_000], MIN_LOCK_DURATION, 5),
+            Err(SwapError::DelfiLockExpired.into())
+        );
+        assert_eq!(delfi_lock.unlock(MIN_LOCK_DURATION, 5).unwrap(), 2_000);
+        assert_eq!(delfi_lock.locked_amount, 0);
+    }
+}
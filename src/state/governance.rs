@@ -0,0 +1,459 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    clock::UnixTimestamp,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use super::*;
+use crate::error::SwapError;
+
+/// Seconds a proposal is open to votes, from the time it is created
+pub const VOTING_PERIOD: UnixTimestamp = 259200;
+/// Seed of the proposal address, derived with the config key and the index
+/// of the proposal
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+/// Seed of the vote record address, derived with the proposal key and the
+/// DELFI lock owner key
+pub const VOTE_RECORD_SEED: &[u8] = b"vote_record";
+
+/// Find the address of a proposal of a config
+pub fn find_proposal_address(
+    program_id: &Pubkey,
+    config: &Pubkey,
+    proposal_index: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            config.as_ref(),
+            &proposal_index.to_le_bytes(),
+            PROPOSAL_SEED,
+        ],
+        program_id,
+    )
+}
+
+/// Find the vote record address of a DELFI lock owner on a proposal
+pub fn find_vote_record_address(
+    program_id: &Pubkey,
+    proposal: &Pubkey,
+    owner: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[proposal.as_ref(), owner.as_ref(), VOTE_RECORD_SEED],
+        program_id,
+    )
+}
+
+/// Fees and rewards of a config proposed to the votes of the DELFI locks
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Proposal {
+    /// Initialization status
+    pub is_initialized: bool,
+    /// Config the proposal changes
+    pub config: Pubkey,
+    /// Proposer authority
+    pub proposer: Pubkey,
+    /// Fees of the config once executed
+    pub fees: Fees,
+    /// Rewards of the config once executed
+    pub rewards: Rewards,
+    /// Timestamp the voting closes at
+    pub voting_end_ts: UnixTimestamp,
+    /// Votes for the proposal
+    pub yes_votes: u64,
+    /// Votes against the proposal
+    pub no_votes: u64,
+    /// Execution status
+    pub is_executed: bool,
+}
+
+impl Proposal {
+    /// Initialize a proposal open to votes for the voting period, the fees
+    /// and rewards proposed being valid for a config
+    ///
+    /// # Arguments
+    ///
+    /// * config - config the proposal changes.
+    /// * proposer - proposer authority.
+    /// * fees - fees proposed.
+    /// * rewards - rewards proposed.
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// initialization status.
+    pub fn init(
+        &mut self,
+        config: Pubkey,
+        proposer: Pubkey,
+        fees: &Fees,
+        rewards: &Rewards,
+        current_ts: UnixTimestamp,
+    ) -> ProgramResult {
+        if !fees.is_valid() || !rewards.is_valid() {
+            return Err(SwapError::InvalidInput.into());
+        }
+        self.is_initialized = true;
+        self.config = config;
+        self.proposer = proposer;
+        self.fees = Fees::new(fees);
+        self.rewards = Rewards::new(rewards);
+        self.voting_end_ts = current_ts
+            .checked_add(VOTING_PERIOD)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Vote on the proposal while the voting is open
+    ///
+    /// # Arguments
+    ///
+    /// * votes - votes cast.
+    /// * approve - votes for the proposal, or against it.
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// vote status.
+    pub fn vote(&mut self, votes: u64, approve: bool, current_ts: UnixTimestamp) -> ProgramResult {
+        if current_ts >= self.voting_end_ts {
+            return Err(SwapError::ProposalVotingClosed.into());
+        }
+        let tally = if approve {
+            &mut self.yes_votes
+        } else {
+            &mut self.no_votes
+        };
+        *tally = tally
+            .checked_add(votes)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Execute the proposal once the voting closed, if a majority of at least
+    /// the quorum votes for it and its fees and rewards are still valid
+    ///
+    /// # Arguments
+    ///
+    /// * quorum - min votes for the proposal.
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// execution status.
+    pub fn execute(&mut self, quorum: u64, current_ts: UnixTimestamp) -> ProgramResult {
+        if self.is_executed {
+            return Err(SwapError::ProposalExecuted.into());
+        }
+        if !self.fees.is_valid() || !self.rewards.is_valid() {
+            return Err(SwapError::InvalidInput.into());
+        }
+        if current_ts < self.voting_end_ts {
+            return Err(SwapError::ProposalVotingOpen.into());
+        }
+        if self.yes_votes < quorum || self.yes_votes <= self.no_votes {
+            return Err(SwapError::ProposalRejected.into());
+        }
+        self.is_executed = true;
+        Ok(())
+    }
+}
+
+impl Sealed for Proposal {}
+impl IsInitialized for Proposal {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+const PROPOSAL_SIZE: usize = 194; // 1 + 32 + 32 + 64 + 40 + 8 + 8 + 8 + 1
+
+impl Pack for Proposal {
+    const LEN: usize = PROPOSAL_SIZE;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, PROPOSAL_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            config,
+            proposer,
+            fees,
+            rewards,
+            voting_end_ts,
+            yes_votes,
+            no_votes,
+            is_executed,
+        ) = mut_array_refs![
+            output,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            Fees::LEN,
+            Rewards::LEN,
+            8,
+            8,
+            8,
+            1
+        ];
+        pack_bool(self.is_initialized, is_initialized);
+        config.copy_from_slice(self.config.as_ref());
+        proposer.copy_from_slice(self.proposer.as_ref());
+        self.fees.pack_into_slice(&mut fees[..]);
+        self.rewards.pack_into_slice(&mut rewards[..]);
+        *voting_end_ts = self.voting_end_ts.to_le_bytes();
+        *yes_votes = self.yes_votes.to_le_bytes();
+        *no_votes = self.no_votes.to_le_bytes();
+        pack_bool(self.is_executed, is_executed);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, PROPOSAL_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            config,
+            proposer,
+            fees,
+            rewards,
+            voting_end_ts,
+            yes_votes,
+            no_votes,
+            is_executed,
+        ) = array_refs![
+            input,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            Fees::LEN,
+            Rewards::LEN,
+            8,
+            8,
+            8,
+            1
+        ];
+        Ok(Self {
+            is_initialized: unpack_bool(is_initialized)?,
+            config: Pubkey::new_from_array(*config),
+            proposer: Pubkey::new_from_array(*proposer),
+            fees: Fees::unpack_from_slice(fees)?,
+            rewards: Rewards::unpack_from_slice(rewards)?,
+            voting_end_ts: i64::from_le_bytes(*voting_end_ts),
+            yes_votes: u64::from_le_bytes(*yes_votes),
+            no_votes: u64::from_le_bytes(*no_votes),
+            is_executed: unpack_bool(is_executed)?,
+        })
+    }
+}
+
+/// Vote of a DELFI lock owner on a proposal, its address existing only once
+/// per owner and proposal
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VoteRecord {
+    /// Initialization status
+    pub is_initialized: bool,
+    /// Proposal voted on
+    pub proposal: Pubkey,
+    /// DELFI lock owner
+    pub owner: Pubkey,
+    /// Votes cast, the DELFI locked at the time of the vote
+    pub votes: u64,
+    /// Votes for the proposal, or against it
+    pub approve: bool,
+}
+
+impl VoteRecord {
+    /// Initialize a vote record
+    pub fn init(&mut self, proposal: Pubkey, owner: Pubkey, votes: u64, approve: bool) {
+        self.is_initialized = true;
+        self.proposal = proposal;
+        self.owner = owner;
+        self.votes = votes;
+        self.approve = approve;
+    }
+}
+
+impl Sealed for VoteRecord {}
+impl IsInitialized for VoteRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+const VOTE_RECORD_SIZE: usize = 74; // 1 + 32 + 32 + 8 + 1
+
+impl Pack for VoteRecord {
+    const LEN: usize = VOTE_RECORD_SIZE;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, VOTE_RECORD_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, proposal, owner, votes, approve) =
+            mut_array_refs![output, 1, PUBKEY_BYTES, PUBKEY_BYTES, 8, 1];
+        pack_bool(self.is_initialized, is_initialized);
+        proposal.copy_from_slice(self.proposal.as_ref());
+        owner.copy_from_slice(self.owner.as_ref());
+        *votes = self.votes.to_le_bytes();
+        pack_bool(self.approve, approve);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, VOTE_RECORD_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, proposal, owner, votes, approve) =
+            array_refs![input, 1, PUBKEY_BYTES, PUBKEY_BYTES, 8, 1];
+        Ok(Self {
+            is_initialized: unpack_bool(is_initialized)?,
+            proposal: Pubkey::new_from_array(*proposal),
+            owner: Pubkey::new_from_array(*owner),
+            votes: u64::from_le_bytes(*votes),
+            approve: unpack_bool(approve)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proposal_packing() {
+        let proposal = Proposal {
+            is_initialized: true,
+            config: Pubkey::new_unique(),
+            proposer: Pubkey::new_unique(),
+            fees: DEFAULT_TEST_FEES,
+            rewards: DEFAULT_TEST_REWARDS,
+            voting_end_ts: 1_700_000_000,
+            yes_votes: 3_000,
+            no_votes: 1_000,
+            is_executed: false,
+        };
+
+        let mut packed = [0u8; Proposal::LEN];
+        Proposal::pack(proposal.clone(), &mut packed).unwrap();
+        assert_eq!(Proposal::unpack(&packed).unwrap(), proposal);
+        assert_eq!(
+            packed[Proposal::LEN - 25..Proposal::LEN - 17],
+            proposal.voting_end_ts.to_le_bytes()
+        );
+
+        let vote_record = VoteRecord {
+            is_initialized: true,
+            proposal: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            votes: 3_000,
+            approve: true,
+        };
+
+        let mut packed = vec![1u8];
+        packed.extend_from_slice(vote_record.proposal.as_ref());
+        packed.extend_from_slice(vote_record.owner.as_ref());
+        packed.extend_from_slice(&vote_record.votes.to_le_bytes());
+        packed.push(1);
+        assert_eq!(VoteRecord::unpack(&packed).unwrap(), vote_record);
+    }
+
+    #[test]
+    fn test_proposal_vote_execute() {
+        let mut proposal = Proposal::default();
+        proposal
+            .init(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                &DEFAULT_TEST_FEES,
+                &DEFAULT_TEST_REWARDS,
+                0,
+            )
+            .unwrap();
+        assert_eq!(proposal.voting_end_ts, VOTING_PERIOD);
+
+        proposal.vote(2_000, true, 0).unwrap();
+        proposal.vote(1_500, false, VOTING_PERIOD - 1).unwrap();
+        assert_eq!(
+            proposal.vote(1_000, false, VOTING_PERIOD),
+            Err(SwapError::ProposalVotingClosed.into())
+        );
+        assert_eq!((proposal.yes_votes, proposal.no_votes), (2_000, 1_500));
+
+        assert_eq!(
+            proposal.execute(1_000, VOTING_PERIOD - 1),
+            Err(SwapError::ProposalVotingOpen.into())
+        );
+        // a majority short of the quorum is rejected
+        assert_eq!(
+            proposal.clone().execute(2_001, VOTING_PERIOD),
+            Err(SwapError::ProposalRejected.into())
+        );
+        proposal.execute(2_000, VOTING_PERIOD).unwrap();
+        assert_eq!(
+            proposal.execute(2_000, VOTING_PERIOD),
+            Err(SwapError::ProposalExecuted.into())
+        );
+
+        // ties are rejected
+        let mut proposal = Proposal {
+            fees: DEFAULT_TEST_FEES,
+            rewards: DEFAULT_TEST_REWARDS,
+            voting_end_ts: VOTING_PERIOD,
+            ..Proposal::default()
+        };
+        proposal.vote(1_000, true, 0).unwrap();
+        proposal.vote(1_000, false, 0).unwrap();
+        assert_eq!(
+            proposal.execute(0, VOTING_PERIOD),
+            Err(SwapError::ProposalRejected.into())
+        );
+    }
+
+    #[test]
+    fn test_invalid_proposal() {
+        // fees taking the whole amount
+        let fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 1,
+            ..DEFAULT_TEST_FEES
+        };
+        assert_eq!(
+            Proposal::default().init(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                &fees,
+                &DEFAULT_TEST_REWARDS,
+                0,
+            ),
+            Err(SwapError::InvalidInput.into())
+        );
+        let rewards = Rewards {
+            liquidity_reward_denominator: 0,
+            ..DEFAULT_TEST_REWARDS
+        };
+        assert_eq!(
+            Proposal::default().init(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                &DEFAULT_TEST_FEES,
+                &rewards,
+                0,
+            ),
+            Err(SwapError::InvalidInput.into())
+        );
+
+        // nor executed when passed with invalid fees
+        let mut proposal = Proposal {
+            fees,
+            rewards: DEFAULT_TEST_REWARDS,
+            voting_end_ts: VOTING_PERIOD,
+            ..Proposal::default()
+        };
+        proposal.vote(1_000, true, 0).unwrap();
+        assert_eq!(
+            proposal.execute(0, VOTING_PERIOD),
+            Err(SwapError::InvalidInput.into())
+        );
+        assert!(!proposal.is_executed);
+    }
+}
@@ -9,24 +9,40 @@ use solana_program::{
 
 use crate::{
     error::SwapError,
-    math::{Decimal, TryDiv, TryMul},
-    state::unpack_bool,
+    math::{Decimal, TryDiv, TryMul, TrySub},
+    state::{pack_decimal, unpack_bool, unpack_decimal},
 };
 
 use std::convert::TryFrom;
 
 /// Max number of positions
 pub const MAX_LIQUIDITY_POSITIONS: usize = 10;
+/// Max number of positions of a grown liquidity provider account
+pub const MAX_GROWN_LIQUIDITY_POSITIONS: usize = u8::MAX as usize;
 /// Min period towards next claim
 pub const MIN_CLAIM_PERIOD: UnixTimestamp = 2592000;
+/// Seed of the liquidity provider address, derived with the owner key
+pub const LIQUIDITY_PROVIDER_SEED: &[u8] = b"liquidity_provider";
+
+/// Find the liquidity provider address of an owner
+pub fn find_liquidity_provider_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[owner.as_ref(), LIQUIDITY_PROVIDER_SEED], program_id)
+}
 
 /// Liquidity user info
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LiquidityProvider {
     /// Initialization status
     pub is_initialized: bool,
     /// Owner authority
     pub owner: Pubkey,
+    /// Delegate allowed to deposit, withdraw and claim on behalf of the owner,
+    /// default pubkey for none
+    pub delegate: Pubkey,
+    /// Referrer minted a slice of the rewards claimed, set at initialization,
+    /// default pubkey for none
+    pub referrer: Pubkey,
     /// Liquidity positions owned by this user
     pub positions: Vec<LiquidityPosition>,
 }
@@ -57,9 +73,22 @@ impl LiquidityProvider {
     pub fn init(&mut self, owner: Pubkey, positions: Vec<LiquidityPosition>) {
         self.is_initialized = true;
         self.owner = owner;
+        self.delegate = Pubkey::default();
+        self.referrer = Pubkey::default();
         self.positions = positions;
     }
 
+    /// Check if an authority may operate the positions, either the owner or
+    /// the delegate if one is set
+    ///
+    /// # Arguments
+    ///
+    /// * authority - authority address.
+    pub fn is_authority(&self, authority: &Pubkey) -> bool {
+        *authority == self.owner
+            || (self.delegate != Pubkey::default() && *authority == self.delegate)
+    }
+
     /// Find position by pool
     ///
     /// # Arguments
@@ -135,7 +164,10 @@ impl LiquidityProvider {
     /// withdraw status
     pub fn withdraw(&mut self, withdraw_amount: u64, position_index: usize) -> ProgramResult {
         let position = &mut self.positions[position_index];
-        if withdraw_amount == position.liquidity_amount && position.rewards_owed == 0 {
+        if withdraw_amount == position.liquidity_amount
+            && position.rewards_owed == 0
+            && position.rewards_estimated == 0
+        {
             self.positions.remove(position_index);
         } else {
             position.withdraw(withdraw_amount)?;
@@ -143,6 +175,110 @@ impl LiquidityProvider {
         Ok(())
     }
 
+    /// Withdraw all the liquidity of a position without settling its rewards,
+    /// the position is removed with the rewards it was owed
+    ///
+    /// # Arguments
+    ///
+    /// * pool - pool address.
+    ///
+    /// # Return value
+    ///
+    /// withdrawn liquidity amount
+    pub fn emergency_withdraw(&mut self, pool: Pubkey) -> Result<u64, ProgramError> {
+        let (position, position_index) = self.find_position(pool)?;
+        let liquidity_amount = position.liquidity_amount;
+        if liquidity_amount == 0 {
+            return Err(SwapError::InsufficientLiquidity.into());
+        }
+        self.positions.remove(position_index);
+        Ok(liquidity_amount)
+    }
+
+    /// Add a position moved from another liquidity provider, merging it into
+    /// the position of the same pool if there is one
+    ///
+    /// # Arguments
+    ///
+    /// * position - position to add.
+    ///
+    /// # Return value
+    ///
+    /// add status
+    pub fn add_position(&mut self, position: LiquidityPosition) -> ProgramResult {
+        match self.find_position_index(position.pool) {
+            Some(position_index) => self.positions[position_index].merge(&position),
+            None => {
+                self.positions.push(position);
+                Ok(())
+            }
+        }
+    }
+
+    /// Check no position holds liquidity or unclaimed rewards
+    pub fn is_empty(&self) -> bool {
+        self.positions
+            .iter()
+            .all(|position| position.liquidity_amount == 0 && position.rewards_owed == 0)
+    }
+
+    /// Number of positions an account of `data_len` bytes can hold
+    pub fn max_positions(data_len: usize) -> usize {
+        data_len.saturating_sub(LIQUIDITY_PROVIDER_HEADER_SIZE) / LIQUIDITY_POSITION_SIZE
+    }
+
+    /// Size of an account holding `max_positions` positions
+    pub fn account_len(max_positions: usize) -> usize {
+        LIQUIDITY_PROVIDER_HEADER_SIZE + LIQUIDITY_POSITION_SIZE * max_positions
+    }
+
+    /// Unpacks a liquidity provider account, grown or not
+    pub fn unpack_account(input: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_account_len(input.len())?;
+        let provider = Self::unpack_from_slice(input)?;
+        if !provider.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(provider)
+    }
+
+    /// Unpacks the referrer of a liquidity provider account, without its positions
+    pub fn unpack_referrer(input: &[u8]) -> Result<Option<Pubkey>, ProgramError> {
+        let input = input
+            .get(..LIQUIDITY_PROVIDER_HEADER_SIZE)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let input = array_ref![input, 0, LIQUIDITY_PROVIDER_HEADER_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (_, _, _, referrer, _) =
+            array_refs![input, 1, PUBKEY_BYTES, PUBKEY_BYTES, PUBKEY_BYTES, 1];
+        let referrer = Pubkey::new_from_array(*referrer);
+        Ok(if referrer == Pubkey::default() {
+            None
+        } else {
+            Some(referrer)
+        })
+    }
+
+    /// Packs a liquidity provider account, grown or not
+    pub fn pack_account(src: Self, dst: &mut [u8]) -> ProgramResult {
+        Self::check_account_len(dst.len())?;
+        if src.positions.len() > Self::max_positions(dst.len()) {
+            return Err(SwapError::LiquidityPositionsFull.into());
+        }
+        src.pack_into_slice(dst);
+        Ok(())
+    }
+
+    fn check_account_len(len: usize) -> ProgramResult {
+        if len < LIQUIDITY_PROVIDER_SIZE
+            || len > Self::account_len(MAX_GROWN_LIQUIDITY_POSITIONS)
+            || Self::account_len(Self::max_positions(len)) != len
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
     /// Claim rewards in corresponding position
     ///
     /// # Arguments
@@ -160,10 +296,39 @@ impl LiquidityProvider {
         }
         Ok(claimed_amount)
     }
+
+    /// Claim rewards of the positions in several pools, pools without a
+    /// position or without rewards owed are skipped
+    ///
+    /// # Arguments
+    ///
+    /// * pools - pool addresses.
+    ///
+    /// # Return value
+    ///
+    /// total claimed amount
+    pub fn claim_all(&mut self, pools: &[Pubkey]) -> Result<u64, ProgramError> {
+        let mut claimed_amount: u64 = 0;
+        for pool in pools {
+            if matches!(
+                self.find_position_index(*pool),
+                Some(position_index) if self.positions[position_index].rewards_owed > 0
+            ) {
+                claimed_amount = claimed_amount
+                    .checked_add(self.claim(*pool)?)
+                    .ok_or(SwapError::CalculationFailure)?;
+            }
+        }
+        if claimed_amount == 0 {
+            return Err(SwapError::InsufficientClaimAmount.into());
+        }
+        Ok(claimed_amount)
+    }
 }
 
 /// Liquidity position of a pool
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LiquidityPosition {
     /// Swap pool address
     pub pool: Pubkey,
@@ -175,6 +340,8 @@ pub struct LiquidityPosition {
     pub rewards_estimated: u64,
     /// Cumulative interest
     pub cumulative_interest: u64,
+    /// Rewards per pool token of the pool when the position was last settled
+    pub reward_per_share_paid: Decimal,
     /// Last updated timestamp
     pub last_update_ts: UnixTimestamp,
     /// Next claim timestamp
@@ -199,6 +366,7 @@ impl LiquidityPosition {
             rewards_owed: 0,
             rewards_estimated: 0,
             cumulative_interest: 0,
+            reward_per_share_paid: Decimal::zero(),
             last_update_ts: current_ts,
             next_claim_ts: current_ts
                 .checked_add(MIN_CLAIM_PERIOD)
@@ -243,6 +411,64 @@ impl LiquidityPosition {
         Ok(())
     }
 
+    /// Merge another position of the same pool into this one. Rewards are
+    /// accrued up to the last settlement, so both must be settled at the same
+    /// rewards per share. The later next claim timestamp is kept
+    ///
+    /// # Arguments
+    ///
+    /// * other - position to merge.
+    ///
+    /// # Return value
+    ///
+    /// merge status
+    pub fn merge(&mut self, other: &Self) -> ProgramResult {
+        if self.pool != other.pool {
+            return Err(SwapError::InvalidPositionKey.into());
+        }
+        if self.reward_per_share_paid != other.reward_per_share_paid {
+            return Err(SwapError::PositionsNotRefreshed.into());
+        }
+        self.liquidity_amount = self
+            .liquidity_amount
+            .checked_add(other.liquidity_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.rewards_owed = self
+            .rewards_owed
+            .checked_add(other.rewards_owed)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.rewards_estimated = self
+            .rewards_estimated
+            .checked_add(other.rewards_estimated)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.cumulative_interest = self
+            .cumulative_interest
+            .checked_add(other.cumulative_interest)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.last_update_ts = self.last_update_ts.max(other.last_update_ts);
+        self.next_claim_ts = self.next_claim_ts.max(other.next_claim_ts);
+        Ok(())
+    }
+
+    /// Liquidity held since the last update, in pool token seconds
+    ///
+    /// # Arguments
+    ///
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// liquidity amount times the seconds elapsed since the last update.
+    pub fn liquidity_seconds(&self, current_ts: UnixTimestamp) -> Result<u128, ProgramError> {
+        let time_elapsed = current_ts
+            .checked_sub(self.last_update_ts)
+            .ok_or(SwapError::CalculationFailure)?
+            .max(0);
+        (self.liquidity_amount as u128)
+            .checked_mul(time_elapsed as u128)
+            .ok_or_else(|| SwapError::CalculationFailure.into())
+    }
+
     /// Update next claim timestamp
     ///
     /// # Return value
@@ -258,11 +484,34 @@ impl LiquidityPosition {
         Ok(())
     }
 
-    /// Calculate and update rewards
+    /// Settle the rewards earned since the last settlement into the
+    /// estimated rewards
     ///
     /// # Arguments
     ///
-    /// * rewards_ratio - rewards ratio calculated by lp token and deltafi token price.
+    /// * reward_per_share - rewards per pool token accumulated by the pool.
+    ///
+    /// # Return value
+    ///
+    /// settle status
+    pub fn settle_rewards(&mut self, reward_per_share: Decimal) -> ProgramResult {
+        self.rewards_estimated = reward_per_share
+            .try_sub(self.reward_per_share_paid)?
+            .try_mul(self.liquidity_amount)?
+            .try_div(MIN_CLAIM_PERIOD as u64)?
+            .try_floor_u64()?
+            .checked_add(self.rewards_estimated)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.reward_per_share_paid = reward_per_share;
+        Ok(())
+    }
+
+    /// Settle rewards and move the estimated rewards to the owed ones once
+    /// the claim period is over
+    ///
+    /// # Arguments
+    ///
+    /// * reward_per_share - rewards per pool token accumulated by the pool.
     /// * current_ts - current unix timestamp.
     ///
     /// # Return value
@@ -270,23 +519,11 @@ impl LiquidityPosition {
     /// reward update status
     pub fn calc_and_update_rewards(
         &mut self,
-        rewards_ratio: Decimal,
+        reward_per_share: Decimal,
         current_ts: UnixTimestamp,
     ) -> ProgramResult {
-        let calc_period = current_ts
-            .checked_sub(self.last_update_ts)
-            .ok_or(SwapError::CalculationFailure)?;
-        if calc_period > 0 {
-            self.rewards_estimated = rewards_ratio
-                .try_mul(self.liquidity_amount)?
-                .try_div(u64::try_from(MIN_CLAIM_PERIOD).unwrap())?
-                .try_mul(u64::try_from(calc_period).unwrap())?
-                .try_floor_u64()?
-                .checked_add(self.rewards_estimated)
-                .ok_or(SwapError::CalculationFailure)?;
-
-            self.last_update_ts = current_ts;
-        }
+        self.settle_rewards(reward_per_share)?;
+        self.last_update_ts = self.last_update_ts.max(current_ts);
 
         if current_ts >= self.next_claim_ts {
             self.rewards_owed = self
@@ -326,106 +563,126 @@ impl IsInitialized for LiquidityProvider {
 }
 
 #[doc(hidden)]
-const LIQUIDITY_POSITION_SIZE: usize = 80; // 32 + 8 + 8 + 8 + 8 + 8 + 8
-const LIQUIDITY_PROVIDER_SIZE: usize = 834; // 1 + 32 + 1 + (80 * 10)
+pub(super) const LIQUIDITY_POSITION_SIZE: usize = 96; // 32 + 8 + 8 + 8 + 8 + 16 + 8 + 8
+pub(super) const LIQUIDITY_PROVIDER_HEADER_SIZE: usize = 98; // 1 + 32 + 32 + 32 + 1
+const LIQUIDITY_PROVIDER_SIZE: usize = 1058; // 1 + 32 + 32 + 32 + 1 + (96 * 10)
 
 impl Pack for LiquidityProvider {
     const LEN: usize = LIQUIDITY_PROVIDER_SIZE;
 
     fn pack_into_slice(&self, output: &mut [u8]) {
-        let output = array_mut_ref![output, 0, LIQUIDITY_PROVIDER_SIZE];
+        // grown accounts keep more positions after the default ones
+        let (output, data_flat) = output.split_at_mut(LIQUIDITY_PROVIDER_HEADER_SIZE);
+        let output = array_mut_ref![output, 0, LIQUIDITY_PROVIDER_HEADER_SIZE];
         #[allow(clippy::ptr_offset_with_cast)]
-        let (is_initialized, owner, positions_len, data_flat) = mut_array_refs![
-            output,
-            1,
-            PUBKEY_BYTES,
-            1,
-            LIQUIDITY_POSITION_SIZE * MAX_LIQUIDITY_POSITIONS
-        ];
+        let (is_initialized, owner, delegate, referrer, positions_len) =
+            mut_array_refs![output, 1, PUBKEY_BYTES, PUBKEY_BYTES, PUBKEY_BYTES, 1];
         is_initialized[0] = self.is_initialized as u8;
         owner.copy_from_slice(self.owner.as_ref());
+        delegate.copy_from_slice(self.delegate.as_ref());
+        referrer.copy_from_slice(self.referrer.as_ref());
         *positions_len = u8::try_from(self.positions.len()).unwrap().to_le_bytes();
 
-        let mut offset = 0;
-        for position in &self.positions {
-            let position_flat = array_mut_ref![data_flat, offset, LIQUIDITY_POSITION_SIZE];
-            #[allow(clippy::ptr_offset_with_cast)]
-            let (
-                pool,
-                liquidity_amount,
-                rewards_owed,
-                rewards_estimated,
-                cumulative_interest,
-                last_update_ts,
-                next_claim_ts,
-            ) = mut_array_refs![position_flat, PUBKEY_BYTES, 8, 8, 8, 8, 8, 8];
-
-            pool.copy_from_slice(position.pool.as_ref());
-            *liquidity_amount = position.liquidity_amount.to_le_bytes();
-            *rewards_owed = position.rewards_owed.to_le_bytes();
-            *rewards_estimated = position.rewards_estimated.to_le_bytes();
-            *cumulative_interest = position.cumulative_interest.to_le_bytes();
-            *last_update_ts = position.last_update_ts.to_le_bytes();
-            *next_claim_ts = position.next_claim_ts.to_le_bytes();
-            offset += LIQUIDITY_POSITION_SIZE;
-        }
+        pack_positions(&self.positions, data_flat);
     }
 
     fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
-        let input = array_ref![input, 0, LIQUIDITY_PROVIDER_SIZE];
+        if input.len() < LIQUIDITY_PROVIDER_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (input, data_flat) = input.split_at(LIQUIDITY_PROVIDER_HEADER_SIZE);
+        let input = array_ref![input, 0, LIQUIDITY_PROVIDER_HEADER_SIZE];
         #[allow(clippy::ptr_offset_with_cast)]
-        let (is_initialized, owner, positions_len, data_flat) = array_refs![
-            input,
-            1,
-            PUBKEY_BYTES,
-            1,
-            LIQUIDITY_POSITION_SIZE * MAX_LIQUIDITY_POSITIONS
-        ];
+        let (is_initialized, owner, delegate, referrer, positions_len) =
+            array_refs![input, 1, PUBKEY_BYTES, PUBKEY_BYTES, PUBKEY_BYTES, 1];
 
         let is_initialized = unpack_bool(is_initialized)?;
         let positions_len = u8::from_le_bytes(*positions_len);
-        let mut positions = Vec::with_capacity(positions_len as usize + 1);
-
-        let mut offset = 0;
-        for _ in 0..positions_len {
-            let positions_flat = array_ref![data_flat, offset, LIQUIDITY_POSITION_SIZE];
-            #[allow(clippy::ptr_offset_with_cast)]
-            let (
-                pool,
-                liquidity_amount,
-                rewards_owed,
-                rewards_estimated,
-                cumulative_interest,
-                last_update_ts,
-                next_claim_ts,
-            ) = array_refs![positions_flat, PUBKEY_BYTES, 8, 8, 8, 8, 8, 8];
-            positions.push(LiquidityPosition {
-                pool: Pubkey::new(pool),
-                liquidity_amount: u64::from_le_bytes(*liquidity_amount),
-                rewards_owed: u64::from_le_bytes(*rewards_owed),
-                rewards_estimated: u64::from_le_bytes(*rewards_estimated),
-                cumulative_interest: u64::from_le_bytes(*cumulative_interest),
-                last_update_ts: i64::from_le_bytes(*last_update_ts),
-                next_claim_ts: i64::from_le_bytes(*next_claim_ts),
-            });
-            offset += LIQUIDITY_POSITION_SIZE;
+        if positions_len as usize > data_flat.len() / LIQUIDITY_POSITION_SIZE {
+            return Err(ProgramError::InvalidAccountData);
         }
+        let positions = unpack_positions(positions_len as usize, data_flat);
         Ok(Self {
             is_initialized,
             owner: Pubkey::new(owner),
+            delegate: Pubkey::new(delegate),
+            referrer: Pubkey::new_from_array(*referrer),
             positions,
         })
     }
 }
 
+/// Pack positions one after another
+pub(super) fn pack_positions(positions: &[LiquidityPosition], data_flat: &mut [u8]) {
+    let mut offset = 0;
+    for position in positions {
+        let position_flat = array_mut_ref![data_flat, offset, LIQUIDITY_POSITION_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            pool,
+            liquidity_amount,
+            rewards_owed,
+            rewards_estimated,
+            cumulative_interest,
+            reward_per_share_paid,
+            last_update_ts,
+            next_claim_ts,
+        ) = mut_array_refs![position_flat, PUBKEY_BYTES, 8, 8, 8, 8, 16, 8, 8];
+
+        pool.copy_from_slice(position.pool.as_ref());
+        *liquidity_amount = position.liquidity_amount.to_le_bytes();
+        *rewards_owed = position.rewards_owed.to_le_bytes();
+        *rewards_estimated = position.rewards_estimated.to_le_bytes();
+        *cumulative_interest = position.cumulative_interest.to_le_bytes();
+        pack_decimal(position.reward_per_share_paid, reward_per_share_paid);
+        *last_update_ts = position.last_update_ts.to_le_bytes();
+        *next_claim_ts = position.next_claim_ts.to_le_bytes();
+        offset += LIQUIDITY_POSITION_SIZE;
+    }
+}
+
+/// Unpack `positions_len` positions packed one after another
+pub(super) fn unpack_positions(positions_len: usize, data_flat: &[u8]) -> Vec<LiquidityPosition> {
+    let mut positions = Vec::with_capacity(positions_len + 1);
+
+    let mut offset = 0;
+    for _ in 0..positions_len {
+        let positions_flat = array_ref![data_flat, offset, LIQUIDITY_POSITION_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            pool,
+            liquidity_amount,
+            rewards_owed,
+            rewards_estimated,
+            cumulative_interest,
+            reward_per_share_paid,
+            last_update_ts,
+            next_claim_ts,
+        ) = array_refs![positions_flat, PUBKEY_BYTES, 8, 8, 8, 8, 16, 8, 8];
+        positions.push(LiquidityPosition {
+            pool: Pubkey::new(pool),
+            liquidity_amount: u64::from_le_bytes(*liquidity_amount),
+            rewards_owed: u64::from_le_bytes(*rewards_owed),
+            rewards_estimated: u64::from_le_bytes(*rewards_estimated),
+            cumulative_interest: u64::from_le_bytes(*cumulative_interest),
+            reward_per_share_paid: unpack_decimal(reward_per_share_paid),
+            last_update_ts: i64::from_le_bytes(*last_update_ts),
+            next_claim_ts: i64::from_le_bytes(*next_claim_ts),
+        });
+        offset += LIQUIDITY_POSITION_SIZE;
+    }
+    positions
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{math::*, solana_program::clock::Clock};
+    use crate::{math::*, solana_program::clock::Clock, state::RewardAccumulator};
     use proptest::prelude::*;
 
     const REFRESH_PERIOD: i64 = 3600;
     const REFRESH_TIMES: i64 = 720;
+    const SETTLE_EVERY: i64 = 24;
 
     prop_compose! {
         fn liquidity_amount_and_ratio()(amount in 0..=u32::MAX)(
@@ -452,14 +709,23 @@ mod tests {
             let max_period_amount = liquidity_amount / rewards_rate;
             let min_period_amount = max_period_amount - max_period_amount / 1_000;
 
+            // the pool accumulator starts a second after the position
+            let mut reward_accumulator = RewardAccumulator {
+                last_update_ts: 1,
+                ..Default::default()
+            };
             for i in 1..=REFRESH_TIMES * period_number {
-                liquidity_position
-                    .calc_and_update_rewards(
-                        Decimal::from_scaled_val(exact_rate),
-                        i * REFRESH_PERIOD,
-                    )
+                let current_ts = 1 + i * REFRESH_PERIOD;
+                reward_accumulator = reward_accumulator
+                    .accumulate(Decimal::from_scaled_val(exact_rate), current_ts)
                     .unwrap();
-                assert!(liquidity_position.rewards_estimated < max_period_amount);
+                // the position only settles on its own deposits, withdrawals and claims
+                if i % SETTLE_EVERY == 0 {
+                    liquidity_position
+                        .calc_and_update_rewards(reward_accumulator.reward_per_share, current_ts)
+                        .unwrap();
+                    assert!(liquidity_position.rewards_estimated < max_period_amount);
+                }
             }
             assert!(liquidity_position.rewards_owed <= max_period_amount * period_number as u64);
             // 0.01% confidence
@@ -468,6 +734,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_liquidity_seconds() {
+        let position = LiquidityPosition {
+            liquidity_amount: 1_000,
+            last_update_ts: 100,
+            ..Default::default()
+        };
+        assert_eq!(position.liquidity_seconds(100).unwrap(), 0);
+        assert_eq!(position.liquidity_seconds(160).unwrap(), 60_000);
+        // a clock behind the last update accrues nothing
+        assert_eq!(position.liquidity_seconds(40).unwrap(), 0);
+
+        let position = LiquidityPosition {
+            liquidity_amount: u64::MAX,
+            last_update_ts: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            position.liquidity_seconds(i64::MAX).unwrap(),
+            u64::MAX as u128 * i64::MAX as u128
+        );
+    }
+
     #[test]
     fn test_failures() {
         let mut position = LiquidityPosition {
@@ -518,6 +807,7 @@ mod tests {
         let rewards_owed_1: u64 = 100;
         let rewards_estimated_1: u64 = 40;
         let cumulative_interest_1: u64 = 1000;
+        let reward_per_share_paid_1 = Decimal::from_scaled_val(1_000);
         let last_update_ts_1 = Clock::clone(&Default::default()).unix_timestamp;
         let next_claim_ts_1 = last_update_ts_1 + MIN_CLAIM_PERIOD;
 
@@ -527,6 +817,7 @@ mod tests {
             rewards_owed: rewards_owed_1,
             rewards_estimated: rewards_estimated_1,
             cumulative_interest: cumulative_interest_1,
+            reward_per_share_paid: reward_per_share_paid_1,
             last_update_ts: last_update_ts_1,
             next_claim_ts: next_claim_ts_1,
         };
@@ -537,6 +828,7 @@ mod tests {
         let rewards_owed_2: u64 = 200;
         let rewards_estimated_2: u64 = 80;
         let cumulative_interest_2: u64 = 2000;
+        let reward_per_share_paid_2 = Decimal::from_scaled_val(2_000);
         let last_update_ts_2 = Clock::clone(&Default::default()).unix_timestamp + 300;
         let next_claim_ts_2 = last_update_ts_2 + MIN_CLAIM_PERIOD;
 
@@ -546,13 +838,20 @@ mod tests {
             rewards_owed: rewards_owed_2,
             rewards_estimated: rewards_estimated_2,
             cumulative_interest: cumulative_interest_2,
+            reward_per_share_paid: reward_per_share_paid_2,
             last_update_ts: last_update_ts_2,
             next_claim_ts: next_claim_ts_2,
         };
 
+        let delegate_key_raw = [4u8; 32];
+        let delegate = Pubkey::new_from_array(delegate_key_raw);
+        let referrer_key_raw = [5u8; 32];
+        let referrer = Pubkey::new_from_array(referrer_key_raw);
         let liquidity_provider = LiquidityProvider {
             is_initialized,
             owner,
+            delegate,
+            referrer,
             positions: vec![position_1, position_2],
         };
 
@@ -560,15 +859,27 @@ mod tests {
         LiquidityProvider::pack_into_slice(&liquidity_provider, &mut packed);
         let unpacked = LiquidityProvider::unpack(&packed).unwrap();
         assert_eq!(liquidity_provider, unpacked);
+        assert_eq!(
+            LiquidityProvider::unpack_referrer(&packed).unwrap(),
+            Some(referrer)
+        );
 
         let mut packed: Vec<u8> = vec![1];
         packed.extend_from_slice(&owner_key_raw);
+        packed.extend_from_slice(&delegate_key_raw);
+        packed.extend_from_slice(&referrer_key_raw);
         packed.extend_from_slice(&(2u8).to_le_bytes());
         packed.extend_from_slice(&pool_1_key_raw);
         packed.extend_from_slice(&liquidity_amount_1.to_le_bytes());
         packed.extend_from_slice(&rewards_owed_1.to_le_bytes());
         packed.extend_from_slice(&rewards_estimated_1.to_le_bytes());
         packed.extend_from_slice(&cumulative_interest_1.to_le_bytes());
+        packed.extend_from_slice(
+            &reward_per_share_paid_1
+                .to_scaled_val()
+                .unwrap()
+                .to_le_bytes(),
+        );
         packed.extend_from_slice(&last_update_ts_1.to_le_bytes());
         packed.extend_from_slice(&next_claim_ts_1.to_le_bytes());
         packed.extend_from_slice(&pool_2_key_raw);
@@ -576,6 +887,12 @@ mod tests {
         packed.extend_from_slice(&rewards_owed_2.to_le_bytes());
         packed.extend_from_slice(&rewards_estimated_2.to_le_bytes());
         packed.extend_from_slice(&cumulative_interest_2.to_le_bytes());
+        packed.extend_from_slice(
+            &reward_per_share_paid_2
+                .to_scaled_val()
+                .unwrap()
+                .to_le_bytes(),
+        );
         packed.extend_from_slice(&last_update_ts_2.to_le_bytes());
         packed.extend_from_slice(&next_claim_ts_2.to_le_bytes());
 
@@ -588,7 +905,217 @@ mod tests {
         let liquidity_provider: LiquidityProvider = Default::default();
         let unpack_unchecked = LiquidityProvider::unpack_unchecked(&packed).unwrap();
         assert_eq!(unpack_unchecked, liquidity_provider);
+        assert_eq!(LiquidityProvider::unpack_referrer(&packed).unwrap(), None);
         let err = LiquidityProvider::unpack(&packed).unwrap_err();
         assert_eq!(err, ProgramError::UninitializedAccount);
     }
+
+    #[test]
+    fn test_liquidity_provider_is_empty() {
+        let pool = Pubkey::new_unique();
+        let mut liquidity_provider = LiquidityProvider::new(Pubkey::new_unique(), vec![]);
+        assert!(liquidity_provider.is_empty());
+
+        liquidity_provider
+            .find_or_add_position(pool, 0)
+            .unwrap()
+            .deposit(100)
+            .unwrap();
+        assert!(!liquidity_provider.is_empty());
+
+        let (position, _) = liquidity_provider.find_position(pool).unwrap();
+        position.withdraw(100).unwrap();
+        position.rewards_owed = 10;
+        assert!(!liquidity_provider.is_empty());
+
+        liquidity_provider.claim(pool).unwrap();
+        assert!(liquidity_provider.is_empty());
+    }
+
+    #[test]
+    fn test_emergency_withdraw() {
+        let pool = Pubkey::new_unique();
+        let other_pool = Pubkey::new_unique();
+        let mut liquidity_provider = LiquidityProvider::new(Pubkey::new_unique(), vec![]);
+        liquidity_provider
+            .find_or_add_position(other_pool, 0)
+            .unwrap()
+            .deposit(50)
+            .unwrap();
+        let position = liquidity_provider.find_or_add_position(pool, 0).unwrap();
+        position.deposit(100).unwrap();
+        position.rewards_owed = 10;
+        position.rewards_estimated = 20;
+
+        // the rewards are forfeited with the position
+        assert_eq!(liquidity_provider.emergency_withdraw(pool).unwrap(), 100);
+        assert_eq!(liquidity_provider.find_position_index(pool), None);
+        assert_eq!(liquidity_provider.positions.len(), 1);
+        assert_eq!(
+            liquidity_provider.emergency_withdraw(pool).unwrap_err(),
+            SwapError::InvalidPositionKey.into()
+        );
+
+        let (position, _) = liquidity_provider.find_position(other_pool).unwrap();
+        position.withdraw(50).unwrap();
+        assert_eq!(
+            liquidity_provider
+                .emergency_withdraw(other_pool)
+                .unwrap_err(),
+            SwapError::InsufficientLiquidity.into()
+        );
+    }
+
+    #[test]
+    fn test_settle_rewards() {
+        let reward_per_share = |rewards: u64| Decimal::from(rewards * MIN_CLAIM_PERIOD as u64);
+        let mut position = LiquidityPosition::new(Pubkey::new_unique(), 0).unwrap();
+        // a new position earns nothing of the rewards accumulated before it
+        position.settle_rewards(reward_per_share(5)).unwrap();
+        assert_eq!(position.rewards_estimated, 0);
+
+        position.deposit(100).unwrap();
+        position.settle_rewards(reward_per_share(7)).unwrap();
+        assert_eq!(position.rewards_estimated, 200);
+        assert_eq!(position.reward_per_share_paid, reward_per_share(7));
+
+        // estimated rewards are owed once the claim period is over
+        position
+            .calc_and_update_rewards(reward_per_share(8), MIN_CLAIM_PERIOD)
+            .unwrap();
+        assert_eq!(position.rewards_estimated, 0);
+        assert_eq!(position.rewards_owed, 300);
+        assert_eq!(position.last_update_ts, MIN_CLAIM_PERIOD);
+        assert_eq!(position.next_claim_ts, MIN_CLAIM_PERIOD * 2);
+
+        assert_eq!(
+            position.settle_rewards(Decimal::one()),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_claim_all() {
+        let pools: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let mut liquidity_provider = LiquidityProvider::new(Pubkey::new_unique(), vec![]);
+        for (pool, rewards_owed) in pools.iter().zip([100, 0, 50]) {
+            let position = liquidity_provider.find_or_add_position(*pool, 0).unwrap();
+            position.deposit(1_000).unwrap();
+            position.rewards_owed = rewards_owed;
+        }
+
+        // unknown and repeated pools are skipped
+        let claim_pools = [pools[0], pools[1], Pubkey::new_unique(), pools[2], pools[0]];
+        assert_eq!(liquidity_provider.claim_all(&claim_pools).unwrap(), 150);
+        assert!(liquidity_provider
+            .positions
+            .iter()
+            .all(|position| position.rewards_owed == 0));
+        assert_eq!(liquidity_provider.positions[0].cumulative_interest, 100);
+        assert_eq!(liquidity_provider.positions[2].cumulative_interest, 50);
+
+        assert_eq!(
+            liquidity_provider.claim_all(&claim_pools),
+            Err(SwapError::InsufficientClaimAmount.into())
+        );
+    }
+
+    #[test]
+    fn test_is_authority() {
+        let owner = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let mut liquidity_provider = LiquidityProvider::new(owner, vec![]);
+        assert!(liquidity_provider.is_authority(&owner));
+        assert!(!liquidity_provider.is_authority(&delegate));
+        assert!(!liquidity_provider.is_authority(&Pubkey::default()));
+
+        liquidity_provider.delegate = delegate;
+        assert!(liquidity_provider.is_authority(&owner));
+        assert!(liquidity_provider.is_authority(&delegate));
+        assert!(!liquidity_provider.is_authority(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_add_position() {
+        let pool = Pubkey::new_unique();
+        let mut position = LiquidityPosition::new(pool, 0).unwrap();
+        position.deposit(100).unwrap();
+        position.rewards_owed = 10;
+        position.rewards_estimated = 5;
+
+        let mut liquidity_provider = LiquidityProvider::new(Pubkey::new_unique(), vec![]);
+        liquidity_provider.add_position(position.clone()).unwrap();
+        assert_eq!(liquidity_provider.positions, vec![position.clone()]);
+
+        let mut later = position.clone();
+        later.next_claim_ts += 1;
+        liquidity_provider.add_position(later.clone()).unwrap();
+        assert_eq!(
+            liquidity_provider.positions,
+            vec![LiquidityPosition {
+                liquidity_amount: 200,
+                rewards_owed: 20,
+                rewards_estimated: 10,
+                next_claim_ts: later.next_claim_ts,
+                ..position.clone()
+            }]
+        );
+
+        let mut stale = position.clone();
+        stale.reward_per_share_paid = Decimal::one();
+        assert_eq!(
+            liquidity_provider.add_position(stale),
+            Err(SwapError::PositionsNotRefreshed.into())
+        );
+        assert_eq!(
+            liquidity_provider.positions[0]
+                .merge(&LiquidityPosition::new(Pubkey::new_unique(), 0).unwrap()),
+            Err(SwapError::InvalidPositionKey.into())
+        );
+    }
+
+    #[test]
+    fn test_grown_liquidity_provider_packing() {
+        let mut liquidity_provider = LiquidityProvider::default();
+        liquidity_provider.init(Pubkey::new_unique(), vec![]);
+        for _ in 0..=MAX_LIQUIDITY_POSITIONS {
+            liquidity_provider
+                .find_or_add_position(Pubkey::new_unique(), 0)
+                .unwrap()
+                .deposit(100)
+                .unwrap();
+        }
+
+        let mut packed = vec![0u8; LiquidityProvider::LEN];
+        assert_eq!(
+            LiquidityProvider::pack_account(liquidity_provider.clone(), &mut packed).unwrap_err(),
+            SwapError::LiquidityPositionsFull.into()
+        );
+
+        let new_len = LiquidityProvider::account_len(MAX_LIQUIDITY_POSITIONS + 1);
+        assert_eq!(LiquidityProvider::max_positions(new_len), 11);
+        packed.resize(new_len, 0);
+        LiquidityProvider::pack_account(liquidity_provider.clone(), &mut packed).unwrap();
+        let unpacked = LiquidityProvider::unpack_account(&packed).unwrap();
+        assert_eq!(liquidity_provider, unpacked);
+
+        // default sized accounts still unpack
+        let mut packed = vec![0u8; LiquidityProvider::LEN];
+        liquidity_provider.positions.truncate(1);
+        LiquidityProvider::pack_account(liquidity_provider.clone(), &mut packed).unwrap();
+        assert_eq!(
+            LiquidityProvider::unpack_account(&packed).unwrap(),
+            liquidity_provider
+        );
+
+        assert_eq!(
+            LiquidityProvider::unpack_account(&packed[..LiquidityProvider::LEN - 1]).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+        packed.push(0);
+        assert_eq!(
+            LiquidityProvider::unpack_account(&packed).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
 }
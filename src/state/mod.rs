@@ -1,16 +1,40 @@
 //! State used in DeFi
 
 mod config;
+mod dca;
+mod deny_list;
 mod fees;
+mod gauge;
+mod governance;
 mod liquidity;
+mod observation;
+mod position_page;
+mod price_feed;
+mod quote;
+mod registry;
 mod rewards;
+mod signed_order;
+mod snapshot;
 mod swap;
+mod whitelist;
 
 pub use config::*;
+pub use dca::*;
+pub use deny_list::*;
 pub use fees::*;
+pub use gauge::*;
+pub use governance::*;
 pub use liquidity::*;
+pub use observation::*;
+pub use position_page::*;
+pub use price_feed::*;
+pub use quote::*;
+pub use registry::*;
 pub use rewards::*;
+pub use signed_order::*;
+pub use snapshot::*;
 pub use swap::*;
+pub use whitelist::*;
 
 pub use crate::math::Decimal;
 
@@ -43,6 +67,52 @@ pub fn unpack_bool(src: &[u8; 1]) -> Result<bool, ProgramError> {
     }
 }
 
+/// Implements Borsh (de)serialization with the packed layout of a state struct,
+/// so accounts can be decoded with `try_from_slice` as well as `unpack`.
+#[cfg(feature = "borsh")]
+macro_rules! impl_borsh_with_pack {
+    ($($state:ty),*) => {
+        $(
+            impl borsh::BorshSerialize for $state {
+                fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+                    let mut packed = [0u8; <$state as solana_program::program_pack::Pack>::LEN];
+                    solana_program::program_pack::Pack::pack_into_slice(self, &mut packed);
+                    writer.write_all(&packed)
+                }
+            }
+
+            impl borsh::BorshDeserialize for $state {
+                fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+                    let len = <$state as solana_program::program_pack::Pack>::LEN;
+                    if buf.len() < len {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "Unexpected length of input",
+                        ));
+                    }
+                    let (packed, rest) = buf.split_at(len);
+                    let state = solana_program::program_pack::Pack::unpack_from_slice(packed)
+                        .map_err(|err| {
+                            std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string())
+                        })?;
+                    *buf = rest;
+                    Ok(state)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "borsh")]
+impl_borsh_with_pack!(
+    ConfigInfo,
+    SwapInfo,
+    LiquidityProvider,
+    LiquidityPositionPage,
+    Fees,
+    Rewards
+);
+
 #[cfg(test)]
 /// Fees for testing
 pub const DEFAULT_TEST_FEES: Fees = Fees {
@@ -54,6 +124,7 @@ pub const DEFAULT_TEST_FEES: Fees = Fees {
     trade_fee_denominator: 100,
     withdraw_fee_numerator: 6,
     withdraw_fee_denominator: 100,
+    min_trade_fee: 0,
 };
 
 #[cfg(test)]
@@ -67,4 +138,66 @@ pub const DEFAULT_TEST_REWARDS: Rewards = Rewards {
 };
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_matches_pack() {
+        use super::*;
+        use borsh::{BorshDeserialize, BorshSerialize};
+        use solana_program::program_pack::Pack;
+
+        let swap_info = SwapInfo {
+            is_initialized: true,
+            fees: DEFAULT_TEST_FEES,
+            rewards: DEFAULT_TEST_REWARDS,
+            ..SwapInfo::default()
+        };
+        let mut packed = vec![0u8; SwapInfo::LEN];
+        SwapInfo::pack(swap_info.clone(), &mut packed).unwrap();
+        assert_eq!(swap_info.try_to_vec().unwrap(), packed);
+        assert_eq!(SwapInfo::try_from_slice(&packed).unwrap(), swap_info);
+
+        let fees = Fees::try_from_slice(&DEFAULT_TEST_FEES.try_to_vec().unwrap()).unwrap();
+        assert_eq!(fees, DEFAULT_TEST_FEES);
+
+        // trailing or missing bytes are rejected
+        packed.push(0);
+        assert!(SwapInfo::try_from_slice(&packed).is_err());
+        assert!(SwapInfo::try_from_slice(&packed[..SwapInfo::LEN - 1]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_serialize() {
+        use super::*;
+        use crate::math::Decimal;
+        use solana_program::pubkey::Pubkey;
+
+        let mut swap_info = SwapInfo {
+            is_initialized: true,
+            fees: DEFAULT_TEST_FEES,
+            rewards: DEFAULT_TEST_REWARDS,
+            ..SwapInfo::default()
+        };
+        swap_info.pool_state.market_price = Decimal::from(20u64);
+        let serialized = serde_yaml::to_string(&swap_info).unwrap();
+        assert!(serialized.contains("is_initialized: true"));
+        assert!(serialized.contains("trade_fee_denominator: 100"));
+        assert!(serialized.contains("market_price: \"20.000000000\""));
+        assert!(serialized.contains("multiplier: One"));
+
+        let owner = Pubkey::new_unique();
+        let liquidity_provider = LiquidityProvider::new(owner, vec![LiquidityPosition::default()]);
+        let serialized = serde_yaml::to_string(&liquidity_provider).unwrap();
+        assert!(serialized.contains("positions:"));
+        assert!(serialized.contains("liquidity_amount: 0"));
+
+        let config_info = ConfigInfo {
+            fees: DEFAULT_TEST_FEES,
+            ..ConfigInfo::default()
+        };
+        let serialized = serde_yaml::to_string(&config_info).unwrap();
+        assert!(serialized.contains("gauge_votes:"));
+        assert!(serialized.contains("tiers:"));
+    }
+}
@@ -0,0 +1,362 @@
+use bytemuck::{try_from_bytes, try_from_bytes_mut, Pod, Zeroable};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use super::*;
+use crate::{error::SwapError, math::*};
+
+/// Max number of observations kept by an observation buffer
+pub const MAX_OBSERVATIONS: usize = 128;
+/// Seed of the observation buffer addresses, derived with the token-swap key
+pub const OBSERVATION_BUFFER_SEED: &[u8] = b"observations";
+
+const OBSERVATION_SIZE: usize = 24; // 8 + 16
+/// Observation buffer size
+pub const OBSERVATION_BUFFER_SIZE: usize = 38 + OBSERVATION_SIZE * MAX_OBSERVATIONS; // 1 + 1 + 32 + 2 + 2 + (24 * 128)
+
+/// Find the address of the observation buffer of a token-swap
+pub fn find_observation_buffer_address(program_id: &Pubkey, swap: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[swap.as_ref(), OBSERVATION_BUFFER_SEED], program_id)
+}
+
+/// Cumulative pool mid price at a point in time
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Observation {
+    timestamp: [u8; 8],
+    base_price_cumulative: [u8; 16],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for Observation {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for Observation {}
+
+impl Observation {
+    /// Create an observation
+    pub fn new(timestamp: u64, base_price_cumulative: Decimal) -> Self {
+        let mut observation = Self {
+            timestamp: timestamp.to_le_bytes(),
+            ..Self::default()
+        };
+        pack_decimal(
+            base_price_cumulative,
+            &mut observation.base_price_cumulative,
+        );
+        observation
+    }
+
+    /// Unix timestamp of the observation
+    pub fn timestamp(&self) -> u64 {
+        u64::from_le_bytes(self.timestamp)
+    }
+
+    /// Sum of the mid prices weighted by the seconds they held, up to the timestamp
+    pub fn base_price_cumulative(&self) -> Decimal {
+        unpack_decimal(&self.base_price_cumulative)
+    }
+}
+
+/// Ring buffer of the latest observations of a token-swap, stored in a
+/// program address derived from `[swap, "observations"]`.
+/// The twap between any two points covered by the buffer is the difference of
+/// their cumulative prices divided by the seconds between them.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ObservationBuffer {
+    is_initialized: [u8; 1],
+    /// Bump seed of the buffer address
+    pub bump_seed: u8,
+    /// Token-swap observed
+    pub swap: Pubkey,
+    next_index: [u8; 2],
+    cardinality: [u8; 2],
+    observations: [Observation; MAX_OBSERVATIONS],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for ObservationBuffer {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for ObservationBuffer {}
+
+impl ObservationBuffer {
+    /// Initialize an observation buffer in freshly allocated account data
+    ///
+    /// # Arguments
+    ///
+    /// * data - account data, all zero.
+    /// * swap - token-swap observed.
+    /// * bump_seed - bump seed of the buffer address.
+    pub fn init(data: &mut [u8], swap: Pubkey, bump_seed: u8) -> Result<&mut Self, ProgramError> {
+        let buffer: &mut Self = data
+            .get_mut(..OBSERVATION_BUFFER_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if buffer.is_initialized()? {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        pack_bool(true, &mut buffer.is_initialized);
+        buffer.bump_seed = bump_seed;
+        buffer.swap = swap;
+        Ok(buffer)
+    }
+
+    /// Borrow an initialized observation buffer
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let buffer: &Self = data
+            .get(..OBSERVATION_BUFFER_SIZE)
+            .and_then(|data| try_from_bytes(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !buffer.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(buffer)
+    }
+
+    /// Mutably borrow an initialized observation buffer
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let buffer: &mut Self = data
+            .get_mut(..OBSERVATION_BUFFER_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !buffer.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(buffer)
+    }
+
+    /// Initialized state
+    pub fn is_initialized(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_initialized)
+    }
+
+    /// Number of observations stored
+    pub fn cardinality(&self) -> usize {
+        u16::from_le_bytes(self.cardinality) as usize
+    }
+
+    fn next_index(&self) -> usize {
+        u16::from_le_bytes(self.next_index) as usize
+    }
+
+    /// Stored observation, from the oldest at index 0 to the latest
+    pub fn get(&self, index: usize) -> Option<Observation> {
+        let cardinality = self.cardinality();
+        if index >= cardinality {
+            return None;
+        }
+        let position =
+            (self.next_index() + MAX_OBSERVATIONS - cardinality + index) % MAX_OBSERVATIONS;
+        Some(self.observations[position])
+    }
+
+    /// Latest stored observation
+    pub fn latest(&self) -> Option<Observation> {
+        self.cardinality()
+            .checked_sub(1)
+            .and_then(|index| self.get(index))
+    }
+
+    /// Store an observation, overwriting the oldest one once the buffer is full.
+    /// Observations not newer than the latest one are skipped, so at most one is
+    /// stored per timestamp.
+    pub fn record(&mut self, observation: Observation) {
+        if let Some(latest) = self.latest() {
+            if observation.timestamp() <= latest.timestamp() {
+                return;
+            }
+        }
+        let next_index = self.next_index();
+        self.observations[next_index] = observation;
+        self.next_index = (((next_index + 1) % MAX_OBSERVATIONS) as u16).to_le_bytes();
+        self.cardinality = ((self.cardinality() + 1).min(MAX_OBSERVATIONS) as u16).to_le_bytes();
+    }
+
+    /// Cumulative price at a timestamp, interpolated between the observations
+    /// around it.
+    ///
+    /// # Arguments
+    ///
+    /// * timestamp - timestamp to look up.
+    /// * current - observation of the current block, not older than the stored ones.
+    ///
+    /// # Return value
+    ///
+    /// cumulative price, none when the timestamp is older than the buffer.
+    pub fn cumulative_at(
+        &self,
+        timestamp: u64,
+        current: &Observation,
+    ) -> Result<Option<Decimal>, ProgramError> {
+        if timestamp >= current.timestamp() {
+            return Ok(Some(current.base_price_cumulative()));
+        }
+        let cardinality = self.cardinality();
+        match self.get(0) {
+            Some(oldest) if oldest.timestamp() <= timestamp => {}
+            _ => return Ok(None),
+        }
+
+        // the current observation closes the buffer at index `cardinality`
+        let at = |index: usize| self.get(index).unwrap_or(*current);
+        let (mut low, mut high) = (0, cardinality);
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if at(mid).timestamp() <= timestamp {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        let before = at(low);
+        let after = at(high);
+        let elapsed = timestamp - before.timestamp();
+        if elapsed == 0 {
+            return Ok(Some(before.base_price_cumulative()));
+        }
+        let delta = after
+            .base_price_cumulative()
+            .try_sub(before.base_price_cumulative())?
+            .try_mul(elapsed)?
+            .try_div(after.timestamp() - before.timestamp())?;
+        Ok(Some(before.base_price_cumulative().try_add(delta)?))
+    }
+
+    /// Time weighted average price over the seconds before the current block,
+    /// limited to the history covered by the buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * current - observation of the current block, not older than the stored ones.
+    /// * window_secs - seconds to look back, zero looks back over the whole buffer.
+    ///
+    /// # Return value
+    ///
+    /// average price, none without any covered time.
+    pub fn average_price(
+        &self,
+        current: &Observation,
+        window_secs: u64,
+    ) -> Result<Option<Decimal>, ProgramError> {
+        let oldest = match self.get(0) {
+            Some(oldest) => oldest.timestamp(),
+            None => return Ok(None),
+        };
+        let now = current.timestamp();
+        let start = if window_secs == 0 {
+            oldest
+        } else {
+            now.saturating_sub(window_secs).max(oldest)
+        };
+        if start >= now {
+            return Ok(None);
+        }
+        let start_cumulative = self
+            .cumulative_at(start, current)?
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(Some(
+            current
+                .base_price_cumulative()
+                .try_sub(start_cumulative)?
+                .try_div(now - start)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_buffer(data: &mut [u8]) -> &mut ObservationBuffer {
+        ObservationBuffer::init(data, Pubkey::new_unique(), 255).unwrap()
+    }
+
+    #[test]
+    fn test_observation_buffer_size() {
+        assert_eq!(std::mem::size_of::<Observation>(), OBSERVATION_SIZE);
+        assert_eq!(
+            std::mem::size_of::<ObservationBuffer>(),
+            OBSERVATION_BUFFER_SIZE
+        );
+    }
+
+    #[test]
+    fn test_record_wraps() {
+        let mut data = vec![0u8; OBSERVATION_BUFFER_SIZE];
+        let buffer = new_buffer(&mut data);
+        assert_eq!(buffer.latest(), None);
+
+        for timestamp in 1..=(MAX_OBSERVATIONS as u64 + 10) {
+            buffer.record(Observation::new(timestamp, Decimal::from(timestamp)));
+        }
+        // same timestamp is skipped
+        buffer.record(Observation::new(
+            MAX_OBSERVATIONS as u64 + 10,
+            Decimal::zero(),
+        ));
+
+        assert_eq!(buffer.cardinality(), MAX_OBSERVATIONS);
+        assert_eq!(buffer.get(0).unwrap().timestamp(), 11);
+        assert_eq!(
+            buffer.latest().unwrap(),
+            Observation::new(
+                MAX_OBSERVATIONS as u64 + 10,
+                Decimal::from(MAX_OBSERVATIONS as u64 + 10)
+            )
+        );
+        assert_eq!(buffer.get(MAX_OBSERVATIONS), None);
+
+        let data = data.clone();
+        assert_eq!(
+            ObservationBuffer::load(&data).unwrap().cardinality(),
+            MAX_OBSERVATIONS
+        );
+        assert_eq!(
+            ObservationBuffer::load(&vec![0u8; OBSERVATION_BUFFER_SIZE]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+    }
+
+    #[test]
+    fn test_average_price() {
+        let mut data = vec![0u8; OBSERVATION_BUFFER_SIZE];
+        let buffer = new_buffer(&mut data);
+        let current = Observation::new(100, Decimal::zero());
+        assert_eq!(buffer.average_price(&current, 0).unwrap(), None);
+
+        // price 10 during [100, 200), 20 during [200, 300)
+        buffer.record(Observation::new(100, Decimal::zero()));
+        buffer.record(Observation::new(200, Decimal::from(1_000u64)));
+        let current = Observation::new(300, Decimal::from(3_000u64));
+
+        assert_eq!(buffer.cumulative_at(99, &current).unwrap(), None);
+        assert_eq!(
+            buffer.cumulative_at(150, &current).unwrap(),
+            Some(Decimal::from(500u64))
+        );
+        assert_eq!(
+            buffer.cumulative_at(250, &current).unwrap(),
+            Some(Decimal::from(2_000u64))
+        );
+
+        assert_eq!(
+            buffer.average_price(&current, 0).unwrap(),
+            Some(Decimal::from(15u64))
+        );
+        assert_eq!(
+            buffer.average_price(&current, 100).unwrap(),
+            Some(Decimal::from(20u64))
+        );
+        assert_eq!(
+            buffer.average_price(&current, 150).unwrap(),
+            Some(Decimal::from(50u64).try_div(3u64).unwrap())
+        );
+        // window longer than the buffer is bounded by the oldest observation
+        assert_eq!(
+            buffer.average_price(&current, 10_000).unwrap(),
+            Some(Decimal::from(15u64))
+        );
+    }
+}
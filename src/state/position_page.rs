@@ -0,0 +1,187 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use crate::state::{
+    pack_positions, unpack_bool, unpack_positions, LiquidityPosition, LIQUIDITY_POSITION_SIZE,
+};
+
+use std::convert::TryFrom;
+
+/// Max number of positions in a position page
+pub const MAX_PAGE_POSITIONS: usize = 32;
+/// Seed of the position page addresses, derived with the liquidity provider key
+/// and the page index
+pub const POSITION_PAGE_SEED: &[u8] = b"position_page";
+
+/// Find the address of a position page of a liquidity provider
+pub fn find_position_page_address(
+    program_id: &Pubkey,
+    liquidity_provider: &Pubkey,
+    page_index: u8,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            liquidity_provider.as_ref(),
+            POSITION_PAGE_SEED,
+            &[page_index],
+        ],
+        program_id,
+    )
+}
+
+/// Overflow positions of a liquidity provider.
+/// Pages are chained from index 0, each one linking to the next.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LiquidityPositionPage {
+    /// Initialization status
+    pub is_initialized: bool,
+    /// Liquidity provider owning the page
+    pub liquidity_provider: Pubkey,
+    /// Index of the page in the chain
+    pub page_index: u8,
+    /// Bump seed of the page address
+    pub bump_seed: u8,
+    /// Next page of the chain, default pubkey for the last page
+    pub next_page: Pubkey,
+    /// Liquidity positions stored in this page
+    pub positions: Vec<LiquidityPosition>,
+}
+
+impl LiquidityPositionPage {
+    /// Initialize a position page
+    ///
+    /// # Arguments
+    ///
+    /// * liquidity_provider - liquidity provider address.
+    /// * page_index - index of the page in the chain.
+    /// * bump_seed - bump seed of the page address.
+    pub fn init(&mut self, liquidity_provider: Pubkey, page_index: u8, bump_seed: u8) {
+        self.is_initialized = true;
+        self.liquidity_provider = liquidity_provider;
+        self.page_index = page_index;
+        self.bump_seed = bump_seed;
+        self.next_page = Pubkey::default();
+        self.positions = vec![];
+    }
+
+    /// Check if the page is the last one of the chain
+    pub fn is_last(&self) -> bool {
+        self.next_page == Pubkey::default()
+    }
+}
+
+impl Sealed for LiquidityPositionPage {}
+impl IsInitialized for LiquidityPositionPage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+const POSITION_PAGE_HEADER_SIZE: usize = 68; // 1 + 32 + 1 + 1 + 32 + 1
+const POSITION_PAGE_SIZE: usize =
+    POSITION_PAGE_HEADER_SIZE + LIQUIDITY_POSITION_SIZE * MAX_PAGE_POSITIONS; // 68 + (96 * 32)
+
+impl Pack for LiquidityPositionPage {
+    const LEN: usize = POSITION_PAGE_SIZE;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, POSITION_PAGE_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            liquidity_provider,
+            page_index,
+            bump_seed,
+            next_page,
+            positions_len,
+            data_flat,
+        ) = mut_array_refs![
+            output,
+            1,
+            PUBKEY_BYTES,
+            1,
+            1,
+            PUBKEY_BYTES,
+            1,
+            LIQUIDITY_POSITION_SIZE * MAX_PAGE_POSITIONS
+        ];
+        is_initialized[0] = self.is_initialized as u8;
+        liquidity_provider.copy_from_slice(self.liquidity_provider.as_ref());
+        page_index[0] = self.page_index;
+        bump_seed[0] = self.bump_seed;
+        next_page.copy_from_slice(self.next_page.as_ref());
+        *positions_len = u8::try_from(self.positions.len()).unwrap().to_le_bytes();
+
+        pack_positions(&self.positions, data_flat);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, POSITION_PAGE_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            liquidity_provider,
+            page_index,
+            bump_seed,
+            next_page,
+            positions_len,
+            data_flat,
+        ) = array_refs![
+            input,
+            1,
+            PUBKEY_BYTES,
+            1,
+            1,
+            PUBKEY_BYTES,
+            1,
+            LIQUIDITY_POSITION_SIZE * MAX_PAGE_POSITIONS
+        ];
+
+        let positions_len = u8::from_le_bytes(*positions_len) as usize;
+        if positions_len > MAX_PAGE_POSITIONS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            is_initialized: unpack_bool(is_initialized)?,
+            liquidity_provider: Pubkey::new_from_array(*liquidity_provider),
+            page_index: page_index[0],
+            bump_seed: bump_seed[0],
+            next_page: Pubkey::new_from_array(*next_page),
+            positions: unpack_positions(positions_len, data_flat),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_page_packing() {
+        let mut page = LiquidityPositionPage::default();
+        page.init(Pubkey::new_unique(), 3, 254);
+        assert!(page.is_last());
+        page.next_page = Pubkey::new_unique();
+        assert!(!page.is_last());
+        for _ in 0..MAX_PAGE_POSITIONS {
+            let mut position = LiquidityPosition::new(Pubkey::new_unique(), 1).unwrap();
+            position.deposit(100).unwrap();
+            page.positions.push(position);
+        }
+
+        let mut packed = [0u8; LiquidityPositionPage::LEN];
+        LiquidityPositionPage::pack(page.clone(), &mut packed).unwrap();
+        let unpacked = LiquidityPositionPage::unpack(&packed).unwrap();
+        assert_eq!(page, unpacked);
+
+        packed[POSITION_PAGE_HEADER_SIZE - 1] = MAX_PAGE_POSITIONS as u8 + 1;
+        assert_eq!(
+            LiquidityPositionPage::unpack(&packed).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use bytemuck::{try_from_bytes, try_from_bytes_mut, Pod, Zeroable};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use super::*;
+use crate::error::SwapError;
+
+/// Seed of the price feed addresses, derived with the token-swap key
+pub const PRICE_FEED_SEED: &[u8] = b"price_feed";
+
+/// Price feed size
+pub const PRICE_FEED_SIZE: usize = 74; // 1 + 1 + 32 + 16 + 16 + 8
+
+/// Find the address of the price feed of a token-swap
+pub fn find_price_feed_address(program_id: &Pubkey, swap: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[swap.as_ref(), PRICE_FEED_SEED], program_id)
+}
+
+/// Prices of a token-swap for other programs, stored in a program address
+/// derived from `[swap, "price_feed"]` and refreshed by the instructions
+/// moving the pool when it is passed to them.
+///
+/// The layout is a stable ABI, all integers little endian and the prices
+/// decimals scaled by 10^9:
+///
+/// | offset | size | field                                                    |
+/// |--------|------|----------------------------------------------------------|
+/// | 0      | 1    | is_initialized, 1 once created                           |
+/// | 1      | 1    | bump seed of the feed address                            |
+/// | 2      | 32   | token-swap                                               |
+/// | 34     | 16   | mid price of the pool, u128                              |
+/// | 50     | 16   | lp virtual price, quote token amount per pool token, u128 |
+/// | 66     | 8    | slot of the last update, u64                             |
+///
+/// Readers should check the owner and the address of the account, and reject
+/// prices older than they can tolerate.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PriceFeed {
+    is_initialized: [u8; 1],
+    /// Bump seed of the feed address
+    pub bump_seed: u8,
+    /// Token-swap priced
+    pub swap: Pubkey,
+    mid_price: [u8; 16],
+    lp_virtual_price: [u8; 16],
+    last_update_slot: [u8; 8],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for PriceFeed {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for PriceFeed {}
+
+impl PriceFeed {
+    /// Initialize a price feed in freshly allocated account data
+    ///
+    /// # Arguments
+    ///
+    /// * data - account data, all zero.
+    /// * swap - token-swap priced.
+    /// * bump_seed - bump seed of the feed address.
+    pub fn init(data: &mut [u8], swap: Pubkey, bump_seed: u8) -> Result<&mut Self, ProgramError> {
+        let feed: &mut Self = data
+            .get_mut(..PRICE_FEED_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if feed.is_initialized()? {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        pack_bool(true, &mut feed.is_initialized);
+        feed.bump_seed = bump_seed;
+        feed.swap = swap;
+        Ok(feed)
+    }
+
+    /// Borrow an initialized price feed
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let feed: &Self = data
+            .get(..PRICE_FEED_SIZE)
+            .and_then(|data| try_from_bytes(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !feed.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(feed)
+    }
+
+    /// Mutably borrow an initialized price feed
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let feed: &mut Self = data
+            .get_mut(..PRICE_FEED_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !feed.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(feed)
+    }
+
+    /// Initialized state
+    pub fn is_initialized(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_initialized)
+    }
+
+    /// Mid price of the pool at the last update
+    pub fn mid_price(&self) -> Decimal {
+        unpack_decimal(&self.mid_price)
+    }
+
+    /// Value of a pool token in quote token amounts at the last update
+    pub fn lp_virtual_price(&self) -> Decimal {
+        unpack_decimal(&self.lp_virtual_price)
+    }
+
+    /// Slot of the last update
+    pub fn last_update_slot(&self) -> u64 {
+        u64::from_le_bytes(self.last_update_slot)
+    }
+
+    /// Store the prices of the pool at the slot
+    pub fn update(&mut self, mid_price: Decimal, lp_virtual_price: Decimal, slot: u64) {
+        pack_decimal(mid_price, &mut self.mid_price);
+        pack_decimal(lp_virtual_price, &mut self.lp_virtual_price);
+        self.last_update_slot = slot.to_le_bytes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_feed_layout() {
+        assert_eq!(std::mem::size_of::<PriceFeed>(), PRICE_FEED_SIZE);
+
+        let swap = Pubkey::new_unique();
+        let mut data = vec![0u8; PRICE_FEED_SIZE];
+        assert_eq!(
+            PriceFeed::load(&data).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+        let feed = PriceFeed::init(&mut data, swap, 254).unwrap();
+        let mid_price = Decimal::from_scaled_val(20_500_000_000);
+        let lp_virtual_price = Decimal::from_scaled_val(1_000_000_007);
+        feed.update(mid_price, lp_virtual_price, 42);
+
+        let mut expect = vec![1, 254];
+        expect.extend_from_slice(swap.as_ref());
+        expect.extend_from_slice(&20_500_000_000u128.to_le_bytes());
+        expect.extend_from_slice(&1_000_000_007u128.to_le_bytes());
+        expect.extend_from_slice(&42u64.to_le_bytes());
+        assert_eq!(data, expect);
+
+        let feed = PriceFeed::load(&data).unwrap();
+        assert_eq!(feed.swap, swap);
+        assert_eq!(feed.mid_price(), mid_price);
+        assert_eq!(feed.lp_virtual_price(), lp_virtual_price);
+        assert_eq!(feed.last_update_slot(), 42);
+
+        assert_eq!(
+            PriceFeed::init(&mut data, swap, 254).err(),
+            Some(SwapError::AlreadyInUse.into())
+        );
+        assert_eq!(
+            PriceFeed::load(&data[..PRICE_FEED_SIZE - 1]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use bytemuck::{try_from_bytes, try_from_bytes_mut, Pod, Zeroable};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use super::*;
+use crate::{curve::SwapQuote, error::SwapError, instruction::SwapDirection};
+
+/// Seed of the quote account addresses, derived with the owner key
+pub const QUOTE_ACCOUNT_SEED: &[u8] = b"quote";
+
+/// Quote account size
+pub const QUOTE_ACCOUNT_SIZE: usize = 115; // 1 + 1 + 1 + 32 + 32 + (8 * 6)
+
+/// Find the address of the quote account of an owner
+pub fn find_quote_account_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[owner.as_ref(), QUOTE_ACCOUNT_SEED], program_id)
+}
+
+/// Scratch account holding the last swap quoted for its owner, stored in a
+/// program address derived from `[owner, "quote"]`.
+/// Programs without return data support read the quote from it after invoking
+/// `CalcReceiveAmount`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct QuoteAccount {
+    is_initialized: [u8; 1],
+    /// Bump seed of the quote account address
+    pub bump_seed: u8,
+    swap_direction: [u8; 1],
+    /// Owner allowed to write quotes, usually a program address of the caller
+    pub owner: Pubkey,
+    /// Token-swap of the last quote
+    pub swap: Pubkey,
+    slot: [u8; 8],
+    amount_in: [u8; 8],
+    amount_out: [u8; 8],
+    trade_fee: [u8; 8],
+    admin_fee: [u8; 8],
+    reward_amount: [u8; 8],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for QuoteAccount {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for QuoteAccount {}
+
+impl QuoteAccount {
+    /// Initialize a quote account in freshly allocated account data
+    ///
+    /// # Arguments
+    ///
+    /// * data - account data, all zero.
+    /// * owner - owner allowed to write quotes.
+    /// * bump_seed - bump seed of the quote account address.
+    pub fn init(data: &mut [u8], owner: Pubkey, bump_seed: u8) -> Result<&mut Self, ProgramError> {
+        let quote_account: &mut Self = data
+            .get_mut(..QUOTE_ACCOUNT_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if quote_account.is_initialized()? {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        pack_bool(true, &mut quote_account.is_initialized);
+        quote_account.bump_seed = bump_seed;
+        quote_account.owner = owner;
+        Ok(quote_account)
+    }
+
+    /// Borrow an initialized quote account
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let quote_account: &Self = data
+            .get(..QUOTE_ACCOUNT_SIZE)
+            .and_then(|data| try_from_bytes(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !quote_account.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(quote_account)
+    }
+
+    /// Mutably borrow an initialized quote account
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let quote_account: &mut Self = data
+            .get_mut(..QUOTE_ACCOUNT_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !quote_account.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(quote_account)
+    }
+
+    /// Initialized state
+    pub fn is_initialized(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_initialized)
+    }
+
+    /// Store a swap quote, replacing the previous one
+    pub fn record(
+        &mut self,
+        swap: Pubkey,
+        slot: u64,
+        amount_in: u64,
+        swap_direction: SwapDirection,
+        quote: &SwapQuote,
+    ) {
+        self.swap = swap;
+        self.slot = slot.to_le_bytes();
+        self.swap_direction = [swap_direction as u8];
+        self.amount_in = amount_in.to_le_bytes();
+        self.amount_out = quote.amount_out.to_le_bytes();
+        self.trade_fee = quote.trade_fee.to_le_bytes();
+        self.admin_fee = quote.admin_fee.to_le_bytes();
+        self.reward_amount = quote.reward_amount.to_le_bytes();
+    }
+
+    /// Slot the quote was taken at
+    pub fn slot(&self) -> u64 {
+        u64::from_le_bytes(self.slot)
+    }
+
+    /// Direction of the quoted swap
+    pub fn swap_direction(&self) -> Result<SwapDirection, ProgramError> {
+        match self.swap_direction {
+            [0] => Ok(SwapDirection::SellBase),
+            [1] => Ok(SwapDirection::SellQuote),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    /// Amount of SOURCE token quoted
+    pub fn amount_in(&self) -> u64 {
+        u64::from_le_bytes(self.amount_in)
+    }
+
+    /// Amount of DESTINATION token the user would receive, after trade fee
+    pub fn amount_out(&self) -> u64 {
+        u64::from_le_bytes(self.amount_out)
+    }
+
+    /// Trade fee charged on the output amount
+    pub fn trade_fee(&self) -> u64 {
+        u64::from_le_bytes(self.trade_fee)
+    }
+
+    /// Part of the trade fee sent to the admin fee account
+    pub fn admin_fee(&self) -> u64 {
+        u64::from_le_bytes(self.admin_fee)
+    }
+
+    /// Deltafi reward the user would receive
+    pub fn reward_amount(&self) -> u64 {
+        u64::from_le_bytes(self.reward_amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Multiplier;
+
+    #[test]
+    fn test_quote_account_size() {
+        assert_eq!(std::mem::size_of::<QuoteAccount>(), QUOTE_ACCOUNT_SIZE);
+    }
+
+    #[test]
+    fn test_record() {
+        let mut data = vec![0u8; QUOTE_ACCOUNT_SIZE];
+        let owner = Pubkey::new_unique();
+        let swap = Pubkey::new_unique();
+        let quote_account = QuoteAccount::init(&mut data, owner, 254).unwrap();
+        quote_account.record(
+            swap,
+            42,
+            1_000,
+            SwapDirection::SellQuote,
+            &SwapQuote {
+                amount_out: 900,
+                trade_fee: 10,
+                admin_fee: 2,
+                reward_amount: 5,
+                multiplier: Multiplier::One,
+            },
+        );
+
+        let quote_account = QuoteAccount::load(&data).unwrap();
+        assert_eq!(quote_account.owner, owner);
+        assert_eq!(quote_account.bump_seed, 254);
+        assert_eq!(quote_account.swap, swap);
+        assert_eq!(quote_account.slot(), 42);
+        assert_eq!(
+            quote_account.swap_direction().unwrap(),
+            SwapDirection::SellQuote
+        );
+        assert_eq!(quote_account.amount_in(), 1_000);
+        assert_eq!(quote_account.amount_out(), 900);
+        assert_eq!(quote_account.trade_fee(), 10);
+        assert_eq!(quote_account.admin_fee(), 2);
+        assert_eq!(quote_account.reward_amount(), 5);
+        assert_eq!(
+            QuoteAccount::init(&mut data.clone(), owner, 254).err(),
+            Some(SwapError::AlreadyInUse.into())
+        );
+        assert_eq!(
+            QuoteAccount::load(&[0u8; QUOTE_ACCOUNT_SIZE]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use bytemuck::{try_from_bytes, try_from_bytes_mut, Pod, Zeroable};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use super::*;
+use crate::error::SwapError;
+
+/// Max number of pools in a registry page
+pub const MAX_REGISTRY_ENTRIES: usize = 64;
+/// Seed of the registry page addresses, derived with the config key and the
+/// page index
+pub const REGISTRY_PAGE_SEED: &[u8] = b"registry";
+
+const REGISTRY_ENTRY_SIZE: usize = PUBKEY_BYTES * 3;
+/// Registry page size
+pub const REGISTRY_PAGE_SIZE: usize = 37 + REGISTRY_ENTRY_SIZE * MAX_REGISTRY_ENTRIES; // 1 + 1 + 1 + 32 + 2 + (96 * 64)
+
+/// Find the address of a registry page of a config
+pub fn find_registry_page_address(
+    program_id: &Pubkey,
+    config: &Pubkey,
+    page_index: u8,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[config.as_ref(), REGISTRY_PAGE_SEED, &[page_index]],
+        program_id,
+    )
+}
+
+/// Pool listed in the registry
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RegistryEntry {
+    /// Token-swap
+    pub swap: Pubkey,
+    /// Mint of token a
+    pub token_a_mint: Pubkey,
+    /// Mint of token b
+    pub token_b_mint: Pubkey,
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for RegistryEntry {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for RegistryEntry {}
+
+/// Pools initialized under a config, in order, stored in program addresses
+/// derived from `[config, "registry", page_index]`.
+/// A page is filled before the next one is created, so off-chain routers
+/// enumerate the pools by reading the pages from index 0 until a missing one.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct RegistryPage {
+    is_initialized: [u8; 1],
+    /// Bump seed of the page address
+    pub bump_seed: u8,
+    /// Index of the page
+    pub page_index: u8,
+    /// Config the pools are initialized under
+    pub config: Pubkey,
+    len: [u8; 2],
+    entries: [RegistryEntry; MAX_REGISTRY_ENTRIES],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for RegistryPage {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for RegistryPage {}
+
+impl RegistryPage {
+    /// Initialize a registry page in freshly allocated account data
+    ///
+    /// # Arguments
+    ///
+    /// * data - account data, all zero.
+    /// * config - config the pools are initialized under.
+    /// * page_index - index of the page.
+    /// * bump_seed - bump seed of the page address.
+    pub fn init(
+        data: &mut [u8],
+        config: Pubkey,
+        page_index: u8,
+        bump_seed: u8,
+    ) -> Result<&mut Self, ProgramError> {
+        let page: &mut Self = data
+            .get_mut(..REGISTRY_PAGE_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if page.is_initialized()? {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        pack_bool(true, &mut page.is_initialized);
+        page.bump_seed = bump_seed;
+        page.page_index = page_index;
+        page.config = config;
+        Ok(page)
+    }
+
+    /// Borrow an initialized registry page
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let page: &Self = data
+            .get(..REGISTRY_PAGE_SIZE)
+            .and_then(|data| try_from_bytes(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !page.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(page)
+    }
+
+    /// Mutably borrow an initialized registry page
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let page: &mut Self = data
+            .get_mut(..REGISTRY_PAGE_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !page.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(page)
+    }
+
+    /// Initialized state
+    pub fn is_initialized(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_initialized)
+    }
+
+    /// Pools listed in the page
+    pub fn entries(&self) -> &[RegistryEntry] {
+        let len = (u16::from_le_bytes(self.len) as usize).min(MAX_REGISTRY_ENTRIES);
+        &self.entries[..len]
+    }
+
+    /// Check if the page has no room for another pool
+    pub fn is_full(&self) -> bool {
+        self.entries().len() == MAX_REGISTRY_ENTRIES
+    }
+
+    /// Append a pool
+    pub fn push(&mut self, entry: RegistryEntry) -> Result<(), ProgramError> {
+        if self.is_full() {
+            return Err(SwapError::RegistryFull.into());
+        }
+        let len = self.entries().len();
+        self.entries[len] = entry;
+        self.len = ((len + 1) as u16).to_le_bytes();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_page_size() {
+        assert_eq!(std::mem::size_of::<RegistryEntry>(), REGISTRY_ENTRY_SIZE);
+        assert_eq!(std::mem::size_of::<RegistryPage>(), REGISTRY_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut data = vec![0u8; REGISTRY_PAGE_SIZE];
+        let config = Pubkey::new_unique();
+        let page = RegistryPage::init(&mut data, config, 1, 255).unwrap();
+        let entries: Vec<RegistryEntry> = (0..MAX_REGISTRY_ENTRIES)
+            .map(|_| RegistryEntry {
+                swap: Pubkey::new_unique(),
+                token_a_mint: Pubkey::new_unique(),
+                token_b_mint: Pubkey::new_unique(),
+            })
+            .collect();
+
+        for entry in entries.iter() {
+            assert!(!page.is_full());
+            page.push(*entry).unwrap();
+        }
+        assert!(page.is_full());
+        assert_eq!(
+            page.push(RegistryEntry::default()).unwrap_err(),
+            SwapError::RegistryFull.into()
+        );
+
+        let page = RegistryPage::load(&data).unwrap();
+        assert_eq!(page.config, config);
+        assert_eq!(page.page_index, 1);
+        assert_eq!(page.entries(), &entries[..]);
+        assert_eq!(
+            RegistryPage::init(&mut data.clone(), config, 1, 255).err(),
+            Some(SwapError::AlreadyInUse.into())
+        );
+        assert_eq!(
+            RegistryPage::load(&vec![0u8; REGISTRY_PAGE_SIZE]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+    }
+}
@@ -2,14 +2,317 @@
 
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::{
+    clock::{Epoch, UnixTimestamp},
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
 };
 
-use crate::math::{Decimal, TryDiv, TryMul};
+use crate::{
+    error::SwapError,
+    math::{Decimal, TryDiv, TryMul},
+    state::MIN_CLAIM_PERIOD,
+};
+
+/// Halving schedule of the rewards of a pool, halving the trade rewards and
+/// the liquidity rewards every period from its start
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HalvingSchedule {
+    /// Timestamp of the first halving
+    pub start_ts: UnixTimestamp,
+    /// Seconds between two halvings, zero disables the schedule
+    pub period_secs: u64,
+    /// Number of halvings after which the rewards stay the same
+    pub halving_count: u8,
+}
+
+impl HalvingSchedule {
+    /// Number of halvings applied at a timestamp
+    ///
+    /// # Arguments
+    ///
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// halvings passed, at most `halving_count`.
+    pub fn halvings(&self, current_ts: UnixTimestamp) -> u8 {
+        if self.period_secs == 0 || current_ts < self.start_ts {
+            return 0;
+        }
+        let periods = (current_ts - self.start_ts) as u64 / self.period_secs;
+        periods.saturating_add(1).min(self.halving_count as u64) as u8
+    }
+
+    /// Timestamp of the next halving after a timestamp
+    ///
+    /// # Arguments
+    ///
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// next halving, none once all the halvings passed.
+    pub fn next_halving_ts(&self, current_ts: UnixTimestamp) -> Option<UnixTimestamp> {
+        let halvings = self.halvings(current_ts);
+        if self.period_secs == 0 || halvings >= self.halving_count {
+            return None;
+        }
+        if current_ts < self.start_ts {
+            return Some(self.start_ts);
+        }
+        (self.period_secs as i64)
+            .checked_mul(halvings as i64)
+            .and_then(|elapsed| self.start_ts.checked_add(elapsed))
+    }
+
+    /// Halve a reward amount by the halvings applied at a timestamp
+    ///
+    /// # Arguments
+    ///
+    /// * amount - reward amount.
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// halved reward amount.
+    pub fn halve_u64(&self, amount: u64, current_ts: UnixTimestamp) -> u64 {
+        amount
+            .checked_shr(self.halvings(current_ts) as u32)
+            .unwrap_or(0)
+    }
+
+    /// Halve a reward rate by the halvings applied at a timestamp
+    ///
+    /// # Arguments
+    ///
+    /// * reward_ratio - reward rate.
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// halved reward rate.
+    pub fn halve(
+        &self,
+        reward_ratio: Decimal,
+        current_ts: UnixTimestamp,
+    ) -> Result<Decimal, ProgramError> {
+        match 1u64.checked_shl(self.halvings(current_ts) as u32) {
+            Some(divisor) => reward_ratio.try_div(divisor),
+            None => Ok(Decimal::zero()),
+        }
+    }
+}
+
+impl Sealed for HalvingSchedule {}
+impl IsInitialized for HalvingSchedule {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+
+/// Halving schedule packed size
+pub const HALVING_SCHEDULE_SIZE: usize = 17;
+impl Pack for HalvingSchedule {
+    const LEN: usize = HALVING_SCHEDULE_SIZE;
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, HALVING_SCHEDULE_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (start_ts, period_secs, halving_count) = array_refs![input, 8, 8, 1];
+        Ok(Self {
+            start_ts: i64::from_le_bytes(*start_ts),
+            period_secs: u64::from_le_bytes(*period_secs),
+            halving_count: u8::from_le_bytes(*halving_count),
+        })
+    }
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, HALVING_SCHEDULE_SIZE];
+        let (start_ts, period_secs, halving_count) = mut_array_refs![output, 8, 8, 1];
+        *start_ts = self.start_ts.to_le_bytes();
+        *period_secs = self.period_secs.to_le_bytes();
+        *halving_count = self.halving_count.to_le_bytes();
+    }
+}
+
+/// Gauge votes cast in an epoch for a pool, or for all the pools of a config,
+/// along with the votes cast in the epoch before
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GaugeVotes {
+    /// Epoch of the votes
+    pub epoch: Epoch,
+    /// Votes cast in `epoch`
+    pub votes: u64,
+    /// Votes cast in the epoch before `epoch`
+    pub previous_votes: u64,
+}
+
+impl GaugeVotes {
+    /// Move the votes to an epoch, the votes of the epoch before it become
+    /// the previous votes
+    ///
+    /// # Arguments
+    ///
+    /// * epoch - current epoch.
+    ///
+    /// # Return value
+    ///
+    /// votes of the epoch.
+    pub fn roll(self, epoch: Epoch) -> Self {
+        if epoch == self.epoch {
+            return self;
+        }
+        Self {
+            epoch,
+            votes: 0,
+            previous_votes: if epoch == self.epoch.saturating_add(1) {
+                self.votes
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Add votes cast in an epoch
+    ///
+    /// # Arguments
+    ///
+    /// * epoch - current epoch.
+    /// * votes - votes cast.
+    ///
+    /// # Return value
+    ///
+    /// votes of the epoch.
+    pub fn add(self, epoch: Epoch, votes: u64) -> Result<Self, ProgramError> {
+        let current = self.roll(epoch);
+        Ok(Self {
+            votes: current
+                .votes
+                .checked_add(votes)
+                .ok_or(SwapError::CalculationFailure)?,
+            ..current
+        })
+    }
+
+    /// Votes finalized for an epoch, cast in the epoch before it
+    pub fn finalized(&self, epoch: Epoch) -> u64 {
+        self.roll(epoch).previous_votes
+    }
+}
+
+impl Sealed for GaugeVotes {}
+impl IsInitialized for GaugeVotes {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+
+/// Gauge votes packed size
+pub const GAUGE_VOTES_SIZE: usize = 24;
+impl Pack for GaugeVotes {
+    const LEN: usize = GAUGE_VOTES_SIZE;
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, GAUGE_VOTES_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (epoch, votes, previous_votes) = array_refs![input, 8, 8, 8];
+        Ok(Self {
+            epoch: u64::from_le_bytes(*epoch),
+            votes: u64::from_le_bytes(*votes),
+            previous_votes: u64::from_le_bytes(*previous_votes),
+        })
+    }
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, GAUGE_VOTES_SIZE];
+        let (epoch, votes, previous_votes) = mut_array_refs![output, 8, 8, 8];
+        *epoch = self.epoch.to_le_bytes();
+        *votes = self.votes.to_le_bytes();
+        *previous_votes = self.previous_votes.to_le_bytes();
+    }
+}
+
+/// Share of the gauge votes finalized for a pool in an epoch, weighting its
+/// liquidity rewards. No votes finalized keeps the full liquidity rewards.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GaugeWeight {
+    /// Epoch the weight is finalized for
+    pub epoch: Epoch,
+    /// Votes finalized for the pool
+    pub votes: u64,
+    /// Votes finalized for all the pools
+    pub total_votes: u64,
+}
+
+impl GaugeWeight {
+    /// Finalize the weight of a pool from the votes cast in the epoch before
+    ///
+    /// # Arguments
+    ///
+    /// * epoch - current epoch.
+    /// * pool_votes - gauge votes of the pool.
+    /// * total_votes - gauge votes of all the pools.
+    ///
+    /// # Return value
+    ///
+    /// weight of the pool in the epoch.
+    pub fn finalize(epoch: Epoch, pool_votes: &GaugeVotes, total_votes: &GaugeVotes) -> Self {
+        Self {
+            epoch,
+            votes: pool_votes.finalized(epoch),
+            total_votes: total_votes.finalized(epoch),
+        }
+    }
+
+    /// Weight a liquidity reward rate by the share of votes of the pool
+    ///
+    /// # Arguments
+    ///
+    /// * reward_ratio - liquidity reward rate.
+    ///
+    /// # Return value
+    ///
+    /// weighted liquidity reward rate.
+    pub fn apply(&self, reward_ratio: Decimal) -> Result<Decimal, ProgramError> {
+        if self.total_votes == 0 {
+            return Ok(reward_ratio);
+        }
+        reward_ratio.try_mul(self.votes)?.try_div(self.total_votes)
+    }
+}
+
+impl Sealed for GaugeWeight {}
+impl IsInitialized for GaugeWeight {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+
+/// Gauge weight packed size
+pub const GAUGE_WEIGHT_SIZE: usize = 24;
+impl Pack for GaugeWeight {
+    const LEN: usize = GAUGE_WEIGHT_SIZE;
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, GAUGE_WEIGHT_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (epoch, votes, total_votes) = array_refs![input, 8, 8, 8];
+        Ok(Self {
+            epoch: u64::from_le_bytes(*epoch),
+            votes: u64::from_le_bytes(*votes),
+            total_votes: u64::from_le_bytes(*total_votes),
+        })
+    }
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, GAUGE_WEIGHT_SIZE];
+        let (epoch, votes, total_votes) = mut_array_refs![output, 8, 8, 8];
+        *epoch = self.epoch.to_le_bytes();
+        *votes = self.votes.to_le_bytes();
+        *total_votes = self.total_votes.to_le_bytes();
+    }
+}
 
 /// Rewards structure
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Rewards {
     /// Trade reward numerator
     pub trade_reward_numerator: u64,
@@ -43,6 +346,12 @@ impl Rewards {
         }
     }
 
+    /// Whether both reward ratios have a denominator, so swaps and claims
+    /// can compute their rewards
+    pub fn is_valid(&self) -> bool {
+        self.trade_reward_denominator > 0 && self.liquidity_reward_denominator > 0
+    }
+
     /// Calc trade reward amount with [`u64`]
     ///
     /// # Arguments
@@ -65,19 +374,21 @@ impl Rewards {
         })
     }
 
-    /// Calc lp reward amount with [`u64`]
+    /// Calc lp reward amount with [`u64`], the reward ratio applying to
+    /// liquidity held over a whole claim period
     ///
     /// # Arguments
     ///
-    /// * amount - liquidity amount.
+    /// * liquidity_seconds - liquidity amount times the seconds it was held.
     ///
     /// # Return value
     ///
     /// liquidity reward.
-    pub fn liquidity_reward_u64(&self, amount: u64) -> Result<u64, ProgramError> {
-        Decimal::from(amount)
+    pub fn liquidity_reward_u64(&self, liquidity_seconds: u128) -> Result<u64, ProgramError> {
+        Decimal::from(liquidity_seconds)
             .try_mul(self.liquidity_reward_numerator)?
             .try_div(self.liquidity_reward_denominator)?
+            .try_div(MIN_CLAIM_PERIOD as u64)?
             .try_floor_u64()
     }
 }
@@ -89,7 +400,8 @@ impl IsInitialized for Rewards {
     }
 }
 
-const REWARDS_SIZE: usize = 40;
+/// Rewards packed size
+pub const REWARDS_SIZE: usize = 40;
 impl Pack for Rewards {
     const LEN: usize = REWARDS_SIZE;
     fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
@@ -151,6 +463,144 @@ mod tests {
         assert_eq!(rewards, unpacked);
     }
 
+    #[test]
+    fn pack_halving_schedule() {
+        let halving_schedule = HalvingSchedule {
+            start_ts: 1_000,
+            period_secs: 3_600,
+            halving_count: 4,
+        };
+
+        let mut packed = vec![];
+        packed.extend_from_slice(&halving_schedule.start_ts.to_le_bytes());
+        packed.extend_from_slice(&halving_schedule.period_secs.to_le_bytes());
+        packed.push(halving_schedule.halving_count);
+        let unpacked = HalvingSchedule::unpack_from_slice(&packed).unwrap();
+        assert_eq!(halving_schedule, unpacked);
+
+        let mut repacked = [0u8; HalvingSchedule::LEN];
+        halving_schedule.pack_into_slice(&mut repacked);
+        assert_eq!(repacked.to_vec(), packed);
+    }
+
+    #[test]
+    fn halving_results() {
+        let halving_schedule = HalvingSchedule {
+            start_ts: 1_000,
+            period_secs: 100,
+            halving_count: 3,
+        };
+
+        // before the first halving
+        assert_eq!(halving_schedule.halvings(999), 0);
+        assert_eq!(halving_schedule.next_halving_ts(999), Some(1_000));
+        assert_eq!(halving_schedule.halve_u64(1_000, 999), 1_000);
+
+        assert_eq!(halving_schedule.halvings(1_000), 1);
+        assert_eq!(halving_schedule.next_halving_ts(1_000), Some(1_100));
+        assert_eq!(halving_schedule.halve_u64(1_000, 1_099), 500);
+        assert_eq!(halving_schedule.halvings(1_100), 2);
+        assert_eq!(halving_schedule.halve_u64(1_000, 1_150), 250);
+        assert_eq!(
+            halving_schedule
+                .halve(Decimal::from(1_000u64), 1_150)
+                .unwrap(),
+            Decimal::from(250u64)
+        );
+
+        // rewards stop halving after the last halving
+        assert_eq!(halving_schedule.halvings(1_200), 3);
+        assert_eq!(halving_schedule.halvings(100_000), 3);
+        assert_eq!(halving_schedule.next_halving_ts(1_200), None);
+        assert_eq!(halving_schedule.halve_u64(1_000, 100_000), 125);
+
+        // no schedule keeps the rewards
+        let halving_schedule = HalvingSchedule::default();
+        assert_eq!(halving_schedule.halvings(100_000), 0);
+        assert_eq!(halving_schedule.next_halving_ts(0), None);
+        assert_eq!(halving_schedule.halve_u64(1_000, 100_000), 1_000);
+
+        // halving the rewards away
+        let halving_schedule = HalvingSchedule {
+            start_ts: 0,
+            period_secs: 1,
+            halving_count: u8::MAX,
+        };
+        assert_eq!(halving_schedule.halve_u64(u64::MAX, 1_000), 0);
+        assert_eq!(
+            halving_schedule
+                .halve(Decimal::from(1_000u64), 1_000)
+                .unwrap(),
+            Decimal::zero()
+        );
+    }
+
+    #[test]
+    fn pack_gauge_votes() {
+        let gauge_votes = GaugeVotes {
+            epoch: 42,
+            votes: 1_000,
+            previous_votes: 2_000,
+        };
+
+        let mut packed = vec![];
+        packed.extend_from_slice(&gauge_votes.epoch.to_le_bytes());
+        packed.extend_from_slice(&gauge_votes.votes.to_le_bytes());
+        packed.extend_from_slice(&gauge_votes.previous_votes.to_le_bytes());
+        let unpacked = GaugeVotes::unpack_from_slice(&packed).unwrap();
+        assert_eq!(gauge_votes, unpacked);
+
+        let gauge_weight = GaugeWeight {
+            epoch: 43,
+            votes: 1_000,
+            total_votes: 4_000,
+        };
+        let mut packed = [0u8; GaugeWeight::LEN];
+        gauge_weight.pack_into_slice(&mut packed);
+        assert_eq!(
+            GaugeWeight::unpack_from_slice(&packed).unwrap(),
+            gauge_weight
+        );
+    }
+
+    #[test]
+    fn gauge_results() {
+        let pool_votes = GaugeVotes::default().add(5, 100).unwrap();
+        let pool_votes = pool_votes.add(5, 50).unwrap();
+        let total_votes = GaugeVotes::default().add(5, 600).unwrap();
+        assert_eq!(pool_votes.votes, 150);
+
+        // votes are only finalized in the next epoch
+        assert_eq!(pool_votes.finalized(5), 0);
+        assert_eq!(pool_votes.finalized(6), 150);
+        assert_eq!(pool_votes.finalized(7), 0);
+
+        let pool_votes = pool_votes.add(6, 10).unwrap();
+        assert_eq!(
+            pool_votes,
+            GaugeVotes {
+                epoch: 6,
+                votes: 10,
+                previous_votes: 150,
+            }
+        );
+
+        let gauge_weight = GaugeWeight::finalize(6, &pool_votes, &total_votes);
+        assert_eq!(gauge_weight.votes, 150);
+        assert_eq!(gauge_weight.total_votes, 600);
+        assert_eq!(
+            gauge_weight.apply(Decimal::from(1_000u64)).unwrap(),
+            Decimal::from(250u64)
+        );
+
+        // without votes the rewards are not weighted
+        let gauge_weight = GaugeWeight::finalize(8, &pool_votes, &total_votes);
+        assert_eq!(
+            gauge_weight.apply(Decimal::from(1_000u64)).unwrap(),
+            Decimal::from(1_000u64)
+        );
+    }
+
     #[test]
     fn reward_results() {
         let trade_reward_numerator = 1;
@@ -191,8 +641,38 @@ mod tests {
         // LP reward calc
         {
             let expected_lp_reward = 100u64;
-            let lp_reward = rewards.liquidity_reward_u64(liquidity_amount).unwrap();
+            let liquidity_seconds = liquidity_amount as u128 * MIN_CLAIM_PERIOD as u128;
+            let lp_reward = rewards.liquidity_reward_u64(liquidity_seconds).unwrap();
             assert_eq!(lp_reward, expected_lp_reward);
+
+            // the same liquidity held for a day earns a thirtieth of it
+            let lp_reward = rewards
+                .liquidity_reward_u64(liquidity_seconds / 30)
+                .unwrap();
+            assert_eq!(lp_reward, expected_lp_reward / 30);
+        }
+    }
+
+    #[test]
+    fn rewards_validity() {
+        assert!(DEFAULT_TEST_REWARDS.is_valid());
+        // a zero ratio turns a reward off
+        assert!(Rewards {
+            trade_reward_numerator: 0,
+            liquidity_reward_numerator: 0,
+            ..DEFAULT_TEST_REWARDS
+        }
+        .is_valid());
+
+        assert!(!Rewards {
+            trade_reward_denominator: 0,
+            ..DEFAULT_TEST_REWARDS
+        }
+        .is_valid());
+        assert!(!Rewards {
+            liquidity_reward_denominator: 0,
+            ..DEFAULT_TEST_REWARDS
         }
+        .is_valid());
     }
 }
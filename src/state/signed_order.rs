@@ -0,0 +1,437 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use bytemuck::{try_from_bytes, try_from_bytes_mut, Pod, Zeroable};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use super::*;
+use crate::{error::SwapError, instruction::SwapDirection};
+
+/// Seed of the order authority, the delegate of the token accounts of the
+/// makers spending them for their signed orders
+pub const ORDER_AUTHORITY_SEED: &[u8] = b"order_authority";
+
+/// Seed of the order nonce addresses, derived with the maker
+pub const ORDER_NONCE_SEED: &[u8] = b"order_nonce";
+
+/// Order nonce size
+pub const ORDER_NONCE_SIZE: usize = 42; // 1 + 1 + 32 + 8
+
+/// Signed order size
+pub const SIGNED_ORDER_SIZE: usize = 161; // 32 + 32 + 32 + 32 + 8 + 8 + 1 + 8 + 8
+
+/// Ed25519 instruction data size of a single signature, without the message
+const ED25519_HEADER_SIZE: usize = 112; // 2 + 14 + 32 + 64
+
+/// Instruction index of the offsets referring to the ed25519 instruction itself
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Find the address of the order authority
+pub fn find_order_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ORDER_AUTHORITY_SEED], program_id)
+}
+
+/// Find the address of the order nonce of the maker
+pub fn find_order_nonce_address(program_id: &Pubkey, maker: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[maker.as_ref(), ORDER_NONCE_SEED], program_id)
+}
+
+/// Swap order signed off-chain by the maker and settled by anyone before its
+/// expiry, if its nonce is not below the next nonce of the maker.
+///
+/// The packed order is the message signed, all integers little endian. It
+/// starts with the program and the config, so an order signed for another
+/// deployment of the program or another config can not be settled here:
+///
+/// | offset | size | field                              |
+/// |--------|------|------------------------------------|
+/// | 0      | 32   | program id                         |
+/// | 32     | 32   | config                             |
+/// | 64     | 32   | maker                              |
+/// | 96     | 32   | token-swap                         |
+/// | 128    | 8    | amount in, u64                     |
+/// | 136    | 8    | minimum amount out, u64            |
+/// | 144    | 1    | swap direction, 0 sell base        |
+/// | 145    | 8    | expiry, unix timestamp, i64        |
+/// | 153    | 8    | nonce, u64                         |
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedOrder {
+    /// Program settling the order
+    pub program_id: Pubkey,
+    /// Config of the token-swap
+    pub config: Pubkey,
+    /// Owner of the token accounts swapped, signer of the order
+    pub maker: Pubkey,
+    /// Token-swap the order is settled in
+    pub swap: Pubkey,
+    /// Amount of tokens sold
+    pub amount_in: u64,
+    /// Minimum amount of tokens bought
+    pub minimum_amount_out: u64,
+    /// Swap direction
+    pub swap_direction: SwapDirection,
+    /// Unix timestamp after which the order can not be settled
+    pub expiry: i64,
+    /// Nonce of the order, orders of a maker settle in increasing nonces
+    pub nonce: u64,
+}
+
+impl SignedOrder {
+    /// Message signed by the maker
+    pub fn pack(&self) -> Vec<u8> {
+        let mut packed = vec![0u8; SIGNED_ORDER_SIZE];
+        let output = array_mut_ref![packed, 0, SIGNED_ORDER_SIZE];
+        let (
+            program_id,
+            config,
+            maker,
+            swap,
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            expiry,
+            nonce,
+        ) = mut_array_refs![output, 32, 32, 32, 32, 8, 8, 1, 8, 8];
+        program_id.copy_from_slice(self.program_id.as_ref());
+        config.copy_from_slice(self.config.as_ref());
+        maker.copy_from_slice(self.maker.as_ref());
+        swap.copy_from_slice(self.swap.as_ref());
+        *amount_in = self.amount_in.to_le_bytes();
+        *minimum_amount_out = self.minimum_amount_out.to_le_bytes();
+        swap_direction[0] = self.swap_direction as u8;
+        *expiry = self.expiry.to_le_bytes();
+        *nonce = self.nonce.to_le_bytes();
+        packed
+    }
+
+    /// Read an order from the message signed
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != SIGNED_ORDER_SIZE {
+            return Err(SwapError::InstructionUnpackError.into());
+        }
+        let input = array_ref![input, 0, SIGNED_ORDER_SIZE];
+        let (
+            program_id,
+            config,
+            maker,
+            swap,
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            expiry,
+            nonce,
+        ) = array_refs![input, 32, 32, 32, 32, 8, 8, 1, 8, 8];
+        Ok(Self {
+            program_id: Pubkey::new_from_array(*program_id),
+            config: Pubkey::new_from_array(*config),
+            maker: Pubkey::new_from_array(*maker),
+            swap: Pubkey::new_from_array(*swap),
+            amount_in: u64::from_le_bytes(*amount_in),
+            minimum_amount_out: u64::from_le_bytes(*minimum_amount_out),
+            swap_direction: match swap_direction[0] {
+                0 => SwapDirection::SellBase,
+                1 => SwapDirection::SellQuote,
+                _ => return Err(SwapError::InstructionUnpackError.into()),
+            },
+            expiry: i64::from_le_bytes(*expiry),
+            nonce: u64::from_le_bytes(*nonce),
+        })
+    }
+}
+
+/// Next nonce of the signed orders of a maker, stored in a program address
+/// derived from `[maker, "order_nonce"]`. Settling an order moves it past the
+/// nonce of the order, and the maker moves it forward to cancel the orders
+/// below it, so no settled or cancelled order can be replayed.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct OrderNonce {
+    is_initialized: [u8; 1],
+    /// Bump seed of the order nonce address
+    pub bump_seed: u8,
+    /// Maker of the orders
+    pub maker: Pubkey,
+    next_nonce: [u8; 8],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for OrderNonce {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for OrderNonce {}
+
+impl OrderNonce {
+    /// Initialize an order nonce in freshly allocated account data
+    pub fn init(data: &mut [u8], maker: Pubkey, bump_seed: u8) -> Result<&mut Self, ProgramError> {
+        let order_nonce: &mut Self = data
+            .get_mut(..ORDER_NONCE_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if order_nonce.is_initialized()? {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        pack_bool(true, &mut order_nonce.is_initialized);
+        order_nonce.bump_seed = bump_seed;
+        order_nonce.maker = maker;
+        Ok(order_nonce)
+    }
+
+    /// Mutably borrow an initialized order nonce
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let order_nonce: &mut Self = data
+            .get_mut(..ORDER_NONCE_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !order_nonce.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(order_nonce)
+    }
+
+    /// Borrow an initialized order nonce
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let order_nonce: &Self = data
+            .get(..ORDER_NONCE_SIZE)
+            .and_then(|data| try_from_bytes(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !order_nonce.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(order_nonce)
+    }
+
+    /// Initialized state
+    pub fn is_initialized(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_initialized)
+    }
+
+    /// Lowest nonce of the orders still valid
+    pub fn next_nonce(&self) -> u64 {
+        u64::from_le_bytes(self.next_nonce)
+    }
+
+    /// Use the nonce of an order settled, the orders below it can not be
+    /// settled anymore
+    pub fn use_nonce(&mut self, nonce: u64) -> Result<(), ProgramError> {
+        if nonce < self.next_nonce() {
+            return Err(SwapError::InvalidOrderNonce.into());
+        }
+        let next_nonce = nonce.checked_add(1).ok_or(SwapError::InvalidOrderNonce)?;
+        self.next_nonce = next_nonce.to_le_bytes();
+        Ok(())
+    }
+
+    /// Cancel the orders below the nonce, which can only move forward
+    pub fn cancel_below(&mut self, next_nonce: u64) -> Result<(), ProgramError> {
+        if next_nonce <= self.next_nonce() {
+            return Err(SwapError::InvalidOrderNonce.into());
+        }
+        self.next_nonce = next_nonce.to_le_bytes();
+        Ok(())
+    }
+}
+
+/// Data of an ed25519 program instruction verifying the signature of the
+/// message by the public key, all held in the instruction
+pub fn ed25519_instruction_data(pubkey: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Vec<u8> {
+    let public_key_offset = 16u16;
+    let signature_offset = public_key_offset + 32;
+    let message_data_offset = signature_offset + 64;
+
+    let mut data = vec![1, 0];
+    for value in [
+        signature_offset,
+        CURRENT_INSTRUCTION,
+        public_key_offset,
+        CURRENT_INSTRUCTION,
+        message_data_offset,
+        message.len() as u16,
+        CURRENT_INSTRUCTION,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(pubkey.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+    data
+}
+
+/// Check that the data of an ed25519 program instruction verifies a single
+/// signature of the message by the public key. The signature itself is
+/// verified by the runtime before the transaction executes.
+pub fn check_ed25519_instruction_data(
+    data: &[u8],
+    pubkey: &Pubkey,
+    message: &[u8],
+) -> Result<(), ProgramError> {
+    if data.len() < ED25519_HEADER_SIZE || data[0] != 1 {
+        return Err(SwapError::InvalidOrderSignature.into());
+    }
+    let offsets = array_ref![data, 2, 14];
+    let (
+        _signature_offset,
+        signature_instruction_index,
+        public_key_offset,
+        public_key_instruction_index,
+        message_data_offset,
+        message_data_size,
+        message_instruction_index,
+    ) = array_refs![offsets, 2, 2, 2, 2, 2, 2, 2];
+    // all the offsets must refer to the ed25519 instruction, other
+    // instructions of the transaction could hold anything
+    if [
+        signature_instruction_index,
+        public_key_instruction_index,
+        message_instruction_index,
+    ]
+    .iter()
+    .any(|index| u16::from_le_bytes(**index) != CURRENT_INSTRUCTION)
+    {
+        return Err(SwapError::InvalidOrderSignature.into());
+    }
+
+    let public_key_offset = u16::from_le_bytes(*public_key_offset) as usize;
+    let message_data_offset = u16::from_le_bytes(*message_data_offset) as usize;
+    let message_data_size = u16::from_le_bytes(*message_data_size) as usize;
+    match (
+        data.get(public_key_offset..public_key_offset + 32),
+        data.get(message_data_offset..message_data_offset + message_data_size),
+    ) {
+        (Some(signer), Some(signed)) if signer == pubkey.as_ref() && signed == message => Ok(()),
+        _ => Err(SwapError::InvalidOrderSignature.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order() -> SignedOrder {
+        SignedOrder {
+            program_id: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            maker: Pubkey::new_unique(),
+            swap: Pubkey::new_unique(),
+            amount_in: 1_000_000,
+            minimum_amount_out: 990_000,
+            swap_direction: SwapDirection::SellQuote,
+            expiry: 1_700_000_000,
+            nonce: 42,
+        }
+    }
+
+    #[test]
+    fn test_signed_order_layout() {
+        let order = order();
+        let packed = order.pack();
+
+        let mut expect = order.program_id.to_bytes().to_vec();
+        expect.extend_from_slice(order.config.as_ref());
+        expect.extend_from_slice(order.maker.as_ref());
+        expect.extend_from_slice(order.swap.as_ref());
+        expect.extend_from_slice(&1_000_000u64.to_le_bytes());
+        expect.extend_from_slice(&990_000u64.to_le_bytes());
+        expect.push(1);
+        expect.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&42u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        assert_eq!(SignedOrder::unpack(&packed).unwrap(), order);
+
+        assert_eq!(
+            SignedOrder::unpack(&packed[..SIGNED_ORDER_SIZE - 1]),
+            Err(SwapError::InstructionUnpackError.into())
+        );
+        let mut invalid_direction = packed;
+        invalid_direction[144] = 2;
+        assert_eq!(
+            SignedOrder::unpack(&invalid_direction),
+            Err(SwapError::InstructionUnpackError.into())
+        );
+    }
+
+    #[test]
+    fn test_order_nonce() {
+        assert_eq!(std::mem::size_of::<OrderNonce>(), ORDER_NONCE_SIZE);
+
+        let maker = Pubkey::new_unique();
+        let mut data = vec![0u8; ORDER_NONCE_SIZE];
+        assert_eq!(
+            OrderNonce::load(&data).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+        let order_nonce = OrderNonce::init(&mut data, maker, 253).unwrap();
+        assert_eq!(order_nonce.next_nonce(), 0);
+
+        // orders settle in increasing nonces, skipped ones are void
+        order_nonce.use_nonce(0).unwrap();
+        order_nonce.use_nonce(5).unwrap();
+        assert_eq!(order_nonce.next_nonce(), 6);
+        let invalid = Some(SwapError::InvalidOrderNonce.into());
+        assert_eq!(order_nonce.use_nonce(5).err(), invalid);
+        assert_eq!(order_nonce.use_nonce(3).err(), invalid);
+        assert_eq!(order_nonce.use_nonce(u64::MAX).err(), invalid);
+
+        // cancelling moves forward only
+        order_nonce.cancel_below(10).unwrap();
+        assert_eq!(order_nonce.cancel_below(10).err(), invalid);
+        assert_eq!(order_nonce.cancel_below(8).err(), invalid);
+        assert_eq!(order_nonce.use_nonce(9).err(), invalid);
+        order_nonce.use_nonce(10).unwrap();
+
+        let mut expect = vec![1, 253];
+        expect.extend_from_slice(maker.as_ref());
+        expect.extend_from_slice(&11u64.to_le_bytes());
+        assert_eq!(data, expect);
+        assert_eq!(OrderNonce::load(&data).unwrap().maker, maker);
+        assert_eq!(
+            OrderNonce::init(&mut data, maker, 253).err(),
+            Some(SwapError::AlreadyInUse.into())
+        );
+        assert_eq!(
+            OrderNonce::load_mut(&mut data[..ORDER_NONCE_SIZE - 1]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_check_ed25519_instruction_data() {
+        let order = order();
+        let message = order.pack();
+        let data = ed25519_instruction_data(&order.maker, &[7u8; 64], &message);
+        assert_eq!(data.len(), ED25519_HEADER_SIZE + SIGNED_ORDER_SIZE);
+        assert_eq!(
+            check_ed25519_instruction_data(&data, &order.maker, &message),
+            Ok(())
+        );
+
+        let invalid = Err(SwapError::InvalidOrderSignature.into());
+        // signed by someone else
+        assert_eq!(
+            check_ed25519_instruction_data(&data, &Pubkey::new_unique(), &message),
+            invalid
+        );
+        // another order
+        let other = SignedOrder { nonce: 43, ..order }.pack();
+        assert_eq!(
+            check_ed25519_instruction_data(&data, &order.maker, &other),
+            invalid
+        );
+        // the message held in another instruction
+        let mut elsewhere = data.clone();
+        elsewhere[14..16].copy_from_slice(&0u16.to_le_bytes());
+        assert_eq!(
+            check_ed25519_instruction_data(&elsewhere, &order.maker, &message),
+            invalid
+        );
+        // several signatures
+        let mut several = data.clone();
+        several[0] = 2;
+        assert_eq!(
+            check_ed25519_instruction_data(&several, &order.maker, &message),
+            invalid
+        );
+        // the message cut short
+        assert_eq!(
+            check_ed25519_instruction_data(&data[..data.len() - 1], &order.maker, &message),
+            invalid
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    clock::{Slot, UnixTimestamp},
+    keccak::{hashv, HASH_BYTES},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use super::*;
+
+/// Seed of the snapshot addresses, derived with the token-swap key and the
+/// snapshot id
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+
+/// Find the address of a snapshot of a token-swap
+pub fn find_snapshot_address(program_id: &Pubkey, swap: &Pubkey, snapshot_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[swap.as_ref(), &snapshot_id.to_le_bytes(), SNAPSHOT_SEED],
+        program_id,
+    )
+}
+
+/// Leaf of a liquidity provider balance in the merkle tree of a snapshot
+pub fn snapshot_balance_leaf(owner: &Pubkey, balance: u64) -> [u8; HASH_BYTES] {
+    hashv(&[owner.as_ref(), &balance.to_le_bytes()]).to_bytes()
+}
+
+/// Liquidity of a token-swap at a block, with the merkle root of the balances
+/// of its liquidity providers for airdrops and off-chain governance
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LiquiditySnapshot {
+    /// Initialization status
+    pub is_initialized: bool,
+    /// Token-swap of the snapshot
+    pub swap: Pubkey,
+    /// Id of the snapshot, unique per token-swap
+    pub snapshot_id: u64,
+    /// Slot of the snapshot
+    pub slot: Slot,
+    /// Block timestamp of the snapshot
+    pub snapshot_ts: UnixTimestamp,
+    /// Supply of the pool mint
+    pub pool_mint_supply: u64,
+    /// Base reserve of the pool
+    pub base_reserve: Decimal,
+    /// Quote reserve of the pool
+    pub quote_reserve: Decimal,
+    /// Merkle root of the liquidity provider balances, computed off-chain at
+    /// the slot of the snapshot
+    pub merkle_root: [u8; HASH_BYTES],
+}
+
+impl LiquiditySnapshot {
+    /// Verify the balance of a liquidity provider against the merkle root.
+    /// The nodes of the tree hash their children in ascending order
+    ///
+    /// # Arguments
+    ///
+    /// * owner - liquidity provider owner.
+    /// * balance - pool tokens of the owner at the snapshot.
+    /// * proof - sibling hashes from the leaf up to the root.
+    ///
+    /// # Return value
+    ///
+    /// whether the balance is part of the snapshot.
+    pub fn verify_balance(&self, owner: &Pubkey, balance: u64, proof: &[[u8; HASH_BYTES]]) -> bool {
+        let root = proof
+            .iter()
+            .fold(snapshot_balance_leaf(owner, balance), |node, sibling| {
+                if node <= *sibling {
+                    hashv(&[&node, sibling]).to_bytes()
+                } else {
+                    hashv(&[sibling, &node]).to_bytes()
+                }
+            });
+        root == self.merkle_root
+    }
+}
+
+impl Sealed for LiquiditySnapshot {}
+impl IsInitialized for LiquiditySnapshot {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+const LIQUIDITY_SNAPSHOT_SIZE: usize = 129; // 1 + 32 + 8 + 8 + 8 + 8 + 16 + 16 + 32
+
+impl Pack for LiquiditySnapshot {
+    const LEN: usize = LIQUIDITY_SNAPSHOT_SIZE;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, LIQUIDITY_SNAPSHOT_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            swap,
+            snapshot_id,
+            slot,
+            snapshot_ts,
+            pool_mint_supply,
+            base_reserve,
+            quote_reserve,
+            merkle_root,
+        ) = mut_array_refs![output, 1, PUBKEY_BYTES, 8, 8, 8, 8, 16, 16, HASH_BYTES];
+        pack_bool(self.is_initialized, is_initialized);
+        swap.copy_from_slice(self.swap.as_ref());
+        *snapshot_id = self.snapshot_id.to_le_bytes();
+        *slot = self.slot.to_le_bytes();
+        *snapshot_ts = self.snapshot_ts.to_le_bytes();
+        *pool_mint_supply = self.pool_mint_supply.to_le_bytes();
+        pack_decimal(self.base_reserve, base_reserve);
+        pack_decimal(self.quote_reserve, quote_reserve);
+        merkle_root.copy_from_slice(&self.merkle_root);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, LIQUIDITY_SNAPSHOT_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            swap,
+            snapshot_id,
+            slot,
+            snapshot_ts,
+            pool_mint_supply,
+            base_reserve,
+            quote_reserve,
+            merkle_root,
+        ) = array_refs![input, 1, PUBKEY_BYTES, 8, 8, 8, 8, 16, 16, HASH_BYTES];
+        Ok(Self {
+            is_initialized: unpack_bool(is_initialized)?,
+            swap: Pubkey::new_from_array(*swap),
+            snapshot_id: u64::from_le_bytes(*snapshot_id),
+            slot: u64::from_le_bytes(*slot),
+            snapshot_ts: i64::from_le_bytes(*snapshot_ts),
+            pool_mint_supply: u64::from_le_bytes(*pool_mint_supply),
+            base_reserve: unpack_decimal(base_reserve),
+            quote_reserve: unpack_decimal(quote_reserve),
+            merkle_root: *merkle_root,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_liquidity_snapshot_packing() {
+        let snapshot = LiquiditySnapshot {
+            is_initialized: true,
+            swap: Pubkey::new_unique(),
+            snapshot_id: 7,
+            slot: 150_000_000,
+            snapshot_ts: 1_700_000_000,
+            pool_mint_supply: 1_000_000_000,
+            base_reserve: Decimal::from(42_000u64),
+            quote_reserve: Decimal::from(800_000u64),
+            merkle_root: [9u8; HASH_BYTES],
+        };
+
+        let mut packed = [0u8; LiquiditySnapshot::LEN];
+        LiquiditySnapshot::pack(snapshot.clone(), &mut packed).unwrap();
+        assert_eq!(LiquiditySnapshot::unpack(&packed).unwrap(), snapshot);
+        assert_eq!(
+            packed[LiquiditySnapshot::LEN - HASH_BYTES..],
+            [9u8; HASH_BYTES]
+        );
+    }
+
+    #[test]
+    fn test_verify_balance() {
+        let balances = [
+            (Pubkey::new_unique(), 1_000u64),
+            (Pubkey::new_unique(), 2_000),
+            (Pubkey::new_unique(), 3_000),
+        ];
+        let leaves: Vec<[u8; HASH_BYTES]> = balances
+            .iter()
+            .map(|(owner, balance)| snapshot_balance_leaf(owner, *balance))
+            .collect();
+        let hash_pair = |a: [u8; HASH_BYTES], b: [u8; HASH_BYTES]| {
+            if a <= b {
+                hashv(&[&a, &b]).to_bytes()
+            } else {
+                hashv(&[&b, &a]).to_bytes()
+            }
+        };
+        // the odd leaf is promoted to the next level
+        let node = hash_pair(leaves[0], leaves[1]);
+        let snapshot = LiquiditySnapshot {
+            merkle_root: hash_pair(node, leaves[2]),
+            ..LiquiditySnapshot::default()
+        };
+
+        let (owner, balance) = balances[1];
+        assert!(snapshot.verify_balance(&owner, balance, &[leaves[0], leaves[2]]));
+        assert!(!snapshot.verify_balance(&owner, balance + 1, &[leaves[0], leaves[2]]));
+        let (owner, balance) = balances[2];
+        assert!(snapshot.verify_balance(&owner, balance, &[node]));
+        assert!(!snapshot.verify_balance(&owner, balance, &[leaves[0]]));
+    }
+}
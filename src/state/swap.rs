@@ -1,16 +1,24 @@
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use bytemuck::{try_from_bytes, try_from_bytes_mut, Pod, Zeroable};
 use solana_program::{
+    clock::{Slot, UnixTimestamp},
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::{Pubkey, PUBKEY_BYTES},
 };
 
 use super::*;
-use crate::{curve::PoolState, math::*};
+use crate::{
+    curve::{CurveType, PoolState, SwapQuote, WithdrawQuote, CURVE_TYPE_SIZE, POOL_STATE_SIZE},
+    error::SwapError,
+    instruction::SwapDirection,
+    math::*,
+};
 
 /// Swap states.
 #[repr(C)]
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SwapInfo {
     /// Initialized state
     pub is_initialized: bool,
@@ -47,7 +55,8 @@ pub struct SwapInfo {
     /// Rewards
     pub rewards: Rewards,
 
-    /// Pool object
+    /// Pool object, with the decimals of the token mints packed after the
+    /// gauge weight
     pub pool_state: PoolState,
     /// twap open flag
     pub is_open_twap: bool,
@@ -57,6 +66,319 @@ pub struct SwapInfo {
     pub cumulative_ticks: u64,
     /// base price cumulative last - twap
     pub base_price_cumulative_last: Decimal,
+    /// Timestamp of the last admin mid price update
+    pub last_mid_price_update_ts: UnixTimestamp,
+
+    /// Pyth price account for token a
+    pub pyth_a: Pubkey,
+    /// Pyth price account for token b
+    pub pyth_b: Pubkey,
+    /// Seconds of observations averaged by the twap price, zero averages all of it
+    pub twap_window_secs: u64,
+    /// Max price impact of a swap in basis points, zero disables the check
+    pub max_price_impact_bps: u64,
+    /// Max amount sold by a swap, zero disables the check
+    pub max_amount_in: u64,
+    /// Max amount sold by a swap in basis points of the pool reserve of the
+    /// token sold, zero disables the check
+    pub max_amount_in_reserve_bps: u64,
+    /// Max number of swaps in a slot, zero disables the check
+    pub max_swaps_per_slot: u64,
+    /// Max base token volume of the swaps in a slot, zero disables the check
+    pub max_volume_per_slot: u64,
+    /// Slot of the last swap
+    pub last_swap_slot: Slot,
+    /// Number of swaps in the slot of the last swap
+    pub slot_swap_count: u64,
+    /// Base token volume of the swaps in the slot of the last swap
+    pub slot_swap_volume: u64,
+    /// Whether the lp share of the trade fees is added to the pool targets
+    pub is_fee_compounding: bool,
+    /// Whether only the traders on the whitelist of the token-swap can swap
+    pub is_whitelist_only: bool,
+    /// Program called to approve the swaps and deposits, default when unset
+    pub transfer_hook_program: Pubkey,
+    /// Liquidity rewards accumulated per pool token
+    pub reward_per_share: Decimal,
+    /// Timestamp of the last rewards accumulation, zero before the first one
+    pub reward_last_update_ts: UnixTimestamp,
+    /// Price of the DELFI token the liquidity rewards are valued at, refreshed
+    /// from the config oracle, zero before the first refresh and then no
+    /// liquidity rewards accrue
+    pub deltafi_price: Decimal,
+    /// Halving schedule of the trade and liquidity rewards
+    pub halving_schedule: HalvingSchedule,
+    /// Gauge votes cast for the pool
+    pub gauge_votes: GaugeVotes,
+    /// Gauge weight of the liquidity rewards, finalized on refresh
+    pub gauge_weight: GaugeWeight,
+    /// Max base token reserve after a deposit, zero disables the check
+    pub max_base_reserve: u64,
+    /// Max quote token reserve after a deposit, zero disables the check
+    pub max_quote_reserve: u64,
+    /// Max liquidity position of a user after a deposit, zero disables the check
+    pub max_user_liquidity: u64,
+    /// Whether the admin can no longer change the parameters of the token-swap
+    pub is_immutable: bool,
+    /// Trade fee rates per swap direction, replacing the trade fee of `fees`
+    pub directional_fees: DirectionalFees,
+    /// Whether the trade fees are collected in the quote token, the base
+    /// trade fee being sold to the pool
+    pub is_fee_in_quote: bool,
+    /// Whether a token mint of the pool has a freeze authority, detected at
+    /// initialization
+    pub has_freezable_mint: bool,
+    /// Lp share of the trade fees accumulated per base target, valued in quote
+    /// token amounts at the market price of each swap. Pool tokens are minted
+    /// and burned with the targets, a pool token standing for
+    /// `base_target / supply` base targets
+    pub cumulative_fee_per_lp: Decimal,
+    /// OpenBook market pricing the pool when the oracles are stale and the
+    /// twap is unavailable, default when unset
+    pub openbook_market: Pubkey,
+    /// Config the token-swap was initialized with, the only config its
+    /// instructions accept
+    pub config_key: Pubkey,
+    /// Curve the swaps are quoted on, standard in version 1 accounts
+    pub curve_type: CurveType,
+
+    /// Version of the token-swap layout
+    pub version: u8,
+}
+
+/// Time weighted average of the pool mid price
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Twap {
+    /// Sum of the mid prices weighted by the seconds they held
+    pub base_price_cumulative_last: Decimal,
+    /// Seconds accumulated in the sum
+    pub cumulative_ticks: u64,
+}
+
+impl Twap {
+    /// Accumulate a mid price held for some seconds
+    ///
+    /// # Arguments
+    ///
+    /// * price - pool mid price.
+    /// * time_elapsed - seconds the price held.
+    ///
+    /// # Return value
+    ///
+    /// accumulated twap.
+    pub fn accumulate(self, price: Decimal, time_elapsed: u64) -> Result<Self, ProgramError> {
+        Ok(Self {
+            base_price_cumulative_last: self
+                .base_price_cumulative_last
+                .try_add(price.try_mul(time_elapsed)?)?,
+            cumulative_ticks: self
+                .cumulative_ticks
+                .checked_add(time_elapsed)
+                .ok_or(SwapError::CalculationFailure)?,
+        })
+    }
+
+    /// Average mid price, none before any time is accumulated
+    pub fn average(&self) -> Result<Option<Decimal>, ProgramError> {
+        if self.cumulative_ticks == 0 {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.base_price_cumulative_last
+                .try_div(self.cumulative_ticks)?,
+        ))
+    }
+}
+
+/// Liquidity rewards accumulated per pool token. A position earns the increase
+/// of `reward_per_share` since it was last settled, times its liquidity over
+/// `MIN_CLAIM_PERIOD`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RewardAccumulator {
+    /// Rewards per pool token accumulated since the first accumulation, times
+    /// `MIN_CLAIM_PERIOD` so hourly accumulations keep the decimal precision
+    pub reward_per_share: Decimal,
+    /// Timestamp of the last accumulation, zero before the first one
+    pub last_update_ts: UnixTimestamp,
+}
+
+impl RewardAccumulator {
+    /// Accumulate the rewards of the period since the last accumulation.
+    /// The first accumulation only starts the period.
+    ///
+    /// # Arguments
+    ///
+    /// * reward_ratio - rewards per pool token over a claim period.
+    /// * current_ts - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// accumulated rewards.
+    pub fn accumulate(
+        self,
+        reward_ratio: Decimal,
+        current_ts: UnixTimestamp,
+    ) -> Result<Self, ProgramError> {
+        if self.last_update_ts == 0 || current_ts <= self.last_update_ts {
+            return Ok(Self {
+                last_update_ts: self.last_update_ts.max(current_ts),
+                ..self
+            });
+        }
+        let time_elapsed = current_ts
+            .checked_sub(self.last_update_ts)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(Self {
+            reward_per_share: reward_ratio
+                .try_mul(time_elapsed as u64)?
+                .try_add(self.reward_per_share)?,
+            last_update_ts: current_ts,
+        })
+    }
+}
+
+/// Swaps traded in the slot of the last swap
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SlotVolume {
+    /// Slot of the last swap
+    pub slot: Slot,
+    /// Number of swaps in the slot
+    pub swap_count: u64,
+    /// Base token volume of the swaps in the slot
+    pub volume: u64,
+}
+
+impl SlotVolume {
+    /// Record a swap, starting over on a new slot
+    ///
+    /// # Arguments
+    ///
+    /// * slot - slot of the swap.
+    /// * volume - base token volume of the swap.
+    ///
+    /// # Return value
+    ///
+    /// slot volume including the swap.
+    pub fn record(self, slot: Slot, volume: u64) -> Result<Self, ProgramError> {
+        let current = if slot == self.slot {
+            self
+        } else {
+            Self {
+                slot,
+                ..Self::default()
+            }
+        };
+        Ok(Self {
+            slot,
+            swap_count: current
+                .swap_count
+                .checked_add(1)
+                .ok_or(SwapError::CalculationFailure)?,
+            volume: current
+                .volume
+                .checked_add(volume)
+                .ok_or(SwapError::CalculationFailure)?,
+        })
+    }
+}
+
+impl SwapInfo {
+    /// Version of a token-swap account. Version 1 accounts end before the
+    /// version byte, which follows their layout in all the later ones.
+    pub fn stored_version(src: &[u8]) -> Result<u8, ProgramError> {
+        match src.len() {
+            SWAP_INFO_SIZE_V1 => Ok(PROGRAM_VERSION_1),
+            SWAP_INFO_SIZE => Ok(src[SWAP_INFO_SIZE_V1]),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    /// Unpacks a token-swap stored in the layout of its version.
+    /// Fields added after version 1 are zeroed in version 1 accounts.
+    pub fn unpack_versioned(src: &[u8]) -> Result<Self, ProgramError> {
+        let version = Self::stored_version(src)?;
+        if version > PROGRAM_VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut packed = [0u8; SWAP_INFO_SIZE];
+        packed[..src.len()].copy_from_slice(src);
+        Ok(Self {
+            version,
+            ..Self::unpack(&packed)?
+        })
+    }
+
+    /// Liquidity rewards accumulated per pool token
+    pub fn reward_accumulator(&self) -> RewardAccumulator {
+        RewardAccumulator {
+            reward_per_share: self.reward_per_share,
+            last_update_ts: self.reward_last_update_ts,
+        }
+    }
+
+    /// Time weighted average of the pool mid price
+    pub fn twap(&self) -> Twap {
+        Twap {
+            base_price_cumulative_last: self.base_price_cumulative_last,
+            cumulative_ticks: self.cumulative_ticks,
+        }
+    }
+
+    /// Swaps traded in the slot of the last swap
+    pub fn slot_volume(&self) -> SlotVolume {
+        SlotVolume {
+            slot: self.last_swap_slot,
+            swap_count: self.slot_swap_count,
+            volume: self.slot_swap_volume,
+        }
+    }
+
+    /// Quote a swap at the stored market price, on the curve of the token-swap
+    pub fn quote_swap(
+        &self,
+        amount_in: u64,
+        swap_direction: SwapDirection,
+    ) -> Result<SwapQuote, ProgramError> {
+        self.curve_type.apply(&self.pool_state)?.quote_swap(
+            amount_in,
+            swap_direction,
+            &self.directional_fees.apply(&self.fees, swap_direction),
+            &self.rewards,
+        )
+    }
+
+    /// Quote pool tokens minted by a deposit
+    pub fn quote_deposit(
+        &self,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        pool_mint_supply: u64,
+    ) -> Result<u64, ProgramError> {
+        self.pool_state
+            .quote_deposit(token_a_amount, token_b_amount, pool_mint_supply)
+    }
+
+    /// Quote token amounts received by a withdraw
+    pub fn quote_withdraw(
+        &self,
+        pool_token_amount: u64,
+        pool_mint_supply: u64,
+    ) -> Result<WithdrawQuote, ProgramError> {
+        self.pool_state
+            .quote_withdraw(pool_token_amount, pool_mint_supply, &self.fees)
+    }
+
+    /// Value of a pool token in quote token amounts, the reserves being
+    /// valued at the stored mid price
+    pub fn lp_virtual_price(&self, pool_mint_supply: u64) -> Result<Decimal, ProgramError> {
+        self.pool_state.lp_virtual_price(pool_mint_supply)
+    }
+
+    /// Value of a pool token in quote token amounts, the targets being valued
+    /// at the stored market price
+    pub fn lp_target_price(&self, pool_mint_supply: u64) -> Result<Decimal, ProgramError> {
+        self.pool_state.lp_target_price(pool_mint_supply)
+    }
 }
 
 impl Sealed for SwapInfo {}
@@ -65,7 +387,9 @@ impl IsInitialized for SwapInfo {
         self.is_initialized
     }
 }
-const SWAP_INFO_SIZE: usize = 461;
+const SWAP_INFO_SIZE: usize = 919;
+/// Token-swap size of version 1 accounts, which have no version byte
+pub const SWAP_INFO_SIZE_V1: usize = 461;
 impl Pack for SwapInfo {
     const LEN: usize = SWAP_INFO_SIZE;
 
@@ -91,6 +415,42 @@ impl Pack for SwapInfo {
             block_timestamp_last,
             cumulative_ticks,
             base_price_cumulative_last,
+            version,
+            last_mid_price_update_ts,
+            pyth_a,
+            pyth_b,
+            twap_window_secs,
+            max_price_impact_bps,
+            max_amount_in,
+            max_amount_in_reserve_bps,
+            max_swaps_per_slot,
+            max_volume_per_slot,
+            last_swap_slot,
+            slot_swap_count,
+            slot_swap_volume,
+            is_fee_compounding,
+            is_whitelist_only,
+            transfer_hook_program,
+            reward_per_share,
+            reward_last_update_ts,
+            deltafi_price,
+            halving_schedule,
+            gauge_votes,
+            gauge_weight,
+            token_a_decimals,
+            token_b_decimals,
+            max_base_reserve,
+            max_quote_reserve,
+            max_user_liquidity,
+            is_immutable,
+            directional_fees,
+            min_trade_fee,
+            is_fee_in_quote,
+            has_freezable_mint,
+            cumulative_fee_per_lp,
+            openbook_market,
+            config_key,
+            curve_type,
         ) = array_refs![
             input,
             1,
@@ -109,8 +469,50 @@ impl Pack for SwapInfo {
             1,
             8,
             8,
-            16
+            16,
+            1,
+            8,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            8,
+            8,
+            8,
+            8,
+            8,
+            8,
+            8,
+            8,
+            8,
+            1,
+            1,
+            PUBKEY_BYTES,
+            16,
+            8,
+            16,
+            HalvingSchedule::LEN,
+            GaugeVotes::LEN,
+            GaugeWeight::LEN,
+            1,
+            1,
+            8,
+            8,
+            8,
+            1,
+            DirectionalFees::LEN,
+            8,
+            1,
+            1,
+            16,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            CurveType::LEN
         ];
+
+        let version = u8::from_le_bytes(*version);
+        if version > PROGRAM_VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
         Ok(Self {
             is_initialized: unpack_bool(is_initialized)?,
             is_paused: unpack_bool(is_paused)?,
@@ -122,13 +524,53 @@ impl Pack for SwapInfo {
             token_b_mint: Pubkey::new_from_array(*token_b_mint),
             admin_fee_key_a: Pubkey::new_from_array(*admin_fee_key_a),
             admin_fee_key_b: Pubkey::new_from_array(*admin_fee_key_b),
-            fees: Fees::unpack_from_slice(fees)?,
+            fees: Fees {
+                min_trade_fee: u64::from_le_bytes(*min_trade_fee),
+                ..Fees::unpack_from_slice(fees)?
+            },
             rewards: Rewards::unpack_from_slice(rewards)?,
-            pool_state: PoolState::unpack_from_slice(pool_state)?,
+            pool_state: PoolState {
+                base_decimals: u8::from_le_bytes(*token_a_decimals),
+                quote_decimals: u8::from_le_bytes(*token_b_decimals),
+                ..PoolState::unpack_from_slice(pool_state)?
+            },
             is_open_twap: unpack_bool(is_open_twap)?,
             block_timestamp_last: u64::from_le_bytes(*block_timestamp_last),
             cumulative_ticks: u64::from_le_bytes(*cumulative_ticks),
             base_price_cumulative_last: unpack_decimal(base_price_cumulative_last),
+            last_mid_price_update_ts: i64::from_le_bytes(*last_mid_price_update_ts),
+            pyth_a: Pubkey::new_from_array(*pyth_a),
+            pyth_b: Pubkey::new_from_array(*pyth_b),
+            twap_window_secs: u64::from_le_bytes(*twap_window_secs),
+            max_price_impact_bps: u64::from_le_bytes(*max_price_impact_bps),
+            max_amount_in: u64::from_le_bytes(*max_amount_in),
+            max_amount_in_reserve_bps: u64::from_le_bytes(*max_amount_in_reserve_bps),
+            max_swaps_per_slot: u64::from_le_bytes(*max_swaps_per_slot),
+            max_volume_per_slot: u64::from_le_bytes(*max_volume_per_slot),
+            last_swap_slot: u64::from_le_bytes(*last_swap_slot),
+            slot_swap_count: u64::from_le_bytes(*slot_swap_count),
+            slot_swap_volume: u64::from_le_bytes(*slot_swap_volume),
+            is_fee_compounding: unpack_bool(is_fee_compounding)?,
+            is_whitelist_only: unpack_bool(is_whitelist_only)?,
+            transfer_hook_program: Pubkey::new_from_array(*transfer_hook_program),
+            reward_per_share: unpack_decimal(reward_per_share),
+            reward_last_update_ts: i64::from_le_bytes(*reward_last_update_ts),
+            deltafi_price: unpack_decimal(deltafi_price),
+            halving_schedule: HalvingSchedule::unpack_from_slice(halving_schedule)?,
+            gauge_votes: GaugeVotes::unpack_from_slice(gauge_votes)?,
+            gauge_weight: GaugeWeight::unpack_from_slice(gauge_weight)?,
+            max_base_reserve: u64::from_le_bytes(*max_base_reserve),
+            max_quote_reserve: u64::from_le_bytes(*max_quote_reserve),
+            max_user_liquidity: u64::from_le_bytes(*max_user_liquidity),
+            is_immutable: unpack_bool(is_immutable)?,
+            directional_fees: DirectionalFees::unpack_from_slice(directional_fees)?,
+            is_fee_in_quote: unpack_bool(is_fee_in_quote)?,
+            has_freezable_mint: unpack_bool(has_freezable_mint)?,
+            cumulative_fee_per_lp: unpack_decimal(cumulative_fee_per_lp),
+            openbook_market: Pubkey::new_from_array(*openbook_market),
+            config_key: Pubkey::new_from_array(*config_key),
+            curve_type: CurveType::unpack_from_slice(curve_type)?,
+            version,
         })
     }
 
@@ -153,6 +595,42 @@ impl Pack for SwapInfo {
             block_timestamp_last,
             cumulative_ticks,
             base_price_cumulative_last,
+            version,
+            last_mid_price_update_ts,
+            pyth_a,
+            pyth_b,
+            twap_window_secs,
+            max_price_impact_bps,
+            max_amount_in,
+            max_amount_in_reserve_bps,
+            max_swaps_per_slot,
+            max_volume_per_slot,
+            last_swap_slot,
+            slot_swap_count,
+            slot_swap_volume,
+            is_fee_compounding,
+            is_whitelist_only,
+            transfer_hook_program,
+            reward_per_share,
+            reward_last_update_ts,
+            deltafi_price,
+            halving_schedule,
+            gauge_votes,
+            gauge_weight,
+            token_a_decimals,
+            token_b_decimals,
+            max_base_reserve,
+            max_quote_reserve,
+            max_user_liquidity,
+            is_immutable,
+            directional_fees,
+            min_trade_fee,
+            is_fee_in_quote,
+            has_freezable_mint,
+            cumulative_fee_per_lp,
+            openbook_market,
+            config_key,
+            curve_type,
         ) = mut_array_refs![
             output,
             1,
@@ -171,7 +649,43 @@ impl Pack for SwapInfo {
             1,
             8,
             8,
-            16
+            16,
+            1,
+            8,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            8,
+            8,
+            8,
+            8,
+            8,
+            8,
+            8,
+            8,
+            8,
+            1,
+            1,
+            PUBKEY_BYTES,
+            16,
+            8,
+            16,
+            HalvingSchedule::LEN,
+            GaugeVotes::LEN,
+            GaugeWeight::LEN,
+            1,
+            1,
+            8,
+            8,
+            8,
+            1,
+            DirectionalFees::LEN,
+            8,
+            1,
+            1,
+            16,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            CurveType::LEN
         ];
         pack_bool(self.is_initialized, is_initialized);
         pack_bool(self.is_paused, is_paused);
@@ -190,6 +704,416 @@ impl Pack for SwapInfo {
         *block_timestamp_last = self.block_timestamp_last.to_le_bytes();
         *cumulative_ticks = self.cumulative_ticks.to_le_bytes();
         pack_decimal(self.base_price_cumulative_last, base_price_cumulative_last);
+        *last_mid_price_update_ts = self.last_mid_price_update_ts.to_le_bytes();
+        pyth_a.copy_from_slice(self.pyth_a.as_ref());
+        pyth_b.copy_from_slice(self.pyth_b.as_ref());
+        *twap_window_secs = self.twap_window_secs.to_le_bytes();
+        *max_price_impact_bps = self.max_price_impact_bps.to_le_bytes();
+        *max_amount_in = self.max_amount_in.to_le_bytes();
+        *max_amount_in_reserve_bps = self.max_amount_in_reserve_bps.to_le_bytes();
+        *max_swaps_per_slot = self.max_swaps_per_slot.to_le_bytes();
+        *max_volume_per_slot = self.max_volume_per_slot.to_le_bytes();
+        *last_swap_slot = self.last_swap_slot.to_le_bytes();
+        *slot_swap_count = self.slot_swap_count.to_le_bytes();
+        *slot_swap_volume = self.slot_swap_volume.to_le_bytes();
+        pack_bool(self.is_fee_compounding, is_fee_compounding);
+        pack_bool(self.is_whitelist_only, is_whitelist_only);
+        transfer_hook_program.copy_from_slice(self.transfer_hook_program.as_ref());
+        pack_decimal(self.reward_per_share, reward_per_share);
+        *reward_last_update_ts = self.reward_last_update_ts.to_le_bytes();
+        pack_decimal(self.deltafi_price, deltafi_price);
+        self.halving_schedule
+            .pack_into_slice(&mut halving_schedule[..]);
+        self.gauge_votes.pack_into_slice(&mut gauge_votes[..]);
+        self.gauge_weight.pack_into_slice(&mut gauge_weight[..]);
+        *token_a_decimals = self.pool_state.base_decimals.to_le_bytes();
+        *token_b_decimals = self.pool_state.quote_decimals.to_le_bytes();
+        *max_base_reserve = self.max_base_reserve.to_le_bytes();
+        *max_quote_reserve = self.max_quote_reserve.to_le_bytes();
+        *max_user_liquidity = self.max_user_liquidity.to_le_bytes();
+        pack_bool(self.is_immutable, is_immutable);
+        self.directional_fees
+            .pack_into_slice(&mut directional_fees[..]);
+        *min_trade_fee = self.fees.min_trade_fee.to_le_bytes();
+        pack_bool(self.is_fee_in_quote, is_fee_in_quote);
+        pack_bool(self.has_freezable_mint, has_freezable_mint);
+        pack_decimal(self.cumulative_fee_per_lp, cumulative_fee_per_lp);
+        openbook_market.copy_from_slice(self.openbook_market.as_ref());
+        config_key.copy_from_slice(self.config_key.as_ref());
+        self.curve_type.pack_into_slice(&mut curve_type[..]);
+        *version = self.version.to_le_bytes();
+    }
+}
+
+/// Zero-copy view of the token-swap account.
+/// Keeps the packed layout of [SwapInfo](struct.SwapInfo.html) so the account
+/// can be read and mutated in place without unpacking it whole.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SwapInfoData {
+    is_initialized: [u8; 1],
+    is_paused: [u8; 1],
+    /// Nonce used in program address
+    pub nonce: u8,
+    /// Token A
+    pub token_a: Pubkey,
+    /// Token B
+    pub token_b: Pubkey,
+    /// Pool token mint
+    pub pool_mint: Pubkey,
+    /// Mint information for token A
+    pub token_a_mint: Pubkey,
+    /// Mint information for token B
+    pub token_b_mint: Pubkey,
+    /// Admin token account to receive fees for token a
+    pub admin_fee_key_a: Pubkey,
+    /// Admin token account to receive fees for token b
+    pub admin_fee_key_b: Pubkey,
+    fees: [u8; FEES_SIZE],
+    rewards: [u8; REWARDS_SIZE],
+    pool_state: [u8; POOL_STATE_SIZE],
+    is_open_twap: [u8; 1],
+    block_timestamp_last: [u8; 8],
+    cumulative_ticks: [u8; 8],
+    base_price_cumulative_last: [u8; 16],
+    /// Version of the token-swap layout, following the version 1 layout
+    pub version: u8,
+    last_mid_price_update_ts: [u8; 8],
+    /// Pyth price account for token a
+    pub pyth_a: Pubkey,
+    /// Pyth price account for token b
+    pub pyth_b: Pubkey,
+    twap_window_secs: [u8; 8],
+    max_price_impact_bps: [u8; 8],
+    max_amount_in: [u8; 8],
+    max_amount_in_reserve_bps: [u8; 8],
+    max_swaps_per_slot: [u8; 8],
+    max_volume_per_slot: [u8; 8],
+    last_swap_slot: [u8; 8],
+    slot_swap_count: [u8; 8],
+    slot_swap_volume: [u8; 8],
+    is_fee_compounding: [u8; 1],
+    is_whitelist_only: [u8; 1],
+    /// Program called to approve the swaps and deposits, default when unset
+    pub transfer_hook_program: Pubkey,
+    reward_per_share: [u8; 16],
+    reward_last_update_ts: [u8; 8],
+    deltafi_price: [u8; 16],
+    halving_schedule: [u8; HALVING_SCHEDULE_SIZE],
+    gauge_votes: [u8; GAUGE_VOTES_SIZE],
+    gauge_weight: [u8; GAUGE_WEIGHT_SIZE],
+    /// Decimals of the token a mint
+    pub token_a_decimals: u8,
+    /// Decimals of the token b mint
+    pub token_b_decimals: u8,
+    max_base_reserve: [u8; 8],
+    max_quote_reserve: [u8; 8],
+    max_user_liquidity: [u8; 8],
+    is_immutable: [u8; 1],
+    directional_fees: [u8; DIRECTIONAL_FEES_SIZE],
+    min_trade_fee: [u8; 8],
+    is_fee_in_quote: [u8; 1],
+    has_freezable_mint: [u8; 1],
+    cumulative_fee_per_lp: [u8; 16],
+    /// OpenBook market pricing the pool, default when unset
+    pub openbook_market: Pubkey,
+    /// Config the token-swap was initialized with
+    pub config_key: Pubkey,
+    curve_type: [u8; CURVE_TYPE_SIZE],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for SwapInfoData {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for SwapInfoData {}
+
+const _: () = assert!(std::mem::size_of::<SwapInfoData>() == SWAP_INFO_SIZE);
+
+impl SwapInfoData {
+    /// Borrow an initialized token-swap account
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let swap_data: &Self = data
+            .get(..SWAP_INFO_SIZE)
+            .and_then(|data| try_from_bytes(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !swap_data.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(swap_data)
+    }
+
+    /// Mutably borrow an initialized token-swap account
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let swap_data: &mut Self = data
+            .get_mut(..SWAP_INFO_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !swap_data.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(swap_data)
+    }
+
+    /// Initialized state
+    pub fn is_initialized(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_initialized)
+    }
+
+    /// Paused state
+    pub fn is_paused(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_paused)
+    }
+
+    /// Fees
+    pub fn fees(&self) -> Result<Fees, ProgramError> {
+        Ok(Fees {
+            min_trade_fee: u64::from_le_bytes(self.min_trade_fee),
+            ..Fees::unpack_from_slice(&self.fees)?
+        })
+    }
+
+    /// Rewards
+    pub fn rewards(&self) -> Result<Rewards, ProgramError> {
+        Rewards::unpack_from_slice(&self.rewards)
+    }
+
+    /// Pool object, with the decimals of the token mints
+    pub fn pool_state(&self) -> Result<PoolState, ProgramError> {
+        Ok(PoolState {
+            base_decimals: self.token_a_decimals,
+            quote_decimals: self.token_b_decimals,
+            ..PoolState::unpack_from_slice(&self.pool_state)?
+        })
+    }
+
+    /// Update pool object, the decimals of the token mints are set at init
+    pub fn set_pool_state(&mut self, pool_state: &PoolState) {
+        pool_state.pack_into_slice(&mut self.pool_state);
+    }
+
+    /// twap open flag
+    pub fn is_open_twap(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_open_twap)
+    }
+
+    /// block timestamp last - twap
+    pub fn block_timestamp_last(&self) -> u64 {
+        u64::from_le_bytes(self.block_timestamp_last)
+    }
+
+    /// Update block timestamp last
+    pub fn set_block_timestamp_last(&mut self, block_timestamp_last: u64) {
+        self.block_timestamp_last = block_timestamp_last.to_le_bytes();
+    }
+
+    /// cumulative ticks in seconds
+    pub fn cumulative_ticks(&self) -> u64 {
+        u64::from_le_bytes(self.cumulative_ticks)
+    }
+
+    /// Update cumulative ticks
+    pub fn set_cumulative_ticks(&mut self, cumulative_ticks: u64) {
+        self.cumulative_ticks = cumulative_ticks.to_le_bytes();
+    }
+
+    /// base price cumulative last - twap
+    pub fn base_price_cumulative_last(&self) -> Decimal {
+        unpack_decimal(&self.base_price_cumulative_last)
+    }
+
+    /// Update base price cumulative last
+    pub fn set_base_price_cumulative_last(&mut self, base_price_cumulative_last: Decimal) {
+        pack_decimal(
+            base_price_cumulative_last,
+            &mut self.base_price_cumulative_last,
+        );
+    }
+
+    /// Timestamp of the last admin mid price update
+    pub fn last_mid_price_update_ts(&self) -> UnixTimestamp {
+        i64::from_le_bytes(self.last_mid_price_update_ts)
+    }
+
+    /// Seconds of observations averaged by the twap price, zero averages all of it
+    pub fn twap_window_secs(&self) -> u64 {
+        u64::from_le_bytes(self.twap_window_secs)
+    }
+
+    /// Max price impact of a swap in basis points, zero disables the check
+    pub fn max_price_impact_bps(&self) -> u64 {
+        u64::from_le_bytes(self.max_price_impact_bps)
+    }
+
+    /// Max amount sold by a swap, zero disables the check
+    pub fn max_amount_in(&self) -> u64 {
+        u64::from_le_bytes(self.max_amount_in)
+    }
+
+    /// Max amount sold by a swap in basis points of the pool reserve of the
+    /// token sold, zero disables the check
+    pub fn max_amount_in_reserve_bps(&self) -> u64 {
+        u64::from_le_bytes(self.max_amount_in_reserve_bps)
+    }
+
+    /// Max number of swaps in a slot, zero disables the check
+    pub fn max_swaps_per_slot(&self) -> u64 {
+        u64::from_le_bytes(self.max_swaps_per_slot)
+    }
+
+    /// Max base token volume of the swaps in a slot, zero disables the check
+    pub fn max_volume_per_slot(&self) -> u64 {
+        u64::from_le_bytes(self.max_volume_per_slot)
+    }
+
+    /// Whether the lp share of the trade fees is added to the pool targets
+    pub fn is_fee_compounding(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_fee_compounding)
+    }
+
+    /// Whether only the traders on the whitelist of the token-swap can swap
+    pub fn is_whitelist_only(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_whitelist_only)
+    }
+
+    /// Swaps traded in the slot of the last swap
+    pub fn slot_volume(&self) -> SlotVolume {
+        SlotVolume {
+            slot: u64::from_le_bytes(self.last_swap_slot),
+            swap_count: u64::from_le_bytes(self.slot_swap_count),
+            volume: u64::from_le_bytes(self.slot_swap_volume),
+        }
+    }
+
+    /// Set the swaps traded in the slot of the last swap
+    pub fn set_slot_volume(&mut self, slot_volume: &SlotVolume) {
+        self.last_swap_slot = slot_volume.slot.to_le_bytes();
+        self.slot_swap_count = slot_volume.swap_count.to_le_bytes();
+        self.slot_swap_volume = slot_volume.volume.to_le_bytes();
+    }
+
+    /// Time weighted average of the pool mid price
+    pub fn twap(&self) -> Twap {
+        Twap {
+            base_price_cumulative_last: self.base_price_cumulative_last(),
+            cumulative_ticks: self.cumulative_ticks(),
+        }
+    }
+
+    /// Update the time weighted average of the pool mid price
+    pub fn set_twap(&mut self, twap: &Twap) {
+        self.set_base_price_cumulative_last(twap.base_price_cumulative_last);
+        self.set_cumulative_ticks(twap.cumulative_ticks);
+    }
+
+    /// Liquidity rewards accumulated per pool token
+    pub fn reward_accumulator(&self) -> RewardAccumulator {
+        RewardAccumulator {
+            reward_per_share: unpack_decimal(&self.reward_per_share),
+            last_update_ts: i64::from_le_bytes(self.reward_last_update_ts),
+        }
+    }
+
+    /// Update the liquidity rewards accumulated per pool token
+    pub fn set_reward_accumulator(&mut self, reward_accumulator: &RewardAccumulator) {
+        pack_decimal(
+            reward_accumulator.reward_per_share,
+            &mut self.reward_per_share,
+        );
+        self.reward_last_update_ts = reward_accumulator.last_update_ts.to_le_bytes();
+    }
+
+    /// Price of the DELFI token the liquidity rewards are valued at, zero
+    /// before the first refresh
+    pub fn deltafi_price(&self) -> Decimal {
+        unpack_decimal(&self.deltafi_price)
+    }
+
+    /// Update the price of the DELFI token
+    pub fn set_deltafi_price(&mut self, deltafi_price: Decimal) {
+        pack_decimal(deltafi_price, &mut self.deltafi_price);
+    }
+
+    /// Halving schedule of the trade and liquidity rewards
+    pub fn halving_schedule(&self) -> Result<HalvingSchedule, ProgramError> {
+        HalvingSchedule::unpack_from_slice(&self.halving_schedule)
+    }
+
+    /// Gauge votes cast for the pool
+    pub fn gauge_votes(&self) -> Result<GaugeVotes, ProgramError> {
+        GaugeVotes::unpack_from_slice(&self.gauge_votes)
+    }
+
+    /// Set the gauge votes cast for the pool
+    pub fn set_gauge_votes(&mut self, gauge_votes: &GaugeVotes) {
+        gauge_votes.pack_into_slice(&mut self.gauge_votes);
+    }
+
+    /// Gauge weight of the liquidity rewards
+    pub fn gauge_weight(&self) -> Result<GaugeWeight, ProgramError> {
+        GaugeWeight::unpack_from_slice(&self.gauge_weight)
+    }
+
+    /// Set the gauge weight of the liquidity rewards
+    pub fn set_gauge_weight(&mut self, gauge_weight: &GaugeWeight) {
+        gauge_weight.pack_into_slice(&mut self.gauge_weight);
+    }
+
+    /// Max base token reserve after a deposit, zero disables the check
+    pub fn max_base_reserve(&self) -> u64 {
+        u64::from_le_bytes(self.max_base_reserve)
+    }
+
+    /// Max quote token reserve after a deposit, zero disables the check
+    pub fn max_quote_reserve(&self) -> u64 {
+        u64::from_le_bytes(self.max_quote_reserve)
+    }
+
+    /// Max liquidity position of a user after a deposit, zero disables the check
+    pub fn max_user_liquidity(&self) -> u64 {
+        u64::from_le_bytes(self.max_user_liquidity)
+    }
+
+    /// Whether the admin can no longer change the parameters of the token-swap
+    pub fn is_immutable(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_immutable)
+    }
+
+    /// Trade fee rates per swap direction
+    pub fn directional_fees(&self) -> Result<DirectionalFees, ProgramError> {
+        DirectionalFees::unpack_from_slice(&self.directional_fees)
+    }
+
+    /// Fees charged on a swap direction
+    pub fn swap_fees(&self, swap_direction: SwapDirection) -> Result<Fees, ProgramError> {
+        Ok(self
+            .directional_fees()?
+            .apply(&self.fees()?, swap_direction))
+    }
+
+    /// Whether the trade fees are collected in the quote token
+    pub fn is_fee_in_quote(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_fee_in_quote)
+    }
+
+    /// Whether a token mint of the pool has a freeze authority
+    pub fn has_freezable_mint(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.has_freezable_mint)
+    }
+
+    /// Lp share of the trade fees accumulated per base target
+    pub fn cumulative_fee_per_lp(&self) -> Decimal {
+        unpack_decimal(&self.cumulative_fee_per_lp)
+    }
+
+    /// Update the lp share of the trade fees accumulated per base target
+    pub fn set_cumulative_fee_per_lp(&mut self, cumulative_fee_per_lp: Decimal) {
+        pack_decimal(cumulative_fee_per_lp, &mut self.cumulative_fee_per_lp);
+    }
+
+    /// Curve the swaps are quoted on
+    pub fn curve_type(&self) -> Result<CurveType, ProgramError> {
+        CurveType::unpack_from_slice(&self.curve_type)
     }
 }
 
@@ -199,7 +1123,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        curve::{default_market_price, default_slope, Multiplier, PoolState},
+        curve::{default_market_price, default_slope, DynamicSlope, Multiplier, PoolState},
         solana_program::clock::Clock,
     };
 
@@ -215,6 +1139,8 @@ mod tests {
         let token_b_mint_raw = [7u8; 32];
         let admin_fee_key_a_raw = [8u8; 32];
         let admin_fee_key_b_raw = [9u8; 32];
+        let pyth_a_raw = [10u8; 32];
+        let pyth_b_raw = [11u8; 32];
         let token_a = Pubkey::new_from_array(token_a_raw);
         let token_b = Pubkey::new_from_array(token_b_raw);
         let pool_mint = Pubkey::new_from_array(pool_mint_raw);
@@ -222,7 +1148,12 @@ mod tests {
         let token_b_mint = Pubkey::new_from_array(token_b_mint_raw);
         let admin_fee_key_a = Pubkey::new_from_array(admin_fee_key_a_raw);
         let admin_fee_key_b = Pubkey::new_from_array(admin_fee_key_b_raw);
-        let fees = DEFAULT_TEST_FEES;
+        let pyth_a = Pubkey::new_from_array(pyth_a_raw);
+        let pyth_b = Pubkey::new_from_array(pyth_b_raw);
+        let fees = Fees {
+            min_trade_fee: 100,
+            ..DEFAULT_TEST_FEES
+        };
         let rewards = DEFAULT_TEST_REWARDS;
         let pool_state = PoolState::new(PoolState {
             market_price: default_market_price(),
@@ -232,6 +1163,8 @@ mod tests {
             base_reserve: Decimal::zero(),
             quote_reserve: Decimal::zero(),
             multiplier: Multiplier::One,
+            base_decimals: 9,
+            quote_decimals: 6,
         })
         .unwrap();
         let is_open_twap = true;
@@ -241,6 +1174,55 @@ mod tests {
             .unwrap();
         let cumulative_ticks = 0;
         let base_price_cumulative_last = Decimal::zero();
+        let last_mid_price_update_ts = 1_000;
+        let twap_window_secs = 3_600;
+        let max_price_impact_bps = 500;
+        let max_amount_in = 1_000_000;
+        let max_amount_in_reserve_bps = 1_000;
+        let max_swaps_per_slot = 4;
+        let max_volume_per_slot = 10_000_000;
+        let last_swap_slot = 100;
+        let slot_swap_count = 2;
+        let slot_swap_volume = 1_000;
+        let is_fee_compounding = true;
+        let is_whitelist_only = true;
+        let transfer_hook_program_raw = [7u8; 32];
+        let transfer_hook_program = Pubkey::new_from_array(transfer_hook_program_raw);
+        let reward_per_share = Decimal::from_scaled_val(12_345);
+        let reward_last_update_ts: UnixTimestamp = 2_000;
+        let deltafi_price = Decimal::from_scaled_val(100_000_000);
+        let halving_schedule = HalvingSchedule {
+            start_ts: 3_000,
+            period_secs: 86_400,
+            halving_count: 4,
+        };
+        let gauge_votes = GaugeVotes {
+            epoch: 5,
+            votes: 1_000,
+            previous_votes: 2_000,
+        };
+        let gauge_weight = GaugeWeight {
+            epoch: 5,
+            votes: 2_000,
+            total_votes: 8_000,
+        };
+        let max_base_reserve = 100_000_000_000;
+        let max_quote_reserve = 2_000_000_000_000;
+        let max_user_liquidity = 5_000_000_000;
+        let is_immutable = true;
+        let directional_fees = DirectionalFees {
+            sell_base_fee_numerator: 1,
+            sell_base_fee_denominator: 1_000,
+            sell_quote_fee_numerator: 5,
+            sell_quote_fee_denominator: 1_000,
+        };
+        let is_fee_in_quote = true;
+        let has_freezable_mint = true;
+        let cumulative_fee_per_lp = Decimal::from_scaled_val(7_000_000_001);
+        let openbook_market = Pubkey::new_unique();
+        let config_key = Pubkey::new_unique();
+        let max_slope = Decimal::from_percent(90);
+        let curve_type = CurveType::DynamicSlope(DynamicSlope::new(max_slope, 2).unwrap());
 
         let swap_info = SwapInfo {
             is_initialized,
@@ -260,6 +1242,39 @@ mod tests {
             block_timestamp_last,
             cumulative_ticks,
             base_price_cumulative_last,
+            last_mid_price_update_ts,
+            pyth_a,
+            pyth_b,
+            twap_window_secs,
+            max_price_impact_bps,
+            max_amount_in,
+            max_amount_in_reserve_bps,
+            max_swaps_per_slot,
+            max_volume_per_slot,
+            last_swap_slot,
+            slot_swap_count,
+            slot_swap_volume,
+            is_fee_compounding,
+            is_whitelist_only,
+            transfer_hook_program,
+            reward_per_share,
+            reward_last_update_ts,
+            deltafi_price,
+            halving_schedule,
+            gauge_votes,
+            gauge_weight,
+            max_base_reserve,
+            max_quote_reserve,
+            max_user_liquidity,
+            is_immutable,
+            directional_fees,
+            is_fee_in_quote,
+            has_freezable_mint,
+            cumulative_fee_per_lp,
+            openbook_market,
+            config_key,
+            curve_type,
+            version: PROGRAM_VERSION,
         };
 
         let mut packed = [0u8; SwapInfo::LEN];
@@ -294,15 +1309,432 @@ mod tests {
             &mut packed_base_price_cumulative_last,
         );
         packed.extend_from_slice(&packed_base_price_cumulative_last);
+        packed.push(PROGRAM_VERSION);
+        packed.extend_from_slice(&last_mid_price_update_ts.to_le_bytes());
+        packed.extend_from_slice(&pyth_a_raw);
+        packed.extend_from_slice(&pyth_b_raw);
+        packed.extend_from_slice(&twap_window_secs.to_le_bytes());
+        packed.extend_from_slice(&max_price_impact_bps.to_le_bytes());
+        packed.extend_from_slice(&max_amount_in.to_le_bytes());
+        packed.extend_from_slice(&max_amount_in_reserve_bps.to_le_bytes());
+        packed.extend_from_slice(&max_swaps_per_slot.to_le_bytes());
+        packed.extend_from_slice(&max_volume_per_slot.to_le_bytes());
+        packed.extend_from_slice(&last_swap_slot.to_le_bytes());
+        packed.extend_from_slice(&slot_swap_count.to_le_bytes());
+        packed.extend_from_slice(&slot_swap_volume.to_le_bytes());
+        packed.extend_from_slice(&(is_fee_compounding as u8).to_le_bytes());
+        packed.extend_from_slice(&(is_whitelist_only as u8).to_le_bytes());
+        packed.extend_from_slice(&transfer_hook_program_raw);
+        let mut packed_reward_per_share = [0u8; 16];
+        pack_decimal(reward_per_share, &mut packed_reward_per_share);
+        packed.extend_from_slice(&packed_reward_per_share);
+        packed.extend_from_slice(&reward_last_update_ts.to_le_bytes());
+        let mut packed_deltafi_price = [0u8; 16];
+        pack_decimal(deltafi_price, &mut packed_deltafi_price);
+        packed.extend_from_slice(&packed_deltafi_price);
+        packed.extend_from_slice(&halving_schedule.start_ts.to_le_bytes());
+        packed.extend_from_slice(&halving_schedule.period_secs.to_le_bytes());
+        packed.push(halving_schedule.halving_count);
+        packed.extend_from_slice(&gauge_votes.epoch.to_le_bytes());
+        packed.extend_from_slice(&gauge_votes.votes.to_le_bytes());
+        packed.extend_from_slice(&gauge_votes.previous_votes.to_le_bytes());
+        packed.extend_from_slice(&gauge_weight.epoch.to_le_bytes());
+        packed.extend_from_slice(&gauge_weight.votes.to_le_bytes());
+        packed.extend_from_slice(&gauge_weight.total_votes.to_le_bytes());
+        packed.push(pool_state.base_decimals);
+        packed.push(pool_state.quote_decimals);
+        packed.extend_from_slice(&max_base_reserve.to_le_bytes());
+        packed.extend_from_slice(&max_quote_reserve.to_le_bytes());
+        packed.extend_from_slice(&max_user_liquidity.to_le_bytes());
+        packed.push(is_immutable as u8);
+        packed.extend_from_slice(&directional_fees.sell_base_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&directional_fees.sell_base_fee_denominator.to_le_bytes());
+        packed.extend_from_slice(&directional_fees.sell_quote_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&directional_fees.sell_quote_fee_denominator.to_le_bytes());
+        packed.extend_from_slice(&fees.min_trade_fee.to_le_bytes());
+        packed.push(is_fee_in_quote as u8);
+        packed.push(has_freezable_mint as u8);
+        packed.extend_from_slice(&cumulative_fee_per_lp.to_scaled_val().unwrap().to_le_bytes());
+        packed.extend_from_slice(openbook_market.as_ref());
+        packed.extend_from_slice(config_key.as_ref());
+        packed.push(1);
+        packed.extend_from_slice(&max_slope.to_scaled_val().unwrap().to_le_bytes());
+        packed.extend_from_slice(&2u64.to_le_bytes());
 
         let unpacked = SwapInfo::unpack(&packed).unwrap();
         assert_eq!(swap_info, unpacked);
 
+        let swap_data = SwapInfoData::load(&packed).unwrap();
+        assert_eq!(swap_data.nonce, nonce);
+        assert_eq!(swap_data.token_a, token_a);
+        assert_eq!(swap_data.admin_fee_key_b, admin_fee_key_b);
+        assert_eq!(swap_data.fees().unwrap(), fees);
+        assert_eq!(swap_data.rewards().unwrap(), rewards);
+        assert_eq!(swap_data.pool_state().unwrap(), pool_state);
+        assert_eq!(swap_data.block_timestamp_last(), block_timestamp_last);
+        assert_eq!(
+            swap_data.last_mid_price_update_ts(),
+            last_mid_price_update_ts
+        );
+        assert_eq!(swap_data.pyth_b, pyth_b);
+        assert_eq!(swap_data.twap_window_secs(), twap_window_secs);
+        assert_eq!(swap_data.max_price_impact_bps(), max_price_impact_bps);
+        assert_eq!(swap_data.max_amount_in(), max_amount_in);
+        assert_eq!(
+            swap_data.max_amount_in_reserve_bps(),
+            max_amount_in_reserve_bps
+        );
+        assert_eq!(swap_data.max_swaps_per_slot(), max_swaps_per_slot);
+        assert_eq!(swap_data.max_volume_per_slot(), max_volume_per_slot);
+        assert_eq!(swap_data.slot_volume(), unpacked.slot_volume());
+        assert_eq!(swap_data.is_fee_compounding().unwrap(), is_fee_compounding);
+        assert_eq!(swap_data.is_whitelist_only().unwrap(), is_whitelist_only);
+        assert_eq!(swap_data.transfer_hook_program, transfer_hook_program);
+        assert_eq!(
+            swap_data.reward_accumulator(),
+            unpacked.reward_accumulator()
+        );
+        assert_eq!(swap_data.deltafi_price(), deltafi_price);
+        assert_eq!(swap_data.halving_schedule().unwrap(), halving_schedule);
+        assert_eq!(swap_data.gauge_votes().unwrap(), gauge_votes);
+        assert_eq!(swap_data.gauge_weight().unwrap(), gauge_weight);
+        assert_eq!(swap_data.max_base_reserve(), max_base_reserve);
+        assert_eq!(swap_data.max_quote_reserve(), max_quote_reserve);
+        assert_eq!(swap_data.max_user_liquidity(), max_user_liquidity);
+        assert_eq!(swap_data.is_immutable().unwrap(), is_immutable);
+        assert_eq!(swap_data.directional_fees().unwrap(), directional_fees);
+        assert_eq!(swap_data.is_fee_in_quote().unwrap(), is_fee_in_quote);
+        assert_eq!(swap_data.has_freezable_mint().unwrap(), has_freezable_mint);
+        assert_eq!(swap_data.cumulative_fee_per_lp(), cumulative_fee_per_lp);
+        assert_eq!(swap_data.openbook_market, openbook_market);
+        assert_eq!(swap_data.config_key, config_key);
+        assert_eq!(swap_data.curve_type().unwrap(), curve_type);
+
         let packed = [0u8; SwapInfo::LEN];
         let swap_info: SwapInfo = Default::default();
         let unpack_unchecked = SwapInfo::unpack_unchecked(&packed).unwrap();
         assert_eq!(unpack_unchecked, swap_info);
         let err = SwapInfo::unpack(&packed).unwrap_err();
         assert_eq!(err, ProgramError::UninitializedAccount);
+        let err = SwapInfoData::load(&packed).err().unwrap();
+        assert_eq!(err, ProgramError::UninitializedAccount);
+    }
+
+    #[test]
+    fn test_swap_info_data_offsets() {
+        fn offset_of<T>(swap_data: &SwapInfoData, field: &T) -> usize {
+            field as *const T as usize - swap_data as *const SwapInfoData as usize
+        }
+
+        let swap_info = SwapInfo {
+            is_initialized: true,
+            nonce: 254,
+            token_a: Pubkey::new_unique(),
+            token_b: Pubkey::new_unique(),
+            pool_mint: Pubkey::new_unique(),
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            admin_fee_key_a: Pubkey::new_unique(),
+            admin_fee_key_b: Pubkey::new_unique(),
+            pyth_a: Pubkey::new_unique(),
+            pyth_b: Pubkey::new_unique(),
+            transfer_hook_program: Pubkey::new_unique(),
+            openbook_market: Pubkey::new_unique(),
+            config_key: Pubkey::new_unique(),
+            version: PROGRAM_VERSION,
+            ..SwapInfo::default()
+        };
+        let mut packed = [0u8; SwapInfo::LEN];
+        swap_info.pack_into_slice(&mut packed);
+        let swap_data = SwapInfoData::load(&packed).unwrap();
+
+        for (field, key) in [
+            (&swap_data.token_a, swap_info.token_a),
+            (&swap_data.token_b, swap_info.token_b),
+            (&swap_data.pool_mint, swap_info.pool_mint),
+            (&swap_data.token_a_mint, swap_info.token_a_mint),
+            (&swap_data.token_b_mint, swap_info.token_b_mint),
+            (&swap_data.admin_fee_key_a, swap_info.admin_fee_key_a),
+            (&swap_data.admin_fee_key_b, swap_info.admin_fee_key_b),
+            (&swap_data.pyth_a, swap_info.pyth_a),
+            (&swap_data.pyth_b, swap_info.pyth_b),
+            (
+                &swap_data.transfer_hook_program,
+                swap_info.transfer_hook_program,
+            ),
+            (&swap_data.openbook_market, swap_info.openbook_market),
+            (&swap_data.config_key, swap_info.config_key),
+        ] {
+            let offset = offset_of(swap_data, field);
+            assert_eq!(&packed[offset..offset + PUBKEY_BYTES], key.as_ref());
+        }
+        assert_eq!(offset_of(swap_data, &swap_data.nonce), 2);
+        assert_eq!(offset_of(swap_data, &swap_data.version), SWAP_INFO_SIZE_V1);
+        assert_eq!(
+            offset_of(swap_data, &swap_data.config_key),
+            SWAP_INFO_SIZE - CURVE_TYPE_SIZE - PUBKEY_BYTES
+        );
+    }
+
+    #[test]
+    fn test_swap_info_unpack_versioned() {
+        let swap_info = SwapInfo {
+            is_initialized: true,
+            nonce: 255,
+            token_a: Pubkey::new_unique(),
+            fees: Fees {
+                min_trade_fee: 100,
+                ..DEFAULT_TEST_FEES
+            },
+            rewards: DEFAULT_TEST_REWARDS,
+            last_mid_price_update_ts: 1_000,
+            pyth_a: Pubkey::new_unique(),
+            pyth_b: Pubkey::new_unique(),
+            twap_window_secs: 3_600,
+            max_price_impact_bps: 500,
+            max_amount_in: 1_000_000,
+            max_amount_in_reserve_bps: 1_000,
+            max_swaps_per_slot: 4,
+            max_volume_per_slot: 10_000_000,
+            last_swap_slot: 100,
+            slot_swap_count: 2,
+            slot_swap_volume: 1_000,
+            is_fee_compounding: true,
+            is_whitelist_only: true,
+            transfer_hook_program: Pubkey::new_unique(),
+            reward_per_share: Decimal::one(),
+            reward_last_update_ts: 2_000,
+            deltafi_price: Decimal::one(),
+            halving_schedule: HalvingSchedule {
+                start_ts: 3_000,
+                period_secs: 86_400,
+                halving_count: 4,
+            },
+            gauge_votes: GaugeVotes {
+                epoch: 5,
+                votes: 1_000,
+                previous_votes: 2_000,
+            },
+            gauge_weight: GaugeWeight {
+                epoch: 5,
+                votes: 2_000,
+                total_votes: 8_000,
+            },
+            max_base_reserve: 100_000_000_000,
+            max_quote_reserve: 2_000_000_000_000,
+            max_user_liquidity: 5_000_000_000,
+            is_immutable: true,
+            directional_fees: DirectionalFees {
+                sell_base_fee_numerator: 1,
+                sell_base_fee_denominator: 1_000,
+                ..DirectionalFees::default()
+            },
+            is_fee_in_quote: true,
+            has_freezable_mint: true,
+            cumulative_fee_per_lp: Decimal::from(3u64),
+            openbook_market: Pubkey::new_unique(),
+            config_key: Pubkey::new_unique(),
+            curve_type: CurveType::DynamicSlope(
+                DynamicSlope::new(Decimal::from_percent(90), 1).unwrap(),
+            ),
+            version: PROGRAM_VERSION,
+            ..SwapInfo::default()
+        };
+        let mut packed = [0u8; SwapInfo::LEN];
+        swap_info.pack_into_slice(&mut packed);
+        assert_eq!(SwapInfo::stored_version(&packed).unwrap(), PROGRAM_VERSION);
+        assert_eq!(SwapInfo::unpack_versioned(&packed).unwrap(), swap_info);
+
+        // version 1 layout stops before the version byte
+        let legacy = &packed[..SWAP_INFO_SIZE_V1];
+        assert_eq!(SwapInfo::stored_version(legacy).unwrap(), PROGRAM_VERSION_1);
+        assert_eq!(
+            SwapInfo::unpack_versioned(legacy).unwrap(),
+            SwapInfo {
+                last_mid_price_update_ts: 0,
+                pyth_a: Pubkey::default(),
+                pyth_b: Pubkey::default(),
+                twap_window_secs: 0,
+                max_price_impact_bps: 0,
+                max_amount_in: 0,
+                max_amount_in_reserve_bps: 0,
+                max_swaps_per_slot: 0,
+                max_volume_per_slot: 0,
+                last_swap_slot: 0,
+                slot_swap_count: 0,
+                slot_swap_volume: 0,
+                is_fee_compounding: false,
+                is_whitelist_only: false,
+                transfer_hook_program: Pubkey::default(),
+                reward_per_share: Decimal::zero(),
+                reward_last_update_ts: 0,
+                deltafi_price: Decimal::zero(),
+                halving_schedule: HalvingSchedule::default(),
+                gauge_votes: GaugeVotes::default(),
+                gauge_weight: GaugeWeight::default(),
+                max_base_reserve: 0,
+                max_quote_reserve: 0,
+                max_user_liquidity: 0,
+                is_immutable: false,
+                fees: DEFAULT_TEST_FEES,
+                is_fee_in_quote: false,
+                has_freezable_mint: false,
+                cumulative_fee_per_lp: Decimal::zero(),
+                openbook_market: Pubkey::default(),
+                config_key: Pubkey::default(),
+                curve_type: CurveType::Standard,
+                directional_fees: DirectionalFees::default(),
+                version: PROGRAM_VERSION_1,
+                ..swap_info
+            }
+        );
+        assert_eq!(
+            SwapInfo::unpack(legacy).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+        assert_eq!(
+            SwapInfo::stored_version(&packed[..SWAP_INFO_SIZE_V1 - 1]).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+
+        packed[SWAP_INFO_SIZE_V1] = PROGRAM_VERSION + 1;
+        assert_eq!(
+            SwapInfo::unpack_versioned(&packed).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
+    #[test]
+    fn test_twap() {
+        let twap = Twap::default();
+        assert_eq!(twap.average().unwrap(), None);
+
+        let twap = twap
+            .accumulate(Decimal::from(10u64), 100)
+            .unwrap()
+            .accumulate(Decimal::from(20u64), 100)
+            .unwrap();
+        assert_eq!(twap.cumulative_ticks, 200);
+        assert_eq!(twap.average().unwrap(), Some(Decimal::from(15u64)));
+
+        // no time, no change
+        assert_eq!(twap.accumulate(Decimal::from(30u64), 0).unwrap(), twap);
+    }
+
+    #[test]
+    fn test_reward_accumulator() {
+        let reward_ratio = Decimal::one();
+
+        // the first accumulation only starts the period
+        let accumulator = RewardAccumulator::default()
+            .accumulate(reward_ratio, 1_000)
+            .unwrap();
+        assert_eq!(
+            accumulator,
+            RewardAccumulator {
+                reward_per_share: Decimal::zero(),
+                last_update_ts: 1_000,
+            }
+        );
+
+        let accumulator = accumulator.accumulate(reward_ratio, 1_010).unwrap();
+        assert_eq!(accumulator.reward_per_share, Decimal::from(10u64));
+        assert_eq!(accumulator.last_update_ts, 1_010);
+
+        let accumulator = accumulator
+            .accumulate(reward_ratio.try_mul(2).unwrap(), 1_015)
+            .unwrap();
+        assert_eq!(accumulator.reward_per_share, Decimal::from(20u64));
+
+        // time going backward accumulates nothing
+        assert_eq!(
+            accumulator.accumulate(reward_ratio, 1_000).unwrap(),
+            accumulator
+        );
+    }
+
+    #[test]
+    fn test_slot_volume() {
+        let slot_volume = SlotVolume::default()
+            .record(10, 100)
+            .unwrap()
+            .record(10, 200)
+            .unwrap();
+        assert_eq!(
+            slot_volume,
+            SlotVolume {
+                slot: 10,
+                swap_count: 2,
+                volume: 300,
+            }
+        );
+
+        // a new slot starts over
+        assert_eq!(
+            slot_volume.record(11, 50).unwrap(),
+            SlotVolume {
+                slot: 11,
+                swap_count: 1,
+                volume: 50,
+            }
+        );
+        assert_eq!(
+            slot_volume.record(10, u64::MAX).unwrap_err(),
+            SwapError::CalculationFailure.into()
+        );
+    }
+
+    #[test]
+    fn test_swap_info_data_in_place() {
+        assert_eq!(std::mem::size_of::<SwapInfoData>(), SwapInfo::LEN);
+
+        let swap_info = SwapInfo {
+            is_initialized: true,
+            is_open_twap: true,
+            pool_state: PoolState::new(PoolState {
+                market_price: default_market_price(),
+                slope: default_slope(),
+                ..PoolState::default()
+            })
+            .unwrap(),
+            ..SwapInfo::default()
+        };
+        let mut packed = [0u8; SwapInfo::LEN];
+        swap_info.pack_into_slice(&mut packed);
+
+        let pool_state = PoolState {
+            base_reserve: Decimal::from(100u64),
+            ..swap_info.pool_state.clone()
+        };
+        let swap_data = SwapInfoData::load_mut(&mut packed).unwrap();
+        swap_data.set_pool_state(&pool_state);
+        swap_data.set_block_timestamp_last(10);
+        swap_data.set_cumulative_ticks(20);
+        swap_data.set_base_price_cumulative_last(Decimal::from(30u64));
+        swap_data.set_slot_volume(&SlotVolume {
+            slot: 40,
+            swap_count: 1,
+            volume: 50,
+        });
+
+        assert_eq!(
+            SwapInfo::unpack(&packed).unwrap(),
+            SwapInfo {
+                pool_state,
+                block_timestamp_last: 10,
+                cumulative_ticks: 20,
+                base_price_cumulative_last: Decimal::from(30u64),
+                last_swap_slot: 40,
+                slot_swap_count: 1,
+                slot_swap_volume: 50,
+                ..swap_info
+            }
+        );
+        assert_eq!(
+            SwapInfoData::load(&packed[..SwapInfo::LEN - 1])
+                .err()
+                .unwrap(),
+            ProgramError::InvalidAccountData
+        );
     }
 }
@@ -0,0 +1,180 @@
+use bytemuck::{try_from_bytes, try_from_bytes_mut, Pod, Zeroable};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+use super::*;
+use crate::error::SwapError;
+
+/// Max number of traders on a whitelist
+pub const MAX_WHITELIST_TRADERS: usize = 64;
+/// Seed of the whitelist addresses, derived with the token-swap key
+pub const WHITELIST_SEED: &[u8] = b"whitelist";
+
+/// Whitelist size
+pub const WHITELIST_SIZE: usize = 36 + PUBKEY_BYTES * MAX_WHITELIST_TRADERS; // 1 + 1 + 32 + 2 + (32 * 64)
+
+/// Find the address of the whitelist of a token-swap
+pub fn find_whitelist_address(program_id: &Pubkey, swap: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[swap.as_ref(), WHITELIST_SEED], program_id)
+}
+
+/// Traders allowed to swap in a whitelist only token-swap, stored in a program
+/// address derived from `[swap, "whitelist"]`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Whitelist {
+    is_initialized: [u8; 1],
+    /// Bump seed of the whitelist address
+    pub bump_seed: u8,
+    /// Token-swap the whitelist applies to
+    pub swap: Pubkey,
+    len: [u8; 2],
+    traders: [Pubkey; MAX_WHITELIST_TRADERS],
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for Whitelist {}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for Whitelist {}
+
+impl Whitelist {
+    /// Initialize a whitelist in freshly allocated account data
+    ///
+    /// # Arguments
+    ///
+    /// * data - account data, all zero.
+    /// * swap - token-swap the whitelist applies to.
+    /// * bump_seed - bump seed of the whitelist address.
+    pub fn init(data: &mut [u8], swap: Pubkey, bump_seed: u8) -> Result<&mut Self, ProgramError> {
+        let whitelist: &mut Self = data
+            .get_mut(..WHITELIST_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if whitelist.is_initialized()? {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        pack_bool(true, &mut whitelist.is_initialized);
+        whitelist.bump_seed = bump_seed;
+        whitelist.swap = swap;
+        Ok(whitelist)
+    }
+
+    /// Borrow an initialized whitelist
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let whitelist: &Self = data
+            .get(..WHITELIST_SIZE)
+            .and_then(|data| try_from_bytes(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !whitelist.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(whitelist)
+    }
+
+    /// Mutably borrow an initialized whitelist
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let whitelist: &mut Self = data
+            .get_mut(..WHITELIST_SIZE)
+            .and_then(|data| try_from_bytes_mut(data).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !whitelist.is_initialized()? {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(whitelist)
+    }
+
+    /// Initialized state
+    pub fn is_initialized(&self) -> Result<bool, ProgramError> {
+        unpack_bool(&self.is_initialized)
+    }
+
+    /// Traders on the whitelist
+    pub fn traders(&self) -> &[Pubkey] {
+        let len = (u16::from_le_bytes(self.len) as usize).min(MAX_WHITELIST_TRADERS);
+        &self.traders[..len]
+    }
+
+    /// Whether a trader is on the whitelist
+    pub fn contains(&self, trader: &Pubkey) -> bool {
+        self.traders().contains(trader)
+    }
+
+    /// Add a trader, nothing changes if the trader is already on the whitelist
+    pub fn add(&mut self, trader: Pubkey) -> Result<(), ProgramError> {
+        if self.contains(&trader) {
+            return Ok(());
+        }
+        let len = self.traders().len();
+        if len == MAX_WHITELIST_TRADERS {
+            return Err(SwapError::WhitelistFull.into());
+        }
+        self.traders[len] = trader;
+        self.len = ((len + 1) as u16).to_le_bytes();
+        Ok(())
+    }
+
+    /// Remove a trader, moving the last trader into its place
+    pub fn remove(&mut self, trader: &Pubkey) {
+        if let Some(index) = self.traders().iter().position(|key| key == trader) {
+            let last = self.traders().len() - 1;
+            self.traders[index] = self.traders[last];
+            self.traders[last] = Pubkey::default();
+            self.len = (last as u16).to_le_bytes();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitelist_size() {
+        assert_eq!(std::mem::size_of::<Whitelist>(), WHITELIST_SIZE);
+    }
+
+    #[test]
+    fn test_add_remove() {
+        let mut data = vec![0u8; WHITELIST_SIZE];
+        let swap = Pubkey::new_unique();
+        let whitelist = Whitelist::init(&mut data, swap, 255).unwrap();
+        let traders: Vec<Pubkey> = (0..MAX_WHITELIST_TRADERS)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+
+        for trader in traders.iter() {
+            whitelist.add(*trader).unwrap();
+        }
+        // already added
+        whitelist.add(traders[0]).unwrap();
+        assert_eq!(whitelist.traders(), &traders[..]);
+        assert_eq!(
+            whitelist.add(Pubkey::new_unique()).unwrap_err(),
+            SwapError::WhitelistFull.into()
+        );
+
+        whitelist.remove(&traders[1]);
+        assert!(!whitelist.contains(&traders[1]));
+        assert_eq!(whitelist.traders().len(), MAX_WHITELIST_TRADERS - 1);
+        assert_eq!(whitelist.traders()[1], traders[MAX_WHITELIST_TRADERS - 1]);
+        // not on the whitelist
+        whitelist.remove(&Pubkey::new_unique());
+        assert_eq!(whitelist.traders().len(), MAX_WHITELIST_TRADERS - 1);
+
+        let data = data.clone();
+        let whitelist = Whitelist::load(&data).unwrap();
+        assert_eq!(whitelist.swap, swap);
+        assert!(whitelist.contains(&traders[0]));
+        assert_eq!(
+            Whitelist::init(&mut data.clone(), swap, 255).err(),
+            Some(SwapError::AlreadyInUse.into())
+        );
+        assert_eq!(
+            Whitelist::load(&vec![0u8; WHITELIST_SIZE]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+    }
+}
@@ -0,0 +1,1506 @@
+//! Account scaffolding of the integration tests, for programs testing their
+//! CPI to the swap program with `solana-program-test`
+//!
+//! Built with the `test-sdk` feature. Oracle accounts are left out, they are
+//! loaded from the fixtures of the tests.
+
+#![allow(clippy::too_many_arguments)]
+
+use crate::{
+    client::{find_market_authority, find_swap_authority},
+    curve::{Multiplier, PoolState},
+    instruction::{
+        append_position_pages, deposit, deposit_imbalanced, init_liquidity_provider,
+        init_observation_buffer, init_registry_page, initialize, initialize_config, swap, withdraw,
+        withdraw_exact, DepositData, InitializeData, SwapData, SwapDirection, WithdrawData,
+        WithdrawExactData,
+    },
+    math::{Decimal, TryDiv, TryMul},
+    pyth,
+    state::{
+        find_liquidity_provider_address, find_observation_buffer_address,
+        find_registry_page_address, ConfigInfo, FeeDiscount, Fees, GaugeVotes, LiquidityPosition,
+        LiquidityProvider, Observation, ObservationBuffer, Rewards, SwapInfo,
+        OBSERVATION_BUFFER_SIZE, PROGRAM_VERSION,
+    },
+};
+use assert_matches::*;
+use solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction::{create_account, transfer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::{
+    instruction::{approve, initialize_account, initialize_mint, set_authority, AuthorityType},
+    native_mint::DECIMALS,
+    state::{Account as Token, AccountState, Mint},
+};
+use std::{convert::TryFrom, str::FromStr};
+
+/// Lamports per SOL
+pub const LAMPORTS_TO_SOL: u64 = 1_000_000_000;
+/// Fractional units per USDC
+pub const FRACTIONAL_TO_USDC: u64 = 1_000_000;
+
+/// Unix timestamp zero
+pub const ZERO_TS: i64 = 0;
+
+/// Fees of the test configs and pools
+pub const TEST_FEES: Fees = Fees {
+    admin_trade_fee_numerator: 2,
+    admin_trade_fee_denominator: 5,
+    admin_withdraw_fee_numerator: 2,
+    admin_withdraw_fee_denominator: 5,
+    trade_fee_numerator: 5,
+    trade_fee_denominator: 1_000,
+    withdraw_fee_numerator: 2,
+    withdraw_fee_denominator: 100,
+    min_trade_fee: 0,
+};
+
+/// Rewards of the test configs and pools
+pub const TEST_REWARDS: Rewards = Rewards {
+    trade_reward_numerator: 1,
+    trade_reward_denominator: 1_000,
+    trade_reward_cap: 10_000_000_000,
+    liquidity_reward_numerator: 1,
+    liquidity_reward_denominator: 1_000,
+};
+
+/// Max deviation of a mid price update of the test configs
+pub const TEST_MAX_MID_PRICE_DEVIATION_BPS: u64 = 500;
+/// Min seconds between mid price updates of the test configs
+pub const TEST_MIN_MID_PRICE_UPDATE_INTERVAL: i64 = 3_600;
+
+/// Address of the SRM mint
+pub const SRM_MINT: &str = "SRMuApVNdxXokk5GT7XD5cUUgXMBCoAz2LHeuAoKWRt";
+
+/// Add accounts packed with `Pack` to a program test
+pub trait AddPacked {
+    /// Add an account holding the packed data
+    fn add_packable_account<T: Pack>(
+        &mut self,
+        pubkey: Pubkey,
+        amount: u64,
+        data: &T,
+        owner: &Pubkey,
+    );
+}
+
+impl AddPacked for ProgramTest {
+    fn add_packable_account<T: Pack>(
+        &mut self,
+        pubkey: Pubkey,
+        amount: u64,
+        data: &T,
+        owner: &Pubkey,
+    ) {
+        let mut account = Account::new(amount, T::get_packed_len(), owner);
+        data.pack_into_slice(&mut account.data);
+        self.add_account(pubkey, account);
+    }
+}
+
+/// Pyth oracle of a test
+pub struct TestOracle {
+    /// Product account
+    pub product_pubkey: Pubkey,
+    /// Price account
+    pub price_pubkey: Pubkey,
+    /// Price of the token
+    pub price: Decimal,
+}
+
+/// Token mint of a test
+pub struct TestMint {
+    /// Mint
+    pub pubkey: Pubkey,
+    /// Mint authority
+    pub authority: Keypair,
+    /// Decimals of the token
+    pub decimals: u8,
+}
+
+/// Add an initialized config, with the test fees and rewards
+pub fn add_swap_config(test: &mut ProgramTest) -> TestSwapConfig {
+    let swap_config_pubkey = Pubkey::new_unique();
+    let (market_authority, bump_seed) = find_market_authority(&crate::id(), &swap_config_pubkey);
+
+    let admin = Keypair::new();
+
+    let deltafi_mint = Pubkey::new_unique();
+    test.add_packable_account(
+        deltafi_mint,
+        u32::MAX as u64,
+        &Mint {
+            is_initialized: true,
+            decimals: DECIMALS,
+            mint_authority: COption::Some(market_authority),
+            freeze_authority: COption::Some(admin.pubkey()),
+            supply: 0,
+        },
+        &spl_token::id(),
+    );
+
+    test.add_packable_account(
+        swap_config_pubkey,
+        u32::MAX as u64,
+        &ConfigInfo {
+            version: PROGRAM_VERSION,
+            bump_seed,
+            admin_key: admin.pubkey(),
+            deltafi_mint,
+            fees: TEST_FEES,
+            rewards: TEST_REWARDS,
+            max_mid_price_deviation_bps: TEST_MAX_MID_PRICE_DEVIATION_BPS,
+            min_mid_price_update_interval: TEST_MIN_MID_PRICE_UPDATE_INTERVAL,
+            deltafi_price_oracle: Pubkey::default(),
+            epoch_reward_budget: 0,
+            reward_epoch: 0,
+            epoch_rewards_minted: 0,
+            poke_reward: 0,
+            min_poke_interval: 0,
+            gauge_votes: GaugeVotes::default(),
+            proposal_quorum: 0,
+            proposal_count: 0,
+            fee_discount: FeeDiscount::default(),
+            referral_reward_bps: 0,
+        },
+        &crate::id(),
+    );
+
+    TestSwapConfig {
+        pubkey: swap_config_pubkey,
+        admin,
+        market_authority,
+        deltafi_mint,
+        fees: TEST_FEES,
+        rewards: TEST_REWARDS,
+        max_mid_price_deviation_bps: TEST_MAX_MID_PRICE_DEVIATION_BPS,
+        min_mid_price_update_interval: TEST_MIN_MID_PRICE_UPDATE_INTERVAL,
+    }
+}
+
+#[derive(Default)]
+/// Arguments of `add_swap_info`
+pub struct AddSwapInfoArgs {
+    /// Token A mint
+    pub token_a_mint: Pubkey,
+    /// Token B mint
+    pub token_b_mint: Pubkey,
+    /// Token A reserve of the pool
+    pub token_a_amount: u64,
+    /// Token B reserve of the pool
+    pub token_b_amount: u64,
+    /// Whether the pool prices with its twap
+    pub is_open_twap: bool,
+    /// Pyth price account of token A
+    pub oracle_a: Pubkey,
+    /// Pyth price account of token B
+    pub oracle_b: Pubkey,
+    /// Market price of the pool
+    pub market_price: Decimal,
+    /// Slope of the pool
+    pub slope: Decimal,
+}
+
+/// Add an initialized pool of the config, its token accounts and pool mint
+pub fn add_swap_info(
+    test: &mut ProgramTest,
+    swap_config: &TestSwapConfig,
+    user_account_owner: &Keypair,
+    admin_account_owner: &Keypair,
+    args: AddSwapInfoArgs,
+) -> TestSwapInfo {
+    let AddSwapInfoArgs {
+        token_a_mint,
+        token_b_mint,
+        token_a_amount,
+        token_b_amount,
+        is_open_twap,
+        oracle_a,
+        oracle_b,
+        market_price,
+        slope,
+    } = args;
+
+    let mut pool_state = PoolState::new(PoolState {
+        market_price,
+        slope,
+        base_target: Decimal::zero(),
+        quote_target: Decimal::zero(),
+        base_reserve: Decimal::zero(),
+        quote_reserve: Decimal::zero(),
+        multiplier: Multiplier::One,
+        base_decimals: DECIMALS,
+        quote_decimals: DECIMALS,
+    })
+    .unwrap();
+
+    let pool_mint_amount = pool_state
+        .buy_shares(token_a_amount, token_b_amount, 0)
+        .unwrap();
+
+    let swap_info_pubkey = Pubkey::new_unique();
+    let (swap_authority_pubkey, nonce) = find_swap_authority(&crate::id(), &swap_info_pubkey);
+
+    let pool_mint = Pubkey::new_unique();
+    test.add_packable_account(
+        pool_mint,
+        u32::MAX as u64,
+        &Mint {
+            is_initialized: true,
+            decimals: DECIMALS,
+            mint_authority: COption::Some(swap_authority_pubkey),
+            freeze_authority: COption::None,
+            supply: pool_mint_amount,
+            ..Mint::default()
+        },
+        &spl_token::id(),
+    );
+
+    let pool_token = Pubkey::new_unique();
+    test.add_packable_account(
+        pool_token,
+        u32::MAX as u64,
+        &Token {
+            mint: pool_mint,
+            owner: user_account_owner.pubkey(),
+            amount: pool_mint_amount,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let token_a = Pubkey::new_unique();
+    test.add_packable_account(
+        token_a,
+        u32::MAX as u64,
+        &Token {
+            mint: token_a_mint,
+            owner: swap_authority_pubkey,
+            amount: token_a_amount,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let token_b = Pubkey::new_unique();
+    test.add_packable_account(
+        token_b,
+        u32::MAX as u64,
+        &Token {
+            mint: token_b_mint,
+            owner: swap_authority_pubkey,
+            amount: token_b_amount,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let admin_fee_a_key = Pubkey::new_unique();
+    test.add_packable_account(
+        admin_fee_a_key,
+        u32::MAX as u64,
+        &Token {
+            mint: token_a_mint,
+            owner: admin_account_owner.pubkey(),
+            amount: 0,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let admin_fee_b_key = Pubkey::new_unique();
+    test.add_packable_account(
+        admin_fee_b_key,
+        u32::MAX as u64,
+        &Token {
+            mint: token_b_mint,
+            owner: admin_account_owner.pubkey(),
+            amount: 0,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let swap_info = SwapInfo {
+        is_initialized: true,
+        is_paused: false,
+        nonce,
+        token_a,
+        token_b,
+        pool_mint,
+        token_a_mint,
+        token_b_mint,
+        admin_fee_key_a: admin_fee_a_key,
+        admin_fee_key_b: admin_fee_b_key,
+        fees: swap_config.fees.clone(),
+        rewards: swap_config.rewards.clone(),
+        is_open_twap,
+        pool_state,
+        pyth_a: oracle_a,
+        pyth_b: oracle_b,
+        // as refreshed from a DELFI oracle, for the liquidity rewards to accrue
+        deltafi_price: Decimal::one().try_div(10).unwrap(),
+        config_key: swap_config.pubkey,
+        version: PROGRAM_VERSION,
+        ..SwapInfo::default()
+    };
+
+    test.add_packable_account(swap_info_pubkey, u32::MAX as u64, &swap_info, &crate::id());
+    let observation_buffer = add_observation_buffer(test, swap_info_pubkey);
+
+    TestSwapInfo {
+        pubkey: swap_info_pubkey,
+        authority: swap_authority_pubkey,
+        nonce,
+        token_a,
+        token_b,
+        pool_token,
+        pool_mint,
+        token_a_mint,
+        token_b_mint,
+        admin_fee_a_key,
+        admin_fee_b_key,
+        is_open_twap,
+        twap_window_secs: 0,
+        observation_buffer,
+        fees: swap_config.fees.clone(),
+        rewards: swap_config.rewards.clone(),
+        oracle_a,
+        oracle_b,
+    }
+}
+
+/// Add the observation buffer of a token-swap, observed from its creation
+pub fn add_observation_buffer(test: &mut ProgramTest, swap_pubkey: Pubkey) -> Pubkey {
+    let (observation_buffer_pubkey, bump_seed) =
+        find_observation_buffer_address(&crate::id(), &swap_pubkey);
+    let mut account = Account::new(u32::MAX as u64, OBSERVATION_BUFFER_SIZE, &crate::id());
+    ObservationBuffer::init(&mut account.data, swap_pubkey, bump_seed)
+        .unwrap()
+        .record(Observation::new(0, Decimal::zero()));
+    test.add_account(observation_buffer_pubkey, account);
+    observation_buffer_pubkey
+}
+
+/// Add an empty liquidity provider of the owner
+pub fn add_liquidity_provider(
+    test: &mut ProgramTest,
+    user_account_owner: &Keypair,
+) -> TestLiquidityProvider {
+    let (liquidity_provider_pubkey, _) =
+        find_liquidity_provider_address(&crate::id(), &user_account_owner.pubkey());
+    test.add_packable_account(
+        liquidity_provider_pubkey,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: user_account_owner.pubkey(),
+            delegate: Pubkey::default(),
+            referrer: Pubkey::default(),
+            positions: vec![],
+        },
+        &crate::id(),
+    );
+
+    TestLiquidityProvider {
+        pubkey: liquidity_provider_pubkey,
+        owner: user_account_owner.pubkey(),
+        positions: vec![],
+        position_pages: vec![],
+    }
+}
+
+/// Add a liquidity provider of the owner with a position in the pool
+pub fn add_position(
+    test: &mut ProgramTest,
+    swap_info: &TestSwapInfo,
+    user_account_owner: &Keypair,
+    liquidity_amount: u64,
+) -> TestLiquidityProvider {
+    let (liquidity_provider_pubkey, _) =
+        find_liquidity_provider_address(&crate::id(), &user_account_owner.pubkey());
+    let mut liquidity_provider = LiquidityProvider {
+        is_initialized: true,
+        owner: user_account_owner.pubkey(),
+        delegate: Pubkey::default(),
+        referrer: Pubkey::default(),
+        positions: vec![],
+    };
+    liquidity_provider
+        .find_or_add_position(swap_info.pubkey, 0)
+        .unwrap()
+        .deposit(liquidity_amount)
+        .unwrap();
+
+    test.add_packable_account(
+        liquidity_provider_pubkey,
+        u32::MAX as u64,
+        &liquidity_provider,
+        &crate::id(),
+    );
+
+    TestLiquidityProvider {
+        pubkey: liquidity_provider_pubkey,
+        owner: user_account_owner.pubkey(),
+        positions: liquidity_provider.positions,
+        position_pages: vec![],
+    }
+}
+
+/// Config of a test
+pub struct TestSwapConfig {
+    /// Config account
+    pub pubkey: Pubkey,
+    /// Admin of the config
+    pub admin: Keypair,
+    /// Market authority of the config
+    pub market_authority: Pubkey,
+    /// DELFI mint
+    pub deltafi_mint: Pubkey,
+    /// Fees
+    pub fees: Fees,
+    /// Rewards
+    pub rewards: Rewards,
+    /// Max deviation of a mid price update
+    pub max_mid_price_deviation_bps: u64,
+    /// Min seconds between mid price updates
+    pub min_mid_price_update_interval: i64,
+}
+
+impl TestSwapConfig {
+    /// Initialize a config with the `InitializeConfig` instruction
+    pub async fn init(banks_client: &mut BanksClient, payer: &Keypair) -> Self {
+        let admin = Keypair::new();
+        let admin_pubkey = admin.pubkey();
+        let swap_config_keypair = Keypair::new();
+        let swap_config_pubkey = swap_config_keypair.pubkey();
+        let (market_authority_pubkey, _bump_seed) =
+            find_market_authority(&crate::id(), &swap_config_pubkey);
+        let deltafi_mint = Keypair::new();
+
+        let rent = banks_client.get_rent().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                create_account(
+                    &payer.pubkey(),
+                    &deltafi_mint.pubkey(),
+                    rent.minimum_balance(Mint::LEN),
+                    Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                initialize_mint(
+                    &spl_token::id(),
+                    &deltafi_mint.pubkey(),
+                    &market_authority_pubkey,
+                    Some(&admin_pubkey),
+                    DECIMALS,
+                )
+                .unwrap(),
+                create_account(
+                    &payer.pubkey(),
+                    &swap_config_pubkey,
+                    rent.minimum_balance(ConfigInfo::LEN),
+                    ConfigInfo::LEN as u64,
+                    &crate::id(),
+                ),
+                initialize_config(
+                    crate::id(),
+                    swap_config_pubkey,
+                    market_authority_pubkey,
+                    deltafi_mint.pubkey(),
+                    admin_pubkey,
+                    TEST_FEES,
+                    TEST_REWARDS,
+                    TEST_MAX_MID_PRICE_DEVIATION_BPS,
+                    TEST_MIN_MID_PRICE_UPDATE_INTERVAL,
+                )
+                .unwrap(),
+            ],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        transaction.sign(
+            &[payer, &admin, &swap_config_keypair, &deltafi_mint],
+            recent_blockhash,
+        );
+
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        Self {
+            pubkey: swap_config_pubkey,
+            admin,
+            market_authority: market_authority_pubkey,
+            deltafi_mint: deltafi_mint.pubkey(),
+            fees: TEST_FEES,
+            rewards: TEST_REWARDS,
+            max_mid_price_deviation_bps: TEST_MAX_MID_PRICE_DEVIATION_BPS,
+            min_mid_price_update_interval: TEST_MIN_MID_PRICE_UPDATE_INTERVAL,
+        }
+    }
+
+    /// Get the config account
+    pub async fn get_state(&self, banks_client: &mut BanksClient) -> ConfigInfo {
+        let swap_config_account: Account = banks_client
+            .get_account(self.pubkey)
+            .await
+            .unwrap()
+            .unwrap();
+        ConfigInfo::unpack(&swap_config_account.data[..]).unwrap()
+    }
+
+    /// Check the account matches the test
+    pub async fn validate_state(&self, banks_client: &mut BanksClient) {
+        let swap_config = self.get_state(banks_client).await;
+        assert_eq!(swap_config.version, PROGRAM_VERSION);
+        assert_eq!(swap_config.admin_key, self.admin.pubkey());
+        assert_eq!(swap_config.deltafi_mint, self.deltafi_mint);
+        assert_eq!(swap_config.fees, self.fees);
+        assert_eq!(swap_config.rewards, self.rewards);
+        assert_eq!(
+            swap_config.max_mid_price_deviation_bps,
+            self.max_mid_price_deviation_bps
+        );
+        assert_eq!(
+            swap_config.min_mid_price_update_interval,
+            self.min_mid_price_update_interval
+        );
+    }
+}
+
+/// Pool of a test
+pub struct TestSwapInfo {
+    /// Token-swap account
+    pub pubkey: Pubkey,
+    /// Swap authority
+    pub authority: Pubkey,
+    /// Nonce of the swap authority
+    pub nonce: u8,
+    /// Token A swap account
+    pub token_a: Pubkey,
+    /// Token B swap account
+    pub token_b: Pubkey,
+    /// Pool token account of the initial liquidity
+    pub pool_token: Pubkey,
+    /// Pool mint
+    pub pool_mint: Pubkey,
+    /// Token A mint
+    pub token_a_mint: Pubkey,
+    /// Token B mint
+    pub token_b_mint: Pubkey,
+    /// Admin fee account of token A
+    pub admin_fee_a_key: Pubkey,
+    /// Admin fee account of token B
+    pub admin_fee_b_key: Pubkey,
+    /// Whether the pool prices with its twap
+    pub is_open_twap: bool,
+    /// Seconds of observations averaged by the twap
+    pub twap_window_secs: u64,
+    /// Observation buffer
+    pub observation_buffer: Pubkey,
+    /// Fees
+    pub fees: Fees,
+    /// Rewards
+    pub rewards: Rewards,
+    /// Pyth price account of token A
+    pub oracle_a: Pubkey,
+    /// Pyth price account of token B
+    pub oracle_b: Pubkey,
+}
+
+/// Arguments of `TestSwapInfo::init`
+pub struct SwapInitArgs {
+    /// Scaled mid price
+    pub mid_price: u128,
+    /// Scaled slope
+    pub slope: u64,
+    /// Whether the pool prices with its twap
+    pub is_open_twap: bool,
+    /// Seconds of observations averaged by the twap
+    pub twap_window_secs: u64,
+}
+
+impl TestSwapInfo {
+    /// Initialize a pool with the `Initialize` instruction
+    pub async fn init(
+        banks_client: &mut BanksClient,
+        swap_config: &TestSwapConfig,
+        cracle_a: &TestOracle,
+        oracle_b: &TestOracle,
+        token_a_mint: Pubkey,
+        token_b_mint: Pubkey,
+        token_a: Pubkey,
+        token_b: Pubkey,
+        admin_fee_a_key: Pubkey,
+        admin_fee_b_key: Pubkey,
+        user_account_owner: &Keypair,
+        payer: &Keypair,
+        args: &SwapInitArgs,
+    ) -> Self {
+        Self::try_init(
+            banks_client,
+            swap_config,
+            cracle_a,
+            oracle_b,
+            token_a_mint,
+            token_b_mint,
+            token_a,
+            token_b,
+            admin_fee_a_key,
+            admin_fee_b_key,
+            user_account_owner,
+            payer,
+            args,
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Initialize a pool with the `Initialize` instruction, returns the error of the transaction
+    pub async fn try_init(
+        banks_client: &mut BanksClient,
+        swap_config: &TestSwapConfig,
+        cracle_a: &TestOracle,
+        oracle_b: &TestOracle,
+        token_a_mint: Pubkey,
+        token_b_mint: Pubkey,
+        token_a: Pubkey,
+        token_b: Pubkey,
+        admin_fee_a_key: Pubkey,
+        admin_fee_b_key: Pubkey,
+        user_account_owner: &Keypair,
+        payer: &Keypair,
+        args: &SwapInitArgs,
+    ) -> Result<Self, TransactionError> {
+        let swap_info = Keypair::new();
+        let swap_info_pubkey = swap_info.pubkey();
+
+        let (swap_authority_pubkey, nonce) = find_swap_authority(&crate::id(), &swap_info_pubkey);
+
+        let pool_mint_keypair = Keypair::new();
+        let user_pool_token_keypair = Keypair::new();
+
+        // the first pool of the config creates its registry
+        let (registry_page, _) = find_registry_page_address(&crate::id(), &swap_config.pubkey, 0);
+        if banks_client
+            .get_account(registry_page)
+            .await
+            .unwrap()
+            .is_none()
+        {
+            let mut transaction = Transaction::new_with_payer(
+                &[init_registry_page(crate::id(), swap_config.pubkey, payer.pubkey(), 0).unwrap()],
+                Some(&payer.pubkey()),
+            );
+            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+            transaction.sign(&[payer], recent_blockhash);
+            assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        }
+
+        let rent = banks_client.get_rent().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                create_account(
+                    &payer.pubkey(),
+                    &pool_mint_keypair.pubkey(),
+                    rent.minimum_balance(Mint::LEN),
+                    Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                initialize_mint(
+                    &spl_token::id(),
+                    &pool_mint_keypair.pubkey(),
+                    &swap_authority_pubkey,
+                    None,
+                    DECIMALS,
+                )
+                .unwrap(),
+                create_account(
+                    &payer.pubkey(),
+                    &user_pool_token_keypair.pubkey(),
+                    rent.minimum_balance(Token::LEN),
+                    Token::LEN as u64,
+                    &spl_token::id(),
+                ),
+                initialize_account(
+                    &spl_token::id(),
+                    &user_pool_token_keypair.pubkey(),
+                    &pool_mint_keypair.pubkey(),
+                    &user_account_owner.pubkey(),
+                )
+                .unwrap(),
+                set_authority(
+                    &spl_token::id(),
+                    &token_a,
+                    Some(&swap_authority_pubkey),
+                    AuthorityType::AccountOwner,
+                    &user_account_owner.pubkey(),
+                    &[],
+                )
+                .unwrap(),
+                set_authority(
+                    &spl_token::id(),
+                    &token_b,
+                    Some(&swap_authority_pubkey),
+                    AuthorityType::AccountOwner,
+                    &user_account_owner.pubkey(),
+                    &[],
+                )
+                .unwrap(),
+                create_account(
+                    &payer.pubkey(),
+                    &swap_info_pubkey,
+                    rent.minimum_balance(SwapInfo::LEN),
+                    SwapInfo::LEN as u64,
+                    &crate::id(),
+                ),
+                initialize(
+                    crate::id(),
+                    swap_config.pubkey,
+                    swap_info_pubkey,
+                    swap_authority_pubkey,
+                    admin_fee_a_key,
+                    admin_fee_b_key,
+                    token_a,
+                    token_b,
+                    pool_mint_keypair.pubkey(),
+                    user_pool_token_keypair.pubkey(),
+                    cracle_a.price_pubkey,
+                    oracle_b.price_pubkey,
+                    0,
+                    token_a_mint,
+                    token_b_mint,
+                    InitializeData {
+                        nonce,
+                        mid_price: args.mid_price,
+                        slope: args.slope,
+                        is_open_twap: args.is_open_twap,
+                        twap_window_secs: args.twap_window_secs,
+                        pair_symbol: None,
+                    },
+                )
+                .unwrap(),
+            ],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        transaction.sign(
+            &vec![
+                payer,
+                user_account_owner,
+                &swap_info,
+                &pool_mint_keypair,
+                &user_pool_token_keypair,
+            ],
+            recent_blockhash,
+        );
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap())?;
+
+        let mut transaction = Transaction::new_with_payer(
+            &[init_observation_buffer(crate::id(), swap_info_pubkey, payer.pubkey()).unwrap()],
+            Some(&payer.pubkey()),
+        );
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        transaction.sign(&[payer], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        let (observation_buffer, _) =
+            find_observation_buffer_address(&crate::id(), &swap_info_pubkey);
+
+        Ok(Self {
+            pubkey: swap_info_pubkey,
+            authority: swap_authority_pubkey,
+            nonce,
+            token_a,
+            token_b,
+            pool_token: user_pool_token_keypair.pubkey(),
+            pool_mint: pool_mint_keypair.pubkey(),
+            admin_fee_a_key,
+            admin_fee_b_key,
+            token_a_mint,
+            token_b_mint,
+            is_open_twap: args.is_open_twap,
+            twap_window_secs: args.twap_window_secs,
+            observation_buffer,
+            fees: swap_config.fees.clone(),
+            rewards: swap_config.rewards.clone(),
+            oracle_a: cracle_a.price_pubkey,
+            oracle_b: oracle_b.price_pubkey,
+        })
+    }
+
+    /// Swap, approving the source amount to a new transfer authority
+    pub async fn swap(
+        &self,
+        banks_client: &mut BanksClient,
+        config_info: &TestSwapConfig,
+        user_account_owner: &Keypair,
+        source_pubkey: Pubkey,
+        destination_pubkey: Pubkey,
+        reward_token_pubkey: Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        swap_direction: SwapDirection,
+        payer: &Keypair,
+    ) {
+        let user_transfer_authority = Keypair::new();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                approve(
+                    &spl_token::id(),
+                    &source_pubkey,
+                    &user_transfer_authority.pubkey(),
+                    &user_account_owner.pubkey(),
+                    &[],
+                    amount_in,
+                )
+                .unwrap(),
+                swap(
+                    crate::id(),
+                    config_info.pubkey,
+                    self.pubkey,
+                    config_info.market_authority,
+                    self.authority,
+                    user_transfer_authority.pubkey(),
+                    source_pubkey,
+                    self.token_a,
+                    self.token_b,
+                    destination_pubkey,
+                    reward_token_pubkey,
+                    config_info.deltafi_mint,
+                    self.admin_fee_b_key,
+                    self.oracle_a,
+                    self.oracle_b,
+                    self.pool_mint,
+                    SwapData {
+                        amount_in,
+                        minimum_amount_out,
+                        swap_direction,
+                        max_price_impact_bps: 0,
+                    },
+                )
+                .unwrap(),
+            ],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        transaction.sign(
+            &[payer, user_account_owner, &user_transfer_authority],
+            recent_blockhash,
+        );
+
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+    }
+
+    /// Deposit, approving the amounts to a new transfer authority
+    pub async fn deposit(
+        &self,
+        banks_client: &mut BanksClient,
+        liquidity_provider: &TestLiquidityProvider,
+        user_account_owner: &Keypair,
+        deposit_token_a_pubkey: Pubkey,
+        deposit_token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        min_mint_amount: u64,
+        payer: &Keypair,
+    ) {
+        let user_transfer_authority = Keypair::new();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                approve(
+                    &spl_token::id(),
+                    &deposit_token_a_pubkey,
+                    &user_transfer_authority.pubkey(),
+                    &user_account_owner.pubkey(),
+                    &[],
+                    token_a_amount,
+                )
+                .unwrap(),
+                approve(
+                    &spl_token::id(),
+                    &deposit_token_b_pubkey,
+                    &user_transfer_authority.pubkey(),
+                    &user_account_owner.pubkey(),
+                    &[],
+                    token_b_amount,
+                )
+                .unwrap(),
+                append_position_pages(
+                    deposit(
+                        crate::id(),
+                        self.pubkey,
+                        self.authority,
+                        user_transfer_authority.pubkey(),
+                        deposit_token_a_pubkey,
+                        deposit_token_b_pubkey,
+                        self.token_a,
+                        self.token_b,
+                        self.pool_mint,
+                        pool_token_pubkey,
+                        liquidity_provider.pubkey,
+                        liquidity_provider.owner,
+                        self.oracle_a,
+                        self.oracle_b,
+                        DepositData {
+                            token_a_amount,
+                            token_b_amount,
+                            min_mint_amount,
+                        },
+                    )
+                    .unwrap(),
+                    liquidity_provider.position_pages.clone(),
+                ),
+            ],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        transaction.sign(
+            &[payer, user_account_owner, &user_transfer_authority],
+            recent_blockhash,
+        );
+
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+    }
+
+    /// Deposit imbalanced amounts, approving them to a new transfer authority
+    pub async fn deposit_imbalanced(
+        &self,
+        banks_client: &mut BanksClient,
+        liquidity_provider: &TestLiquidityProvider,
+        user_account_owner: &Keypair,
+        deposit_token_a_pubkey: Pubkey,
+        deposit_token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        min_mint_amount: u64,
+        payer: &Keypair,
+    ) -> Result<(), TransactionError> {
+        let user_transfer_authority = Keypair::new();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                approve(
+                    &spl_token::id(),
+                    &deposit_token_a_pubkey,
+                    &user_transfer_authority.pubkey(),
+                    &user_account_owner.pubkey(),
+                    &[],
+                    token_a_amount,
+                )
+                .unwrap(),
+                approve(
+                    &spl_token::id(),
+                    &deposit_token_b_pubkey,
+                    &user_transfer_authority.pubkey(),
+                    &user_account_owner.pubkey(),
+                    &[],
+                    token_b_amount,
+                )
+                .unwrap(),
+                append_position_pages(
+                    deposit_imbalanced(
+                        crate::id(),
+                        self.pubkey,
+                        self.authority,
+                        user_transfer_authority.pubkey(),
+                        deposit_token_a_pubkey,
+                        deposit_token_b_pubkey,
+                        self.token_a,
+                        self.token_b,
+                        self.pool_mint,
+                        pool_token_pubkey,
+                        self.admin_fee_a_key,
+                        self.admin_fee_b_key,
+                        liquidity_provider.pubkey,
+                        liquidity_provider.owner,
+                        self.oracle_a,
+                        self.oracle_b,
+                        DepositData {
+                            token_a_amount,
+                            token_b_amount,
+                            min_mint_amount,
+                        },
+                    )
+                    .unwrap(),
+                    liquidity_provider.position_pages.clone(),
+                ),
+            ],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        transaction.sign(
+            &[payer, user_account_owner, &user_transfer_authority],
+            recent_blockhash,
+        );
+
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap())
+    }
+
+    /// Withdraw, approving the pool tokens to a new transfer authority
+    pub async fn withdraw(
+        &self,
+        banks_client: &mut BanksClient,
+        liquidity_provider: &TestLiquidityProvider,
+        user_account_owner: &Keypair,
+        token_a_pubkey: Pubkey,
+        token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        pool_token_amount: u64,
+        minimum_token_a_amount: u64,
+        minimum_token_b_amount: u64,
+        payer: &Keypair,
+    ) {
+        let user_transfer_authority = Keypair::new();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                approve(
+                    &spl_token::id(),
+                    &pool_token_pubkey,
+                    &user_transfer_authority.pubkey(),
+                    &user_account_owner.pubkey(),
+                    &[],
+                    pool_token_amount,
+                )
+                .unwrap(),
+                append_position_pages(
+                    withdraw(
+                        crate::id(),
+                        self.pubkey,
+                        self.authority,
+                        user_transfer_authority.pubkey(),
+                        self.pool_mint,
+                        pool_token_pubkey,
+                        self.token_a,
+                        self.token_b,
+                        token_a_pubkey,
+                        token_b_pubkey,
+                        self.admin_fee_a_key,
+                        self.admin_fee_b_key,
+                        liquidity_provider.pubkey,
+                        liquidity_provider.owner,
+                        self.oracle_a,
+                        self.oracle_b,
+                        WithdrawData {
+                            pool_token_amount,
+                            minimum_token_a_amount,
+                            minimum_token_b_amount,
+                        },
+                    )
+                    .unwrap(),
+                    liquidity_provider.position_pages.clone(),
+                ),
+            ],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        transaction.sign(
+            &[payer, user_account_owner, &user_transfer_authority],
+            recent_blockhash,
+        );
+
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+    }
+
+    /// Withdraw exact amounts, approving the pool tokens to a new transfer authority
+    pub async fn withdraw_exact(
+        &self,
+        banks_client: &mut BanksClient,
+        liquidity_provider: &TestLiquidityProvider,
+        user_account_owner: &Keypair,
+        token_a_pubkey: Pubkey,
+        token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        max_pool_token_amount: u64,
+        payer: &Keypair,
+    ) -> Result<(), TransactionError> {
+        let user_transfer_authority = Keypair::new();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                approve(
+                    &spl_token::id(),
+                    &pool_token_pubkey,
+                    &user_transfer_authority.pubkey(),
+                    &user_account_owner.pubkey(),
+                    &[],
+                    max_pool_token_amount,
+                )
+                .unwrap(),
+                append_position_pages(
+                    withdraw_exact(
+                        crate::id(),
+                        self.pubkey,
+                        self.authority,
+                        user_transfer_authority.pubkey(),
+                        self.pool_mint,
+                        pool_token_pubkey,
+                        self.token_a,
+                        self.token_b,
+                        token_a_pubkey,
+                        token_b_pubkey,
+                        self.admin_fee_a_key,
+                        self.admin_fee_b_key,
+                        liquidity_provider.pubkey,
+                        liquidity_provider.owner,
+                        self.oracle_a,
+                        self.oracle_b,
+                        WithdrawExactData {
+                            token_a_amount,
+                            token_b_amount,
+                            max_pool_token_amount,
+                        },
+                    )
+                    .unwrap(),
+                    liquidity_provider.position_pages.clone(),
+                ),
+            ],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        transaction.sign(
+            &[payer, user_account_owner, &user_transfer_authority],
+            recent_blockhash,
+        );
+
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap())
+    }
+
+    /// Get the token-swap account
+    pub async fn get_state(&self, banks_client: &mut BanksClient) -> SwapInfo {
+        let swap_account: Account = banks_client
+            .get_account(self.pubkey)
+            .await
+            .unwrap()
+            .unwrap();
+        SwapInfo::unpack(&swap_account.data[..]).unwrap()
+    }
+
+    /// Check the account matches the test
+    pub async fn validate_state(&self, banks_client: &mut BanksClient) {
+        let swap_info = self.get_state(banks_client).await;
+        assert!(swap_info.is_initialized);
+        assert_eq!(swap_info.version, PROGRAM_VERSION);
+        assert_eq!(swap_info.token_a, self.token_a);
+        assert_eq!(swap_info.token_b, self.token_b);
+        assert_eq!(swap_info.admin_fee_key_a, self.admin_fee_a_key);
+        assert_eq!(swap_info.admin_fee_key_b, self.admin_fee_b_key);
+        assert_eq!(swap_info.token_a_mint, self.token_a_mint);
+        assert_eq!(swap_info.token_b_mint, self.token_b_mint);
+        assert_eq!(swap_info.pool_state.base_decimals, DECIMALS);
+        assert_eq!(swap_info.pool_state.quote_decimals, DECIMALS);
+        assert_eq!(swap_info.is_open_twap, self.is_open_twap);
+        assert_eq!(swap_info.twap_window_secs, self.twap_window_secs);
+        assert_eq!(swap_info.fees, self.fees);
+        assert_eq!(swap_info.rewards, self.rewards);
+
+        let observation_buffer = banks_client
+            .get_account(self.observation_buffer)
+            .await
+            .unwrap()
+            .unwrap();
+        let observation_buffer = ObservationBuffer::load(&observation_buffer.data).unwrap();
+        assert_eq!(observation_buffer.swap, self.pubkey);
+        assert!(observation_buffer.cardinality() > 0);
+    }
+}
+
+/// Liquidity provider of a test
+pub struct TestLiquidityProvider {
+    /// Liquidity provider account
+    pub pubkey: Pubkey,
+    /// Owner of the liquidity provider
+    pub owner: Pubkey,
+    /// Positions of the liquidity provider
+    pub positions: Vec<LiquidityPosition>,
+    /// Position pages of the liquidity provider
+    pub position_pages: Vec<Pubkey>,
+}
+
+impl TestLiquidityProvider {
+    /// Initialize a liquidity provider with the `InitializeLiquidityProvider` instruction
+    pub async fn init(
+        banks_client: &mut BanksClient,
+        user_account_owner: &Keypair,
+        payer: &Keypair,
+    ) -> Self {
+        let (liquidity_provider_pubkey, _) =
+            find_liquidity_provider_address(&crate::id(), &user_account_owner.pubkey());
+
+        let rent = banks_client.get_rent().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                transfer(
+                    &payer.pubkey(),
+                    &user_account_owner.pubkey(),
+                    rent.minimum_balance(LiquidityProvider::LEN),
+                ),
+                init_liquidity_provider(crate::id(), user_account_owner.pubkey()).unwrap(),
+            ],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        transaction.sign(&vec![payer, user_account_owner], recent_blockhash);
+
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+        Self {
+            pubkey: liquidity_provider_pubkey,
+            owner: user_account_owner.pubkey(),
+            positions: vec![],
+            position_pages: vec![],
+        }
+    }
+
+    /// Get the liquidity provider account
+    pub async fn get_state(&self, banks_client: &mut BanksClient) -> LiquidityProvider {
+        let liquidity_provider: Account = banks_client
+            .get_account(self.pubkey)
+            .await
+            .unwrap()
+            .unwrap();
+        LiquidityProvider::unpack_account(&liquidity_provider.data[..]).unwrap()
+    }
+
+    /// Check the account matches the test
+    pub async fn validate_state(&self, banks_client: &mut BanksClient) {
+        let liquidity_provider = self.get_state(banks_client).await;
+        assert!(liquidity_provider.is_initialized);
+        assert_eq!(liquidity_provider.owner, self.owner);
+    }
+}
+
+/// Create a token account, minting the amount to it, wrapped for the native mint
+pub async fn create_and_mint_to_token_account(
+    banks_client: &mut BanksClient,
+    mint_pubkey: Pubkey,
+    mint_authority: Option<&Keypair>,
+    payer: &Keypair,
+    authority: Pubkey,
+    amount: u64,
+) -> Pubkey {
+    if let Some(mint_authority) = mint_authority {
+        let account_pubkey =
+            create_token_account(banks_client, mint_pubkey, &payer, Some(authority), None).await;
+
+        mint_to(
+            banks_client,
+            mint_pubkey,
+            &payer,
+            account_pubkey,
+            mint_authority,
+            amount,
+        )
+        .await;
+
+        account_pubkey
+    } else {
+        create_token_account(
+            banks_client,
+            mint_pubkey,
+            &payer,
+            Some(authority),
+            Some(amount),
+        )
+        .await
+    }
+}
+
+/// Create an empty token account
+pub async fn create_token_account(
+    banks_client: &mut BanksClient,
+    mint_pubkey: Pubkey,
+    payer: &Keypair,
+    authority: Option<Pubkey>,
+    native_amount: Option<u64>,
+) -> Pubkey {
+    let token_keypair = Keypair::new();
+    let token_pubkey = token_keypair.pubkey();
+    let authority_pubkey = authority.unwrap_or_else(|| payer.pubkey());
+
+    let rent = banks_client.get_rent().await.unwrap();
+    let lamports = rent.minimum_balance(Token::LEN) + native_amount.unwrap_or_default();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_account(
+                &payer.pubkey(),
+                &token_pubkey,
+                lamports,
+                Token::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &token_pubkey,
+                &mint_pubkey,
+                &authority_pubkey,
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &token_keypair], recent_blockhash);
+
+    assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+    token_pubkey
+}
+
+/// Mint tokens to the account
+pub async fn mint_to(
+    banks_client: &mut BanksClient,
+    mint_pubkey: Pubkey,
+    payer: &Keypair,
+    account_pubkey: Pubkey,
+    authority: &Keypair,
+    amount: u64,
+) {
+    let mut transaction = Transaction::new_with_payer(
+        &[spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &mint_pubkey,
+            &account_pubkey,
+            &authority.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer, authority], recent_blockhash);
+
+    assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+}
+
+/// Token balance of the account
+pub async fn get_token_balance(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
+    let token: Account = banks_client.get_account(pubkey).await.unwrap().unwrap();
+
+    spl_token::state::Account::unpack(&token.data[..])
+        .unwrap()
+        .amount
+}
+
+/// Add a mock pyth oracle of the price, price * 10^expo, valid at the slot
+pub fn add_pyth_oracle(
+    test: &mut ProgramTest,
+    price: i64,
+    expo: i32,
+    valid_slot: u64,
+) -> TestOracle {
+    let product_pubkey = Pubkey::new_unique();
+    let price_pubkey = Pubkey::new_unique();
+
+    test.add_account(
+        product_pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data: pyth::test::product_data(&price_pubkey, "USD"),
+            owner: pyth::id(),
+            ..Account::default()
+        },
+    );
+    test.add_account(
+        price_pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data: pyth::test::price_data(price, expo, valid_slot),
+            owner: pyth::id(),
+            ..Account::default()
+        },
+    );
+
+    let zeros = 10u64.pow(expo.unsigned_abs());
+    let price = Decimal::from(u64::try_from(price).unwrap());
+    TestOracle {
+        product_pubkey,
+        price_pubkey,
+        price: if expo >= 0 {
+            price.try_mul(zeros).unwrap()
+        } else {
+            price.try_div(zeros).unwrap()
+        },
+    }
+}
+
+/// Add the SRM mint
+pub fn add_srm_mint(test: &mut ProgramTest) -> TestMint {
+    let authority = Keypair::new();
+    let pubkey = Pubkey::from_str(SRM_MINT).unwrap();
+    let decimals = DECIMALS;
+    test.add_packable_account(
+        pubkey,
+        u32::MAX as u64,
+        &Mint {
+            is_initialized: true,
+            mint_authority: COption::Some(authority.pubkey()),
+            decimals,
+            ..Mint::default()
+        },
+        &spl_token::id(),
+    );
+
+    TestMint {
+        pubkey,
+        authority,
+        decimals,
+    }
+}
+
+/// Add a mint whose authority can also freeze its token accounts
+pub fn add_freezable_mint(test: &mut ProgramTest) -> TestMint {
+    let authority = Keypair::new();
+    let pubkey = Pubkey::new_unique();
+    let decimals = DECIMALS;
+    test.add_packable_account(
+        pubkey,
+        u32::MAX as u64,
+        &Mint {
+            is_initialized: true,
+            mint_authority: COption::Some(authority.pubkey()),
+            freeze_authority: COption::Some(authority.pubkey()),
+            decimals,
+            ..Mint::default()
+        },
+        &spl_token::id(),
+    );
+
+    TestMint {
+        pubkey,
+        authority,
+        decimals,
+    }
+}
@@ -0,0 +1,243 @@
+//! WebAssembly bindings of the quote math
+//!
+//! Built with the `wasm` feature, so web clients run the same curve code as
+//! the program instead of reimplementing it.
+
+use std::convert::TryFrom;
+
+use solana_program::{program_error::ProgramError, program_pack::Pack};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    curve::PoolState, error::SwapError, instruction::SwapDirection, math::*, state::SwapInfo,
+};
+
+fn to_js_error(err: ProgramError) -> JsValue {
+    JsValue::from_str(&err.to_string())
+}
+
+fn scaled_to_u64(value: Decimal) -> Result<u64, ProgramError> {
+    u64::try_from(value.to_scaled_val()?).map_err(|_| SwapError::CalculationFailure.into())
+}
+
+/// Swap quote
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WasmSwapQuote {
+    /// Amount sent to the user, after trade fee
+    pub amount_out: u64,
+    /// Trade fee charged on the output amount
+    pub trade_fee: u64,
+    /// Part of the trade fee sent to the admin fee account
+    pub admin_fee: u64,
+    /// Deltafi reward minted to the user
+    pub reward_amount: u64,
+    /// Price impact against the mid price, in basis points
+    pub price_impact_bps: u64,
+}
+
+/// Withdraw quote
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WasmWithdrawQuote {
+    /// Token A amount sent to the user, after withdraw fee
+    pub token_a_amount: u64,
+    /// Token B amount sent to the user, after withdraw fee
+    pub token_b_amount: u64,
+    /// Withdraw fee charged on token A
+    pub withdraw_fee_a: u64,
+    /// Withdraw fee charged on token B
+    pub withdraw_fee_b: u64,
+}
+
+/// Swap pool loaded from its account data
+#[wasm_bindgen]
+pub struct WasmPool {
+    swap: SwapInfo,
+}
+
+#[wasm_bindgen]
+impl WasmPool {
+    /// Unpack the pool from the token-swap account data
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8]) -> Result<WasmPool, JsValue> {
+        SwapInfo::unpack(data)
+            .map(|swap| Self { swap })
+            .map_err(to_js_error)
+    }
+
+    /// Adjust the pool to a new market price - real value * 10**9
+    #[wasm_bindgen(js_name = setMarketPrice)]
+    pub fn set_market_price(&mut self, market_price: u64) -> Result<(), JsValue> {
+        self.swap.pool_state = PoolState::new(PoolState {
+            market_price: Decimal::from_scaled_val(market_price.into()),
+            ..self.swap.pool_state.clone()
+        })
+        .map_err(to_js_error)?;
+        Ok(())
+    }
+
+    /// Mid price of the pool - real value * 10**9
+    #[wasm_bindgen(js_name = midPrice)]
+    pub fn mid_price(&self) -> Result<u64, JsValue> {
+        self.swap
+            .pool_state
+            .clone()
+            .get_mid_price()
+            .and_then(scaled_to_u64)
+            .map_err(to_js_error)
+    }
+
+    /// Value of a pool token in quote token amounts - real value * 10**9
+    #[wasm_bindgen(js_name = lpVirtualPrice)]
+    pub fn lp_virtual_price(&self, pool_mint_supply: u64) -> Result<u64, JsValue> {
+        self.swap
+            .lp_virtual_price(pool_mint_supply)
+            .and_then(scaled_to_u64)
+            .map_err(to_js_error)
+    }
+
+    /// Quote a swap, selling base token when `sell_base` is set
+    #[wasm_bindgen(js_name = quoteSwap)]
+    pub fn quote_swap(&self, amount_in: u64, sell_base: bool) -> Result<WasmSwapQuote, JsValue> {
+        let swap_direction = if sell_base {
+            SwapDirection::SellBase
+        } else {
+            SwapDirection::SellQuote
+        };
+        self.try_quote_swap(amount_in, swap_direction)
+            .map_err(to_js_error)
+    }
+
+    /// Quote pool tokens minted by a deposit
+    #[wasm_bindgen(js_name = quoteDeposit)]
+    pub fn quote_deposit(
+        &self,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        pool_mint_supply: u64,
+    ) -> Result<u64, JsValue> {
+        self.swap
+            .quote_deposit(token_a_amount, token_b_amount, pool_mint_supply)
+            .map_err(to_js_error)
+    }
+
+    /// Quote token amounts received by a withdraw
+    #[wasm_bindgen(js_name = quoteWithdraw)]
+    pub fn quote_withdraw(
+        &self,
+        pool_token_amount: u64,
+        pool_mint_supply: u64,
+    ) -> Result<WasmWithdrawQuote, JsValue> {
+        let quote = self
+            .swap
+            .quote_withdraw(pool_token_amount, pool_mint_supply)
+            .map_err(to_js_error)?;
+        Ok(WasmWithdrawQuote {
+            token_a_amount: quote.token_a_amount,
+            token_b_amount: quote.token_b_amount,
+            withdraw_fee_a: quote.withdraw_fee_a,
+            withdraw_fee_b: quote.withdraw_fee_b,
+        })
+    }
+}
+
+impl WasmPool {
+    fn try_quote_swap(
+        &self,
+        amount_in: u64,
+        swap_direction: SwapDirection,
+    ) -> Result<WasmSwapQuote, ProgramError> {
+        let quote = self.swap.quote_swap(amount_in, swap_direction)?;
+
+        let receive_amount = quote
+            .amount_out
+            .checked_add(quote.trade_fee)
+            .ok_or(SwapError::CalculationFailure)?;
+        let price_impact_bps =
+            self.swap
+                .pool_state
+                .price_impact_bps(amount_in, receive_amount, swap_direction)?;
+
+        Ok(WasmSwapQuote {
+            amount_out: quote.amount_out,
+            trade_fee: quote.trade_fee,
+            admin_fee: quote.admin_fee,
+            reward_amount: quote.reward_amount,
+            price_impact_bps,
+        })
+    }
+}
+
+/// Minimum amount out accepted for a quoted amount and a slippage in basis points
+#[wasm_bindgen(js_name = minimumAmountOut)]
+pub fn minimum_amount_out(amount_out: u64, slippage_bps: u64) -> u64 {
+    let slippage_bps = slippage_bps.min(BPS_DENOMINATOR);
+    (amount_out as u128 * (BPS_DENOMINATOR - slippage_bps) as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Message of a custom program error of the swap program, none for numbers
+/// not assigned to an error
+#[wasm_bindgen(js_name = errorMessage)]
+pub fn error_message(code: u32) -> Option<String> {
+    SwapError::try_from(code)
+        .ok()
+        .map(|error| error.to_string())
+}
+
+/// Errors of the swap program as the `errors` array of an Anchor IDL, in JSON
+#[wasm_bindgen(js_name = errorTable)]
+pub fn error_table() -> String {
+    SwapError::idl_errors_json()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        curve::{default_market_price, default_slope},
+        state::{DEFAULT_TEST_FEES, DEFAULT_TEST_REWARDS},
+    };
+
+    #[test]
+    fn test_price_impact() {
+        let mut pool_state = PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            ..PoolState::default()
+        })
+        .unwrap();
+        pool_state.buy_shares(1_000_000, 100_000_000, 0).unwrap();
+        let pool = WasmPool {
+            swap: SwapInfo {
+                is_initialized: true,
+                fees: DEFAULT_TEST_FEES,
+                rewards: DEFAULT_TEST_REWARDS,
+                pool_state,
+                ..SwapInfo::default()
+            },
+        };
+
+        let small = pool.try_quote_swap(100, SwapDirection::SellBase).unwrap();
+        let large = pool
+            .try_quote_swap(100_000, SwapDirection::SellBase)
+            .unwrap();
+        assert_eq!(small.amount_out + small.trade_fee, 10_000);
+        assert_eq!(small.price_impact_bps, 0);
+        assert!(large.price_impact_bps > small.price_impact_bps);
+
+        let quote = pool
+            .try_quote_swap(100_000, SwapDirection::SellQuote)
+            .unwrap();
+        assert!(quote.price_impact_bps < BPS_DENOMINATOR);
+    }
+
+    #[test]
+    fn test_minimum_amount_out() {
+        assert_eq!(minimum_amount_out(1_000_000, 0), 1_000_000);
+        assert_eq!(minimum_amount_out(1_000_000, 50), 995_000);
+        assert_eq!(minimum_amount_out(999, 100), 989);
+        assert_eq!(minimum_amount_out(u64::MAX, 0), u64::MAX);
+        assert_eq!(minimum_amount_out(1_000_000, 20_000), 0);
+    }
+}
@@ -0,0 +1,309 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        append_associated_token_accounts, claim_liquidity_rewards, withdraw, WithdrawData,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_liquidity_provider_address, LiquidityPosition, LiquidityProvider},
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::instruction::approve;
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    liquidity_provider: Pubkey,
+}
+
+const REWARDS_OWED: u64 = 1_000_000;
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (liquidity_provider, _) =
+        find_liquidity_provider_address(&deltafi_swap::id(), &user_account_owner.pubkey());
+    test.add_packable_account(
+        liquidity_provider,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: user_account_owner.pubkey(),
+            delegate: Pubkey::default(),
+            referrer: Pubkey::default(),
+            positions: vec![LiquidityPosition {
+                pool: swap_info.pubkey,
+                liquidity_amount: 2_000_000_000,
+                rewards_owed: REWARDS_OWED,
+                ..LiquidityPosition::default()
+            }],
+        },
+        &deltafi_swap::id(),
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        liquidity_provider,
+    }
+}
+
+fn withdraw_instruction(
+    context: &TestContext,
+    user_transfer_authority: Pubkey,
+    destination_token_a: Pubkey,
+    destination_token_b: Pubkey,
+) -> Instruction {
+    withdraw(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.swap_info.authority,
+        user_transfer_authority,
+        context.swap_info.pool_mint,
+        context.swap_info.pool_token,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        destination_token_a,
+        destination_token_b,
+        context.swap_info.admin_fee_a_key,
+        context.swap_info.admin_fee_b_key,
+        context.liquidity_provider,
+        context.user_account_owner.pubkey(),
+        context.swap_info.oracle_a,
+        context.swap_info.oracle_b,
+        WithdrawData {
+            pool_token_amount: 2_000_000_000,
+            minimum_token_a_amount: 0,
+            minimum_token_b_amount: 0,
+        },
+    )
+    .unwrap()
+}
+
+async fn process_withdraw(
+    context: &mut TestContext,
+    instruction: Instruction,
+    user_transfer_authority: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &context.swap_info.pool_token,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                2_000_000_000,
+            )
+            .unwrap(),
+            instruction,
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.user_account_owner,
+            user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_withdraw_creates_accounts() {
+    let mut context = setup().await;
+    let owner = context.user_account_owner.pubkey();
+    let sol_account = get_associated_token_address(&owner, &context.swap_info.token_a_mint);
+    let srm_account = get_associated_token_address(&owner, &context.swap_info.token_b_mint);
+    assert!(context
+        .banks_client
+        .get_account(srm_account)
+        .await
+        .unwrap()
+        .is_none());
+
+    let user_transfer_authority = Keypair::new();
+    let instruction = append_associated_token_accounts(
+        withdraw_instruction(
+            &context,
+            user_transfer_authority.pubkey(),
+            sol_account,
+            srm_account,
+        ),
+        context.payer.pubkey(),
+        vec![
+            context.swap_info.token_a_mint,
+            context.swap_info.token_b_mint,
+        ],
+    );
+    process_withdraw(&mut context, instruction, &user_transfer_authority)
+        .await
+        .unwrap();
+
+    assert!(get_token_balance(&mut context.banks_client, sol_account).await > 0);
+    assert!(get_token_balance(&mut context.banks_client, srm_account).await > 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_existing_account() {
+    let mut context = setup().await;
+    let owner = context.user_account_owner.pubkey();
+    let sol_account = get_associated_token_address(&owner, &context.swap_info.token_a_mint);
+    // not an associated token account, left as it is
+    let srm_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        context.swap_info.token_b_mint,
+        None,
+        &context.payer,
+        owner,
+        0,
+    )
+    .await;
+
+    let user_transfer_authority = Keypair::new();
+    let instruction = append_associated_token_accounts(
+        withdraw_instruction(
+            &context,
+            user_transfer_authority.pubkey(),
+            sol_account,
+            srm_account,
+        ),
+        context.payer.pubkey(),
+        vec![
+            context.swap_info.token_a_mint,
+            context.swap_info.token_b_mint,
+        ],
+    );
+    process_withdraw(&mut context, instruction, &user_transfer_authority)
+        .await
+        .unwrap();
+
+    assert!(get_token_balance(&mut context.banks_client, sol_account).await > 0);
+    assert!(get_token_balance(&mut context.banks_client, srm_account).await > 0);
+}
+
+#[tokio::test]
+async fn test_withdraw_incorrect_mint() {
+    let mut context = setup().await;
+    let owner = context.user_account_owner.pubkey();
+    let sol_account = get_associated_token_address(&owner, &context.swap_info.token_a_mint);
+    let srm_account = get_associated_token_address(&owner, &context.swap_info.token_b_mint);
+
+    let user_transfer_authority = Keypair::new();
+    let instruction = append_associated_token_accounts(
+        withdraw_instruction(
+            &context,
+            user_transfer_authority.pubkey(),
+            sol_account,
+            srm_account,
+        ),
+        context.payer.pubkey(),
+        vec![
+            context.swap_info.token_b_mint,
+            context.swap_info.token_a_mint,
+        ],
+    );
+    assert_eq!(
+        process_withdraw(&mut context, instruction, &user_transfer_authority)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::IncorrectMint as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_claim_creates_account() {
+    let mut context = setup().await;
+    let owner = context.user_account_owner.pubkey();
+    let deltafi_account = get_associated_token_address(&owner, &context.swap_config.deltafi_mint);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[append_associated_token_accounts(
+            claim_liquidity_rewards(
+                deltafi_swap::id(),
+                context.swap_config.pubkey,
+                context.swap_info.pubkey,
+                context.swap_config.market_authority,
+                context.liquidity_provider,
+                owner,
+                deltafi_account,
+                context.swap_config.deltafi_mint,
+            )
+            .unwrap(),
+            context.payer.pubkey(),
+            vec![context.swap_config.deltafi_mint],
+        )],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.user_account_owner],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, deltafi_account).await,
+        REWARDS_OWED
+    );
+}
@@ -0,0 +1,242 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{batch_swap, swap, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+const AMOUNT_IN: u64 = 1_000_000_000;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_infos: Vec<TestSwapInfo>,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_infos = (0..2)
+        .map(|_| {
+            add_swap_info(
+                &mut test,
+                &swap_config,
+                &user_account_owner,
+                &admin_account_owner,
+                AddSwapInfoArgs {
+                    token_a_mint: spl_token::native_mint::id(),
+                    token_b_mint: srm_mint.pubkey,
+                    token_a_amount: 42_000_000_000,
+                    token_b_amount: 800_000_000_000,
+                    is_open_twap: true,
+                    oracle_a: sol_oracle.price_pubkey,
+                    oracle_b: srm_oracle.price_pubkey,
+                    market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+                    slope: Decimal::one().try_div(2).unwrap(),
+                },
+            )
+        })
+        .collect();
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_infos,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+// sells SOL for SRM on the token-swap
+fn swap_instruction(
+    context: &TestContext,
+    swap_info: &TestSwapInfo,
+    user_transfer_authority: Pubkey,
+) -> Instruction {
+    swap(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        swap_info.pubkey,
+        context.swap_config.market_authority,
+        swap_info.authority,
+        user_transfer_authority,
+        context.sol_user_account,
+        swap_info.token_a,
+        swap_info.token_b,
+        context.srm_user_account,
+        context.deltafi_user_account,
+        context.swap_config.deltafi_mint,
+        swap_info.admin_fee_b_key,
+        swap_info.oracle_a,
+        swap_info.oracle_b,
+        swap_info.pool_mint,
+        SwapData {
+            amount_in: AMOUNT_IN,
+            minimum_amount_out: 0,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        },
+    )
+    .unwrap()
+}
+
+async fn try_batch_swap(
+    context: &mut TestContext,
+    swap_indexes: &[usize],
+    minimum_total_amount_out: u64,
+) -> Result<(), TransactionError> {
+    let user_transfer_authority = Keypair::new();
+    let swaps = swap_indexes
+        .iter()
+        .map(|index| {
+            swap_instruction(
+                context,
+                &context.swap_infos[*index],
+                user_transfer_authority.pubkey(),
+            )
+        })
+        .collect();
+    let instruction = batch_swap(swaps, minimum_total_amount_out).unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &context.sol_user_account,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                AMOUNT_IN * swap_indexes.len() as u64,
+            )
+            .unwrap(),
+            instruction,
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.user_account_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_batch_swap() {
+    let mut context = setup().await;
+    let sol_balance = get_token_balance(&mut context.banks_client, context.sol_user_account).await;
+
+    try_batch_swap(&mut context, &[0, 1], 1).await.unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.sol_user_account).await,
+        sol_balance - 2 * AMOUNT_IN
+    );
+    assert!(get_token_balance(&mut context.banks_client, context.srm_user_account).await > 0);
+    // each pool received its input
+    for swap_info in context.swap_infos.iter() {
+        assert_eq!(
+            get_token_balance(&mut context.banks_client, swap_info.token_a).await,
+            42_000_000_000 + AMOUNT_IN
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_batch_swap_exceeded_slippage() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_batch_swap(&mut context, &[0, 1], u64::MAX)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::ExceededSlippage as u32)
+        )
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.srm_user_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_batch_swap_same_pool() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_batch_swap(&mut context, &[0, 0], 0).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidInput as u32)
+        )
+    );
+}
@@ -0,0 +1,234 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{buyback_and_burn, BuybackAndBurn},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::AccountSharedData,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as Token, Mint};
+use utils::*;
+
+const ADMIN_FEES: u64 = 1_000_000_000;
+const DELFI_SUPPLY: u64 = 10_000_000_000_000;
+
+struct TestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    // SOL/DELFI pool, buying back DELFI with its own SOL admin fees
+    swap_info: TestSwapInfo,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    // mocks the DELFI/USD price account
+    let deltafi_oracle = add_srm_oracle(&mut test);
+
+    let user_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &swap_config.admin,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: swap_config.deltafi_mint,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: deltafi_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(deltafi_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    // the DELFI in the pool is backed by the supply it is burned from
+    update_packed::<Mint>(&mut context, swap_config.deltafi_mint, |mint| {
+        mint.supply = DELFI_SUPPLY
+    })
+    .await;
+    update_packed::<Token>(&mut context, swap_info.admin_fee_a_key, |token| {
+        token.amount = ADMIN_FEES
+    })
+    .await;
+
+    TestContext {
+        context,
+        swap_config,
+        swap_info,
+    }
+}
+
+async fn update_packed<T: Pack>(
+    context: &mut ProgramTestContext,
+    pubkey: Pubkey,
+    update: impl FnOnce(&mut T),
+) {
+    let mut account = context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = T::unpack_unchecked(&account.data).unwrap();
+    update(&mut state);
+    T::pack(state, &mut account.data).unwrap();
+    context.set_account(&pubkey, &AccountSharedData::from(account));
+}
+
+async fn get_packed<T: Pack>(context: &mut ProgramTestContext, pubkey: Pubkey) -> T {
+    let account = context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    T::unpack(&account.data).unwrap()
+}
+
+fn buyback_instruction(
+    test_context: &TestContext,
+    admin: Pubkey,
+    admin_fee_source: Pubkey,
+) -> Instruction {
+    let swap_info = &test_context.swap_info;
+    buyback_and_burn(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        swap_info.pubkey,
+        swap_info.authority,
+        admin,
+        admin_fee_source,
+        swap_info.pubkey,
+        swap_info.authority,
+        swap_info.token_a,
+        swap_info.token_b,
+        swap_info.admin_fee_b_key,
+        test_context.swap_config.deltafi_mint,
+        swap_info.oracle_a,
+        swap_info.oracle_b,
+        swap_info.pool_mint,
+        BuybackAndBurn {
+            amount_in: ADMIN_FEES,
+            minimum_amount_out: 0,
+        },
+    )
+    .unwrap()
+}
+
+async fn process_instruction(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, signer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_buyback_and_burn() {
+    let mut test_context = setup().await;
+    let admin_fee_source = test_context.swap_info.admin_fee_a_key;
+    let admin_fee_destination = test_context.swap_info.admin_fee_b_key;
+    let deltafi_reserve = test_context.swap_info.token_b;
+    let deltafi_mint = test_context.swap_config.deltafi_mint;
+
+    let reserve_before =
+        get_token_balance(&mut test_context.context.banks_client, deltafi_reserve).await;
+
+    let instruction = buyback_instruction(
+        &test_context,
+        test_context.swap_config.admin.pubkey(),
+        admin_fee_source,
+    );
+    process_instruction(
+        &mut test_context.context,
+        instruction,
+        &test_context.swap_config.admin,
+    )
+    .await
+    .unwrap();
+
+    let banks_client = &mut test_context.context.banks_client;
+    assert_eq!(get_token_balance(banks_client, admin_fee_source).await, 0);
+    let admin_fee = get_token_balance(banks_client, admin_fee_destination).await;
+    let reserve_after = get_token_balance(banks_client, deltafi_reserve).await;
+    let burned = reserve_before - reserve_after - admin_fee;
+    assert!(burned > 0);
+
+    let mint = get_packed::<Mint>(&mut test_context.context, deltafi_mint).await;
+    assert_eq!(mint.supply, DELFI_SUPPLY - burned);
+}
+
+#[tokio::test]
+async fn test_buyback_incorrect_mint() {
+    let mut test_context = setup().await;
+
+    // DELFI admin fees cannot buy DELFI
+    let instruction = buyback_instruction(
+        &test_context,
+        test_context.swap_config.admin.pubkey(),
+        test_context.swap_info.admin_fee_b_key,
+    );
+    assert_eq!(
+        process_instruction(
+            &mut test_context.context,
+            instruction,
+            &test_context.swap_config.admin,
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectMint as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_buyback_not_admin() {
+    let mut test_context = setup().await;
+    let not_admin = Keypair::new();
+
+    let instruction = buyback_instruction(
+        &test_context,
+        not_admin.pubkey(),
+        test_context.swap_info.admin_fee_a_key,
+    );
+    assert_eq!(
+        process_instruction(&mut test_context.context, instruction, &not_admin)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,289 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        calc_receive_amount, init_quote_account, swap, QuoteSwapData, SwapData, SwapDirection,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_quote_account_address, QuoteAccount},
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    quote_owner: Keypair,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let quote_owner = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[init_quote_account(deltafi_swap::id(), quote_owner.pubkey(), payer.pubkey()).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        quote_owner,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+fn calc(context: &TestContext, owner: Pubkey, amount_in: u64) -> Instruction {
+    calc_receive_amount(
+        deltafi_swap::id(),
+        owner,
+        context.swap_info.pubkey,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        context.swap_info.oracle_a,
+        context.swap_info.oracle_b,
+        QuoteSwapData {
+            amount_in,
+            swap_direction: SwapDirection::SellBase,
+        },
+    )
+    .unwrap()
+}
+
+async fn get_quote_account_data(context: &mut TestContext) -> Vec<u8> {
+    let (quote_account_pubkey, _) =
+        find_quote_account_address(&deltafi_swap::id(), &context.quote_owner.pubkey());
+    context
+        .banks_client
+        .get_account(quote_account_pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+    let amount_in = 1_000_000_000;
+    let swap_before = context.swap_info.get_state(&mut context.banks_client).await;
+
+    let user_transfer_authority = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            calc(&context, context.quote_owner.pubkey(), amount_in),
+            approve(
+                &spl_token::id(),
+                &context.sol_user_account,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                amount_in,
+            )
+            .unwrap(),
+            swap(
+                deltafi_swap::id(),
+                context.swap_config.pubkey,
+                context.swap_info.pubkey,
+                context.swap_config.market_authority,
+                context.swap_info.authority,
+                user_transfer_authority.pubkey(),
+                context.sol_user_account,
+                context.swap_info.token_a,
+                context.swap_info.token_b,
+                context.srm_user_account,
+                context.deltafi_user_account,
+                context.swap_config.deltafi_mint,
+                context.swap_info.admin_fee_b_key,
+                context.swap_info.oracle_a,
+                context.swap_info.oracle_b,
+                context.swap_info.pool_mint,
+                SwapData {
+                    amount_in,
+                    minimum_amount_out: 0,
+                    swap_direction: SwapDirection::SellBase,
+                    max_price_impact_bps: 0,
+                },
+            )
+            .unwrap(),
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.quote_owner,
+            &context.user_account_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let data = get_quote_account_data(&mut context).await;
+    let quote_account = QuoteAccount::load(&data).unwrap();
+    assert_eq!(quote_account.owner, context.quote_owner.pubkey());
+    assert_eq!(quote_account.swap, context.swap_info.pubkey);
+    assert_eq!(
+        quote_account.swap_direction().unwrap(),
+        SwapDirection::SellBase
+    );
+    assert_eq!(quote_account.amount_in(), amount_in);
+    assert!(quote_account.amount_out() > 0);
+    assert!(quote_account.trade_fee() >= quote_account.admin_fee());
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.srm_user_account).await,
+        quote_account.amount_out()
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.deltafi_user_account).await,
+        quote_account.reward_amount()
+    );
+    assert_ne!(
+        context.swap_info.get_state(&mut context.banks_client).await,
+        swap_before
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_owner() {
+    let mut context = setup().await;
+
+    // signed by another owner than the one of the quote account
+    let other_owner = Keypair::new();
+    let mut instruction = calc(&context, other_owner.pubkey(), 1_000_000_000);
+    instruction.accounts[0].pubkey =
+        find_quote_account_address(&deltafi_swap::id(), &context.quote_owner.pubkey()).0;
+
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &other_owner], recent_blockhash);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_already_initialized() {
+    let mut context = setup().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[init_quote_account(
+            deltafi_swap::id(),
+            context.quote_owner.pubkey(),
+            context.payer.pubkey(),
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::AlreadyInUse as u32)
+        )
+    );
+}
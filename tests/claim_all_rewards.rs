@@ -0,0 +1,207 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::claim_all_rewards,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_liquidity_provider_address, LiquidityPosition, LiquidityProvider},
+};
+use solana_program::{instruction::InstructionError, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_infos: Vec<TestSwapInfo>,
+    user_account_owner: Keypair,
+    liquidity_provider: Pubkey,
+    claim_destination: Pubkey,
+}
+
+const REWARDS_OWED: [u64; 2] = [1_000_000, 3_000_000];
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_infos: Vec<TestSwapInfo> = (0..REWARDS_OWED.len())
+        .map(|_| {
+            let srm_mint = add_srm_mint(&mut test);
+            add_swap_info(
+                &mut test,
+                &swap_config,
+                &user_account_owner,
+                &admin_account_owner,
+                AddSwapInfoArgs {
+                    token_a_mint: spl_token::native_mint::id(),
+                    token_b_mint: srm_mint.pubkey,
+                    token_a_amount: 42_000_000_000,
+                    token_b_amount: 800_000_000_000,
+                    is_open_twap: true,
+                    oracle_a: sol_oracle.price_pubkey,
+                    oracle_b: srm_oracle.price_pubkey,
+                    market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+                    slope: Decimal::one().try_div(2).unwrap(),
+                },
+            )
+        })
+        .collect();
+
+    let (liquidity_provider, _) =
+        find_liquidity_provider_address(&deltafi_swap::id(), &user_account_owner.pubkey());
+    test.add_packable_account(
+        liquidity_provider,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: user_account_owner.pubkey(),
+            delegate: Pubkey::default(),
+            referrer: Pubkey::default(),
+            positions: swap_infos
+                .iter()
+                .zip(REWARDS_OWED)
+                .map(|(swap_info, rewards_owed)| LiquidityPosition {
+                    pool: swap_info.pubkey,
+                    liquidity_amount: 2_000_000_000,
+                    rewards_owed,
+                    ..LiquidityPosition::default()
+                })
+                .collect(),
+        },
+        &deltafi_swap::id(),
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let claim_destination = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_infos,
+        user_account_owner,
+        liquidity_provider,
+        claim_destination,
+    }
+}
+
+async fn process_claim_all_rewards(
+    context: &mut TestContext,
+    swap_pubkeys: Vec<Pubkey>,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[claim_all_rewards(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_config.market_authority,
+            context.liquidity_provider,
+            context.user_account_owner.pubkey(),
+            context.claim_destination,
+            context.swap_config.deltafi_mint,
+            swap_pubkeys,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.user_account_owner],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_claim_all_rewards() {
+    let mut context = setup().await;
+    let swap_pubkeys = context
+        .swap_infos
+        .iter()
+        .map(|swap_info| swap_info.pubkey)
+        .collect();
+
+    process_claim_all_rewards(&mut context, swap_pubkeys)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.claim_destination).await,
+        REWARDS_OWED.iter().sum::<u64>()
+    );
+    let account = context
+        .banks_client
+        .get_account(context.liquidity_provider)
+        .await
+        .unwrap()
+        .unwrap();
+    let liquidity_provider = LiquidityProvider::unpack(&account.data).unwrap();
+    for (position, rewards_owed) in liquidity_provider.positions.iter().zip(REWARDS_OWED) {
+        assert_eq!(position.rewards_owed, 0);
+        assert_eq!(position.cumulative_interest, rewards_owed);
+    }
+}
+
+#[tokio::test]
+async fn test_claim_some_pools() {
+    let mut context = setup().await;
+    let swap_pubkeys = vec![context.swap_infos[1].pubkey];
+
+    process_claim_all_rewards(&mut context, swap_pubkeys)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.claim_destination).await,
+        REWARDS_OWED[1]
+    );
+}
+
+#[tokio::test]
+async fn test_nothing_to_claim() {
+    let mut context = setup().await;
+    let swap_pubkeys = vec![context.swap_infos[0].pubkey];
+
+    process_claim_all_rewards(&mut context, swap_pubkeys.clone())
+        .await
+        .unwrap();
+    // repeated pools are claimed once
+    let swap_pubkeys = vec![swap_pubkeys[0], swap_pubkeys[0]];
+    assert_eq!(
+        process_claim_all_rewards(&mut context, swap_pubkeys)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InsufficientClaimAmount as u32)
+        )
+    );
+}
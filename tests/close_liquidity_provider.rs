@@ -0,0 +1,115 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::close_liquidity_provider,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    // limit to track compute unit increase
+    test.set_compute_max_units(10_000);
+
+    let user_account_owner = Keypair::new();
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let test_liquidity_provider =
+        TestLiquidityProvider::init(&mut banks_client, &user_account_owner, &payer).await;
+    let liquidity_provider_lamports = banks_client
+        .get_balance(test_liquidity_provider.pubkey)
+        .await
+        .unwrap();
+    let owner_lamports = banks_client
+        .get_balance(user_account_owner.pubkey())
+        .await
+        .unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            close_liquidity_provider(deltafi_swap::id(), user_account_owner.pubkey(), vec![])
+                .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &user_account_owner], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+
+    assert!(banks_client
+        .get_account(test_liquidity_provider.pubkey)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        banks_client
+            .get_balance(user_account_owner.pubkey())
+            .await
+            .unwrap(),
+        owner_lamports + liquidity_provider_lamports
+    );
+}
+
+#[tokio::test]
+async fn test_not_empty() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+    let liquidity_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &liquidity_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 10_000_000_000,
+            token_b_amount: 1_000_000_000_000,
+            is_open_twap: true,
+            oracle_a: Pubkey::new_unique(),
+            oracle_b: Pubkey::new_unique(),
+            market_price: Decimal::from(100u64),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    add_position(&mut test, &swap_info, &liquidity_owner, 1_000_000);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[close_liquidity_provider(deltafi_swap::id(), liquidity_owner.pubkey(), vec![]).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &liquidity_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::LiquidityProviderNotEmpty as u32)
+        )
+    );
+}
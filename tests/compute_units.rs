@@ -0,0 +1,323 @@
+#![cfg(feature = "test-bpf")]
+
+//! Compute units consumed per instruction, to catch regressions of the curve math.
+//!
+//! Print the report with `./do.sh bench-cu`.
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::{
+        deposit, refresh_liquidity_obligation, swap, withdraw, DepositData, SwapData,
+        SwapDirection, WithdrawData,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+
+use solana_program::instruction::Instruction;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+/// Max compute units allowed per instruction
+const SWAP_BUDGET: u64 = 100_000;
+const DEPOSIT_BUDGET: u64 = 100_000;
+const WITHDRAW_BUDGET: u64 = 100_000;
+const REFRESH_BUDGET: u64 = 50_000;
+
+async fn approve_transfer(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    owner: &Keypair,
+    source_pubkey: Pubkey,
+    delegate: &Keypair,
+    amount: u64,
+) {
+    let mut transaction = Transaction::new_with_payer(
+        &[approve(
+            &spl_token::id(),
+            &source_pubkey,
+            &delegate.pubkey(),
+            &owner.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer, owner], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+/// Process a single instruction and return the compute units it consumed
+async fn measure(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> u64 {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, recent_blockhash);
+
+    let simulation = banks_client
+        .simulate_transaction(transaction.clone())
+        .await
+        .unwrap();
+    simulation.result.unwrap().unwrap();
+    let units_consumed = simulation.simulation_details.unwrap().units_consumed;
+
+    banks_client.process_transaction(transaction).await.unwrap();
+    units_consumed
+}
+
+#[tokio::test]
+async fn test_compute_units() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let pool_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &pool_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let user_account_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &user_account_owner);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        400_000_000_000,
+    )
+    .await;
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_info.pool_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let mut report = vec![];
+
+    // deposit
+    let user_transfer_authority = Keypair::new();
+    for source in [sol_user_account, srm_user_account] {
+        approve_transfer(
+            &mut banks_client,
+            &payer,
+            &user_account_owner,
+            source,
+            &user_transfer_authority,
+            u64::MAX,
+        )
+        .await;
+    }
+    let units = measure(
+        &mut banks_client,
+        &payer,
+        deposit(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            swap_info.authority,
+            user_transfer_authority.pubkey(),
+            sol_user_account,
+            srm_user_account,
+            swap_info.token_a,
+            swap_info.token_b,
+            swap_info.pool_mint,
+            pool_token_account,
+            liquidity_provider.pubkey,
+            liquidity_provider.owner,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            DepositData {
+                token_a_amount: 8_000_000_000,
+                token_b_amount: 160_000_000_000,
+                min_mint_amount: 0,
+            },
+        )
+        .unwrap(),
+        &[&user_account_owner, &user_transfer_authority],
+    )
+    .await;
+    report.push(("deposit", units, DEPOSIT_BUDGET));
+
+    // swaps, in both directions
+    for (label, swap_direction, source, destination, admin_fee, amount_in) in [
+        (
+            "swap base",
+            SwapDirection::SellBase,
+            sol_user_account,
+            srm_user_account,
+            swap_info.admin_fee_b_key,
+            1_000_000_000,
+        ),
+        (
+            "swap quote",
+            SwapDirection::SellQuote,
+            srm_user_account,
+            sol_user_account,
+            swap_info.admin_fee_a_key,
+            20_000_000_000,
+        ),
+    ] {
+        let units = measure(
+            &mut banks_client,
+            &payer,
+            swap(
+                deltafi_swap::id(),
+                swap_config.pubkey,
+                swap_info.pubkey,
+                swap_config.market_authority,
+                swap_info.authority,
+                user_transfer_authority.pubkey(),
+                source,
+                swap_info.token_a,
+                swap_info.token_b,
+                destination,
+                deltafi_user_account,
+                swap_config.deltafi_mint,
+                admin_fee,
+                swap_info.oracle_a,
+                swap_info.oracle_b,
+                swap_info.pool_mint,
+                SwapData {
+                    amount_in,
+                    minimum_amount_out: 0,
+                    swap_direction,
+                    max_price_impact_bps: 0,
+                },
+            )
+            .unwrap(),
+            &[&user_transfer_authority],
+        )
+        .await;
+        report.push((label, units, SWAP_BUDGET));
+    }
+
+    // refresh
+    let units = measure(
+        &mut banks_client,
+        &payer,
+        refresh_liquidity_obligation(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            swap_config.pubkey,
+            Pubkey::default(),
+        )
+        .unwrap(),
+        &[],
+    )
+    .await;
+    report.push(("refresh", units, REFRESH_BUDGET));
+
+    // withdraw
+    let pool_token_amount = get_token_balance(&mut banks_client, pool_token_account).await / 2;
+    approve_transfer(
+        &mut banks_client,
+        &payer,
+        &user_account_owner,
+        pool_token_account,
+        &user_transfer_authority,
+        pool_token_amount,
+    )
+    .await;
+    let units = measure(
+        &mut banks_client,
+        &payer,
+        withdraw(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            swap_info.authority,
+            user_transfer_authority.pubkey(),
+            swap_info.pool_mint,
+            pool_token_account,
+            swap_info.token_a,
+            swap_info.token_b,
+            sol_user_account,
+            srm_user_account,
+            swap_info.admin_fee_a_key,
+            swap_info.admin_fee_b_key,
+            liquidity_provider.pubkey,
+            liquidity_provider.owner,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            WithdrawData {
+                pool_token_amount,
+                minimum_token_a_amount: 0,
+                minimum_token_b_amount: 0,
+            },
+        )
+        .unwrap(),
+        &[&user_account_owner, &user_transfer_authority],
+    )
+    .await;
+    report.push(("withdraw", units, WITHDRAW_BUDGET));
+
+    println!("{:<12} {:>10} {:>10}", "instruction", "units", "budget");
+    for (label, units, budget) in &report {
+        println!("{:<12} {:>10} {:>10}", label, units, budget);
+    }
+    for (label, units, budget) in report {
+        assert!(
+            units <= budget,
+            "{} consumed {} compute units, over the budget of {}",
+            label,
+            units,
+            budget
+        );
+    }
+}
@@ -0,0 +1,189 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    curve::{CurveType, DynamicSlope},
+    error::SwapError,
+    instruction::{set_curve_type, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn try_set_curve_type(
+    context: &mut TestContext,
+    admin: &Keypair,
+    curve_type: CurveType,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_curve_type(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_info.pubkey,
+            admin.pubkey(),
+            curve_type,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+// sells SOL for SRM, returning the SRM received
+async fn swap_sol(context: &mut TestContext) -> u64 {
+    let srm_balance = get_token_balance(&mut context.banks_client, context.srm_user_account).await;
+    context
+        .swap_info
+        .swap(
+            &mut context.banks_client,
+            &context.swap_config,
+            &context.user_account_owner,
+            context.sol_user_account,
+            context.srm_user_account,
+            context.deltafi_user_account,
+            1_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &context.payer,
+        )
+        .await;
+    get_token_balance(&mut context.banks_client, context.srm_user_account).await - srm_balance
+}
+
+#[tokio::test]
+async fn test_dynamic_slope() {
+    let mut standard = setup().await;
+    let mut dynamic = setup().await;
+
+    let swap = dynamic.swap_info.get_state(&mut dynamic.banks_client).await;
+    assert_eq!(swap.curve_type, CurveType::Standard);
+    let curve_type =
+        CurveType::DynamicSlope(DynamicSlope::new(Decimal::from_percent(90), 1).unwrap());
+    let admin = Keypair::from_bytes(&dynamic.swap_config.admin.to_bytes()).unwrap();
+    try_set_curve_type(&mut dynamic, &admin, curve_type)
+        .await
+        .unwrap();
+    let swap = dynamic.swap_info.get_state(&mut dynamic.banks_client).await;
+    assert_eq!(swap.curve_type, curve_type);
+
+    // both curves quote the pool at the peg alike
+    assert_eq!(swap_sol(&mut standard).await, swap_sol(&mut dynamic).await);
+
+    // once SRM is short, the dynamic slope pays less for more SOL
+    assert!(swap_sol(&mut dynamic).await < swap_sol(&mut standard).await);
+
+    // the pool keeps its own slope
+    let swap = dynamic.swap_info.get_state(&mut dynamic.banks_client).await;
+    assert_eq!(swap.pool_state.slope, Decimal::one().try_div(2).unwrap());
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_set_curve_type(&mut context, &Keypair::new(), CurveType::Standard)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,470 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        cancel_dca, create_dca_plan, execute_dca, swap, CreateDcaPlanData, SwapData, SwapDirection,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+    pyth,
+    state::{find_dca_plan_address, DcaPlan},
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    sysvar::clock::Clock,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account as TokenAccount;
+use utils::*;
+
+const AMOUNT_PER_PERIOD: u64 = 1_000_000_000;
+const INTERVAL: i64 = 3_600;
+const MAX_PRICE_IMPACT_BPS: u64 = 2_000;
+const KEEPER_REWARD_BPS: u64 = 10;
+
+/// Pyth price account last valid at the first slot, stale once the test
+/// warps past it
+fn add_stale_oracle(test: &mut ProgramTest) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    test.add_account(
+        pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data: pyth::test::price_data(20, 0, 0),
+            owner: pyth::id(),
+            ..Account::default()
+        },
+    );
+    pubkey
+}
+
+struct TestContext {
+    program_context: ProgramTestContext,
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+    keeper_account: Pubkey,
+}
+
+/// A pool of SOL and SRM, along with an owner holding SOL to sell over three
+/// periods and a keeper paid in SOL
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+    let oracle_a = add_stale_oracle(&mut test);
+    let oracle_b = add_stale_oracle(&mut test);
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 10_000_000_000,
+            token_b_amount: 200_000_000_000,
+            is_open_twap: false,
+            oracle_a,
+            oracle_b,
+            market_price: Decimal::from(20u64),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut program_context = test.start_with_context().await;
+    program_context.warp_to_slot(100).unwrap();
+    let mut banks_client = program_context.banks_client.clone();
+    let payer = Keypair::from_bytes(&program_context.payer.to_bytes()).unwrap();
+
+    let owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        owner.pubkey(),
+        3 * AMOUNT_PER_PERIOD,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        owner.pubkey(),
+        0,
+    )
+    .await;
+    let keeper_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        payer.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        program_context,
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+        keeper_account,
+    }
+}
+
+fn dca_plan_pubkey(context: &TestContext) -> Pubkey {
+    find_dca_plan_address(
+        &deltafi_swap::id(),
+        &context.swap_info.pubkey,
+        &context.owner.pubkey(),
+    )
+    .0
+}
+
+fn escrow_pubkey(context: &TestContext) -> Pubkey {
+    get_associated_token_address(&dca_plan_pubkey(context), &spl_token::native_mint::id())
+}
+
+fn create_instruction(context: &TestContext, max_price_impact_bps: u64) -> Instruction {
+    create_dca_plan(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.owner.pubkey(),
+        context.sol_user_account,
+        spl_token::native_mint::id(),
+        context.payer.pubkey(),
+        CreateDcaPlanData {
+            amount: 3 * AMOUNT_PER_PERIOD,
+            amount_per_period: AMOUNT_PER_PERIOD,
+            interval: INTERVAL,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps,
+            keeper_reward_bps: KEEPER_REWARD_BPS,
+        },
+    )
+    .unwrap()
+}
+
+/// The execution of the plan selling SOL from the escrow for the SRM of the
+/// destination
+fn execute_instruction(context: &TestContext, destination: Pubkey) -> Instruction {
+    let swap_info = &context.swap_info;
+    let dca_plan = dca_plan_pubkey(context);
+    execute_dca(
+        swap(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            swap_info.pubkey,
+            context.swap_config.market_authority,
+            swap_info.authority,
+            dca_plan,
+            escrow_pubkey(context),
+            swap_info.token_a,
+            swap_info.token_b,
+            destination,
+            context.deltafi_user_account,
+            context.swap_config.deltafi_mint,
+            swap_info.admin_fee_b_key,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            swap_info.pool_mint,
+            SwapData {
+                amount_in: AMOUNT_PER_PERIOD,
+                minimum_amount_out: 0,
+                swap_direction: SwapDirection::SellBase,
+                max_price_impact_bps: 0,
+            },
+        )
+        .unwrap(),
+        dca_plan,
+        context.keeper_account,
+    )
+    .unwrap()
+}
+
+async fn send(
+    context: &mut TestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&context.payer.pubkey()));
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    // a new blockhash, so that resent instructions are not a duplicate
+    let recent_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+async fn create_plan(context: &mut TestContext, max_price_impact_bps: u64) {
+    let instruction = create_instruction(context, max_price_impact_bps);
+    let owner = Keypair::from_bytes(&context.owner.to_bytes()).unwrap();
+    send(context, &[instruction], &[&owner]).await.unwrap();
+}
+
+async fn warp_clock(context: &mut TestContext, seconds: i64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    context.program_context.set_sysvar(&clock);
+}
+
+async fn token_amount(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
+    let account = banks_client.get_account(pubkey).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+async fn remaining_amount(context: &mut TestContext) -> u64 {
+    let account = context
+        .banks_client
+        .get_account(dca_plan_pubkey(context))
+        .await
+        .unwrap()
+        .unwrap();
+    DcaPlan::load(&account.data).unwrap().remaining_amount()
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+    create_plan(&mut context, MAX_PRICE_IMPACT_BPS).await;
+
+    assert_eq!(
+        token_amount(&mut context.banks_client, context.sol_user_account).await,
+        0
+    );
+    assert_eq!(
+        token_amount(&mut context.banks_client, escrow_pubkey(&context)).await,
+        3 * AMOUNT_PER_PERIOD
+    );
+
+    // the first period is due right away
+    let keeper_reward = AMOUNT_PER_PERIOD * KEEPER_REWARD_BPS / 10_000;
+    let instruction = execute_instruction(&context, context.srm_user_account);
+    send(&mut context, &[instruction.clone()], &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        token_amount(&mut context.banks_client, escrow_pubkey(&context)).await,
+        2 * AMOUNT_PER_PERIOD
+    );
+    assert_eq!(
+        token_amount(&mut context.banks_client, context.keeper_account).await,
+        keeper_reward
+    );
+    let srm_amount = token_amount(&mut context.banks_client, context.srm_user_account).await;
+    assert!(srm_amount > 0);
+    assert_eq!(remaining_amount(&mut context).await, 2 * AMOUNT_PER_PERIOD);
+
+    // the next one an interval later
+    assert_eq!(
+        send(&mut context, &[instruction.clone()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::DcaNotDue as u32)
+        )
+    );
+    for remaining in [AMOUNT_PER_PERIOD, 0] {
+        warp_clock(&mut context, INTERVAL).await;
+        send(&mut context, &[instruction.clone()], &[])
+            .await
+            .unwrap();
+        assert_eq!(remaining_amount(&mut context).await, remaining);
+    }
+    assert_eq!(
+        token_amount(&mut context.banks_client, escrow_pubkey(&context)).await,
+        0
+    );
+    assert_eq!(
+        token_amount(&mut context.banks_client, context.keeper_account).await,
+        3 * keeper_reward
+    );
+    assert!(token_amount(&mut context.banks_client, context.srm_user_account).await > srm_amount);
+
+    warp_clock(&mut context, INTERVAL).await;
+    assert_eq!(
+        send(&mut context, &[instruction], &[]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::DcaPlanCompleted as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_already_created() {
+    let mut context = setup().await;
+    create_plan(&mut context, MAX_PRICE_IMPACT_BPS).await;
+
+    let instruction = create_instruction(&context, MAX_PRICE_IMPACT_BPS);
+    let owner = Keypair::from_bytes(&context.owner.to_bytes()).unwrap();
+    assert_eq!(
+        send(&mut context, &[instruction], &[&owner])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::AlreadyInUse as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_destination_of_keeper() {
+    let mut context = setup().await;
+    create_plan(&mut context, MAX_PRICE_IMPACT_BPS).await;
+
+    // the keeper cannot take the tokens bought
+    let mut banks_client = context.banks_client.clone();
+    let keeper_srm_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        context.swap_info.token_b_mint,
+        None,
+        &context.payer,
+        context.payer.pubkey(),
+        0,
+    )
+    .await;
+    let instruction = execute_instruction(&context, keeper_srm_account);
+    assert_eq!(
+        send(&mut context, &[instruction], &[]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_without_price_impact_limit() {
+    let mut context = setup().await;
+
+    // periods are swapped without a minimum amount out, so a limit is required
+    let instruction = create_instruction(&context, 0);
+    let owner = Keypair::from_bytes(&context.owner.to_bytes()).unwrap();
+    assert_eq!(
+        send(&mut context, &[instruction], &[&owner])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidInput as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_price_impact_exceeded() {
+    let mut context = setup().await;
+    create_plan(&mut context, 1).await;
+
+    let instruction = execute_instruction(&context, context.srm_user_account);
+    assert_eq!(
+        send(&mut context, &[instruction], &[]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::PriceImpactExceeded as u32)
+        )
+    );
+    assert_eq!(remaining_amount(&mut context).await, 3 * AMOUNT_PER_PERIOD);
+}
+
+#[tokio::test]
+async fn test_cancel() {
+    let mut context = setup().await;
+    create_plan(&mut context, MAX_PRICE_IMPACT_BPS).await;
+    let instruction = execute_instruction(&context, context.srm_user_account);
+    send(&mut context, &[instruction], &[]).await.unwrap();
+
+    let instruction = cancel_dca(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.owner.pubkey(),
+        context.sol_user_account,
+        spl_token::native_mint::id(),
+    )
+    .unwrap();
+    // only the owner cancels its plan
+    let mut other_instruction = instruction.clone();
+    other_instruction.accounts[2].pubkey = context.payer.pubkey();
+    assert_eq!(
+        send(&mut context, &[other_instruction], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        )
+    );
+
+    let owner = Keypair::from_bytes(&context.owner.to_bytes()).unwrap();
+    send(&mut context, &[instruction], &[&owner]).await.unwrap();
+    assert_eq!(
+        token_amount(&mut context.banks_client, context.sol_user_account).await,
+        2 * AMOUNT_PER_PERIOD
+    );
+    for pubkey in [dca_plan_pubkey(&context), escrow_pubkey(&context)] {
+        assert!(context
+            .banks_client
+            .get_account(pubkey)
+            .await
+            .unwrap()
+            .is_none());
+    }
+    // the rent of the plan and the escrow goes to the owner
+    assert!(
+        context
+            .banks_client
+            .get_balance(context.owner.pubkey())
+            .await
+            .unwrap()
+            > 0
+    );
+}
@@ -0,0 +1,264 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{refresh_liquidity_obligation, set_deltafi_price_oracle},
+    math::{Decimal, TryDiv},
+    processor::process,
+    pyth,
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    sol_oracle: TestOracle,
+    // mocks the DELFI/USD price account
+    deltafi_oracle: TestOracle,
+    // config created by anyone, with an oracle of its choice
+    other_config: TestSwapConfig,
+    // price account of another program
+    fake_oracle: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let other_config = add_swap_config(&mut test);
+    let fake_oracle = Pubkey::new_unique();
+    test.add_account(
+        fake_oracle,
+        Account {
+            lamports: u32::MAX as u64,
+            data: pyth::test::price_data(1_000_000, 0, 0),
+            owner: Pubkey::new_unique(),
+            ..Account::default()
+        },
+    );
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        sol_oracle,
+        deltafi_oracle: srm_oracle,
+        other_config,
+        fake_oracle,
+    }
+}
+
+async fn process_instruction(
+    context: &mut TestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn set_oracle(context: &mut TestContext, deltafi_price_oracle: Pubkey) {
+    let swap_config = &context.swap_config;
+    set_config_oracle(context, swap_config, deltafi_price_oracle).await;
+}
+
+async fn set_config_oracle(
+    context: &TestContext,
+    swap_config: &TestSwapConfig,
+    deltafi_price_oracle: Pubkey,
+) {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_deltafi_price_oracle(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_config.admin.pubkey(),
+            deltafi_price_oracle,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let mut banks_client = context.banks_client.clone();
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &swap_config.admin], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+fn refresh_instruction(context: &TestContext, deltafi_price_oracle: Pubkey) -> Instruction {
+    refresh_liquidity_obligation(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.swap_config.pubkey,
+        deltafi_price_oracle,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_refresh_deltafi_price() {
+    let mut context = setup().await;
+
+    // without oracle the pool keeps the price last refreshed
+    let swap_info = context.swap_info.get_state(&mut context.banks_client).await;
+    let deltafi_price = swap_info.deltafi_price;
+    let instruction = refresh_instruction(&context, Pubkey::default());
+    process_instruction(&mut context, instruction, &[])
+        .await
+        .unwrap();
+    let swap_info = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap_info.deltafi_price, deltafi_price);
+
+    let deltafi_price_oracle = context.deltafi_oracle.price_pubkey;
+    set_oracle(&mut context, deltafi_price_oracle).await;
+    let swap_config = context
+        .swap_config
+        .get_state(&mut context.banks_client)
+        .await;
+    assert_eq!(swap_config.deltafi_price_oracle, deltafi_price_oracle);
+
+    let instruction = refresh_instruction(&context, deltafi_price_oracle);
+    process_instruction(&mut context, instruction, &[])
+        .await
+        .unwrap();
+    let swap_info = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap_info.deltafi_price, context.deltafi_oracle.price);
+}
+
+#[tokio::test]
+async fn test_refresh_incorrect_oracle() {
+    let mut context = setup().await;
+    let deltafi_price_oracle = context.deltafi_oracle.price_pubkey;
+    set_oracle(&mut context, deltafi_price_oracle).await;
+
+    let instruction = refresh_instruction(&context, context.sol_oracle.price_pubkey);
+    assert_eq!(
+        process_instruction(&mut context, instruction, &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectOracleAccount as u32)
+        )
+    );
+
+    // the oracle account is required once configured
+    let instruction = refresh_instruction(&context, Pubkey::default());
+    assert_eq!(
+        process_instruction(&mut context, instruction, &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_other_config() {
+    let mut context = setup().await;
+    set_config_oracle(&context, &context.other_config, context.fake_oracle).await;
+
+    // the pool only refreshes from its own config
+    let instruction = refresh_liquidity_obligation(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.other_config.pubkey,
+        context.fake_oracle,
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(&mut context, instruction, &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectConfigAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_oracle_of_other_program() {
+    let mut context = setup().await;
+    let fake_oracle = context.fake_oracle;
+    set_oracle(&mut context, fake_oracle).await;
+
+    let instruction = refresh_instruction(&context, fake_oracle);
+    assert_eq!(
+        process_instruction(&mut context, instruction, &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectOracleAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_set_oracle_not_admin() {
+    let mut context = setup().await;
+    let not_admin = Keypair::new();
+
+    let instruction = set_deltafi_price_oracle(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        not_admin.pubkey(),
+        context.deltafi_oracle.price_pubkey,
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(&mut context, instruction, &[&not_admin])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,249 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{initialize_deny_list, set_denied_mint},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_deny_list_address, DenyList},
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    sol_oracle: TestOracle,
+    srm_oracle: TestOracle,
+    srm_mint: TestMint,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        sol_oracle,
+        srm_oracle,
+        srm_mint,
+    }
+}
+
+async fn process_admin_instruction(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instruction: Instruction,
+    admin: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer, admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn init_deny_list(context: &mut TestContext) {
+    let instruction = initialize_deny_list(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_config.admin.pubkey(),
+        context.payer.pubkey(),
+    )
+    .unwrap();
+    process_admin_instruction(
+        &mut context.banks_client,
+        &context.payer,
+        instruction,
+        &context.swap_config.admin,
+    )
+    .await
+    .unwrap();
+}
+
+async fn try_set_denied_mint(
+    context: &mut TestContext,
+    admin: Option<&Keypair>,
+    mint: Pubkey,
+    is_denied: bool,
+) -> Result<(), TransactionError> {
+    let admin = admin.unwrap_or(&context.swap_config.admin);
+    let instruction = set_denied_mint(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        admin.pubkey(),
+        mint,
+        is_denied,
+    )
+    .unwrap();
+    process_admin_instruction(
+        &mut context.banks_client,
+        &context.payer,
+        instruction,
+        admin,
+    )
+    .await
+}
+
+async fn get_denied_mints(context: &mut TestContext) -> Vec<Pubkey> {
+    let (deny_list, _) = find_deny_list_address(&deltafi_swap::id(), &context.swap_config.pubkey);
+    let account = context
+        .banks_client
+        .get_account(deny_list)
+        .await
+        .unwrap()
+        .unwrap();
+    DenyList::load(&account.data).unwrap().mints().to_vec()
+}
+
+async fn try_init_pool(context: &mut TestContext) -> Result<TestSwapInfo, TransactionError> {
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        context.srm_mint.pubkey,
+        Some(&context.srm_mint.authority),
+        &context.payer,
+        user_accounts_owner.pubkey(),
+        800_000_000_000,
+    )
+    .await;
+
+    let admin_fee_accounts = Keypair::new();
+    let sol_admin_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        context.srm_mint.pubkey,
+        Some(&context.srm_mint.authority),
+        &context.payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+
+    TestSwapInfo::try_init(
+        &mut context.banks_client,
+        &context.swap_config,
+        &context.sol_oracle,
+        &context.srm_oracle,
+        spl_token::native_mint::id(),
+        context.srm_mint.pubkey,
+        sol_user_account,
+        srm_user_account,
+        sol_admin_account,
+        srm_admin_account,
+        &user_accounts_owner,
+        &context.payer,
+        &SwapInitArgs {
+            mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+            slope: Decimal::one()
+                .try_div(2)
+                .unwrap()
+                .to_scaled_val()
+                .unwrap()
+                .try_into()
+                .unwrap(),
+            is_open_twap: true,
+            twap_window_secs: 3_600,
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+
+    // pools can be created before the config has a deny-list
+    try_init_pool(&mut context).await.unwrap();
+
+    init_deny_list(&mut context).await;
+    assert!(get_denied_mints(&mut context).await.is_empty());
+
+    let srm_mint = context.srm_mint.pubkey;
+    try_set_denied_mint(&mut context, None, srm_mint, true)
+        .await
+        .unwrap();
+    assert_eq!(get_denied_mints(&mut context).await, vec![srm_mint]);
+    assert_eq!(
+        try_init_pool(&mut context).await.err(),
+        Some(TransactionError::InstructionError(
+            7,
+            InstructionError::Custom(SwapError::DeniedMint as u32)
+        ))
+    );
+
+    // removed mints are accepted again
+    try_set_denied_mint(&mut context, None, srm_mint, false)
+        .await
+        .unwrap();
+    assert!(get_denied_mints(&mut context).await.is_empty());
+    try_init_pool(&mut context).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut context = setup().await;
+    init_deny_list(&mut context).await;
+
+    let srm_mint = context.srm_mint.pubkey;
+    assert_eq!(
+        try_set_denied_mint(&mut context, Some(&Keypair::new()), srm_mint, true).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_set_denied_mint_without_deny_list() {
+    let mut context = setup().await;
+
+    let srm_mint = context.srm_mint.pubkey;
+    assert_eq!(
+        try_set_denied_mint(&mut context, None, srm_mint, true).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidAccountOwner as u32)
+        ))
+    );
+}
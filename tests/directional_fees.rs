@@ -0,0 +1,227 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{set_directional_fees, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::DirectionalFees,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn try_set_directional_fees(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    swap_config: &TestSwapConfig,
+    swap_pubkey: Pubkey,
+    admin: &Keypair,
+    directional_fees: DirectionalFees,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_directional_fees(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_pubkey,
+            admin.pubkey(),
+            directional_fees,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer, admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+// sells SOL for SRM, returning the admin fee charged
+async fn swap_sol(context: &mut TestContext) -> u64 {
+    let admin_fee_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await;
+    context
+        .swap_info
+        .swap(
+            &mut context.banks_client,
+            &context.swap_config,
+            &context.user_account_owner,
+            context.sol_user_account,
+            context.srm_user_account,
+            context.deltafi_user_account,
+            1_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &context.payer,
+        )
+        .await;
+    get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await
+        - admin_fee_balance
+}
+
+#[tokio::test]
+async fn test_directional_fees() {
+    let mut context = setup().await;
+    assert!(swap_sol(&mut context).await > 0);
+
+    // selling the base token is free, selling the quote token keeps the pool fee
+    let directional_fees = DirectionalFees {
+        sell_base_fee_numerator: 0,
+        sell_base_fee_denominator: 1_000,
+        ..DirectionalFees::default()
+    };
+    try_set_directional_fees(
+        &mut context.banks_client,
+        &context.payer,
+        &context.swap_config,
+        context.swap_info.pubkey,
+        &context.swap_config.admin,
+        directional_fees,
+    )
+    .await
+    .unwrap();
+
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap.directional_fees, directional_fees);
+    assert_eq!(swap_sol(&mut context).await, 0);
+}
+
+#[tokio::test]
+async fn test_invalid_directional_fees() {
+    let mut context = setup().await;
+    let directional_fees = DirectionalFees {
+        sell_quote_fee_numerator: 2,
+        sell_quote_fee_denominator: 1,
+        ..DirectionalFees::default()
+    };
+
+    assert_eq!(
+        try_set_directional_fees(
+            &mut context.banks_client,
+            &context.payer,
+            &context.swap_config,
+            context.swap_info.pubkey,
+            &context.swap_config.admin,
+            directional_fees,
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidInput as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_set_directional_fees(
+            &mut context.banks_client,
+            &context.payer,
+            &context.swap_config,
+            context.swap_info.pubkey,
+            &Keypair::new(),
+            DirectionalFees::default(),
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
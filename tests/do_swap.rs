@@ -20,7 +20,7 @@ async fn test_success() {
     let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
 
     // limit to track compute unit increase
-    test.set_bpf_compute_max_units(100_000);
+    test.set_compute_max_units(100_000);
 
     let swap_config = add_swap_config(&mut test);
 
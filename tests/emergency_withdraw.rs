@@ -0,0 +1,251 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{append_position_pages, emergency_withdraw, EmergencyWithdrawData},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::LiquidityProvider,
+};
+use solana_program::{instruction::InstructionError, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_info: TestSwapInfo,
+    liquidity_provider: TestLiquidityProvider,
+    liquidity_owner: Keypair,
+    sol_deposit_account: Pubkey,
+    srm_deposit_account: Pubkey,
+    pool_token_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        liquidity_owner.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_info.pool_mint,
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_info,
+        liquidity_provider,
+        liquidity_owner,
+        sol_deposit_account,
+        srm_deposit_account,
+        pool_token_account,
+    }
+}
+
+async fn try_emergency_withdraw(
+    context: &mut TestContext,
+    minimum_token_a_amount: u64,
+    minimum_token_b_amount: u64,
+) -> Result<(), TransactionError> {
+    let pool_token_amount =
+        get_token_balance(&mut context.banks_client, context.pool_token_account).await;
+    let user_transfer_authority = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &context.pool_token_account,
+                &user_transfer_authority.pubkey(),
+                &context.liquidity_owner.pubkey(),
+                &[],
+                pool_token_amount,
+            )
+            .unwrap(),
+            append_position_pages(
+                emergency_withdraw(
+                    deltafi_swap::id(),
+                    context.swap_info.pubkey,
+                    context.swap_info.authority,
+                    user_transfer_authority.pubkey(),
+                    context.swap_info.pool_mint,
+                    context.pool_token_account,
+                    context.swap_info.token_a,
+                    context.swap_info.token_b,
+                    context.sol_deposit_account,
+                    context.srm_deposit_account,
+                    context.swap_info.admin_fee_a_key,
+                    context.swap_info.admin_fee_b_key,
+                    context.liquidity_provider.pubkey,
+                    context.liquidity_provider.owner,
+                    EmergencyWithdrawData {
+                        minimum_token_a_amount,
+                        minimum_token_b_amount,
+                    },
+                )
+                .unwrap(),
+                context.liquidity_provider.position_pages.clone(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.liquidity_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn deposit(context: &mut TestContext) {
+    context
+        .swap_info
+        .deposit(
+            &mut context.banks_client,
+            &context.liquidity_provider,
+            &context.liquidity_owner,
+            context.sol_deposit_account,
+            context.srm_deposit_account,
+            context.pool_token_account,
+            2_000_000_000,
+            40_000_000_000,
+            0,
+            &context.payer,
+        )
+        .await;
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw() {
+    let mut context = setup().await;
+    deposit(&mut context).await;
+    let sol_balance =
+        get_token_balance(&mut context.banks_client, context.sol_deposit_account).await;
+    let srm_balance =
+        get_token_balance(&mut context.banks_client, context.srm_deposit_account).await;
+
+    try_emergency_withdraw(&mut context, 1, 1).await.unwrap();
+
+    assert!(
+        get_token_balance(&mut context.banks_client, context.sol_deposit_account).await
+            > sol_balance
+    );
+    assert!(
+        get_token_balance(&mut context.banks_client, context.srm_deposit_account).await
+            > srm_balance
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.pool_token_account).await,
+        0
+    );
+
+    // the position is gone with its rewards
+    let lp = context
+        .banks_client
+        .get_account(context.liquidity_provider.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let lp_state = LiquidityProvider::unpack(&lp.data[..]).unwrap();
+    assert!(lp_state.positions.is_empty());
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw_slippage() {
+    let mut context = setup().await;
+    deposit(&mut context).await;
+
+    assert_eq!(
+        try_emergency_withdraw(&mut context, u64::MAX, 0)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::WithdrawNotEnough as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_emergency_withdraw_no_position() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_emergency_withdraw(&mut context, 0, 0)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::LiquidityPositionEmpty as u32)
+        )
+    );
+}
@@ -0,0 +1,225 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{claim_liquidity_rewards, set_epoch_reward_budget},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_liquidity_provider_address, LiquidityPosition, LiquidityProvider},
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const REWARDS_OWED: u64 = 1_000_000;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    liquidity_provider: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (liquidity_provider, _) =
+        find_liquidity_provider_address(&deltafi_swap::id(), &user_account_owner.pubkey());
+    test.add_packable_account(
+        liquidity_provider,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: user_account_owner.pubkey(),
+            delegate: Pubkey::default(),
+            referrer: Pubkey::default(),
+            positions: vec![LiquidityPosition {
+                pool: swap_info.pubkey,
+                liquidity_amount: 2_000_000_000,
+                rewards_owed: REWARDS_OWED,
+                ..LiquidityPosition::default()
+            }],
+        },
+        &deltafi_swap::id(),
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        liquidity_provider,
+    }
+}
+
+async fn process_instruction(
+    context: &mut TestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn set_budget(context: &mut TestContext, epoch_reward_budget: u64) {
+    let instruction = set_epoch_reward_budget(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_config.admin.pubkey(),
+        epoch_reward_budget,
+    )
+    .unwrap();
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.swap_config.admin],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+async fn claim(context: &mut TestContext) -> Result<Pubkey, TransactionError> {
+    let owner = context.user_account_owner.pubkey();
+    let deltafi_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        context.swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        owner,
+        0,
+    )
+    .await;
+    let instruction = claim_liquidity_rewards(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.market_authority,
+        context.liquidity_provider,
+        owner,
+        deltafi_account,
+        context.swap_config.deltafi_mint,
+    )
+    .unwrap();
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.user_account_owner],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())?;
+    Ok(deltafi_account)
+}
+
+#[tokio::test]
+async fn test_claim_over_budget() {
+    let mut context = setup().await;
+    set_budget(&mut context, REWARDS_OWED - 1).await;
+
+    assert_eq!(
+        claim(&mut context).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::EpochRewardBudgetExceeded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_claim_within_budget() {
+    let mut context = setup().await;
+    set_budget(&mut context, REWARDS_OWED).await;
+
+    let deltafi_account = claim(&mut context).await.unwrap();
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, deltafi_account).await,
+        REWARDS_OWED
+    );
+
+    let swap_config = context
+        .swap_config
+        .get_state(&mut context.banks_client)
+        .await;
+    assert_eq!(swap_config.epoch_reward_budget, REWARDS_OWED);
+    assert_eq!(swap_config.epoch_rewards_minted, REWARDS_OWED);
+    assert_eq!(swap_config.epoch_rewards_left(swap_config.reward_epoch), 0);
+}
+
+#[tokio::test]
+async fn test_set_budget_not_admin() {
+    let mut context = setup().await;
+    let not_admin = Keypair::new();
+
+    let instruction = set_epoch_reward_budget(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        not_admin.pubkey(),
+        REWARDS_OWED,
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(&mut context, instruction, &[&not_admin])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
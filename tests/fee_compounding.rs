@@ -0,0 +1,163 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::{set_fee_compounding, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn set_fee_compounding_of_pool(context: &mut TestContext, is_fee_compounding: bool) {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_fee_compounding(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_info.pubkey,
+            context.swap_config.admin.pubkey(),
+            is_fee_compounding,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.swap_config.admin],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+
+    set_fee_compounding_of_pool(&mut context, true).await;
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert!(swap.is_fee_compounding);
+
+    context
+        .swap_info
+        .swap(
+            &mut context.banks_client,
+            &context.swap_config,
+            &context.user_account_owner,
+            context.sol_user_account,
+            context.srm_user_account,
+            context.deltafi_user_account,
+            2_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &context.payer,
+        )
+        .await;
+
+    // the admin fee left the pool, the lp fee stays in the reserve
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    let token_b_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.token_b).await;
+    assert_eq!(
+        swap.pool_state.quote_reserve,
+        Decimal::from(token_b_balance)
+    );
+    assert!(
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await > 0
+    );
+
+    set_fee_compounding_of_pool(&mut context, false).await;
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert!(!swap.is_fee_compounding);
+}
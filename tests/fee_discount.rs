@@ -0,0 +1,370 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{set_fee_discount, swap, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{ConfigInfo, FeeDiscount, FeeDiscountTier},
+};
+use solana_program::{instruction::InstructionError, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{
+    account::AccountSharedData,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::Account as Token;
+use utils::*;
+
+const AMOUNT_IN: u64 = 1_000_000_000;
+const DELFI_BALANCE: u64 = 10_000_000;
+
+struct TestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    // config of anyone, its tiers discounting nothing but its own pools
+    other_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let other_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    // DELFI is only minted by the program, so the balance is written directly
+    set_token_balance(&mut context, deltafi_user_account, DELFI_BALANCE).await;
+
+    TestContext {
+        context,
+        swap_config,
+        other_config,
+        swap_info,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn set_token_balance(context: &mut ProgramTestContext, pubkey: Pubkey, amount: u64) {
+    let mut account = context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut token = Token::unpack(&account.data).unwrap();
+    token.amount = amount;
+    Token::pack(token, &mut account.data).unwrap();
+    context.set_account(&pubkey, &AccountSharedData::from(account));
+}
+
+fn test_fee_discount(discount_bps: u64) -> FeeDiscount {
+    FeeDiscount {
+        tiers: [
+            FeeDiscountTier {
+                min_balance: DELFI_BALANCE,
+                discount_bps,
+            },
+            FeeDiscountTier {
+                min_balance: DELFI_BALANCE * 10,
+                discount_bps: 10_000,
+            },
+            FeeDiscountTier::default(),
+        ],
+    }
+}
+
+async fn try_set_fee_discount(
+    context: &mut ProgramTestContext,
+    config_pubkey: Pubkey,
+    admin: &Keypair,
+    fee_discount: FeeDiscount,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_fee_discount(
+            deltafi_swap::id(),
+            config_pubkey,
+            admin.pubkey(),
+            fee_discount,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+// sells SOL for SRM, returning the SRM received
+async fn swap_sol(test_context: &mut TestContext, reward_token: Pubkey) -> u64 {
+    let srm_balance = get_token_balance(
+        &mut test_context.context.banks_client,
+        test_context.srm_user_account,
+    )
+    .await;
+    test_context
+        .swap_info
+        .swap(
+            &mut test_context.context.banks_client,
+            &test_context.swap_config,
+            &test_context.user_account_owner,
+            test_context.sol_user_account,
+            test_context.srm_user_account,
+            reward_token,
+            AMOUNT_IN,
+            0,
+            SwapDirection::SellBase,
+            &test_context.context.payer,
+        )
+        .await;
+    get_token_balance(
+        &mut test_context.context.banks_client,
+        test_context.srm_user_account,
+    )
+    .await
+        - srm_balance
+}
+
+#[tokio::test]
+async fn test_fee_discount_applied() {
+    let mut undiscounted = setup().await;
+    let reward_token = undiscounted.deltafi_user_account;
+    let undiscounted_amount_out = swap_sol(&mut undiscounted, reward_token).await;
+
+    let mut discounted = setup().await;
+    try_set_fee_discount(
+        &mut discounted.context,
+        discounted.swap_config.pubkey,
+        &discounted.swap_config.admin,
+        test_fee_discount(5_000),
+    )
+    .await
+    .unwrap();
+    let config = ConfigInfo::unpack(
+        &discounted
+            .context
+            .banks_client
+            .get_account(discounted.swap_config.pubkey)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    assert_eq!(config.fee_discount, test_fee_discount(5_000));
+
+    let reward_token = discounted.deltafi_user_account;
+    let discounted_amount_out = swap_sol(&mut discounted, reward_token).await;
+    assert!(discounted_amount_out > undiscounted_amount_out);
+}
+
+#[tokio::test]
+async fn test_fee_discount_not_owned() {
+    let mut undiscounted = setup().await;
+    let reward_token = undiscounted.deltafi_user_account;
+    let undiscounted_amount_out = swap_sol(&mut undiscounted, reward_token).await;
+
+    let mut test_context = setup().await;
+    try_set_fee_discount(
+        &mut test_context.context,
+        test_context.swap_config.pubkey,
+        &test_context.swap_config.admin,
+        test_fee_discount(5_000),
+    )
+    .await
+    .unwrap();
+
+    // the DELFI of another holder does not discount the swap
+    let holder_account = create_and_mint_to_token_account(
+        &mut test_context.context.banks_client,
+        test_context.swap_config.deltafi_mint,
+        None,
+        &test_context.context.payer,
+        Keypair::new().pubkey(),
+        0,
+    )
+    .await;
+    set_token_balance(
+        &mut test_context.context,
+        holder_account,
+        DELFI_BALANCE * 10,
+    )
+    .await;
+    assert_eq!(
+        swap_sol(&mut test_context, holder_account).await,
+        undiscounted_amount_out
+    );
+}
+
+#[tokio::test]
+async fn test_fee_discount_of_other_config() {
+    let mut test_context = setup().await;
+    try_set_fee_discount(
+        &mut test_context.context,
+        test_context.other_config.pubkey,
+        &test_context.other_config.admin,
+        test_fee_discount(10_000),
+    )
+    .await
+    .unwrap();
+
+    // the tiers of a config apply to its pools only
+    let swap_info = &test_context.swap_info;
+    let other_config = &test_context.other_config;
+    let mut transaction = Transaction::new_with_payer(
+        &[swap(
+            deltafi_swap::id(),
+            other_config.pubkey,
+            swap_info.pubkey,
+            other_config.market_authority,
+            swap_info.authority,
+            test_context.user_account_owner.pubkey(),
+            test_context.sol_user_account,
+            swap_info.token_a,
+            swap_info.token_b,
+            test_context.srm_user_account,
+            test_context.deltafi_user_account,
+            other_config.deltafi_mint,
+            swap_info.admin_fee_b_key,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            swap_info.pool_mint,
+            SwapData {
+                amount_in: AMOUNT_IN,
+                minimum_amount_out: 0,
+                swap_direction: SwapDirection::SellBase,
+                max_price_impact_bps: 0,
+            },
+        )
+        .unwrap()],
+        Some(&test_context.context.payer.pubkey()),
+    );
+    transaction.sign(
+        &[
+            &test_context.context.payer,
+            &test_context.user_account_owner,
+        ],
+        test_context.context.last_blockhash,
+    );
+    assert_eq!(
+        test_context
+            .context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectConfigAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_fee_discount() {
+    let mut test_context = setup().await;
+
+    assert_eq!(
+        try_set_fee_discount(
+            &mut test_context.context,
+            test_context.swap_config.pubkey,
+            &test_context.swap_config.admin,
+            test_fee_discount(10_001),
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidInput as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut test_context = setup().await;
+
+    assert_eq!(
+        try_set_fee_discount(
+            &mut test_context.context,
+            test_context.swap_config.pubkey,
+            &Keypair::new(),
+            test_fee_discount(5_000),
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
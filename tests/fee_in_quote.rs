@@ -0,0 +1,258 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{set_fee_in_quote, swap, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn try_set_fee_in_quote(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    swap_config: &TestSwapConfig,
+    swap_pubkey: Pubkey,
+    admin: &Keypair,
+    is_fee_in_quote: bool,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_fee_in_quote(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_pubkey,
+            admin.pubkey(),
+            is_fee_in_quote,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer, admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+// sells SRM for SOL, paying the admin fee to the given account
+async fn try_swap_srm(
+    context: &mut TestContext,
+    admin_fee_destination: Pubkey,
+) -> Result<(), TransactionError> {
+    let amount_in = 1_000_000_000;
+    let user_transfer_authority = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &context.srm_user_account,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                amount_in,
+            )
+            .unwrap(),
+            swap(
+                deltafi_swap::id(),
+                context.swap_config.pubkey,
+                context.swap_info.pubkey,
+                context.swap_config.market_authority,
+                context.swap_info.authority,
+                user_transfer_authority.pubkey(),
+                context.sol_user_account,
+                context.swap_info.token_a,
+                context.swap_info.token_b,
+                context.srm_user_account,
+                context.deltafi_user_account,
+                context.swap_config.deltafi_mint,
+                admin_fee_destination,
+                context.swap_info.oracle_a,
+                context.swap_info.oracle_b,
+                context.swap_info.pool_mint,
+                SwapData {
+                    amount_in,
+                    minimum_amount_out: 0,
+                    swap_direction: SwapDirection::SellQuote,
+                    max_price_impact_bps: 0,
+                },
+            )
+            .unwrap(),
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.user_account_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_fee_in_quote() {
+    let mut context = setup().await;
+    try_set_fee_in_quote(
+        &mut context.banks_client,
+        &context.payer,
+        &context.swap_config,
+        context.swap_info.pubkey,
+        &context.swap_config.admin,
+        true,
+    )
+    .await
+    .unwrap();
+
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert!(swap.is_fee_in_quote);
+
+    // the base admin fee account is no longer paid
+    let admin_fee_a_key = context.swap_info.admin_fee_a_key;
+    assert_eq!(
+        try_swap_srm(&mut context, admin_fee_a_key)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidAdmin as u32)
+        )
+    );
+
+    let admin_fee_a_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_a_key).await;
+    let admin_fee_b_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await;
+    let admin_fee_b_key = context.swap_info.admin_fee_b_key;
+    try_swap_srm(&mut context, admin_fee_b_key).await.unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_a_key).await,
+        admin_fee_a_balance
+    );
+    assert!(
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await
+            > admin_fee_b_balance
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_set_fee_in_quote(
+            &mut context.banks_client,
+            &context.payer,
+            &context.swap_config,
+            context.swap_info.pubkey,
+            &Keypair::new(),
+            true,
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,362 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        flash_swap, set_fee_in_quote, swap, FlashSwapCallbackInstruction, ReceiveFlashSwapData,
+        SwapData, SwapDirection,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, InstructionError},
+    program::invoke,
+    program_error::ProgramError,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::{approve, mint_to, transfer};
+use utils::*;
+
+const AMOUNT_IN: u64 = 1_000_000_000;
+
+/// Pays back the amount in the callback data from the forwarded source account
+fn process_flash_swap_callback(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let FlashSwapCallbackInstruction::ReceiveFlashSwap(ReceiveFlashSwapData { data, .. }) =
+        FlashSwapCallbackInstruction::unpack(input)?;
+    let amount = data
+        .get(..8)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let (authority_info, swap_in_info, token_program_info, source_info) = match (
+        accounts.get(1),
+        accounts.get(2),
+        accounts.get(4),
+        accounts.get(5),
+    ) {
+        (Some(authority), Some(swap_in), Some(token_program), Some(source)) => {
+            (authority, swap_in, token_program, source)
+        }
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+    invoke(
+        &transfer(
+            token_program_info.key,
+            source_info.key,
+            swap_in_info.key,
+            authority_info.key,
+            &[],
+            amount,
+        )?,
+        &[
+            source_info.clone(),
+            swap_in_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+    )
+}
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    srm_mint: TestMint,
+    callback_program: Pubkey,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let callback_program = Pubkey::new_unique();
+    test.add_program(
+        "flash_swap_callback",
+        callback_program,
+        processor!(process_flash_swap_callback),
+    );
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        srm_mint,
+        callback_program,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn try_flash_swap(
+    context: &mut TestContext,
+    swap_direction: SwapDirection,
+    repaid_amount: u64,
+) -> Result<(), TransactionError> {
+    let repaid_account = match swap_direction {
+        SwapDirection::SellBase => context.sol_user_account,
+        SwapDirection::SellQuote => context.srm_user_account,
+    };
+    let user_transfer_authority = Keypair::new();
+    let instruction = swap(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.market_authority,
+        context.swap_info.authority,
+        user_transfer_authority.pubkey(),
+        context.sol_user_account,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        context.srm_user_account,
+        context.deltafi_user_account,
+        context.swap_config.deltafi_mint,
+        context.swap_info.admin_fee_b_key,
+        context.swap_info.oracle_a,
+        context.swap_info.oracle_b,
+        context.swap_info.pool_mint,
+        SwapData {
+            amount_in: AMOUNT_IN,
+            minimum_amount_out: 0,
+            swap_direction,
+            max_price_impact_bps: 0,
+        },
+    )
+    .unwrap();
+    // the callback pays back from the account of the user of the token sold
+    let instruction = flash_swap(
+        instruction,
+        context.callback_program,
+        vec![AccountMeta::new(repaid_account, false)],
+        repaid_amount.to_le_bytes().to_vec(),
+    )
+    .unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &repaid_account,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                repaid_amount,
+            )
+            .unwrap(),
+            instruction,
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.user_account_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_flash_swap() {
+    let mut context = setup().await;
+    let sol_balance = get_token_balance(&mut context.banks_client, context.sol_user_account).await;
+    let pool_sol_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.token_a).await;
+
+    try_flash_swap(&mut context, SwapDirection::SellBase, AMOUNT_IN)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.sol_user_account).await,
+        sol_balance - AMOUNT_IN
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.swap_info.token_a).await,
+        pool_sol_balance + AMOUNT_IN
+    );
+    assert!(get_token_balance(&mut context.banks_client, context.srm_user_account).await > 0);
+}
+
+#[tokio::test]
+async fn test_flash_swap_not_repaid() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_flash_swap(&mut context, SwapDirection::SellBase, AMOUNT_IN - 1)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::FlashSwapNotRepaid as u32)
+        )
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.srm_user_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_flash_swap_admin_fee_in_input() {
+    let mut context = setup().await;
+
+    // selling quote with the fee in quote, the admin fee is paid out of the
+    // input reserve before the callback pays back
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            set_fee_in_quote(
+                deltafi_swap::id(),
+                context.swap_config.pubkey,
+                context.swap_info.pubkey,
+                context.swap_config.admin.pubkey(),
+                true,
+            )
+            .unwrap(),
+            mint_to(
+                &spl_token::id(),
+                &context.srm_mint.pubkey,
+                &context.srm_user_account,
+                &context.srm_mint.authority.pubkey(),
+                &[],
+                AMOUNT_IN,
+            )
+            .unwrap(),
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.swap_config.admin,
+            &context.srm_mint.authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        try_flash_swap(&mut context, SwapDirection::SellQuote, AMOUNT_IN - 1)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::FlashSwapNotRepaid as u32)
+        )
+    );
+
+    let pool_srm_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.token_b).await;
+    let admin_fee_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await;
+    let sol_balance = get_token_balance(&mut context.banks_client, context.sol_user_account).await;
+    try_flash_swap(&mut context, SwapDirection::SellQuote, AMOUNT_IN)
+        .await
+        .unwrap();
+
+    let admin_fee = get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key)
+        .await
+        - admin_fee_balance;
+    assert!(admin_fee > 0);
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.srm_user_account).await,
+        0
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.swap_info.token_b).await,
+        pool_srm_balance + AMOUNT_IN - admin_fee
+    );
+    assert!(
+        get_token_balance(&mut context.banks_client, context.sol_user_account).await > sol_balance
+    );
+}
@@ -0,0 +1,482 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        initialize_delfi_lock, lock_delfi, refresh_liquidity_obligation, unlock_delfi, vote_gauge,
+        LockDelfiData,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{
+        find_delfi_lock_address, ConfigInfo, DelfiLock, GaugeVotes, SwapInfo, MIN_LOCK_DURATION,
+    },
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    system_instruction,
+    sysvar::clock::Clock,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::AccountSharedData,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use spl_token::state::Account as Token;
+use utils::*;
+
+const LOCKED_AMOUNT: u64 = 1_000_000_000;
+
+struct TestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    // config of anyone, with gauge votes of its choice
+    other_config: TestSwapConfig,
+    swap_infos: Vec<TestSwapInfo>,
+    lock_owner: Keypair,
+    owner_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let other_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_infos = (0..2)
+        .map(|_| {
+            add_swap_info(
+                &mut test,
+                &swap_config,
+                &user_account_owner,
+                &admin_account_owner,
+                AddSwapInfoArgs {
+                    token_a_mint: spl_token::native_mint::id(),
+                    token_b_mint: srm_mint.pubkey,
+                    token_a_amount: 42_000_000_000,
+                    token_b_amount: 800_000_000_000,
+                    is_open_twap: true,
+                    oracle_a: sol_oracle.price_pubkey,
+                    oracle_b: srm_oracle.price_pubkey,
+                    market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+                    slope: Decimal::one().try_div(2).unwrap(),
+                },
+            )
+        })
+        .collect();
+
+    let mut context = test.start_with_context().await;
+    let lock_owner = Keypair::new();
+    let owner_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        lock_owner.pubkey(),
+        0,
+    )
+    .await;
+    update_packed::<Token>(&mut context, owner_account, |token| {
+        token.amount = LOCKED_AMOUNT
+    })
+    .await;
+
+    // the DELFI vault of the market authority
+    let instruction = create_associated_token_account(
+        &context.payer.pubkey(),
+        &swap_config.market_authority,
+        &swap_config.deltafi_mint,
+    );
+    process_instruction(&mut context, instruction, &[])
+        .await
+        .unwrap();
+
+    initialize_lock(&mut context, swap_config.pubkey, &lock_owner).await;
+
+    TestContext {
+        context,
+        swap_config,
+        other_config,
+        swap_infos,
+        lock_owner,
+        owner_account,
+    }
+}
+
+// the owner pays the rent of its lock
+async fn initialize_lock(context: &mut ProgramTestContext, config: Pubkey, lock_owner: &Keypair) {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let instruction = system_instruction::transfer(
+        &context.payer.pubkey(),
+        &lock_owner.pubkey(),
+        rent.minimum_balance(DelfiLock::LEN),
+    );
+    process_instruction(context, instruction, &[])
+        .await
+        .unwrap();
+
+    let instruction =
+        initialize_delfi_lock(deltafi_swap::id(), config, lock_owner.pubkey()).unwrap();
+    process_instruction(context, instruction, &[lock_owner])
+        .await
+        .unwrap();
+}
+
+async fn update_packed<T: Pack>(
+    context: &mut ProgramTestContext,
+    pubkey: Pubkey,
+    update: impl FnOnce(&mut T),
+) {
+    let mut account = context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = T::unpack_unchecked(&account.data).unwrap();
+    update(&mut state);
+    T::pack(state, &mut account.data).unwrap();
+    context.set_account(&pubkey, &AccountSharedData::from(account));
+}
+
+async fn get_packed<T: Pack>(context: &mut ProgramTestContext, pubkey: Pubkey) -> T {
+    let account = context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    T::unpack(&account.data).unwrap()
+}
+
+async fn process_instruction(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+// a new slot for the same instruction not to be rejected as already processed
+async fn warp_clock(context: &mut ProgramTestContext, seconds: i64, epochs: u64) {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.warp_to_slot(clock.slot + 1).unwrap();
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    clock.epoch += epochs;
+    context.set_sysvar(&clock);
+}
+
+async fn process_lock(test_context: &mut TestContext, unlock_ts: i64) {
+    let instruction = lock_delfi(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        test_context.swap_config.market_authority,
+        test_context.lock_owner.pubkey(),
+        test_context.owner_account,
+        test_context.swap_config.deltafi_mint,
+        LockDelfiData {
+            amount: LOCKED_AMOUNT,
+            unlock_ts,
+        },
+    )
+    .unwrap();
+    process_instruction(
+        &mut test_context.context,
+        instruction,
+        &[&test_context.lock_owner],
+    )
+    .await
+    .unwrap();
+}
+
+async fn process_vote(
+    test_context: &mut TestContext,
+    weights_bps: Vec<u16>,
+) -> Result<(), TransactionError> {
+    let swap_weights_bps = test_context
+        .swap_infos
+        .iter()
+        .map(|swap_info| swap_info.pubkey)
+        .zip(weights_bps)
+        .collect();
+    let instruction = vote_gauge(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        test_context.lock_owner.pubkey(),
+        swap_weights_bps,
+    )
+    .unwrap();
+    process_instruction(
+        &mut test_context.context,
+        instruction,
+        &[&test_context.lock_owner],
+    )
+    .await
+}
+
+async fn process_unlock(test_context: &mut TestContext) -> Result<(), TransactionError> {
+    let instruction = unlock_delfi(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        test_context.swap_config.market_authority,
+        test_context.lock_owner.pubkey(),
+        test_context.owner_account,
+        test_context.swap_config.deltafi_mint,
+    )
+    .unwrap();
+    process_instruction(
+        &mut test_context.context,
+        instruction,
+        &[&test_context.lock_owner],
+    )
+    .await
+}
+
+async fn current_ts(context: &mut ProgramTestContext) -> i64 {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp
+}
+
+#[tokio::test]
+async fn test_vote_weights_liquidity_rewards() {
+    let mut test_context = setup().await;
+    let unlock_ts = current_ts(&mut test_context.context).await + MIN_LOCK_DURATION;
+    process_lock(&mut test_context, unlock_ts).await;
+
+    let vault = get_associated_token_address(
+        &test_context.swap_config.market_authority,
+        &test_context.swap_config.deltafi_mint,
+    );
+    let banks_client = &mut test_context.context.banks_client;
+    assert_eq!(get_token_balance(banks_client, vault).await, LOCKED_AMOUNT);
+    assert_eq!(
+        get_token_balance(banks_client, test_context.owner_account).await,
+        0
+    );
+
+    process_vote(&mut test_context, vec![7_500, 2_500])
+        .await
+        .unwrap();
+    warp_clock(&mut test_context.context, 1, 1).await;
+
+    for (swap_info, weight_bps) in test_context.swap_infos.iter().zip([7_500, 2_500]) {
+        let instruction = refresh_liquidity_obligation(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            test_context.swap_config.pubkey,
+            Pubkey::default(),
+        )
+        .unwrap();
+        process_instruction(&mut test_context.context, instruction, &[])
+            .await
+            .unwrap();
+
+        let swap_state = get_packed::<SwapInfo>(&mut test_context.context, swap_info.pubkey).await;
+        assert_eq!(swap_state.gauge_weight.total_votes, LOCKED_AMOUNT);
+        assert_eq!(
+            swap_state.gauge_weight.votes,
+            LOCKED_AMOUNT * weight_bps / 10_000
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_refresh_with_other_config() {
+    let mut test_context = setup().await;
+    let unlock_ts = current_ts(&mut test_context.context).await + MIN_LOCK_DURATION;
+    process_lock(&mut test_context, unlock_ts).await;
+    process_vote(&mut test_context, vec![10_000, 0])
+        .await
+        .unwrap();
+    warp_clock(&mut test_context.context, 1, 1).await;
+
+    // a config with a single vote cast for all the pools of the epoch
+    let other_config = test_context.other_config.pubkey;
+    update_packed::<ConfigInfo>(&mut test_context.context, other_config, |config| {
+        config.gauge_votes = GaugeVotes {
+            epoch: 0,
+            votes: 1,
+            previous_votes: 0,
+        }
+    })
+    .await;
+    let instruction = refresh_liquidity_obligation(
+        deltafi_swap::id(),
+        test_context.swap_infos[0].pubkey,
+        other_config,
+        Pubkey::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(&mut test_context.context, instruction, &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectConfigAccount as u32)
+        )
+    );
+
+    // nor counts its votes in the totals of another config
+    let mut instruction = vote_gauge(
+        deltafi_swap::id(),
+        other_config,
+        test_context.lock_owner.pubkey(),
+        vec![(test_context.swap_infos[0].pubkey, 10_000)],
+    )
+    .unwrap();
+    let (delfi_lock_key, _) = find_delfi_lock_address(
+        &deltafi_swap::id(),
+        &test_context.swap_config.pubkey,
+        &test_context.lock_owner.pubkey(),
+    );
+    instruction.accounts[1].pubkey = delfi_lock_key;
+    assert_eq!(
+        process_instruction(
+            &mut test_context.context,
+            instruction,
+            &[&test_context.lock_owner],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectConfigAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_vote_once_per_epoch() {
+    let mut test_context = setup().await;
+    let unlock_ts = current_ts(&mut test_context.context).await + MIN_LOCK_DURATION;
+    process_lock(&mut test_context, unlock_ts).await;
+
+    process_vote(&mut test_context, vec![10_000, 0])
+        .await
+        .unwrap();
+    warp_clock(&mut test_context.context, 1, 0).await;
+    assert_eq!(
+        process_vote(&mut test_context, vec![0, 10_000])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::GaugeVoteCast as u32)
+        )
+    );
+
+    warp_clock(&mut test_context.context, 1, 1).await;
+    process_vote(&mut test_context, vec![0, 10_000])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_unlock_delfi() {
+    let mut test_context = setup().await;
+    let unlock_ts = current_ts(&mut test_context.context).await + MIN_LOCK_DURATION;
+    process_lock(&mut test_context, unlock_ts).await;
+
+    assert_eq!(
+        process_unlock(&mut test_context).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::DelfiLocked as u32)
+        )
+    );
+
+    warp_clock(&mut test_context.context, MIN_LOCK_DURATION, 1).await;
+    process_unlock(&mut test_context).await.unwrap();
+
+    assert_eq!(
+        get_token_balance(
+            &mut test_context.context.banks_client,
+            test_context.owner_account
+        )
+        .await,
+        LOCKED_AMOUNT
+    );
+    let (delfi_lock_key, _) = find_delfi_lock_address(
+        &deltafi_swap::id(),
+        &test_context.swap_config.pubkey,
+        &test_context.lock_owner.pubkey(),
+    );
+    let delfi_lock = get_packed::<DelfiLock>(&mut test_context.context, delfi_lock_key).await;
+    assert_eq!(delfi_lock.locked_amount, 0);
+}
+
+#[tokio::test]
+async fn test_unlock_with_lock_of_other_config() {
+    let mut test_context = setup().await;
+    let unlock_ts = current_ts(&mut test_context.context).await + MIN_LOCK_DURATION;
+    process_lock(&mut test_context, unlock_ts).await;
+
+    // a lock in a config of anyone, with DELFI of that config unlockable
+    let other_config = test_context.other_config.pubkey;
+    initialize_lock(
+        &mut test_context.context,
+        other_config,
+        &test_context.lock_owner,
+    )
+    .await;
+    let (other_lock_key, _) = find_delfi_lock_address(
+        &deltafi_swap::id(),
+        &other_config,
+        &test_context.lock_owner.pubkey(),
+    );
+    update_packed::<DelfiLock>(&mut test_context.context, other_lock_key, |delfi_lock| {
+        delfi_lock.locked_amount = LOCKED_AMOUNT
+    })
+    .await;
+
+    // cannot be paid out of the vault of the config
+    let mut instruction = unlock_delfi(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        test_context.swap_config.market_authority,
+        test_context.lock_owner.pubkey(),
+        test_context.owner_account,
+        test_context.swap_config.deltafi_mint,
+    )
+    .unwrap();
+    instruction.accounts[2].pubkey = other_lock_key;
+    assert_eq!(
+        process_instruction(
+            &mut test_context.context,
+            instruction,
+            &[&test_context.lock_owner],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectConfigAccount as u32)
+        )
+    );
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "test-bpf")]
+
+use deltafi_swap::{
+    instruction::{get_version, ProgramBuild},
+    interface,
+    processor::process,
+    state::PROGRAM_VERSION,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+};
+use solana_program_test::*;
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signer, transaction::Transaction};
+
+/// Gets the version of the swap program, the second account, then stores the
+/// build in the first account.
+fn process_version_caller(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _input: &[u8],
+) -> ProgramResult {
+    let (build_info, program_info) = match accounts {
+        [build_info, program_info] => (build_info, program_info),
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+    let build = interface::get_version(program_info)?;
+    build_info.data.borrow_mut()[..ProgramBuild::LEN].copy_from_slice(&build.pack());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let version_caller_program = Pubkey::new_unique();
+    test.add_program(
+        "version_caller",
+        version_caller_program,
+        processor!(process_version_caller),
+    );
+    let build_account = Pubkey::new_unique();
+    test.add_account(
+        build_account,
+        Account {
+            lamports: u32::MAX as u64,
+            data: vec![0; ProgramBuild::LEN],
+            owner: version_caller_program,
+            ..Account::default()
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    // the instruction takes no accounts
+    let instruction = get_version(deltafi_swap::id()).unwrap();
+    assert!(instruction.accounts.is_empty());
+
+    let mut transaction = Transaction::new_with_payer(
+        &[Instruction {
+            program_id: version_caller_program,
+            accounts: vec![
+                AccountMeta::new(build_account, false),
+                AccountMeta::new_readonly(deltafi_swap::id(), false),
+            ],
+            data: vec![],
+        }],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let data = banks_client
+        .get_account(build_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let build = ProgramBuild::unpack(&data).unwrap();
+    assert_eq!(build, ProgramBuild::current());
+    assert_eq!(build.version, PROGRAM_VERSION);
+}
@@ -0,0 +1,193 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::get_virtual_price,
+    interface,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_error::ProgramError,
+    program_pack::Pack,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::Mint;
+use utils::*;
+
+/// Gets the virtual price of the token-swap, the second account, through the
+/// swap program, the last account, then stores it in the first account.
+fn process_price_caller(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _input: &[u8],
+) -> ProgramResult {
+    let (price_info, swap_info, pool_mint_info, token_program_info, program_info) = match accounts {
+        [price_info, swap_info, pool_mint_info, token_program_info, program_info] => (
+            price_info,
+            swap_info,
+            pool_mint_info,
+            token_program_info,
+            program_info,
+        ),
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+    let price =
+        interface::get_virtual_price(program_info, swap_info, pool_mint_info, token_program_info)?;
+    price_info.data.borrow_mut()[..16].copy_from_slice(&price.to_scaled_val()?.to_le_bytes());
+    Ok(())
+}
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_info: TestSwapInfo,
+    price_caller_program: Pubkey,
+    price_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let price_caller_program = Pubkey::new_unique();
+    test.add_program(
+        "price_caller",
+        price_caller_program,
+        processor!(process_price_caller),
+    );
+    let price_account = Pubkey::new_unique();
+    test.add_account(
+        price_account,
+        Account {
+            lamports: u32::MAX as u64,
+            data: vec![0; 16],
+            owner: price_caller_program,
+            ..Account::default()
+        },
+    );
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_info,
+        price_caller_program,
+        price_account,
+    }
+}
+
+fn price_through_caller(context: &TestContext, pool_mint: Pubkey) -> Instruction {
+    let mut instruction =
+        get_virtual_price(deltafi_swap::id(), context.swap_info.pubkey, pool_mint).unwrap();
+    instruction.program_id = context.price_caller_program;
+    instruction
+        .accounts
+        .insert(0, AccountMeta::new(context.price_account, false));
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(deltafi_swap::id(), false));
+    instruction
+}
+
+async fn signed_transaction(context: &mut TestContext, instruction: Instruction) -> Transaction {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    transaction
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+
+    let instruction = price_through_caller(&context, context.swap_info.pool_mint);
+    let transaction = signed_transaction(&mut context, instruction).await;
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let data = context
+        .banks_client
+        .get_account(context.price_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let price = Decimal::from_scaled_val(u128::from_le_bytes(data[..16].try_into().unwrap()));
+    let pool_mint = Mint::unpack(
+        &context
+            .banks_client
+            .get_account(context.swap_info.pool_mint)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    let swap_info = context.swap_info.get_state(&mut context.banks_client).await;
+    assert!(price > Decimal::zero());
+    assert_eq!(price, swap_info.lp_target_price(pool_mint.supply).unwrap());
+}
+
+#[tokio::test]
+async fn test_incorrect_mint() {
+    let mut context = setup().await;
+
+    let instruction = price_through_caller(&context, context.swap_info.token_b_mint);
+    let transaction = signed_transaction(&mut context, instruction).await;
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectMint as u32)
+        )
+    );
+}
@@ -0,0 +1,405 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        create_proposal, execute_proposal, initialize_delfi_lock, lock_delfi, set_proposal_quorum,
+        vote_proposal, CreateProposalData, LockDelfiData,
+    },
+    processor::process,
+    state::{
+        find_delfi_lock_address, find_proposal_address, ConfigInfo, DelfiLock, Fees, Proposal,
+        MIN_LOCK_DURATION, VOTING_PERIOD,
+    },
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    system_instruction,
+    sysvar::clock::Clock,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::AccountSharedData,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::instruction::create_associated_token_account;
+use spl_token::state::Account as Token;
+use utils::*;
+
+const LOCKED_AMOUNT: u64 = 1_000_000_000;
+const PROPOSAL_QUORUM: u64 = 1_500_000_000;
+
+const PROPOSED_FEES: Fees = Fees {
+    admin_trade_fee_numerator: 1,
+    admin_trade_fee_denominator: 4,
+    admin_withdraw_fee_numerator: 1,
+    admin_withdraw_fee_denominator: 4,
+    trade_fee_numerator: 3,
+    trade_fee_denominator: 1_000,
+    withdraw_fee_numerator: 1,
+    withdraw_fee_denominator: 1_000,
+    min_trade_fee: 0,
+};
+
+struct TestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    // config of anyone, with DELFI locks of its choice
+    other_config: TestSwapConfig,
+    voters: Vec<Keypair>,
+}
+
+async fn setup(voter_count: usize) -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let swap_config = add_swap_config(&mut test);
+    let other_config = add_swap_config(&mut test);
+    let mut context = test.start_with_context().await;
+
+    // the DELFI vault of the market authority
+    let instruction = create_associated_token_account(
+        &context.payer.pubkey(),
+        &swap_config.market_authority,
+        &swap_config.deltafi_mint,
+    );
+    process_instruction(&mut context, instruction, &[])
+        .await
+        .unwrap();
+
+    let instruction = set_proposal_quorum(
+        deltafi_swap::id(),
+        swap_config.pubkey,
+        swap_config.admin.pubkey(),
+        PROPOSAL_QUORUM,
+    )
+    .unwrap();
+    process_instruction(&mut context, instruction, &[&swap_config.admin])
+        .await
+        .unwrap();
+
+    // each voter locks DELFI past the voting period
+    let unlock_ts = current_ts(&mut context).await + MIN_LOCK_DURATION;
+    let mut voters = vec![];
+    for _ in 0..voter_count {
+        let voter = Keypair::new();
+        let voter_account = create_and_mint_to_token_account(
+            &mut context.banks_client,
+            swap_config.deltafi_mint,
+            None,
+            &context.payer,
+            voter.pubkey(),
+            0,
+        )
+        .await;
+        update_packed::<Token>(&mut context, voter_account, |token| {
+            token.amount = LOCKED_AMOUNT
+        })
+        .await;
+        fund_account(&mut context, voter.pubkey()).await;
+
+        let instruction =
+            initialize_delfi_lock(deltafi_swap::id(), swap_config.pubkey, voter.pubkey()).unwrap();
+        process_instruction(&mut context, instruction, &[&voter])
+            .await
+            .unwrap();
+        let instruction = lock_delfi(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_config.market_authority,
+            voter.pubkey(),
+            voter_account,
+            swap_config.deltafi_mint,
+            LockDelfiData {
+                amount: LOCKED_AMOUNT,
+                unlock_ts,
+            },
+        )
+        .unwrap();
+        process_instruction(&mut context, instruction, &[&voter])
+            .await
+            .unwrap();
+        voters.push(voter);
+    }
+
+    TestContext {
+        context,
+        swap_config,
+        other_config,
+        voters,
+    }
+}
+
+async fn update_packed<T: Pack>(
+    context: &mut ProgramTestContext,
+    pubkey: Pubkey,
+    update: impl FnOnce(&mut T),
+) {
+    let mut account = context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = T::unpack_unchecked(&account.data).unwrap();
+    update(&mut state);
+    T::pack(state, &mut account.data).unwrap();
+    context.set_account(&pubkey, &AccountSharedData::from(account));
+}
+
+async fn get_packed<T: Pack>(context: &mut ProgramTestContext, pubkey: Pubkey) -> T {
+    let account = context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    T::unpack(&account.data).unwrap()
+}
+
+// voters pay the rent of their lock and vote records
+async fn fund_account(context: &mut ProgramTestContext, pubkey: Pubkey) {
+    let instruction = system_instruction::transfer(&context.payer.pubkey(), &pubkey, 1_000_000_000);
+    process_instruction(context, instruction, &[])
+        .await
+        .unwrap();
+}
+
+async fn process_instruction(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn current_ts(context: &mut ProgramTestContext) -> i64 {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp
+}
+
+// a new slot for the same instruction not to be rejected as already processed
+async fn warp_clock(context: &mut ProgramTestContext, seconds: i64) {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.warp_to_slot(clock.slot + 1).unwrap();
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    context.set_sysvar(&clock);
+}
+
+async fn process_create_proposal(test_context: &mut TestContext) -> Pubkey {
+    let config_pubkey = test_context.swap_config.pubkey;
+    let proposal_index = get_packed::<ConfigInfo>(&mut test_context.context, config_pubkey)
+        .await
+        .proposal_count;
+    let proposer = &test_context.voters[0];
+    let instruction = create_proposal(
+        deltafi_swap::id(),
+        config_pubkey,
+        proposal_index,
+        proposer.pubkey(),
+        CreateProposalData {
+            fees: PROPOSED_FEES,
+            rewards: test_context.swap_config.rewards.clone(),
+        },
+    )
+    .unwrap();
+    process_instruction(&mut test_context.context, instruction, &[proposer])
+        .await
+        .unwrap();
+    find_proposal_address(&deltafi_swap::id(), &config_pubkey, proposal_index).0
+}
+
+async fn process_vote(
+    test_context: &mut TestContext,
+    proposal: Pubkey,
+    voter_index: usize,
+    approve: bool,
+) -> Result<(), TransactionError> {
+    let voter = &test_context.voters[voter_index];
+    let instruction = vote_proposal(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        proposal,
+        voter.pubkey(),
+        approve,
+    )
+    .unwrap();
+    process_instruction(&mut test_context.context, instruction, &[voter]).await
+}
+
+async fn process_execute(
+    test_context: &mut TestContext,
+    proposal: Pubkey,
+) -> Result<(), TransactionError> {
+    let instruction = execute_proposal(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        proposal,
+    )
+    .unwrap();
+    process_instruction(&mut test_context.context, instruction, &[]).await
+}
+
+fn swap_error(error: SwapError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_proposal_executed() {
+    let mut test_context = setup(3).await;
+    let proposal = process_create_proposal(&mut test_context).await;
+
+    process_vote(&mut test_context, proposal, 0, true)
+        .await
+        .unwrap();
+    process_vote(&mut test_context, proposal, 1, true)
+        .await
+        .unwrap();
+    process_vote(&mut test_context, proposal, 2, false)
+        .await
+        .unwrap();
+    // a lock votes once per proposal
+    warp_clock(&mut test_context.context, 1).await;
+    assert_eq!(
+        process_vote(&mut test_context, proposal, 2, true)
+            .await
+            .unwrap_err(),
+        swap_error(SwapError::AlreadyInUse)
+    );
+
+    assert_eq!(
+        process_execute(&mut test_context, proposal)
+            .await
+            .unwrap_err(),
+        swap_error(SwapError::ProposalVotingOpen)
+    );
+    warp_clock(&mut test_context.context, VOTING_PERIOD).await;
+    process_execute(&mut test_context, proposal).await.unwrap();
+
+    let proposal_state = get_packed::<Proposal>(&mut test_context.context, proposal).await;
+    assert_eq!(proposal_state.yes_votes, 2 * LOCKED_AMOUNT);
+    assert_eq!(proposal_state.no_votes, LOCKED_AMOUNT);
+    assert!(proposal_state.is_executed);
+    let config =
+        get_packed::<ConfigInfo>(&mut test_context.context, test_context.swap_config.pubkey).await;
+    assert_eq!(config.fees, PROPOSED_FEES);
+    assert_eq!(config.proposal_count, 1);
+
+    warp_clock(&mut test_context.context, 1).await;
+    assert_eq!(
+        process_execute(&mut test_context, proposal)
+            .await
+            .unwrap_err(),
+        swap_error(SwapError::ProposalExecuted)
+    );
+}
+
+#[tokio::test]
+async fn test_proposal_rejected() {
+    let mut test_context = setup(2).await;
+    let proposal = process_create_proposal(&mut test_context).await;
+
+    // a majority short of the quorum
+    process_vote(&mut test_context, proposal, 0, true)
+        .await
+        .unwrap();
+    warp_clock(&mut test_context.context, VOTING_PERIOD).await;
+    assert_eq!(
+        process_vote(&mut test_context, proposal, 1, true)
+            .await
+            .unwrap_err(),
+        swap_error(SwapError::ProposalVotingClosed)
+    );
+    assert_eq!(
+        process_execute(&mut test_context, proposal)
+            .await
+            .unwrap_err(),
+        swap_error(SwapError::ProposalRejected)
+    );
+
+    let config =
+        get_packed::<ConfigInfo>(&mut test_context.context, test_context.swap_config.pubkey).await;
+    assert_eq!(config.fees, test_context.swap_config.fees);
+}
+
+#[tokio::test]
+async fn test_vote_with_lock_of_other_config() {
+    let mut test_context = setup(1).await;
+    let proposal = process_create_proposal(&mut test_context).await;
+
+    // a lock in a config of anyone, with votes of its choice
+    let other_config = test_context.other_config.pubkey;
+    let voter = &test_context.voters[0];
+    let instruction =
+        initialize_delfi_lock(deltafi_swap::id(), other_config, voter.pubkey()).unwrap();
+    process_instruction(&mut test_context.context, instruction, &[voter])
+        .await
+        .unwrap();
+    let (other_lock_key, _) =
+        find_delfi_lock_address(&deltafi_swap::id(), &other_config, &voter.pubkey());
+    let unlock_ts = current_ts(&mut test_context.context).await + MIN_LOCK_DURATION;
+    update_packed::<DelfiLock>(&mut test_context.context, other_lock_key, |delfi_lock| {
+        delfi_lock.locked_amount = PROPOSAL_QUORUM;
+        delfi_lock.unlock_ts = unlock_ts;
+    })
+    .await;
+
+    // cannot vote on the proposals of the config
+    let voter = &test_context.voters[0];
+    let instruction = vote_proposal(
+        deltafi_swap::id(),
+        other_config,
+        proposal,
+        voter.pubkey(),
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(&mut test_context.context, instruction, &[voter])
+            .await
+            .unwrap_err(),
+        swap_error(SwapError::IncorrectConfigAccount)
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_proposal() {
+    let mut test_context = setup(1).await;
+
+    // a trade fee of the whole amount
+    let proposer = &test_context.voters[0];
+    let instruction = create_proposal(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        0,
+        proposer.pubkey(),
+        CreateProposalData {
+            fees: Fees {
+                trade_fee_numerator: 1_000,
+                ..PROPOSED_FEES
+            },
+            rewards: test_context.swap_config.rewards.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(&mut test_context.context, instruction, &[proposer])
+            .await
+            .unwrap_err(),
+        swap_error(SwapError::InvalidInput)
+    );
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::grow_provider_account,
+    processor::process,
+    state::{LiquidityProvider, MAX_LIQUIDITY_POSITIONS},
+};
+
+use solana_program::{instruction::InstructionError, rent::Rent};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const NEW_MAX_POSITIONS: u8 = 20;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    // limit to track compute unit increase
+    test.set_compute_max_units(10_000);
+
+    let user_account_owner = Keypair::new();
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let test_liquidity_provider =
+        TestLiquidityProvider::init(&mut banks_client, &user_account_owner, &payer).await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[grow_provider_account(
+            deltafi_swap::id(),
+            test_liquidity_provider.pubkey,
+            user_account_owner.pubkey(),
+            payer.pubkey(),
+            NEW_MAX_POSITIONS,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &user_account_owner], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+
+    let account = banks_client
+        .get_account(test_liquidity_provider.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let new_len = LiquidityProvider::account_len(NEW_MAX_POSITIONS as usize);
+    assert_eq!(account.data.len(), new_len);
+    assert!(Rent::default().is_exempt(account.lamports, new_len));
+
+    test_liquidity_provider
+        .validate_state(&mut banks_client)
+        .await;
+}
+
+#[tokio::test]
+async fn test_invalid_owner() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let invalid_owner = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[grow_provider_account(
+            deltafi_swap::id(),
+            liquidity_provider.pubkey,
+            invalid_owner.pubkey(),
+            payer.pubkey(),
+            NEW_MAX_POSITIONS,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &invalid_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_not_growing() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[grow_provider_account(
+            deltafi_swap::id(),
+            liquidity_provider.pubkey,
+            liquidity_owner.pubkey(),
+            payer.pubkey(),
+            MAX_LIQUIDITY_POSITIONS as u8,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &liquidity_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidInput as u32)
+        )
+    );
+}
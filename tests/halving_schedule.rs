@@ -0,0 +1,209 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{set_halving_schedule, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::HalvingSchedule,
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const AMOUNT_IN: u64 = 2_000_000_000;
+
+// one halving passed since the start of the schedule
+const HALVING_SCHEDULE: HalvingSchedule = HalvingSchedule {
+    start_ts: 0,
+    period_secs: 365 * 24 * 3_600 * 1_000,
+    halving_count: 1,
+};
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn process_instruction(
+    context: &mut TestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn swap_rewards(context: &mut TestContext) -> u64 {
+    let rewards_before =
+        get_token_balance(&mut context.banks_client, context.deltafi_user_account).await;
+    context
+        .swap_info
+        .swap(
+            &mut context.banks_client,
+            &context.swap_config,
+            &context.user_account_owner,
+            context.sol_user_account,
+            context.srm_user_account,
+            context.deltafi_user_account,
+            AMOUNT_IN,
+            0,
+            SwapDirection::SellBase,
+            &context.payer,
+        )
+        .await;
+    get_token_balance(&mut context.banks_client, context.deltafi_user_account).await
+        - rewards_before
+}
+
+#[tokio::test]
+async fn test_halved_trade_rewards() {
+    let mut context = setup().await;
+
+    let full_rewards = swap_rewards(&mut context).await;
+    assert!(full_rewards > 1);
+
+    let instruction = set_halving_schedule(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.admin.pubkey(),
+        HALVING_SCHEDULE,
+    )
+    .unwrap();
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.swap_config.admin],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let swap_info = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap_info.halving_schedule, HALVING_SCHEDULE);
+
+    // same amount in earns the same rewards, halved once
+    assert_eq!(swap_rewards(&mut context).await, full_rewards / 2);
+}
+
+#[tokio::test]
+async fn test_set_halving_schedule_not_admin() {
+    let mut context = setup().await;
+    let not_admin = Keypair::new();
+
+    let instruction = set_halving_schedule(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        not_admin.pubkey(),
+        HALVING_SCHEDULE,
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(&mut context, instruction, &[&not_admin])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
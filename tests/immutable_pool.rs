@@ -0,0 +1,153 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{make_immutable, pause, set_max_price_impact, set_mid_price},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct TestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let context = test.start_with_context().await;
+
+    TestContext {
+        context,
+        swap_config,
+        swap_info,
+    }
+}
+
+async fn process_instruction(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, signer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_make_immutable() {
+    let mut test_context = setup().await;
+    let admin = test_context.swap_config.admin.pubkey();
+    let config = test_context.swap_config.pubkey;
+    let swap = test_context.swap_info.pubkey;
+
+    process_instruction(
+        &mut test_context.context,
+        make_immutable(deltafi_swap::id(), config, swap, admin).unwrap(),
+        &test_context.swap_config.admin,
+    )
+    .await
+    .unwrap();
+    let swap_state = test_context
+        .swap_info
+        .get_state(&mut test_context.context.banks_client)
+        .await;
+    assert!(swap_state.is_immutable);
+
+    // the admin can no longer change the pool
+    for instruction in vec![
+        pause(deltafi_swap::id(), config, swap, admin).unwrap(),
+        set_max_price_impact(deltafi_swap::id(), config, swap, admin, 100).unwrap(),
+        set_mid_price(
+            deltafi_swap::id(),
+            config,
+            swap,
+            admin,
+            test_context.swap_info.oracle_a,
+            test_context.swap_info.oracle_b,
+            test_context.swap_info.pool_mint,
+            swap_state.pool_state.market_price.to_scaled_val().unwrap(),
+        )
+        .unwrap(),
+        make_immutable(deltafi_swap::id(), config, swap, admin).unwrap(),
+    ] {
+        assert_eq!(
+            process_instruction(
+                &mut test_context.context,
+                instruction,
+                &test_context.swap_config.admin,
+            )
+            .await
+            .unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(SwapError::ImmutablePool as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_make_immutable_not_admin() {
+    let mut test_context = setup().await;
+    let not_admin = Keypair::new();
+
+    let instruction = make_immutable(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        test_context.swap_info.pubkey,
+        not_admin.pubkey(),
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(&mut test_context.context, instruction, &not_admin)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -18,7 +18,7 @@ async fn test_success() {
     let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
 
     // limit to track compute unit increase
-    test.set_bpf_compute_max_units(3_000);
+    test.set_compute_max_units(20_000);
 
     let user_account_owner = Keypair::new();
 
@@ -41,12 +41,7 @@ async fn test_already_initialized() {
     let (mut banks_client, payer, recent_blockhash) = test.start().await;
 
     let mut transaction = Transaction::new_with_payer(
-        &[init_liquidity_provider(
-            deltafi_swap::id(),
-            existing_liquidity_provider.pubkey,
-            existing_liquidity_provider.owner,
-        )
-        .unwrap()],
+        &[init_liquidity_provider(deltafi_swap::id(), existing_liquidity_provider.owner).unwrap()],
         Some(&payer.pubkey()),
     );
     transaction.sign(&[&payer, &liquidity_owner], recent_blockhash);
@@ -62,3 +57,26 @@ async fn test_already_initialized() {
         )
     );
 }
+
+#[tokio::test]
+async fn test_invalid_address() {
+    let test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut instruction = init_liquidity_provider(deltafi_swap::id(), payer.pubkey()).unwrap();
+    instruction.accounts[0].pubkey = Pubkey::new_unique();
+
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidProgramAddress as u32)
+        )
+    );
+}
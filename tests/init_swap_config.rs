@@ -17,7 +17,7 @@ async fn test_success() {
     let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
 
     // limit to track compute unit increase
-    test.set_bpf_compute_max_units(20_000);
+    test.set_compute_max_units(20_000);
 
     let (mut banks_client, payer, _recent_blockhash) = test.start().await;
 
@@ -42,6 +42,8 @@ async fn test_already_initialized() {
             existing_config.admin.pubkey(),
             existing_config.fees,
             existing_config.rewards,
+            existing_config.max_mid_price_deviation_bps,
+            existing_config.min_mid_price_update_interval,
         )
         .unwrap()],
         Some(&payer.pubkey()),
@@ -9,9 +9,11 @@ use deltafi_swap::{
     instruction::{initialize, InitializeData},
     math::{Decimal, TryDiv},
     processor::process,
+    pyth,
 };
 use solana_program_test::*;
 use solana_sdk::{
+    account::Account,
     instruction::InstructionError,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
@@ -24,7 +26,7 @@ async fn test_success() {
     let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
 
     // limit to track compute unit increase
-    test.set_bpf_compute_max_units(30_000);
+    test.set_compute_max_units(30_000);
 
     let swap_config = add_swap_config(&mut test);
     let sol_oracle = add_sol_oracle(&mut test);
@@ -96,6 +98,7 @@ async fn test_success() {
                 .try_into()
                 .unwrap(),
             is_open_twap: true,
+            twap_window_secs: 3_600,
         },
     )
     .await;
@@ -150,6 +153,9 @@ async fn test_already_initialized() {
             existing_swap.pool_token,
             sol_oracle.price_pubkey,
             srm_oracle.price_pubkey,
+            0,
+            existing_swap.token_a_mint,
+            existing_swap.token_b_mint,
             InitializeData {
                 nonce: existing_swap.nonce,
                 mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
@@ -161,6 +167,8 @@ async fn test_already_initialized() {
                     .try_into()
                     .unwrap(),
                 is_open_twap: true,
+                twap_window_secs: 3_600,
+                pair_symbol: None,
             },
         )
         .unwrap()],
@@ -180,3 +188,142 @@ async fn test_already_initialized() {
         )
     );
 }
+
+/// Initialize a SOL/SRM pool with the oracles, returns the error of the
+/// transaction
+async fn try_init_with_oracles(
+    test: ProgramTest,
+    swap_config: &TestSwapConfig,
+    sol_oracle: &TestOracle,
+    srm_oracle: &TestOracle,
+    srm_mint: &TestMint,
+) -> Result<TestSwapInfo, TransactionError> {
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_accounts_owner.pubkey(),
+        800_000_000_000,
+    )
+    .await;
+
+    let admin_fee_accounts = Keypair::new();
+    let sol_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+
+    TestSwapInfo::try_init(
+        &mut banks_client,
+        swap_config,
+        sol_oracle,
+        srm_oracle,
+        spl_token::native_mint::id(),
+        srm_mint.pubkey,
+        sol_user_account,
+        srm_user_account,
+        sol_admin_account,
+        srm_admin_account,
+        &user_accounts_owner,
+        &payer,
+        &SwapInitArgs {
+            mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+            slope: Decimal::one()
+                .try_div(2)
+                .unwrap()
+                .to_scaled_val()
+                .unwrap()
+                .try_into()
+                .unwrap(),
+            is_open_twap: true,
+            twap_window_secs: 3_600,
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_oracle_not_price_account() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    // the product account of SOL, owned by the Pyth program
+    let product_oracle = TestOracle {
+        price_pubkey: sol_oracle.product_pubkey,
+        ..sol_oracle
+    };
+    assert_eq!(
+        try_init_with_oracles(test, &swap_config, &product_oracle, &srm_oracle, &srm_mint)
+            .await
+            .err(),
+        Some(TransactionError::InstructionError(
+            7,
+            InstructionError::Custom(SwapError::IncorrectOracleAccount as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_oracle_of_other_program() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    // a copy of a valid price account, owned by anyone
+    let fake_oracle = TestOracle {
+        price_pubkey: Pubkey::new_unique(),
+        ..sol_oracle
+    };
+    test.add_account(
+        fake_oracle.price_pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data: pyth::test::price_data(150, 0, 0),
+            owner: Pubkey::new_unique(),
+            ..Account::default()
+        },
+    );
+    assert_eq!(
+        try_init_with_oracles(test, &swap_config, &fake_oracle, &srm_oracle, &srm_mint)
+            .await
+            .err(),
+        Some(TransactionError::InstructionError(
+            7,
+            InstructionError::Custom(SwapError::IncorrectOracleAccount as u32)
+        ))
+    );
+}
@@ -0,0 +1,236 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{claim_liquidity_rewards, set_liquidity_provider_delegate},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_liquidity_provider_address, LiquidityPosition, LiquidityProvider},
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    owner: Pubkey,
+    liquidity_provider: Pubkey,
+}
+
+const REWARDS_OWED: u64 = 1_000_000;
+
+async fn setup() -> (TestContext, Keypair, Keypair) {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let owner = Keypair::new();
+    let delegate = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (liquidity_provider, _) =
+        find_liquidity_provider_address(&deltafi_swap::id(), &owner.pubkey());
+    test.add_packable_account(
+        liquidity_provider,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: owner.pubkey(),
+            delegate: Pubkey::default(),
+            referrer: Pubkey::default(),
+            positions: vec![LiquidityPosition {
+                pool: swap_info.pubkey,
+                liquidity_amount: 2_000_000_000,
+                rewards_owed: REWARDS_OWED,
+                ..LiquidityPosition::default()
+            }],
+        },
+        &deltafi_swap::id(),
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    (
+        TestContext {
+            banks_client,
+            payer,
+            swap_config,
+            swap_info,
+            owner: owner.pubkey(),
+            liquidity_provider,
+        },
+        owner,
+        delegate,
+    )
+}
+
+async fn process_instruction(
+    context: &mut TestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, signer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn set_delegate(
+    context: &mut TestContext,
+    signer: &Keypair,
+    delegate: Pubkey,
+) -> Result<(), TransactionError> {
+    let mut instruction =
+        set_liquidity_provider_delegate(deltafi_swap::id(), context.owner, delegate).unwrap();
+    // the liquidity provider of the owner, signed by another authority
+    instruction.accounts[1].pubkey = signer.pubkey();
+    process_instruction(context, instruction, signer).await
+}
+
+async fn claim_as(
+    context: &mut TestContext,
+    authority: &Keypair,
+) -> Result<Pubkey, TransactionError> {
+    let claim_destination = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        context.swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        authority.pubkey(),
+        0,
+    )
+    .await;
+    let instruction = claim_liquidity_rewards(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.market_authority,
+        context.liquidity_provider,
+        authority.pubkey(),
+        claim_destination,
+        context.swap_config.deltafi_mint,
+    )
+    .unwrap();
+    process_instruction(context, instruction, authority)
+        .await
+        .map(|_| claim_destination)
+}
+
+async fn get_liquidity_provider(context: &mut TestContext) -> LiquidityProvider {
+    let account = context
+        .banks_client
+        .get_account(context.liquidity_provider)
+        .await
+        .unwrap()
+        .unwrap();
+    LiquidityProvider::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_delegate_claims() {
+    let (mut context, owner, delegate) = setup().await;
+
+    set_delegate(&mut context, &owner, delegate.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        get_liquidity_provider(&mut context).await.delegate,
+        delegate.pubkey()
+    );
+
+    let claim_destination = claim_as(&mut context, &delegate).await.unwrap();
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, claim_destination).await,
+        REWARDS_OWED
+    );
+}
+
+#[tokio::test]
+async fn test_no_delegate() {
+    let (mut context, _owner, delegate) = setup().await;
+
+    assert_eq!(
+        claim_as(&mut context, &delegate).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_removed_delegate() {
+    let (mut context, owner, delegate) = setup().await;
+
+    set_delegate(&mut context, &owner, delegate.pubkey())
+        .await
+        .unwrap();
+    set_delegate(&mut context, &owner, Pubkey::default())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        claim_as(&mut context, &delegate).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_delegate_cannot_set_delegate() {
+    let (mut context, owner, delegate) = setup().await;
+
+    set_delegate(&mut context, &owner, delegate.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        set_delegate(&mut context, &delegate, Pubkey::new_unique())
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        )
+    );
+}
@@ -0,0 +1,219 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{migrate_config, migrate_swap},
+    processor::process,
+    state::{
+        ConfigInfo, SwapInfo, CONFIG_INFO_SIZE_V1, PROGRAM_VERSION, PROGRAM_VERSION_1,
+        SWAP_INFO_SIZE_V1,
+    },
+};
+use solana_program::{
+    instruction::InstructionError, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+fn add_legacy_account(test: &mut ProgramTest, pubkey: Pubkey, data: &[u8]) {
+    let mut account = Account::new(
+        Rent::default().minimum_balance(data.len()),
+        data.len(),
+        &deltafi_swap::id(),
+    );
+    account.data.copy_from_slice(data);
+    test.add_account(pubkey, account);
+}
+
+fn add_legacy_config(test: &mut ProgramTest, admin: &Keypair) -> Pubkey {
+    let config_pubkey = Pubkey::new_unique();
+    let mut packed = [0u8; ConfigInfo::LEN];
+    ConfigInfo {
+        version: PROGRAM_VERSION_1,
+        admin_key: admin.pubkey(),
+        deltafi_mint: Pubkey::new_unique(),
+        fees: TEST_FEES,
+        rewards: TEST_REWARDS,
+        ..ConfigInfo::default()
+    }
+    .pack_into_slice(&mut packed);
+    add_legacy_account(test, config_pubkey, &packed[..CONFIG_INFO_SIZE_V1]);
+    config_pubkey
+}
+
+fn add_legacy_swap(test: &mut ProgramTest) -> Pubkey {
+    let swap_pubkey = Pubkey::new_unique();
+    let mut packed = [0u8; SwapInfo::LEN];
+    SwapInfo {
+        is_initialized: true,
+        token_a: Pubkey::new_unique(),
+        token_b: Pubkey::new_unique(),
+        fees: TEST_FEES,
+        rewards: TEST_REWARDS,
+        ..SwapInfo::default()
+    }
+    .pack_into_slice(&mut packed);
+    add_legacy_account(test, swap_pubkey, &packed[..SWAP_INFO_SIZE_V1]);
+    swap_pubkey
+}
+
+fn add_current_swap(test: &mut ProgramTest, pyth_a: Pubkey, pyth_b: Pubkey) -> Pubkey {
+    let swap_pubkey = Pubkey::new_unique();
+    let mut packed = [0u8; SwapInfo::LEN];
+    SwapInfo {
+        is_initialized: true,
+        token_a: Pubkey::new_unique(),
+        token_b: Pubkey::new_unique(),
+        fees: TEST_FEES,
+        rewards: TEST_REWARDS,
+        pyth_a,
+        pyth_b,
+        version: PROGRAM_VERSION,
+        ..SwapInfo::default()
+    }
+    .pack_into_slice(&mut packed);
+    add_legacy_account(test, swap_pubkey, &packed);
+    swap_pubkey
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let admin = Keypair::new();
+    let config_pubkey = add_legacy_config(&mut test, &admin);
+    let swap_pubkey = add_legacy_swap(&mut test);
+    let pyth_a = add_sol_oracle(&mut test).price_pubkey;
+    let pyth_b = add_srm_oracle(&mut test).price_pubkey;
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[migrate_swap(
+            deltafi_swap::id(),
+            config_pubkey,
+            swap_pubkey,
+            admin.pubkey(),
+            payer.pubkey(),
+            pyth_a,
+            pyth_b,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+
+    let config_account = banks_client
+        .get_account(config_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let config = ConfigInfo::unpack(&config_account.data).unwrap();
+    assert_eq!(config.version, PROGRAM_VERSION);
+    assert_eq!(config.admin_key, admin.pubkey());
+    assert_eq!(config.fees, TEST_FEES);
+    assert_eq!(config.max_mid_price_deviation_bps, 0);
+    assert_eq!(config.min_mid_price_update_interval, 0);
+    assert!(Rent::default().is_exempt(config_account.lamports, ConfigInfo::LEN));
+
+    let swap_account = banks_client
+        .get_account(swap_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_swap = SwapInfo::unpack(&swap_account.data).unwrap();
+    assert_eq!(token_swap.version, PROGRAM_VERSION);
+    assert_eq!(token_swap.pyth_a, pyth_a);
+    assert_eq!(token_swap.pyth_b, pyth_b);
+    assert_eq!(token_swap.rewards, TEST_REWARDS);
+    assert!(Rent::default().is_exempt(swap_account.lamports, SwapInfo::LEN));
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let admin = Keypair::new();
+    let config_pubkey = add_legacy_config(&mut test, &admin);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let invalid_admin = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[migrate_config(
+            deltafi_swap::id(),
+            config_pubkey,
+            invalid_admin.pubkey(),
+            payer.pubkey(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &invalid_admin], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_current_version() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let admin = Keypair::new();
+    let config_pubkey = add_legacy_config(&mut test, &admin);
+    let pyth_a = Pubkey::new_unique();
+    let pyth_b = Pubkey::new_unique();
+    let swap_pubkey = add_current_swap(&mut test, pyth_a, pyth_b);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[migrate_swap(
+            deltafi_swap::id(),
+            config_pubkey,
+            swap_pubkey,
+            admin.pubkey(),
+            payer.pubkey(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+
+    let swap_account = banks_client
+        .get_account(swap_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_swap = SwapInfo::unpack(&swap_account.data).unwrap();
+    assert_eq!(token_swap.version, PROGRAM_VERSION);
+    // current pools keep their stored oracles
+    assert_eq!(token_swap.pyth_a, pyth_a);
+    assert_eq!(token_swap.pyth_b, pyth_b);
+    assert_eq!(token_swap.rewards, TEST_REWARDS);
+    assert!(Rent::default().is_exempt(swap_account.lamports, SwapInfo::LEN));
+}
@@ -0,0 +1,412 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{deposit, swap, wrap_native_sol, DepositData, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    system_program,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const WALLET_LAMPORTS: u64 = 100_000_000_000;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    wallet: Keypair,
+    liquidity_provider: TestLiquidityProvider,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+    pool_token_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let wallet = Keypair::new();
+    test.add_account(
+        wallet.pubkey(),
+        Account {
+            lamports: WALLET_LAMPORTS,
+            owner: system_program::id(),
+            ..Account::default()
+        },
+    );
+    let liquidity_provider = add_liquidity_provider(&mut test, &wallet);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    // pool token accounts are real wrapped SOL accounts, moving lamports on transfers
+    let pool_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    let sol_pool_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        pool_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_pool_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        pool_owner.pubkey(),
+        800_000_000_000,
+    )
+    .await;
+    let sol_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        admin_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        admin_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let swap_info = TestSwapInfo::init(
+        &mut banks_client,
+        &swap_config,
+        &sol_oracle,
+        &srm_oracle,
+        spl_token::native_mint::id(),
+        srm_mint.pubkey,
+        sol_pool_account,
+        srm_pool_account,
+        sol_admin_account,
+        srm_admin_account,
+        &pool_owner,
+        &payer,
+        &SwapInitArgs {
+            mid_price: sol_oracle
+                .price
+                .try_div(srm_oracle.price)
+                .unwrap()
+                .to_scaled_val()
+                .unwrap(),
+            slope: Decimal::one()
+                .try_div(2)
+                .unwrap()
+                .to_scaled_val()
+                .unwrap()
+                .try_into()
+                .unwrap(),
+            is_open_twap: true,
+            twap_window_secs: 3_600,
+        },
+    )
+    .await;
+
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        wallet.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        wallet.pubkey(),
+        0,
+    )
+    .await;
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_info.pool_mint,
+        None,
+        &payer,
+        wallet.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        wallet,
+        liquidity_provider,
+        srm_user_account,
+        deltafi_user_account,
+        pool_token_account,
+    }
+}
+
+/// Swap instruction of the wallet, token_a of the pool is SOL so the
+/// temporary account is the user token_a account in both directions
+fn swap_instruction(
+    context: &TestContext,
+    native_account: Pubkey,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+) -> Instruction {
+    let admin_fee_key = match swap_direction {
+        SwapDirection::SellBase => context.swap_info.admin_fee_b_key,
+        SwapDirection::SellQuote => context.swap_info.admin_fee_a_key,
+    };
+    swap(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.market_authority,
+        context.swap_info.authority,
+        context.wallet.pubkey(),
+        native_account,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        context.srm_user_account,
+        context.deltafi_user_account,
+        context.swap_config.deltafi_mint,
+        admin_fee_key,
+        context.swap_info.oracle_a,
+        context.swap_info.oracle_b,
+        context.swap_info.pool_mint,
+        SwapData {
+            amount_in,
+            minimum_amount_out: 0,
+            swap_direction,
+            max_price_impact_bps: 0,
+        },
+    )
+    .unwrap()
+}
+
+fn swap_native(
+    context: &TestContext,
+    native_account: Pubkey,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+) -> Transaction {
+    let instruction = swap_instruction(context, native_account, amount_in, swap_direction);
+    Transaction::new_with_payer(
+        &[wrap_native_sol(instruction, context.wallet.pubkey(), native_account).unwrap()],
+        Some(&context.payer.pubkey()),
+    )
+}
+
+async fn process_transaction(
+    context: &mut TestContext,
+    mut transaction: Transaction,
+    native_account: &Keypair,
+) -> Result<(), TransactionError> {
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.wallet, native_account],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn get_lamports(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
+    banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .map_or(0, |account| account.lamports)
+}
+
+#[tokio::test]
+async fn test_swap_sell_sol() {
+    let mut context = setup().await;
+    let native_account = Keypair::new();
+    let amount_in = 1_000_000_000;
+
+    let transaction = swap_native(
+        &context,
+        native_account.pubkey(),
+        amount_in,
+        SwapDirection::SellBase,
+    );
+    process_transaction(&mut context, transaction, &native_account)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_lamports(&mut context.banks_client, context.wallet.pubkey()).await,
+        WALLET_LAMPORTS - amount_in
+    );
+    assert_eq!(
+        get_lamports(&mut context.banks_client, native_account.pubkey()).await,
+        0
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.swap_info.token_a).await,
+        42_000_000_000 + amount_in
+    );
+    assert!(
+        get_token_balance(&mut context.banks_client, context.srm_user_account).await
+            > 200_000_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_swap_buy_sol() {
+    let mut context = setup().await;
+    let native_account = Keypair::new();
+    let amount_in = 20_000_000_000;
+
+    let transaction = swap_native(
+        &context,
+        native_account.pubkey(),
+        amount_in,
+        SwapDirection::SellQuote,
+    );
+    process_transaction(&mut context, transaction, &native_account)
+        .await
+        .unwrap();
+
+    let amount_out = 42_000_000_000
+        - get_token_balance(&mut context.banks_client, context.swap_info.token_a).await
+        - get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_a_key).await;
+    assert!(amount_out > 0);
+    assert_eq!(
+        get_lamports(&mut context.banks_client, context.wallet.pubkey()).await,
+        WALLET_LAMPORTS + amount_out
+    );
+    assert_eq!(
+        get_lamports(&mut context.banks_client, native_account.pubkey()).await,
+        0
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.srm_user_account).await,
+        200_000_000_000 - amount_in
+    );
+}
+
+#[tokio::test]
+async fn test_deposit() {
+    let mut context = setup().await;
+    let native_account = Keypair::new();
+
+    let instruction = deposit(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.swap_info.authority,
+        context.wallet.pubkey(),
+        native_account.pubkey(),
+        context.srm_user_account,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        context.swap_info.pool_mint,
+        context.pool_token_account,
+        context.liquidity_provider.pubkey,
+        context.wallet.pubkey(),
+        context.swap_info.oracle_a,
+        context.swap_info.oracle_b,
+        DepositData {
+            token_a_amount: 8_000_000_000,
+            token_b_amount: 160_000_000_000,
+            min_mint_amount: 0,
+        },
+    )
+    .unwrap();
+    let transaction = Transaction::new_with_payer(
+        &[wrap_native_sol(
+            instruction,
+            context.wallet.pubkey(),
+            native_account.pubkey(),
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    process_transaction(&mut context, transaction, &native_account)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_lamports(&mut context.banks_client, context.wallet.pubkey()).await,
+        WALLET_LAMPORTS - 8_000_000_000
+    );
+    assert_eq!(
+        get_lamports(&mut context.banks_client, native_account.pubkey()).await,
+        0
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.swap_info.token_a).await,
+        50_000_000_000
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.srm_user_account).await,
+        40_000_000_000
+    );
+    assert!(get_token_balance(&mut context.banks_client, context.pool_token_account).await > 0);
+}
+
+#[tokio::test]
+async fn test_native_account_not_in_swap() {
+    let mut context = setup().await;
+    let native_account = Keypair::new();
+
+    // the swap debits another account than the temporary one
+    let instruction = swap_instruction(
+        &context,
+        Pubkey::new_unique(),
+        1_000_000_000,
+        SwapDirection::SellBase,
+    );
+    let transaction = Transaction::new_with_payer(
+        &[wrap_native_sol(
+            instruction,
+            context.wallet.pubkey(),
+            native_account.pubkey(),
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    assert_eq!(
+        process_transaction(&mut context, transaction, &native_account)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidInput as u32)
+        )
+    );
+    assert_eq!(
+        get_lamports(&mut context.banks_client, context.wallet.pubkey()).await,
+        WALLET_LAMPORTS
+    );
+}
@@ -0,0 +1,330 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{init_observation_buffer, set_mid_price, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::ObservationBuffer,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+async fn get_observation_buffer(
+    banks_client: &mut BanksClient,
+    observation_buffer_pubkey: Pubkey,
+) -> Vec<u8> {
+    banks_client
+        .get_account(observation_buffer_pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    swap_info
+        .swap(
+            &mut banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            2_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &payer,
+        )
+        .await;
+
+    let data = get_observation_buffer(&mut banks_client, swap_info.observation_buffer).await;
+    let observation_buffer = ObservationBuffer::load(&data).unwrap();
+    assert_eq!(observation_buffer.cardinality(), 2);
+
+    let swap = swap_info.get_state(&mut banks_client).await;
+    let latest = observation_buffer.latest().unwrap();
+    assert_eq!(latest.timestamp(), swap.block_timestamp_last);
+    assert_eq!(
+        latest.base_price_cumulative(),
+        swap.base_price_cumulative_last
+    );
+}
+
+#[tokio::test]
+async fn test_init() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    // limit to track compute unit increase
+    test.set_compute_max_units(30_000);
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_accounts_owner.pubkey(),
+        800_000_000_000,
+    )
+    .await;
+
+    let admin_fee_accounts = Keypair::new();
+    let sol_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+
+    // creates the observation buffer right after the token-swap
+    let swap_info = TestSwapInfo::init(
+        &mut banks_client,
+        &swap_config,
+        &sol_oracle,
+        &srm_oracle,
+        spl_token::native_mint::id(),
+        srm_mint.pubkey,
+        sol_user_account,
+        srm_user_account,
+        sol_admin_account,
+        srm_admin_account,
+        &user_accounts_owner,
+        &payer,
+        &SwapInitArgs {
+            mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+            slope: Decimal::one()
+                .try_div(2)
+                .unwrap()
+                .to_scaled_val()
+                .unwrap()
+                .try_into()
+                .unwrap(),
+            is_open_twap: true,
+            twap_window_secs: 3_600,
+        },
+    )
+    .await;
+
+    let swap = swap_info.get_state(&mut banks_client).await;
+    let data = get_observation_buffer(&mut banks_client, swap_info.observation_buffer).await;
+    let observation_buffer = ObservationBuffer::load(&data).unwrap();
+    assert_eq!(observation_buffer.swap, swap_info.pubkey);
+    assert_eq!(observation_buffer.cardinality(), 1);
+    assert_eq!(
+        observation_buffer.latest().unwrap().timestamp(),
+        swap.block_timestamp_last
+    );
+}
+
+#[tokio::test]
+async fn test_already_initialized() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 10_000_000_000,
+            token_b_amount: 1_000_000_000_000,
+            is_open_twap: true,
+            oracle_a: Pubkey::new_unique(),
+            oracle_b: Pubkey::new_unique(),
+            market_price: Decimal::from(100u64),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[init_observation_buffer(deltafi_swap::id(), swap_info.pubkey, payer.pubkey()).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::AlreadyInUse as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_failure_observation_buffer_of_other_swap() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let args = || AddSwapInfoArgs {
+        token_a_mint: spl_token::native_mint::id(),
+        token_b_mint: srm_mint.pubkey,
+        token_a_amount: 10_000_000_000,
+        token_b_amount: 1_000_000_000_000,
+        is_open_twap: true,
+        oracle_a: Pubkey::new_unique(),
+        oracle_b: Pubkey::new_unique(),
+        market_price: Decimal::from(100u64),
+        slope: Decimal::one().try_div(2).unwrap(),
+    };
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        args(),
+    );
+    let other_swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        args(),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut instruction = set_mid_price(
+        deltafi_swap::id(),
+        swap_config.pubkey,
+        swap_info.pubkey,
+        swap_config.admin.pubkey(),
+        swap_info.oracle_a,
+        swap_info.oracle_b,
+        swap_info.pool_mint,
+        Decimal::from(101u64).to_scaled_val().unwrap(),
+    )
+    .unwrap();
+    instruction.accounts[6].pubkey = other_swap_info.observation_buffer;
+
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidProgramAddress as u32)
+        )
+    );
+}
@@ -0,0 +1,421 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{append_openbook_market, set_openbook_market, swap, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    openbook::{
+        self,
+        test::{market_data, slab_data},
+        Side,
+    },
+    processor::process,
+    pyth,
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+/// Lot sizes of the test markets, a price in lots being a price in tokens
+const LOT_SIZE: u64 = 1_000_000;
+
+/// Accounts of an OpenBook market added to the test
+#[derive(Clone, Copy)]
+struct TestMarket {
+    pubkey: Pubkey,
+    bids: Pubkey,
+    asks: Pubkey,
+}
+
+fn add_openbook_account(test: &mut ProgramTest, pubkey: Pubkey, data: Vec<u8>) {
+    test.add_account(
+        pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data,
+            owner: openbook::id(),
+            ..Account::default()
+        },
+    );
+}
+
+/// Pyth price account last valid at the first slot, stale once the test
+/// warps past it
+fn add_stale_oracle(test: &mut ProgramTest) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    test.add_account(
+        pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data: pyth::test::price_data(20, 0, 0),
+            owner: pyth::id(),
+            ..Account::default()
+        },
+    );
+    pubkey
+}
+
+fn add_market(
+    test: &mut ProgramTest,
+    coin_mint: &Pubkey,
+    pc_mint: &Pubkey,
+    bid_prices: &[u64],
+    ask_prices: &[u64],
+) -> TestMarket {
+    let market = TestMarket {
+        pubkey: Pubkey::new_unique(),
+        bids: Pubkey::new_unique(),
+        asks: Pubkey::new_unique(),
+    };
+    add_openbook_account(
+        test,
+        market.pubkey,
+        market_data(
+            &market.pubkey,
+            coin_mint,
+            pc_mint,
+            &market.bids,
+            &market.asks,
+            LOT_SIZE,
+            LOT_SIZE,
+        ),
+    );
+    add_openbook_account(test, market.bids, slab_data(Side::Bids, bid_prices));
+    add_openbook_account(test, market.asks, slab_data(Side::Asks, ask_prices));
+    market
+}
+
+struct TestContext {
+    _program_context: ProgramTestContext,
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    market: TestMarket,
+    other_market: TestMarket,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+/// A pool priced at 20 with stale oracles and without twap, along with a
+/// market quoting 24/26 and another one of its tokens
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+    let oracle_a = add_stale_oracle(&mut test);
+    let oracle_b = add_stale_oracle(&mut test);
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 10_000_000_000,
+            token_b_amount: 200_000_000_000,
+            is_open_twap: false,
+            oracle_a,
+            oracle_b,
+            market_price: Decimal::from(20u64),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    let market = add_market(
+        &mut test,
+        &spl_token::native_mint::id(),
+        &srm_mint.pubkey,
+        &[23, 24],
+        &[26, 27],
+    );
+    let other_market = add_market(
+        &mut test,
+        &spl_token::native_mint::id(),
+        &srm_mint.pubkey,
+        &[30],
+        &[31],
+    );
+
+    let mut program_context = test.start_with_context().await;
+    program_context.warp_to_slot(100).unwrap();
+    let mut banks_client = program_context.banks_client.clone();
+    let payer = Keypair::from_bytes(&program_context.payer.to_bytes()).unwrap();
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        _program_context: program_context,
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        market,
+        other_market,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn set_market(
+    context: &mut TestContext,
+    openbook_market: Pubkey,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_openbook_market(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_info.pubkey,
+            context.swap_config.admin.pubkey(),
+            openbook_market,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.swap_config.admin],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+/// Sell 1 SOL, passing the market when given
+async fn sell_base(
+    context: &mut TestContext,
+    market: Option<&TestMarket>,
+) -> Result<(), TransactionError> {
+    let amount_in = 1_000_000_000;
+    let user_transfer_authority = Keypair::new();
+    let swap_info = &context.swap_info;
+    let mut instruction: Instruction = swap(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        swap_info.pubkey,
+        context.swap_config.market_authority,
+        swap_info.authority,
+        user_transfer_authority.pubkey(),
+        context.sol_user_account,
+        swap_info.token_a,
+        swap_info.token_b,
+        context.srm_user_account,
+        context.deltafi_user_account,
+        context.swap_config.deltafi_mint,
+        swap_info.admin_fee_b_key,
+        swap_info.oracle_a,
+        swap_info.oracle_b,
+        swap_info.pool_mint,
+        SwapData {
+            amount_in,
+            minimum_amount_out: 0,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        },
+    )
+    .unwrap();
+    if let Some(market) = market {
+        instruction = append_openbook_market(instruction, market.pubkey, market.bids, market.asks);
+    }
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &context.sol_user_account,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                amount_in,
+            )
+            .unwrap(),
+            instruction,
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.user_account_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+
+    let market = context.market.pubkey;
+    set_market(&mut context, market).await.unwrap();
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap.openbook_market, market);
+    assert_eq!(swap.pool_state.market_price, Decimal::from(20u64));
+
+    // the oracles are stale and the twap is closed, the mid of the book
+    // prices the pool
+    let market = context.market;
+    sell_base(&mut context, Some(&market)).await.unwrap();
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap.pool_state.market_price, Decimal::from(25u64));
+
+    // and removed
+    set_market(&mut context, Pubkey::default()).await.unwrap();
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap.openbook_market, Pubkey::default());
+}
+
+#[tokio::test]
+async fn test_incorrect_market() {
+    let mut context = setup().await;
+
+    let market = context.market.pubkey;
+    set_market(&mut context, market).await.unwrap();
+
+    // the market of the pool is required once set
+    assert_eq!(
+        sell_base(&mut context, None).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::IncorrectOracleAccount as u32)
+        )
+    );
+
+    // a market other than the one of the pool
+    let other_market = context.other_market;
+    assert_eq!(
+        sell_base(&mut context, Some(&other_market))
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::IncorrectOracleAccount as u32)
+        )
+    );
+
+    // the bids of another market
+    let market = TestMarket {
+        bids: context.other_market.bids,
+        ..context.market
+    };
+    assert_eq!(
+        sell_base(&mut context, Some(&market)).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::IncorrectOracleAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_market_of_other_tokens() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 10_000_000_000,
+            token_b_amount: 200_000_000_000,
+            is_open_twap: false,
+            oracle_a: Pubkey::new_unique(),
+            oracle_b: Pubkey::new_unique(),
+            market_price: Decimal::from(20u64),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    // quoting token a in token b
+    let market = add_market(
+        &mut test,
+        &srm_mint.pubkey,
+        &spl_token::native_mint::id(),
+        &[1],
+        &[2],
+    );
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_openbook_market(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            market.pubkey,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectMint as u32)
+        )
+    );
+}
@@ -0,0 +1,255 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{poke, set_poke_reward},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    sysvar::clock::Clock,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const POKE_REWARD: u64 = 1_000;
+const MIN_POKE_INTERVAL: i64 = 600;
+
+struct TestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    keeper_account: Pubkey,
+}
+
+async fn setup(is_open_twap: bool) -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+    let keeper_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        Keypair::new().pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        context,
+        swap_config,
+        swap_info,
+        keeper_account,
+    }
+}
+
+async fn process_instruction(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn set_reward(test_context: &mut TestContext) {
+    let instruction = set_poke_reward(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        test_context.swap_config.admin.pubkey(),
+        POKE_REWARD,
+        MIN_POKE_INTERVAL,
+    )
+    .unwrap();
+    process_instruction(
+        &mut test_context.context,
+        instruction,
+        &[&test_context.swap_config.admin],
+    )
+    .await
+    .unwrap();
+}
+
+async fn process_poke(test_context: &mut TestContext) -> Result<(), TransactionError> {
+    let instruction = poke(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        test_context.swap_info.pubkey,
+        test_context.swap_config.market_authority,
+        test_context.keeper_account,
+        test_context.swap_config.deltafi_mint,
+    )
+    .unwrap();
+    process_instruction(&mut test_context.context, instruction, &[]).await
+}
+
+// a new slot for the same poke not to be rejected as already processed
+async fn warp_clock(context: &mut ProgramTestContext, seconds: i64) {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.warp_to_slot(clock.slot + 1).unwrap();
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    context.set_sysvar(&clock);
+}
+
+#[tokio::test]
+async fn test_poke_rewards_keeper() {
+    let mut test_context = setup(true).await;
+    set_reward(&mut test_context).await;
+
+    process_poke(&mut test_context).await.unwrap();
+    let swap_info = test_context
+        .swap_info
+        .get_state(&mut test_context.context.banks_client)
+        .await;
+    let clock: Clock = test_context
+        .context
+        .banks_client
+        .get_sysvar()
+        .await
+        .unwrap();
+    assert_eq!(swap_info.block_timestamp_last, clock.unix_timestamp as u64);
+    assert!(swap_info.cumulative_ticks > 0);
+    assert!(swap_info.base_price_cumulative_last > Decimal::zero());
+    assert_eq!(
+        get_token_balance(
+            &mut test_context.context.banks_client,
+            test_context.keeper_account
+        )
+        .await,
+        POKE_REWARD
+    );
+
+    // the twap still moves within the min poke interval, without reward
+    warp_clock(&mut test_context.context, MIN_POKE_INTERVAL / 2).await;
+    process_poke(&mut test_context).await.unwrap();
+    let poked_swap_info = test_context
+        .swap_info
+        .get_state(&mut test_context.context.banks_client)
+        .await;
+    assert!(
+        poked_swap_info.cumulative_ticks
+            >= swap_info.cumulative_ticks + (MIN_POKE_INTERVAL / 2) as u64
+    );
+    assert_eq!(
+        get_token_balance(
+            &mut test_context.context.banks_client,
+            test_context.keeper_account
+        )
+        .await,
+        POKE_REWARD
+    );
+
+    warp_clock(&mut test_context.context, MIN_POKE_INTERVAL).await;
+    process_poke(&mut test_context).await.unwrap();
+    assert_eq!(
+        get_token_balance(
+            &mut test_context.context.banks_client,
+            test_context.keeper_account
+        )
+        .await,
+        2 * POKE_REWARD
+    );
+}
+
+#[tokio::test]
+async fn test_poke_without_reward() {
+    let mut test_context = setup(true).await;
+
+    process_poke(&mut test_context).await.unwrap();
+    let swap_info = test_context
+        .swap_info
+        .get_state(&mut test_context.context.banks_client)
+        .await;
+    assert!(swap_info.cumulative_ticks > 0);
+    assert_eq!(
+        get_token_balance(
+            &mut test_context.context.banks_client,
+            test_context.keeper_account
+        )
+        .await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_poke_twap_not_open() {
+    let mut test_context = setup(false).await;
+    set_reward(&mut test_context).await;
+
+    assert_eq!(
+        process_poke(&mut test_context).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::TwapNotOpen as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_set_poke_reward_not_admin() {
+    let mut test_context = setup(true).await;
+    let not_admin = Keypair::new();
+
+    let instruction = set_poke_reward(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        not_admin.pubkey(),
+        POKE_REWARD,
+        MIN_POKE_INTERVAL,
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(&mut test_context.context, instruction, &[&not_admin])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,195 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::set_new_fees,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::Fees,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const NEW_FEES: Fees = Fees {
+    admin_trade_fee_numerator: 1,
+    admin_trade_fee_denominator: 4,
+    admin_withdraw_fee_numerator: 1,
+    admin_withdraw_fee_denominator: 4,
+    trade_fee_numerator: 3,
+    trade_fee_denominator: 1_000,
+    withdraw_fee_numerator: 1,
+    withdraw_fee_denominator: 1_000,
+    min_trade_fee: 0,
+};
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+    }
+}
+
+async fn try_set_new_fees(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    swap_config: &TestSwapConfig,
+    swap_pubkey: Pubkey,
+    admin: &Keypair,
+    new_fees: Fees,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_new_fees(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_pubkey,
+            admin.pubkey(),
+            new_fees,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer, admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_set_new_fees() {
+    let mut context = setup().await;
+    let config = context
+        .swap_config
+        .get_state(&mut context.banks_client)
+        .await;
+
+    try_set_new_fees(
+        &mut context.banks_client,
+        &context.payer,
+        &context.swap_config,
+        context.swap_info.pubkey,
+        &context.swap_config.admin,
+        NEW_FEES,
+    )
+    .await
+    .unwrap();
+
+    // only the pool is retuned
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap.fees, NEW_FEES);
+    assert_eq!(
+        context
+            .swap_config
+            .get_state(&mut context.banks_client)
+            .await
+            .fees,
+        config.fees
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_fees() {
+    let mut context = setup().await;
+
+    for new_fees in [
+        Fees {
+            trade_fee_numerator: 1_000,
+            ..NEW_FEES
+        },
+        Fees {
+            withdraw_fee_denominator: 0,
+            ..NEW_FEES
+        },
+        Fees {
+            admin_trade_fee_numerator: 5,
+            ..NEW_FEES
+        },
+    ] {
+        assert_eq!(
+            try_set_new_fees(
+                &mut context.banks_client,
+                &context.payer,
+                &context.swap_config,
+                context.swap_info.pubkey,
+                &context.swap_config.admin,
+                new_fees,
+            )
+            .await
+            .unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(SwapError::InvalidInput as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_set_new_fees(
+            &mut context.banks_client,
+            &context.payer,
+            &context.swap_config,
+            context.swap_info.pubkey,
+            &Keypair::new(),
+            NEW_FEES,
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
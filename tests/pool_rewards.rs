@@ -0,0 +1,187 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::set_new_rewards,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::Rewards,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const NEW_REWARDS: Rewards = Rewards {
+    trade_reward_numerator: 3,
+    trade_reward_denominator: 2,
+    trade_reward_cap: 50_000,
+    liquidity_reward_numerator: 1,
+    liquidity_reward_denominator: 100,
+};
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+    }
+}
+
+async fn try_set_new_rewards(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    swap_config: &TestSwapConfig,
+    swap_pubkey: Pubkey,
+    admin: &Keypair,
+    new_rewards: Rewards,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_new_rewards(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_pubkey,
+            admin.pubkey(),
+            new_rewards,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer, admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_set_new_rewards() {
+    let mut context = setup().await;
+    let config = context
+        .swap_config
+        .get_state(&mut context.banks_client)
+        .await;
+
+    try_set_new_rewards(
+        &mut context.banks_client,
+        &context.payer,
+        &context.swap_config,
+        context.swap_info.pubkey,
+        &context.swap_config.admin,
+        NEW_REWARDS,
+    )
+    .await
+    .unwrap();
+
+    // only the pool is retuned
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap.rewards, NEW_REWARDS);
+    assert_eq!(
+        context
+            .swap_config
+            .get_state(&mut context.banks_client)
+            .await
+            .rewards,
+        config.rewards
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_rewards() {
+    let mut context = setup().await;
+
+    for new_rewards in [
+        Rewards {
+            trade_reward_denominator: 0,
+            ..NEW_REWARDS
+        },
+        Rewards {
+            liquidity_reward_denominator: 0,
+            ..NEW_REWARDS
+        },
+    ] {
+        assert_eq!(
+            try_set_new_rewards(
+                &mut context.banks_client,
+                &context.payer,
+                &context.swap_config,
+                context.swap_info.pubkey,
+                &context.swap_config.admin,
+                new_rewards,
+            )
+            .await
+            .unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(SwapError::InvalidInput as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_set_new_rewards(
+            &mut context.banks_client,
+            &context.payer,
+            &context.swap_config,
+            context.swap_info.pubkey,
+            &Keypair::new(),
+            NEW_REWARDS,
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,220 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::init_position_page,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{
+        find_liquidity_provider_address, find_position_page_address, LiquidityPosition,
+        LiquidityPositionPage, LiquidityProvider, MAX_LIQUIDITY_POSITIONS,
+    },
+};
+
+use solana_program::{instruction::InstructionError, program_pack::Pack, system_program};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+fn add_funded_owner(test: &mut ProgramTest) -> Keypair {
+    let owner = Keypair::new();
+    test.add_account(
+        owner.pubkey(),
+        Account::new(LAMPORTS_TO_SOL, 0, &system_program::id()),
+    );
+    owner
+}
+
+fn add_full_liquidity_provider(
+    test: &mut ProgramTest,
+    user_account_owner: &Keypair,
+) -> TestLiquidityProvider {
+    let (liquidity_provider_pubkey, _) =
+        find_liquidity_provider_address(&deltafi_swap::id(), &user_account_owner.pubkey());
+    let positions: Vec<LiquidityPosition> = (0..MAX_LIQUIDITY_POSITIONS)
+        .map(|_| LiquidityPosition {
+            pool: Pubkey::new_unique(),
+            liquidity_amount: 1_000,
+            ..LiquidityPosition::default()
+        })
+        .collect();
+    test.add_packable_account(
+        liquidity_provider_pubkey,
+        u32::MAX as u64,
+        &LiquidityProvider::new(user_account_owner.pubkey(), positions.clone()),
+        &deltafi_swap::id(),
+    );
+
+    TestLiquidityProvider {
+        pubkey: liquidity_provider_pubkey,
+        owner: user_account_owner.pubkey(),
+        positions,
+        position_pages: vec![],
+    }
+}
+
+async fn init_page(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    user_account_owner: &Keypair,
+    page_index: u8,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            init_position_page(deltafi_swap::id(), user_account_owner.pubkey(), page_index)
+                .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer, user_account_owner], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+async fn get_page(banks_client: &mut BanksClient, pubkey: Pubkey) -> LiquidityPositionPage {
+    let account = banks_client.get_account(pubkey).await.unwrap().unwrap();
+    LiquidityPositionPage::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_init_chain() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let liquidity_owner = add_funded_owner(&mut test);
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    // pages are chained from index 0
+    assert_eq!(
+        init_page(&mut banks_client, &payer, &liquidity_owner, 1)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidAccountOwner as u32)
+        )
+    );
+
+    init_page(&mut banks_client, &payer, &liquidity_owner, 0)
+        .await
+        .unwrap();
+    init_page(&mut banks_client, &payer, &liquidity_owner, 1)
+        .await
+        .unwrap();
+
+    let (page_0_pubkey, _) =
+        find_position_page_address(&deltafi_swap::id(), &liquidity_provider.pubkey, 0);
+    let (page_1_pubkey, _) =
+        find_position_page_address(&deltafi_swap::id(), &liquidity_provider.pubkey, 1);
+    let page_0 = get_page(&mut banks_client, page_0_pubkey).await;
+    let page_1 = get_page(&mut banks_client, page_1_pubkey).await;
+    assert_eq!(page_0.liquidity_provider, liquidity_provider.pubkey);
+    assert_eq!(page_0.next_page, page_1_pubkey);
+    assert_eq!(page_1.page_index, 1);
+    assert!(page_1.is_last());
+}
+
+#[tokio::test]
+async fn test_deposit_into_page() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_owner = add_funded_owner(&mut test);
+    let mut liquidity_provider = add_full_liquidity_provider(&mut test, &liquidity_owner);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    init_page(&mut banks_client, &payer, &liquidity_owner, 0)
+        .await
+        .unwrap();
+    let (page_pubkey, _) =
+        find_position_page_address(&deltafi_swap::id(), &liquidity_provider.pubkey, 0);
+    liquidity_provider.position_pages.push(page_pubkey);
+
+    let sol_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        liquidity_owner.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_info.pool_mint,
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    swap_info
+        .deposit(
+            &mut banks_client,
+            &liquidity_provider,
+            &liquidity_owner,
+            sol_deposit_account,
+            srm_deposit_account,
+            pool_token_account,
+            8_000_000_000,
+            160_000_000_000,
+            0,
+            &payer,
+        )
+        .await;
+
+    let provider = liquidity_provider.get_state(&mut banks_client).await;
+    assert_eq!(provider.positions, liquidity_provider.positions);
+
+    let page = get_page(&mut banks_client, page_pubkey).await;
+    assert_eq!(page.positions.len(), 1);
+    assert_eq!(page.positions[0].pool, swap_info.pubkey);
+    assert_eq!(
+        page.positions[0].liquidity_amount,
+        get_token_balance(&mut banks_client, pool_token_account).await
+    );
+}
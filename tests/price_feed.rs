@@ -0,0 +1,288 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{init_price_feed, set_mid_price, sync},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_price_feed_address, PriceFeed},
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::Mint;
+use utils::*;
+
+fn add_pool_without_oracle(test: &mut ProgramTest, swap_config: &TestSwapConfig) -> TestSwapInfo {
+    let srm_mint = add_srm_mint(test);
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    add_swap_info(
+        test,
+        swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 10_000_000_000,
+            token_b_amount: 1_000_000_000_000,
+            is_open_twap: true,
+            oracle_a: Pubkey::new_unique(),
+            oracle_b: Pubkey::new_unique(),
+            market_price: Decimal::from(100u64),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    )
+}
+
+fn set_mid_price_instruction(
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    mid_price: Decimal,
+) -> Instruction {
+    set_mid_price(
+        deltafi_swap::id(),
+        swap_config.pubkey,
+        swap_info.pubkey,
+        swap_config.admin.pubkey(),
+        swap_info.oracle_a,
+        swap_info.oracle_b,
+        swap_info.pool_mint,
+        mid_price.to_scaled_val().unwrap(),
+    )
+    .unwrap()
+}
+
+async fn init_feed(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    swap_info: &TestSwapInfo,
+    pool_mint: Pubkey,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[init_price_feed(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            pool_mint,
+            payer.pubkey(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+/// Prices of the token-swap stored in its feed, along with the prices of the
+/// token-swap itself
+async fn feed_and_pool_prices(
+    banks_client: &mut BanksClient,
+    swap_info: &TestSwapInfo,
+) -> ((Decimal, Decimal, u64), (Decimal, Decimal)) {
+    let (price_feed_pubkey, _) = find_price_feed_address(&deltafi_swap::id(), &swap_info.pubkey);
+    let data = banks_client
+        .get_account(price_feed_pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let price_feed = PriceFeed::load(&data).unwrap();
+    assert_eq!(price_feed.swap, swap_info.pubkey);
+
+    let pool_mint = Mint::unpack(
+        &banks_client
+            .get_account(swap_info.pool_mint)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    let swap = swap_info.get_state(banks_client).await;
+    (
+        (
+            price_feed.mid_price(),
+            price_feed.lp_virtual_price(),
+            price_feed.last_update_slot(),
+        ),
+        (
+            swap.pool_state.clone().get_mid_price().unwrap(),
+            swap.lp_virtual_price(pool_mint.supply).unwrap(),
+        ),
+    )
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let mut context = test.start_with_context().await;
+    context.warp_to_slot(10).unwrap();
+
+    init_feed(
+        &mut context.banks_client,
+        &context.payer,
+        &swap_info,
+        swap_info.pool_mint,
+    )
+    .await
+    .unwrap();
+    let ((mid_price, lp_virtual_price, slot), pool_prices) =
+        feed_and_pool_prices(&mut context.banks_client, &swap_info).await;
+    assert_eq!((mid_price, lp_virtual_price), pool_prices);
+    assert!(lp_virtual_price > Decimal::zero());
+    assert_eq!(slot, 10);
+
+    // any instruction moving the pool refreshes the feed at the clock slot
+    context.warp_to_slot(20).unwrap();
+    let instruction = set_mid_price_instruction(&swap_config, &swap_info, Decimal::from(101u64));
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    transaction.sign(
+        &[&context.payer, &swap_config.admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let ((feed_mid_price, lp_virtual_price, slot), pool_prices) =
+        feed_and_pool_prices(&mut context.banks_client, &swap_info).await;
+    assert_eq!((feed_mid_price, lp_virtual_price), pool_prices);
+    assert_ne!(feed_mid_price, mid_price);
+    assert_eq!(slot, 20);
+
+    context.warp_to_slot(30).unwrap();
+    let instruction = sync(
+        deltafi_swap::id(),
+        swap_info.pubkey,
+        swap_info.token_a,
+        swap_info.token_b,
+        swap_info.pool_mint,
+    )
+    .unwrap();
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    transaction.sign(&[&context.payer], context.last_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let ((mid_price, lp_virtual_price, slot), pool_prices) =
+        feed_and_pool_prices(&mut context.banks_client, &swap_info).await;
+    assert_eq!((mid_price, lp_virtual_price), pool_prices);
+    assert_eq!(mid_price, feed_mid_price);
+    assert_eq!(slot, 30);
+}
+
+#[tokio::test]
+async fn test_already_initialized() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    init_feed(&mut banks_client, &payer, &swap_info, swap_info.pool_mint)
+        .await
+        .unwrap();
+    // a new blockhash, so that the second transaction is not a duplicate
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    assert_eq!(
+        init_feed(&mut banks_client, &payer, &swap_info, swap_info.pool_mint)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::AlreadyInUse as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_incorrect_mint() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    assert_eq!(
+        init_feed(
+            &mut banks_client,
+            &payer,
+            &swap_info,
+            swap_info.token_b_mint
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectMint as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_feed_of_other_swap() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let other_swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    init_feed(
+        &mut banks_client,
+        &payer,
+        &other_swap_info,
+        other_swap_info.pool_mint,
+    )
+    .await
+    .unwrap();
+
+    // the feed of the other token-swap can not stand for the feed of this one
+    let mut instruction =
+        set_mid_price_instruction(&swap_config, &swap_info, Decimal::from(101u64));
+    instruction.accounts[7].pubkey =
+        find_price_feed_address(&deltafi_swap::id(), &other_swap_info.pubkey).0;
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidProgramAddress as u32)
+        )
+    );
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use utils::*;
+
+const ORACLE_SLOT: u64 = 100;
+const MID_PRICE: u64 = 20;
+
+/// Initialize a SOL/SRM pool priced by mock oracles valid at the slot, returns
+/// the market price of the pool along with the oracle price
+async fn init_pool(valid_slot: u64) -> (Decimal, Decimal) {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    // SOL at $150 and SRM at $7
+    let sol_oracle = add_pyth_oracle(&mut test, 150_000_000, -6, valid_slot);
+    let srm_oracle = add_pyth_oracle(&mut test, 7, 0, valid_slot);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let mut context = test.start_with_context().await;
+    context.warp_to_slot(ORACLE_SLOT).unwrap();
+    let (banks_client, payer) = (&mut context.banks_client, &context.payer);
+
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        banks_client,
+        spl_token::native_mint::id(),
+        None,
+        payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        payer,
+        user_accounts_owner.pubkey(),
+        800_000_000_000,
+    )
+    .await;
+
+    let admin_fee_accounts = Keypair::new();
+    let sol_admin_account = create_and_mint_to_token_account(
+        banks_client,
+        spl_token::native_mint::id(),
+        None,
+        payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+
+    let test_swap_info = TestSwapInfo::init(
+        banks_client,
+        &swap_config,
+        &sol_oracle,
+        &srm_oracle,
+        spl_token::native_mint::id(),
+        srm_mint.pubkey,
+        sol_user_account,
+        srm_user_account,
+        sol_admin_account,
+        srm_admin_account,
+        &user_accounts_owner,
+        payer,
+        &SwapInitArgs {
+            mid_price: Decimal::from(MID_PRICE).to_scaled_val().unwrap(),
+            slope: Decimal::one()
+                .try_div(2)
+                .unwrap()
+                .to_scaled_val()
+                .unwrap()
+                .try_into()
+                .unwrap(),
+            is_open_twap: true,
+            twap_window_secs: 3_600,
+        },
+    )
+    .await;
+
+    let swap_info = test_swap_info.get_state(banks_client).await;
+    (
+        swap_info.pool_state.market_price,
+        sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_fresh_oracle_price() {
+    let (market_price, oracle_price) = init_pool(ORACLE_SLOT).await;
+    assert_eq!(market_price, oracle_price);
+}
+
+#[tokio::test]
+async fn test_stale_oracle_price() {
+    // the oracle prices are stale 5 slots after their valid slot
+    let (market_price, oracle_price) = init_pool(ORACLE_SLOT - 5).await;
+    assert_ne!(market_price, oracle_price);
+    assert_eq!(market_price, Decimal::from(MID_PRICE));
+}
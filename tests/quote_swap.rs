@@ -0,0 +1,392 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{append_pool_mint, quote_swap, swap, QuoteSwapData, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program::{get_return_data, invoke},
+    program_error::ProgramError,
+    program_pack::Pack,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::{instruction::approve, state::Mint};
+use utils::*;
+
+/// Forwards the instruction data to the swap program with all but the first
+/// account and the swap program, then stores the returned quote in the first
+/// account.
+fn process_quote_caller(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let (quote_info, swap_accounts) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    invoke(
+        &Instruction {
+            program_id: deltafi_swap::id(),
+            accounts: swap_accounts
+                .iter()
+                .filter(|info| *info.key != deltafi_swap::id())
+                .map(|info| AccountMeta::new_readonly(*info.key, false))
+                .collect(),
+            data: input.to_vec(),
+        },
+        swap_accounts,
+    )?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == deltafi_swap::id() => {
+            quote_info.data.borrow_mut()[..data.len()].copy_from_slice(&data);
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    quote_caller_program: Pubkey,
+    quote_account: Pubkey,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let quote_caller_program = Pubkey::new_unique();
+    test.add_program(
+        "quote_caller",
+        quote_caller_program,
+        processor!(process_quote_caller),
+    );
+    let quote_account = Pubkey::new_unique();
+    test.add_account(
+        quote_account,
+        Account {
+            lamports: u32::MAX as u64,
+            data: vec![0; 24],
+            owner: quote_caller_program,
+            ..Account::default()
+        },
+    );
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        quote_caller_program,
+        quote_account,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+fn quote_through_caller(context: &TestContext, amount_in: u64) -> Instruction {
+    let mut instruction = quote_swap(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        context.swap_info.oracle_a,
+        context.swap_info.oracle_b,
+        QuoteSwapData {
+            amount_in,
+            swap_direction: SwapDirection::SellBase,
+        },
+    )
+    .unwrap();
+    instruction.program_id = context.quote_caller_program;
+    instruction
+        .accounts
+        .insert(0, AccountMeta::new(context.quote_account, false));
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(deltafi_swap::id(), false));
+    instruction
+}
+
+async fn get_quote(context: &mut TestContext) -> u64 {
+    let data = context
+        .banks_client
+        .get_account(context.quote_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    u64::from_le_bytes(data[..8].try_into().unwrap())
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+    let amount_in = 1_000_000_000;
+    let swap_before = context.swap_info.get_state(&mut context.banks_client).await;
+
+    let user_transfer_authority = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            quote_through_caller(&context, amount_in),
+            approve(
+                &spl_token::id(),
+                &context.sol_user_account,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                amount_in,
+            )
+            .unwrap(),
+            swap(
+                deltafi_swap::id(),
+                context.swap_config.pubkey,
+                context.swap_info.pubkey,
+                context.swap_config.market_authority,
+                context.swap_info.authority,
+                user_transfer_authority.pubkey(),
+                context.sol_user_account,
+                context.swap_info.token_a,
+                context.swap_info.token_b,
+                context.srm_user_account,
+                context.deltafi_user_account,
+                context.swap_config.deltafi_mint,
+                context.swap_info.admin_fee_b_key,
+                context.swap_info.oracle_a,
+                context.swap_info.oracle_b,
+                context.swap_info.pool_mint,
+                SwapData {
+                    amount_in,
+                    minimum_amount_out: 0,
+                    swap_direction: SwapDirection::SellBase,
+                    max_price_impact_bps: 0,
+                },
+            )
+            .unwrap(),
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.user_account_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let amount_out = get_quote(&mut context).await;
+    assert!(amount_out > 0);
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.srm_user_account).await,
+        amount_out
+    );
+    // the quote alone leaves the pool untouched, the swap moved it
+    assert_ne!(
+        context.swap_info.get_state(&mut context.banks_client).await,
+        swap_before
+    );
+}
+
+#[tokio::test]
+async fn test_lp_virtual_price() {
+    let mut context = setup().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[append_pool_mint(
+            quote_through_caller(&context, 1_000_000_000),
+            context.swap_info.pool_mint,
+        )],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let data = context
+        .banks_client
+        .get_account(context.quote_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let lp_virtual_price =
+        Decimal::from_scaled_val(u128::from_le_bytes(data[8..24].try_into().unwrap()));
+    let pool_mint = Mint::unpack(
+        &context
+            .banks_client
+            .get_account(context.swap_info.pool_mint)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    // the oracle price matches the stored market price
+    let swap_info = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(
+        lp_virtual_price,
+        swap_info.lp_virtual_price(pool_mint.supply).unwrap()
+    );
+    assert!(get_quote(&mut context).await > 0);
+
+    // any other mint is rejected
+    let mut transaction = Transaction::new_with_payer(
+        &[append_pool_mint(
+            quote_through_caller(&context, 1_000_000_000),
+            context.swap_info.token_b_mint,
+        )],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectMint as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_no_state_change() {
+    let mut context = setup().await;
+    let swap_before = context.swap_info.get_state(&mut context.banks_client).await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[quote_through_caller(&context, 1_000_000_000)],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    assert!(get_quote(&mut context).await > 0);
+    assert_eq!(
+        context.swap_info.get_state(&mut context.banks_client).await,
+        swap_before
+    );
+}
+
+#[tokio::test]
+async fn test_insufficient_funds() {
+    let mut context = setup().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[quote_through_caller(&context, 42_000_000_001)],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InsufficientFunds as u32)
+        )
+    );
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    curve::Multiplier,
+    error::SwapError,
+    instruction::recalibrate,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    srm_mint: TestMint,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        srm_mint,
+    }
+}
+
+async fn try_recalibrate(
+    context: &mut TestContext,
+    admin: Option<&Keypair>,
+) -> Result<(), TransactionError> {
+    let admin = admin.unwrap_or(&context.swap_config.admin);
+    let mut transaction = Transaction::new_with_payer(
+        &[recalibrate(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_info.pubkey,
+            admin.pubkey(),
+            context.swap_info.token_a,
+            context.swap_info.token_b,
+            context.swap_info.oracle_a,
+            context.swap_info.oracle_b,
+            context.swap_info.pool_mint,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+
+    // tokens sent straight to the swap account leave the reserve behind
+    let token_b = context.swap_info.token_b;
+    mint_to(
+        &mut context.banks_client,
+        context.srm_mint.pubkey,
+        &context.payer,
+        token_b,
+        &context.srm_mint.authority,
+        5_000_000_000,
+    )
+    .await;
+
+    try_recalibrate(&mut context, None).await.unwrap();
+
+    let balance_a = get_token_balance(&mut context.banks_client, context.swap_info.token_a).await;
+    let balance_b = get_token_balance(&mut context.banks_client, token_b).await;
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap.pool_state.base_reserve, Decimal::from(balance_a));
+    assert_eq!(swap.pool_state.quote_reserve, Decimal::from(balance_b));
+    assert_eq!(swap.pool_state.base_target, Decimal::from(balance_a));
+    assert_eq!(swap.pool_state.quote_target, Decimal::from(balance_b));
+    assert_eq!(swap.pool_state.multiplier, Multiplier::One);
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_recalibrate(&mut context, Some(&Keypair::new())).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        ))
+    );
+}
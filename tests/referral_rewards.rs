@@ -0,0 +1,328 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        append_referrer_destination, claim_liquidity_rewards,
+        init_liquidity_provider_with_referrer, set_referral_reward,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_liquidity_provider_address, LiquidityPosition, LiquidityProvider},
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    system_instruction,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const REWARDS_OWED: u64 = 1_000_000;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    referrer: Keypair,
+    liquidity_provider: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    let referrer = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (liquidity_provider, _) =
+        find_liquidity_provider_address(&deltafi_swap::id(), &user_account_owner.pubkey());
+    test.add_packable_account(
+        liquidity_provider,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: user_account_owner.pubkey(),
+            delegate: Pubkey::default(),
+            referrer: referrer.pubkey(),
+            positions: vec![LiquidityPosition {
+                pool: swap_info.pubkey,
+                liquidity_amount: 2_000_000_000,
+                rewards_owed: REWARDS_OWED,
+                ..LiquidityPosition::default()
+            }],
+        },
+        &deltafi_swap::id(),
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        referrer,
+        liquidity_provider,
+    }
+}
+
+async fn process_instruction(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn set_bps(context: &mut TestContext, referral_reward_bps: u64) {
+    let instruction = set_referral_reward(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_config.admin.pubkey(),
+        referral_reward_bps,
+    )
+    .unwrap();
+    process_instruction(
+        &mut context.banks_client,
+        &context.payer,
+        instruction,
+        &[&context.swap_config.admin],
+    )
+    .await
+    .unwrap();
+}
+
+async fn create_deltafi_account(context: &mut TestContext, owner: Pubkey) -> Pubkey {
+    create_and_mint_to_token_account(
+        &mut context.banks_client,
+        context.swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        owner,
+        0,
+    )
+    .await
+}
+
+// claims the rewards owed, minting the referral reward to the referrer destination if any
+async fn claim(
+    context: &mut TestContext,
+    claim_destination: Pubkey,
+    referrer_destination: Option<Pubkey>,
+) -> Result<(), TransactionError> {
+    let mut instruction = claim_liquidity_rewards(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.market_authority,
+        context.liquidity_provider,
+        context.user_account_owner.pubkey(),
+        claim_destination,
+        context.swap_config.deltafi_mint,
+    )
+    .unwrap();
+    if let Some(referrer_destination) = referrer_destination {
+        instruction = append_referrer_destination(instruction, referrer_destination);
+    }
+    process_instruction(
+        &mut context.banks_client,
+        &context.payer,
+        instruction,
+        &[&context.user_account_owner],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_referral_reward() {
+    let mut context = setup().await;
+    set_bps(&mut context, 1_000).await;
+    let owner = context.user_account_owner.pubkey();
+    let referrer = context.referrer.pubkey();
+    assert_eq!(
+        context
+            .swap_config
+            .get_state(&mut context.banks_client)
+            .await
+            .referral_reward_bps,
+        1_000
+    );
+
+    let claim_destination = create_deltafi_account(&mut context, owner).await;
+    let referrer_destination = create_deltafi_account(&mut context, referrer).await;
+    claim(&mut context, claim_destination, Some(referrer_destination))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, claim_destination).await,
+        REWARDS_OWED
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, referrer_destination).await,
+        REWARDS_OWED / 10
+    );
+}
+
+#[tokio::test]
+async fn test_missing_referrer_destination() {
+    let mut context = setup().await;
+    set_bps(&mut context, 1_000).await;
+    let owner = context.user_account_owner.pubkey();
+
+    let claim_destination = create_deltafi_account(&mut context, owner).await;
+    assert_eq!(
+        claim(&mut context, claim_destination, None)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_referrer_destination() {
+    let mut context = setup().await;
+    set_bps(&mut context, 1_000).await;
+    let owner = context.user_account_owner.pubkey();
+
+    // the owner can not redirect the referral reward to itself
+    let claim_destination = create_deltafi_account(&mut context, owner).await;
+    let other_destination = create_deltafi_account(&mut context, owner).await;
+    assert_eq!(
+        claim(&mut context, claim_destination, Some(other_destination))
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidReferrer as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_init_with_referrer() {
+    let mut context = setup().await;
+    let owner = Keypair::new();
+    let referrer = context.referrer.pubkey();
+
+    // the owner pays the rent of its liquidity provider
+    let instruction =
+        system_instruction::transfer(&context.payer.pubkey(), &owner.pubkey(), 1_000_000_000);
+    process_instruction(&mut context.banks_client, &context.payer, instruction, &[])
+        .await
+        .unwrap();
+
+    let instruction =
+        init_liquidity_provider_with_referrer(deltafi_swap::id(), owner.pubkey(), owner.pubkey())
+            .unwrap();
+    assert_eq!(
+        process_instruction(
+            &mut context.banks_client,
+            &context.payer,
+            instruction,
+            &[&owner]
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidReferrer as u32)
+        )
+    );
+
+    let instruction =
+        init_liquidity_provider_with_referrer(deltafi_swap::id(), owner.pubkey(), referrer)
+            .unwrap();
+    process_instruction(
+        &mut context.banks_client,
+        &context.payer,
+        instruction,
+        &[&owner],
+    )
+    .await
+    .unwrap();
+
+    let (liquidity_provider, _) =
+        find_liquidity_provider_address(&deltafi_swap::id(), &owner.pubkey());
+    let account = context
+        .banks_client
+        .get_account(liquidity_provider)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        LiquidityProvider::unpack(&account.data).unwrap().referrer,
+        referrer
+    );
+}
+
+#[tokio::test]
+async fn test_set_referral_reward_not_admin() {
+    let mut context = setup().await;
+    let not_admin = Keypair::new();
+
+    let instruction = set_referral_reward(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        not_admin.pubkey(),
+        1_000,
+    )
+    .unwrap();
+    assert_eq!(
+        process_instruction(
+            &mut context.banks_client,
+            &context.payer,
+            instruction,
+            &[&not_admin]
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
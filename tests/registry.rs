@@ -0,0 +1,204 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::init_registry_page,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_registry_page_address, RegistryEntry, RegistryPage},
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+async fn get_registry_page(
+    banks_client: &mut BanksClient,
+    config_pubkey: Pubkey,
+    page_index: u8,
+) -> Vec<u8> {
+    let (registry_page_pubkey, _) =
+        find_registry_page_address(&deltafi_swap::id(), &config_pubkey, page_index);
+    banks_client
+        .get_account(registry_page_pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
+
+async fn try_init_registry_page(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    config_pubkey: Pubkey,
+    page_index: u8,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[init_registry_page(
+            deltafi_swap::id(),
+            config_pubkey,
+            payer.pubkey(),
+            page_index,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let user_accounts_owner = Keypair::new();
+    let admin_fee_accounts = Keypair::new();
+    let mut pools = vec![];
+    for _ in 0..2 {
+        let sol_user_account = create_and_mint_to_token_account(
+            &mut banks_client,
+            spl_token::native_mint::id(),
+            None,
+            &payer,
+            user_accounts_owner.pubkey(),
+            42_000_000_000,
+        )
+        .await;
+        let srm_user_account = create_and_mint_to_token_account(
+            &mut banks_client,
+            srm_mint.pubkey,
+            Some(&srm_mint.authority),
+            &payer,
+            user_accounts_owner.pubkey(),
+            800_000_000_000,
+        )
+        .await;
+        let sol_admin_account = create_and_mint_to_token_account(
+            &mut banks_client,
+            spl_token::native_mint::id(),
+            None,
+            &payer,
+            admin_fee_accounts.pubkey(),
+            0,
+        )
+        .await;
+        let srm_admin_account = create_and_mint_to_token_account(
+            &mut banks_client,
+            srm_mint.pubkey,
+            Some(&srm_mint.authority),
+            &payer,
+            admin_fee_accounts.pubkey(),
+            0,
+        )
+        .await;
+
+        let swap_info = TestSwapInfo::init(
+            &mut banks_client,
+            &swap_config,
+            &sol_oracle,
+            &srm_oracle,
+            spl_token::native_mint::id(),
+            srm_mint.pubkey,
+            sol_user_account,
+            srm_user_account,
+            sol_admin_account,
+            srm_admin_account,
+            &user_accounts_owner,
+            &payer,
+            &SwapInitArgs {
+                mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+                slope: Decimal::one()
+                    .try_div(2)
+                    .unwrap()
+                    .to_scaled_val()
+                    .unwrap()
+                    .try_into()
+                    .unwrap(),
+                is_open_twap: true,
+                twap_window_secs: 3_600,
+            },
+        )
+        .await;
+        pools.push(RegistryEntry {
+            swap: swap_info.pubkey,
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+        });
+    }
+
+    let data = get_registry_page(&mut banks_client, swap_config.pubkey, 0).await;
+    let registry_page = RegistryPage::load(&data).unwrap();
+    assert_eq!(registry_page.config, swap_config.pubkey);
+    assert_eq!(registry_page.page_index, 0);
+    assert_eq!(registry_page.entries(), &pools[..]);
+}
+
+#[tokio::test]
+async fn test_already_initialized() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let swap_config = add_swap_config(&mut test);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    try_init_registry_page(&mut banks_client, &payer, swap_config.pubkey, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        try_init_registry_page(&mut banks_client, &payer, swap_config.pubkey, 0)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::AlreadyInUse as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_previous_page_not_full() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let swap_config = add_swap_config(&mut test);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    // no page 0 yet
+    assert_eq!(
+        try_init_registry_page(&mut banks_client, &payer, swap_config.pubkey, 1)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidAccountOwner as u32)
+        )
+    );
+
+    try_init_registry_page(&mut banks_client, &payer, swap_config.pubkey, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        try_init_registry_page(&mut banks_client, &payer, swap_config.pubkey, 1)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidInput as u32)
+        )
+    );
+}
@@ -0,0 +1,278 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{relay_swap, swap, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    pyth,
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::{instruction::approve, state::Account as TokenAccount};
+use utils::*;
+
+const AMOUNT_IN: u64 = 1_000_000_000;
+const RELAYER_FEE: u64 = 5_000_000;
+
+/// Pyth price account last valid at the first slot, stale once the test
+/// warps past it
+fn add_stale_oracle(test: &mut ProgramTest) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    test.add_account(
+        pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data: pyth::test::price_data(20, 0, 0),
+            owner: pyth::id(),
+            ..Account::default()
+        },
+    );
+    pubkey
+}
+
+struct TestContext {
+    _program_context: ProgramTestContext,
+    banks_client: BanksClient,
+    relayer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    trader: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+    sol_relayer_account: Pubkey,
+}
+
+/// A pool of SOL and SRM, along with a trader holding SOL and no lamports and
+/// the SOL account of the relayer paid the relayer fee
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+    let oracle_a = add_stale_oracle(&mut test);
+    let oracle_b = add_stale_oracle(&mut test);
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 10_000_000_000,
+            token_b_amount: 200_000_000_000,
+            is_open_twap: false,
+            oracle_a,
+            oracle_b,
+            market_price: Decimal::from(20u64),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut program_context = test.start_with_context().await;
+    program_context.warp_to_slot(100).unwrap();
+    let mut banks_client = program_context.banks_client.clone();
+    let relayer = Keypair::from_bytes(&program_context.payer.to_bytes()).unwrap();
+
+    let trader = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &relayer,
+        trader.pubkey(),
+        AMOUNT_IN + RELAYER_FEE,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &relayer,
+        trader.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &relayer,
+        trader.pubkey(),
+        0,
+    )
+    .await;
+    let sol_relayer_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &relayer,
+        relayer.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        _program_context: program_context,
+        banks_client,
+        relayer,
+        swap_config,
+        swap_info,
+        trader,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+        sol_relayer_account,
+    }
+}
+
+/// Relayed swap selling the SOL of the trader, debited by the authority
+fn sell_base_instruction(context: &TestContext, user_transfer_authority: Pubkey) -> Instruction {
+    let swap_info = &context.swap_info;
+    relay_swap(
+        swap(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            swap_info.pubkey,
+            context.swap_config.market_authority,
+            swap_info.authority,
+            user_transfer_authority,
+            context.sol_user_account,
+            swap_info.token_a,
+            swap_info.token_b,
+            context.srm_user_account,
+            context.deltafi_user_account,
+            context.swap_config.deltafi_mint,
+            swap_info.admin_fee_b_key,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            swap_info.pool_mint,
+            SwapData {
+                amount_in: AMOUNT_IN,
+                minimum_amount_out: 0,
+                swap_direction: SwapDirection::SellBase,
+                max_price_impact_bps: 0,
+            },
+        )
+        .unwrap(),
+        context.relayer.pubkey(),
+        context.sol_relayer_account,
+        RELAYER_FEE,
+    )
+    .unwrap()
+}
+
+async fn send(
+    context: &mut TestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(instructions, Some(&context.relayer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(signers, recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+async fn token_amount(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
+    let account = banks_client.get_account(pubkey).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+
+    // the trader signs, the relayer pays
+    let instruction = sell_base_instruction(&context, context.trader.pubkey());
+    let relayer = Keypair::from_bytes(&context.relayer.to_bytes()).unwrap();
+    let trader = Keypair::from_bytes(&context.trader.to_bytes()).unwrap();
+    send(&mut context, &[instruction], &[&relayer, &trader])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        token_amount(&mut context.banks_client, context.sol_user_account).await,
+        0
+    );
+    assert!(token_amount(&mut context.banks_client, context.srm_user_account).await > 0);
+    // the relayer is paid the fee signed by the trader
+    assert_eq!(
+        token_amount(&mut context.banks_client, context.sol_relayer_account).await,
+        RELAYER_FEE
+    );
+    // the trader has never held lamports
+    assert!(context
+        .banks_client
+        .get_account(trader.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_delegate_authority() {
+    let mut context = setup().await;
+
+    // a delegate is not the owner of the account sold from
+    let delegate = Keypair::new();
+    let instructions = [
+        approve(
+            &spl_token::id(),
+            &context.sol_user_account,
+            &delegate.pubkey(),
+            &context.trader.pubkey(),
+            &[],
+            AMOUNT_IN + RELAYER_FEE,
+        )
+        .unwrap(),
+        sell_base_instruction(&context, delegate.pubkey()),
+    ];
+    let relayer = Keypair::from_bytes(&context.relayer.to_bytes()).unwrap();
+    let trader = Keypair::from_bytes(&context.trader.to_bytes()).unwrap();
+    assert_eq!(
+        send(&mut context, &instructions, &[&relayer, &trader, &delegate])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_missing_owner_signature() {
+    let mut context = setup().await;
+
+    // the relayer submits the swap of the trader alone
+    let mut instruction = sell_base_instruction(&context, context.trader.pubkey());
+    instruction.accounts[6].is_signer = false;
+    let relayer = Keypair::from_bytes(&context.relayer.to_bytes()).unwrap();
+    assert_eq!(
+        send(&mut context, &[instruction], &[&relayer])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
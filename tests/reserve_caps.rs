@@ -0,0 +1,300 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{append_position_pages, deposit, set_reserve_caps, DepositData},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    liquidity_provider: TestLiquidityProvider,
+    liquidity_owner: Keypair,
+    sol_deposit_account: Pubkey,
+    srm_deposit_account: Pubkey,
+    pool_token_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        liquidity_owner.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_info.pool_mint,
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        liquidity_provider,
+        liquidity_owner,
+        sol_deposit_account,
+        srm_deposit_account,
+        pool_token_account,
+    }
+}
+
+async fn set_caps(context: &mut TestContext, max_base_reserve: u64, max_quote_reserve: u64) {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_reserve_caps(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_info.pubkey,
+            context.swap_config.admin.pubkey(),
+            max_base_reserve,
+            max_quote_reserve,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.swap_config.admin],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+async fn try_deposit(
+    context: &mut TestContext,
+    token_a_amount: u64,
+    token_b_amount: u64,
+) -> Result<(), TransactionError> {
+    let user_transfer_authority = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &context.sol_deposit_account,
+                &user_transfer_authority.pubkey(),
+                &context.liquidity_owner.pubkey(),
+                &[],
+                token_a_amount,
+            )
+            .unwrap(),
+            approve(
+                &spl_token::id(),
+                &context.srm_deposit_account,
+                &user_transfer_authority.pubkey(),
+                &context.liquidity_owner.pubkey(),
+                &[],
+                token_b_amount,
+            )
+            .unwrap(),
+            append_position_pages(
+                deposit(
+                    deltafi_swap::id(),
+                    context.swap_info.pubkey,
+                    context.swap_info.authority,
+                    user_transfer_authority.pubkey(),
+                    context.sol_deposit_account,
+                    context.srm_deposit_account,
+                    context.swap_info.token_a,
+                    context.swap_info.token_b,
+                    context.swap_info.pool_mint,
+                    context.pool_token_account,
+                    context.liquidity_provider.pubkey,
+                    context.liquidity_provider.owner,
+                    context.swap_info.oracle_a,
+                    context.swap_info.oracle_b,
+                    DepositData {
+                        token_a_amount,
+                        token_b_amount,
+                        min_mint_amount: 0,
+                    },
+                )
+                .unwrap(),
+                context.liquidity_provider.position_pages.clone(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.liquidity_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_deposit_within_caps() {
+    let mut context = setup().await;
+
+    set_caps(&mut context, 50_000_000_000, 1_000_000_000_000).await;
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap.max_base_reserve, 50_000_000_000);
+    assert_eq!(swap.max_quote_reserve, 1_000_000_000_000);
+
+    try_deposit(&mut context, 2_000_000_000, 40_000_000_000)
+        .await
+        .unwrap();
+    assert!(get_token_balance(&mut context.banks_client, context.pool_token_account).await > 0);
+}
+
+#[tokio::test]
+async fn test_deposit_above_caps() {
+    let mut context = setup().await;
+
+    set_caps(&mut context, 43_000_000_000, 0).await;
+    assert_eq!(
+        try_deposit(&mut context, 2_000_000_000, 40_000_000_000)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(SwapError::ReserveCapExceeded as u32)
+        )
+    );
+
+    // a zero cap lifts the limit
+    set_caps(&mut context, 0, 0).await;
+    try_deposit(&mut context, 2_000_000_000, 40_000_000_000)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_deposit_imbalanced_above_caps() {
+    let mut context = setup().await;
+
+    set_caps(&mut context, 43_000_000_000, 0).await;
+    assert_eq!(
+        context
+            .swap_info
+            .deposit_imbalanced(
+                &mut context.banks_client,
+                &context.liquidity_provider,
+                &context.liquidity_owner,
+                context.sol_deposit_account,
+                context.srm_deposit_account,
+                context.pool_token_account,
+                8_000_000_000,
+                0,
+                0,
+                &context.payer,
+            )
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(SwapError::ReserveCapExceeded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut context = setup().await;
+    let fake_admin = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[set_reserve_caps(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_info.pubkey,
+            fake_admin.pubkey(),
+            1,
+            1,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &fake_admin], recent_blockhash);
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,220 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::{claim_liquidity_rewards, refresh_liquidity_obligation},
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+    state::{SwapInfo, MIN_CLAIM_PERIOD},
+};
+use solana_program::{instruction::Instruction, program_pack::Pack, sysvar::clock::Clock};
+use solana_program_test::*;
+use solana_sdk::{
+    account::AccountSharedData,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use utils::*;
+
+const LIQUIDITY_AMOUNT: u64 = 2_000_000_000;
+
+struct TestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    liquidity_provider: TestLiquidityProvider,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, LIQUIDITY_AMOUNT);
+
+    let context = test.start_with_context().await;
+
+    TestContext {
+        context,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        liquidity_provider,
+    }
+}
+
+async fn process_instruction(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&all_signers, recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+async fn refresh(test_context: &mut TestContext) {
+    process_instruction(
+        &mut test_context.context.banks_client,
+        &test_context.context.payer,
+        refresh_liquidity_obligation(
+            deltafi_swap::id(),
+            test_context.swap_info.pubkey,
+            test_context.swap_config.pubkey,
+            Pubkey::default(),
+        )
+        .unwrap(),
+        &[],
+    )
+    .await;
+}
+
+async fn warp_clock(context: &mut ProgramTestContext, seconds: i64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += seconds;
+    context.set_sysvar(&clock);
+}
+
+#[tokio::test]
+async fn test_accumulate_and_claim() {
+    let mut test_context = setup().await;
+
+    // the first accumulation only starts the period
+    refresh(&mut test_context).await;
+    let swap_info = test_context
+        .swap_info
+        .get_state(&mut test_context.context.banks_client)
+        .await;
+    assert_eq!(swap_info.reward_per_share, Decimal::zero());
+    assert!(swap_info.reward_last_update_ts > 0);
+
+    warp_clock(&mut test_context.context, MIN_CLAIM_PERIOD).await;
+    refresh(&mut test_context).await;
+    let swap_info = test_context
+        .swap_info
+        .get_state(&mut test_context.context.banks_client)
+        .await;
+    assert!(swap_info.reward_per_share > Decimal::zero());
+
+    // the position is only settled on claim
+    let liquidity_provider = test_context
+        .liquidity_provider
+        .get_state(&mut test_context.context.banks_client)
+        .await;
+    assert_eq!(liquidity_provider.positions[0].rewards_estimated, 0);
+    assert_eq!(liquidity_provider.positions[0].rewards_owed, 0);
+
+    let owner = test_context.user_account_owner.pubkey();
+    let deltafi_account = create_and_mint_to_token_account(
+        &mut test_context.context.banks_client,
+        test_context.swap_config.deltafi_mint,
+        None,
+        &test_context.context.payer,
+        owner,
+        0,
+    )
+    .await;
+    process_instruction(
+        &mut test_context.context.banks_client,
+        &test_context.context.payer,
+        claim_liquidity_rewards(
+            deltafi_swap::id(),
+            test_context.swap_config.pubkey,
+            test_context.swap_info.pubkey,
+            test_context.swap_config.market_authority,
+            test_context.liquidity_provider.pubkey,
+            owner,
+            deltafi_account,
+            test_context.swap_config.deltafi_mint,
+        )
+        .unwrap(),
+        &[&test_context.user_account_owner],
+    )
+    .await;
+
+    let expected_rewards = swap_info
+        .reward_per_share
+        .try_mul(LIQUIDITY_AMOUNT)
+        .unwrap()
+        .try_div(MIN_CLAIM_PERIOD as u64)
+        .unwrap()
+        .try_floor_u64()
+        .unwrap();
+    assert!(expected_rewards > 0);
+    assert_eq!(
+        get_token_balance(&mut test_context.context.banks_client, deltafi_account).await,
+        expected_rewards
+    );
+
+    let liquidity_provider = test_context
+        .liquidity_provider
+        .get_state(&mut test_context.context.banks_client)
+        .await;
+    assert_eq!(
+        liquidity_provider.positions[0].reward_per_share_paid,
+        swap_info.reward_per_share
+    );
+    assert_eq!(liquidity_provider.positions[0].rewards_owed, 0);
+}
+
+#[tokio::test]
+async fn test_no_rewards_before_price_refresh() {
+    let mut test_context = setup().await;
+
+    // pool never refreshed from a DELFI oracle
+    let swap_pubkey = test_context.swap_info.pubkey;
+    let mut account = test_context
+        .context
+        .banks_client
+        .get_account(swap_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut swap_info = SwapInfo::unpack(&account.data).unwrap();
+    swap_info.deltafi_price = Decimal::zero();
+    SwapInfo::pack(swap_info, &mut account.data).unwrap();
+    test_context
+        .context
+        .set_account(&swap_pubkey, &AccountSharedData::from(account));
+
+    refresh(&mut test_context).await;
+    warp_clock(&mut test_context.context, MIN_CLAIM_PERIOD).await;
+    refresh(&mut test_context).await;
+    let swap_info = test_context
+        .swap_info
+        .get_state(&mut test_context.context.banks_client)
+        .await;
+    assert_eq!(swap_info.reward_per_share, Decimal::zero());
+    assert!(swap_info.reward_last_update_ts > 0);
+}
@@ -0,0 +1,328 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{set_mid_price, set_mid_price_bounds},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::ConfigInfo,
+};
+use solana_program::{instruction::InstructionError, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+fn add_pool_without_oracle(test: &mut ProgramTest, swap_config: &TestSwapConfig) -> TestSwapInfo {
+    let srm_mint = add_srm_mint(test);
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    add_swap_info(
+        test,
+        swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 10_000_000_000,
+            token_b_amount: 1_000_000_000_000,
+            is_open_twap: true,
+            oracle_a: Pubkey::new_unique(),
+            oracle_b: Pubkey::new_unique(),
+            market_price: Decimal::from(100u64),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    // limit to track compute unit increase
+    test.set_compute_max_units(50_000);
+
+    let swap_config = add_swap_config(&mut test);
+    let swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let new_mid_price = Decimal::from(104u64);
+    let mut transaction = Transaction::new_with_payer(
+        &[set_mid_price(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            swap_info.pool_mint,
+            new_mid_price.to_scaled_val().unwrap(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+
+    let swap = swap_info.get_state(&mut banks_client).await;
+    assert_eq!(swap.pool_state.market_price, new_mid_price);
+    assert!(swap.last_mid_price_update_ts > 0);
+    assert_eq!(
+        swap.block_timestamp_last,
+        swap.last_mid_price_update_ts as u64
+    );
+    assert!(swap.base_price_cumulative_last > Decimal::zero());
+
+    // second update within the minimum interval is rejected
+    let mut transaction = Transaction::new_with_payer(
+        &[set_mid_price(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            swap_info.pool_mint,
+            Decimal::from(105u64).to_scaled_val().unwrap(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::MidPriceUpdateTooFrequent as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_deviation_exceeded() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_mid_price(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            swap_info.pool_mint,
+            Decimal::from(110u64).to_scaled_val().unwrap(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::MidPriceDeviationExceeded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let invalid_admin = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[set_mid_price(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            invalid_admin.pubkey(),
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            swap_info.pool_mint,
+            Decimal::from(101u64).to_scaled_val().unwrap(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &invalid_admin], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_incorrect_oracle_account() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_mid_price(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            Pubkey::new_unique(),
+            swap_info.oracle_b,
+            swap_info.pool_mint,
+            Decimal::from(101u64).to_scaled_val().unwrap(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectOracleAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_set_bounds() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let swap_info = add_pool_without_oracle(&mut test, &swap_config);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    // widen the deviation to let a 10% move through
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            set_mid_price_bounds(
+                deltafi_swap::id(),
+                swap_config.pubkey,
+                swap_config.admin.pubkey(),
+                1_000,
+                0,
+            )
+            .unwrap(),
+            set_mid_price(
+                deltafi_swap::id(),
+                swap_config.pubkey,
+                swap_info.pubkey,
+                swap_config.admin.pubkey(),
+                swap_info.oracle_a,
+                swap_info.oracle_b,
+                swap_info.pool_mint,
+                Decimal::from(110u64).to_scaled_val().unwrap(),
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+
+    let config_account = banks_client
+        .get_account(swap_config.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let config = ConfigInfo::unpack(&config_account.data).unwrap();
+    assert_eq!(config.max_mid_price_deviation_bps, 1_000);
+    assert_eq!(config.min_mid_price_update_interval, 0);
+    let swap = swap_info.get_state(&mut banks_client).await;
+    assert_eq!(swap.pool_state.market_price, Decimal::from(110u64));
+
+    // deviation above 100% is rejected
+    let mut transaction = Transaction::new_with_payer(
+        &[set_mid_price_bounds(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_config.admin.pubkey(),
+            10_001,
+            0,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidInput as u32)
+        )
+    );
+
+    let invalid_admin = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[set_mid_price_bounds(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            invalid_admin.pubkey(),
+            1_000,
+            0,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &invalid_admin], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,434 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        cancel_signed_orders, settle_signed_order, swap, verify_signed_order, SwapData,
+        SwapDirection,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+    pyth,
+    state::{
+        ed25519_instruction_data, find_order_authority_address, find_order_nonce_address,
+        OrderNonce, SignedOrder,
+    },
+};
+use solana_program::{
+    ed25519_program,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::{instruction::approve, state::Account as TokenAccount};
+use utils::*;
+
+const AMOUNT_IN: u64 = 1_000_000_000;
+
+/// Pyth price account last valid at the first slot, stale once the test
+/// warps past it
+fn add_stale_oracle(test: &mut ProgramTest) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    test.add_account(
+        pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data: pyth::test::price_data(20, 0, 0),
+            owner: pyth::id(),
+            ..Account::default()
+        },
+    );
+    pubkey
+}
+
+struct TestContext {
+    _program_context: ProgramTestContext,
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    maker: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+/// A pool of SOL and SRM, along with a maker holding SOL delegated to the
+/// order authority
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+    let oracle_a = add_stale_oracle(&mut test);
+    let oracle_b = add_stale_oracle(&mut test);
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 10_000_000_000,
+            token_b_amount: 200_000_000_000,
+            is_open_twap: false,
+            oracle_a,
+            oracle_b,
+            market_price: Decimal::from(20u64),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut program_context = test.start_with_context().await;
+    program_context.warp_to_slot(100).unwrap();
+    let mut banks_client = program_context.banks_client.clone();
+    let payer = Keypair::from_bytes(&program_context.payer.to_bytes()).unwrap();
+
+    let maker = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        maker.pubkey(),
+        2 * AMOUNT_IN,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        maker.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        maker.pubkey(),
+        0,
+    )
+    .await;
+
+    let (order_authority, _) = find_order_authority_address(&deltafi_swap::id());
+    let mut transaction = Transaction::new_with_payer(
+        &[approve(
+            &spl_token::id(),
+            &sol_user_account,
+            &order_authority,
+            &maker.pubkey(),
+            &[],
+            2 * AMOUNT_IN,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &maker], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    TestContext {
+        _program_context: program_context,
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        maker,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+fn sell_base_order(context: &TestContext, expiry: i64, nonce: u64) -> SignedOrder {
+    SignedOrder {
+        program_id: deltafi_swap::id(),
+        config: context.swap_config.pubkey,
+        maker: context.maker.pubkey(),
+        swap: context.swap_info.pubkey,
+        amount_in: AMOUNT_IN,
+        minimum_amount_out: 0,
+        swap_direction: SwapDirection::SellBase,
+        expiry,
+        nonce,
+    }
+}
+
+/// The instructions settling the order signed by the signer, the ed25519
+/// instruction verifying the signature of the signer
+fn settle_instructions(
+    context: &TestContext,
+    order: &SignedOrder,
+    signer: &Keypair,
+) -> Vec<Instruction> {
+    let swap_info = &context.swap_info;
+    let (order_authority, _) = find_order_authority_address(&deltafi_swap::id());
+    let signature = signer.sign_message(&order.pack());
+    let signature = signature.as_ref().try_into().unwrap();
+    let verify_instruction = if signer.pubkey() == order.maker {
+        verify_signed_order(order, signature)
+    } else {
+        Instruction {
+            program_id: ed25519_program::id(),
+            accounts: vec![],
+            data: ed25519_instruction_data(&signer.pubkey(), signature, &order.pack()),
+        }
+    };
+    vec![
+        verify_instruction,
+        settle_signed_order(
+            swap(
+                deltafi_swap::id(),
+                context.swap_config.pubkey,
+                swap_info.pubkey,
+                context.swap_config.market_authority,
+                swap_info.authority,
+                order_authority,
+                context.sol_user_account,
+                swap_info.token_a,
+                swap_info.token_b,
+                context.srm_user_account,
+                context.deltafi_user_account,
+                context.swap_config.deltafi_mint,
+                swap_info.admin_fee_b_key,
+                swap_info.oracle_a,
+                swap_info.oracle_b,
+                swap_info.pool_mint,
+                SwapData {
+                    amount_in: order.amount_in,
+                    minimum_amount_out: order.minimum_amount_out,
+                    swap_direction: order.swap_direction,
+                    max_price_impact_bps: 0,
+                },
+            )
+            .unwrap(),
+            order.clone(),
+            context.payer.pubkey(),
+        )
+        .unwrap(),
+    ]
+}
+
+async fn send(
+    context: &mut TestContext,
+    instructions: &[Instruction],
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    // a new blockhash, so that resent instructions are not a duplicate
+    let recent_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+async fn next_nonce(context: &mut TestContext) -> u64 {
+    let (order_nonce, _) = find_order_nonce_address(&deltafi_swap::id(), &context.maker.pubkey());
+    let account = context
+        .banks_client
+        .get_account(order_nonce)
+        .await
+        .unwrap()
+        .unwrap();
+    OrderNonce::load(&account.data).unwrap().next_nonce()
+}
+
+async fn token_amount(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
+    let account = banks_client.get_account(pubkey).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+
+    // settled by the payer alone
+    let order = sell_base_order(&context, i64::MAX, 1);
+    let maker = Keypair::from_bytes(&context.maker.to_bytes()).unwrap();
+    let instructions = settle_instructions(&context, &order, &maker);
+    send(&mut context, &instructions).await.unwrap();
+
+    assert_eq!(
+        token_amount(&mut context.banks_client, context.sol_user_account).await,
+        AMOUNT_IN
+    );
+    assert!(token_amount(&mut context.banks_client, context.srm_user_account).await > 0);
+    assert_eq!(next_nonce(&mut context).await, 2);
+
+    // settled once at most
+    assert_eq!(
+        send(&mut context, &instructions).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidOrderNonce as u32)
+        )
+    );
+
+    // a higher nonce is another order
+    let order = sell_base_order(&context, i64::MAX, 4);
+    let instructions = settle_instructions(&context, &order, &maker);
+    send(&mut context, &instructions).await.unwrap();
+    assert_eq!(
+        token_amount(&mut context.banks_client, context.sol_user_account).await,
+        0
+    );
+    assert_eq!(next_nonce(&mut context).await, 5);
+}
+
+#[tokio::test]
+async fn test_cancelled_orders() {
+    let mut context = setup().await;
+
+    let maker = Keypair::from_bytes(&context.maker.to_bytes()).unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[cancel_signed_orders(
+            deltafi_swap::id(),
+            maker.pubkey(),
+            context.payer.pubkey(),
+            5,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &maker], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(next_nonce(&mut context).await, 5);
+
+    let order = sell_base_order(&context, i64::MAX, 3);
+    let instructions = settle_instructions(&context, &order, &maker);
+    assert_eq!(
+        send(&mut context, &instructions).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidOrderNonce as u32)
+        )
+    );
+    let order = sell_base_order(&context, i64::MAX, 5);
+    let instructions = settle_instructions(&context, &order, &maker);
+    send(&mut context, &instructions).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_expired_order() {
+    let mut context = setup().await;
+
+    let order = sell_base_order(&context, 0, 1);
+    let maker = Keypair::from_bytes(&context.maker.to_bytes()).unwrap();
+    let instructions = settle_instructions(&context, &order, &maker);
+    assert_eq!(
+        send(&mut context, &instructions).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::OrderExpired as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_signature() {
+    let mut context = setup().await;
+
+    // signed by someone else than the maker
+    let order = sell_base_order(&context, i64::MAX, 1);
+    let instructions = settle_instructions(&context, &order, &Keypair::new());
+    assert_eq!(
+        send(&mut context, &instructions).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidOrderSignature as u32)
+        )
+    );
+
+    // without the ed25519 instruction
+    let maker = Keypair::from_bytes(&context.maker.to_bytes()).unwrap();
+    let instructions = settle_instructions(&context, &order, &maker);
+    assert_eq!(
+        send(&mut context, &instructions[1..]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOrderSignature as u32)
+        )
+    );
+
+    // the signed order with another amount
+    let mut instructions = settle_instructions(&context, &order, &maker);
+    instructions[1].data =
+        deltafi_swap::instruction::SwapInstruction::SettleSignedOrder(SignedOrder {
+            amount_in: 2 * AMOUNT_IN,
+            ..order
+        })
+        .pack();
+    assert_eq!(
+        send(&mut context, &instructions).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidOrderSignature as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_order_of_another_deployment() {
+    let mut context = setup().await;
+    let maker = Keypair::from_bytes(&context.maker.to_bytes()).unwrap();
+    let order = sell_base_order(&context, i64::MAX, 0);
+
+    // signed for another program
+    let other_program = SignedOrder {
+        program_id: Pubkey::new_unique(),
+        ..order.clone()
+    };
+    let instructions = settle_instructions(&context, &other_program, &maker);
+    assert_eq!(
+        send(&mut context, &instructions).await.unwrap_err(),
+        TransactionError::InstructionError(1, InstructionError::IncorrectProgramId)
+    );
+
+    // signed for another config
+    let other_config = SignedOrder {
+        config: Pubkey::new_unique(),
+        ..order.clone()
+    };
+    let instructions = settle_instructions(&context, &other_config, &maker);
+    assert_eq!(
+        send(&mut context, &instructions).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::IncorrectConfigAccount as u32)
+        )
+    );
+
+    // the order signed for this config still settles
+    let instructions = settle_instructions(&context, &order, &maker);
+    send(&mut context, &instructions).await.unwrap();
+    assert_eq!(next_nonce(&mut context).await, 1);
+}
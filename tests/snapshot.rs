@@ -0,0 +1,249 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::snapshot,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_snapshot_address, snapshot_balance_leaf, LiquiditySnapshot, SwapInfo},
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    sysvar::clock::Clock,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::Mint;
+use utils::*;
+
+const SNAPSHOT_ID: u64 = 1;
+
+struct TestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let context = test.start_with_context().await;
+
+    TestContext {
+        context,
+        swap_config,
+        swap_info,
+        user_account_owner,
+    }
+}
+
+async fn get_packed<T: Pack>(context: &mut ProgramTestContext, pubkey: Pubkey) -> T {
+    let account = context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    T::unpack(&account.data).unwrap()
+}
+
+async fn process_instruction(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, signer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+fn snapshot_instruction(
+    test_context: &TestContext,
+    admin: Pubkey,
+    pool_mint: Pubkey,
+    merkle_root: [u8; 32],
+) -> Instruction {
+    snapshot(
+        deltafi_swap::id(),
+        test_context.swap_config.pubkey,
+        test_context.swap_info.pubkey,
+        admin,
+        pool_mint,
+        test_context.context.payer.pubkey(),
+        SNAPSHOT_ID,
+        merkle_root,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_snapshot() {
+    let mut test_context = setup().await;
+    let pool_mint = test_context.swap_info.pool_mint;
+    let pool_token_balance = get_token_balance(
+        &mut test_context.context.banks_client,
+        test_context.swap_info.pool_token,
+    )
+    .await;
+    // a single provider is the root of the tree
+    let merkle_root = snapshot_balance_leaf(
+        &test_context.user_account_owner.pubkey(),
+        pool_token_balance,
+    );
+
+    let instruction = snapshot_instruction(
+        &test_context,
+        test_context.swap_config.admin.pubkey(),
+        pool_mint,
+        merkle_root,
+    );
+    process_instruction(
+        &mut test_context.context,
+        instruction,
+        &test_context.swap_config.admin,
+    )
+    .await
+    .unwrap();
+
+    let clock: Clock = test_context
+        .context
+        .banks_client
+        .get_sysvar()
+        .await
+        .unwrap();
+    let mint = get_packed::<Mint>(&mut test_context.context, pool_mint).await;
+    let swap_state =
+        get_packed::<SwapInfo>(&mut test_context.context, test_context.swap_info.pubkey).await;
+    let (snapshot_pubkey, _) = find_snapshot_address(
+        &deltafi_swap::id(),
+        &test_context.swap_info.pubkey,
+        SNAPSHOT_ID,
+    );
+    let snapshot_state =
+        get_packed::<LiquiditySnapshot>(&mut test_context.context, snapshot_pubkey).await;
+    assert_eq!(snapshot_state.swap, test_context.swap_info.pubkey);
+    assert_eq!(snapshot_state.snapshot_id, SNAPSHOT_ID);
+    assert_eq!(snapshot_state.snapshot_ts, clock.unix_timestamp);
+    assert_eq!(snapshot_state.pool_mint_supply, mint.supply);
+    assert_eq!(
+        snapshot_state.base_reserve,
+        swap_state.pool_state.base_reserve
+    );
+    assert_eq!(
+        snapshot_state.quote_reserve,
+        swap_state.pool_state.quote_reserve
+    );
+    assert!(snapshot_state.verify_balance(
+        &test_context.user_account_owner.pubkey(),
+        pool_token_balance,
+        &[]
+    ));
+
+    // snapshot ids are not reused
+    let instruction = snapshot_instruction(
+        &test_context,
+        test_context.swap_config.admin.pubkey(),
+        pool_mint,
+        [0u8; 32],
+    );
+    assert_eq!(
+        process_instruction(
+            &mut test_context.context,
+            instruction,
+            &test_context.swap_config.admin,
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::AlreadyInUse as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_snapshot_incorrect_pool_mint() {
+    let mut test_context = setup().await;
+
+    let instruction = snapshot_instruction(
+        &test_context,
+        test_context.swap_config.admin.pubkey(),
+        test_context.swap_config.deltafi_mint,
+        [0u8; 32],
+    );
+    assert_eq!(
+        process_instruction(
+            &mut test_context.context,
+            instruction,
+            &test_context.swap_config.admin,
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectMint as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_snapshot_not_admin() {
+    let mut test_context = setup().await;
+    let not_admin = Keypair::new();
+
+    let instruction = snapshot_instruction(
+        &test_context,
+        not_admin.pubkey(),
+        test_context.swap_info.pool_mint,
+        [0u8; 32],
+    );
+    assert_eq!(
+        process_instruction(&mut test_context.context, instruction, &not_admin)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
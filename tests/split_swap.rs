@@ -0,0 +1,306 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    curve::{CurveType, DynamicSlope},
+    error::SwapError,
+    instruction::{set_curve_type, split_swap, swap, SplitSwapData, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+const AMOUNT_IN: u64 = 2_000_000_000;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_infos: Vec<TestSwapInfo>,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_infos = (0..2)
+        .map(|_| {
+            add_swap_info(
+                &mut test,
+                &swap_config,
+                &user_account_owner,
+                &admin_account_owner,
+                AddSwapInfoArgs {
+                    token_a_mint: spl_token::native_mint::id(),
+                    token_b_mint: srm_mint.pubkey,
+                    token_a_amount: 42_000_000_000,
+                    token_b_amount: 800_000_000_000,
+                    is_open_twap: true,
+                    oracle_a: sol_oracle.price_pubkey,
+                    oracle_b: srm_oracle.price_pubkey,
+                    market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+                    slope: Decimal::one().try_div(2).unwrap(),
+                },
+            )
+        })
+        .collect();
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_infos,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+// sells SOL for SRM on the token-swap, the amount is set by the split
+fn swap_instruction(
+    context: &TestContext,
+    swap_info: &TestSwapInfo,
+    user_transfer_authority: Pubkey,
+) -> Instruction {
+    swap(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        swap_info.pubkey,
+        context.swap_config.market_authority,
+        swap_info.authority,
+        user_transfer_authority,
+        context.sol_user_account,
+        swap_info.token_a,
+        swap_info.token_b,
+        context.srm_user_account,
+        context.deltafi_user_account,
+        context.swap_config.deltafi_mint,
+        swap_info.admin_fee_b_key,
+        swap_info.oracle_a,
+        swap_info.oracle_b,
+        swap_info.pool_mint,
+        SwapData {
+            amount_in: 0,
+            minimum_amount_out: 0,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        },
+    )
+    .unwrap()
+}
+
+async fn try_split_swap(
+    context: &mut TestContext,
+    fractions_bps: Vec<u16>,
+    minimum_amount_out: u64,
+) -> Result<(), TransactionError> {
+    let user_transfer_authority = Keypair::new();
+    let swaps = context
+        .swap_infos
+        .iter()
+        .map(|swap_info| swap_instruction(context, swap_info, user_transfer_authority.pubkey()))
+        .collect();
+    let instruction = split_swap(
+        swaps,
+        SplitSwapData {
+            amount_in: AMOUNT_IN,
+            minimum_amount_out,
+            swap_direction: SwapDirection::SellBase,
+            fractions_bps,
+        },
+    )
+    .unwrap();
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &context.sol_user_account,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                AMOUNT_IN,
+            )
+            .unwrap(),
+            instruction,
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.user_account_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn set_dynamic_slope(context: &mut TestContext, swap_index: usize) {
+    let curve_type =
+        CurveType::DynamicSlope(DynamicSlope::new(Decimal::from_percent(90), 1).unwrap());
+    let mut transaction = Transaction::new_with_payer(
+        &[set_curve_type(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_infos[swap_index].pubkey,
+            context.swap_config.admin.pubkey(),
+            curve_type,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.swap_config.admin],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+// splits SOL evenly between the token-swaps, returning the SRM received
+async fn split_swap_sol(context: &mut TestContext) -> u64 {
+    let srm_balance = get_token_balance(&mut context.banks_client, context.srm_user_account).await;
+    try_split_swap(context, vec![5_000, 5_000], 1)
+        .await
+        .unwrap();
+    get_token_balance(&mut context.banks_client, context.srm_user_account).await - srm_balance
+}
+
+#[tokio::test]
+async fn test_split_swap() {
+    let mut context = setup().await;
+    let sol_balance = get_token_balance(&mut context.banks_client, context.sol_user_account).await;
+
+    try_split_swap(&mut context, vec![7_000, 3_000], 1)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.sol_user_account).await,
+        sol_balance - AMOUNT_IN
+    );
+    assert!(get_token_balance(&mut context.banks_client, context.srm_user_account).await > 0);
+    for (swap_info, amount_in) in context.swap_infos.iter().zip([1_400_000_000, 600_000_000]) {
+        assert_eq!(
+            get_token_balance(&mut context.banks_client, swap_info.token_a).await,
+            42_000_000_000 + amount_in
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_split_swap_exceeded_slippage() {
+    let mut context = setup().await;
+
+    assert_eq!(
+        try_split_swap(&mut context, vec![5_000, 5_000], u64::MAX)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::ExceededSlippage as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_split_swap_invalid_fractions() {
+    let mut context = setup().await;
+
+    for fractions_bps in [vec![5_000, 4_000], vec![10_000, 0]] {
+        assert_eq!(
+            try_split_swap(&mut context, fractions_bps, 0)
+                .await
+                .unwrap_err(),
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(SwapError::InvalidInput as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_split_swap_across_curves() {
+    let mut standard = setup().await;
+    let mut mixed = setup().await;
+    set_dynamic_slope(&mut mixed, 1).await;
+    let standard_swap = mixed.swap_infos[0].get_state(&mut mixed.banks_client).await;
+    assert_eq!(standard_swap.curve_type, CurveType::Standard);
+    let dynamic_swap = mixed.swap_infos[1].get_state(&mut mixed.banks_client).await;
+    assert!(matches!(
+        dynamic_swap.curve_type,
+        CurveType::DynamicSlope(_)
+    ));
+
+    // both curves quote the pools at the peg alike
+    assert_eq!(
+        split_swap_sol(&mut standard).await,
+        split_swap_sol(&mut mixed).await
+    );
+
+    // once SRM is short, the leg on the dynamic slope pays less
+    assert!(split_swap_sol(&mut mixed).await < split_swap_sol(&mut standard).await);
+}
@@ -0,0 +1,217 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{skim, sync},
+    math::{Decimal, TryAdd, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const DONATION: u64 = 1_000_000;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_info: TestSwapInfo,
+    srm_mint: TestMint,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_info,
+        srm_mint,
+    }
+}
+
+async fn process_instruction(
+    context: &mut TestContext,
+    instruction: Instruction,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+// mints SRM straight to the swap account, out of any deposit
+async fn donate(context: &mut TestContext) {
+    mint_to(
+        &mut context.banks_client,
+        context.srm_mint.pubkey,
+        &context.payer,
+        context.swap_info.token_b,
+        &context.srm_mint.authority,
+        DONATION,
+    )
+    .await;
+}
+
+fn skim_instruction(context: &TestContext) -> Instruction {
+    skim(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.swap_info.authority,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        context.swap_info.admin_fee_a_key,
+        context.swap_info.admin_fee_b_key,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_sync() {
+    let mut context = setup().await;
+    let pool_state = context
+        .swap_info
+        .get_state(&mut context.banks_client)
+        .await
+        .pool_state;
+    donate(&mut context).await;
+
+    let instruction = sync(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        context.swap_info.pool_mint,
+    )
+    .unwrap();
+    process_instruction(&mut context, instruction)
+        .await
+        .unwrap();
+
+    let synced = context
+        .swap_info
+        .get_state(&mut context.banks_client)
+        .await
+        .pool_state;
+    assert_eq!(synced.base_reserve, pool_state.base_reserve);
+    assert_eq!(
+        synced.quote_reserve,
+        pool_state
+            .quote_reserve
+            .try_add(Decimal::from(DONATION))
+            .unwrap()
+    );
+    assert_eq!(
+        synced.quote_target,
+        pool_state
+            .quote_target
+            .try_add(Decimal::from(DONATION))
+            .unwrap()
+    );
+
+    // the surplus is gone, nothing is left to skim
+    let admin_fee_b_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await;
+    let instruction = skim_instruction(&context);
+    process_instruction(&mut context, instruction)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await,
+        admin_fee_b_balance
+    );
+}
+
+#[tokio::test]
+async fn test_skim() {
+    let mut context = setup().await;
+    let pool_state = context
+        .swap_info
+        .get_state(&mut context.banks_client)
+        .await
+        .pool_state;
+    let token_b_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.token_b).await;
+    let admin_fee_b_balance =
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await;
+    donate(&mut context).await;
+
+    let instruction = skim_instruction(&context);
+    process_instruction(&mut context, instruction)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.swap_info.token_b).await,
+        token_b_balance
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.swap_info.admin_fee_b_key).await,
+        admin_fee_b_balance + DONATION
+    );
+    assert_eq!(
+        context
+            .swap_info
+            .get_state(&mut context.banks_client)
+            .await
+            .pool_state,
+        pool_state
+    );
+}
+
+#[tokio::test]
+async fn test_skim_invalid_admin_fee() {
+    let mut context = setup().await;
+    donate(&mut context).await;
+
+    let mut instruction = skim_instruction(&context);
+    instruction.accounts[5].pubkey = context.swap_info.token_b;
+    assert_eq!(
+        process_instruction(&mut context, instruction)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidAdmin as u32)
+        )
+    );
+}
@@ -0,0 +1,450 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        append_position_pages, append_transfer_hook, deposit, set_transfer_hook, swap,
+        CheckDepositData, CheckSwapData, DepositData, SwapData, SwapDirection,
+        TransferHookInstruction,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::InstructionError,
+    program_error::ProgramError,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+/// Error returned by the test hook to veto an operation
+const VETO: u32 = 42;
+/// Largest amount of token a approved by the test hook
+const MAX_APPROVED_AMOUNT: u64 = 1_000_000_000;
+
+fn process_transfer_hook(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    if !accounts.get(1).map_or(false, |info| info.is_signer) {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let amount = match TransferHookInstruction::unpack(input)? {
+        TransferHookInstruction::CheckSwap(CheckSwapData {
+            amount_in,
+            amount_out,
+            swap_direction,
+            ..
+        }) => match swap_direction {
+            SwapDirection::SellBase => amount_in,
+            SwapDirection::SellQuote => amount_out,
+        },
+        TransferHookInstruction::CheckDeposit(CheckDepositData { token_a_amount, .. }) => {
+            token_a_amount
+        }
+    };
+    if amount > MAX_APPROVED_AMOUNT {
+        return Err(ProgramError::Custom(VETO));
+    }
+    Ok(())
+}
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    transfer_hook_program: Pubkey,
+    srm_mint: TestMint,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+    liquidity_owner: Keypair,
+    liquidity_provider: TestLiquidityProvider,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let transfer_hook_program = Pubkey::new_unique();
+    test.add_program(
+        "transfer_hook",
+        transfer_hook_program,
+        processor!(process_transfer_hook),
+    );
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        transfer_hook_program,
+        srm_mint,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+        liquidity_owner,
+        liquidity_provider,
+    }
+}
+
+async fn set_hook(context: &mut TestContext, transfer_hook_program: Pubkey) {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_transfer_hook(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_info.pubkey,
+            context.swap_config.admin.pubkey(),
+            transfer_hook_program,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.swap_config.admin],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+async fn try_swap(
+    context: &mut TestContext,
+    amount_in: u64,
+    transfer_hook_program: Pubkey,
+) -> Result<(), TransactionError> {
+    let user_transfer_authority = Keypair::new();
+    let instruction = swap(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.market_authority,
+        context.swap_info.authority,
+        user_transfer_authority.pubkey(),
+        context.sol_user_account,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        context.srm_user_account,
+        context.deltafi_user_account,
+        context.swap_config.deltafi_mint,
+        context.swap_info.admin_fee_b_key,
+        context.swap_info.oracle_a,
+        context.swap_info.oracle_b,
+        context.swap_info.pool_mint,
+        SwapData {
+            amount_in,
+            minimum_amount_out: 0,
+            swap_direction: SwapDirection::SellBase,
+            max_price_impact_bps: 0,
+        },
+    )
+    .unwrap();
+    let instruction = append_transfer_hook(instruction, transfer_hook_program);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &context.sol_user_account,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                amount_in,
+            )
+            .unwrap(),
+            instruction,
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.user_account_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+
+    let transfer_hook_program = context.transfer_hook_program;
+    set_hook(&mut context, transfer_hook_program).await;
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(swap.transfer_hook_program, transfer_hook_program);
+
+    try_swap(&mut context, MAX_APPROVED_AMOUNT, transfer_hook_program)
+        .await
+        .unwrap();
+    assert!(get_token_balance(&mut context.banks_client, context.srm_user_account).await > 0);
+
+    // removing the hook lifts the checks
+    set_hook(&mut context, Pubkey::default()).await;
+    try_swap(&mut context, 2 * MAX_APPROVED_AMOUNT, Pubkey::default())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_swap_vetoed() {
+    let mut context = setup().await;
+
+    let transfer_hook_program = context.transfer_hook_program;
+    set_hook(&mut context, transfer_hook_program).await;
+
+    assert_eq!(
+        try_swap(&mut context, 2 * MAX_APPROVED_AMOUNT, transfer_hook_program)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(1, InstructionError::Custom(VETO))
+    );
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, context.srm_user_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_missing_hook() {
+    let mut context = setup().await;
+
+    let transfer_hook_program = context.transfer_hook_program;
+    set_hook(&mut context, transfer_hook_program).await;
+
+    for hook in [Pubkey::default(), Pubkey::new_unique()] {
+        assert_eq!(
+            try_swap(&mut context, MAX_APPROVED_AMOUNT, hook)
+                .await
+                .unwrap_err(),
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(SwapError::IncorrectTransferHookProgram as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_deposit_vetoed() {
+    let mut context = setup().await;
+
+    let transfer_hook_program = context.transfer_hook_program;
+    set_hook(&mut context, transfer_hook_program).await;
+
+    let sol_deposit_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        context.liquidity_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_deposit_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        context.srm_mint.pubkey,
+        Some(&context.srm_mint.authority),
+        &context.payer,
+        context.liquidity_owner.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        context.swap_info.pool_mint,
+        None,
+        &context.payer,
+        context.liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let token_a_amount = 8_000_000_000;
+    let token_b_amount = 160_000_000_000;
+    let user_transfer_authority = Keypair::new();
+    let instruction = deposit(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.swap_info.authority,
+        user_transfer_authority.pubkey(),
+        sol_deposit_account,
+        srm_deposit_account,
+        context.swap_info.token_a,
+        context.swap_info.token_b,
+        context.swap_info.pool_mint,
+        pool_token_account,
+        context.liquidity_provider.pubkey,
+        context.liquidity_provider.owner,
+        context.swap_info.oracle_a,
+        context.swap_info.oracle_b,
+        DepositData {
+            token_a_amount,
+            token_b_amount,
+            min_mint_amount: 0,
+        },
+    )
+    .unwrap();
+    let instruction = append_position_pages(
+        append_transfer_hook(instruction, transfer_hook_program),
+        context.liquidity_provider.position_pages.clone(),
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &sol_deposit_account,
+                &user_transfer_authority.pubkey(),
+                &context.liquidity_owner.pubkey(),
+                &[],
+                token_a_amount,
+            )
+            .unwrap(),
+            approve(
+                &spl_token::id(),
+                &srm_deposit_account,
+                &user_transfer_authority.pubkey(),
+                &context.liquidity_owner.pubkey(),
+                &[],
+                token_b_amount,
+            )
+            .unwrap(),
+            instruction,
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.liquidity_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(2, InstructionError::Custom(VETO))
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_admin() {
+    let mut context = setup().await;
+
+    let fake_admin = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[set_transfer_hook(
+            deltafi_swap::id(),
+            context.swap_config.pubkey,
+            context.swap_info.pubkey,
+            fake_admin.pubkey(),
+            context.transfer_hook_program,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &fake_admin], recent_blockhash);
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,216 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::transfer_position,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{find_liquidity_provider_address, LiquidityPosition, LiquidityProvider},
+};
+use solana_program::{
+    instruction::{AccountMeta, InstructionError},
+    program_pack::Pack,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_info: TestSwapInfo,
+    source_owner: Keypair,
+    destination_owner: Keypair,
+}
+
+fn add_liquidity_provider_with_positions(
+    test: &mut ProgramTest,
+    owner: &Keypair,
+    positions: Vec<LiquidityPosition>,
+) {
+    let (liquidity_provider, _) =
+        find_liquidity_provider_address(&deltafi_swap::id(), &owner.pubkey());
+    test.add_packable_account(
+        liquidity_provider,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: owner.pubkey(),
+            delegate: Pubkey::default(),
+            referrer: Pubkey::default(),
+            positions,
+        },
+        &deltafi_swap::id(),
+    );
+}
+
+async fn setup(destination_liquidity_amount: Option<u64>) -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let source_owner = Keypair::new();
+    let destination_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &source_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    add_liquidity_provider_with_positions(
+        &mut test,
+        &source_owner,
+        vec![LiquidityPosition {
+            pool: swap_info.pubkey,
+            liquidity_amount: 2_000_000_000,
+            rewards_owed: 1_000,
+            ..LiquidityPosition::default()
+        }],
+    );
+    add_liquidity_provider_with_positions(
+        &mut test,
+        &destination_owner,
+        destination_liquidity_amount
+            .map(|liquidity_amount| {
+                vec![LiquidityPosition {
+                    pool: swap_info.pubkey,
+                    liquidity_amount,
+                    rewards_owed: 500,
+                    ..LiquidityPosition::default()
+                }]
+            })
+            .unwrap_or_default(),
+    );
+
+    let (banks_client, payer, _recent_blockhash) = test.start().await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_info,
+        source_owner,
+        destination_owner,
+    }
+}
+
+async fn get_liquidity_provider(
+    banks_client: &mut BanksClient,
+    owner: Pubkey,
+) -> LiquidityProvider {
+    let (liquidity_provider, _) = find_liquidity_provider_address(&deltafi_swap::id(), &owner);
+    let account = banks_client
+        .get_account(liquidity_provider)
+        .await
+        .unwrap()
+        .unwrap();
+    LiquidityProvider::unpack(&account.data).unwrap()
+}
+
+async fn process_transfer_position(
+    context: &mut TestContext,
+    destination_is_signer: bool,
+) -> Result<(), TransactionError> {
+    let mut instruction = transfer_position(
+        deltafi_swap::id(),
+        context.swap_info.pubkey,
+        context.source_owner.pubkey(),
+        context.destination_owner.pubkey(),
+        vec![],
+        vec![],
+    )
+    .unwrap();
+    let mut signers = vec![&context.payer, &context.source_owner];
+    if destination_is_signer {
+        signers.push(&context.destination_owner);
+    } else {
+        instruction.accounts[4] =
+            AccountMeta::new_readonly(context.destination_owner.pubkey(), false);
+    }
+
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&signers, recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_transfer_position() {
+    let mut context = setup(None).await;
+
+    process_transfer_position(&mut context, true).await.unwrap();
+
+    let source =
+        get_liquidity_provider(&mut context.banks_client, context.source_owner.pubkey()).await;
+    assert!(source.positions.is_empty());
+    let destination = get_liquidity_provider(
+        &mut context.banks_client,
+        context.destination_owner.pubkey(),
+    )
+    .await;
+    assert_eq!(destination.positions.len(), 1);
+    assert_eq!(destination.positions[0].pool, context.swap_info.pubkey);
+    assert_eq!(destination.positions[0].liquidity_amount, 2_000_000_000);
+    assert_eq!(destination.positions[0].rewards_owed, 1_000);
+}
+
+#[tokio::test]
+async fn test_transfer_position_merge() {
+    let mut context = setup(Some(1_000_000_000)).await;
+
+    process_transfer_position(&mut context, true).await.unwrap();
+
+    let source =
+        get_liquidity_provider(&mut context.banks_client, context.source_owner.pubkey()).await;
+    assert!(source.positions.is_empty());
+    let destination = get_liquidity_provider(
+        &mut context.banks_client,
+        context.destination_owner.pubkey(),
+    )
+    .await;
+    assert_eq!(destination.positions.len(), 1);
+    assert_eq!(destination.positions[0].liquidity_amount, 3_000_000_000);
+    assert_eq!(destination.positions[0].rewards_owed, 1_500);
+}
+
+#[tokio::test]
+async fn test_transfer_position_destination_not_signer() {
+    let mut context = setup(None).await;
+
+    assert_eq!(
+        process_transfer_position(&mut context, false)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidSigner as u32)
+        )
+    );
+}
@@ -0,0 +1,296 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        initialize_whitelist, set_whitelist_only, set_whitelist_trader, swap, SwapData,
+        SwapDirection,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+struct TestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> TestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    TestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn process_admin_instruction(
+    context: &mut TestContext,
+    instruction: Instruction,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&context.payer, &context.swap_config.admin],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+async fn set_trader(context: &mut TestContext, trader: Pubkey, is_allowed: bool) {
+    let instruction = set_whitelist_trader(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.admin.pubkey(),
+        trader,
+        is_allowed,
+    )
+    .unwrap();
+    process_admin_instruction(context, instruction)
+        .await
+        .unwrap();
+}
+
+async fn init_whitelist_only(context: &mut TestContext) {
+    let instruction = initialize_whitelist(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.admin.pubkey(),
+        context.payer.pubkey(),
+    )
+    .unwrap();
+    process_admin_instruction(context, instruction)
+        .await
+        .unwrap();
+
+    let instruction = set_whitelist_only(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.admin.pubkey(),
+        true,
+    )
+    .unwrap();
+    process_admin_instruction(context, instruction)
+        .await
+        .unwrap();
+}
+
+async fn try_swap(context: &mut TestContext, amount_in: u64) -> Result<(), TransactionError> {
+    let user_transfer_authority = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &context.sol_user_account,
+                &user_transfer_authority.pubkey(),
+                &context.user_account_owner.pubkey(),
+                &[],
+                amount_in,
+            )
+            .unwrap(),
+            swap(
+                deltafi_swap::id(),
+                context.swap_config.pubkey,
+                context.swap_info.pubkey,
+                context.swap_config.market_authority,
+                context.swap_info.authority,
+                user_transfer_authority.pubkey(),
+                context.sol_user_account,
+                context.swap_info.token_a,
+                context.swap_info.token_b,
+                context.srm_user_account,
+                context.deltafi_user_account,
+                context.swap_config.deltafi_mint,
+                context.swap_info.admin_fee_b_key,
+                context.swap_info.oracle_a,
+                context.swap_info.oracle_b,
+                context.swap_info.pool_mint,
+                SwapData {
+                    amount_in,
+                    minimum_amount_out: 0,
+                    swap_direction: SwapDirection::SellBase,
+                    max_price_impact_bps: 0,
+                },
+            )
+            .unwrap(),
+        ],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[
+            &context.payer,
+            &context.user_account_owner,
+            &user_transfer_authority,
+        ],
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut context = setup().await;
+
+    init_whitelist_only(&mut context).await;
+    let swap = context.swap_info.get_state(&mut context.banks_client).await;
+    assert!(swap.is_whitelist_only);
+
+    let trader = context.user_account_owner.pubkey();
+    set_trader(&mut context, trader, true).await;
+    try_swap(&mut context, 100_000_000).await.unwrap();
+    assert!(get_token_balance(&mut context.banks_client, context.srm_user_account).await > 0);
+}
+
+#[tokio::test]
+async fn test_trader_not_whitelisted() {
+    let mut context = setup().await;
+
+    init_whitelist_only(&mut context).await;
+    assert_eq!(
+        try_swap(&mut context, 100_000_000).await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::TraderNotWhitelisted as u32)
+        ))
+    );
+
+    // removed traders are rejected again
+    let trader = context.user_account_owner.pubkey();
+    set_trader(&mut context, trader, true).await;
+    set_trader(&mut context, trader, false).await;
+    assert_eq!(
+        try_swap(&mut context, 200_000_000).await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::TraderNotWhitelisted as u32)
+        ))
+    );
+
+    // lifting the restriction opens the pool to everyone
+    let instruction = set_whitelist_only(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.admin.pubkey(),
+        false,
+    )
+    .unwrap();
+    process_admin_instruction(&mut context, instruction)
+        .await
+        .unwrap();
+    try_swap(&mut context, 300_000_000).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_whitelist_only_without_whitelist() {
+    let mut context = setup().await;
+
+    let instruction = set_whitelist_only(
+        deltafi_swap::id(),
+        context.swap_config.pubkey,
+        context.swap_info.pubkey,
+        context.swap_config.admin.pubkey(),
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        process_admin_instruction(&mut context, instruction).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidAccountOwner as u32)
+        ))
+    );
+}